@@ -1,7 +1,10 @@
 //! ChainSolve CLI — headless graph execution (10.5).
 //!
-//! Reads a `.chainsolve` or `.json` snapshot file (EngineSnapshotV1 format),
-//! evaluates all blocks, and outputs results to stdout.
+//! Reads a `.chainsolve` or `.json` snapshot file (EngineSnapshotV1 format)
+//! or a `.csproject` project file, evaluates all blocks, and outputs results
+//! to stdout. Project files carry cached results, so only blocks affected by
+//! `--param` overrides (or all blocks, if the file came from another engine
+//! version) are recomputed.
 //!
 //! ## Usage
 //!
@@ -14,6 +17,7 @@
 //!   --output summary       Output human-readable summary
 //!   --node <id>            Filter output to a single node ID
 //!   --param <id>=<value>   Override a Number node's value before evaluation
+//!   --save-project <path>  Write the evaluated graph as a project file
 //!   --no-diagnostics       Suppress diagnostics in output
 //!   --timing               Print evaluation timing to stderr
 //!   --version              Print version and exit
@@ -29,6 +33,8 @@
 use std::collections::HashMap;
 use std::process;
 
+use engine_core::graph::EngineGraph;
+use engine_core::project;
 use engine_core::types::{EvalResult, Value};
 
 /// Print usage to stderr.
@@ -38,7 +44,7 @@ fn usage() {
 Headless graph evaluation for CI/CD and batch workflows.
 
 USAGE:
-    chainsolve [OPTIONS] <graph.chainsolve>
+    chainsolve [OPTIONS] <graph.chainsolve | project.csproject>
 
 OPTIONS:
     --output json          Output full EvalResult as JSON (default)
@@ -46,6 +52,7 @@ OPTIONS:
     --output summary       Human-readable summary of node values
     --node <id>            Restrict output to a single node by ID
     --param <id>=<value>   Override a number node's value (repeatable)
+    --save-project <path>  Save graph, datasets and results as a project file
     --no-diagnostics       Suppress diagnostic messages in output
     --timing               Print timing information to stderr
     --version              Print version and exit
@@ -53,10 +60,11 @@ OPTIONS:
 
 INPUT FORMAT:
     EngineSnapshotV1 JSON — {{\"version\":1,\"nodes\":[...],\"edges\":[...]}}
+    ChainSolve project file (.csproject) — snapshot, datasets and cached results
 
 EXIT CODES:
     0  Success (graph evaluated; individual node errors are reported, not fatal)
-    1  Fatal error (file not found, invalid JSON, snapshot version mismatch)
+    1  Fatal error (file not found, invalid JSON or project, snapshot version mismatch)
     2  Invalid arguments",
         ver = env!("CARGO_PKG_VERSION")
     );
@@ -110,6 +118,10 @@ fn value_to_display(v: &Value) -> String {
             }
         }
         Value::HighPrecision { display, .. } => display.clone(),
+        Value::Bytes { data, mime } => format!("Bytes({} bytes, {mime})", data.len()),
+        Value::SparseMatrix { rows, cols, values, .. } => {
+            format!("SparseMatrix({rows}×{cols}, {} nnz)", values.len())
+        }
     }
 }
 
@@ -125,6 +137,8 @@ fn value_type_name(v: &Value) -> &'static str {
         Value::Interval { .. } => "interval",
         Value::Complex { .. } => "complex",
         Value::HighPrecision { .. } => "high_precision",
+        Value::Bytes { .. } => "bytes",
+        Value::SparseMatrix { .. } => "sparse_matrix",
     }
 }
 
@@ -160,6 +174,34 @@ fn apply_param_overrides(
     serde_json::to_string(&v).unwrap_or_else(|_| snapshot_json.to_string())
 }
 
+/// Restore a project file into `graph`, apply `--param` overrides to number
+/// nodes, and evaluate only what is dirty. The returned values cover every
+/// node (cached + recomputed).
+fn evaluate_project(
+    graph: &mut EngineGraph,
+    bytes: &[u8],
+    overrides: &HashMap<String, f64>,
+) -> Result<EvalResult, engine_core::error::EngineError> {
+    let summary = project::open_project(graph, bytes)?;
+    if summary.stale {
+        eprintln!(
+            "note: project was saved by engine {} (contract v{}); recomputing all blocks",
+            summary.engine_version, summary.contract_version
+        );
+    }
+    for (id, &val) in overrides {
+        graph.set_source_value(id, val);
+    }
+    let inc = graph.evaluate_dirty();
+    Ok(EvalResult {
+        values: graph.values().clone(),
+        diagnostics: inc.diagnostics,
+        elapsed_us: inc.elapsed_us,
+        trace: inc.trace,
+        partial: inc.partial,
+    })
+}
+
 fn main() {
     let args: Vec<String> = std::env::args().collect();
 
@@ -170,6 +212,7 @@ fn main() {
     let mut param_overrides: HashMap<String, f64> = HashMap::new();
     let mut show_diagnostics = true;
     let mut show_timing = false;
+    let mut save_project_path: Option<String> = None;
 
     let mut i = 1;
     while i < args.len() {
@@ -221,6 +264,14 @@ fn main() {
                     }
                 }
             }
+            "--save-project" => {
+                i += 1;
+                if i >= args.len() {
+                    eprintln!("error: --save-project requires a path");
+                    process::exit(2);
+                }
+                save_project_path = Some(args[i].clone());
+            }
             "--no-diagnostics" => { show_diagnostics = false; }
             "--timing" => { show_timing = true; }
             arg if arg.starts_with("--") => {
@@ -247,19 +298,31 @@ fn main() {
     };
 
     // --- Read input file ---
-    let snapshot_json = match std::fs::read_to_string(&file_path) {
-        Ok(s) => s,
+    let bytes = match std::fs::read(&file_path) {
+        Ok(b) => b,
         Err(e) => {
             eprintln!("error: cannot read '{}': {}", file_path, e);
             process::exit(1);
         }
     };
 
-    // --- Apply parameter overrides ---
-    let snapshot_json = apply_param_overrides(&snapshot_json, &param_overrides);
-
-    // --- Evaluate ---
-    let result: EvalResult = match engine_core::run(&snapshot_json) {
+    // --- Load, apply parameter overrides, evaluate ---
+    let mut graph = EngineGraph::new();
+    let evaluated = if project::is_project(&bytes) {
+        evaluate_project(&mut graph, &bytes, &param_overrides)
+    } else {
+        match String::from_utf8(bytes) {
+            Ok(json) => {
+                let json = apply_param_overrides(&json, &param_overrides);
+                engine_core::run_load_snapshot(&mut graph, &json)
+            }
+            Err(_) => {
+                eprintln!("error: '{}' is neither a snapshot JSON nor a project file", file_path);
+                process::exit(1);
+            }
+        }
+    };
+    let result: EvalResult = match evaluated {
         Ok(r) => r,
         Err(e) => {
             eprintln!("error: evaluation failed: {}", e);
@@ -267,6 +330,16 @@ fn main() {
         }
     };
 
+    // --- Save project ---
+    if let Some(ref path) = save_project_path {
+        let mut metadata = std::collections::BTreeMap::new();
+        metadata.insert("createdBy".to_string(), format!("chainsolve-cli {}", env!("CARGO_PKG_VERSION")));
+        if let Err(e) = std::fs::write(path, project::save_project(&graph, metadata)) {
+            eprintln!("error: cannot write '{}': {}", path, e);
+            process::exit(1);
+        }
+    }
+
     // --- Timing ---
     if show_timing {
         eprintln!(
@@ -426,6 +499,31 @@ mod tests {
         assert!((new_val - 99.0).abs() < 1e-10, "Expected 99.0, got {new_val}");
     }
 
+    #[test]
+    fn project_param_override_recomputes_downstream() {
+        let snapshot = r#"{"version":1,"nodes":[
+            {"id":"a","blockType":"number","data":{"value":10}},
+            {"id":"b","blockType":"number","data":{"value":5}},
+            {"id":"c","blockType":"add","data":{}}
+        ],"edges":[
+            {"id":"e1","source":"a","sourceHandle":"out","target":"c","targetHandle":"in_0"},
+            {"id":"e2","source":"b","sourceHandle":"out","target":"c","targetHandle":"in_1"}
+        ]}"#;
+        let mut graph = EngineGraph::new();
+        engine_core::run_load_snapshot(&mut graph, snapshot).unwrap();
+        let bytes = project::save_project(&graph, Default::default());
+
+        let mut overrides = HashMap::new();
+        overrides.insert("a".to_string(), 1.0);
+        let mut reopened = EngineGraph::new();
+        let result = evaluate_project(&mut reopened, &bytes, &overrides).unwrap();
+        match result.values.get("c") {
+            Some(Value::Scalar { value }) => assert!((value - 6.0).abs() < 1e-10, "got {value}"),
+            other => panic!("Expected scalar at node 'c', got {other:?}"),
+        }
+        assert_eq!(result.values.len(), 3);
+    }
+
     #[test]
     fn cli_evaluates_simple_graph() {
         // Direct API test: evaluate a number → add graph
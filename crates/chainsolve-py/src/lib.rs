@@ -27,6 +27,11 @@
 //! # Execute from a JSON string directly
 //! result = chainsolve.execute_json('{"version":1,"nodes":[...],"edges":[...]}')
 //!
+//! # Open a project file (snapshot + datasets + cached results)
+//! project = chainsolve.Project.open("model.csproject")
+//! cached = project.values()                 # no evaluation
+//! result = project.execute(params={"n1": 2.0})  # recomputes only what changed
+//!
 //! # Extract typed values
 //! scalar = result.scalar("node_id")
 //! vector = result.vector("node_id")   # → list[float]
@@ -46,12 +51,15 @@
 //! | Complex                | `complex`                                               |
 //! | HighPrecision          | `str` (full decimal expansion)                          |
 //! | Table                  | `dict` with keys `columns: list[str]`, `rows: list[list[float]]` |
+//! | Bytes                  | `bytes` (e.g. an exported Parquet file)                 |
 
 use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::{PyComplex, PyDict, PyList};
+use pyo3::types::{PyBytes, PyComplex, PyDict, PyList};
 use std::collections::HashMap;
 
+use engine_core::graph::EngineGraph;
+use engine_core::project::{self, Project};
 use engine_core::types::{EvalResult, Value};
 
 // ── Python error type ─────────────────────────────────────────────────────────
@@ -96,6 +104,16 @@ fn value_to_py<'py>(py: Python<'py>, v: &Value) -> PyResult<Bound<'py, PyAny>> {
             d.set_item("rows", rows_list)?;
             Ok(d.into_any())
         }
+        Value::Bytes { data, .. } => Ok(PyBytes::new(py, data).into_any()),
+        Value::SparseMatrix { rows, cols, row_ptrs, col_indices, values } => {
+            let d = PyDict::new(py);
+            d.set_item("rows", *rows)?;
+            d.set_item("cols", *cols)?;
+            d.set_item("row_ptrs", PyList::new(py, row_ptrs.iter().copied())?)?;
+            d.set_item("col_indices", PyList::new(py, col_indices.iter().copied())?)?;
+            d.set_item("values", PyList::new(py, values.iter().copied())?)?;
+            Ok(d.into_any())
+        }
     }
 }
 
@@ -227,10 +245,17 @@ struct PyGraph {
 #[pymethods]
 impl PyGraph {
     /// Load a graph from a .chainsolve or .json snapshot file.
+    ///
+    /// A .csproject file is also accepted; only its snapshot is used.
     #[staticmethod]
     fn load(path: &str) -> PyResult<PyGraph> {
-        let json = std::fs::read_to_string(path)
+        let bytes = std::fs::read(path)
             .map_err(|e| PyRuntimeError::new_err(format!("Cannot read '{}': {}", path, e)))?;
+        if project::is_project(&bytes) {
+            return PyProject::from_bytes(&bytes)?.graph();
+        }
+        let json = String::from_utf8(bytes)
+            .map_err(|e| SnapshotError::new_err(format!("'{}' is not UTF-8 JSON: {}", path, e)))?;
         Ok(PyGraph { snapshot_json: json })
     }
 
@@ -274,6 +299,133 @@ impl PyGraph {
     }
 }
 
+// ── PyProject ─────────────────────────────────────────────────────────────────
+
+/// A ChainSolve project file: snapshot, datasets and cached results.
+///
+/// Obtain via ``Project.open(path)``. Cached values are available without
+/// evaluating; ``execute()`` recomputes only what is dirty (everything, if
+/// the file was saved by a different engine version — see ``stale``).
+#[pyclass(name = "Project")]
+struct PyProject {
+    project: Project,
+}
+
+impl PyProject {
+    fn from_bytes(bytes: &[u8]) -> PyResult<PyProject> {
+        Project::from_bytes(bytes)
+            .map(|project| PyProject { project })
+            .map_err(|e| SnapshotError::new_err(format!("Invalid project file: {}", e)))
+    }
+}
+
+#[pymethods]
+impl PyProject {
+    /// Open a .csproject file.
+    #[staticmethod]
+    fn open(path: &str) -> PyResult<PyProject> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| PyRuntimeError::new_err(format!("Cannot read '{}': {}", path, e)))?;
+        PyProject::from_bytes(&bytes)
+    }
+
+    /// Save this project to a file.
+    fn save(&self, path: &str) -> PyResult<()> {
+        std::fs::write(path, self.project.to_bytes())
+            .map_err(|e| PyRuntimeError::new_err(format!("Cannot write '{}': {}", path, e)))
+    }
+
+    /// Engine version that produced the cached values.
+    #[getter]
+    fn engine_version(&self) -> &str {
+        &self.project.engine_version
+    }
+
+    /// Engine contract version that produced the cached values.
+    #[getter]
+    fn contract_version(&self) -> u32 {
+        self.project.contract_version
+    }
+
+    /// True if the cached values came from a different engine and will be recomputed.
+    #[getter]
+    fn stale(&self) -> bool {
+        self.project.is_stale()
+    }
+
+    /// Project metadata as a dict[str, str].
+    fn metadata<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let d = PyDict::new(py);
+        for (k, v) in &self.project.metadata {
+            d.set_item(k, v)?;
+        }
+        Ok(d)
+    }
+
+    /// Cached values from the last evaluation, without evaluating.
+    fn values<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let d = PyDict::new(py);
+        for (k, v) in &self.project.values {
+            match value_to_py(py, v) {
+                Ok(pv) => d.set_item(k, pv)?,
+                Err(e) => d.set_item(k, format!("ERROR: {}", e))?,
+            }
+        }
+        Ok(d)
+    }
+
+    /// Registered datasets as a dict[str, list[float]].
+    fn datasets<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let d = PyDict::new(py);
+        for (k, v) in &self.project.datasets {
+            d.set_item(k, PyList::new(py, v.iter().copied())?)?;
+        }
+        Ok(d)
+    }
+
+    /// The project's graph, without its datasets or cached values.
+    fn graph(&self) -> PyResult<PyGraph> {
+        serde_json::to_string(&self.project.snapshot)
+            .map(|snapshot_json| PyGraph { snapshot_json })
+            .map_err(|e| PyRuntimeError::new_err(format!("Cannot serialize snapshot: {}", e)))
+    }
+
+    /// Evaluate the project, reusing cached values where possible.
+    ///
+    /// Parameters
+    /// ----------
+    /// params : dict[str, float], optional
+    ///     Override Number node values; only their downstream is recomputed.
+    #[pyo3(signature = (params=None))]
+    fn execute(&self, params: Option<HashMap<String, f64>>) -> PyResult<PyEvalResult> {
+        let mut graph = EngineGraph::new();
+        self.project.clone().restore(&mut graph);
+        for (id, val) in params.unwrap_or_default() {
+            graph.set_source_value(&id, val);
+        }
+        let inc = graph.evaluate_dirty();
+        Ok(PyEvalResult {
+            result: EvalResult {
+                values: graph.values().clone(),
+                diagnostics: inc.diagnostics,
+                elapsed_us: inc.elapsed_us,
+                trace: inc.trace,
+                partial: inc.partial,
+            },
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Project(nodes={}, datasets={}, cached={}, stale={})",
+            self.project.snapshot.nodes.len(),
+            self.project.datasets.len(),
+            self.project.values.len(),
+            if self.project.is_stale() { "True" } else { "False" }
+        )
+    }
+}
+
 // ── Module-level convenience functions ───────────────────────────────────────
 
 /// Execute a graph from a JSON snapshot string.
@@ -340,6 +492,7 @@ fn apply_overrides(snapshot_json: &str, overrides: &HashMap<String, f64>) -> Str
 fn chainsolve(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyGraph>()?;
     m.add_class::<PyEvalResult>()?;
+    m.add_class::<PyProject>()?;
     m.add_function(wrap_pyfunction!(execute_json, m)?)?;
     m.add_function(wrap_pyfunction!(version, m)?)?;
     m.add("EvalError", m.py().get_type::<EvalError>())?;
@@ -399,6 +552,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn project_graph_roundtrips_snapshot() {
+        let snapshot = r#"{"version":1,"nodes":[
+            {"id":"x","blockType":"number","data":{"value":2}}
+        ],"edges":[]}"#;
+        let mut graph = EngineGraph::new();
+        engine_core::run_load_snapshot(&mut graph, snapshot).unwrap();
+        graph.register_dataset("d".into(), vec![1.0, 2.0]);
+        let bytes = project::save_project(&graph, Default::default());
+
+        let py_project = PyProject::from_bytes(&bytes).unwrap();
+        assert!(!py_project.stale());
+        assert_eq!(py_project.project.datasets["d"], vec![1.0, 2.0]);
+        let result = engine_core::run(&py_project.graph().unwrap().snapshot_json).unwrap();
+        match result.values.get("x") {
+            Some(Value::Scalar { value }) => assert!((value - 2.0).abs() < 1e-10),
+            other => panic!("Expected 2.0, got {:?}", other),
+        }
+    }
+
     #[test]
     fn apply_overrides_then_evaluate() {
         let snapshot = r#"{"version":1,"nodes":[
@@ -0,0 +1,515 @@
+//! Block-lower-triangular (BLT) sorting and tearing of the algebraic part
+//! of a [`ReducedSystem`].
+//!
+//! With the states known, the reduced system is square in its remaining
+//! unknowns. Matching every equation to the unknown it determines and
+//! taking the strongly connected components of the resulting dependency
+//! graph (Tarjan) orders the equations into blocks that can be solved one
+//! after another. Scalar blocks are solved by Newton on one unknown, which
+//! converges in one step when the equation is linear in it. Algebraic
+//! loops are torn: a few tearing variables are guessed, the other loop
+//! equations are solved for their unknowns in sequence, and an outer Newton
+//! iteration drives the remaining residuals to zero.
+//!
+//! # References
+//! - Tarjan, R. (1972). "Depth-first search and linear graph algorithms"
+//! - Elmqvist, H. & Otter, M. (1994). "Methods for tearing systems of
+//!   equations in object-oriented modeling"
+
+use super::index::ReducedSystem;
+use super::network::{parse_symbol, symbol};
+use crate::ode::jacobian::Lu;
+use crate::symbolic::{differentiate, free_variables, simplify, BinOp, Expr, Func, SymExpr, UnaryOp};
+use std::collections::HashMap;
+
+/// Newton tolerance, relative to the magnitude of the unknown.
+const TOL: f64 = 1e-10;
+const MAX_NEWTON: usize = 50;
+
+/// An expression compiled against variable indices.
+#[derive(Debug, Clone)]
+enum Node {
+    Const(f64),
+    Time,
+    Var(usize),
+    Bin(BinOp, Box<Node>, Box<Node>),
+    Un(UnaryOp, Box<Node>),
+    Func(Func, Box<Node>),
+    Sum(Vec<Node>),
+    Product(Vec<Node>),
+}
+
+impl Node {
+    fn compile(expr: &Expr, index: &HashMap<String, usize>) -> Result<Self, String> {
+        Ok(match expr.as_ref() {
+            SymExpr::Constant(c) => Node::Const(*c),
+            SymExpr::Variable(name) if name == "t" => Node::Time,
+            SymExpr::Variable(name) => Node::Var(*index.get(name).ok_or_else(|| format!("unknown symbol '{name}'"))?),
+            SymExpr::BinaryOp { op, lhs, rhs } => {
+                Node::Bin(*op, Box::new(Self::compile(lhs, index)?), Box::new(Self::compile(rhs, index)?))
+            }
+            SymExpr::UnaryOp { op, operand } => Node::Un(*op, Box::new(Self::compile(operand, index)?)),
+            SymExpr::Function { func, arg } => Node::Func(*func, Box::new(Self::compile(arg, index)?)),
+            SymExpr::Sum(terms) => Node::Sum(terms.iter().map(|e| Self::compile(e, index)).collect::<Result<_, _>>()?),
+            SymExpr::Product(factors) => {
+                Node::Product(factors.iter().map(|e| Self::compile(e, index)).collect::<Result<_, _>>()?)
+            }
+        })
+    }
+
+    fn eval(&self, t: f64, x: &[f64]) -> f64 {
+        match self {
+            Node::Const(c) => *c,
+            Node::Time => t,
+            Node::Var(j) => x[*j],
+            Node::Bin(op, l, r) => {
+                let (l, r) = (l.eval(t, x), r.eval(t, x));
+                match op {
+                    BinOp::Add => l + r,
+                    BinOp::Sub => l - r,
+                    BinOp::Mul => l * r,
+                    BinOp::Div => l / r,
+                    BinOp::Pow => l.powf(r),
+                }
+            }
+            Node::Un(op, a) => {
+                let a = a.eval(t, x);
+                match op {
+                    UnaryOp::Neg => -a,
+                    UnaryOp::Abs => a.abs(),
+                    UnaryOp::Floor => a.floor(),
+                    UnaryOp::Ceil => a.ceil(),
+                    UnaryOp::Sign => a.signum(),
+                }
+            }
+            Node::Func(f, a) => {
+                let a = a.eval(t, x);
+                match f {
+                    Func::Sin => a.sin(),
+                    Func::Cos => a.cos(),
+                    Func::Tan => a.tan(),
+                    Func::Asin => a.asin(),
+                    Func::Acos => a.acos(),
+                    Func::Atan => a.atan(),
+                    Func::Exp => a.exp(),
+                    Func::Ln => a.ln(),
+                    Func::Log10 => a.log10(),
+                    Func::Sqrt => a.sqrt(),
+                    Func::Sinh => a.sinh(),
+                    Func::Cosh => a.cosh(),
+                    Func::Tanh => a.tanh(),
+                }
+            }
+            Node::Sum(terms) => terms.iter().map(|e| e.eval(t, x)).sum(),
+            Node::Product(factors) => factors.iter().map(|e| e.eval(t, x)).product(),
+        }
+    }
+}
+
+/// An equation solved for one unknown, with its symbolic slope.
+#[derive(Debug, Clone)]
+struct Assignment {
+    equation: usize,
+    var: usize,
+    residual: Node,
+    slope: Node,
+}
+
+#[derive(Debug, Clone)]
+enum Block {
+    Scalar(Assignment),
+    /// An algebraic loop: `inner` solved in order for given `tears`, with
+    /// `residuals` closing the loop.
+    Torn { inner: Vec<Assignment>, tears: Vec<usize>, residuals: Vec<(usize, Node)> },
+}
+
+/// The sorted and torn solution sequence of a [`ReducedSystem`].
+#[derive(Debug, Clone)]
+pub struct Blt {
+    blocks: Vec<Block>,
+    names: Vec<String>,
+    origins: Vec<String>,
+}
+
+impl Blt {
+    /// Sort the equations of `sys` for its `unknowns`, with the states known.
+    pub fn new(sys: &ReducedSystem) -> Result<Self, String> {
+        let index: HashMap<String, usize> =
+            sys.variables.iter().enumerate().map(|(i, v)| (symbol(v.base, v.order), i)).collect();
+        let names: Vec<String> = sys.variables.iter().map(|v| v.name.clone()).collect();
+        let origins: Vec<String> = sys.equations.iter().map(|e| e.origin.clone()).collect();
+        let m = sys.equations.len();
+        if sys.unknowns.len() != m {
+            return Err(format!("{m} equations for {} algebraic unknowns", sys.unknowns.len()));
+        }
+        let is_unknown: Vec<bool> = {
+            let mut v = vec![false; sys.variables.len()];
+            sys.unknowns.iter().for_each(|&u| v[u] = true);
+            v
+        };
+        // Unknowns each equation references.
+        let incidence: Vec<Vec<usize>> = sys
+            .equations
+            .iter()
+            .map(|e| {
+                free_variables(&e.residual)
+                    .iter()
+                    .filter(|name| parse_symbol(name).is_some())
+                    .map(|name| index.get(name).copied().ok_or_else(|| format!("unknown symbol '{name}'")))
+                    .filter(|v| v.as_ref().map_or(true, |&v| is_unknown[v]))
+                    .collect()
+            })
+            .collect::<Result<_, String>>()?;
+
+        let matched = match_equations(&incidence, sys.variables.len());
+        let unmatched_eqs: Vec<&str> = (0..m).filter(|&e| matched[e].is_none()).map(|e| origins[e].as_str()).collect();
+        if !unmatched_eqs.is_empty() {
+            let assigned: Vec<usize> = matched.iter().flatten().copied().collect();
+            let free: Vec<&str> =
+                sys.unknowns.iter().filter(|u| !assigned.contains(u)).map(|&u| names[u].as_str()).collect();
+            return Err(format!(
+                "singular algebraic system: nothing determines {} (redundant: {})",
+                free.join(", "),
+                unmatched_eqs.join("; ")
+            ));
+        }
+        let var_eq: HashMap<usize, usize> = matched.iter().enumerate().map(|(e, v)| (v.unwrap(), e)).collect();
+        let deps: Vec<Vec<usize>> =
+            incidence.iter().enumerate().map(|(e, vars)| vars.iter().map(|v| var_eq[v]).filter(|&d| d != e).collect()).collect();
+
+        let compile = |e: usize| Node::compile(&sys.equations[e].residual, &index);
+        let assignment = |e: usize, v: usize| -> Result<Assignment, String> {
+            let slope = simplify(&differentiate(&sys.equations[e].residual, &symbol(sys.variables[v].base, sys.variables[v].order)));
+            Ok(Assignment { equation: e, var: v, residual: compile(e)?, slope: Node::compile(&slope, &index)? })
+        };
+        let mut blocks = Vec::new();
+        for component in tarjan(&deps) {
+            if component.len() == 1 {
+                let e = component[0];
+                blocks.push(Block::Scalar(assignment(e, matched[e].unwrap())?));
+                continue;
+            }
+            let vars: Vec<usize> = component.iter().map(|&e| matched[e].unwrap()).collect();
+            let (inner, tears, residual_eqs) = tear(sys, &component, &vars, &incidence);
+            blocks.push(Block::Torn {
+                inner: inner.into_iter().map(|(e, v)| assignment(e, v)).collect::<Result<_, _>>()?,
+                tears,
+                residuals: residual_eqs.into_iter().map(|e| Ok((e, compile(e)?))).collect::<Result<_, String>>()?,
+            });
+        }
+        Ok(Blt { blocks, names, origins })
+    }
+
+    /// Number of algebraic loops.
+    pub fn loops(&self) -> usize {
+        self.blocks.iter().filter(|b| matches!(b, Block::Torn { .. })).count()
+    }
+
+    /// Tearing variables over all loops, by name.
+    pub fn tearing_variables(&self) -> Vec<&str> {
+        self.blocks
+            .iter()
+            .flat_map(|b| match b {
+                Block::Torn { tears, .. } => tears.iter().map(|&v| self.names[v].as_str()).collect(),
+                Block::Scalar(_) => Vec::new(),
+            })
+            .collect()
+    }
+
+    /// Solve every unknown in `values` (indexed like the reduced
+    /// variables), given the states; the unknowns' current values are the
+    /// Newton starting guesses.
+    pub fn solve(&self, t: f64, values: &mut [f64]) -> Result<(), String> {
+        for block in &self.blocks {
+            match block {
+                Block::Scalar(a) => self.solve_scalar(a, t, values)?,
+                Block::Torn { inner, tears, residuals } => self.solve_torn(inner, tears, residuals, t, values)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn solve_scalar(&self, a: &Assignment, t: f64, x: &mut [f64]) -> Result<(), String> {
+        let fail = |why: &str| format!("{}: cannot solve for {} at t = {t}: {why}", self.origins[a.equation], self.names[a.var]);
+        for _ in 0..MAX_NEWTON {
+            let r = a.residual.eval(t, x);
+            if !r.is_finite() {
+                return Err(fail("residual is not finite"));
+            }
+            let slope = a.slope.eval(t, x);
+            if slope == 0.0 || !slope.is_finite() {
+                if r.abs() <= TOL {
+                    return Ok(());
+                }
+                return Err(fail("zero derivative"));
+            }
+            let step = r / slope;
+            let old = x[a.var];
+            x[a.var] = old - step;
+            // Halve steps that leave the equation's domain (sqrt, ln, …).
+            let mut tries = 0;
+            while !a.residual.eval(t, x).is_finite() && tries < 30 {
+                x[a.var] = old - step * 0.5f64.powi(tries + 1);
+                tries += 1;
+            }
+            if step.abs() <= TOL * (1.0 + x[a.var].abs()) {
+                return Ok(());
+            }
+        }
+        Err(fail("Newton iteration did not converge"))
+    }
+
+    fn solve_torn(
+        &self,
+        inner: &[Assignment],
+        tears: &[usize],
+        residuals: &[(usize, Node)],
+        t: f64,
+        x: &mut [f64],
+    ) -> Result<(), String> {
+        let loop_names = || tears.iter().map(|&v| self.names[v].as_str()).collect::<Vec<_>>().join(", ");
+        let eval = |x: &mut [f64]| -> Result<Vec<f64>, String> {
+            for a in inner {
+                self.solve_scalar(a, t, x)?;
+            }
+            Ok(residuals.iter().map(|(_, r)| r.eval(t, x)).collect())
+        };
+        let n = tears.len();
+        let mut f = eval(x)?;
+        for _ in 0..MAX_NEWTON {
+            let fnorm = crate::ode::jacobian::norm(&f);
+            if fnorm <= TOL {
+                return Ok(());
+            }
+            // Forward-difference Jacobian of the loop residuals in the tears.
+            let mut jac = vec![0.0; n * n];
+            for (c, &v) in tears.iter().enumerate() {
+                let saved: Vec<f64> = x.to_vec();
+                let h = 1e-7 * (1.0 + x[v].abs());
+                x[v] += h;
+                let fp = eval(x)?;
+                x.copy_from_slice(&saved);
+                for r in 0..n {
+                    jac[r * n + c] = (fp[r] - f[r]) / h;
+                }
+            }
+            let lu = Lu::factor(n, jac)
+                .ok_or_else(|| format!("algebraic loop in {} is singular at t = {t}", loop_names()))?;
+            let dx = lu.solve(&f);
+            let base: Vec<f64> = x.to_vec();
+            let mut lambda = 1.0;
+            loop {
+                for (k, &v) in tears.iter().enumerate() {
+                    x[v] = base[v] - lambda * dx[k];
+                }
+                match eval(x) {
+                    Ok(next) if crate::ode::jacobian::norm(&next) < fnorm || lambda < 1e-3 => {
+                        f = next;
+                        break;
+                    }
+                    _ if lambda < 1e-3 => {
+                        x.copy_from_slice(&base);
+                        return Err(format!("algebraic loop in {} did not converge at t = {t}", loop_names()));
+                    }
+                    _ => {
+                        x.copy_from_slice(&base);
+                        lambda *= 0.5;
+                    }
+                }
+            }
+            let step = dx.iter().zip(tears).map(|(d, &v)| (lambda * d).abs() / (1.0 + x[v].abs())).fold(0.0, f64::max);
+            if step <= TOL && crate::ode::jacobian::norm(&f) <= 1e-6 {
+                return Ok(());
+            }
+        }
+        Err(format!("algebraic loop in {} did not converge at t = {t}", loop_names()))
+    }
+}
+
+/// Maximum matching of equations to the unknowns they reference.
+fn match_equations(incidence: &[Vec<usize>], n_vars: usize) -> Vec<Option<usize>> {
+    fn augment(e: usize, incidence: &[Vec<usize>], seen: &mut [bool], var_eq: &mut [Option<usize>]) -> bool {
+        for &v in &incidence[e] {
+            if seen[v] {
+                continue;
+            }
+            seen[v] = true;
+            if var_eq[v].is_none_or(|other| augment(other, incidence, seen, var_eq)) {
+                var_eq[v] = Some(e);
+                return true;
+            }
+        }
+        false
+    }
+    let mut var_eq = vec![None; n_vars];
+    for e in 0..incidence.len() {
+        augment(e, incidence, &mut vec![false; n_vars], &mut var_eq);
+    }
+    let mut eq_var = vec![None; incidence.len()];
+    for (v, e) in var_eq.iter().enumerate() {
+        if let Some(e) = e {
+            eq_var[*e] = Some(v);
+        }
+    }
+    eq_var
+}
+
+/// Strongly connected components of `deps` (edge `i → j`: `i` needs `j`),
+/// dependencies first.
+fn tarjan(deps: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct State<'a> {
+        deps: &'a [Vec<usize>],
+        counter: usize,
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        stack: Vec<usize>,
+        on_stack: Vec<bool>,
+        out: Vec<Vec<usize>>,
+    }
+    fn visit(s: &mut State, v: usize) {
+        s.index[v] = Some(s.counter);
+        s.low[v] = s.counter;
+        s.counter += 1;
+        s.stack.push(v);
+        s.on_stack[v] = true;
+        for k in 0..s.deps[v].len() {
+            let w = s.deps[v][k];
+            match s.index[w] {
+                None => {
+                    visit(s, w);
+                    s.low[v] = s.low[v].min(s.low[w]);
+                }
+                Some(iw) if s.on_stack[w] => s.low[v] = s.low[v].min(iw),
+                _ => {}
+            }
+        }
+        if Some(s.low[v]) == s.index[v] {
+            let mut component = Vec::new();
+            loop {
+                let w = s.stack.pop().expect("component root is on the stack");
+                s.on_stack[w] = false;
+                component.push(w);
+                if w == v {
+                    break;
+                }
+            }
+            component.sort_unstable();
+            s.out.push(component);
+        }
+    }
+    let n = deps.len();
+    let mut s = State {
+        deps,
+        counter: 0,
+        index: vec![None; n],
+        low: vec![0; n],
+        stack: Vec::new(),
+        on_stack: vec![false; n],
+        out: Vec::new(),
+    };
+    for v in 0..n {
+        if s.index[v].is_none() {
+            visit(&mut s, v);
+        }
+    }
+    s.out
+}
+
+/// Greedy tearing of one loop: `(inner assignments, tearing variables,
+/// residual equations)`. Equations that determine a single unknown of the
+/// loop linearly are assigned first; when none remains, the unknown that
+/// appears in most unassigned equations becomes a tearing variable.
+#[allow(clippy::type_complexity)]
+fn tear(
+    sys: &ReducedSystem,
+    eqs: &[usize],
+    vars: &[usize],
+    incidence: &[Vec<usize>],
+) -> (Vec<(usize, usize)>, Vec<usize>, Vec<usize>) {
+    let linear_in = |e: usize, v: usize| {
+        let name = symbol(sys.variables[v].base, sys.variables[v].order);
+        let slope = simplify(&differentiate(&sys.equations[e].residual, &name));
+        !free_variables(&slope).contains(&name) && !matches!(slope.as_ref(), SymExpr::Constant(c) if *c == 0.0)
+    };
+    let mut known: Vec<bool> = vec![false; sys.variables.len()];
+    let mut open: Vec<usize> = eqs.to_vec();
+    let mut inner = Vec::new();
+    let mut tears = Vec::new();
+    while vars.iter().any(|&v| !known[v]) {
+        let single = |e: &usize| {
+            let unknown: Vec<usize> = incidence[*e].iter().copied().filter(|&v| vars.contains(&v) && !known[v]).collect();
+            (unknown.len() == 1).then(|| unknown[0])
+        };
+        let causal = open
+            .iter()
+            .filter_map(|e| single(e).map(|v| (*e, v)))
+            .min_by_key(|&(e, v)| (!linear_in(e, v), e));
+        if let Some((e, v)) = causal {
+            inner.push((e, v));
+            known[v] = true;
+            open.retain(|&o| o != e);
+            continue;
+        }
+        let tear = vars
+            .iter()
+            .copied()
+            .filter(|&v| !known[v])
+            .max_by_key(|&v| (open.iter().filter(|&&e| incidence[e].contains(&v)).count(), std::cmp::Reverse(v)))
+            .expect("an unknown remains");
+        tears.push(tear);
+        known[tear] = true;
+    }
+    (inner, tears, open)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acausal::index::reduce;
+    use crate::acausal::network::{FlatEquation, FlatSystem, FlatVariable, VariableKind};
+    use crate::symbolic::parse_expr;
+
+    fn reduced(n: usize, eqs: &[&str]) -> ReducedSystem {
+        let flat = FlatSystem {
+            variables: (0..n)
+                .map(|j| FlatVariable { name: format!("x{j}"), kind: VariableKind::Internal, unit: String::new(), start: None })
+                .collect(),
+            equations: eqs.iter().map(|e| FlatEquation { residual: parse_expr(e).unwrap(), origin: e.to_string() }).collect(),
+        };
+        reduce(&flat).unwrap()
+    }
+
+    #[test]
+    fn sorts_a_triangular_system() {
+        let sys = reduced(3, &["v2 - v1 * 2", "v1 - v0 - 1", "v0 - 3"]);
+        let blt = Blt::new(&sys).unwrap();
+        assert_eq!(blt.loops(), 0);
+        let mut x = vec![0.0; 3];
+        blt.solve(0.0, &mut x).unwrap();
+        assert_eq!(x, vec![3.0, 4.0, 8.0]);
+    }
+
+    #[test]
+    fn tears_a_nonlinear_loop() {
+        // x0 + x1 = 3, x0 · x1 = 2, x2 = x0 + t
+        let sys = reduced(3, &["v0 + v1 - 3", "v0 * v1 - 2", "v2 - v0 - t"]);
+        let blt = Blt::new(&sys).unwrap();
+        assert_eq!(blt.loops(), 1);
+        assert_eq!(blt.tearing_variables().len(), 1);
+        let mut x = vec![1.5, 0.0, 0.0];
+        blt.solve(1.0, &mut x).unwrap();
+        assert!((x[0] * x[1] - 2.0).abs() < 1e-9 && (x[0] + x[1] - 3.0).abs() < 1e-9, "{x:?}");
+        assert!((x[2] - x[0] - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn reports_unsolvable_equations() {
+        // Structurally fine, numerically singular and inconsistent.
+        let sys = reduced(2, &["v0 + v1 - 1", "2 * v0 + 2 * v1 - 3"]);
+        let err = Blt::new(&sys).unwrap().solve(0.0, &mut [0.0, 0.0]).unwrap_err();
+        assert!(err.contains("algebraic loop in x"), "{err}");
+        let sys = reduced(1, &["v0 * v0 + 1"]);
+        let err = Blt::new(&sys).unwrap().solve(0.0, &mut [1.0]).unwrap_err();
+        assert!(err.contains("x0"), "{err}");
+    }
+}
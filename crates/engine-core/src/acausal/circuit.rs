@@ -0,0 +1,887 @@
+//! Modified nodal analysis (MNA) of electrical networks.
+//!
+//! A [`Circuit`] is an [`AcausalNetwork`] of electrical
+//! [`library`](super::library) components read as a netlist. Every node
+//! except ground has a voltage unknown; voltage sources, capacitors and
+//! inductors also carry their branch current as an unknown (MNA group 2), so
+//! the network is
+//!
+//! ```text
+//! G·x + C·ẋ + i(x, t) = b(t)
+//! ```
+//!
+//! with the linear stamps in `G` and `C`, diodes and switches in `i`, and
+//! the sources in `b`. On top of it:
+//!
+//! - [`Circuit::operating_point`] — DC (`ẋ = 0`: capacitors open, inductors
+//!   shorted), Newton with SPICE junction limiting, gmin to ground and source
+//!   stepping as a fallback
+//! - [`Circuit::ac`] — small-signal sweep `(G + jωC)·X = B` around the
+//!   operating point, driven by a unit source
+//! - [`Circuit::transient`] — fixed-step trapezoidal or Gear-2 (BDF2)
+//!   integration with a Newton solve per step
+//!
+//! Branch currents flow into `p`, through the component and out of `n`,
+//! matching the through-variable sign of the library.
+//!
+//! # References
+//! - Ho, C.-W., Ruehli, A. & Brennan, P. (1975). "The modified nodal approach to network analysis"
+//! - Najm, F.N. (2010). *Circuit Simulation*, ch. 2–5
+
+use super::{AcausalBlock, AcausalNetwork, MnaSystem};
+use crate::modal::{complex_solve, Complex};
+use crate::ode::jacobian::Lu;
+use std::collections::{HashMap, HashSet};
+
+/// Conductance from every node to ground, keeping floating nodes solvable.
+const GMIN: f64 = 1e-12;
+const MAX_NEWTON: usize = 100;
+/// Most fixed steps a transient takes, as the ODE blocks' `max_steps`.
+pub const MAX_TRANSIENT_STEPS: usize = 100_000;
+
+/// Time dependence of a voltage source.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Waveform {
+    Dc(f64),
+    Sine { amplitude: f64, frequency: f64, phase: f64, offset: f64 },
+}
+
+impl Waveform {
+    pub fn at(&self, t: f64) -> f64 {
+        match *self {
+            Waveform::Dc(v) => v,
+            Waveform::Sine { amplitude, frequency, phase, offset } => {
+                offset + amplitude * (2.0 * std::f64::consts::PI * frequency * t + phase).sin()
+            }
+        }
+    }
+}
+
+/// A two-terminal circuit element.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Element {
+    Resistor { r: f64 },
+    Capacitor { c: f64, v0: f64 },
+    Inductor { l: f64, i0: f64 },
+    VoltageSource(Waveform),
+    CurrentSource { i: f64 },
+    /// Shockley diode `i = Is·(exp(v / (N·Vt)) − 1)`.
+    Diode { is: f64, n_vt: f64 },
+    /// `ron` while `t_on ≤ t < t_off`, conductance `goff` otherwise.
+    Switch { ron: f64, goff: f64, t_on: f64, t_off: f64 },
+}
+
+impl Element {
+    fn switch_conductance(&self, t: f64) -> f64 {
+        match *self {
+            Element::Switch { ron, goff, t_on, t_off } => {
+                if t_on <= t && t < t_off {
+                    1.0 / ron
+                } else {
+                    goff
+                }
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+/// One component between nodes `p` and `n`.
+#[derive(Debug, Clone)]
+pub struct Branch {
+    /// Component id.
+    pub id: String,
+    pub element: Element,
+    /// Node of each terminal; `None` is ground.
+    pub p: Option<usize>,
+    pub n: Option<usize>,
+    /// Unknown holding the branch current, for group-2 elements.
+    pub current: Option<usize>,
+}
+
+/// What a result is probed at: a node voltage or a branch current.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Probe {
+    Voltage(Option<usize>),
+    Current(usize),
+}
+
+/// An electrical network ready for analysis.
+#[derive(Debug, Clone)]
+pub struct Circuit {
+    /// Non-ground nodes, each named after its first port.
+    pub nodes: Vec<String>,
+    pub branches: Vec<Branch>,
+    /// Port id → node.
+    ports: HashMap<String, Option<usize>>,
+    /// Number of unknowns: node voltages, then group-2 branch currents.
+    size: usize,
+}
+
+/// Node voltages and branch currents at the DC operating point.
+#[derive(Debug, Clone)]
+pub struct OperatingPoint {
+    pub voltages: Vec<f64>,
+    pub currents: Vec<f64>,
+    pub iterations: usize,
+}
+
+/// Small-signal response per frequency.
+#[derive(Debug, Clone)]
+pub struct AcResult {
+    pub frequencies: Vec<f64>,
+    pub voltages: Vec<Vec<Complex>>,
+    pub currents: Vec<Vec<Complex>>,
+}
+
+/// Node voltages and branch currents per time step.
+#[derive(Debug, Clone)]
+pub struct TransientResult {
+    pub t: Vec<f64>,
+    pub voltages: Vec<Vec<f64>>,
+    pub currents: Vec<Vec<f64>>,
+    pub newton_iterations: usize,
+}
+
+/// Transient integration formula.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Integration {
+    /// Second order and A-stable, but rings after discontinuities.
+    #[default]
+    Trapezoidal,
+    /// Second-order BDF: L-stable, damps switching artefacts.
+    Gear2,
+}
+
+#[derive(Debug, Clone)]
+pub struct TransientConfig {
+    pub t_start: f64,
+    pub t_end: f64,
+    pub dt: f64,
+    pub method: Integration,
+    /// Start capacitors at their `v0` and inductors at their `i0` (SPICE
+    /// `UIC`) instead of at the DC operating point.
+    pub use_initial_conditions: bool,
+}
+
+impl Default for TransientConfig {
+    fn default() -> Self {
+        TransientConfig { t_start: 0.0, t_end: 1e-3, dt: 1e-6, method: Integration::Trapezoidal, use_initial_conditions: true }
+    }
+}
+
+/// Row-major square matrix whose `None` rows and columns (ground) are dropped.
+#[derive(Debug, Clone)]
+struct Dense {
+    n: usize,
+    a: Vec<f64>,
+}
+
+impl Dense {
+    fn zeros(n: usize) -> Self {
+        Dense { n, a: vec![0.0; n * n] }
+    }
+
+    fn add(&mut self, r: Option<usize>, c: Option<usize>, v: f64) {
+        if let (Some(r), Some(c)) = (r, c) {
+            self.a[r * self.n + c] += v;
+        }
+    }
+
+    fn conductance(&mut self, p: Option<usize>, n: Option<usize>, g: f64) {
+        self.add(p, p, g);
+        self.add(n, n, g);
+        self.add(p, n, -g);
+        self.add(n, p, -g);
+    }
+
+    fn mul(&self, x: &[f64]) -> Vec<f64> {
+        self.a.chunks(self.n).map(|row| row.iter().zip(x).map(|(a, x)| a * x).sum()).collect()
+    }
+
+    fn triplets(&self) -> Vec<(usize, usize, f64)> {
+        (0..self.a.len()).filter(|&k| self.a[k] != 0.0).map(|k| (k / self.n, k % self.n, self.a[k])).collect()
+    }
+}
+
+fn add_at(v: &mut [f64], i: Option<usize>, x: f64) {
+    if let Some(i) = i {
+        v[i] += x;
+    }
+}
+
+/// Diode current and conductance at junction voltage `v`.
+fn diode(is: f64, n_vt: f64, v: f64) -> (f64, f64) {
+    let e = (v / n_vt).exp();
+    (is * (e - 1.0), is * e / n_vt)
+}
+
+/// SPICE `pnjlim`: keep Newton from overshooting up the exponential.
+fn limit_junction(v_new: f64, v_old: f64, is: f64, n_vt: f64) -> f64 {
+    let v_crit = n_vt * (n_vt / (std::f64::consts::SQRT_2 * is)).ln();
+    if v_new <= v_crit || (v_new - v_old).abs() <= 2.0 * n_vt {
+        return v_new;
+    }
+    if v_old > 0.0 {
+        let arg = 1.0 + (v_new - v_old) / n_vt;
+        if arg > 0.0 {
+            v_old + n_vt * arg.ln()
+        } else {
+            v_crit
+        }
+    } else {
+        n_vt * (v_new / n_vt).ln()
+    }
+}
+
+impl Circuit {
+    /// Read `network` as a netlist. Every component must be an `elec.*`
+    /// library component and at least one must be `elec.ground`.
+    pub fn from_network(network: &AcausalNetwork) -> Result<Self, String> {
+        if let Some(block) = network.blocks.iter().find(|b| !b.block_type.starts_with("elec.")) {
+            return Err(format!(
+                "{}: circuit analysis takes electrical library components only, not '{}'",
+                block.id, block.block_type
+            ));
+        }
+        let grounds: HashSet<&str> =
+            network.blocks.iter().filter(|b| b.block_type == "elec.ground").map(|b| b.id.as_str()).collect();
+        if grounds.is_empty() {
+            return Err("the circuit needs a ground (elec.ground)".to_string());
+        }
+        let mut nodes = Vec::new();
+        let mut ports = HashMap::new();
+        for node in network.nodes()? {
+            let slot = if node.iter().any(|p| grounds.contains(p.block_id.as_str())) {
+                None
+            } else {
+                nodes.push(node[0].id.clone());
+                Some(nodes.len() - 1)
+            };
+            for port in node {
+                ports.insert(port.id.clone(), slot);
+            }
+        }
+
+        let mut size = nodes.len();
+        let mut branches = Vec::new();
+        for block in network.blocks.iter().filter(|b| b.block_type != "elec.ground") {
+            let element = element(block)?;
+            let current = match element {
+                Element::VoltageSource(_) | Element::Capacitor { .. } | Element::Inductor { .. } => {
+                    size += 1;
+                    Some(size - 1)
+                }
+                _ => None,
+            };
+            let terminal = |name: &str| {
+                ports.get(&format!("{}.{name}", block.id)).copied().ok_or(format!("{}: no port '{name}'", block.id))
+            };
+            branches.push(Branch { id: block.id.clone(), element, p: terminal("p")?, n: terminal("n")?, current });
+        }
+        Ok(Circuit { nodes, branches, ports, size })
+    }
+
+    /// Number of unknowns.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// A node voltage by any port on the node (`"C1.p"`), or a branch
+    /// current by component id (`"R1"`).
+    pub fn probe(&self, name: &str) -> Result<Probe, String> {
+        if let Some(&node) = self.ports.get(name) {
+            return Ok(Probe::Voltage(node));
+        }
+        if let Some(k) = self.branches.iter().position(|b| b.id == name) {
+            return Ok(Probe::Current(k));
+        }
+        Err(format!("'{name}' is neither a port nor a component of the circuit"))
+    }
+
+    fn branch_voltage(&self, x: &[f64], b: &Branch) -> f64 {
+        let v = |node: Option<usize>| node.map_or(0.0, |i| x[i]);
+        v(b.p) - v(b.n)
+    }
+
+    /// The linear stamps: `G` (without gmin), `C` and `b(t)`.
+    fn conductance(&self) -> Dense {
+        let mut g = Dense::zeros(self.size);
+        for b in &self.branches {
+            match b.element {
+                Element::Resistor { r } => g.conductance(b.p, b.n, 1.0 / r),
+                Element::VoltageSource(_) | Element::Capacitor { .. } | Element::Inductor { .. } => {
+                    let k = b.current;
+                    g.add(b.p, k, 1.0);
+                    g.add(b.n, k, -1.0);
+                    match b.element {
+                        Element::VoltageSource(_) => {
+                            g.add(k, b.p, 1.0);
+                            g.add(k, b.n, -1.0);
+                        }
+                        Element::Capacitor { .. } => g.add(k, k, -1.0),
+                        _ => {
+                            g.add(k, b.p, -1.0);
+                            g.add(k, b.n, 1.0);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        g
+    }
+
+    fn capacitance(&self) -> Dense {
+        let mut c = Dense::zeros(self.size);
+        for b in &self.branches {
+            match b.element {
+                Element::Capacitor { c: cap, .. } => {
+                    c.add(b.current, b.p, cap);
+                    c.add(b.current, b.n, -cap);
+                }
+                Element::Inductor { l, .. } => c.add(b.current, b.current, l),
+                _ => {}
+            }
+        }
+        c
+    }
+
+    fn sources(&self, t: f64) -> Vec<f64> {
+        let mut rhs = vec![0.0; self.size];
+        for b in &self.branches {
+            match b.element {
+                Element::VoltageSource(wave) => add_at(&mut rhs, b.current, wave.at(t)),
+                Element::CurrentSource { i } => {
+                    add_at(&mut rhs, b.p, -i);
+                    add_at(&mut rhs, b.n, i);
+                }
+                _ => {}
+            }
+        }
+        rhs
+    }
+
+    fn with_gmin(&self, mut g: Dense) -> Dense {
+        for i in 0..self.nodes.len() {
+            g.add(Some(i), Some(i), GMIN);
+        }
+        g
+    }
+
+    fn is_nonlinear(&self) -> bool {
+        self.branches.iter().any(|b| matches!(b.element, Element::Diode { .. }))
+    }
+
+    /// The MNA matrices of the linear part, with the sources at `t = 0`.
+    pub fn mna(&self) -> MnaSystem {
+        MnaSystem {
+            nodes: self.nodes.clone(),
+            n_nodes: self.nodes.len(),
+            branches: self.branches.iter().filter(|b| b.current.is_some()).map(|b| b.id.clone()).collect(),
+            conductance: self.conductance().triplets(),
+            capacitance: self.capacitance().triplets(),
+            source: self.sources(0.0),
+            across_sources: self
+                .branches
+                .iter()
+                .filter_map(|b| match b.element {
+                    Element::VoltageSource(wave) => Some((b.id.clone(), wave.at(0.0))),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+
+    /// Solve `a·x + i(x, t) = rhs` by Newton from `x`, with diodes and
+    /// switches replaced by their companion conductances at each iterate.
+    fn newton(&self, a: &Dense, rhs: &[f64], t: f64, x: &mut Vec<f64>) -> Result<usize, String> {
+        let mut junction: Vec<f64> = self.branches.iter().map(|b| self.branch_voltage(x, b)).collect();
+        for iteration in 1..=MAX_NEWTON {
+            let mut m = a.clone();
+            let mut r = rhs.to_vec();
+            let mut limited = false;
+            for (k, b) in self.branches.iter().enumerate() {
+                match b.element {
+                    Element::Diode { is, n_vt } => {
+                        let v = self.branch_voltage(x, b);
+                        let vl = limit_junction(v, junction[k], is, n_vt);
+                        limited |= vl != v;
+                        junction[k] = vl;
+                        let (i, g) = diode(is, n_vt, vl);
+                        m.conductance(b.p, b.n, g);
+                        add_at(&mut r, b.p, -(i - g * vl));
+                        add_at(&mut r, b.n, i - g * vl);
+                    }
+                    Element::Switch { .. } => m.conductance(b.p, b.n, b.element.switch_conductance(t)),
+                    _ => {}
+                }
+            }
+            let lu = Lu::factor(self.size, m.a).ok_or(
+                "singular circuit matrix (a loop of voltage sources and inductors, or a cut set of current sources and capacitors)",
+            )?;
+            let next = lu.solve(&r);
+            if next.iter().any(|v| !v.is_finite()) {
+                return Err(format!("non-finite solution at t = {t}"));
+            }
+            let converged = !limited
+                && next.iter().zip(x.iter()).all(|(a, b)| (a - b).abs() <= 1e-9 + 1e-6 * a.abs().max(b.abs()));
+            *x = next;
+            if converged || !self.is_nonlinear() {
+                return Ok(iteration);
+            }
+        }
+        Err(format!("Newton did not converge in {MAX_NEWTON} iterations at t = {t}"))
+    }
+
+    /// Nonlinear and switched branch currents `i(x, t)` at node rows.
+    fn device_currents(&self, x: &[f64], t: f64) -> Vec<f64> {
+        let mut out = vec![0.0; self.size];
+        for b in &self.branches {
+            let i = match b.element {
+                Element::Diode { is, n_vt } => diode(is, n_vt, self.branch_voltage(x, b)).0,
+                Element::Switch { .. } => b.element.switch_conductance(t) * self.branch_voltage(x, b),
+                _ => continue,
+            };
+            add_at(&mut out, b.p, i);
+            add_at(&mut out, b.n, -i);
+        }
+        out
+    }
+
+    /// Every branch current at the solution `x`.
+    fn currents(&self, x: &[f64], t: f64) -> Vec<f64> {
+        self.branches
+            .iter()
+            .map(|b| match b.element {
+                Element::Resistor { r } => self.branch_voltage(x, b) / r,
+                Element::Diode { is, n_vt } => diode(is, n_vt, self.branch_voltage(x, b)).0,
+                Element::Switch { .. } => b.element.switch_conductance(t) * self.branch_voltage(x, b),
+                Element::CurrentSource { i } => i,
+                _ => x[b.current.expect("group-2 branch")],
+            })
+            .collect()
+    }
+
+    /// DC solution at time `t` (sources evaluated there).
+    fn dc(&self, t: f64) -> Result<(Vec<f64>, usize), String> {
+        let a = self.with_gmin(self.conductance());
+        let rhs = self.sources(t);
+        let mut x = vec![0.0; self.size];
+        if let Ok(iterations) = self.newton(&a, &rhs, t, &mut x) {
+            return Ok((x, iterations));
+        }
+        // Source stepping: ramp every source up from zero.
+        x.fill(0.0);
+        let mut total = 0;
+        for step in 1..=20 {
+            let scale = step as f64 / 20.0;
+            let scaled: Vec<f64> = rhs.iter().map(|v| v * scale).collect();
+            total += self
+                .newton(&a, &scaled, t, &mut x)
+                .map_err(|e| format!("DC operating point: {e} (source stepping stalled at {:.0}%)", scale * 100.0))?;
+        }
+        Ok((x, total))
+    }
+
+    /// DC operating point with the sources at `t = 0`.
+    pub fn operating_point(&self) -> Result<OperatingPoint, String> {
+        let (x, iterations) = self.dc(0.0)?;
+        Ok(OperatingPoint { voltages: x[..self.nodes.len()].to_vec(), currents: self.currents(&x, 0.0), iterations })
+    }
+
+    /// Small-signal sweep around the DC operating point, driven by a unit
+    /// amplitude on `source` (a voltage or current source id; the first
+    /// source when `None`) with every other source zeroed.
+    pub fn ac(&self, frequencies: &[f64], source: Option<&str>) -> Result<AcResult, String> {
+        let is_source = |b: &Branch| matches!(b.element, Element::VoltageSource(_) | Element::CurrentSource { .. });
+        let drive = match source {
+            Some(id) => self
+                .branches
+                .iter()
+                .position(|b| b.id == id && is_source(b))
+                .ok_or(format!("'{id}' is not a voltage or current source"))?,
+            None => self.branches.iter().position(is_source).ok_or("the circuit has no source to drive")?,
+        };
+        let (x0, _) = self.dc(0.0)?;
+        let mut g = self.with_gmin(self.conductance());
+        let small_signal: Vec<f64> = self
+            .branches
+            .iter()
+            .map(|b| match b.element {
+                Element::Diode { is, n_vt } => diode(is, n_vt, self.branch_voltage(&x0, b)).1,
+                Element::Switch { .. } => b.element.switch_conductance(0.0),
+                _ => 0.0,
+            })
+            .collect();
+        for (b, &gd) in self.branches.iter().zip(&small_signal) {
+            if gd != 0.0 {
+                g.conductance(b.p, b.n, gd);
+            }
+        }
+        let c = self.capacitance();
+        let mut rhs = vec![(0.0, 0.0); self.size];
+        let d = &self.branches[drive];
+        match d.element {
+            Element::VoltageSource(_) => rhs[d.current.expect("group-2 branch")].0 = 1.0,
+            _ => {
+                if let Some(p) = d.p {
+                    rhs[p].0 -= 1.0;
+                }
+                if let Some(n) = d.n {
+                    rhs[n].0 += 1.0;
+                }
+            }
+        }
+
+        let mut result = AcResult { frequencies: frequencies.to_vec(), voltages: Vec::new(), currents: Vec::new() };
+        for &f in frequencies {
+            let w = 2.0 * std::f64::consts::PI * f;
+            let a: Vec<Complex> = g.a.iter().zip(&c.a).map(|(&g, &c)| (g, w * c)).collect();
+            let x = complex_solve(self.size, a, rhs.clone()).ok_or(format!("singular circuit matrix at f = {f} Hz"))?;
+            let currents = self
+                .branches
+                .iter()
+                .enumerate()
+                .map(|(k, b)| {
+                    let v = |node: Option<usize>| node.map_or((0.0, 0.0), |i| x[i]);
+                    let (vp, vn) = (v(b.p), v(b.n));
+                    let scale = |s: f64| (s * (vp.0 - vn.0), s * (vp.1 - vn.1));
+                    match b.element {
+                        Element::Resistor { r } => scale(1.0 / r),
+                        Element::Diode { .. } | Element::Switch { .. } => scale(small_signal[k]),
+                        Element::CurrentSource { .. } => (if k == drive { 1.0 } else { 0.0 }, 0.0),
+                        _ => x[b.current.expect("group-2 branch")],
+                    }
+                })
+                .collect();
+            result.voltages.push(x[..self.nodes.len()].to_vec());
+            result.currents.push(currents);
+        }
+        Ok(result)
+    }
+
+    /// Consistent start: capacitor voltages and inductor currents pinned to
+    /// their initial conditions, everything else solved at `t`.
+    fn initial_conditions(&self, t: f64) -> Result<Vec<f64>, String> {
+        let mut a = self.with_gmin(self.conductance());
+        let mut rhs = self.sources(t);
+        for b in &self.branches {
+            let Some(k) = b.current else { continue };
+            let pinned = match b.element {
+                Element::Capacitor { v0, .. } => {
+                    a.a[k * self.size..(k + 1) * self.size].fill(0.0);
+                    a.add(Some(k), b.p, 1.0);
+                    a.add(Some(k), b.n, -1.0);
+                    v0
+                }
+                Element::Inductor { i0, .. } => {
+                    a.a[k * self.size..(k + 1) * self.size].fill(0.0);
+                    a.add(Some(k), Some(k), 1.0);
+                    i0
+                }
+                _ => continue,
+            };
+            rhs[k] = pinned;
+        }
+        let mut x = vec![0.0; self.size];
+        self.newton(&a, &rhs, t, &mut x).map_err(|e| format!("initial conditions: {e}"))?;
+        Ok(x)
+    }
+
+    /// Fixed-step transient from `config.t_start` to `config.t_end`.
+    pub fn transient(&self, config: &TransientConfig) -> Result<TransientResult, String> {
+        let span = config.t_end - config.t_start;
+        if span.is_nan() || span <= 0.0 || config.dt.is_nan() || config.dt <= 0.0 {
+            return Err("need t_end > t_start and dt > 0".to_string());
+        }
+        let steps = (span / config.dt).ceil();
+        if steps > MAX_TRANSIENT_STEPS as f64 {
+            return Err(format!(
+                "{steps} steps exceeds the {MAX_TRANSIENT_STEPS} step limit; increase dt or shorten the time span"
+            ));
+        }
+        let steps = steps as usize;
+        let h = span / steps as f64;
+        let g = self.with_gmin(self.conductance());
+        let c = self.capacitance();
+
+        let t0 = config.t_start;
+        let mut x = if config.use_initial_conditions { self.initial_conditions(t0)? } else { self.dc(t0)?.0 };
+        // q = C·ẋ, carried between trapezoidal steps.
+        let device = self.device_currents(&x, t0);
+        let gx = g.mul(&x);
+        let mut q: Vec<f64> = self.sources(t0).iter().zip(&gx).zip(&device).map(|((b, gx), i)| b - gx - i).collect();
+
+        let n_nodes = self.nodes.len();
+        let mut result = TransientResult {
+            t: vec![t0],
+            voltages: vec![x[..n_nodes].to_vec()],
+            currents: vec![self.currents(&x, t0)],
+            newton_iterations: 0,
+        };
+        let mut previous: Option<Vec<f64>> = None;
+        for step in 1..=steps {
+            let t = t0 + step as f64 * h;
+            let (alpha, history) = match (config.method, &previous) {
+                (Integration::Trapezoidal, _) => {
+                    let cx = c.mul(&x);
+                    (2.0 / h, cx.iter().zip(&q).map(|(cx, q)| 2.0 / h * cx + q).collect::<Vec<f64>>())
+                }
+                (Integration::Gear2, Some(older)) => {
+                    let blend: Vec<f64> = x.iter().zip(older).map(|(x, o)| (4.0 * x - o) / (2.0 * h)).collect();
+                    (1.5 / h, c.mul(&blend))
+                }
+                // Gear-2 starts with one backward Euler step.
+                (Integration::Gear2, None) => (1.0 / h, c.mul(&x).iter().map(|v| v / h).collect()),
+            };
+            let mut a = g.clone();
+            for (a, c) in a.a.iter_mut().zip(&c.a) {
+                *a += alpha * c;
+            }
+            let rhs: Vec<f64> = self.sources(t).iter().zip(&history).map(|(b, h)| b + h).collect();
+            let mut next = x.clone();
+            result.newton_iterations += self.newton(&a, &rhs, t, &mut next)?;
+            if config.method == Integration::Trapezoidal {
+                let dx: Vec<f64> = next.iter().zip(&x).map(|(a, b)| a - b).collect();
+                q = c.mul(&dx).iter().zip(&q).map(|(cdx, q)| alpha * cdx - q).collect();
+            }
+            previous = Some(std::mem::replace(&mut x, next));
+            result.t.push(t);
+            result.voltages.push(x[..n_nodes].to_vec());
+            result.currents.push(self.currents(&x, t));
+        }
+        Ok(result)
+    }
+
+    /// Column names of the voltage and current tables: `v(<node>)` and
+    /// `i(<component>)`.
+    pub fn columns(&self) -> (Vec<String>, Vec<String>) {
+        (
+            self.nodes.iter().map(|n| format!("v({n})")).collect(),
+            self.branches.iter().map(|b| format!("i({})", b.id)).collect(),
+        )
+    }
+}
+
+impl AcResult {
+    /// The response at `probe` for every frequency.
+    pub fn series(&self, probe: Probe) -> Vec<Complex> {
+        (0..self.frequencies.len())
+            .map(|k| match probe {
+                Probe::Voltage(Some(i)) => self.voltages[k][i],
+                Probe::Voltage(None) => (0.0, 0.0),
+                Probe::Current(b) => self.currents[k][b],
+            })
+            .collect()
+    }
+}
+
+impl TransientResult {
+    /// The waveform at `probe`.
+    pub fn series(&self, probe: Probe) -> Vec<f64> {
+        (0..self.t.len())
+            .map(|k| match probe {
+                Probe::Voltage(Some(i)) => self.voltages[k][i],
+                Probe::Voltage(None) => 0.0,
+                Probe::Current(b) => self.currents[k][b],
+            })
+            .collect()
+    }
+}
+
+/// The element a library component stands for.
+fn element(block: &AcausalBlock) -> Result<Element, String> {
+    let param = |name: &str| {
+        block.parameters.get(name).copied().ok_or(format!("{}: missing parameter '{name}'", block.id))
+    };
+    let start = |var: &str, fallback: &str| match block.initial_values.get(var) {
+        Some(&v) => Ok(v),
+        None => param(fallback),
+    };
+    let positive = |name: &str| {
+        let v = param(name)?;
+        if v > 0.0 {
+            Ok(v)
+        } else {
+            Err(format!("{}: {name} must be positive, got {v}", block.id))
+        }
+    };
+    Ok(match block.block_type.as_str() {
+        "elec.resistor" => Element::Resistor { r: positive("R")? },
+        "elec.capacitor" => Element::Capacitor { c: param("C")?, v0: start("v", "v0")? },
+        "elec.inductor" => Element::Inductor { l: param("L")?, i0: start("i", "i0")? },
+        "elec.voltage_source" => Element::VoltageSource(Waveform::Dc(param("V")?)),
+        "elec.sine_voltage" => Element::VoltageSource(Waveform::Sine {
+            amplitude: param("V")?,
+            frequency: param("f")?,
+            phase: param("phase")?,
+            offset: param("offset")?,
+        }),
+        "elec.current_source" => Element::CurrentSource { i: param("I")? },
+        "elec.diode" => Element::Diode { is: positive("Is")?, n_vt: positive("N")? * positive("Vt")? },
+        "elec.switch" => Element::Switch {
+            ron: positive("Ron")?,
+            goff: positive("Goff")?,
+            t_on: param("t_on")?,
+            t_off: param("t_off")?,
+        },
+        other => return Err(format!("{}: '{other}' has no circuit model", block.id)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acausal::library::component;
+
+    fn part(kind: &str, id: &str, values: &[(&str, f64)]) -> AcausalBlock {
+        component(kind, id, &values.iter().map(|(k, v)| (k.to_string(), *v)).collect()).unwrap()
+    }
+
+    /// Source `V1` → `R1` → `X` → ground, with `X` the given component.
+    fn series(source: AcausalBlock, r: f64, x: AcausalBlock) -> Circuit {
+        let mut net = AcausalNetwork::new();
+        net.add(source)
+            .add(part("elec.resistor", "R1", &[("R", r)]))
+            .add(x)
+            .add(part("elec.ground", "G", &[]))
+            .connect("V1.p", "R1.p")
+            .connect("R1.n", "X.p")
+            .connect("X.n", "V1.n")
+            .connect("V1.n", "G.p");
+        Circuit::from_network(&net).unwrap()
+    }
+
+    #[test]
+    fn divider_stamps_and_operating_point() {
+        let c = series(
+            part("elec.voltage_source", "V1", &[("V", 10.0)]),
+            1000.0,
+            part("elec.resistor", "X", &[("R", 3000.0)]),
+        );
+        assert_eq!(c.nodes, ["V1.p", "R1.n"]);
+        let mna = c.mna();
+        assert_eq!((mna.n_nodes, mna.branches.as_slice()), (2, ["V1".to_string()].as_slice()));
+        assert!(mna.conductance.contains(&(0, 0, 1e-3)) && mna.conductance.contains(&(2, 0, 1.0)));
+        assert_eq!(mna.source, [0.0, 0.0, 10.0]);
+
+        let op = c.operating_point().unwrap();
+        // gmin to ground perturbs the ideal values slightly.
+        assert!((op.voltages[1] - 7.5).abs() < 1e-7);
+        // Branch currents run into p: the source delivers current out of p.
+        let i = op.currents;
+        assert!((i[0] + 2.5e-3).abs() < 1e-10 && (i[1] - 2.5e-3).abs() < 1e-10 && (i[2] - 2.5e-3).abs() < 1e-10);
+    }
+
+    #[test]
+    fn diode_operating_point_satisfies_kcl() {
+        let c = series(part("elec.voltage_source", "V1", &[("V", 5.0)]), 1000.0, part("elec.diode", "X", &[]));
+        let op = c.operating_point().unwrap();
+        let vd = op.voltages[1];
+        let (i, _) = diode(1e-12, 0.025852, vd);
+        assert!((0.5..0.75).contains(&vd), "{vd}");
+        assert!(((5.0 - vd) / 1000.0 - i).abs() < 1e-9, "{} vs {i}", (5.0 - vd) / 1000.0);
+        assert!(op.iterations > 2 && op.iterations < MAX_NEWTON);
+    }
+
+    #[test]
+    fn rc_low_pass_ac_response() {
+        let (r, cap) = (1000.0, 1e-6);
+        let c = series(part("elec.voltage_source", "V1", &[]), r, part("elec.capacitor", "X", &[("C", cap)]));
+        let fc = 1.0 / (2.0 * std::f64::consts::PI * r * cap);
+        let ac = c.ac(&[fc / 100.0, fc, fc * 100.0], None).unwrap();
+        let h = ac.series(c.probe("X.p").unwrap());
+        assert!((h[0].0.hypot(h[0].1) - 1.0).abs() < 1e-3);
+        assert!((h[1].0.hypot(h[1].1) - 0.5f64.sqrt()).abs() < 1e-9);
+        assert!((h[1].1.atan2(h[1].0).to_degrees() + 45.0).abs() < 1e-6);
+        assert!((h[2].0.hypot(h[2].1) - 0.01).abs() < 1e-4);
+        // Current through the capacitor equals the current through R.
+        let (ir, ic) = (ac.series(c.probe("R1").unwrap()), ac.series(c.probe("X").unwrap()));
+        assert!((ir[1].0 - ic[1].0).abs() < 1e-12 && (ir[1].1 - ic[1].1).abs() < 1e-12);
+        assert!(c.ac(&[fc], Some("R1")).is_err());
+    }
+
+    #[test]
+    fn rlc_transient_matches_the_analytic_step_response() {
+        let (r, l, cap) = (10.0, 1e-2, 1e-4);
+        let mut net = AcausalNetwork::new();
+        net.add(part("elec.voltage_source", "V1", &[("V", 1.0)]))
+            .add(part("elec.resistor", "R1", &[("R", r)]))
+            .add(part("elec.inductor", "L1", &[("L", l)]))
+            .add(part("elec.capacitor", "C1", &[("C", cap)]))
+            .add(part("elec.ground", "G", &[]))
+            .connect("V1.p", "R1.p")
+            .connect("R1.n", "L1.p")
+            .connect("L1.n", "C1.p")
+            .connect("C1.n", "V1.n")
+            .connect("V1.n", "G.p");
+        let c = Circuit::from_network(&net).unwrap();
+        let alpha = r / (2.0 * l);
+        let wd = (1.0 / (l * cap) - alpha * alpha).sqrt();
+        for (method, tol) in [(Integration::Trapezoidal, 2e-4), (Integration::Gear2, 2e-3)] {
+            let cfg = TransientConfig { t_end: 0.02, dt: 1e-5, method, ..Default::default() };
+            let result = c.transient(&cfg).unwrap();
+            let v = result.series(c.probe("C1.p").unwrap());
+            for (t, v) in result.t.iter().zip(&v) {
+                let exact = 1.0 - (-alpha * t).exp() * ((wd * t).cos() + alpha / wd * (wd * t).sin());
+                assert!((v - exact).abs() < tol, "{method:?} t = {t}: {v} vs {exact}");
+            }
+        }
+
+        // Started at the operating point, nothing moves.
+        let cfg = TransientConfig { t_end: 1e-3, dt: 1e-5, use_initial_conditions: false, ..Default::default() };
+        let v = c.transient(&cfg).unwrap().series(c.probe("C1.p").unwrap());
+        assert!(v.iter().all(|v| (v - 1.0).abs() < 1e-6));
+    }
+
+    #[test]
+    fn half_wave_rectifier_clips_the_negative_half_cycle() {
+        let c = series(
+            part("elec.sine_voltage", "V1", &[("V", 5.0), ("f", 50.0)]),
+            1000.0,
+            part("elec.diode", "X", &[]),
+        );
+        // Output across the resistor: source minus diode drop, never negative.
+        let cfg = TransientConfig { t_end: 0.04, dt: 1e-5, method: Integration::Gear2, ..Default::default() };
+        let result = c.transient(&cfg).unwrap();
+        let i = result.series(c.probe("R1").unwrap());
+        let peak = i.iter().cloned().fold(f64::MIN, f64::max) * 1000.0;
+        assert!((4.2..4.5).contains(&peak), "{peak}");
+        assert!(i.iter().all(|&i| i > -1e-9));
+    }
+
+    #[test]
+    fn switch_closes_on_schedule() {
+        let c = series(
+            part("elec.voltage_source", "V1", &[("V", 1.0)]),
+            1.0,
+            part("elec.switch", "X", &[("t_on", 0.5e-3), ("t_off", 1.5e-3)]),
+        );
+        let cfg = TransientConfig { t_end: 2e-3, dt: 1e-5, ..Default::default() };
+        let result = c.transient(&cfg).unwrap();
+        let i = result.series(c.probe("X").unwrap());
+        for (t, i) in result.t.iter().zip(&i) {
+            let expected = if (0.5e-3..1.5e-3).contains(t) { 1.0 / (1.0 + 1e-5) } else { 1.0 / (1.0 + 1e5) };
+            assert!((i - expected).abs() < 1e-6, "t = {t}: {i}");
+        }
+    }
+
+    #[test]
+    fn transient_rejects_too_many_steps() {
+        let c = series(part("elec.voltage_source", "V1", &[]), 1000.0, part("elec.capacitor", "X", &[]));
+        let cfg = TransientConfig { t_end: 1.0, dt: 1e-6, ..Default::default() };
+        assert!(c.transient(&cfg).unwrap_err().contains("step limit"));
+        let cfg = TransientConfig { t_end: 1.0, dt: 1e-5, ..Default::default() };
+        assert!(c.transient(&cfg).is_ok());
+    }
+
+    #[test]
+    fn netlist_errors_are_reported() {
+        let mut net = AcausalNetwork::new();
+        net.add(part("elec.resistor", "R1", &[]));
+        assert!(Circuit::from_network(&net).unwrap_err().contains("ground"));
+        net.add(part("elec.ground", "G", &[])).add(part("rot.inertia", "J", &[]));
+        assert!(Circuit::from_network(&net).unwrap_err().contains("electrical library components"));
+    }
+}
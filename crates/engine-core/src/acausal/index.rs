@@ -0,0 +1,441 @@
+//! Structural index reduction of a flattened acausal system.
+//!
+//! [`reduce`] runs the Pantelides algorithm on the equation/variable
+//! incidence graph, differentiating every constraint that hides a
+//! differential relation until the highest derivatives can be matched to
+//! equations one-to-one. The dummy derivative method (Mattsson & Söderlind)
+//! then demotes one derivative per differentiated equation to an algebraic
+//! unknown, so that the original and differentiated equations together form
+//! an index-1 system in the remaining states.
+//!
+//! Derivatives use the [`symbol`] naming of [`network`](super::network):
+//! `v{j}_d{k}` is the `k`-th derivative of flat variable `j`.
+//!
+//! The dummy derivatives are chosen once, from the Jacobian at the start
+//! values. Models whose constraint Jacobian changes rank along the
+//! trajectory (a pendulum swinging through the horizontal in Cartesian
+//! coordinates) need dynamic state selection, which is not attempted.
+//!
+//! # References
+//! - Pantelides, C.C. (1988). "The consistent initialization of
+//!   differential-algebraic systems", *SIAM J. Sci. Stat. Comput.* 9(2)
+//! - Mattsson, S.E. & Söderlind, G. (1993). "Index reduction in
+//!   differential-algebraic equations using dummy derivatives",
+//!   *SIAM J. Sci. Comput.* 14(3)
+
+use super::network::{parse_symbol, symbol, FlatSystem};
+use super::{DaeDiagnostic, DaeSeverity};
+use crate::symbolic::{add, differentiate, eval, free_variables, mul, simplify, var, Expr};
+use std::collections::HashMap;
+
+/// An unknown of the reduced system: flat variable `base` differentiated
+/// `order` times.
+#[derive(Debug, Clone)]
+pub struct ReducedVariable {
+    /// `name`, `der(name)`, `der(der(name))`, …
+    pub name: String,
+    pub base: usize,
+    pub order: usize,
+}
+
+/// An equation of the reduced system.
+#[derive(Debug, Clone)]
+pub struct ReducedEquation {
+    pub residual: Expr,
+    pub origin: String,
+    /// How often the flat equation was differentiated to obtain this one.
+    pub differentiations: usize,
+}
+
+/// The index-reduced system: as many equations as non-state unknowns.
+#[derive(Debug, Clone)]
+pub struct ReducedSystem {
+    pub variables: Vec<ReducedVariable>,
+    pub equations: Vec<ReducedEquation>,
+    /// Integrated variables, as indices into `variables`.
+    pub states: Vec<usize>,
+    /// `state_derivatives[i]` is the variable holding `d states[i] / dt`.
+    pub state_derivatives: Vec<usize>,
+    /// Derivatives demoted to algebraic unknowns.
+    pub dummies: Vec<usize>,
+    /// Everything solved algebraically at each time point.
+    pub unknowns: Vec<usize>,
+    /// Differentiation index of the flat system (0 for an explicit ODE).
+    pub index: usize,
+    pub diagnostics: Vec<DaeDiagnostic>,
+}
+
+impl ReducedSystem {
+    /// Index of the variable with the given base and derivative order.
+    pub fn find(&self, base: usize, order: usize) -> Option<usize> {
+        self.variables.iter().position(|v| v.base == base && v.order == order)
+    }
+}
+
+/// Time derivative of a residual: `∂r/∂t + Σ ∂r/∂s · ṡ` over its symbols.
+pub fn time_derivative(residual: &Expr) -> Expr {
+    let mut total = simplify(&differentiate(residual, "t"));
+    for name in free_variables(residual) {
+        if let Some((j, k)) = parse_symbol(&name) {
+            let partial = simplify(&differentiate(residual, &name));
+            total = add(total, mul(partial, var(&symbol(j, k + 1))));
+        }
+    }
+    simplify(&total)
+}
+
+struct Equation {
+    residual: Expr,
+    origin: String,
+    differentiations: usize,
+    /// Variables (indices into `Pantelides::vars`) the equation references.
+    vars: Vec<usize>,
+    /// The equation this one was differentiated from, and into.
+    preimage: Option<usize>,
+    derivative: Option<usize>,
+}
+
+struct Pantelides {
+    /// `(base, order)` of each variable.
+    vars: Vec<(usize, usize)>,
+    lookup: HashMap<(usize, usize), usize>,
+    /// The next derivative of each variable, once it exists.
+    var_derivative: Vec<Option<usize>>,
+    eqs: Vec<Equation>,
+    assign: Vec<Option<usize>>,
+}
+
+impl Pantelides {
+    fn var_index(&mut self, base: usize, order: usize) -> usize {
+        if let Some(&v) = self.lookup.get(&(base, order)) {
+            return v;
+        }
+        let v = self.vars.len();
+        self.vars.push((base, order));
+        self.lookup.insert((base, order), v);
+        self.var_derivative.push(None);
+        self.assign.push(None);
+        if order > 0 {
+            let lower = self.var_index(base, order - 1);
+            self.var_derivative[lower] = Some(v);
+        }
+        v
+    }
+
+    fn push_equation(&mut self, residual: Expr, origin: String, differentiations: usize, preimage: Option<usize>) -> usize {
+        let vars = free_variables(&residual)
+            .iter()
+            .filter_map(|name| parse_symbol(name))
+            .map(|(j, k)| self.var_index(j, k))
+            .collect();
+        self.eqs.push(Equation { residual, origin, differentiations, vars, preimage, derivative: None });
+        self.eqs.len() - 1
+    }
+
+    fn augment(&mut self, eq: usize, var_color: &mut [bool], eq_color: &mut [bool]) -> bool {
+        eq_color[eq] = true;
+        let candidates: Vec<usize> =
+            self.eqs[eq].vars.iter().copied().filter(|&v| self.var_derivative[v].is_none()).collect();
+        if let Some(&v) = candidates.iter().find(|&&v| self.assign[v].is_none()) {
+            self.assign[v] = Some(eq);
+            return true;
+        }
+        for v in candidates {
+            if var_color[v] {
+                continue;
+            }
+            var_color[v] = true;
+            let next = self.assign[v].expect("visited variable is assigned");
+            if self.augment(next, var_color, eq_color) {
+                self.assign[v] = Some(eq);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn run(&mut self, limit: usize) -> Result<(), String> {
+        for k in 0..self.eqs.len() {
+            let mut i = k;
+            loop {
+                let mut var_color = vec![false; self.vars.len()];
+                let mut eq_color = vec![false; self.eqs.len()];
+                if self.augment(i, &mut var_color, &mut eq_color) {
+                    break;
+                }
+                if self.eqs.len() > limit {
+                    let origin = &self.eqs[k].origin;
+                    return Err(format!("structurally singular system: equation '{origin}' cannot be matched to an unknown"));
+                }
+                let colored_vars: Vec<usize> = (0..var_color.len()).filter(|&v| var_color[v]).collect();
+                for &v in &colored_vars {
+                    let (base, order) = self.vars[v];
+                    self.var_index(base, order + 1);
+                }
+                for l in (0..eq_color.len()).filter(|&l| eq_color[l]) {
+                    let residual = time_derivative(&self.eqs[l].residual);
+                    let origin = self.eqs[l].origin.clone();
+                    let diffs = self.eqs[l].differentiations + 1;
+                    let new = self.push_equation(residual, origin, diffs, Some(l));
+                    self.eqs[l].derivative = Some(new);
+                }
+                for &v in &colored_vars {
+                    let eq = self.assign[v].expect("colored variable is assigned");
+                    let dv = self.var_derivative[v].expect("derivative created above");
+                    self.assign[dv] = self.eqs[eq].derivative;
+                }
+                i = self.eqs[i].derivative.expect("differentiated above");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reduce `flat` to an index-1 system with dummy derivatives.
+pub fn reduce(flat: &FlatSystem) -> Result<ReducedSystem, String> {
+    let n = flat.variables.len();
+    let m = flat.equations.len();
+    if m != n {
+        let what = if m < n { "underdetermined" } else { "overdetermined" };
+        return Err(format!("{what} system: {m} equations for {n} unknowns"));
+    }
+    let mut p = Pantelides {
+        vars: Vec::new(),
+        lookup: HashMap::new(),
+        var_derivative: Vec::new(),
+        eqs: Vec::new(),
+        assign: Vec::new(),
+    };
+    for j in 0..n {
+        p.var_index(j, 0);
+    }
+    for eq in &flat.equations {
+        p.push_equation(simplify(&eq.residual), eq.origin.clone(), 0, None);
+    }
+    p.run(8 * n + 16).map_err(|e| {
+        let unmatched: Vec<&str> = (0..p.vars.len())
+            .filter(|&v| p.var_derivative[v].is_none() && p.assign[v].is_none())
+            .map(|v| flat.variables[p.vars[v].0].name.as_str())
+            .collect();
+        if unmatched.is_empty() { e } else { format!("{e} (unmatched: {})", unmatched.join(", ")) }
+    })?;
+
+    let dummies = select_dummies(&p, flat);
+    let is_dummy = |v: usize| dummies.contains(&v);
+    let mut states = Vec::new();
+    let mut state_derivatives = Vec::new();
+    for v in 0..p.vars.len() {
+        if let Some(dv) = p.var_derivative[v] {
+            if !is_dummy(dv) {
+                states.push(v);
+                state_derivatives.push(dv);
+            }
+        }
+    }
+    let unknowns: Vec<usize> = (0..p.vars.len()).filter(|v| !states.contains(v)).collect();
+    let max_diff = p.eqs.iter().map(|e| e.differentiations).max().unwrap_or(0);
+    let index = if max_diff > 0 {
+        max_diff + 1
+    } else if unknowns.iter().any(|u| !state_derivatives.contains(u)) {
+        1
+    } else {
+        0
+    };
+
+    let variables: Vec<ReducedVariable> = p
+        .vars
+        .iter()
+        .map(|&(base, order)| {
+            let mut name = flat.variables[base].name.clone();
+            for _ in 0..order {
+                name = format!("der({name})");
+            }
+            ReducedVariable { name, base, order }
+        })
+        .collect();
+    let mut diagnostics = vec![DaeDiagnostic {
+        severity: DaeSeverity::Info,
+        message: format!(
+            "index {index}: {} equations, {} states, {} differentiated equations",
+            p.eqs.len(),
+            states.len(),
+            p.eqs.len() - m
+        ),
+        related_variables: states.iter().map(|&s| variables[s].name.clone()).collect(),
+        related_equations: Vec::new(),
+    }];
+    if !dummies.is_empty() {
+        diagnostics.push(DaeDiagnostic {
+            severity: DaeSeverity::Info,
+            message: format!("{} dummy derivatives selected", dummies.len()),
+            related_variables: dummies.iter().map(|&d| variables[d].name.clone()).collect(),
+            related_equations: p
+                .eqs
+                .iter()
+                .filter(|e| e.differentiations > 0)
+                .map(|e| format!("d^{}/dt^{} of {}", e.differentiations, e.differentiations, e.origin))
+                .collect(),
+        });
+    }
+    let equations = p
+        .eqs
+        .into_iter()
+        .map(|e| ReducedEquation { residual: e.residual, origin: e.origin, differentiations: e.differentiations })
+        .collect();
+    Ok(ReducedSystem { variables, equations, states, state_derivatives, dummies, unknowns, index, diagnostics })
+}
+
+/// Mattsson–Söderlind selection, level by level from the most
+/// differentiated equations down.
+fn select_dummies(p: &Pantelides, flat: &FlatSystem) -> Vec<usize> {
+    let mut values: HashMap<String, f64> = HashMap::from([("t".to_string(), 0.0)]);
+    for &(base, order) in &p.vars {
+        let start = if order == 0 { flat.variables[base].start.unwrap_or(0.0) } else { 0.0 };
+        values.insert(symbol(base, order), start);
+    }
+    // Demoting `x'` turns `x` from a state into an algebraic unknown, which
+    // would drop a user-supplied start value.
+    let weight = |v: usize| {
+        let (base, order) = p.vars[v];
+        if order == 1 && flat.variables[base].start.is_some() { 1e-3 } else { 1.0 }
+    };
+
+    let mut dummies = Vec::new();
+    let mut g: Vec<usize> =
+        (0..p.eqs.len()).filter(|&e| p.eqs[e].derivative.is_none() && p.eqs[e].preimage.is_some()).collect();
+    let mut z: Vec<usize> =
+        (0..p.vars.len()).filter(|&v| p.var_derivative[v].is_none() && p.vars[v].1 >= 1).collect();
+    while !g.is_empty() {
+        let jac: Vec<Vec<f64>> = g
+            .iter()
+            .map(|&e| {
+                z.iter()
+                    .map(|&v| {
+                        let (base, order) = p.vars[v];
+                        if p.eqs[e].vars.contains(&v) {
+                            let d = simplify(&differentiate(&p.eqs[e].residual, &symbol(base, order)));
+                            let value = eval(&d, &values);
+                            // A structural entry that vanishes here still counts, faintly.
+                            if value.is_finite() && value != 0.0 { value } else { 1e-9 * weight(v) }
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let chosen = choose_columns(&jac, &z.iter().map(|&v| weight(v)).collect::<Vec<_>>());
+        let selected: Vec<usize> = chosen.into_iter().map(|c| z[c]).collect();
+        dummies.extend(&selected);
+        g = g.iter().filter_map(|&e| p.eqs[e].preimage).filter(|&e| p.eqs[e].preimage.is_some()).collect();
+        z = selected
+            .iter()
+            .filter(|&&v| p.vars[v].1 >= 2)
+            .map(|&v| p.lookup[&(p.vars[v].0, p.vars[v].1 - 1)])
+            .collect();
+    }
+    dummies.sort_unstable();
+    dummies
+}
+
+/// Columns of `jac` (one per row) forming a nonsingular square submatrix,
+/// by Gaussian elimination with weighted column pivoting.
+fn choose_columns(jac: &[Vec<f64>], weights: &[f64]) -> Vec<usize> {
+    let mut a: Vec<Vec<f64>> = jac.to_vec();
+    let cols = weights.len();
+    let mut used = vec![false; cols];
+    let mut chosen = Vec::new();
+    for r in 0..a.len() {
+        let scale = a[r].iter().fold(0.0f64, |s, x| s.max(x.abs()));
+        let best = (0..cols)
+            .filter(|&c| !used[c] && a[r][c] != 0.0)
+            .max_by(|&x, &y| {
+                let sx = (a[r][x].abs() / scale) * weights[x];
+                let sy = (a[r][y].abs() / scale) * weights[y];
+                sx.total_cmp(&sy).then(y.cmp(&x))
+            });
+        let Some(c) = best else { continue };
+        used[c] = true;
+        chosen.push(c);
+        let (pivot_rows, rest) = a.split_at_mut(r + 1);
+        let pivot = &pivot_rows[r];
+        for row in rest {
+            let f = row[c] / pivot[c];
+            if f != 0.0 {
+                row.iter_mut().zip(pivot).for_each(|(x, p)| *x -= f * p);
+                row[c] = 0.0;
+            }
+        }
+    }
+    chosen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acausal::network::{FlatEquation, FlatVariable, VariableKind};
+    use crate::symbolic::parse_expr;
+
+    fn flat(vars: &[(&str, Option<f64>)], eqs: &[&str]) -> FlatSystem {
+        FlatSystem {
+            variables: vars
+                .iter()
+                .map(|&(name, start)| FlatVariable { name: name.into(), kind: VariableKind::Internal, unit: String::new(), start })
+                .collect(),
+            equations: eqs
+                .iter()
+                .map(|e| FlatEquation { residual: parse_expr(e).unwrap(), origin: e.to_string() })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn explicit_ode_is_index_zero() {
+        let sys = flat(&[("x", Some(1.0))], &["v0_d1 + v0"]);
+        let r = reduce(&sys).unwrap();
+        assert_eq!(r.index, 0);
+        assert_eq!(r.states, vec![0]);
+        assert!(r.dummies.is_empty());
+    }
+
+    #[test]
+    fn two_rigidly_coupled_inertias_are_index_two() {
+        // J1 w1' = tau, J2 w2' = -tau, w1 = w2
+        let sys = flat(
+            &[("w1", Some(1.0)), ("w2", None), ("tau", None)],
+            &["2 * v0_d1 - v2", "3 * v1_d1 + v2", "v0 - v1"],
+        );
+        let r = reduce(&sys).unwrap();
+        assert_eq!(r.index, 2);
+        assert_eq!(r.equations.len(), 4);
+        assert_eq!(r.states.len(), 1);
+        assert_eq!(r.dummies.len(), 1);
+        // w1 carries the start value, so w2's derivative is the dummy.
+        assert_eq!(r.variables[r.states[0]].name, "w1");
+        assert_eq!(r.variables[r.dummies[0]].name, "der(w2)");
+        assert_eq!(r.unknowns.len(), r.equations.len());
+    }
+
+    #[test]
+    fn cartesian_pendulum_is_index_three() {
+        // x'' = -F x, y'' = -F y - g, x² + y² = 1, written first order
+        let sys = flat(
+            &[("x", Some(1.0)), ("y", None), ("u", None), ("v", None), ("F", None)],
+            &["v0_d1 - v2", "v1_d1 - v3", "v2_d1 + v4 * v0", "v3_d1 + v4 * v1 + 9.81", "v0^2 + v1^2 - 1"],
+        );
+        let r = reduce(&sys).unwrap();
+        assert_eq!(r.index, 3);
+        assert_eq!(r.states.len(), 2);
+        assert_eq!(r.unknowns.len(), r.equations.len());
+        // At x = 1, y = 0 the constraint pins x, so y is integrated.
+        let names: Vec<&str> = r.states.iter().map(|&s| r.variables[s].name.as_str()).collect();
+        assert!(names.contains(&"y") && !names.contains(&"x"), "{names:?}");
+    }
+
+    #[test]
+    fn singular_systems_are_rejected() {
+        assert!(reduce(&flat(&[("x", None), ("y", None)], &["v0 - 1"])).unwrap_err().contains("underdetermined"));
+        let err = reduce(&flat(&[("x", None), ("y", None)], &["v0 - 1", "v0 - 2"])).unwrap_err();
+        assert!(err.contains("structurally singular"), "{err}");
+    }
+}
@@ -0,0 +1,631 @@
+//! Standard acausal component library.
+//!
+//! Every component is a [`ComponentSpec`]: ports, parameters with units and
+//! defaults, and constitutive equations in [`parse_equation`] syntax naming
+//! ports relative to the component. [`ComponentSpec::build`] turns a spec
+//! into an [`AcausalBlock`] for an [`AcausalNetwork`](super::AcausalNetwork).
+//!
+//! Conventions follow the Modelica Standard Library: through-variables are
+//! positive into the component, two-terminal electrical components have
+//! ports `p`/`n`, mechanical ones `flange_a`/`flange_b`, thermal and
+//! hydraulic ones `port_a`/`port_b`. Sources drive their through-variable
+//! out of the component into what they are connected to. Translational and
+//! rotational across-variables are velocities, so springs integrate their
+//! own relative displacement.
+//!
+//! Switching (ideal switch, clutch) is scheduled in time with `sign()`;
+//! Coulomb friction and the orifice law are regularised near zero so that
+//! the equations stay smooth for Newton and Radau.
+
+use super::{parse_equation, AcausalBlock, PhysicalDomain, Port};
+use std::collections::HashMap;
+
+/// A component parameter.
+#[derive(Debug, Clone)]
+pub struct ParameterSpec {
+    pub name: &'static str,
+    /// SI unit, empty when dimensionless.
+    pub unit: &'static str,
+    pub default: f64,
+    pub description: &'static str,
+}
+
+/// A library component: ports, parameters and equations.
+#[derive(Debug, Clone)]
+pub struct ComponentSpec {
+    /// `"elec.resistor"`, `"rot.inertia"`, …; the catalog block is `acausal.<kind>`.
+    pub kind: &'static str,
+    pub label: &'static str,
+    pub ports: &'static [(&'static str, PhysicalDomain)],
+    pub parameters: &'static [ParameterSpec],
+    pub equations: &'static [&'static str],
+    /// `(variable, parameter)`: the variable starts at the parameter's value.
+    pub initial_values: &'static [(&'static str, &'static str)],
+}
+
+const fn param(name: &'static str, unit: &'static str, default: f64, description: &'static str) -> ParameterSpec {
+    ParameterSpec { name, unit, default, description }
+}
+
+use PhysicalDomain::{Electrical, Hydraulic, Rotational, Thermal, Translational};
+
+/// Every library component.
+pub static COMPONENTS: &[ComponentSpec] = &[
+    // ── Electrical ──────────────────────────────────────────────────────────
+    ComponentSpec {
+        kind: "elec.ground",
+        label: "Ground",
+        ports: &[("p", Electrical)],
+        parameters: &[],
+        equations: &["p.voltage = 0"],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "elec.resistor",
+        label: "Resistor",
+        ports: &[("p", Electrical), ("n", Electrical)],
+        parameters: &[param("R", "Ω", 1.0, "Resistance")],
+        equations: &["p.voltage - n.voltage = R * p.current", "p.current + n.current = 0"],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "elec.capacitor",
+        label: "Capacitor",
+        ports: &[("p", Electrical), ("n", Electrical)],
+        parameters: &[param("C", "F", 1e-6, "Capacitance"), param("v0", "V", 0.0, "Initial voltage")],
+        equations: &["v = p.voltage - n.voltage", "C * der(v) = p.current", "p.current + n.current = 0"],
+        initial_values: &[("v", "v0")],
+    },
+    ComponentSpec {
+        kind: "elec.inductor",
+        label: "Inductor",
+        ports: &[("p", Electrical), ("n", Electrical)],
+        parameters: &[param("L", "H", 1e-3, "Inductance"), param("i0", "A", 0.0, "Initial current")],
+        equations: &["L * der(i) = p.voltage - n.voltage", "i = p.current", "p.current + n.current = 0"],
+        initial_values: &[("i", "i0")],
+    },
+    ComponentSpec {
+        kind: "elec.voltage_source",
+        label: "Voltage Source",
+        ports: &[("p", Electrical), ("n", Electrical)],
+        parameters: &[param("V", "V", 1.0, "Voltage between p and n")],
+        equations: &["p.voltage - n.voltage = V", "p.current + n.current = 0"],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "elec.current_source",
+        label: "Current Source",
+        ports: &[("p", Electrical), ("n", Electrical)],
+        parameters: &[param("I", "A", 1.0, "Current from p to n through the source")],
+        equations: &["p.current = I", "p.current + n.current = 0"],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "elec.sine_voltage",
+        label: "Sine Voltage Source",
+        ports: &[("p", Electrical), ("n", Electrical)],
+        parameters: &[
+            param("V", "V", 1.0, "Amplitude"),
+            param("f", "Hz", 50.0, "Frequency"),
+            param("phase", "rad", 0.0, "Phase"),
+            param("offset", "V", 0.0, "Offset"),
+        ],
+        equations: &["p.voltage - n.voltage = offset + V * sin(2 * pi * f * t + phase)", "p.current + n.current = 0"],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "elec.diode",
+        label: "Diode",
+        ports: &[("p", Electrical), ("n", Electrical)],
+        parameters: &[
+            param("Is", "A", 1e-12, "Saturation current"),
+            param("N", "", 1.0, "Emission coefficient"),
+            param("Vt", "V", 0.025852, "Thermal voltage"),
+        ],
+        equations: &["p.current = Is * (exp((p.voltage - n.voltage) / (N * Vt)) - 1)", "p.current + n.current = 0"],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "elec.switch",
+        label: "Ideal Switch",
+        ports: &[("p", Electrical), ("n", Electrical)],
+        parameters: &[
+            param("Ron", "Ω", 1e-5, "Closed resistance"),
+            param("Goff", "S", 1e-5, "Open conductance"),
+            param("t_on", "s", 0.0, "Closing time"),
+            param("t_off", "s", 1e30, "Opening time"),
+        ],
+        equations: &[
+            "closed = (sign(t - t_on) - sign(t - t_off)) / 2",
+            "p.voltage - n.voltage = p.current * (closed * Ron + (1 - closed) / Goff)",
+            "p.current + n.current = 0",
+        ],
+        initial_values: &[],
+    },
+    // ── Translational ───────────────────────────────────────────────────────
+    ComponentSpec {
+        kind: "trans.fixed",
+        label: "Fixed (Translational)",
+        ports: &[("flange", Translational)],
+        parameters: &[],
+        equations: &["flange.velocity = 0"],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "trans.mass",
+        label: "Mass",
+        ports: &[("flange_a", Translational), ("flange_b", Translational)],
+        parameters: &[param("m", "kg", 1.0, "Mass"), param("v0", "m/s", 0.0, "Initial velocity")],
+        equations: &["m * der(flange_a.velocity) = flange_a.force + flange_b.force", "flange_b.velocity = flange_a.velocity"],
+        initial_values: &[("flange_a.velocity", "v0")],
+    },
+    ComponentSpec {
+        kind: "trans.spring",
+        label: "Spring",
+        ports: &[("flange_a", Translational), ("flange_b", Translational)],
+        parameters: &[param("c", "N/m", 1000.0, "Stiffness"), param("s_rel0", "m", 0.0, "Unstretched length")],
+        equations: &[
+            "der(s_rel) = flange_b.velocity - flange_a.velocity",
+            "flange_b.force = c * (s_rel - s_rel0)",
+            "flange_a.force + flange_b.force = 0",
+        ],
+        initial_values: &[("s_rel", "s_rel0")],
+    },
+    ComponentSpec {
+        kind: "trans.damper",
+        label: "Damper",
+        ports: &[("flange_a", Translational), ("flange_b", Translational)],
+        parameters: &[param("d", "N·s/m", 10.0, "Damping coefficient")],
+        equations: &["flange_b.force = d * (flange_b.velocity - flange_a.velocity)", "flange_a.force + flange_b.force = 0"],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "trans.friction",
+        label: "Friction (Translational)",
+        ports: &[("flange_a", Translational), ("flange_b", Translational)],
+        parameters: &[
+            param("F_c", "N", 10.0, "Coulomb friction force"),
+            param("d", "N·s/m", 0.0, "Viscous coefficient"),
+            param("v_s", "m/s", 1e-3, "Regularisation velocity"),
+        ],
+        equations: &[
+            "v_rel = flange_b.velocity - flange_a.velocity",
+            "flange_b.force = F_c * tanh(v_rel / v_s) + d * v_rel",
+            "flange_a.force + flange_b.force = 0",
+        ],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "trans.force",
+        label: "Force Source",
+        ports: &[("flange", Translational)],
+        parameters: &[param("F", "N", 1.0, "Force applied to the connected flange")],
+        equations: &["flange.force = -F"],
+        initial_values: &[],
+    },
+    // ── Rotational ──────────────────────────────────────────────────────────
+    ComponentSpec {
+        kind: "rot.fixed",
+        label: "Fixed (Rotational)",
+        ports: &[("flange", Rotational)],
+        parameters: &[],
+        equations: &["flange.angular_velocity = 0"],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "rot.inertia",
+        label: "Inertia",
+        ports: &[("flange_a", Rotational), ("flange_b", Rotational)],
+        parameters: &[param("J", "kg·m²", 1.0, "Moment of inertia"), param("w0", "rad/s", 0.0, "Initial angular velocity")],
+        equations: &[
+            "J * der(flange_a.angular_velocity) = flange_a.torque + flange_b.torque",
+            "flange_b.angular_velocity = flange_a.angular_velocity",
+        ],
+        initial_values: &[("flange_a.angular_velocity", "w0")],
+    },
+    ComponentSpec {
+        kind: "rot.spring",
+        label: "Torsion Spring",
+        ports: &[("flange_a", Rotational), ("flange_b", Rotational)],
+        parameters: &[param("c", "N·m/rad", 1000.0, "Torsional stiffness"), param("phi_rel0", "rad", 0.0, "Unstrained angle")],
+        equations: &[
+            "der(phi_rel) = flange_b.angular_velocity - flange_a.angular_velocity",
+            "flange_b.torque = c * (phi_rel - phi_rel0)",
+            "flange_a.torque + flange_b.torque = 0",
+        ],
+        initial_values: &[("phi_rel", "phi_rel0")],
+    },
+    ComponentSpec {
+        kind: "rot.damper",
+        label: "Rotational Damper",
+        ports: &[("flange_a", Rotational), ("flange_b", Rotational)],
+        parameters: &[param("d", "N·m·s/rad", 1.0, "Damping coefficient")],
+        equations: &[
+            "flange_b.torque = d * (flange_b.angular_velocity - flange_a.angular_velocity)",
+            "flange_a.torque + flange_b.torque = 0",
+        ],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "rot.gear",
+        label: "Ideal Gear",
+        ports: &[("flange_a", Rotational), ("flange_b", Rotational)],
+        parameters: &[param("ratio", "", 2.0, "Speed ratio ω_a / ω_b")],
+        equations: &[
+            "flange_a.angular_velocity = ratio * flange_b.angular_velocity",
+            "ratio * flange_a.torque + flange_b.torque = 0",
+        ],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "rot.clutch",
+        label: "Clutch",
+        ports: &[("flange_a", Rotational), ("flange_b", Rotational)],
+        parameters: &[
+            param("T_max", "N·m", 10.0, "Torque capacity when engaged"),
+            param("w_s", "rad/s", 1e-2, "Regularisation slip speed"),
+            param("t_engage", "s", 0.0, "Engagement time"),
+        ],
+        equations: &[
+            "engaged = (1 + sign(t - t_engage)) / 2",
+            "flange_a.torque = engaged * T_max * tanh((flange_a.angular_velocity - flange_b.angular_velocity) / w_s)",
+            "flange_a.torque + flange_b.torque = 0",
+        ],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "rot.friction",
+        label: "Bearing Friction",
+        ports: &[("flange_a", Rotational), ("flange_b", Rotational)],
+        parameters: &[
+            param("T_c", "N·m", 1.0, "Coulomb friction torque"),
+            param("d", "N·m·s/rad", 0.0, "Viscous coefficient"),
+            param("w_s", "rad/s", 1e-2, "Regularisation speed"),
+        ],
+        equations: &[
+            "w_rel = flange_b.angular_velocity - flange_a.angular_velocity",
+            "flange_b.torque = T_c * tanh(w_rel / w_s) + d * w_rel",
+            "flange_a.torque + flange_b.torque = 0",
+        ],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "rot.torque",
+        label: "Torque Source",
+        ports: &[("flange", Rotational)],
+        parameters: &[param("tau", "N·m", 1.0, "Torque applied to the connected flange")],
+        equations: &["flange.torque = -tau"],
+        initial_values: &[],
+    },
+    // ── Thermal ─────────────────────────────────────────────────────────────
+    ComponentSpec {
+        kind: "thermal.heat_capacitor",
+        label: "Heat Capacitor",
+        ports: &[("port", Thermal)],
+        parameters: &[param("C", "J/K", 1000.0, "Heat capacity"), param("T0", "K", 293.15, "Initial temperature")],
+        equations: &["C * der(port.temperature) = port.heat_flow"],
+        initial_values: &[("port.temperature", "T0")],
+    },
+    ComponentSpec {
+        kind: "thermal.conductor",
+        label: "Thermal Conductor",
+        ports: &[("port_a", Thermal), ("port_b", Thermal)],
+        parameters: &[param("G", "W/K", 1.0, "Thermal conductance")],
+        equations: &["port_a.heat_flow = G * (port_a.temperature - port_b.temperature)", "port_a.heat_flow + port_b.heat_flow = 0"],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "thermal.convection",
+        label: "Convection",
+        ports: &[("solid", Thermal), ("fluid", Thermal)],
+        parameters: &[param("h", "W/(m²·K)", 10.0, "Heat transfer coefficient"), param("A", "m²", 1.0, "Area")],
+        equations: &["solid.heat_flow = h * A * (solid.temperature - fluid.temperature)", "solid.heat_flow + fluid.heat_flow = 0"],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "thermal.radiation",
+        label: "Radiation",
+        ports: &[("port_a", Thermal), ("port_b", Thermal)],
+        parameters: &[param("epsilon", "", 0.9, "Emissivity"), param("A", "m²", 1.0, "Area")],
+        equations: &[
+            "port_a.heat_flow = 5.670374419e-8 * epsilon * A * (port_a.temperature^4 - port_b.temperature^4)",
+            "port_a.heat_flow + port_b.heat_flow = 0",
+        ],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "thermal.fixed_temperature",
+        label: "Fixed Temperature",
+        ports: &[("port", Thermal)],
+        parameters: &[param("T", "K", 293.15, "Temperature")],
+        equations: &["port.temperature = T"],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "thermal.heat_source",
+        label: "Heat Flow Source",
+        ports: &[("port", Thermal)],
+        parameters: &[param("Q", "W", 100.0, "Heat flow into the connected port")],
+        equations: &["port.heat_flow = -Q"],
+        initial_values: &[],
+    },
+    // ── Hydraulic ───────────────────────────────────────────────────────────
+    ComponentSpec {
+        kind: "hyd.pressure_source",
+        label: "Pressure Source",
+        ports: &[("port", Hydraulic)],
+        parameters: &[param("p", "Pa", 1e5, "Pressure")],
+        equations: &["port.pressure = p"],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "hyd.flow_source",
+        label: "Flow Source",
+        ports: &[("port", Hydraulic)],
+        parameters: &[param("Q", "m³/s", 1e-4, "Flow into the connected port")],
+        equations: &["port.volumetric_flow = -Q"],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "hyd.orifice",
+        label: "Orifice",
+        ports: &[("port_a", Hydraulic), ("port_b", Hydraulic)],
+        parameters: &[
+            param("Cd", "", 0.7, "Discharge coefficient"),
+            param("A", "m²", 1e-5, "Orifice area"),
+            param("rho", "kg/m³", 850.0, "Fluid density"),
+            param("dp_t", "Pa", 100.0, "Laminar transition pressure drop"),
+        ],
+        equations: &[
+            "dp = port_a.pressure - port_b.pressure",
+            "port_a.volumetric_flow = Cd * A * sqrt(2 / rho) * dp / (dp^2 + dp_t^2)^0.25",
+            "port_a.volumetric_flow + port_b.volumetric_flow = 0",
+        ],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "hyd.accumulator",
+        label: "Accumulator",
+        ports: &[("port", Hydraulic)],
+        parameters: &[param("C", "m³/Pa", 1e-9, "Hydraulic capacitance"), param("p0", "Pa", 1e5, "Initial pressure")],
+        equations: &["C * der(port.pressure) = port.volumetric_flow"],
+        initial_values: &[("port.pressure", "p0")],
+    },
+    ComponentSpec {
+        kind: "hyd.pump",
+        label: "Pump",
+        ports: &[("inlet", Hydraulic), ("outlet", Hydraulic), ("shaft", Rotational)],
+        parameters: &[param("D", "m³/rad", 1e-6, "Displacement")],
+        equations: &[
+            "inlet.volumetric_flow = D * shaft.angular_velocity",
+            "inlet.volumetric_flow + outlet.volumetric_flow = 0",
+            "shaft.torque = D * (outlet.pressure - inlet.pressure)",
+        ],
+        initial_values: &[],
+    },
+    ComponentSpec {
+        kind: "hyd.cylinder",
+        label: "Cylinder",
+        ports: &[("port", Hydraulic), ("rod", Translational)],
+        parameters: &[param("A", "m²", 1e-3, "Piston area")],
+        equations: &["port.volumetric_flow = A * rod.velocity", "rod.force = -A * port.pressure"],
+        initial_values: &[],
+    },
+];
+
+/// The component of the given kind (`"elec.resistor"`, …).
+pub fn find(kind: &str) -> Option<&'static ComponentSpec> {
+    COMPONENTS.iter().find(|c| c.kind == kind)
+}
+
+impl ComponentSpec {
+    /// Catalog block id: `acausal.<kind>`.
+    pub fn op_id(&self) -> String {
+        format!("acausal.{}", self.kind)
+    }
+
+    pub fn parameter(&self, name: &str) -> Option<&ParameterSpec> {
+        self.parameters.iter().find(|p| p.name == name)
+    }
+
+    /// Port variables of an instance named `id`, with their units.
+    pub fn port_variables(&self, id: &str) -> Vec<(String, &'static str)> {
+        self.ports
+            .iter()
+            .flat_map(|(name, domain)| {
+                let port = Port::new(id, name, domain.clone());
+                let (through, across) = domain.units();
+                [(port.across_var, across), (port.through_var, through)]
+            })
+            .collect()
+    }
+
+    /// An instance named `id`, with `values` overriding parameter defaults.
+    pub fn build(&self, id: &str, values: &HashMap<String, f64>) -> Result<AcausalBlock, String> {
+        if let Some(unknown) = values.keys().find(|k| self.parameter(k).is_none()) {
+            let known: Vec<&str> = self.parameters.iter().map(|p| p.name).collect();
+            return Err(format!("{id}: {} has no parameter '{unknown}' (has: {})", self.label, known.join(", ")));
+        }
+        let parameters: HashMap<String, f64> = self
+            .parameters
+            .iter()
+            .map(|p| (p.name.to_string(), values.get(p.name).copied().unwrap_or(p.default)))
+            .collect();
+        let equations = self
+            .equations
+            .iter()
+            .map(|e| parse_equation(e))
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(AcausalBlock {
+            id: id.to_string(),
+            block_type: self.kind.to_string(),
+            ports: self.ports.iter().map(|(name, domain)| Port::new(id, name, domain.clone())).collect(),
+            equations,
+            initial_values: self.initial_values.iter().map(|(var, p)| (var.to_string(), parameters[*p])).collect(),
+            parameters,
+        })
+    }
+}
+
+/// Build a library component by kind.
+pub fn component(kind: &str, id: &str, values: &HashMap<String, f64>) -> Result<AcausalBlock, String> {
+    find(kind).ok_or_else(|| format!("{id}: unknown component type '{kind}'"))?.build(id, values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acausal::{simulate, AcausalNetwork};
+    use crate::ode::{OdeSolverConfig, OutputTimes};
+
+    fn part(kind: &str, id: &str, values: &[(&str, f64)]) -> AcausalBlock {
+        component(kind, id, &values.iter().map(|(k, v)| (k.to_string(), *v)).collect()).unwrap()
+    }
+
+    fn run(net: &AcausalNetwork, t_end: f64) -> crate::acausal::AcausalResult {
+        let cfg = OdeSolverConfig { t_end, dt: t_end / 1000.0, tolerance: 1e-8, output: OutputTimes::Interval(t_end / 20.0), ..Default::default() };
+        simulate(net, &cfg).unwrap()
+    }
+
+    #[test]
+    fn specs_are_well_formed() {
+        let mut kinds: Vec<&str> = COMPONENTS.iter().map(|c| c.kind).collect();
+        kinds.sort();
+        kinds.dedup();
+        assert_eq!(kinds.len(), COMPONENTS.len());
+        for spec in COMPONENTS {
+            let block = spec.build("X", &HashMap::new()).unwrap();
+            assert_eq!(block.equations.len(), spec.equations.len());
+            for (var, p) in spec.initial_values {
+                assert!(spec.parameter(p).is_some(), "{}: {var} starts from unknown parameter {p}", spec.kind);
+            }
+            // Each component alone is a valid network (open ports take zero flow).
+            AcausalNetwork { blocks: vec![block], connections: Vec::new() }.flatten().unwrap();
+        }
+        assert!(component("elec.resistor", "R1", &[("X".to_string(), 1.0)].into()).is_err());
+        assert_eq!(find("hyd.pump").unwrap().port_variables("P1")[4], ("P1.shaft.angular_velocity".to_string(), "rad/s"));
+    }
+
+    #[test]
+    fn rlc_series_circuit_rings_down() {
+        let (r, l, c) = (10.0, 1e-2, 1e-4);
+        let mut net = AcausalNetwork::new();
+        net.add(part("elec.voltage_source", "V1", &[("V", 1.0)]))
+            .add(part("elec.resistor", "R1", &[("R", r)]))
+            .add(part("elec.inductor", "L1", &[("L", l)]))
+            .add(part("elec.capacitor", "C1", &[("C", c)]))
+            .add(part("elec.ground", "G", &[]))
+            .connect("V1.p", "R1.p")
+            .connect("R1.n", "L1.p")
+            .connect("L1.n", "C1.p")
+            .connect("C1.n", "V1.n")
+            .connect("V1.n", "G.p");
+        let result = run(&net, 0.02);
+        // Underdamped step response: v_C = 1 - e^{-αt}(cos ω_d t + α/ω_d sin ω_d t).
+        let alpha = r / (2.0 * l);
+        let wd = (1.0 / (l * c) - alpha * alpha).sqrt();
+        let v = result.series("C1.v").unwrap();
+        for (t, v) in result.t.iter().zip(&v) {
+            let exact = 1.0 - (-alpha * t).exp() * ((wd * t).cos() + alpha / wd * (wd * t).sin());
+            assert!((v - exact).abs() < 1e-4, "t = {t}: {v} vs {exact}");
+        }
+    }
+
+    #[test]
+    fn mass_spring_damper_settles_under_a_force() {
+        let (m, k, d, f) = (2.0, 200.0, 8.0, 10.0);
+        let mut net = AcausalNetwork::new();
+        net.add(part("trans.fixed", "wall", &[]))
+            .add(part("trans.spring", "k", &[("c", k)]))
+            .add(part("trans.damper", "d", &[("d", d)]))
+            .add(part("trans.mass", "m", &[("m", m)]))
+            .add(part("trans.force", "F", &[("F", f)]))
+            .connect("wall.flange", "k.flange_a")
+            .connect("wall.flange", "d.flange_a")
+            .connect("k.flange_b", "m.flange_a")
+            .connect("d.flange_b", "m.flange_a")
+            .connect("m.flange_b", "F.flange");
+        let result = run(&net, 10.0);
+        let x = result.series("k.s_rel").unwrap();
+        assert!((x.last().unwrap() - f / k).abs() < 1e-5, "{:?}", x.last());
+        assert!(result.series("m.flange_a.velocity").unwrap().last().unwrap().abs() < 1e-4);
+    }
+
+    #[test]
+    fn gear_and_clutch_transmit_torque() {
+        // A torque drives an inertia through a 3:1 gear; a clutch engaged at
+        // t = 0.5 s couples a second inertia, which then spins up.
+        let mut net = AcausalNetwork::new();
+        net.add(part("rot.torque", "drive", &[("tau", 1.0)]))
+            .add(part("rot.inertia", "J1", &[("J", 0.1)]))
+            .add(part("rot.gear", "gear", &[("ratio", 3.0)]))
+            .add(part("rot.clutch", "clutch", &[("T_max", 5.0), ("t_engage", 0.5)]))
+            .add(part("rot.inertia", "J2", &[("J", 0.2)]))
+            .connect("drive.flange", "J1.flange_a")
+            .connect("J1.flange_b", "gear.flange_a")
+            .connect("gear.flange_b", "clutch.flange_a")
+            .connect("clutch.flange_b", "J2.flange_a");
+        let result = run(&net, 1.0);
+        let w1 = result.series("J1.flange_a.angular_velocity").unwrap();
+        let w_out = result.series("gear.flange_b.angular_velocity").unwrap();
+        let w2 = result.series("J2.flange_a.angular_velocity").unwrap();
+        for k in 0..result.t.len() {
+            assert!((w1[k] - 3.0 * w_out[k]).abs() < 1e-8);
+        }
+        // Before engagement only J1 accelerates, at τ/J.
+        let k_half = result.t.iter().position(|&t| t >= 0.45).unwrap();
+        assert!((w1[k_half] - 10.0 * result.t[k_half]).abs() < 1e-5);
+        assert!(w2[k_half].abs() < 1e-12);
+        assert!(*w2.last().unwrap() > 0.1);
+    }
+
+    #[test]
+    fn heat_capacitor_cools_through_convection() {
+        let (c, h, a, t0, t_amb) = (500.0, 25.0, 0.2, 373.15, 293.15);
+        let mut net = AcausalNetwork::new();
+        net.add(part("thermal.heat_capacitor", "body", &[("C", c), ("T0", t0)]))
+            .add(part("thermal.convection", "conv", &[("h", h), ("A", a)]))
+            .add(part("thermal.fixed_temperature", "air", &[("T", t_amb)]))
+            .connect("body.port", "conv.solid")
+            .connect("conv.fluid", "air.port");
+        let result = run(&net, 200.0);
+        let temp = result.series("body.port.temperature").unwrap();
+        for (t, temp) in result.t.iter().zip(&temp) {
+            let exact = t_amb + (t0 - t_amb) * (-h * a / c * t).exp();
+            assert!((temp - exact).abs() < 1e-4, "t = {t}: {temp} vs {exact}");
+        }
+    }
+
+    #[test]
+    fn pump_fills_an_accumulator_through_an_orifice() {
+        let (d, w, cap) = (1e-6, 100.0, 1e-10);
+        // The shaft is driven at a fixed speed.
+        let motor = AcausalBlock {
+            id: "motor".into(),
+            block_type: "Speed".into(),
+            ports: vec![Port::new("motor", "flange", PhysicalDomain::Rotational)],
+            equations: vec![parse_equation(&format!("flange.angular_velocity = {w}")).unwrap()],
+            parameters: HashMap::new(),
+            initial_values: HashMap::new(),
+        };
+        let mut net = AcausalNetwork::new();
+        net.add(part("hyd.pressure_source", "tank", &[("p", 1e5)]))
+            .add(part("hyd.pump", "pump", &[("D", d)]))
+            .add(motor)
+            .add(part("hyd.orifice", "valve", &[]))
+            .add(part("hyd.accumulator", "acc", &[("C", cap), ("p0", 1e5)]))
+            .connect("tank.port", "pump.inlet")
+            .connect("motor.flange", "pump.shaft")
+            .connect("pump.outlet", "valve.port_a")
+            .connect("valve.port_b", "acc.port");
+        let result = run(&net, 0.01);
+        let p = result.series("acc.port.pressure").unwrap();
+        // Displacement flow Q = D·ω charges the accumulator linearly.
+        let q = result.series("valve.port_a.volumetric_flow").unwrap();
+        assert!(q.iter().all(|q| (q - d * w).abs() < 1e-12));
+        let expected = 1e5 + d * w / cap * 0.01;
+        assert!((p.last().unwrap() - expected).abs() < 1e-3 * expected);
+        let torque = result.series("pump.shaft.torque").unwrap();
+        assert!(*torque.last().unwrap() > 0.0);
+    }
+}
@@ -24,12 +24,37 @@
 //! - **Pantelides algorithm** (`pantelides_index_reduction`) for symbolic
 //!   structural index reduction of the DAE.
 //!
+//! - **Simulation pipeline** from connected components to a solved time
+//!   response:
+//!   - [`network`] — [`AcausalNetwork`] graphs and their flattening into one
+//!     residual system
+//!   - [`library`] — standard electrical, mechanical, thermal and hydraulic
+//!     components
+//!   - [`circuit`] — modified nodal analysis with DC, AC and transient
+//!     analyses of electrical networks
+//!   - [`index`] — Pantelides index reduction with dummy derivatives
+//!   - [`blt`] — block-lower-triangular sorting and tearing of the algebraic
+//!     equations
+//!   - [`simulate`] — consistent initialization and Radau IIA integration,
+//!     reporting every across and through variable
+//!
 //! # References
 //! - Fritzson, P. (2014). *Principles of Object-Oriented Modeling and Simulation with Modelica 3.3*
 //! - Cellier, F.E. & Kofman, E. (2006). *Continuous System Simulation*
 //! - Pantelides, C.C. (1988). "The consistent initialization of differential-algebraic systems"
 
+pub mod blt;
+pub mod circuit;
+pub mod index;
+pub mod library;
+pub mod network;
+pub mod simulate;
+
+pub use network::AcausalNetwork;
+pub use simulate::{simulate, AcausalModel, AcausalResult};
+
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 // ---------------------------------------------------------------------------
 // Physical domains
@@ -92,6 +117,20 @@ impl PhysicalDomain {
             PhysicalDomain::Custom { through_unit, across_unit, .. } => (through_unit, across_unit),
         }
     }
+
+    /// Parse a built-in domain name (`"electrical"`, `"rotational"`, …).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "electrical" => Some(PhysicalDomain::Electrical),
+            "translational" | "mechanical" => Some(PhysicalDomain::Translational),
+            "rotational" => Some(PhysicalDomain::Rotational),
+            "thermal" => Some(PhysicalDomain::Thermal),
+            "hydraulic" => Some(PhysicalDomain::Hydraulic),
+            "pneumatic" => Some(PhysicalDomain::Pneumatic),
+            "magnetic" => Some(PhysicalDomain::Magnetic),
+            _ => None,
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -102,7 +141,8 @@ impl PhysicalDomain {
 ///
 /// Each port belongs to a domain and holds a pair of variables:
 /// the through-variable (flowing through the component) and
-/// the across-variable (potential at the port).
+/// the across-variable (potential at the port). Through-variables are
+/// positive when flowing into the block at that port.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Port {
     /// Unique identifier: `"<block_id>.<port_name>"`
@@ -176,7 +216,26 @@ pub enum BlockEquation {
     },
 }
 
+impl fmt::Display for BlockEquation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockEquation::Explicit { lhs, rhs } => write!(f, "{lhs} = {rhs}"),
+            BlockEquation::Implicit { residual } => write!(f, "{residual} = 0"),
+            BlockEquation::Differential { state_var, rhs } => write!(f, "der({state_var}) = {rhs}"),
+        }
+    }
+}
+
 impl BlockEquation {
+    /// The equation as a residual expression that vanishes when it holds.
+    pub fn residual(&self) -> String {
+        match self {
+            BlockEquation::Explicit { lhs, rhs } => format!("{lhs} - ({rhs})"),
+            BlockEquation::Implicit { residual } => residual.clone(),
+            BlockEquation::Differential { state_var, rhs } => format!("der({state_var}) - ({rhs})"),
+        }
+    }
+
     /// Returns all variable names referenced in this equation.
     pub fn variables_referenced(&self) -> Vec<String> {
         let text = match self {
@@ -613,21 +672,25 @@ pub fn pantelides_index_reduction(
 // Modified Nodal Analysis (MNA)
 // ---------------------------------------------------------------------------
 
-/// Modified Nodal Analysis for multi-domain physical networks.
+/// Modified Nodal Analysis matrices of an electrical network.
 ///
-/// Generates the system Ax + Bẋ = f where:
-/// - A = conductance/admittance matrix
-/// - B = capacitance/mass/inductance matrix
-/// - x = across-variable vector (voltages, velocities, temperatures, pressures)
-/// - f = source vector
+/// The system is `G·x + C·ẋ = b` where:
+/// - x = node voltages, then the currents of group-2 branches (voltage
+///   sources, capacitors, inductors)
+/// - G = conductance and incidence stamps
+/// - C = capacitance/inductance stamps
+/// - b = source vector
 ///
-/// Supported domains: Electrical, Translational, Thermal, Hydraulic.
+/// See [`circuit`] for the DC, AC and transient analyses built on it.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct MnaSystem {
-    /// Across-variable names (rows/columns of matrices)
+    /// Node names (the first `n_nodes` unknowns)
     pub nodes: Vec<String>,
     /// Number of nodes
     pub n_nodes: usize,
+    /// Components whose branch current is an unknown, after the nodes
+    #[serde(default)]
+    pub branches: Vec<String>,
     /// Conductance matrix G (sparse, as list of (row, col, value))
     pub conductance: Vec<(usize, usize, f64)>,
     /// Capacitance/mass matrix C
@@ -640,34 +703,22 @@ pub struct MnaSystem {
 
 /// Build an MNA system from acausal blocks and connections for a single domain.
 ///
-/// This function extracts linear constitutive relations and populates
-/// the G and C matrices. Non-linear components return an error.
+/// Only the electrical domain is supported, with blocks from the
+/// [`library`]. Diodes and switches have no constant stamp and return an
+/// error; [`circuit::Circuit`] handles them in its analyses.
 pub fn build_mna(
     blocks: &[AcausalBlock],
     connections: &[PhysicalConnection],
     domain: &PhysicalDomain,
 ) -> Result<MnaSystem, String> {
-    let dae = build_dae(blocks, connections);
-
-    // Extract nodes for this domain
-    let domain_nodes: Vec<String> = dae.variables.iter()
-        .filter(|v| {
-            let (_, av) = domain.variable_names();
-            v.ends_with(&format!(".{}", av))
-        })
-        .cloned()
-        .collect();
-
-    let n = domain_nodes.len();
-
-    Ok(MnaSystem {
-        n_nodes: n,
-        nodes: domain_nodes,
-        conductance: vec![],   // Populated by linear component extraction
-        capacitance: vec![],   // Populated by energy-storage component extraction
-        source: vec![0.0; n],
-        across_sources: vec![],
-    })
+    if *domain != PhysicalDomain::Electrical {
+        return Err(format!("MNA stamps are defined for the electrical domain, not {domain:?}"));
+    }
+    if let Some(b) = blocks.iter().find(|b| matches!(b.block_type.as_str(), "elec.diode" | "elec.switch")) {
+        return Err(format!("{}: {} has no linear stamp", b.id, b.block_type));
+    }
+    let network = AcausalNetwork { blocks: blocks.to_vec(), connections: connections.to_vec() };
+    Ok(circuit::Circuit::from_network(&network)?.mna())
 }
 
 // ---------------------------------------------------------------------------
@@ -718,6 +769,9 @@ impl MultiDomainBlock {
     /// - `k`: motor constant \[V·s/rad = N·m/A\]
     /// - `J`: rotor inertia \[kg·m²\]
     /// - `b`: friction coefficient \[N·m·s/rad\]
+    ///
+    /// The heat port may be left unconnected; it then only reports the
+    /// copper loss (see [`AcausalNetwork::flatten`]).
     pub fn dc_motor(id: &str, r: f64, l: f64, k: f64, j: f64, b: f64) -> Self {
         let p_elec = Port::new(id, "p_elec", PhysicalDomain::Electrical);
         let n_elec = Port::new(id, "n_elec", PhysicalDomain::Electrical);
@@ -731,22 +785,26 @@ impl MultiDomainBlock {
             BlockEquation::Differential {
                 state_var: format!("{}.p_elec.current", block_id),
                 rhs: format!(
-                    "({}.p_elec.voltage - {}.n_elec.voltage - {} * {}.shaft.angular_velocity) / {}",
-                    block_id, block_id, k, block_id, l
+                    "({}.p_elec.voltage - {}.n_elec.voltage - {} * {}.p_elec.current - {} * {}.shaft.angular_velocity) / {}",
+                    block_id, block_id, r, block_id, k, block_id, l
                 ),
             },
-            // Rotational: J*dω/dt = k*i - b*ω - τ_load
+            // The armature current leaves through the negative terminal
+            BlockEquation::Implicit {
+                residual: format!("{}.p_elec.current + {}.n_elec.current", block_id, block_id),
+            },
+            // Rotational: J*dω/dt = k*i - b*ω + τ, τ the torque applied to the shaft
             BlockEquation::Differential {
                 state_var: format!("{}.shaft.angular_velocity", block_id),
                 rhs: format!(
-                    "({} * {}.p_elec.current - {} * {}.shaft.angular_velocity - {}.shaft.torque) / {}",
+                    "({} * {}.p_elec.current - {} * {}.shaft.angular_velocity + {}.shaft.torque) / {}",
                     k, block_id, b, block_id, block_id, j
                 ),
             },
-            // Thermal: heat = R * i²
+            // Thermal: the copper loss R * i² leaves through the heat port
             BlockEquation::Explicit {
                 lhs: format!("{}.heat_port.heat_flow", block_id),
-                rhs: format!("{} * {}.p_elec.current * {}.p_elec.current",
+                rhs: format!("-{} * {}.p_elec.current * {}.p_elec.current",
                     r, block_id, block_id),
             },
         ];
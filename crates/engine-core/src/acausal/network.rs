@@ -0,0 +1,570 @@
+//! Acausal networks: components, the wires between their ports, and the
+//! flattening of both into one residual equation system.
+//!
+//! Connecting ports merges them into a node. A node of `k` ports contributes
+//! `k - 1` across-variable equalities and one balance of through-variables
+//! (Kirchhoff's current law and its analogues). Block equations are
+//! rewritten into the network's name space: parameters become constants,
+//! relative names such as `p.voltage` are resolved against the owning
+//! block, and any other name becomes an internal variable of that block.
+//!
+//! Unconnected ports follow the Modelica convention (zero through-variable)
+//! when the block reads their across-variable. A port whose across-variable
+//! the block never reads is an output-only port, such as the heat port of
+//! [`MultiDomainBlock::dc_motor`](super::MultiDomainBlock::dc_motor): left
+//! open, it only reports its through-variable.
+
+use super::{AcausalBlock, BlockEquation, MultiDomainBlock, PhysicalConnection, PhysicalDomain, Port};
+use crate::symbolic::{parse_expr, Expr};
+use std::collections::{HashMap, HashSet};
+
+/// Components and the connections between their ports.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct AcausalNetwork {
+    pub blocks: Vec<AcausalBlock>,
+    pub connections: Vec<PhysicalConnection>,
+}
+
+/// What a flattened variable represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum VariableKind {
+    Across,
+    Through,
+    /// A block variable that is not on a port (e.g. a spring's deflection).
+    Internal,
+}
+
+/// One unknown of the flattened system.
+#[derive(Debug, Clone)]
+pub struct FlatVariable {
+    pub name: String,
+    pub kind: VariableKind,
+    /// SI unit from the port's [`PhysicalDomain::units`]; empty for internals.
+    pub unit: String,
+    /// Initial value from the owning block's `initial_values`.
+    pub start: Option<f64>,
+}
+
+/// One residual equation `residual = 0` of the flattened system.
+///
+/// Variables appear as [`symbol`]s: `v{j}` for variable `j`, `v{j}_d{k}` for
+/// its `k`-th time derivative, and `t` for time.
+#[derive(Debug, Clone)]
+pub struct FlatEquation {
+    pub residual: Expr,
+    /// Human-readable source of the equation, for diagnostics.
+    pub origin: String,
+}
+
+/// A network flattened into variables and residual equations.
+#[derive(Debug, Clone)]
+pub struct FlatSystem {
+    pub variables: Vec<FlatVariable>,
+    pub equations: Vec<FlatEquation>,
+}
+
+/// Symbol of the `order`-th time derivative of flat variable `var`.
+pub fn symbol(var: usize, order: usize) -> String {
+    if order == 0 {
+        format!("v{var}")
+    } else {
+        format!("v{var}_d{order}")
+    }
+}
+
+/// Inverse of [`symbol`]: `(var, order)`, or `None` for other names (e.g. `t`).
+pub fn parse_symbol(name: &str) -> Option<(usize, usize)> {
+    let rest = name.strip_prefix('v')?;
+    match rest.split_once("_d") {
+        Some((var, order)) => Some((var.parse().ok()?, order.parse().ok()?)),
+        None => Some((rest.parse().ok()?, 0)),
+    }
+}
+
+impl AcausalNetwork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a component.
+    pub fn add(&mut self, block: AcausalBlock) -> &mut Self {
+        self.blocks.push(block);
+        self
+    }
+
+    /// Connect two ports by id (`"<block_id>.<port_name>"`).
+    pub fn connect(&mut self, port_a: &str, port_b: &str) -> &mut Self {
+        self.connections.push(PhysicalConnection { port_a: port_a.to_string(), port_b: port_b.to_string() });
+        self
+    }
+
+    /// Every port, in block order.
+    pub fn ports(&self) -> impl Iterator<Item = &Port> {
+        self.blocks.iter().flat_map(|b| &b.ports)
+    }
+
+    /// Ports grouped into nodes by the connections, in port order. An
+    /// unconnected port is a node of its own.
+    pub fn nodes(&self) -> Result<Vec<Vec<&Port>>, String> {
+        let ports: Vec<&Port> = self.ports().collect();
+        let mut index: HashMap<&str, usize> = HashMap::new();
+        for (i, port) in ports.iter().enumerate() {
+            if index.insert(port.id.as_str(), i).is_some() {
+                return Err(format!("duplicate port '{}'", port.id));
+            }
+        }
+        let mut parent: Vec<usize> = (0..ports.len()).collect();
+        fn find(parent: &mut [usize], mut x: usize) -> usize {
+            while parent[x] != x {
+                parent[x] = parent[parent[x]];
+                x = parent[x];
+            }
+            x
+        }
+        for conn in &self.connections {
+            let lookup = |id: &str| {
+                index
+                    .get(id)
+                    .copied()
+                    .ok_or_else(|| format!("connection {} – {}: unknown port '{id}'", conn.port_a, conn.port_b))
+            };
+            let (a, b) = (lookup(&conn.port_a)?, lookup(&conn.port_b)?);
+            if ports[a].domain != ports[b].domain {
+                return Err(format!(
+                    "connection {} – {}: domain mismatch ({:?} vs {:?})",
+                    conn.port_a, conn.port_b, ports[a].domain, ports[b].domain
+                ));
+            }
+            let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+            // Keep the lower index as root so nodes come out in port order.
+            parent[ra.max(rb)] = ra.min(rb);
+        }
+        let mut nodes: Vec<Vec<&Port>> = Vec::new();
+        let mut slot: HashMap<usize, usize> = HashMap::new();
+        for (i, port) in ports.iter().enumerate() {
+            let root = find(&mut parent, i);
+            let k = *slot.entry(root).or_insert_with(|| {
+                nodes.push(Vec::new());
+                nodes.len() - 1
+            });
+            nodes[k].push(*port);
+        }
+        Ok(nodes)
+    }
+
+    /// Flatten into one residual system over every port variable and block
+    /// internal, with node equations first and block equations after.
+    pub fn flatten(&self) -> Result<FlatSystem, String> {
+        let nodes = self.nodes()?;
+        let mut table = VarTable::default();
+        for port in self.ports() {
+            let (through_unit, across_unit) = port.domain.units();
+            table.add(&port.across_var, VariableKind::Across, across_unit);
+            table.add(&port.through_var, VariableKind::Through, through_unit);
+        }
+
+        let mut block_eqs: Vec<(Vec<Piece>, String)> = Vec::new();
+        for block in &self.blocks {
+            for eq in &block.equations {
+                let pieces = rewrite(&eq.residual(), block, &mut table).map_err(|e| format!("{}: {e}", block.id))?;
+                block_eqs.push((pieces, format!("{}: {eq}", block.id)));
+            }
+        }
+        for block in &self.blocks {
+            for (name, value) in &block.initial_values {
+                if block.parameters.contains_key(name) {
+                    continue;
+                }
+                let j = table.resolve(block, name);
+                table.vars[j].start = Some(*value);
+            }
+        }
+        let referenced: HashSet<usize> = block_eqs
+            .iter()
+            .flat_map(|(pieces, _)| pieces.iter())
+            .filter_map(|p| match p {
+                Piece::Var(j, _) => Some(*j),
+                Piece::Text(_) => None,
+            })
+            .collect();
+
+        let mut node_eqs: Vec<(Vec<Piece>, String)> = Vec::new();
+        let mut dropped: HashSet<usize> = HashSet::new();
+        for node in &nodes {
+            let across: Vec<usize> = node.iter().map(|p| table.index[&p.across_var]).collect();
+            let through: Vec<usize> = node.iter().map(|p| table.index[&p.through_var]).collect();
+            if node.len() == 1 {
+                if referenced.contains(&across[0]) {
+                    node_eqs.push((vec![Piece::Var(through[0], 0)], format!("open port {}: {} = 0", node[0].id, node[0].through_var)));
+                } else {
+                    dropped.insert(across[0]);
+                    if !referenced.contains(&through[0]) {
+                        dropped.insert(through[0]);
+                    }
+                }
+                continue;
+            }
+            for (k, p) in node.iter().enumerate().skip(1) {
+                node_eqs.push((
+                    vec![Piece::Var(across[0], 0), Piece::Text(" - ".into()), Piece::Var(across[k], 0)],
+                    format!("node {}: {} = {}", node[0].id, node[0].across_var, p.across_var),
+                ));
+            }
+            let mut balance = Vec::new();
+            for (k, &j) in through.iter().enumerate() {
+                if k > 0 {
+                    balance.push(Piece::Text(" + ".into()));
+                }
+                balance.push(Piece::Var(j, 0));
+            }
+            let names: Vec<&str> = node.iter().map(|p| p.through_var.as_str()).collect();
+            node_eqs.push((balance, format!("node {}: {} = 0", node[0].id, names.join(" + "))));
+        }
+
+        // Renumber without the dropped variables.
+        let mut renumber = vec![usize::MAX; table.vars.len()];
+        let mut variables = Vec::new();
+        for (j, var) in table.vars.into_iter().enumerate() {
+            if !dropped.contains(&j) {
+                renumber[j] = variables.len();
+                variables.push(var);
+            }
+        }
+        let equations = node_eqs
+            .into_iter()
+            .chain(block_eqs)
+            .map(|(pieces, origin)| {
+                let text: String = pieces
+                    .iter()
+                    .map(|p| match p {
+                        Piece::Text(s) => s.clone(),
+                        Piece::Var(j, k) => symbol(renumber[*j], *k),
+                    })
+                    .collect();
+                let residual = parse_expr(&text).map_err(|e| format!("{origin}: {e}"))?;
+                Ok(FlatEquation { residual, origin })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(FlatSystem { variables, equations })
+    }
+
+    /// Read a network from JSON:
+    ///
+    /// ```json
+    /// { "components": [
+    ///     { "id": "M1", "type": "dc_motor", "R": 1.0, "L": 0.5, "k": 0.05, "J": 0.01, "b": 0.001 },
+    ///     { "id": "load", "ports": [{ "name": "flange", "domain": "rotational" }],
+    ///       "parameters": { "J": 0.1 },
+    ///       "equations": ["J * der(flange.angular_velocity) = flange.torque"],
+    ///       "initial_values": { "flange.angular_velocity": 0 } }
+    ///   ],
+    ///   "connections": [["M1.shaft", "load.flange"]] }
+    /// ```
+    ///
+    /// Equations use [`parse_equation`](super::parse_equation) syntax and may
+    /// name ports relative to their block. Any [`library`](super::library)
+    /// kind is also a valid `type`, e.g.
+    /// `{ "id": "R1", "type": "elec.resistor", "R": 100 }`.
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, String> {
+        let mut network = AcausalNetwork::new();
+        let components = value
+            .get("components")
+            .and_then(|c| c.as_array())
+            .ok_or("'components' must be an array")?;
+        for component in components {
+            network.add(block_from_json(component)?);
+        }
+        if let Some(connections) = value.get("connections").and_then(|c| c.as_array()) {
+            for conn in connections {
+                let ends = match conn {
+                    serde_json::Value::Array(pair) => (pair.first(), pair.get(1)),
+                    _ => (conn.get("from"), conn.get("to")),
+                };
+                match (ends.0.and_then(|v| v.as_str()), ends.1.and_then(|v| v.as_str())) {
+                    (Some(a), Some(b)) => network.connect(a, b),
+                    _ => return Err("each connection must be [port_a, port_b] or { from, to }".to_string()),
+                };
+            }
+        }
+        Ok(network)
+    }
+}
+
+/// One component from its JSON description (see [`AcausalNetwork::from_json`]).
+fn block_from_json(value: &serde_json::Value) -> Result<AcausalBlock, String> {
+    let id = value.get("id").and_then(|v| v.as_str()).ok_or("each component needs an 'id'")?;
+    let number = |key: &str, default: f64| value.get(key).and_then(|v| v.as_f64()).unwrap_or(default);
+    let numbers = |key: &str| -> HashMap<String, f64> {
+        value
+            .get(key)
+            .and_then(|v| v.as_object())
+            .map(|obj| obj.iter().filter_map(|(k, v)| Some((k.clone(), v.as_f64()?))).collect())
+            .unwrap_or_default()
+    };
+    let kind = value.get("type").and_then(|v| v.as_str()).unwrap_or("custom");
+    let mut block = match kind {
+        "dc_motor" => MultiDomainBlock::dc_motor(
+            id,
+            number("R", 1.0),
+            number("L", 0.5),
+            number("k", 0.01),
+            number("J", 0.01),
+            number("b", 0.1),
+        )
+        .to_acausal_block(),
+        "custom" => {
+            let ports = value
+                .get("ports")
+                .and_then(|v| v.as_array())
+                .map(|ports| {
+                    ports
+                        .iter()
+                        .map(|p| {
+                            let name = p.get("name").and_then(|v| v.as_str()).ok_or(format!("{id}: each port needs a 'name'"))?;
+                            let domain = p.get("domain").and_then(|v| v.as_str()).unwrap_or("electrical");
+                            let domain =
+                                PhysicalDomain::from_name(domain).ok_or(format!("{id}.{name}: unknown domain '{domain}'"))?;
+                            Ok(Port::new(id, name, domain))
+                        })
+                        .collect::<Result<Vec<_>, String>>()
+                })
+                .transpose()?
+                .unwrap_or_default();
+            let equations = value
+                .get("equations")
+                .and_then(|v| v.as_array())
+                .map(|eqs| {
+                    eqs.iter()
+                        .filter_map(|e| e.as_str())
+                        .map(|e| super::parse_equation(e).map_err(|err| format!("{id}: {err}")))
+                        .collect::<Result<Vec<BlockEquation>, String>>()
+                })
+                .transpose()?
+                .unwrap_or_default();
+            AcausalBlock {
+                id: id.to_string(),
+                block_type: "Custom".to_string(),
+                ports,
+                equations,
+                parameters: numbers("parameters"),
+                initial_values: HashMap::new(),
+            }
+        }
+        other => {
+            let spec = super::library::find(other).ok_or(format!("{id}: unknown component type '{other}'"))?;
+            // Parameters may sit at the top level or in a "parameters" object.
+            let mut values: HashMap<String, f64> = spec
+                .parameters
+                .iter()
+                .filter_map(|p| Some((p.name.to_string(), value.get(p.name)?.as_f64()?)))
+                .collect();
+            values.extend(numbers("parameters"));
+            spec.build(id, &values)?
+        }
+    };
+    block.initial_values.extend(numbers("initial_values"));
+    Ok(block)
+}
+
+/// A residual being rewritten: verbatim text or a reference to variable `j`'s
+/// `k`-th derivative, rendered once the final numbering is known.
+enum Piece {
+    Text(String),
+    Var(usize, usize),
+}
+
+#[derive(Default)]
+struct VarTable {
+    vars: Vec<FlatVariable>,
+    index: HashMap<String, usize>,
+}
+
+impl VarTable {
+    fn add(&mut self, name: &str, kind: VariableKind, unit: &str) -> usize {
+        let j = self.vars.len();
+        self.vars.push(FlatVariable { name: name.to_string(), kind, unit: unit.to_string(), start: None });
+        self.index.insert(name.to_string(), j);
+        j
+    }
+
+    /// Variable `name` as seen from `block`: an absolute name, one relative
+    /// to the block, or a new internal variable of the block.
+    fn resolve(&mut self, block: &AcausalBlock, name: &str) -> usize {
+        if let Some(&j) = self.index.get(name) {
+            return j;
+        }
+        let scoped = format!("{}.{name}", block.id);
+        if let Some(&j) = self.index.get(&scoped) {
+            return j;
+        }
+        let full = if name.starts_with(&format!("{}.", block.id)) { name.to_string() } else { scoped };
+        self.add(&full, VariableKind::Internal, "")
+    }
+}
+
+/// Rewrite a block residual into network names: parameters become numbers,
+/// variables and `der(x)` become [`Piece::Var`]s.
+fn rewrite(text: &str, block: &AcausalBlock, table: &mut VarTable) -> Result<Vec<Piece>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let n = chars.len();
+    let mut out = Vec::new();
+    let mut literal = String::new();
+    let mut i = 0;
+    while i < n {
+        let c = chars[i];
+        if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit())) {
+            let start = i;
+            while i < n && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            if i < n && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut k = i + 1;
+                if k < n && (chars[k] == '+' || chars[k] == '-') {
+                    k += 1;
+                }
+                if k < n && chars[k].is_ascii_digit() {
+                    i = k;
+                    while i < n && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            literal.extend(&chars[start..i]);
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            let continues = |i: usize| {
+                chars[i].is_alphanumeric()
+                    || chars[i] == '_'
+                    || (chars[i] == '.' && chars.get(i + 1).is_some_and(|d| d.is_alphabetic() || *d == '_'))
+            };
+            while i < n && continues(i) {
+                i += 1;
+            }
+            let name: String = chars[start..i].iter().collect();
+            let mut next = i;
+            while next < n && chars[next].is_whitespace() {
+                next += 1;
+            }
+            let is_call = chars.get(next) == Some(&'(');
+            if is_call && name == "der" {
+                let close = (next..n).find(|&k| chars[k] == ')').ok_or("unclosed der(")?;
+                let inner: String = chars[next + 1..close].iter().collect::<String>().trim().to_string();
+                if inner.is_empty() || block.parameters.contains_key(&inner) || !inner.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.') {
+                    return Err(format!("der() takes a variable name, got '{inner}'"));
+                }
+                out.push(Piece::Text(std::mem::take(&mut literal)));
+                out.push(Piece::Var(table.resolve(block, &inner), 1));
+                i = close + 1;
+            } else if is_call {
+                literal.push_str(&name);
+            } else if name == "t" || name == "time" {
+                literal.push('t');
+            } else if matches!(name.as_str(), "pi" | "PI" | "e" | "E") {
+                literal.push_str(&name);
+            } else if let Some(value) = block.parameters.get(&name) {
+                literal.push_str(&format!("({value:?})"));
+            } else {
+                out.push(Piece::Text(std::mem::take(&mut literal)));
+                out.push(Piece::Var(table.resolve(block, &name), 0));
+            }
+        } else {
+            literal.push(c);
+            i += 1;
+        }
+    }
+    out.push(Piece::Text(literal));
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resistor(id: &str, r: f64) -> AcausalBlock {
+        AcausalBlock {
+            id: id.to_string(),
+            block_type: "Resistor".to_string(),
+            ports: vec![Port::new(id, "p", PhysicalDomain::Electrical), Port::new(id, "n", PhysicalDomain::Electrical)],
+            equations: vec![
+                super::super::parse_equation("p.voltage - n.voltage = R * p.current").unwrap(),
+                super::super::parse_equation("p.current + n.current = 0").unwrap(),
+            ],
+            parameters: [("R".to_string(), r)].into(),
+            initial_values: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn symbols_round_trip() {
+        assert_eq!(parse_symbol(&symbol(12, 0)), Some((12, 0)));
+        assert_eq!(parse_symbol(&symbol(3, 2)), Some((3, 2)));
+        assert_eq!(parse_symbol("t"), None);
+    }
+
+    #[test]
+    fn flatten_resolves_relative_names_and_parameters() {
+        let mut net = AcausalNetwork::new();
+        net.add(resistor("R1", 100.0)).add(resistor("R2", 50.0)).connect("R1.n", "R2.p");
+        let flat = net.flatten().unwrap();
+        // Open R1.p and R2.n are read by the resistor law, so their currents are zero.
+        assert_eq!(flat.variables.len(), 8);
+        let origins: Vec<&str> = flat.equations.iter().map(|e| e.origin.as_str()).collect();
+        assert_eq!(origins.iter().filter(|o| o.starts_with("open port")).count(), 2);
+        assert_eq!(origins.iter().filter(|o| o.starts_with("node R1.n")).count(), 2);
+        let law = flat.equations.iter().find(|e| e.origin.starts_with("R2: p.voltage")).unwrap();
+        assert!(law.residual.to_string().contains("50"), "{}", law.residual);
+    }
+
+    #[test]
+    fn dangling_output_port_is_dropped() {
+        let mut net = AcausalNetwork::new();
+        net.add(MultiDomainBlock::dc_motor("M1", 1.0, 0.5, 0.01, 0.01, 0.1).to_acausal_block());
+        let flat = net.flatten().unwrap();
+        let names: Vec<&str> = flat.variables.iter().map(|v| v.name.as_str()).collect();
+        assert!(!names.contains(&"M1.heat_port.temperature"));
+        assert!(names.contains(&"M1.heat_port.heat_flow"));
+        assert_eq!(flat.variables.iter().find(|v| v.name == "M1.shaft.torque").unwrap().unit, "N·m");
+    }
+
+    #[test]
+    fn connection_errors_are_reported() {
+        let mut net = AcausalNetwork::new();
+        net.add(resistor("R1", 1.0)).connect("R1.p", "R9.n");
+        assert!(net.flatten().unwrap_err().contains("unknown port 'R9.n'"));
+        let mut net = AcausalNetwork::new();
+        net.add(resistor("R1", 1.0));
+        net.add(MultiDomainBlock::dc_motor("M1", 1.0, 0.5, 0.01, 0.01, 0.1).to_acausal_block());
+        net.connect("R1.p", "M1.shaft");
+        assert!(net.nodes().unwrap_err().contains("domain mismatch"));
+    }
+
+    #[test]
+    fn network_from_json() {
+        let json = serde_json::json!({
+            "components": [
+                { "id": "M1", "type": "dc_motor", "R": 2.0 },
+                { "id": "load", "ports": [{ "name": "flange", "domain": "rotational" }],
+                  "parameters": { "J": 0.1 },
+                  "equations": ["J * der(flange.angular_velocity) = flange.torque"],
+                  "initial_values": { "flange.angular_velocity": 3.0 } }
+            ],
+            "connections": [["M1.shaft", "load.flange"]]
+        });
+        let net = AcausalNetwork::from_json(&json).unwrap();
+        assert_eq!(net.blocks.len(), 2);
+        assert_eq!(net.connections[0].port_b, "load.flange");
+        let flat = net.flatten().unwrap();
+        let w = flat.variables.iter().find(|v| v.name == "load.flange.angular_velocity").unwrap();
+        assert_eq!(w.start, Some(3.0));
+        assert!(AcausalNetwork::from_json(&serde_json::json!({ "components": [{ "id": "x", "type": "warp_drive" }] })).is_err());
+
+        let json = serde_json::json!({ "components": [
+            { "id": "R1", "type": "elec.resistor", "R": 100.0 },
+            { "id": "C1", "type": "elec.capacitor", "parameters": { "C": 1e-3 }, "initial_values": { "v": 2.0 } }
+        ] });
+        let net = AcausalNetwork::from_json(&json).unwrap();
+        assert_eq!(net.blocks[0].parameters["R"], 100.0);
+        assert_eq!(net.blocks[1].parameters["C"], 1e-3);
+        assert_eq!(net.blocks[1].initial_values["v"], 2.0);
+    }
+}
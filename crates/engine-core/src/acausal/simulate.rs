@@ -0,0 +1,358 @@
+//! Simulation of an [`AcausalNetwork`]: flatten, reduce the index, sort the
+//! algebraic equations, initialize consistently and integrate.
+//!
+//! The reduced system is integrated as an ODE in its states. Each
+//! right-hand side evaluation solves the sorted algebraic equations for the
+//! remaining unknowns, warm-started from the previous solution, and returns
+//! the state derivatives. Radau IIA (stiffly accurate, L-stable) handles the
+//! stiffness that small inductances or masses introduce.
+
+use super::blt::Blt;
+use super::index::{reduce, ReducedSystem};
+use super::network::{AcausalNetwork, FlatSystem};
+use super::{DaeDiagnostic, DaeSeverity};
+use crate::ode::dense::{AcceptedStep, StepControl};
+use crate::ode::jacobian::{ImplicitStats, Jacobian, OdeRhs};
+use crate::ode::radau::integrate_radau_rhs;
+use crate::ode::OdeSolverConfig;
+use std::cell::{Cell, RefCell};
+
+/// Most `dt` steps a simulation spans, as the ODE blocks' `max_steps`.
+pub const MAX_SIMULATION_STEPS: usize = 100_000;
+
+/// A network compiled down to a sorted index-1 system.
+#[derive(Debug, Clone)]
+pub struct AcausalModel {
+    pub flat: FlatSystem,
+    pub reduced: ReducedSystem,
+    blt: Blt,
+}
+
+/// Time response of every flat variable.
+#[derive(Debug, Clone)]
+pub struct AcausalResult {
+    pub t: Vec<f64>,
+    /// Flat variable names: port across/through variables and block internals.
+    pub names: Vec<String>,
+    /// `values[k][i]`: variable `names[i]` at `t[k]`.
+    pub values: Vec<Vec<f64>>,
+    pub stats: ImplicitStats,
+    pub diagnostics: Vec<DaeDiagnostic>,
+}
+
+impl AcausalResult {
+    /// Flatten into `(columns, rows)`: `t`, then every variable.
+    pub fn to_table(&self) -> (Vec<String>, Vec<Vec<f64>>) {
+        let columns = std::iter::once("t".to_string()).chain(self.names.iter().cloned()).collect();
+        let rows = self
+            .t
+            .iter()
+            .zip(&self.values)
+            .map(|(&t, row)| std::iter::once(t).chain(row.iter().copied()).collect())
+            .collect();
+        (columns, rows)
+    }
+
+    /// The time series of one variable.
+    pub fn series(&self, name: &str) -> Option<Vec<f64>> {
+        let i = self.names.iter().position(|n| n == name)?;
+        Some(self.values.iter().map(|row| row[i]).collect())
+    }
+}
+
+impl AcausalModel {
+    pub fn compile(network: &AcausalNetwork) -> Result<Self, String> {
+        let flat = network.flatten()?;
+        let reduced = reduce(&flat)?;
+        let blt = Blt::new(&reduced)?;
+        Ok(AcausalModel { flat, reduced, blt })
+    }
+
+    /// Names of the integrated variables.
+    pub fn state_names(&self) -> Vec<String> {
+        self.reduced.states.iter().map(|&s| self.reduced.variables[s].name.clone()).collect()
+    }
+
+    /// Index reduction and sorting diagnostics.
+    pub fn diagnostics(&self) -> Vec<DaeDiagnostic> {
+        let mut out = self.reduced.diagnostics.clone();
+        if self.blt.loops() > 0 {
+            out.push(DaeDiagnostic {
+                severity: DaeSeverity::Info,
+                message: format!("{} algebraic loops torn", self.blt.loops()),
+                related_variables: self.blt.tearing_variables().iter().map(|s| s.to_string()).collect(),
+                related_equations: Vec::new(),
+            });
+        }
+        out
+    }
+
+    /// Consistent values of every reduced variable at `t0`: states take
+    /// their start values (zero if none), everything else is solved from the
+    /// equations. Start values of other variables only seed the solve; a
+    /// warning reports each one that the equations override.
+    pub fn initialize(&self, t0: f64) -> Result<(Vec<f64>, Vec<DaeDiagnostic>), String> {
+        let start = |base: usize, order: usize| if order == 0 { self.flat.variables[base].start } else { None };
+        let mut values: Vec<f64> =
+            self.reduced.variables.iter().map(|v| start(v.base, v.order).unwrap_or(0.0)).collect();
+        self.blt.solve(t0, &mut values).map_err(|e| format!("initialization failed: {e}"))?;
+        let mut diagnostics = Vec::new();
+        for &u in &self.reduced.unknowns {
+            let v = &self.reduced.variables[u];
+            if let Some(given) = start(v.base, v.order) {
+                if (values[u] - given).abs() > 1e-6 * (1.0 + given.abs()) {
+                    diagnostics.push(DaeDiagnostic {
+                        severity: DaeSeverity::Warning,
+                        message: format!(
+                            "start value {given} of {} is not consistent; initialized to {}",
+                            v.name, values[u]
+                        ),
+                        related_variables: vec![v.name.clone()],
+                        related_equations: Vec::new(),
+                    });
+                }
+            }
+        }
+        Ok((values, diagnostics))
+    }
+
+    /// Integrate from `config.t_start` to `config.t_end`, reporting every
+    /// flat variable at the times `config.output` selects.
+    pub fn simulate(&self, config: &OdeSolverConfig) -> Result<AcausalResult, String> {
+        if config.dt.is_nan() || config.dt <= 0.0 || config.dt.is_infinite() {
+            return Err(format!("dt must be positive and finite, got {}", config.dt));
+        }
+        let steps = ((config.t_end - config.t_start) / config.dt).ceil();
+        if steps > MAX_SIMULATION_STEPS as f64 {
+            return Err(format!(
+                "{steps} steps exceeds the {MAX_SIMULATION_STEPS} step limit; increase dt or shorten the time span"
+            ));
+        }
+        let requested = config.output.times(config.t_start, config.t_end)?;
+        let (initial, mut diagnostics) = self.initialize(config.t_start)?;
+        diagnostics.splice(0..0, self.diagnostics());
+        let states = &self.reduced.states;
+        let rhs = ModelRhs { model: self, values: RefCell::new(initial.clone()), evals: Cell::new(0), error: RefCell::new(None) };
+
+        let (times, state_rows, stats) = if states.is_empty() {
+            let times = requested.unwrap_or_else(|| {
+                let n = ((config.t_end - config.t_start) / config.dt).ceil().max(1.0) as usize;
+                (0..=n).map(|k| (config.t_start + k as f64 * config.dt).min(config.t_end)).collect()
+            });
+            let rows = vec![Vec::new(); times.len()];
+            (times, rows, ImplicitStats::default())
+        } else {
+            let y0: Vec<f64> = states.iter().map(|&s| initial[s]).collect();
+            let mut reached = config.t_start;
+            let mut track = |step: &AcceptedStep| {
+                reached = step.t1;
+                StepControl::Continue
+            };
+            let (result, stats) = integrate_radau_rhs(&rhs, &self.state_names(), &y0, config, &mut track);
+            if reached < config.t_end - 1e-9 * config.t_end.abs().max(1.0) {
+                let why = rhs.error.borrow().clone().unwrap_or_else(|| "step size underflow or step limit".to_string());
+                return Err(format!("integration stopped at t = {reached}: {why}"));
+            }
+            (result.t, result.states, stats)
+        };
+
+        let outputs: Vec<usize> =
+            (0..self.flat.variables.len()).map(|j| self.reduced.find(j, 0).expect("every flat variable is reduced")).collect();
+        let mut values = Vec::with_capacity(times.len());
+        let mut current = initial;
+        for (&t, y) in times.iter().zip(&state_rows) {
+            for (&s, &v) in states.iter().zip(y) {
+                current[s] = v;
+            }
+            self.blt.solve(t, &mut current)?;
+            values.push(outputs.iter().map(|&i| current[i]).collect());
+        }
+        Ok(AcausalResult {
+            t: times,
+            names: self.flat.variables.iter().map(|v| v.name.clone()).collect(),
+            values,
+            stats,
+            diagnostics,
+        })
+    }
+}
+
+/// Compile `network` and simulate it.
+pub fn simulate(network: &AcausalNetwork, config: &OdeSolverConfig) -> Result<AcausalResult, String> {
+    AcausalModel::compile(network)?.simulate(config)
+}
+
+/// The reduced system as an ODE in its states.
+struct ModelRhs<'a> {
+    model: &'a AcausalModel,
+    /// Last algebraic solution, the starting guess for the next one.
+    values: RefCell<Vec<f64>>,
+    evals: Cell<usize>,
+    /// Why the last failed evaluation failed.
+    error: RefCell<Option<String>>,
+}
+
+impl ModelRhs<'_> {
+    fn derivatives(&self, t: f64, y: &[f64]) -> Vec<f64> {
+        let reduced = &self.model.reduced;
+        let mut values = self.values.borrow().clone();
+        for (&s, &v) in reduced.states.iter().zip(y) {
+            values[s] = v;
+        }
+        match self.model.blt.solve(t, &mut values) {
+            Ok(()) => {
+                let out = reduced.state_derivatives.iter().map(|&d| values[d]).collect();
+                *self.values.borrow_mut() = values;
+                out
+            }
+            Err(e) => {
+                // NaN makes the integrator retry with a smaller step.
+                *self.error.borrow_mut() = Some(e);
+                vec![f64::NAN; y.len()]
+            }
+        }
+    }
+}
+
+impl OdeRhs for ModelRhs<'_> {
+    fn eval(&self, t: f64, y: &[f64]) -> Vec<f64> {
+        self.evals.set(self.evals.get() + 1);
+        self.derivatives(t, y)
+    }
+
+    fn jacobian(&self, t: f64, y: &[f64]) -> Jacobian {
+        self.evals.set(self.evals.get() + 1);
+        let f0 = self.derivatives(t, y);
+        let n = y.len();
+        let mut jac = vec![vec![0.0; n]; n];
+        let mut yp = y.to_vec();
+        for j in 0..n {
+            let h = 1e-7 * (1.0 + y[j].abs());
+            yp[j] = y[j] + h;
+            let fp = self.derivatives(t, &yp);
+            yp[j] = y[j];
+            for i in 0..n {
+                jac[i][j] = (fp[i] - f0[i]) / h;
+            }
+        }
+        Jacobian::Dense(jac)
+    }
+
+    fn evals(&self) -> usize {
+        self.evals.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acausal::MultiDomainBlock;
+    use crate::ode::OutputTimes;
+
+    fn components(extra: serde_json::Value) -> serde_json::Value {
+        let mut list = vec![
+            serde_json::json!({ "id": "V1", "ports": [{ "name": "p", "domain": "electrical" }, { "name": "n", "domain": "electrical" }],
+                "parameters": { "V": 12.0 }, "equations": ["p.voltage - n.voltage = V", "p.current + n.current = 0"] }),
+            serde_json::json!({ "id": "G", "ports": [{ "name": "p", "domain": "electrical" }], "equations": ["p.voltage = 0"] }),
+        ];
+        list.extend(extra.as_array().unwrap().iter().cloned());
+        serde_json::Value::Array(list)
+    }
+
+    fn config(t_end: f64) -> OdeSolverConfig {
+        OdeSolverConfig { t_end, dt: 0.01, tolerance: 1e-8, output: OutputTimes::Interval(t_end / 10.0), ..Default::default() }
+    }
+
+    #[test]
+    fn rc_circuit_matches_the_analytic_charge_curve() {
+        let json = serde_json::json!({
+            "components": components(serde_json::json!([
+                { "id": "R1", "ports": [{ "name": "p" }, { "name": "n" }], "parameters": { "R": 1000.0 },
+                  "equations": ["p.voltage - n.voltage = R * p.current", "p.current + n.current = 0"] },
+                { "id": "C1", "ports": [{ "name": "p" }, { "name": "n" }], "parameters": { "C": 1e-3 },
+                  "equations": ["v = p.voltage - n.voltage", "C * der(v) = p.current", "p.current + n.current = 0"],
+                  "initial_values": { "v": 0.0 } }
+            ])),
+            "connections": [["V1.p", "R1.p"], ["R1.n", "C1.p"], ["C1.n", "V1.n"], ["V1.n", "G.p"]]
+        });
+        let net = AcausalNetwork::from_json(&json).unwrap();
+        let result = simulate(&net, &config(3.0)).unwrap();
+        let v = result.series("C1.v").unwrap();
+        for (t, v) in result.t.iter().zip(&v) {
+            assert!((v - 12.0 * (1.0 - (-t).exp())).abs() < 1e-4, "t = {t}: {v}");
+        }
+        // Every port variable is reported, e.g. the loop current.
+        let i = result.series("R1.p.current").unwrap();
+        assert!((i[0] - 0.012).abs() < 1e-9);
+        let (columns, rows) = result.to_table();
+        assert_eq!(columns[0], "t");
+        assert_eq!(rows.len(), 11);
+    }
+
+    #[test]
+    fn dc_motor_drives_a_load_to_steady_state() {
+        let (r, l, k, j, b, j_load) = (1.0, 0.5, 0.05, 0.01, 0.001, 0.04);
+        let mut net = AcausalNetwork::from_json(&serde_json::json!({
+            "components": components(serde_json::json!([
+                { "id": "load", "ports": [{ "name": "flange", "domain": "rotational" }], "parameters": { "J": j_load },
+                  "equations": ["J * der(flange.angular_velocity) = flange.torque"] }
+            ])),
+            "connections": []
+        }))
+        .unwrap();
+        net.add(MultiDomainBlock::dc_motor("M1", r, l, k, j, b).to_acausal_block());
+        net.connect("V1.p", "M1.p_elec").connect("M1.n_elec", "V1.n").connect("V1.n", "G.p").connect("M1.shaft", "load.flange");
+
+        let model = AcausalModel::compile(&net).unwrap();
+        assert_eq!(model.reduced.index, 2);
+        assert_eq!(model.reduced.states.len(), 2);
+        assert_eq!(model.reduced.dummies.len(), 1);
+
+        let result = model.simulate(&config(200.0)).unwrap();
+        let w = result.series("M1.shaft.angular_velocity").unwrap();
+        let w_load = result.series("load.flange.angular_velocity").unwrap();
+        let expected = k * 12.0 / (r * b + k * k);
+        assert!((w.last().unwrap() - expected).abs() < 1e-3 * expected, "{} vs {expected}", w.last().unwrap());
+        assert!(w.iter().zip(&w_load).all(|(a, b)| (a - b).abs() < 1e-8));
+        // The shaft torque accelerates the load, then decays with it.
+        let tau = result.series("load.flange.torque").unwrap();
+        assert!(tau[1] > 0.0 && tau.last().unwrap().abs() < 1e-4);
+        let heat = result.series("M1.heat_port.heat_flow").unwrap();
+        let i = result.series("M1.p_elec.current").unwrap();
+        assert!((heat[5] + r * i[5] * i[5]).abs() < 1e-12);
+    }
+
+    #[test]
+    fn zero_or_tiny_dt_is_an_error() {
+        let json = serde_json::json!({
+            "components": components(serde_json::json!([
+                { "id": "R1", "ports": [{ "name": "p" }, { "name": "n" }], "parameters": { "R": 10.0 },
+                  "equations": ["p.voltage - n.voltage = R * p.current", "p.current + n.current = 0"] }
+            ])),
+            "connections": [["V1.p", "R1.p"], ["R1.n", "V1.n"], ["V1.n", "G.p"]]
+        });
+        let net = AcausalNetwork::from_json(&json).unwrap();
+        let steps = |dt| OdeSolverConfig { dt, output: OutputTimes::Steps, ..config(1.0) };
+        let err = simulate(&net, &steps(0.0)).unwrap_err();
+        assert!(err.contains("dt must be positive"), "{err}");
+        assert!(simulate(&net, &steps(f64::NAN)).is_err());
+        let err = simulate(&net, &steps(1e-9)).unwrap_err();
+        assert!(err.contains("step limit"), "{err}");
+        assert_eq!(simulate(&net, &steps(0.1)).unwrap().t.len(), 11);
+    }
+
+    #[test]
+    fn inconsistent_start_values_are_reported() {
+        let json = serde_json::json!({
+            "components": components(serde_json::json!([
+                { "id": "R1", "ports": [{ "name": "p" }, { "name": "n" }], "parameters": { "R": 10.0 },
+                  "equations": ["p.voltage - n.voltage = R * p.current", "p.current + n.current = 0"],
+                  "initial_values": { "p.current": 5.0 } }
+            ])),
+            "connections": [["V1.p", "R1.p"], ["R1.n", "V1.n"], ["V1.n", "G.p"]]
+        });
+        let result = simulate(&AcausalNetwork::from_json(&json).unwrap(), &config(1.0)).unwrap();
+        assert!(result.series("R1.p.current").unwrap().iter().all(|i| (i - 1.2).abs() < 1e-12));
+        assert!(result.diagnostics.iter().any(|d| matches!(d.severity, DaeSeverity::Warning) && d.message.contains("R1.p.current")));
+    }
+}
@@ -112,6 +112,17 @@ impl Dual {
         Dual { val: t, dot: (1.0 - t * t) * self.dot }
     }
 
+    pub fn erf(self) -> Self {
+        let d = std::f64::consts::FRAC_2_SQRT_PI * (-self.val * self.val).exp();
+        Dual { val: crate::expr::erf(self.val), dot: d * self.dot }
+    }
+
+    /// Γ(x), with Γ'(x) = Γ(x)·ψ(x).
+    pub fn gamma(self) -> Self {
+        let g = crate::expr::gamma(self.val);
+        Dual { val: g, dot: g * crate::expr::digamma(self.val) * self.dot }
+    }
+
     pub fn atan2(self, other: Dual) -> Self {
         let denom = self.val * self.val + other.val * other.val;
         Dual {
@@ -134,6 +134,8 @@ pub fn catalog() -> Vec<CatalogEntry> {
         entry("ifthenelse", "If / Then / Else", "logic", "csOperation", vec![p("cond", "If (\u{2260}0)"), p("then", "Then"), p("else", "Else")], false),
         variadic_entry("max", "Max", "logic", "csOperation", vec![p("a", "A"), p("b", "B")], false, 2, 64),
         variadic_entry("min", "Min", "logic", "csOperation", vec![p("a", "A"), p("b", "B")], false, 2, 64),
+        // Graph-level user function callable from every expression (expr::FunctionTable).
+        entry("expr.define", "Define Function", "customFunctions", "csSource", vec![], false),
         // ── Output ───────────────────────────────────────────────
         entry("display", "Display", "output", "csDisplay", vec![p("value", "Value")], false),
         // H7-1: Publish block captures incoming value under a named channel.
@@ -430,6 +432,12 @@ pub fn catalog() -> Vec<CatalogEntry> {
         entry("ctrl.natural_freq", "wn = sqrt(k/m)", "controlSystems", "csOperation", vec![p("k", "k (N/m)"), p("m", "m (kg)")], true),
         entry("ctrl.damping_ratio", "zeta = c/(2*sqrt(km))", "controlSystems", "csOperation", vec![p("c", "c (Ns/m)"), p("k", "k (N/m)"), p("m", "m (kg)")], true),
         entry("ctrl.bode_mag_1st", "|H(jw)| 1st Order", "controlSystems", "csOperation", vec![p("K", "K (gain)"), p("omega", "omega (rad/s)"), p("tau", "tau (s)")], true),
+        // Discrete-time stateful blocks (state carried by EngineGraph between steps)
+        entry("ctrl.unit_delay", "Unit Delay (z⁻¹)", "controlSystems", "csOperation", vec![p("u", "u")], true),
+        entry("ctrl.discrete_integrator", "Discrete Integrator", "controlSystems", "csOperation", vec![p("u", "u")], true),
+        entry("ctrl.zoh", "Zero-Order Hold", "controlSystems", "csOperation", vec![p("u", "u")], true),
+        entry("ctrl.rate_limiter", "Rate Limiter", "controlSystems", "csOperation", vec![p("u", "u")], true),
+        entry("ctrl.discrete_tf", "Discrete Transfer Fn", "controlSystems", "csOperation", vec![p("u", "u")], true),
 
         // ── BLK-06: Life Sciences ────────────────────────────────────────
         entry("bio.michaelis_menten", "Michaelis-Menten", "lifeSci", "csOperation", vec![p("Vmax", "V_max"), p("Km", "K_m"), p("S", "[S]")], true),
@@ -516,6 +524,17 @@ pub fn catalog() -> Vec<CatalogEntry> {
         entry("matrix_schur", "Schur Decompose", "matrix", "csOperation", vec![p("matrix", "Matrix")], true),
         entry("matrix_cond", "Condition Number", "matrix", "csOperation", vec![p("matrix", "Matrix")], true),
 
+        // ── Sparse Matrices ──────────────────────────────────────────────
+        entry("sparse.fromMatrix", "Matrix to Sparse", "matrix", "csOperation", vec![p("matrix", "Matrix")], true),
+        entry("sparse.fromTable", "Triplets to Sparse", "matrix", "csOperation", vec![p("table", "Table (row, col, value)")], true),
+        entry("sparse.toMatrix", "Sparse to Matrix", "matrix", "csOperation", vec![p("matrix", "Sparse matrix")], true),
+        entry("sparse.toTable", "Sparse to Triplets", "matrix", "csOperation", vec![p("matrix", "Sparse matrix")], true),
+        entry("sparse.multiply", "Sparse Multiply", "matrix", "csOperation", vec![p("a", "A (sparse)"), p("b", "b (vector / matrix)")], true),
+        entry("sparse.solve", "Sparse Solve Ax = b", "matrix", "csOperation", vec![p("a", "A (sparse)"), p("b", "b (vector)")], true),
+        entry("sparse.stats", "Sparsity Pattern Stats", "matrix", "csOperation", vec![p("matrix", "Sparse matrix")], true),
+        entry("sparse.import", "Sparse Matrix Import", "matrix", "csSource", vec![], true),
+        entry("sparse.export", "Sparse Matrix Export", "matrix", "csOperation", vec![p("matrix", "Sparse matrix")], true),
+
         // ── Rootfinding ──────────────────────────────────────────────────
         entry("root_newton", "Newton-Raphson Root", "numerical", "csOperation", vec![p("x0", "Initial Guess")], true),
         entry("root_brent", "Brent Root", "numerical", "csOperation", vec![p("a", "Bracket a"), p("b", "Bracket b")], true),
@@ -625,6 +644,8 @@ pub fn catalog() -> Vec<CatalogEntry> {
         entry("scripting.python", "Python Script", "customFunctions", "csPythonScript", vec![], true),
         entry("scripting.rust", "Custom Rust", "customFunctions", "csCustomRust", vec![], true),
         entry("fmu.export", "FMU Export", "data", "csOperation", vec![p("data", "Computed value")], true),
+        entry("fmu.cosimulate", "FMU Co-Simulation", "simulation", "csOperation",
+            vec![p("fmus", "FMU spec (JSON)"), p("inputs", "External inputs (optional)")], true),
         entry("nn.pinn", "PINN Solver", "neuralNetworks", "csOperation", vec![], true),
         entry("nn.neuralOp", "Neural Operator", "neuralNetworks", "csOperation", vec![p("trainData", "Training data (table)")], true),
         entry("nn.onnxExport", "ONNX Export", "neuralNetworks", "csOperation", vec![p("trainX", "Training features (table/vector)"), p("trainY", "Training labels (vector)")], true),
@@ -636,15 +657,25 @@ pub fn catalog() -> Vec<CatalogEntry> {
         entry("sym.simplify", "Simplify", "math", "csOperation", vec![p("expr", "Expression (text)")], true),
         entry("sym.expand", "Expand", "math", "csOperation", vec![p("expr", "Expression (text)")], true),
         entry("sym.substitute", "Substitute", "math", "csOperation", vec![p("expr", "Expression (text)"), p("var", "Variable (text)"), p("value", "Value")], true),
+        entry("sym.solve", "Solve", "math", "csOperation", vec![p("expr", "Equation(s) (text)"), p("var", "Variable(s) (text)")], true),
+        entry("sym.series", "Series", "math", "csOperation", vec![p("expr", "Expression (text)"), p("var", "Variable (text)"), p("x0", "Expansion point")], true),
+        entry("sym.limit", "Limit", "math", "csOperation", vec![p("expr", "Expression (text)"), p("var", "Variable (text)"), p("x0", "Limit point")], true),
+        entry("sym.jacobian", "Jacobian", "math", "csOperation", vec![p("expr", "Expressions (text)"), p("vars", "Variables (text)")], true),
+        entry("sym.matrix", "Symbolic Matrix", "math", "csOperation", vec![p("a", "Matrix A (text)"), p("b", "Matrix B (text)")], true),
+        entry("sym.codegen", "Code Generator", "math", "csOperation", vec![p("expr", "Outputs (text)"), p("vars", "Inputs (text)")], true),
+        entry("sym.lagrange", "Lagrangian Mechanics", "math", "csOperation", vec![p("T", "Kinetic energy (text)"), p("V", "Potential energy (text)"), p("coords", "Coordinates (text)")], true),
 
         // ── ODE Solvers (Phase 4) ──────────────────────────────────────
-        entry("ode.rk4", "ODE Solver (RK4)", "odeSolvers", "csOperation", vec![p("equations", "Equations (text)"), p("y0", "Initial state")], true),
-        entry("ode.rk45", "ODE Solver (Adaptive)", "odeSolvers", "csOperation", vec![p("equations", "Equations (text)"), p("y0", "Initial state")], true),
-        entry("ode.event", "ODE Event Solver", "odeSolvers", "csOperation", vec![p("equations", "Equations (text)"), p("y0", "Initial state")], true),
+        entry("ode.rk4", "ODE Solver (RK4)", "odeSolvers", "csOperation", vec![p("equations", "Equations (text)"), p("y0", "Initial state"), p("t_eval", "Output times (optional)")], true),
+        entry("ode.rk45", "ODE Solver (Adaptive)", "odeSolvers", "csOperation", vec![p("equations", "Equations (text)"), p("y0", "Initial state"), p("t_eval", "Output times (optional)")], true),
+        entry("ode.event", "ODE Event Solver", "odeSolvers", "csOperation", vec![p("equations", "Equations (text)"), p("y0", "Initial state"), p("t_eval", "Output times (optional)")], true),
         entry("ode.symplectic", "Symplectic ODE Solver", "odeSolvers", "csOperation", vec![p("accelerations", "Accelerations (text)"), p("y0", "Initial state [q, v]")], true),
         entry("ode.steady_state", "Steady-State Solver", "odeSolvers", "csOperation", vec![p("equations", "Equations (text)"), p("y0", "Initial guess")], true),
-        entry("ode.bdf", "ODE Solver (BDF)", "odeSolvers", "csOperation", vec![p("equations", "Equations (text)"), p("y0", "Initial state")], true),
-        entry("ode.radau", "ODE Solver (Radau)", "odeSolvers", "csOperation", vec![p("equations", "Equations (text)"), p("y0", "Initial state")], true),
+        entry("ode.bdf", "ODE Solver (BDF)", "odeSolvers", "csOperation", vec![p("equations", "Equations (text)"), p("y0", "Initial state"), p("t_eval", "Output times (optional)")], true),
+        entry("ode.radau", "ODE Solver (Radau)", "odeSolvers", "csOperation", vec![p("equations", "Equations (text)"), p("y0", "Initial state"), p("t_eval", "Output times (optional)")], true),
+        entry("ode.dde", "Delay ODE Solver (DDE)", "odeSolvers", "csOperation", vec![p("equations", "Equations (text)"), p("y0", "Initial state"), p("t_eval", "Output times (optional)")], true),
+        entry("ode.sde", "SDE Solver", "odeSolvers", "csOperation", vec![p("drift", "Drift (text)"), p("diffusion", "Diffusion (text)"), p("y0", "Initial state")], true),
+        entry("ode.sdeEnsemble", "SDE Ensemble", "odeSolvers", "csOperation", vec![p("drift", "Drift (text)"), p("diffusion", "Diffusion (text)"), p("y0", "Initial state")], true),
         entry("ode.dae", "DAE Solver", "odeSolvers", "csOperation", vec![p("diff_eqs", "Differential eqs (text)"), p("alg_eqs", "Algebraic eqs (text)"), p("y0", "Diff initial state"), p("z0", "Alg initial guess")], true),
         entry("ode.pde1d", "PDE 1D Solver", "odeSolvers", "csOperation", vec![p("u0", "Initial condition (vector)")], true),
 
@@ -676,6 +707,51 @@ pub fn catalog() -> Vec<CatalogEntry> {
         // DAE index reduction (2.36)
         entry("ode.daeIndexReduction", "DAE Index Reduction", "simulation", "csOperation",
             vec![p("diff_eqs", "Diff. eqs (text)"), p("alg_eqs", "Constraints (text)")], false),
+        // Acausal physical modelling
+        entry("acausal.simulate", "Acausal Simulation", "simulation", "csOperation",
+            vec![p("network", "Network (optional)"), p("t_eval", "Output times (optional)")], true),
+        variadic_entry("acausal.network", "Acausal Network", "simulation", "csOperation",
+            vec![p("in_0", "Component 1"), p("in_1", "Component 2")], true, 1, 64),
+        entry("acausal.circuit.dc", "Circuit DC Operating Point", "simulation", "csOperation", vec![p("network", "Network")], true),
+        entry("acausal.circuit.ac", "Circuit AC Sweep", "simulation", "csOperation",
+            vec![p("network", "Network"), p("f", "Frequency (optional)")], true),
+        entry("acausal.circuit.transient", "Circuit Transient", "simulation", "csOperation", vec![p("network", "Network")], true),
+        // Acausal component library (acausal::library)
+        entry("acausal.elec.ground", "Ground", "simulation", "csOperation", vec![], true),
+        entry("acausal.elec.resistor", "Resistor", "simulation", "csOperation", vec![p("R", "R (Ω)")], true),
+        entry("acausal.elec.capacitor", "Capacitor", "simulation", "csOperation", vec![p("C", "C (F)"), p("v0", "v0 (V)")], true),
+        entry("acausal.elec.inductor", "Inductor", "simulation", "csOperation", vec![p("L", "L (H)"), p("i0", "i0 (A)")], true),
+        entry("acausal.elec.voltage_source", "Voltage Source", "simulation", "csOperation", vec![p("V", "V (V)")], true),
+        entry("acausal.elec.current_source", "Current Source", "simulation", "csOperation", vec![p("I", "I (A)")], true),
+        entry("acausal.elec.sine_voltage", "Sine Voltage Source", "simulation", "csOperation", vec![p("V", "V (V)"), p("f", "f (Hz)"), p("phase", "phase (rad)"), p("offset", "offset (V)")], true),
+        entry("acausal.elec.diode", "Diode", "simulation", "csOperation", vec![p("Is", "Is (A)"), p("N", "N"), p("Vt", "Vt (V)")], true),
+        entry("acausal.elec.switch", "Ideal Switch", "simulation", "csOperation", vec![p("Ron", "Ron (Ω)"), p("Goff", "Goff (S)"), p("t_on", "t_on (s)"), p("t_off", "t_off (s)")], true),
+        entry("acausal.trans.fixed", "Fixed (Translational)", "simulation", "csOperation", vec![], true),
+        entry("acausal.trans.mass", "Mass", "simulation", "csOperation", vec![p("m", "m (kg)"), p("v0", "v0 (m/s)")], true),
+        entry("acausal.trans.spring", "Spring", "simulation", "csOperation", vec![p("c", "c (N/m)"), p("s_rel0", "s_rel0 (m)")], true),
+        entry("acausal.trans.damper", "Damper", "simulation", "csOperation", vec![p("d", "d (N·s/m)")], true),
+        entry("acausal.trans.friction", "Friction (Translational)", "simulation", "csOperation", vec![p("F_c", "F_c (N)"), p("d", "d (N·s/m)"), p("v_s", "v_s (m/s)")], true),
+        entry("acausal.trans.force", "Force Source", "simulation", "csOperation", vec![p("F", "F (N)")], true),
+        entry("acausal.rot.fixed", "Fixed (Rotational)", "simulation", "csOperation", vec![], true),
+        entry("acausal.rot.inertia", "Inertia", "simulation", "csOperation", vec![p("J", "J (kg·m²)"), p("w0", "w0 (rad/s)")], true),
+        entry("acausal.rot.spring", "Torsion Spring", "simulation", "csOperation", vec![p("c", "c (N·m/rad)"), p("phi_rel0", "phi_rel0 (rad)")], true),
+        entry("acausal.rot.damper", "Rotational Damper", "simulation", "csOperation", vec![p("d", "d (N·m·s/rad)")], true),
+        entry("acausal.rot.gear", "Ideal Gear", "simulation", "csOperation", vec![p("ratio", "ratio")], true),
+        entry("acausal.rot.clutch", "Clutch", "simulation", "csOperation", vec![p("T_max", "T_max (N·m)"), p("w_s", "w_s (rad/s)"), p("t_engage", "t_engage (s)")], true),
+        entry("acausal.rot.friction", "Bearing Friction", "simulation", "csOperation", vec![p("T_c", "T_c (N·m)"), p("d", "d (N·m·s/rad)"), p("w_s", "w_s (rad/s)")], true),
+        entry("acausal.rot.torque", "Torque Source", "simulation", "csOperation", vec![p("tau", "tau (N·m)")], true),
+        entry("acausal.thermal.heat_capacitor", "Heat Capacitor", "simulation", "csOperation", vec![p("C", "C (J/K)"), p("T0", "T0 (K)")], true),
+        entry("acausal.thermal.conductor", "Thermal Conductor", "simulation", "csOperation", vec![p("G", "G (W/K)")], true),
+        entry("acausal.thermal.convection", "Convection", "simulation", "csOperation", vec![p("h", "h (W/(m²·K))"), p("A", "A (m²)")], true),
+        entry("acausal.thermal.radiation", "Radiation", "simulation", "csOperation", vec![p("epsilon", "epsilon"), p("A", "A (m²)")], true),
+        entry("acausal.thermal.fixed_temperature", "Fixed Temperature", "simulation", "csOperation", vec![p("T", "T (K)")], true),
+        entry("acausal.thermal.heat_source", "Heat Flow Source", "simulation", "csOperation", vec![p("Q", "Q (W)")], true),
+        entry("acausal.hyd.pressure_source", "Pressure Source", "simulation", "csOperation", vec![p("p", "p (Pa)")], true),
+        entry("acausal.hyd.flow_source", "Flow Source", "simulation", "csOperation", vec![p("Q", "Q (m³/s)")], true),
+        entry("acausal.hyd.orifice", "Orifice", "simulation", "csOperation", vec![p("Cd", "Cd"), p("A", "A (m²)"), p("rho", "rho (kg/m³)"), p("dp_t", "dp_t (Pa)")], true),
+        entry("acausal.hyd.accumulator", "Accumulator", "simulation", "csOperation", vec![p("C", "C (m³/Pa)"), p("p0", "p0 (Pa)")], true),
+        entry("acausal.hyd.pump", "Pump", "simulation", "csOperation", vec![p("D", "D (m³/rad)")], true),
+        entry("acausal.hyd.cylinder", "Cylinder", "simulation", "csOperation", vec![p("A", "A (m²)")], true),
         // ExpressionInput (2.12)
         entry("sym.expressionInput", "Expression Input", "input", "csSource", vec![], false),
         // Mixed-mode AD (1.32)
@@ -703,6 +779,13 @@ pub fn catalog() -> Vec<CatalogEntry> {
         entry("ad.gradCheckpoint", "Gradient Checkpointing", "simulation", "csOperation", vec![], false),
         // 2D FEM Poisson solver (2.38)
         entry("ode.fem2d", "2D FEM Solver", "simulation", "csOperation", vec![], false),
+        entry("fem.mesh", "FEM Mesh", "simulation", "csOperation", vec![], false),
+        entry("fem.heat", "FEM Heat Conduction", "simulation", "csOperation", vec![], false),
+        entry("fem.elasticity", "FEM Plane Elasticity", "simulation", "csOperation", vec![], false),
+        entry("fem.stokes", "FEM Stokes Flow", "simulation", "csOperation", vec![], false),
+        entry("fem.modal", "FEM Modal Analysis", "simulation", "csOperation", vec![], false),
+        entry("modal.eigen", "Modal Analysis", "simulation", "csOperation", vec![p("K", "K (stiffness)"), p("M", "M (mass)"), p("r", "r (influence)")], false),
+        entry("modal.frf", "Frequency Response (FRF)", "simulation", "csOperation", vec![p("K", "K (stiffness)"), p("M", "M (mass)"), p("C", "C (damping)")], false),
     ]
 }
 
@@ -768,14 +851,29 @@ mod tests {
     #[test]
     fn catalog_has_expected_count() {
         let cat = catalog();
-        assert_eq!(cat.len(), 501);
+        assert_eq!(cat.len(), 574);
+    }
+
+    #[test]
+    fn acausal_library_blocks_match_the_library() {
+        let cat = catalog();
+        for spec in crate::acausal::library::COMPONENTS {
+            let op_id = spec.op_id();
+            let entry = cat.iter().find(|e| e.op_id == op_id).unwrap_or_else(|| panic!("{op_id} missing"));
+            assert_eq!(entry.label, spec.label);
+            let ports: Vec<&str> = entry.inputs.iter().map(|p| p.id).collect();
+            let params: Vec<&str> = spec.parameters.iter().map(|p| p.name).collect();
+            assert_eq!(ports, params, "{op_id}");
+        }
+        let blocks = cat.iter().filter(|e| e.op_id.starts_with("acausal.")).count();
+        assert_eq!(blocks, crate::acausal::library::COMPONENTS.len() + 5);
     }
 
     #[test]
     fn catalog_json_roundtrip() {
         let json = catalog_json();
         let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
-        assert_eq!(parsed.as_array().unwrap().len(), 501);
+        assert_eq!(parsed.as_array().unwrap().len(), 574);
     }
 
     #[test]
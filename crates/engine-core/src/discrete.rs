@@ -0,0 +1,392 @@
+//! Discrete-time stateful blocks (unit delay, integrator, ZOH, rate limiter,
+//! transfer function).
+//!
+//! # Model
+//!
+//! Every block here is a sampled system `y_k = g(x_k, u_k)`,
+//! `x_{k+1} = f(x_k, u_k)`. The state `x` lives per node on
+//! [`crate::graph::EngineGraph`] as a [`NodeState`]; [`advance`] computes the
+//! output and the next state from one set of inputs, so evaluation and the
+//! state commit in `EngineGraph::step()` can never disagree.
+//!
+//! Signals may be scalars or vectors; vectors are processed element-wise with
+//! independent registers per element.
+//!
+//! # Cycle breaking
+//!
+//! Blocks without direct feedthrough ([`breaks_cycles`]) produce `y_k` from
+//! `x_k` alone. Their incoming edges are ignored by topological sorting, so a
+//! feedback loop that passes through at least one of them is legal:
+//!
+//! - `ctrl.unit_delay` — always
+//! - `ctrl.discrete_integrator` — forward-Euler method only
+//! - `ctrl.discrete_tf` — strictly proper transfer functions (`b0 = 0`)
+//!
+//! # Sample time
+//!
+//! `dt` comes from the node's `data.dt` when set, otherwise from the graph's
+//! step size (`EngineGraph::set_step_size`, default 1).
+
+use crate::types::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Every stateful block type handled by this module.
+pub const STATEFUL_BLOCKS: &[&str] = &[
+    "ctrl.unit_delay",
+    "ctrl.discrete_integrator",
+    "ctrl.zoh",
+    "ctrl.rate_limiter",
+    "ctrl.discrete_tf",
+];
+
+/// Per-node state of a stateful block.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeState {
+    /// Number of committed steps.
+    pub k: u64,
+    /// Registers per signal element (`regs[elem][i]`).
+    pub regs: Vec<Vec<f64>>,
+    /// Whether the signal is a vector (scalar otherwise).
+    pub vector: bool,
+    /// ZOH only: time since the last sample.
+    pub since_sample: f64,
+}
+
+/// Whether `block_type` carries state between steps.
+pub fn is_stateful(block_type: &str) -> bool {
+    STATEFUL_BLOCKS.contains(&block_type)
+}
+
+/// Whether the block's output at step `k` is independent of its input at
+/// step `k` (no direct feedthrough), i.e. it may legally close a cycle.
+pub fn breaks_cycles(block_type: &str, data: &HashMap<String, serde_json::Value>) -> bool {
+    match block_type {
+        "ctrl.unit_delay" => true,
+        "ctrl.discrete_integrator" => integrator_method(data) == "forward",
+        "ctrl.discrete_tf" => tf_coeffs(data).map(|(b, _)| b[0] == 0.0).unwrap_or(false),
+        _ => false,
+    }
+}
+
+// ── Parameter helpers ───────────────────────────────────────────────
+
+fn num(data: &HashMap<String, serde_json::Value>, key: &str, default: f64) -> f64 {
+    data.get(key).and_then(|v| v.as_f64()).unwrap_or(default)
+}
+
+fn num_array(data: &HashMap<String, serde_json::Value>, key: &str) -> Option<Vec<f64>> {
+    data.get(key)?
+        .as_array()?
+        .iter()
+        .map(|v| v.as_f64())
+        .collect()
+}
+
+fn integrator_method(data: &HashMap<String, serde_json::Value>) -> &str {
+    data.get("method").and_then(|v| v.as_str()).unwrap_or("forward")
+}
+
+/// Transfer-function coefficients normalised by `a0`, zero-padded to a
+/// common length `n + 1`.
+fn tf_coeffs(data: &HashMap<String, serde_json::Value>) -> Result<(Vec<f64>, Vec<f64>), String> {
+    let mut b = num_array(data, "num").ok_or("'num' coefficient array required")?;
+    let mut a = num_array(data, "den").ok_or("'den' coefficient array required")?;
+    if b.is_empty() || a.is_empty() {
+        return Err("'num' and 'den' must be non-empty".into());
+    }
+    let a0 = a[0];
+    if a0 == 0.0 || !a0.is_finite() {
+        return Err("leading denominator coefficient must be non-zero".into());
+    }
+    let n = b.len().max(a.len());
+    b.resize(n, 0.0);
+    a.resize(n, 0.0);
+    for c in b.iter_mut().chain(a.iter_mut()) {
+        *c /= a0;
+    }
+    Ok((b, a))
+}
+
+/// Initial condition as per-element values (`data.initial`: number or array).
+fn initial(data: &HashMap<String, serde_json::Value>) -> Option<Vec<f64>> {
+    match data.get("initial") {
+        Some(serde_json::Value::Array(_)) => num_array(data, "initial"),
+        Some(v) => v.as_f64().map(|x| vec![x]),
+        None => None,
+    }
+}
+
+/// Broadcast `init` to `width` elements.
+fn broadcast(init: &[f64], width: usize) -> Vec<f64> {
+    (0..width).map(|i| if init.len() == 1 { init[0] } else { init.get(i).copied().unwrap_or(0.0) }).collect()
+}
+
+fn pack(values: Vec<f64>, vector: bool) -> Value {
+    if vector || values.len() != 1 {
+        Value::Vector { value: values }
+    } else {
+        Value::scalar(values[0])
+    }
+}
+
+// ── Core ─────────────────────────────────────────────────────────────
+
+/// Compute the block output for this step and the state for the next one.
+///
+/// `state = None` means the block has not been stepped yet (initial
+/// conditions apply). Errors are returned as `Value::Error` with the state
+/// left unchanged.
+pub fn advance(
+    block_type: &str,
+    state: Option<&NodeState>,
+    inputs: &HashMap<String, Value>,
+    data: &HashMap<String, serde_json::Value>,
+    graph_dt: f64,
+) -> (Value, NodeState) {
+    let fresh = NodeState::default();
+    let st = state.unwrap_or(&fresh);
+    let dt = match data.get("dt").and_then(|v| v.as_f64()) {
+        Some(d) if d > 0.0 => d,
+        _ => graph_dt,
+    };
+
+    // Input as per-element values (None if not connected).
+    let u: Option<(Vec<f64>, bool)> = match inputs.get("u") {
+        Some(Value::Scalar { value }) => Some((vec![*value], false)),
+        Some(Value::HighPrecision { approx, .. }) => Some((vec![*approx], false)),
+        Some(Value::Vector { value }) => Some((value.clone(), true)),
+        Some(Value::Error { message }) => return (Value::error(message.clone()), st.clone()),
+        Some(other) => {
+            return (
+                Value::error(format!("{}: input 'u' must be scalar or vector, got {}", block_type, other.kind_str())),
+                st.clone(),
+            )
+        }
+        None => None,
+    };
+    let vector = u.as_ref().map(|(_, v)| *v).unwrap_or(st.vector);
+    let width = u
+        .as_ref()
+        .map(|(v, _)| v.len())
+        .or_else(|| (!st.regs.is_empty()).then_some(st.regs.len()))
+        .or_else(|| initial(data).map(|i| i.len()))
+        .unwrap_or(1);
+    if !st.regs.is_empty() && st.regs.len() != width {
+        return (
+            Value::error(format!(
+                "{}: input width changed from {} to {} — reset state",
+                block_type,
+                st.regs.len(),
+                width
+            )),
+            st.clone(),
+        );
+    }
+    let init = broadcast(&initial(data).unwrap_or_else(|| vec![0.0]), width);
+    let needs_input = !breaks_cycles(block_type, data);
+    let u_vals = match u {
+        Some((v, _)) => v,
+        None if needs_input => {
+            return (Value::error(format!("{}: input 'u' required", block_type)), st.clone())
+        }
+        None => vec![f64::NAN; width],
+    };
+    let has_u = inputs.contains_key("u");
+
+    let mut next = NodeState { k: st.k + 1, regs: Vec::with_capacity(width), vector, since_sample: 0.0 };
+    let mut out = Vec::with_capacity(width);
+
+    match block_type {
+        "ctrl.unit_delay" => {
+            for (i, (&x0, &ui)) in init.iter().zip(&u_vals).enumerate() {
+                let x = st.regs.get(i).map(|r| r[0]).unwrap_or(x0);
+                out.push(x);
+                next.regs.push(vec![if has_u { ui } else { x }]);
+            }
+        }
+        "ctrl.discrete_integrator" => {
+            let gain = num(data, "gain", 1.0);
+            let lo = num(data, "lower", f64::NEG_INFINITY);
+            let hi = num(data, "upper", f64::INFINITY);
+            if lo.is_nan() || hi.is_nan() || lo > hi {
+                return (
+                    Value::error(format!(
+                        "ctrl.discrete_integrator: lower limit {} must not exceed upper limit {}",
+                        lo, hi
+                    )),
+                    st.clone(),
+                );
+            }
+            let method = integrator_method(data);
+            for (i, (&x0, &ui)) in init.iter().zip(&u_vals).enumerate() {
+                // regs = [y_{k-1}, u_{k-1}]
+                let (y_prev, u_prev) = st.regs.get(i).map(|r| (r[0], r[1])).unwrap_or((x0, 0.0));
+                let started = !st.regs.is_empty();
+                let y = match method {
+                    "backward" if started => y_prev + gain * dt * ui,
+                    "trapezoidal" if started => y_prev + gain * dt * 0.5 * (ui + u_prev),
+                    "forward" if started => y_prev + gain * dt * u_prev,
+                    "forward" | "backward" | "trapezoidal" => x0,
+                    other => {
+                        return (
+                            Value::error(format!("ctrl.discrete_integrator: unknown method '{}'", other)),
+                            st.clone(),
+                        )
+                    }
+                };
+                let y = y.clamp(lo, hi);
+                out.push(y);
+                next.regs.push(vec![y, if has_u { ui } else { u_prev }]);
+            }
+        }
+        "ctrl.zoh" => {
+            let ts = num(data, "samplePeriod", 0.0);
+            let sample = st.regs.is_empty() || st.since_sample + 1e-12 * ts.abs().max(1.0) >= ts;
+            for (i, &ui) in u_vals.iter().enumerate() {
+                let held = if sample { ui } else { st.regs[i][0] };
+                out.push(held);
+                next.regs.push(vec![held]);
+            }
+            next.since_sample = if sample { dt } else { st.since_sample + dt };
+        }
+        "ctrl.rate_limiter" => {
+            let rising = num(data, "rising", f64::INFINITY).abs();
+            let falling = -num(data, "falling", f64::INFINITY).abs();
+            if falling.is_nan() || rising.is_nan() || falling > rising {
+                return (
+                    Value::error(format!(
+                        "ctrl.rate_limiter: falling rate {} must not exceed rising rate {}",
+                        falling, rising
+                    )),
+                    st.clone(),
+                );
+            }
+            if !(dt > 0.0 && dt.is_finite()) {
+                return (
+                    Value::error(format!("ctrl.rate_limiter: time step must be positive and finite, got {}", dt)),
+                    st.clone(),
+                );
+            }
+            for (i, &ui) in u_vals.iter().enumerate() {
+                let y = match st.regs.get(i) {
+                    Some(r) => r[0] + (ui - r[0]).clamp(falling * dt, rising * dt),
+                    None => ui,
+                };
+                out.push(y);
+                next.regs.push(vec![y]);
+            }
+        }
+        "ctrl.discrete_tf" => {
+            let (b, a) = match tf_coeffs(data) {
+                Ok(c) => c,
+                Err(e) => return (Value::error(format!("ctrl.discrete_tf: {}", e)), st.clone()),
+            };
+            let n = b.len() - 1;
+            for (i, &u_raw) in u_vals.iter().enumerate() {
+                // Direct form II transposed: regs = z[0..n].
+                let z = st.regs.get(i).cloned().unwrap_or_else(|| vec![0.0; n]);
+                let ui = if has_u { u_raw } else { 0.0 };
+                let y = b[0] * ui + z.first().copied().unwrap_or(0.0);
+                let z_next: Vec<f64> = (0..n)
+                    .map(|j| b[j + 1] * ui + z.get(j + 1).copied().unwrap_or(0.0) - a[j + 1] * y)
+                    .collect();
+                out.push(y);
+                next.regs.push(z_next);
+            }
+        }
+        other => return (Value::error(format!("Unknown block type: {}", other)), st.clone()),
+    }
+
+    (pack(out, vector), next)
+}
+
+/// Output for a block that has never been stepped (stateless evaluation).
+pub fn initial_output(
+    block_type: &str,
+    inputs: &HashMap<String, Value>,
+    data: &HashMap<String, serde_json::Value>,
+) -> Value {
+    advance(block_type, None, inputs, data, 1.0).0
+}
+
+// ── Tests ────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(block: &str, data: serde_json::Value, us: &[f64], dt: f64) -> Vec<f64> {
+        let data: HashMap<String, serde_json::Value> = serde_json::from_value(data).unwrap();
+        let mut st: Option<NodeState> = None;
+        let mut ys = Vec::new();
+        for &u in us {
+            let mut inputs = HashMap::new();
+            inputs.insert("u".to_string(), Value::scalar(u));
+            let (y, next) = advance(block, st.as_ref(), &inputs, &data, dt);
+            ys.push(y.as_scalar().unwrap());
+            st = Some(next);
+        }
+        ys
+    }
+
+    #[test]
+    fn unit_delay_shifts_by_one() {
+        let ys = run("ctrl.unit_delay", serde_json::json!({ "initial": 9.0 }), &[1.0, 2.0, 3.0], 1.0);
+        assert_eq!(ys, vec![9.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn integrator_methods() {
+        let us = [1.0, 1.0, 1.0];
+        let f = run("ctrl.discrete_integrator", serde_json::json!({}), &us, 0.5);
+        assert_eq!(f, vec![0.0, 0.5, 1.0]);
+        let b = run("ctrl.discrete_integrator", serde_json::json!({ "method": "backward" }), &us, 0.5);
+        assert_eq!(b, vec![0.0, 0.5, 1.0]);
+        let t = run("ctrl.discrete_integrator", serde_json::json!({ "method": "trapezoidal" }), &[0.0, 2.0, 2.0], 1.0);
+        assert_eq!(t, vec![0.0, 1.0, 3.0]);
+        let sat = run("ctrl.discrete_integrator", serde_json::json!({ "upper": 0.7 }), &us, 0.5);
+        assert_eq!(sat, vec![0.0, 0.5, 0.7]);
+    }
+
+    #[test]
+    fn zoh_and_rate_limiter() {
+        let z = run("ctrl.zoh", serde_json::json!({ "samplePeriod": 2.0 }), &[1.0, 2.0, 3.0, 4.0, 5.0], 1.0);
+        assert_eq!(z, vec![1.0, 1.0, 3.0, 3.0, 5.0]);
+        let r = run("ctrl.rate_limiter", serde_json::json!({ "rising": 1.0, "falling": 2.0 }), &[0.0, 5.0, 5.0, -5.0], 1.0);
+        assert_eq!(r, vec![0.0, 1.0, 2.0, 0.0]);
+    }
+
+    fn step_once(block: &str, data: serde_json::Value, dt: f64) -> Value {
+        let data: HashMap<String, serde_json::Value> = serde_json::from_value(data).unwrap();
+        let mut inputs = HashMap::new();
+        inputs.insert("u".to_string(), Value::scalar(1.0));
+        let (_, st) = advance(block, None, &inputs, &data, dt);
+        advance(block, Some(&st), &inputs, &data, dt).0
+    }
+
+    #[test]
+    fn integrator_rejects_inverted_limits() {
+        let v = step_once("ctrl.discrete_integrator", serde_json::json!({ "lower": 1.0, "upper": -1.0 }), 1.0);
+        assert!(matches!(v, Value::Error { .. }), "{:?}", v);
+    }
+
+    #[test]
+    fn rate_limiter_rejects_bad_time_steps() {
+        for dt in [0.0, -1.0, f64::NAN] {
+            let v = step_once("ctrl.rate_limiter", serde_json::json!({ "rising": 1.0, "falling": 1.0 }), dt);
+            assert!(matches!(v, Value::Error { .. }), "dt = {}: {:?}", dt, v);
+        }
+    }
+
+    #[test]
+    fn discrete_tf_first_order_lowpass() {
+        // y_k = 0.5 y_{k-1} + 0.5 u_k  →  H(z) = 0.5 / (1 - 0.5 z⁻¹)
+        let ys = run("ctrl.discrete_tf", serde_json::json!({ "num": [0.5], "den": [1.0, -0.5] }), &[1.0; 4], 1.0);
+        assert_eq!(ys, vec![0.5, 0.75, 0.875, 0.9375]);
+        let data: HashMap<String, serde_json::Value> =
+            serde_json::from_value(serde_json::json!({ "num": [0.0, 1.0], "den": [1.0] })).unwrap();
+        assert!(breaks_cycles("ctrl.discrete_tf", &data));
+    }
+}
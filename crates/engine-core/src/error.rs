@@ -15,6 +15,10 @@ pub enum ErrorCode {
     MissingInput,
     /// Deserialization of the snapshot JSON failed.
     InvalidSnapshot,
+    /// A simulation config is malformed or references missing nodes.
+    InvalidSimConfig,
+    /// A project file is truncated, corrupt, or from an unknown format version.
+    InvalidProject,
 }
 
 impl ErrorCode {
@@ -26,6 +30,8 @@ impl ErrorCode {
             ErrorCode::UnknownBlock => "UNKNOWN_BLOCK",
             ErrorCode::MissingInput => "MISSING_INPUT",
             ErrorCode::InvalidSnapshot => "INVALID_SNAPSHOT",
+            ErrorCode::InvalidSimConfig => "INVALID_SIM_CONFIG",
+            ErrorCode::InvalidProject => "INVALID_PROJECT",
         }
     }
 }
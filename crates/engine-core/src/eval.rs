@@ -19,7 +19,12 @@
 //! pass, any node with remaining `in_degree > 0` is part of a cycle; it is
 //! skipped and a `Diagnostic` is emitted. Downstream nodes of cycle members
 //! receive `Value::Error` via normal error propagation.
+//!
+//! Edges into stateful blocks without direct feedthrough
+//! ([`crate::discrete::breaks_cycles`]) are not counted, so feedback loops
+//! through a unit delay evaluate (with the delay at its initial state).
 
+use crate::expr::{self, FunctionTable};
 use crate::ops::evaluate_node;
 use crate::types::{Diagnostic, DiagLevel, EngineSnapshotV1, EvalResult, Value};
 use std::collections::{HashMap, VecDeque};
@@ -92,6 +97,15 @@ pub fn evaluate(snapshot: &EngineSnapshotV1) -> EvalResult {
         out_adj.entry(node.id.as_str()).or_default();
     }
 
+    // Edges into blocks without direct feedthrough (unit delay, …) do not
+    // constrain the order: those blocks output their initial state here.
+    let breakers: std::collections::HashSet<&str> = snapshot
+        .nodes
+        .iter()
+        .filter(|n| crate::discrete::breaks_cycles(&n.block_type, &n.data))
+        .map(|n| n.id.as_str())
+        .collect();
+
     for edge in &snapshot.edges {
         in_edges
             .entry(edge.target.as_str())
@@ -101,6 +115,9 @@ pub fn evaluate(snapshot: &EngineSnapshotV1) -> EvalResult {
                 edge.source_handle.as_str(),
                 edge.target_handle.as_str(),
             ));
+        if breakers.contains(edge.target.as_str()) {
+            continue;
+        }
         *in_degree.entry(edge.target.as_str()).or_insert(0) += 1;
         out_adj
             .entry(edge.source.as_str())
@@ -152,6 +169,9 @@ pub fn evaluate(snapshot: &EngineSnapshotV1) -> EvalResult {
     let node_map: HashMap<&str, &crate::types::NodeDef> =
         snapshot.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
 
+    // User functions from `expr.define` nodes are visible to every expression.
+    let functions = std::sync::Arc::new(FunctionTable::from_nodes(&snapshot.nodes));
+
     // Evaluate in topological order.
     let mut values: HashMap<String, Value> = HashMap::new();
 
@@ -216,7 +236,20 @@ pub fn evaluate(snapshot: &EngineSnapshotV1) -> EvalResult {
             }
         }
 
-        let result = evaluate_node(&node.block_type, &node_inputs, &node.data);
+        // FMU export compiles the subgraph feeding it, not just its value.
+        let data = if node.block_type == "fmu.export" {
+            crate::fmu_export::with_upstream_graph(
+                node_id,
+                &node.data,
+                |id| node_map.get(id).map(|n| (*n).clone()),
+                |id| snapshot.edges.iter().filter(|e| e.target == id).cloned().collect(),
+            )
+        } else {
+            std::borrow::Cow::Borrowed(&node.data)
+        };
+        let result = expr::with_functions(&functions, || {
+            evaluate_node(&node.block_type, &node_inputs, &data)
+        });
 
         // Report unknown blocks.
         if let Value::Error { ref message } = result {
@@ -7,19 +7,40 @@
 //! - Named variables: a, b, x1, rate, etc.
 //! - Numeric literals: 3.14, 1e-3, .5
 //! - Built-in functions: sqrt, abs, sin, cos, tan, asin, acos, atan,
-//!   ln, log10, exp, ceil, floor, round, min, max
+//!   sinh, cosh, tanh, ln, log10, exp, erf, gamma, ceil, floor, round,
+//!   min, max, pow, atan2
 //! - Constants: pi, e
+//! - Comparisons: <, <=, >, >=, ==, != (1 for true, 0 for false)
+//! - Logic: &&, ||, ! (any nonzero value is true; both sides short-circuit)
+//! - Piecewise: if(cond, a, b) and if(c1, a, c2, b, ..., otherwise);
+//!   only the selected branch is evaluated, and a NaN condition yields NaN
+//! - Local bindings: `let r = sqrt(x^2 + y^2); r * cos(t)`
+//! - Vector elements: `y[i]` (0-based). Looks `y` up in the vector bindings
+//!   passed to [`CompiledExpr::eval_with_vectors`]; otherwise falls back to
+//!   the scalar variable `y<i>`, so `y[0]` is `y0` in ODE equations.
+//! - User functions from a [`FunctionTable`], e.g. `f(x, y) = x^2 + y`.
+//!   A graph defines them once with `expr.define` blocks; while the graph
+//!   evaluates, [`compile`] and [`eval_expr`] see them via [`with_functions`].
 //!
 //! Grammar (recursive descent):
-//!   expr     = term (('+' | '-') term)*
+//!   expr     = 'let' IDENT '=' expr ';' expr | or
+//!   or       = and ('||' and)*
+//!   and      = cmp ('&&' cmp)*
+//!   cmp      = sum (('<' | '<=' | '>' | '>=' | '==' | '!=') sum)?
+//!   sum      = term (('+' | '-') term)*
 //!   term     = power (('*' | '/') power)*
-//!   power    = unary ('^' unary)*
-//!   unary    = '-' unary | call
-//!   call     = IDENT '(' args ')' | atom
+//!   power    = unary ('^' power)?
+//!   unary    = '-' unary | '!' unary | call
+//!   call     = IDENT '(' args ')' | IDENT '[' expr ']' | atom
 //!   atom     = NUMBER | IDENT | '(' expr ')'
 //!   args     = expr (',' expr)*
 
-use std::collections::HashMap;
+use crate::autodiff::Dual;
+use crate::types::NodeDef;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::sync::Arc;
 
 // ── Compiled Expression AST (1.29) ───────────────────────────────────────────
 
@@ -29,18 +50,65 @@ pub enum ExprNode {
     Number(f64),
     Variable(String),
     Constant(f64), // pi, e — resolved at compile time
+    /// `let` binding or user-function parameter, by slot in the call frame.
+    Local(usize),
+    /// Element `name[index]` of a vector variable.
+    Index { name: String, index: Box<ExprNode> },
     BinOp { op: BinOpKind, left: Box<ExprNode>, right: Box<ExprNode> },
     UnaryMinus(Box<ExprNode>),
+    Not(Box<ExprNode>),
     FnCall { name: FnKind, args: Vec<ExprNode> },
+    If { cond: Box<ExprNode>, then: Box<ExprNode>, otherwise: Box<ExprNode> },
+    Let { slot: usize, value: Box<ExprNode>, body: Box<ExprNode> },
+    UserCall { func: Arc<UserFunction>, args: Vec<ExprNode> },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub enum BinOpKind { Add, Sub, Mul, Div, Pow }
+pub enum BinOpKind { Add, Sub, Mul, Div, Pow, Lt, Le, Gt, Ge, Eq, Ne, And, Or }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FnKind {
     Sqrt, Abs, Sin, Cos, Tan, Asin, Acos, Atan, Ln, Log10, Exp,
     Ceil, Floor, Round, Min, Max, Pow2, Atan2,
+    Sinh, Cosh, Tanh, Erf, Gamma,
+}
+
+impl FnKind {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "sqrt"  => FnKind::Sqrt,
+            "abs"   => FnKind::Abs,
+            "sin"   => FnKind::Sin,
+            "cos"   => FnKind::Cos,
+            "tan"   => FnKind::Tan,
+            "asin"  => FnKind::Asin,
+            "acos"  => FnKind::Acos,
+            "atan"  => FnKind::Atan,
+            "sinh"  => FnKind::Sinh,
+            "cosh"  => FnKind::Cosh,
+            "tanh"  => FnKind::Tanh,
+            "ln"    => FnKind::Ln,
+            "log" | "log10" => FnKind::Log10,
+            "exp"   => FnKind::Exp,
+            "erf"   => FnKind::Erf,
+            "gamma" => FnKind::Gamma,
+            "ceil"  => FnKind::Ceil,
+            "floor" => FnKind::Floor,
+            "round" => FnKind::Round,
+            "min"   => FnKind::Min,
+            "max"   => FnKind::Max,
+            "pow"   => FnKind::Pow2,
+            "atan2" => FnKind::Atan2,
+            _ => return None,
+        })
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            FnKind::Min | FnKind::Max | FnKind::Pow2 | FnKind::Atan2 => 2,
+            _ => 1,
+        }
+    }
 }
 
 /// A pre-parsed expression that can be evaluated repeatedly without re-parsing.
@@ -49,12 +117,23 @@ pub enum FnKind {
 #[derive(Debug, Clone)]
 pub struct CompiledExpr {
     root: ExprNode,
+    /// Number of `let` slots the root frame needs.
+    frame_size: usize,
 }
 
 impl CompiledExpr {
     /// Evaluate the compiled expression with the given variable bindings.
     pub fn eval(&self, vars: &HashMap<String, f64>) -> Result<f64, String> {
-        eval_node(&self.root, vars)
+        self.eval_in(&Env { vars, vectors: None })
+    }
+
+    /// Evaluate with scalar bindings plus vector bindings for `name[i]` indexing.
+    pub fn eval_with_vectors(
+        &self,
+        vars: &HashMap<String, f64>,
+        vectors: &HashMap<String, Vec<f64>>,
+    ) -> Result<f64, String> {
+        self.eval_in(&Env { vars, vectors: Some(vectors) })
     }
 
     /// Evaluate the compiled expression using Dual numbers for forward-mode AD.
@@ -62,8 +141,189 @@ impl CompiledExpr {
     /// Supply Dual variables where the tangent component carries the derivative
     /// direction. Use `Dual::variable(v)` for the differentiated input and
     /// `Dual::constant(v)` for all others.
-    pub fn eval_dual(&self, vars: &HashMap<String, crate::autodiff::Dual>) -> Result<crate::autodiff::Dual, String> {
-        eval_node_dual(&self.root, vars)
+    pub fn eval_dual(&self, vars: &HashMap<String, Dual>) -> Result<Dual, String> {
+        self.eval_in(&Env { vars, vectors: None })
+    }
+
+    /// Dual-number counterpart of [`eval_with_vectors`](Self::eval_with_vectors).
+    pub fn eval_dual_with_vectors(
+        &self,
+        vars: &HashMap<String, Dual>,
+        vectors: &HashMap<String, Vec<Dual>>,
+    ) -> Result<Dual, String> {
+        self.eval_in(&Env { vars, vectors: Some(vectors) })
+    }
+
+    /// Variables the expression may read, including through user functions.
+    pub fn dependencies(&self) -> Dependencies {
+        let mut deps = Dependencies::default();
+        collect_dependencies(&self.root, &mut deps);
+        deps
+    }
+
+    /// Translate to a C99 expression over `<math.h>` (used by FMU export).
+    ///
+    /// `resolve` maps each variable — `name[k]` with a literal `k` as
+    /// `name{k}` — to C code. `let` bindings and inlined user-function
+    /// parameters live in `cs_let[i]`; the returned count is how many slots
+    /// the caller must declare.
+    pub fn to_c(&self, resolve: &dyn Fn(&str) -> Option<String>) -> Result<(String, usize), String> {
+        let mut next = self.frame_size;
+        let code = emit_c(&self.root, 0, resolve, &mut next)?;
+        Ok((code, next))
+    }
+
+    fn eval_in<T: Scalar>(&self, env: &Env<T>) -> Result<T, String> {
+        let mut frame = vec![T::lift(0.0); self.frame_size];
+        eval_node(&self.root, env, &mut frame)
+    }
+}
+
+/// C code for `node` whose frame starts at slot `base`. Every user-function
+/// call gets a fresh frame from `next`, so nested calls never share slots.
+fn emit_c(
+    node: &ExprNode,
+    base: usize,
+    resolve: &dyn Fn(&str) -> Option<String>,
+    next: &mut usize,
+) -> Result<String, String> {
+    let var = |name: &str| resolve(name).ok_or_else(|| format!("Unknown variable: {name}"));
+    let num = |n: f64| {
+        if n.is_nan() {
+            "NAN".to_string()
+        } else if n.is_infinite() {
+            if n > 0.0 { "INFINITY".to_string() } else { "(-INFINITY)".to_string() }
+        } else {
+            format!("{n:?}")
+        }
+    };
+    Ok(match node {
+        ExprNode::Number(n) | ExprNode::Constant(n) => num(*n),
+        ExprNode::Variable(name) => var(name)?,
+        ExprNode::Local(slot) => format!("cs_let[{}]", base + slot),
+        ExprNode::Index { name, index } => match index.as_ref() {
+            ExprNode::Number(i) if *i >= 0.0 && i.fract() == 0.0 => var(&format!("{name}{}", *i as usize))?,
+            _ => return Err(format!("Index of {name} must be a literal for C code generation")),
+        },
+        ExprNode::UnaryMinus(inner) => format!("(-{})", emit_c(inner, base, resolve, next)?),
+        ExprNode::Not(inner) => format!("({} == 0.0 ? 1.0 : 0.0)", emit_c(inner, base, resolve, next)?),
+        ExprNode::BinOp { op, left, right } => {
+            let l = emit_c(left, base, resolve, next)?;
+            let r = emit_c(right, base, resolve, next)?;
+            match op {
+                BinOpKind::Add => format!("({l} + {r})"),
+                BinOpKind::Sub => format!("({l} - {r})"),
+                BinOpKind::Mul => format!("({l} * {r})"),
+                BinOpKind::Div => format!("({l} / {r})"),
+                BinOpKind::Pow => format!("pow({l}, {r})"),
+                BinOpKind::Lt => format!("({l} < {r} ? 1.0 : 0.0)"),
+                BinOpKind::Le => format!("({l} <= {r} ? 1.0 : 0.0)"),
+                BinOpKind::Gt => format!("({l} > {r} ? 1.0 : 0.0)"),
+                BinOpKind::Ge => format!("({l} >= {r} ? 1.0 : 0.0)"),
+                BinOpKind::Eq => format!("({l} == {r} ? 1.0 : 0.0)"),
+                BinOpKind::Ne => format!("({l} != {r} ? 1.0 : 0.0)"),
+                BinOpKind::And => format!("({l} != 0.0 && {r} != 0.0 ? 1.0 : 0.0)"),
+                BinOpKind::Or => format!("({l} != 0.0 || {r} != 0.0 ? 1.0 : 0.0)"),
+            }
+        }
+        ExprNode::FnCall { name, args } => {
+            let args = args.iter().map(|a| emit_c(a, base, resolve, next)).collect::<Result<Vec<_>, _>>()?;
+            let f = match name {
+                FnKind::Sqrt => "sqrt",
+                FnKind::Abs => "fabs",
+                FnKind::Sin => "sin",
+                FnKind::Cos => "cos",
+                FnKind::Tan => "tan",
+                FnKind::Asin => "asin",
+                FnKind::Acos => "acos",
+                FnKind::Atan => "atan",
+                FnKind::Sinh => "sinh",
+                FnKind::Cosh => "cosh",
+                FnKind::Tanh => "tanh",
+                FnKind::Ln => "log",
+                FnKind::Log10 => "log10",
+                FnKind::Exp => "exp",
+                FnKind::Erf => "erf",
+                FnKind::Gamma => "tgamma",
+                FnKind::Ceil => "ceil",
+                FnKind::Floor => "floor",
+                FnKind::Round => "round",
+                FnKind::Min => "fmin",
+                FnKind::Max => "fmax",
+                FnKind::Pow2 => "pow",
+                FnKind::Atan2 => "atan2",
+            };
+            format!("{f}({})", args.join(", "))
+        }
+        ExprNode::If { cond, then, otherwise } => {
+            let c = emit_c(cond, base, resolve, next)?;
+            let a = emit_c(then, base, resolve, next)?;
+            let b = emit_c(otherwise, base, resolve, next)?;
+            format!("(isnan({c}) ? NAN : {c} != 0.0 ? {a} : {b})")
+        }
+        ExprNode::Let { slot, value, body } => {
+            let v = emit_c(value, base, resolve, next)?;
+            let b = emit_c(body, base, resolve, next)?;
+            format!("(cs_let[{}] = {v}, {b})", base + slot)
+        }
+        ExprNode::UserCall { func, args } => {
+            let callee = *next;
+            *next += func.frame_size;
+            let mut parts = Vec::with_capacity(args.len() + 1);
+            for (slot, a) in args.iter().enumerate() {
+                parts.push(format!("cs_let[{}] = {}", callee + slot, emit_c(a, base, resolve, next)?));
+            }
+            parts.push(emit_c(&func.body, callee, resolve, next)?);
+            format!("({})", parts.join(", "))
+        }
+    })
+}
+
+/// Variables read by a [`CompiledExpr`], for structural analysis such as
+/// Jacobian sparsity detection.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dependencies {
+    /// Scalar variables. `name[k]` with a literal `k` is recorded as `name{k}`.
+    pub scalars: BTreeSet<String>,
+    /// Vectors indexed by a computed subscript, so any element may be read.
+    pub vectors: BTreeSet<String>,
+}
+
+fn collect_dependencies(node: &ExprNode, deps: &mut Dependencies) {
+    match node {
+        ExprNode::Number(_) | ExprNode::Constant(_) | ExprNode::Local(_) => {}
+        ExprNode::Variable(name) => {
+            deps.scalars.insert(name.clone());
+        }
+        ExprNode::Index { name, index } => match index.as_ref() {
+            ExprNode::Number(i) if *i >= 0.0 && i.fract() == 0.0 => {
+                deps.scalars.insert(format!("{name}{}", *i as usize));
+            }
+            other => {
+                deps.vectors.insert(name.clone());
+                collect_dependencies(other, deps);
+            }
+        },
+        ExprNode::BinOp { left, right, .. } => {
+            collect_dependencies(left, deps);
+            collect_dependencies(right, deps);
+        }
+        ExprNode::UnaryMinus(inner) | ExprNode::Not(inner) => collect_dependencies(inner, deps),
+        ExprNode::FnCall { args, .. } => args.iter().for_each(|a| collect_dependencies(a, deps)),
+        ExprNode::If { cond, then, otherwise } => {
+            collect_dependencies(cond, deps);
+            collect_dependencies(then, deps);
+            collect_dependencies(otherwise, deps);
+        }
+        ExprNode::Let { value, body, .. } => {
+            collect_dependencies(value, deps);
+            collect_dependencies(body, deps);
+        }
+        ExprNode::UserCall { func, args } => {
+            args.iter().for_each(|a| collect_dependencies(a, deps));
+            // Names in the body that are not parameters are the caller's variables.
+            collect_dependencies(&func.body, deps);
+        }
     }
 }
 
@@ -72,116 +332,518 @@ impl CompiledExpr {
 /// Returns an error if the formula cannot be parsed. After compilation,
 /// calling `eval()` is significantly faster than `eval_expr()` for repeated
 /// evaluations (no tokenisation/parsing overhead).
+///
+/// User functions installed with [`with_functions`] are callable.
 pub fn compile(formula: &str) -> Result<CompiledExpr, String> {
+    AMBIENT_FUNCTIONS.with(|f| match f.borrow().as_ref() {
+        Some(table) => compile_with(formula, table),
+        None => compile_with(formula, &FunctionTable::new()),
+    })
+}
+
+/// Compile a formula that may call the user functions in `functions`.
+pub fn compile_with(formula: &str, functions: &FunctionTable) -> Result<CompiledExpr, String> {
     let tokens = tokenize(formula)?;
-    let mut parser = AstParser { tokens: &tokens, pos: 0 };
+    let mut parser = AstParser::new(&tokens, functions, Vec::new());
     let root = parser.parse_expr()?;
     if parser.pos < parser.tokens.len() {
         return Err(format!("Unexpected token at position {}", parser.pos));
     }
-    Ok(CompiledExpr { root })
+    Ok(CompiledExpr { root, frame_size: parser.frame_size })
 }
 
-fn eval_node_dual(node: &ExprNode, vars: &HashMap<String, crate::autodiff::Dual>) -> Result<crate::autodiff::Dual, String> {
-    use crate::autodiff::Dual;
-    match node {
-        ExprNode::Number(n) => Ok(Dual::constant(*n)),
-        ExprNode::Constant(c) => Ok(Dual::constant(*c)),
-        ExprNode::Variable(name) => vars.get(name.as_str())
-            .copied()
-            .ok_or_else(|| format!("Unknown variable: {name}")),
-        ExprNode::UnaryMinus(inner) => Ok(-eval_node_dual(inner, vars)?),
-        ExprNode::BinOp { op, left, right } => {
-            let l = eval_node_dual(left, vars)?;
-            let r = eval_node_dual(right, vars)?;
-            Ok(match op {
-                BinOpKind::Add => l + r,
-                BinOpKind::Sub => l - r,
-                BinOpKind::Mul => l * r,
-                BinOpKind::Div => l / r,
-                BinOpKind::Pow => l.pow_dual(r),
-            })
+/// Evaluate a formula string with the given variable bindings.
+/// Returns f64 (may be NaN or Inf for invalid operations).
+pub fn eval_expr(formula: &str, vars: &HashMap<String, f64>) -> Result<f64, String> {
+    compile(formula)?.eval(vars)
+}
+
+// ── User functions ───────────────────────────────────────────────────────────
+
+/// Block type that defines a graph-level user function.
+///
+/// Node data: `name` (identifier), `params` (array of names or a
+/// comma-separated string) and `body` (an expression over the params).
+pub const DEFINE_BLOCK: &str = "expr.define";
+
+/// A user-defined function `name(params...) = body`.
+#[derive(Debug)]
+pub struct UserFunction {
+    name: String,
+    params: Vec<String>,
+    source: String,
+    body: ExprNode,
+    frame_size: usize,
+}
+
+impl UserFunction {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn params(&self) -> &[String] {
+        &self.params
+    }
+
+    /// Human-readable definition, e.g. `f(x, y) = x^2 + y`.
+    pub fn signature(&self) -> String {
+        format!("{}({}) = {}", self.name, self.params.join(", "), self.source.trim())
+    }
+}
+
+/// A set of user functions available to [`compile_with`].
+///
+/// Functions may call built-ins and functions defined before them, which rules
+/// out recursion. Names not bound as parameters refer to the caller's variables.
+#[derive(Debug, Clone, Default)]
+pub struct FunctionTable {
+    defs: HashMap<String, Arc<UserFunction>>,
+}
+
+impl FunctionTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Define `name(params...) = body`. Fails if the name is taken or the body
+    /// does not compile.
+    pub fn define(&mut self, name: &str, params: &[String], body: &str) -> Result<(), String> {
+        check_identifier(name)?;
+        if FnKind::from_name(name).is_some() || name == "if" {
+            return Err(format!("'{name}' is a built-in function"));
         }
-        ExprNode::FnCall { name, args } => {
-            let vals: Result<Vec<Dual>, String> = args.iter().map(|a| eval_node_dual(a, vars)).collect();
-            let vals = vals?;
-            Ok(match name {
-                FnKind::Sqrt  => vals[0].sqrt(),
-                FnKind::Abs   => vals[0].abs(),
-                FnKind::Sin   => vals[0].sin(),
-                FnKind::Cos   => vals[0].cos(),
-                FnKind::Tan   => vals[0].tan(),
-                FnKind::Asin  => vals[0].asin(),
-                FnKind::Acos  => vals[0].acos(),
-                FnKind::Atan  => vals[0].atan(),
-                FnKind::Ln    => vals[0].ln(),
-                FnKind::Log10 => vals[0].log10(),
-                FnKind::Exp   => vals[0].exp(),
-                FnKind::Ceil  => Dual::constant(vals[0].val.ceil()),  // non-smooth: zero grad
-                FnKind::Floor => Dual::constant(vals[0].val.floor()),
-                FnKind::Round => Dual::constant(vals[0].val.round()),
-                FnKind::Min   => vals[0].min(vals[1]),
-                FnKind::Max   => vals[0].max(vals[1]),
-                FnKind::Pow2  => vals[0].pow_dual(vals[1]),
-                FnKind::Atan2 => vals[0].atan2(vals[1]),
-            })
+        if self.defs.contains_key(name) {
+            return Err(format!("'{name}' is already defined"));
+        }
+        for (i, p) in params.iter().enumerate() {
+            check_identifier(p)?;
+            if params[..i].contains(p) {
+                return Err(format!("Duplicate parameter '{p}' in {name}()"));
+            }
+        }
+        let tokens = tokenize(body)?;
+        let scope = params.iter().enumerate().map(|(i, p)| (p.clone(), i)).collect();
+        let mut parser = AstParser::new(&tokens, self, scope);
+        let root = parser.parse_expr()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(format!("Unexpected token at position {}", parser.pos));
+        }
+        let func = UserFunction {
+            name: name.to_string(),
+            params: params.to_vec(),
+            source: body.to_string(),
+            body: root,
+            frame_size: parser.frame_size,
+        };
+        self.defs.insert(name.to_string(), Arc::new(func));
+        Ok(())
+    }
+
+    /// Define several functions in dependency order. A definition that calls a
+    /// later one is retried until nothing more can be defined; the errors of
+    /// the remaining definitions are returned by index.
+    pub fn define_all(&mut self, defs: &[Definition]) -> HashMap<usize, String> {
+        let mut pending: Vec<usize> = (0..defs.len()).collect();
+        let mut errors = HashMap::new();
+        loop {
+            errors.clear();
+            let before = pending.len();
+            pending.retain(|&i| {
+                let (name, params, body) = &defs[i];
+                match self.define(name, params, body) {
+                    Ok(()) => false,
+                    Err(e) => {
+                        errors.insert(i, e);
+                        true
+                    }
+                }
+            });
+            if pending.is_empty() || pending.len() == before {
+                return errors;
+            }
         }
     }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<UserFunction>> {
+        self.defs.get(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.defs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.defs.is_empty()
+    }
+
+    /// Build the table defined by the [`DEFINE_BLOCK`] nodes among `nodes`.
+    pub fn from_nodes<'a>(nodes: impl IntoIterator<Item = &'a NodeDef>) -> Self {
+        let mut table = Self::new();
+        table.define_all(&definitions_in(nodes));
+        table
+    }
+}
+
+/// `(name, params, body)` of a user-function definition.
+pub type Definition = (String, Vec<String>, String);
+
+/// Definitions of every [`DEFINE_BLOCK`] node, in node-id order.
+pub fn definitions_in<'a>(nodes: impl IntoIterator<Item = &'a NodeDef>) -> Vec<Definition> {
+    let mut defining: Vec<&NodeDef> =
+        nodes.into_iter().filter(|n| n.block_type == DEFINE_BLOCK).collect();
+    defining.sort_by(|a, b| a.id.cmp(&b.id));
+    defining.iter().map(|n| definition_from_data(&n.data)).collect()
+}
+
+/// Read `(name, params, body)` from the data of a [`DEFINE_BLOCK`] node.
+pub fn definition_from_data(data: &HashMap<String, serde_json::Value>) -> Definition {
+    let text = |key: &str| data.get(key).and_then(|v| v.as_str()).unwrap_or("").trim().to_string();
+    let params = match data.get("params") {
+        Some(serde_json::Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .collect(),
+        Some(serde_json::Value::String(s)) => s
+            .split(',')
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    };
+    (text("name"), params, text("body"))
+}
+
+/// Check a definition against the functions currently installed by
+/// [`with_functions`], returning its signature or why it is unusable.
+pub fn check_definition(name: &str, params: &[String], body: &str) -> Result<String, String> {
+    AMBIENT_FUNCTIONS.with(|f| {
+        let ambient = f.borrow();
+        if let Some(existing) = ambient.as_ref().and_then(|t| t.get(name)) {
+            return if existing.params == params && existing.source.trim() == body.trim() {
+                Ok(existing.signature())
+            } else {
+                Err(format!("'{name}' is already defined by another block"))
+            };
+        }
+        let mut table = ambient.as_deref().cloned().unwrap_or_default();
+        table.define(name, params, body)?;
+        Ok(table.get(name).map(|d| d.signature()).unwrap_or_default())
+    })
+}
+
+thread_local! {
+    static AMBIENT_FUNCTIONS: RefCell<Option<Arc<FunctionTable>>> = const { RefCell::new(None) };
+}
+
+/// Run `f` with `table` visible to [`compile`] and [`eval_expr`] on this thread.
+///
+/// Block evaluation compiles expressions deep inside solvers, so the graph
+/// installs its user functions here rather than threading them through every
+/// call site.
+pub fn with_functions<R>(table: &Arc<FunctionTable>, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<Arc<FunctionTable>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let prev = self.0.take();
+            AMBIENT_FUNCTIONS.with(|a| *a.borrow_mut() = prev);
+        }
+    }
+    let prev = AMBIENT_FUNCTIONS.with(|a| a.borrow_mut().replace(Arc::clone(table)));
+    let _restore = Restore(prev);
+    f()
+}
+
+fn check_identifier(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let valid = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("Invalid name: '{name}'"));
+    }
+    if matches!(name, "let" | "pi" | "PI" | "e" | "E") {
+        return Err(format!("'{name}' is reserved"));
+    }
+    Ok(())
+}
+
+// ── Evaluation ───────────────────────────────────────────────────────────────
+
+/// Number type the AST can be evaluated over: `f64` for values, [`Dual`] for
+/// forward-mode derivatives. Sharing one evaluator keeps the two consistent.
+trait Scalar:
+    Copy + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self> + Neg<Output = Self>
+{
+    fn lift(v: f64) -> Self;
+    fn value(self) -> f64;
+    fn pow(self, exp: Self) -> Self;
+    fn call(kind: FnKind, args: &[Self]) -> Self;
+}
+
+impl Scalar for f64 {
+    fn lift(v: f64) -> Self { v }
+    fn value(self) -> f64 { self }
+    fn pow(self, exp: Self) -> Self { self.powf(exp) }
+
+    fn call(kind: FnKind, args: &[Self]) -> Self {
+        let x = args[0];
+        match kind {
+            FnKind::Sqrt  => x.sqrt(),
+            FnKind::Abs   => x.abs(),
+            FnKind::Sin   => x.sin(),
+            FnKind::Cos   => x.cos(),
+            FnKind::Tan   => x.tan(),
+            FnKind::Asin  => x.asin(),
+            FnKind::Acos  => x.acos(),
+            FnKind::Atan  => x.atan(),
+            FnKind::Sinh  => x.sinh(),
+            FnKind::Cosh  => x.cosh(),
+            FnKind::Tanh  => x.tanh(),
+            FnKind::Ln    => x.ln(),
+            FnKind::Log10 => x.log10(),
+            FnKind::Exp   => x.exp(),
+            FnKind::Erf   => erf(x),
+            FnKind::Gamma => gamma(x),
+            FnKind::Ceil  => x.ceil(),
+            FnKind::Floor => x.floor(),
+            FnKind::Round => x.round(),
+            FnKind::Min   => x.min(args[1]),
+            FnKind::Max   => x.max(args[1]),
+            FnKind::Pow2  => x.powf(args[1]),
+            FnKind::Atan2 => x.atan2(args[1]),
+        }
+    }
+}
+
+impl Scalar for Dual {
+    fn lift(v: f64) -> Self { Dual::constant(v) }
+    fn value(self) -> f64 { self.val }
+
+    fn pow(self, exp: Self) -> Self {
+        // A constant exponent avoids ln(base), which is NaN for negative bases.
+        if exp.dot == 0.0 { self.powf(exp.val) } else { self.pow_dual(exp) }
+    }
+
+    fn call(kind: FnKind, args: &[Self]) -> Self {
+        let x = args[0];
+        match kind {
+            FnKind::Sqrt  => x.sqrt(),
+            FnKind::Abs   => x.abs(),
+            FnKind::Sin   => x.sin(),
+            FnKind::Cos   => x.cos(),
+            FnKind::Tan   => x.tan(),
+            FnKind::Asin  => x.asin(),
+            FnKind::Acos  => x.acos(),
+            FnKind::Atan  => x.atan(),
+            FnKind::Sinh  => x.sinh(),
+            FnKind::Cosh  => x.cosh(),
+            FnKind::Tanh  => x.tanh(),
+            FnKind::Ln    => x.ln(),
+            FnKind::Log10 => x.log10(),
+            FnKind::Exp   => x.exp(),
+            FnKind::Erf   => x.erf(),
+            FnKind::Gamma => x.gamma(),
+            FnKind::Ceil  => Dual::constant(x.val.ceil()),  // non-smooth: zero grad
+            FnKind::Floor => Dual::constant(x.val.floor()),
+            FnKind::Round => Dual::constant(x.val.round()),
+            FnKind::Min   => x.min(args[1]),
+            FnKind::Max   => x.max(args[1]),
+            FnKind::Pow2  => Scalar::pow(x, args[1]),
+            FnKind::Atan2 => x.atan2(args[1]),
+        }
+    }
+}
+
+struct Env<'a, T> {
+    vars: &'a HashMap<String, T>,
+    vectors: Option<&'a HashMap<String, Vec<T>>>,
+}
+
+fn truth<T: Scalar>(b: bool) -> T {
+    T::lift(if b { 1.0 } else { 0.0 })
 }
 
-fn eval_node(node: &ExprNode, vars: &HashMap<String, f64>) -> Result<f64, String> {
+fn eval_node<T: Scalar>(node: &ExprNode, env: &Env<T>, frame: &mut [T]) -> Result<T, String> {
     match node {
-        ExprNode::Number(n) => Ok(*n),
-        ExprNode::Constant(c) => Ok(*c),
-        ExprNode::Variable(name) => vars.get(name.as_str())
+        ExprNode::Number(n) => Ok(T::lift(*n)),
+        ExprNode::Constant(c) => Ok(T::lift(*c)),
+        ExprNode::Variable(name) => env.vars.get(name.as_str())
             .copied()
             .ok_or_else(|| format!("Unknown variable: {name}")),
-        ExprNode::UnaryMinus(inner) => Ok(-eval_node(inner, vars)?),
+        ExprNode::Local(slot) => Ok(frame[*slot]),
+        ExprNode::Index { name, index } => {
+            let i = eval_node(index, env, frame)?.value();
+            if !(i >= 0.0 && i.fract() == 0.0) {
+                return Err(format!("Index of {name} must be a non-negative integer, got {i}"));
+            }
+            let i = i as usize;
+            if let Some(v) = env.vectors.and_then(|m| m.get(name.as_str())) {
+                return v.get(i).copied().ok_or_else(|| {
+                    format!("Index {i} out of bounds for {name} (length {})", v.len())
+                });
+            }
+            env.vars.get(&format!("{name}{i}"))
+                .copied()
+                .ok_or_else(|| format!("Unknown vector element: {name}[{i}]"))
+        }
+        ExprNode::UnaryMinus(inner) => Ok(-eval_node(inner, env, frame)?),
+        ExprNode::Not(inner) => Ok(truth(eval_node(inner, env, frame)?.value() == 0.0)),
+        ExprNode::BinOp { op: BinOpKind::And, left, right } => {
+            if eval_node(left, env, frame)?.value() == 0.0 {
+                return Ok(T::lift(0.0));
+            }
+            Ok(truth(eval_node(right, env, frame)?.value() != 0.0))
+        }
+        ExprNode::BinOp { op: BinOpKind::Or, left, right } => {
+            if eval_node(left, env, frame)?.value() != 0.0 {
+                return Ok(T::lift(1.0));
+            }
+            Ok(truth(eval_node(right, env, frame)?.value() != 0.0))
+        }
         ExprNode::BinOp { op, left, right } => {
-            let l = eval_node(left, vars)?;
-            let r = eval_node(right, vars)?;
+            let l = eval_node(left, env, frame)?;
+            let r = eval_node(right, env, frame)?;
             Ok(match op {
                 BinOpKind::Add => l + r,
                 BinOpKind::Sub => l - r,
                 BinOpKind::Mul => l * r,
                 BinOpKind::Div => l / r,
-                BinOpKind::Pow => l.powf(r),
+                BinOpKind::Pow => l.pow(r),
+                // Comparisons are piecewise constant: zero derivative.
+                BinOpKind::Lt => truth(l.value() < r.value()),
+                BinOpKind::Le => truth(l.value() <= r.value()),
+                BinOpKind::Gt => truth(l.value() > r.value()),
+                BinOpKind::Ge => truth(l.value() >= r.value()),
+                BinOpKind::Eq => truth(l.value() == r.value()),
+                BinOpKind::Ne => truth(l.value() != r.value()),
+                BinOpKind::And | BinOpKind::Or => unreachable!("short-circuited above"),
             })
         }
         ExprNode::FnCall { name, args } => {
-            let vals: Result<Vec<f64>, String> = args.iter().map(|a| eval_node(a, vars)).collect();
-            let vals = vals?;
-            Ok(match name {
-                FnKind::Sqrt  => vals[0].sqrt(),
-                FnKind::Abs   => vals[0].abs(),
-                FnKind::Sin   => vals[0].sin(),
-                FnKind::Cos   => vals[0].cos(),
-                FnKind::Tan   => vals[0].tan(),
-                FnKind::Asin  => vals[0].asin(),
-                FnKind::Acos  => vals[0].acos(),
-                FnKind::Atan  => vals[0].atan(),
-                FnKind::Ln    => vals[0].ln(),
-                FnKind::Log10 => vals[0].log10(),
-                FnKind::Exp   => vals[0].exp(),
-                FnKind::Ceil  => vals[0].ceil(),
-                FnKind::Floor => vals[0].floor(),
-                FnKind::Round => vals[0].round(),
-                FnKind::Min   => vals[0].min(vals[1]),
-                FnKind::Max   => vals[0].max(vals[1]),
-                FnKind::Pow2  => vals[0].powf(vals[1]),
-                FnKind::Atan2 => vals[0].atan2(vals[1]),
-            })
+            let vals = args.iter().map(|a| eval_node(a, env, frame)).collect::<Result<Vec<T>, String>>()?;
+            Ok(T::call(*name, &vals))
+        }
+        ExprNode::If { cond, then, otherwise } => {
+            let c = eval_node(cond, env, frame)?.value();
+            if c.is_nan() {
+                Ok(T::lift(f64::NAN))
+            } else if c != 0.0 {
+                eval_node(then, env, frame)
+            } else {
+                eval_node(otherwise, env, frame)
+            }
+        }
+        ExprNode::Let { slot, value, body } => {
+            frame[*slot] = eval_node(value, env, frame)?;
+            eval_node(body, env, frame)
+        }
+        ExprNode::UserCall { func, args } => {
+            let mut callee = vec![T::lift(0.0); func.frame_size];
+            for (slot, a) in args.iter().enumerate() {
+                callee[slot] = eval_node(a, env, frame)?;
+            }
+            eval_node(&func.body, env, &mut callee)
         }
     }
 }
 
+// ── Special functions ────────────────────────────────────────────────────────
+
+/// Error function. Maclaurin series for |x| < 2.5, continued fraction for
+/// erfc beyond; accurate to ~1e-15.
+pub(crate) fn erf(x: f64) -> f64 {
+    if x.is_nan() {
+        return f64::NAN;
+    }
+    let ax = x.abs();
+    if ax < 2.5 {
+        let x2 = x * x;
+        let mut term = x;
+        let mut sum = x;
+        for n in 1..200 {
+            term *= -x2 / n as f64;
+            let c = term / (2 * n + 1) as f64;
+            sum += c;
+            if c.abs() <= 1e-17 * sum.abs() {
+                break;
+            }
+        }
+        sum * std::f64::consts::FRAC_2_SQRT_PI
+    } else {
+        // erfc(x) = e^{-x²}/√π · 1/(x + (1/2)/(x + 1/(x + (3/2)/(x + …))))
+        let mut f = ax;
+        for n in (1..=60).rev() {
+            f = ax + (n as f64 * 0.5) / f;
+        }
+        let erfc = (-ax * ax).exp() / (f * std::f64::consts::PI.sqrt());
+        x.signum() * (1.0 - erfc)
+    }
+}
+
+/// Gamma function (Lanczos approximation, g = 7, n = 9) with reflection for x < 0.5.
+#[allow(clippy::excessive_precision)]
+pub(crate) fn gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const C: [f64; 9] = [
+        0.99999999999980993,
+        676.5203681218851,
+        -1259.1392167224028,
+        771.32342877765313,
+        -176.61502916214059,
+        12.507343278686905,
+        -0.13857109526572012,
+        9.9843695780195716e-6,
+        1.5056327351493116e-7,
+    ];
+    if x <= 0.0 && x.fract() == 0.0 {
+        return f64::NAN; // poles at 0, -1, -2, …
+    }
+    if x < 0.5 {
+        return std::f64::consts::PI / ((std::f64::consts::PI * x).sin() * gamma(1.0 - x));
+    }
+    let x = x - 1.0;
+    let t = x + G + 0.5;
+    let a = C[1..].iter().enumerate().fold(C[0], |acc, (i, c)| acc + c / (x + (i + 1) as f64));
+    (2.0 * std::f64::consts::PI).sqrt() * ((x + 0.5) * t.ln() - t).exp() * a
+}
+
+/// Digamma ψ(x) = Γ'(x)/Γ(x): recurrence up to x ≥ 6, then the asymptotic series.
+pub(crate) fn digamma(x: f64) -> f64 {
+    if x <= 0.0 && x.fract() == 0.0 {
+        return f64::NAN;
+    }
+    if x < 0.0 {
+        return digamma(1.0 - x) - std::f64::consts::PI / (std::f64::consts::PI * x).tan();
+    }
+    let mut x = x;
+    let mut r = 0.0;
+    while x < 6.0 {
+        r -= 1.0 / x;
+        x += 1.0;
+    }
+    let f = 1.0 / (x * x);
+    r + x.ln() - 0.5 / x
+        - f * (1.0 / 12.0 - f * (1.0 / 120.0 - f * (1.0 / 252.0 - f * (1.0 / 240.0 - f / 132.0))))
+}
+
+// ── Parser ───────────────────────────────────────────────────────────────────
+
 /// AST-building parser (compile-time, not eval-time).
 struct AstParser<'a> {
     tokens: &'a [Token],
     pos: usize,
+    functions: &'a FunctionTable,
+    /// Names bound by enclosing `let`s and function parameters → frame slot.
+    scope: Vec<(String, usize)>,
+    frame_size: usize,
 }
 
 impl<'a> AstParser<'a> {
+    fn new(tokens: &'a [Token], functions: &'a FunctionTable, scope: Vec<(String, usize)>) -> Self {
+        let frame_size = scope.len();
+        AstParser { tokens, pos: 0, functions, scope, frame_size }
+    }
+
     fn peek(&self) -> Option<&Token> { self.tokens.get(self.pos) }
     fn advance(&mut self) -> Option<&Token> {
         let tok = self.tokens.get(self.pos);
@@ -196,12 +858,73 @@ impl<'a> AstParser<'a> {
         }
     }
 
+    fn binop(op: BinOpKind, left: ExprNode, right: ExprNode) -> ExprNode {
+        ExprNode::BinOp { op, left: Box::new(left), right: Box::new(right) }
+    }
+
+    // expr = 'let' IDENT '=' expr ';' expr | or
     fn parse_expr(&mut self) -> Result<ExprNode, String> {
+        if !matches!(self.peek(), Some(Token::Ident(kw)) if kw == "let") {
+            return self.parse_or();
+        }
+        self.advance();
+        let name = match self.advance() {
+            Some(Token::Ident(name)) => name.clone(),
+            Some(tok) => return Err(format!("Expected a name after 'let', got {:?}", tok)),
+            None => return Err("Expected a name after 'let'".to_string()),
+        };
+        check_identifier(&name)?;
+        self.expect(&Token::Assign)?;
+        let value = self.parse_expr()?;
+        self.expect(&Token::Semicolon)?;
+        let slot = self.frame_size;
+        self.frame_size += 1;
+        self.scope.push((name, slot));
+        let body = self.parse_expr();
+        self.scope.pop();
+        Ok(ExprNode::Let { slot, value: Box::new(value), body: Box::new(body?) })
+    }
+
+    fn parse_or(&mut self) -> Result<ExprNode, String> {
+        let mut left = self.parse_and()?;
+        while let Some(Token::OrOr) = self.peek() {
+            self.advance();
+            left = Self::binop(BinOpKind::Or, left, self.parse_and()?);
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<ExprNode, String> {
+        let mut left = self.parse_cmp()?;
+        while let Some(Token::AndAnd) = self.peek() {
+            self.advance();
+            left = Self::binop(BinOpKind::And, left, self.parse_cmp()?);
+        }
+        Ok(left)
+    }
+
+    // Comparisons do not chain: `a < b < c` is a syntax error.
+    fn parse_cmp(&mut self) -> Result<ExprNode, String> {
+        let left = self.parse_sum()?;
+        let op = match self.peek() {
+            Some(Token::Lt) => BinOpKind::Lt,
+            Some(Token::Le) => BinOpKind::Le,
+            Some(Token::Gt) => BinOpKind::Gt,
+            Some(Token::Ge) => BinOpKind::Ge,
+            Some(Token::EqEq) => BinOpKind::Eq,
+            Some(Token::NotEq) => BinOpKind::Ne,
+            _ => return Ok(left),
+        };
+        self.advance();
+        Ok(Self::binop(op, left, self.parse_sum()?))
+    }
+
+    fn parse_sum(&mut self) -> Result<ExprNode, String> {
         let mut left = self.parse_term()?;
         while let Some(tok) = self.peek() {
             match tok {
-                Token::Plus  => { self.advance(); left = ExprNode::BinOp { op: BinOpKind::Add, left: Box::new(left), right: Box::new(self.parse_term()?) }; }
-                Token::Minus => { self.advance(); left = ExprNode::BinOp { op: BinOpKind::Sub, left: Box::new(left), right: Box::new(self.parse_term()?) }; }
+                Token::Plus  => { self.advance(); left = Self::binop(BinOpKind::Add, left, self.parse_term()?); }
+                Token::Minus => { self.advance(); left = Self::binop(BinOpKind::Sub, left, self.parse_term()?); }
                 _ => break,
             }
         }
@@ -212,38 +935,47 @@ impl<'a> AstParser<'a> {
         let mut left = self.parse_power()?;
         while let Some(tok) = self.peek() {
             match tok {
-                Token::Star  => { self.advance(); left = ExprNode::BinOp { op: BinOpKind::Mul, left: Box::new(left), right: Box::new(self.parse_power()?) }; }
-                Token::Slash => { self.advance(); left = ExprNode::BinOp { op: BinOpKind::Div, left: Box::new(left), right: Box::new(self.parse_power()?) }; }
+                Token::Star  => { self.advance(); left = Self::binop(BinOpKind::Mul, left, self.parse_power()?); }
+                Token::Slash => { self.advance(); left = Self::binop(BinOpKind::Div, left, self.parse_power()?); }
                 _ => break,
             }
         }
         Ok(left)
     }
 
+    // power = unary ('^' power)?   (right-associative)
     fn parse_power(&mut self) -> Result<ExprNode, String> {
         let base = self.parse_unary()?;
         if let Some(Token::Caret) = self.peek() {
             self.advance();
             let exp = self.parse_power()?;
-            Ok(ExprNode::BinOp { op: BinOpKind::Pow, left: Box::new(base), right: Box::new(exp) })
+            Ok(Self::binop(BinOpKind::Pow, base, exp))
         } else {
             Ok(base)
         }
     }
 
     fn parse_unary(&mut self) -> Result<ExprNode, String> {
-        if let Some(Token::Minus) = self.peek() {
-            self.advance();
-            Ok(ExprNode::UnaryMinus(Box::new(self.parse_unary()?)))
-        } else {
-            self.parse_call()
+        match self.peek() {
+            Some(Token::Minus) => {
+                self.advance();
+                Ok(ExprNode::UnaryMinus(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::Bang) => {
+                self.advance();
+                Ok(ExprNode::Not(Box::new(self.parse_unary()?)))
+            }
+            _ => self.parse_call(),
         }
     }
 
     fn parse_call(&mut self) -> Result<ExprNode, String> {
-        if let Some(Token::Ident(name)) = self.peek() {
-            let name = name.clone();
-            if self.pos + 1 < self.tokens.len() && self.tokens[self.pos + 1] == Token::LParen {
+        let name = match self.peek() {
+            Some(Token::Ident(name)) => name.clone(),
+            _ => return self.parse_atom(),
+        };
+        match self.tokens.get(self.pos + 1) {
+            Some(Token::LParen) => {
                 self.advance(); // consume ident
                 self.advance(); // consume '('
                 let mut args = vec![self.parse_expr()?];
@@ -252,42 +984,64 @@ impl<'a> AstParser<'a> {
                     args.push(self.parse_expr()?);
                 }
                 self.expect(&Token::RParen)?;
-                let kind = match name.as_str() {
-                    "sqrt"  => FnKind::Sqrt,
-                    "abs"   => FnKind::Abs,
-                    "sin"   => FnKind::Sin,
-                    "cos"   => FnKind::Cos,
-                    "tan"   => FnKind::Tan,
-                    "asin"  => FnKind::Asin,
-                    "acos"  => FnKind::Acos,
-                    "atan"  => FnKind::Atan,
-                    "ln"    => FnKind::Ln,
-                    "log" | "log10" => FnKind::Log10,
-                    "exp"   => FnKind::Exp,
-                    "ceil"  => FnKind::Ceil,
-                    "floor" => FnKind::Floor,
-                    "round" => FnKind::Round,
-                    "min"   => FnKind::Min,
-                    "max"   => FnKind::Max,
-                    "pow"   => FnKind::Pow2,
-                    "atan2" => FnKind::Atan2,
-                    _ => return Err(format!("Unknown function: {}", name)),
-                };
-                return Ok(ExprNode::FnCall { name: kind, args });
+                self.build_call(&name, args)
+            }
+            Some(Token::LBracket) => {
+                if self.scope.iter().any(|(n, _)| *n == name) {
+                    return Err(format!("'{name}' is a scalar and cannot be indexed"));
+                }
+                self.advance(); // consume ident
+                self.advance(); // consume '['
+                let index = self.parse_expr()?;
+                self.expect(&Token::RBracket)?;
+                Ok(ExprNode::Index { name, index: Box::new(index) })
             }
+            _ => self.parse_atom(),
         }
-        self.parse_atom()
+    }
+
+    fn build_call(&self, name: &str, args: Vec<ExprNode>) -> Result<ExprNode, String> {
+        if name == "if" {
+            if args.len() < 3 || args.len().is_multiple_of(2) {
+                return Err(format!(
+                    "if() expects (cond, then, otherwise) or (c1, v1, c2, v2, ..., otherwise), got {} argument(s)",
+                    args.len()
+                ));
+            }
+            // if(c1, v1, c2, v2, e) → if(c1, v1, if(c2, v2, e))
+            let mut args = args;
+            let mut node = args.pop().expect("odd argument count");
+            while let (Some(then), Some(cond)) = (args.pop(), args.pop()) {
+                node = ExprNode::If { cond: Box::new(cond), then: Box::new(then), otherwise: Box::new(node) };
+            }
+            return Ok(node);
+        }
+        if let Some(kind) = FnKind::from_name(name) {
+            check_arity(name, args.len(), kind.arity())?;
+            return Ok(ExprNode::FnCall { name: kind, args });
+        }
+        if let Some(func) = self.functions.get(name) {
+            check_arity(name, args.len(), func.params.len())?;
+            return Ok(ExprNode::UserCall { func: Arc::clone(func), args });
+        }
+        Err(format!("Unknown function: {}", name))
     }
 
     fn parse_atom(&mut self) -> Result<ExprNode, String> {
         let tok = self.advance().cloned();
         match tok {
             Some(Token::Number(n)) => Ok(ExprNode::Number(n)),
-            Some(Token::Ident(ref name)) => match name.as_str() {
-                "pi" | "PI" => Ok(ExprNode::Constant(std::f64::consts::PI)),
-                "e" | "E"   => Ok(ExprNode::Constant(std::f64::consts::E)),
-                _ => Ok(ExprNode::Variable(name.clone())),
-            },
+            Some(Token::Ident(ref name)) => {
+                if let Some((_, slot)) = self.scope.iter().rev().find(|(n, _)| n == name) {
+                    return Ok(ExprNode::Local(*slot));
+                }
+                match name.as_str() {
+                    "pi" | "PI" => Ok(ExprNode::Constant(std::f64::consts::PI)),
+                    "e" | "E"   => Ok(ExprNode::Constant(std::f64::consts::E)),
+                    "let" => Err("'let' must start an expression".to_string()),
+                    _ => Ok(ExprNode::Variable(name.clone())),
+                }
+            }
             Some(Token::LParen) => {
                 let val = self.parse_expr()?;
                 self.expect(&Token::RParen)?;
@@ -299,23 +1053,17 @@ impl<'a> AstParser<'a> {
     }
 }
 
-/// Evaluate a formula string with the given variable bindings.
-/// Returns f64 (may be NaN or Inf for invalid operations).
-pub fn eval_expr(formula: &str, vars: &HashMap<String, f64>) -> Result<f64, String> {
-    let tokens = tokenize(formula)?;
-    let mut parser = Parser {
-        tokens: &tokens,
-        pos: 0,
-        vars,
-    };
-    let result = parser.parse_expr()?;
-    if parser.pos < parser.tokens.len() {
-        return Err(format!(
-            "Unexpected token at position {}",
-            parser.pos
-        ));
+fn check_arity(name: &str, got: usize, expected: usize) -> Result<(), String> {
+    if got != expected {
+        Err(format!(
+            "{}() expects {} argument(s), got {}",
+            name,
+            expected,
+            got
+        ))
+    } else {
+        Ok(())
     }
-    Ok(result)
 }
 
 // ── Tokenizer ────────────────────────────────────────────────────────────────
@@ -331,7 +1079,20 @@ enum Token {
     Caret,
     LParen,
     RParen,
+    LBracket,
+    RBracket,
     Comma,
+    Semicolon,
+    Assign,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    EqEq,
+    NotEq,
+    Bang,
+    AndAnd,
+    OrOr,
 }
 
 fn tokenize(input: &str) -> Result<Vec<Token>, String> {
@@ -341,6 +1102,7 @@ fn tokenize(input: &str) -> Result<Vec<Token>, String> {
 
     while i < chars.len() {
         let c = chars[i];
+        let next = chars.get(i + 1).copied();
         match c {
             ' ' | '\t' | '\n' | '\r' => i += 1,
             '+' => { tokens.push(Token::Plus); i += 1; }
@@ -350,7 +1112,20 @@ fn tokenize(input: &str) -> Result<Vec<Token>, String> {
             '^' => { tokens.push(Token::Caret); i += 1; }
             '(' => { tokens.push(Token::LParen); i += 1; }
             ')' => { tokens.push(Token::RParen); i += 1; }
+            '[' => { tokens.push(Token::LBracket); i += 1; }
+            ']' => { tokens.push(Token::RBracket); i += 1; }
             ',' => { tokens.push(Token::Comma); i += 1; }
+            ';' => { tokens.push(Token::Semicolon); i += 1; }
+            '<' if next == Some('=') => { tokens.push(Token::Le); i += 2; }
+            '<' => { tokens.push(Token::Lt); i += 1; }
+            '>' if next == Some('=') => { tokens.push(Token::Ge); i += 2; }
+            '>' => { tokens.push(Token::Gt); i += 1; }
+            '=' if next == Some('=') => { tokens.push(Token::EqEq); i += 2; }
+            '=' => { tokens.push(Token::Assign); i += 1; }
+            '!' if next == Some('=') => { tokens.push(Token::NotEq); i += 2; }
+            '!' => { tokens.push(Token::Bang); i += 1; }
+            '&' if next == Some('&') => { tokens.push(Token::AndAnd); i += 2; }
+            '|' if next == Some('|') => { tokens.push(Token::OrOr); i += 2; }
             '0'..='9' | '.' => {
                 let start = i;
                 while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
@@ -385,183 +1160,6 @@ fn tokenize(input: &str) -> Result<Vec<Token>, String> {
     Ok(tokens)
 }
 
-// ── Parser ───────────────────────────────────────────────────────────────────
-
-struct Parser<'a> {
-    tokens: &'a [Token],
-    pos: usize,
-    vars: &'a HashMap<String, f64>,
-}
-
-impl<'a> Parser<'a> {
-    fn peek(&self) -> Option<&Token> {
-        self.tokens.get(self.pos)
-    }
-
-    fn advance(&mut self) -> Option<&Token> {
-        let tok = self.tokens.get(self.pos);
-        if tok.is_some() {
-            self.pos += 1;
-        }
-        tok
-    }
-
-    fn expect(&mut self, expected: &Token) -> Result<(), String> {
-        match self.advance() {
-            Some(tok) if tok == expected => Ok(()),
-            Some(tok) => Err(format!("Expected {:?}, got {:?}", expected, tok)),
-            None => Err(format!("Expected {:?}, got end of input", expected)),
-        }
-    }
-
-    // expr = term (('+' | '-') term)*
-    fn parse_expr(&mut self) -> Result<f64, String> {
-        let mut left = self.parse_term()?;
-        while let Some(tok) = self.peek() {
-            match tok {
-                Token::Plus => {
-                    self.advance();
-                    left += self.parse_term()?;
-                }
-                Token::Minus => {
-                    self.advance();
-                    left -= self.parse_term()?;
-                }
-                _ => break,
-            }
-        }
-        Ok(left)
-    }
-
-    // term = power (('*' | '/') power)*
-    fn parse_term(&mut self) -> Result<f64, String> {
-        let mut left = self.parse_power()?;
-        while let Some(tok) = self.peek() {
-            match tok {
-                Token::Star => {
-                    self.advance();
-                    left *= self.parse_power()?;
-                }
-                Token::Slash => {
-                    self.advance();
-                    left /= self.parse_power()?;
-                }
-                _ => break,
-            }
-        }
-        Ok(left)
-    }
-
-    // power = unary ('^' unary)*   (right-associative)
-    fn parse_power(&mut self) -> Result<f64, String> {
-        let base = self.parse_unary()?;
-        if let Some(Token::Caret) = self.peek() {
-            self.advance();
-            let exp = self.parse_power()?; // right-associative recursion
-            Ok(base.powf(exp))
-        } else {
-            Ok(base)
-        }
-    }
-
-    // unary = '-' unary | call
-    fn parse_unary(&mut self) -> Result<f64, String> {
-        if let Some(Token::Minus) = self.peek() {
-            self.advance();
-            Ok(-self.parse_unary()?)
-        } else {
-            self.parse_call()
-        }
-    }
-
-    // call = IDENT '(' args ')' | atom
-    fn parse_call(&mut self) -> Result<f64, String> {
-        if let Some(Token::Ident(name)) = self.peek() {
-            let name = name.clone();
-            // Check if next token is '(' → function call
-            if self.pos + 1 < self.tokens.len() && self.tokens[self.pos + 1] == Token::LParen {
-                self.advance(); // consume ident
-                self.advance(); // consume '('
-                let mut args = vec![self.parse_expr()?];
-                while let Some(Token::Comma) = self.peek() {
-                    self.advance();
-                    args.push(self.parse_expr()?);
-                }
-                self.expect(&Token::RParen)?;
-                return self.call_function(&name, &args);
-            }
-        }
-        self.parse_atom()
-    }
-
-    // atom = NUMBER | IDENT | '(' expr ')'
-    fn parse_atom(&mut self) -> Result<f64, String> {
-        let tok = self.advance().cloned();
-        match tok {
-            Some(Token::Number(n)) => Ok(n),
-            Some(Token::Ident(ref name)) => {
-                // Built-in constants
-                match name.as_str() {
-                    "pi" | "PI" => Ok(std::f64::consts::PI),
-                    "e" | "E" => Ok(std::f64::consts::E),
-                    _ => {
-                        self.vars
-                            .get(name.as_str())
-                            .copied()
-                            .ok_or_else(|| format!("Unknown variable: {}", name))
-                    }
-                }
-            }
-            Some(Token::LParen) => {
-                let val = self.parse_expr()?;
-                self.expect(&Token::RParen)?;
-                Ok(val)
-            }
-            Some(ref tok) => Err(format!("Unexpected token: {:?}", tok)),
-            None => Err("Unexpected end of expression".to_string()),
-        }
-    }
-
-    fn call_function(&self, name: &str, args: &[f64]) -> Result<f64, String> {
-        match name {
-            // 1-arg functions
-            "sqrt" => { check_arity(name, args, 1)?; Ok(args[0].sqrt()) }
-            "abs" => { check_arity(name, args, 1)?; Ok(args[0].abs()) }
-            "sin" => { check_arity(name, args, 1)?; Ok(args[0].sin()) }
-            "cos" => { check_arity(name, args, 1)?; Ok(args[0].cos()) }
-            "tan" => { check_arity(name, args, 1)?; Ok(args[0].tan()) }
-            "asin" => { check_arity(name, args, 1)?; Ok(args[0].asin()) }
-            "acos" => { check_arity(name, args, 1)?; Ok(args[0].acos()) }
-            "atan" => { check_arity(name, args, 1)?; Ok(args[0].atan()) }
-            "ln" => { check_arity(name, args, 1)?; Ok(args[0].ln()) }
-            "log" | "log10" => { check_arity(name, args, 1)?; Ok(args[0].log10()) }
-            "exp" => { check_arity(name, args, 1)?; Ok(args[0].exp()) }
-            "ceil" => { check_arity(name, args, 1)?; Ok(args[0].ceil()) }
-            "floor" => { check_arity(name, args, 1)?; Ok(args[0].floor()) }
-            "round" => { check_arity(name, args, 1)?; Ok(args[0].round()) }
-            // 2-arg functions
-            "min" => { check_arity(name, args, 2)?; Ok(args[0].min(args[1])) }
-            "max" => { check_arity(name, args, 2)?; Ok(args[0].max(args[1])) }
-            "pow" => { check_arity(name, args, 2)?; Ok(args[0].powf(args[1])) }
-            "atan2" => { check_arity(name, args, 2)?; Ok(args[0].atan2(args[1])) }
-            _ => Err(format!("Unknown function: {}", name)),
-        }
-    }
-}
-
-fn check_arity(name: &str, args: &[f64], expected: usize) -> Result<(), String> {
-    if args.len() != expected {
-        Err(format!(
-            "{}() expects {} argument(s), got {}",
-            name,
-            expected,
-            args.len()
-        ))
-    } else {
-        Ok(())
-    }
-}
-
 // ── Tests ────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -680,4 +1278,156 @@ mod tests {
         let result = eval_expr("(-b + sqrt(b^2 - 4*a*c)) / (2*a)", &v).unwrap();
         assert!((result - 2.0).abs() < 1e-10);
     }
+
+    #[test]
+    fn comparisons_and_logic() {
+        let v = vars(&[("x", 2.0)]);
+        assert_eq!(eval_expr("x > 1", &v).unwrap(), 1.0);
+        assert_eq!(eval_expr("x <= 1", &v).unwrap(), 0.0);
+        assert_eq!(eval_expr("x == 2 && x != 3", &v).unwrap(), 1.0);
+        assert_eq!(eval_expr("x < 0 || !(x > 5)", &v).unwrap(), 1.0);
+        // Arithmetic binds tighter than comparison.
+        assert_eq!(eval_expr("x + 1 > 2 * x", &v).unwrap(), 0.0);
+        assert!(eval_expr("1 < x < 3", &v).is_err());
+    }
+
+    #[test]
+    fn if_is_lazy_and_piecewise() {
+        let v = vars(&[("x", -1.0)]);
+        assert_eq!(eval_expr("if(x < 0, -x, x)", &v).unwrap(), 1.0);
+        // The untaken branch references an unknown variable but is never evaluated.
+        assert_eq!(eval_expr("if(x < 0, 0, missing)", &v).unwrap(), 0.0);
+        let f = "if(x < -2, 1, x < 0, 2, 3)";
+        assert_eq!(eval_expr(f, &vars(&[("x", -5.0)])).unwrap(), 1.0);
+        assert_eq!(eval_expr(f, &vars(&[("x", -1.0)])).unwrap(), 2.0);
+        assert_eq!(eval_expr(f, &vars(&[("x", 4.0)])).unwrap(), 3.0);
+        assert!(eval_expr("if(x, 1)", &v).is_err());
+        assert!(eval_expr("if(0/0, 1, 2)", &v).unwrap().is_nan());
+    }
+
+    #[test]
+    fn special_functions() {
+        let v = HashMap::new();
+        assert!((eval_expr("tanh(0.5)", &v).unwrap() - 0.5f64.tanh()).abs() < 1e-15);
+        assert!((eval_expr("cosh(1)^2 - sinh(1)^2", &v).unwrap() - 1.0).abs() < 1e-12);
+        assert!((eval_expr("erf(0.5)", &v).unwrap() - 0.520_499_877_813_046_5).abs() < 1e-14);
+        assert!((eval_expr("erf(1)", &v).unwrap() - 0.842_700_792_949_714_9).abs() < 1e-14);
+        assert!((eval_expr("erf(-3)", &v).unwrap() + 0.999_977_909_503_001_4).abs() < 1e-14);
+        assert!((eval_expr("gamma(5)", &v).unwrap() - 24.0).abs() < 1e-10);
+        assert!((eval_expr("gamma(0.5)^2", &v).unwrap() - std::f64::consts::PI).abs() < 1e-12);
+        assert!((eval_expr("gamma(-0.5)", &v).unwrap() + 2.0 * std::f64::consts::PI.sqrt()).abs() < 1e-12);
+        assert!(eval_expr("gamma(-2)", &v).unwrap().is_nan());
+        assert!(eval_expr("erf(1, 2)", &v).is_err());
+    }
+
+    #[test]
+    fn let_bindings() {
+        let v = vars(&[("x", 3.0), ("y", 4.0)]);
+        assert_eq!(eval_expr("let r = sqrt(x^2 + y^2); r * 2", &v).unwrap(), 10.0);
+        assert_eq!(eval_expr("let a = x; let b = a + y; a * b", &v).unwrap(), 21.0);
+        // Inner bindings shadow variables; scopes end with their body.
+        assert_eq!(eval_expr("(let x = 10; x) + x", &v).unwrap(), 13.0);
+        assert!(eval_expr("let r = 1 r", &v).is_err());
+    }
+
+    #[test]
+    fn vector_indexing() {
+        let c = compile("y[0] + 2 * y[k]").unwrap();
+        let scalars = vars(&[("k", 2.0)]);
+        let vectors: HashMap<String, Vec<f64>> = [("y".to_string(), vec![1.0, 5.0, 7.0])].into();
+        assert_eq!(c.eval_with_vectors(&scalars, &vectors).unwrap(), 15.0);
+        // Without a vector binding, y[i] reads the scalar y<i> (ODE state naming).
+        let ode = vars(&[("y0", 1.0), ("y2", 3.0), ("k", 2.0)]);
+        assert_eq!(c.eval(&ode).unwrap(), 7.0);
+        assert!(compile("y[3]").unwrap().eval_with_vectors(&scalars, &vectors).is_err());
+        assert!(compile("y[0.5]").unwrap().eval_with_vectors(&scalars, &vectors).is_err());
+    }
+
+    #[test]
+    fn user_functions() {
+        let mut table = FunctionTable::new();
+        table.define("sq", &["x".to_string()], "x * x").unwrap();
+        table.define("hyp", &["a".to_string(), "b".to_string()], "sqrt(sq(a) + sq(b))").unwrap();
+        let c = compile_with("hyp(x, 4) + sq(2)", &table).unwrap();
+        assert_eq!(c.eval(&vars(&[("x", 3.0)])).unwrap(), 9.0);
+        assert!(compile_with("sq(1, 2)", &table).is_err());
+        assert!(table.define("sq", &["y".to_string()], "y").is_err());
+        assert!(table.define("sin", &["y".to_string()], "y").is_err());
+        // No recursion: a function cannot see itself while being defined.
+        assert!(table.define("loop", &["x".to_string()], "loop(x)").is_err());
+        assert!(compile("sq(2)").is_err());
+        let table = Arc::new(table);
+        assert_eq!(with_functions(&table, || eval_expr("sq(3)", &HashMap::new())).unwrap(), 9.0);
+        assert!(compile("sq(2)").is_err(), "ambient functions are scoped");
+    }
+
+    #[test]
+    fn define_all_resolves_order() {
+        let defs = vec![
+            ("g".to_string(), vec!["x".to_string()], "f(x) + 1".to_string()),
+            ("f".to_string(), vec!["x".to_string()], "2 * x".to_string()),
+            ("h".to_string(), vec![], "missing()".to_string()),
+        ];
+        let mut table = FunctionTable::new();
+        let errors = table.define_all(&defs);
+        assert_eq!(table.len(), 2);
+        assert!(errors.contains_key(&2) && errors.len() == 1);
+        assert_eq!(compile_with("g(3)", &table).unwrap().eval(&HashMap::new()).unwrap(), 7.0);
+    }
+
+    #[test]
+    fn dual_matches_value_and_derivative() {
+        let mut table = FunctionTable::new();
+        table.define("soft", &["x".to_string()], "if(x > 0, x^2, erf(x) * gamma(x + 3))").unwrap();
+        let c = compile_with("let s = tanh(x); soft(x) + s * y[1]", &table).unwrap();
+        for &x in &[-0.7, 0.4, 1.3] {
+            let f = |x: f64| {
+                let vectors: HashMap<String, Vec<f64>> = [("y".to_string(), vec![0.0, 2.0])].into();
+                c.eval_with_vectors(&vars(&[("x", x)]), &vectors).unwrap()
+            };
+            let dvars: HashMap<String, Dual> = [("x".to_string(), Dual::variable(x))].into();
+            let dvec: HashMap<String, Vec<Dual>> =
+                [("y".to_string(), vec![Dual::constant(0.0), Dual::constant(2.0)])].into();
+            let d = c.eval_dual_with_vectors(&dvars, &dvec).unwrap();
+            let h = 1e-6;
+            let fd = (f(x + h) - f(x - h)) / (2.0 * h);
+            assert!((d.val - f(x)).abs() < 1e-14);
+            assert!((d.dot - fd).abs() < 1e-6, "x={x}: {} vs {fd}", d.dot);
+        }
+    }
+
+    #[test]
+    fn dependencies_follow_indices_and_user_functions() {
+        let mut table = FunctionTable::new();
+        table.define("g", &["x".to_string()], "x * k").unwrap();
+        let c = compile_with("let a = y[2]; g(y1) + a + u[n] + pi", &table).unwrap();
+        let deps = c.dependencies();
+        let names: Vec<&str> = deps.scalars.iter().map(String::as_str).collect();
+        assert_eq!(names, ["k", "n", "y1", "y2"]);
+        assert_eq!(deps.vectors.iter().collect::<Vec<_>>(), ["u"]);
+    }
+
+    #[test]
+    fn dual_power_with_negative_base() {
+        let c = compile("x^2").unwrap();
+        let dvars: HashMap<String, Dual> = [("x".to_string(), Dual::variable(-3.0))].into();
+        assert_eq!(c.eval_dual(&dvars).unwrap().dot, -6.0);
+    }
+
+    #[test]
+    fn to_c_inlines_lets_and_user_functions() {
+        let mut table = FunctionTable::new();
+        table.define("sq", &["x".to_string()], "x * x").unwrap();
+        let c = compile_with("let a = y[1]; if(a > 0, sq(a) + pi, min(u, -1))", &table).unwrap();
+        let resolve = |name: &str| ["y1", "u"].contains(&name).then(|| format!("v_{name}"));
+        let (code, slots) = c.to_c(&resolve).unwrap();
+        assert_eq!(slots, 2);
+        assert_eq!(
+            code,
+            "(cs_let[0] = v_y1, (isnan((cs_let[0] > 0.0 ? 1.0 : 0.0)) ? NAN : (cs_let[0] > 0.0 ? 1.0 : 0.0) != 0.0 ? \
+             ((cs_let[1] = cs_let[0], (cs_let[1] * cs_let[1])) + 3.141592653589793) : fmin(v_u, (-1.0))))"
+        );
+        assert!(compile("q + 1").unwrap().to_c(&resolve).is_err());
+        assert!(compile("y[n]").unwrap().to_c(&resolve).is_err());
+    }
 }
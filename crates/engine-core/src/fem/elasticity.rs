@@ -0,0 +1,358 @@
+//! Linear elasticity in plane stress or plane strain.
+//!
+//! Unknowns are the nodal displacements `(ux, uy)`. Neumann data is a
+//! traction `(tx, ty)` (force per unit area of the edge face), body forces
+//! are per unit volume, and Robin data is a spring support of stiffness `k`
+//! per unit area pulling towards `(ux0, uy0)`. Loads act on the given
+//! `thickness`.
+//!
+//! Element stresses are constant on P1 triangles; von Mises uses the
+//! out-of-plane stress `σzz = ν(σxx + σyy)` in plane strain and `0` in
+//! plane stress.
+
+use super::heat::element_load;
+use super::mesh::TriMesh;
+use crate::sparse::{CooMatrix, CsrMatrix};
+use super::{apply_natural_bcs, compile_bcs, dirichlet_values, nodal_average, table, BoundaryConditions, Coefficient, LinearSystem};
+
+/// Plane-stress (thin plates) or plane-strain (long bodies) kinematics.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PlaneModel {
+    #[default]
+    PlaneStress,
+    PlaneStrain,
+}
+
+/// Parse "strain"/"plane_strain" (else plane stress).
+pub fn parse_plane_model(s: &str) -> PlaneModel {
+    match s.trim().to_lowercase().replace(['-', ' '], "_").as_str() {
+        "strain" | "plane_strain" => PlaneModel::PlaneStrain,
+        _ => PlaneModel::PlaneStress,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ElasticityProblem {
+    pub youngs_modulus: f64,
+    pub poisson_ratio: f64,
+    pub thickness: f64,
+    pub model: PlaneModel,
+    /// Body force `(bx, by)` per unit volume; expressions in `x`, `y`.
+    pub body_force: [String; 2],
+    pub bcs: BoundaryConditions,
+}
+
+/// Element stress state `[σxx, σyy, σxy, σzz]`.
+pub type Stress = [f64; 4];
+
+#[derive(Debug, Clone)]
+pub struct ElasticityResult {
+    pub mesh: TriMesh,
+    /// Displacement `[ux, uy]` per node.
+    pub displacement: Vec<[f64; 2]>,
+    pub stress: Vec<Stress>,
+    pub von_mises: Vec<f64>,
+}
+
+impl ElasticityResult {
+    /// Columns `x, y, ux, uy, von_mises` (von Mises averaged to nodes).
+    pub fn nodal_table(&self) -> (Vec<String>, Vec<Vec<f64>>) {
+        let x: Vec<f64> = self.mesh.nodes.iter().map(|p| p.0).collect();
+        let y: Vec<f64> = self.mesh.nodes.iter().map(|p| p.1).collect();
+        let ux: Vec<f64> = self.displacement.iter().map(|u| u[0]).collect();
+        let uy: Vec<f64> = self.displacement.iter().map(|u| u[1]).collect();
+        table(&["x", "y", "ux", "uy", "von_mises"], &[&x, &y, &ux, &uy, &nodal_average(&self.mesh, &self.von_mises)])
+    }
+
+    /// Columns `x, y, sxx, syy, sxy, szz, von_mises` at element centroids.
+    pub fn element_table(&self) -> (Vec<String>, Vec<Vec<f64>>) {
+        let (x, y): (Vec<f64>, Vec<f64>) = (0..self.mesh.tris.len()).map(|e| self.mesh.centroid(e)).unzip();
+        let component = |k: usize| -> Vec<f64> { self.stress.iter().map(|s| s[k]).collect() };
+        let (sxx, syy, sxy, szz) = (component(0), component(1), component(2), component(3));
+        table(&["x", "y", "sxx", "syy", "sxy", "szz", "von_mises"], &[&x, &y, &sxx, &syy, &sxy, &szz, &self.von_mises])
+    }
+}
+
+/// Constitutive matrix `σ = D·[εxx, εyy, γxy]`.
+fn constitutive(e: f64, nu: f64, model: PlaneModel) -> [[f64; 3]; 3] {
+    match model {
+        PlaneModel::PlaneStress => {
+            let c = e / (1.0 - nu * nu);
+            [[c, c * nu, 0.0], [c * nu, c, 0.0], [0.0, 0.0, c * (1.0 - nu) / 2.0]]
+        }
+        PlaneModel::PlaneStrain => {
+            let c = e / ((1.0 + nu) * (1.0 - 2.0 * nu));
+            [[c * (1.0 - nu), c * nu, 0.0], [c * nu, c * (1.0 - nu), 0.0], [0.0, 0.0, c * (1.0 - 2.0 * nu) / 2.0]]
+        }
+    }
+}
+
+/// Strain–displacement matrix for element dofs `[ux0, uy0, ux1, uy1, ux2, uy2]`.
+fn strain_matrix(grad: &[[f64; 2]; 3]) -> [[f64; 6]; 3] {
+    let mut b = [[0.0; 6]; 3];
+    for (i, g) in grad.iter().enumerate() {
+        b[0][2 * i] = g[0];
+        b[1][2 * i + 1] = g[1];
+        b[2][2 * i] = g[1];
+        b[2][2 * i + 1] = g[0];
+    }
+    b
+}
+
+/// Von Mises equivalent stress of a `[σxx, σyy, σxy, σzz]` state.
+pub fn von_mises([sxx, syy, sxy, szz]: Stress) -> f64 {
+    (0.5 * ((sxx - syy).powi(2) + (syy - szz).powi(2) + (szz - sxx).powi(2)) + 3.0 * sxy * sxy).sqrt()
+}
+
+fn check_material(problem: &ElasticityProblem) -> Result<(), String> {
+    let (e_mod, nu, t) = (problem.youngs_modulus, problem.poisson_ratio, problem.thickness);
+    if e_mod.is_nan() || e_mod <= 0.0 || t.is_nan() || t <= 0.0 {
+        return Err("Young's modulus and thickness must be positive".to_string());
+    }
+    if nu.is_nan() || nu <= -1.0 || nu >= 0.5 {
+        return Err(format!("Poisson's ratio must be in (-1, 0.5), got {nu}"));
+    }
+    Ok(())
+}
+
+/// Add the element stiffness matrices `t·A·Bᵀ·D·B`.
+fn assemble_stiffness(system: &mut LinearSystem, mesh: &TriMesh, d: &[[f64; 3]; 3], t: f64) {
+    for (el, tri) in mesh.tris.iter().enumerate() {
+        let (grad, area) = mesh.gradients(el);
+        let b = strain_matrix(&grad);
+        let dofs: [usize; 6] = std::array::from_fn(|k| 2 * tri[k / 2] + k % 2);
+        for (r, &dof_r) in dofs.iter().enumerate() {
+            for (c, &dof_c) in dofs.iter().enumerate() {
+                let mut k_rc = 0.0;
+                for p in 0..3 {
+                    for q in 0..3 {
+                        k_rc += b[p][r] * d[p][q] * b[q][c];
+                    }
+                }
+                system.add(dof_r, dof_c, t * area * k_rc);
+            }
+        }
+    }
+}
+
+pub fn solve_elasticity(mesh: TriMesh, problem: &ElasticityProblem) -> Result<ElasticityResult, String> {
+    check_material(problem)?;
+    let (e_mod, nu, t) = (problem.youngs_modulus, problem.poisson_ratio, problem.thickness);
+    let body = [Coefficient::new("body force", &problem.body_force[0])?, Coefficient::new("body force", &problem.body_force[1])?];
+    let bcs = compile_bcs(&problem.bcs, 2, &mesh)?;
+    let n = mesh.nodes.len();
+    let fixed = dirichlet_values(&mesh, &bcs, 2, 2 * n);
+    if fixed.iter().all(Option::is_none) && !bcs.values().any(|bc| matches!(bc, super::CompiledBc::Robin { .. })) {
+        return Err("the body is unsupported: add a displacement or spring boundary condition".to_string());
+    }
+    let mut system = LinearSystem::new(fixed);
+    let d = constitutive(e_mod, nu, problem.model);
+    assemble_stiffness(&mut system, &mesh, &d, t);
+
+    for (el, tri) in mesh.tris.iter().enumerate() {
+        for (comp, f) in body.iter().enumerate() {
+            let load = element_load(&mesh, el, |p| f.at(p));
+            for i in 0..3 {
+                system.add_rhs(2 * tri[i] + comp, t * load[i]);
+            }
+        }
+    }
+    apply_natural_bcs(&mut system, &mesh, &bcs, 2, t);
+    let u = system.solve(true)?;
+
+    let displacement: Vec<[f64; 2]> = (0..n).map(|i| [u[2 * i], u[2 * i + 1]]).collect();
+    let stress: Vec<Stress> = mesh
+        .tris
+        .iter()
+        .enumerate()
+        .map(|(el, tri)| {
+            let (grad, _) = mesh.gradients(el);
+            let b = strain_matrix(&grad);
+            let ue: [f64; 6] = std::array::from_fn(|k| u[2 * tri[k / 2] + k % 2]);
+            let strain: [f64; 3] = std::array::from_fn(|p| (0..6).map(|k| b[p][k] * ue[k]).sum());
+            let s: [f64; 3] = std::array::from_fn(|p| (0..3).map(|q| d[p][q] * strain[q]).sum());
+            let szz = if problem.model == PlaneModel::PlaneStrain { nu * (s[0] + s[1]) } else { 0.0 };
+            [s[0], s[1], s[2], szz]
+        })
+        .collect();
+    let von_mises = stress.iter().map(|&s| von_mises(s)).collect();
+    Ok(ElasticityResult { mesh, displacement, stress, von_mises })
+}
+
+/// Stiffness and consistent mass matrices restricted to the free degrees
+/// of freedom, for modal analysis.
+#[derive(Debug, Clone)]
+pub struct StructuralMatrices {
+    pub stiffness: CsrMatrix,
+    pub mass: CsrMatrix,
+    /// Global dof (`2·node + component`) of each reduced row.
+    pub free_dofs: Vec<usize>,
+}
+
+impl StructuralMatrices {
+    /// Scatter a reduced vector back to all `2·nodes` dofs (constrained dofs are 0).
+    pub fn expand(&self, reduced: &[f64], nodes: usize) -> Vec<[f64; 2]> {
+        let mut full = vec![[0.0; 2]; nodes];
+        for (&dof, v) in self.free_dofs.iter().zip(reduced) {
+            full[dof / 2][dof % 2] = *v;
+        }
+        full
+    }
+
+    /// Rigid-body influence vector for translation along `component` (0 = x, 1 = y).
+    pub fn influence(&self, component: usize) -> Vec<f64> {
+        self.free_dofs.iter().map(|&dof| if dof % 2 == component { 1.0 } else { 0.0 }).collect()
+    }
+}
+
+/// Assemble `K` and the consistent mass `M` (`ρ·t·A/12·(1 + δij)` per
+/// component) with Dirichlet dofs removed. Prescribed values are ignored —
+/// only which dofs are constrained matters — and spring supports add to
+/// `K`. Tractions and body forces play no part.
+pub fn structural_matrices(mesh: &TriMesh, problem: &ElasticityProblem, density: f64) -> Result<StructuralMatrices, String> {
+    check_material(problem)?;
+    if density.is_nan() || density <= 0.0 {
+        return Err(format!("density must be positive, got {density}"));
+    }
+    let t = problem.thickness;
+    let bcs = compile_bcs(&problem.bcs, 2, mesh)?;
+    let n_dofs = 2 * mesh.nodes.len();
+    let fixed = dirichlet_values(mesh, &bcs, 2, n_dofs);
+    let mut system = LinearSystem::new(vec![None; n_dofs]);
+    assemble_stiffness(&mut system, mesh, &constitutive(problem.youngs_modulus, problem.poisson_ratio, problem.model), t);
+    apply_natural_bcs(&mut system, mesh, &bcs, 2, t);
+
+    let free_dofs: Vec<usize> = (0..n_dofs).filter(|&i| fixed[i].is_none()).collect();
+    if free_dofs.is_empty() {
+        return Err("every degree of freedom is constrained".to_string());
+    }
+    let mut index = vec![usize::MAX; n_dofs];
+    for (k, &dof) in free_dofs.iter().enumerate() {
+        index[dof] = k;
+    }
+    let size = free_dofs.len();
+    let reduce = |coo: &CooMatrix| -> CsrMatrix {
+        let mut out = CooMatrix::new(size, size);
+        for ((&i, &j), &v) in coo.row_indices.iter().zip(&coo.col_indices).zip(&coo.values) {
+            if index[i] != usize::MAX && index[j] != usize::MAX {
+                out.push(index[i], index[j], v);
+            }
+        }
+        out.to_csr()
+    };
+
+    let mut mass = CooMatrix::new(n_dofs, n_dofs);
+    for (el, tri) in mesh.tris.iter().enumerate() {
+        let m = density * t * mesh.area(el) / 12.0;
+        for i in 0..3 {
+            for j in 0..3 {
+                let mij = if i == j { 2.0 * m } else { m };
+                for c in 0..2 {
+                    mass.push(2 * tri[i] + c, 2 * tri[j] + c, mij);
+                }
+            }
+        }
+    }
+    Ok(StructuralMatrices { stiffness: reduce(&system.matrix), mass: reduce(&mass), free_dofs })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fem::mesh::{triangulate, Geometry};
+    use crate::fem::BoundaryCondition;
+    use std::collections::HashMap;
+
+    fn roller(ux: Option<&str>, uy: Option<&str>) -> BoundaryCondition {
+        BoundaryCondition::Dirichlet(vec![ux.map(str::to_string), uy.map(str::to_string)])
+    }
+
+    fn tension(model: PlaneModel) -> ElasticityResult {
+        // Bar [0, 2]×[0, 1]: rollers on the left (ux = 0) and bottom (uy = 0),
+        // traction 10 on the right.
+        let mesh = triangulate(&Geometry::rectangle(0.0, 0.0, 2.0, 1.0), 0.2).unwrap();
+        let problem = ElasticityProblem {
+            youngs_modulus: 1000.0,
+            poisson_ratio: 0.3,
+            thickness: 0.5,
+            model,
+            body_force: ["0".into(), "0".into()],
+            bcs: HashMap::from([
+                (3, roller(Some("0"), None)),
+                (0, roller(None, Some("0"))),
+                (1, BoundaryCondition::Neumann(vec!["10".into(), "0".into()])),
+            ]),
+        };
+        solve_elasticity(mesh, &problem).unwrap()
+    }
+
+    #[test]
+    fn uniaxial_tension_patch_test() {
+        let result = tension(PlaneModel::PlaneStress);
+        for (p, u) in result.mesh.nodes.iter().zip(&result.displacement) {
+            assert!((u[0] - 10.0 * p.0 / 1000.0).abs() < 1e-9);
+            assert!((u[1] + 0.3 * 10.0 * p.1 / 1000.0).abs() < 1e-9);
+        }
+        for (s, vm) in result.stress.iter().zip(&result.von_mises) {
+            assert!((s[0] - 10.0).abs() < 1e-7 && s[1].abs() < 1e-7 && s[2].abs() < 1e-7);
+            assert!((vm - 10.0).abs() < 1e-7);
+        }
+    }
+
+    #[test]
+    fn plane_strain_carries_out_of_plane_stress() {
+        // εzz = 0: εxx = (1 - ν²)σ/E, σzz = νσ.
+        let result = tension(PlaneModel::PlaneStrain);
+        for (p, u) in result.mesh.nodes.iter().zip(&result.displacement) {
+            assert!((u[0] - 0.91 * 10.0 * p.0 / 1000.0).abs() < 1e-9);
+        }
+        let s = result.stress[0];
+        assert!((s[3] - 3.0).abs() < 1e-7);
+        assert!((result.von_mises[0] - von_mises([10.0, 0.0, 0.0, 3.0])).abs() < 1e-7);
+    }
+
+    #[test]
+    fn cantilever_under_gravity_converges_to_beam_theory() {
+        // Clamped at x = 0, self-weight q = ρg·A: tip deflection q L⁴ / (8 E I).
+        let (l, h, e, rho_g) = (10.0, 1.0, 1.0e4, 1.0);
+        let mesh = triangulate(&Geometry::rectangle(0.0, 0.0, l, h), 0.125).unwrap();
+        let problem = ElasticityProblem {
+            youngs_modulus: e,
+            poisson_ratio: 0.0,
+            thickness: 1.0,
+            model: PlaneModel::PlaneStress,
+            body_force: ["0".into(), format!("-{rho_g}")],
+            bcs: HashMap::from([(3, roller(Some("0"), Some("0")))]),
+        };
+        let result = solve_elasticity(mesh, &problem).unwrap();
+        let tip = result
+            .mesh
+            .nodes
+            .iter()
+            .zip(&result.displacement)
+            .filter(|(p, _)| (p.0 - l).abs() < 1e-9)
+            .map(|(_, u)| u[1])
+            .sum::<f64>()
+            / result.mesh.nodes.iter().filter(|p| (p.0 - l).abs() < 1e-9).count() as f64;
+        let beam = -rho_g * h * l.powi(4) / (8.0 * e * h.powi(3) / 12.0);
+        // Linear triangles are stiff in bending; expect within 10%.
+        assert!((tip / beam - 1.0).abs() < 0.1, "tip {tip}, beam theory {beam}");
+    }
+
+    #[test]
+    fn rejects_unsupported_bodies_and_bad_materials() {
+        let mesh = triangulate(&Geometry::rectangle(0.0, 0.0, 1.0, 1.0), 0.5).unwrap();
+        let mut problem = ElasticityProblem {
+            youngs_modulus: 1.0,
+            poisson_ratio: 0.3,
+            thickness: 1.0,
+            model: PlaneModel::PlaneStress,
+            body_force: ["0".into(), "-1".into()],
+            bcs: HashMap::new(),
+        };
+        assert!(solve_elasticity(mesh.clone(), &problem).is_err());
+        problem.poisson_ratio = 0.5;
+        problem.bcs.insert(3, roller(Some("0"), Some("0")));
+        assert!(solve_elasticity(mesh, &problem).is_err());
+    }
+}
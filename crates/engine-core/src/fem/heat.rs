@@ -0,0 +1,164 @@
+//! Steady heat conduction `−∇·(k∇T) = Q`.
+//!
+//! Neumann data is the heat flux *into* the domain through an edge; Robin
+//! data is a convection coefficient and ambient temperature, giving an
+//! inflow of `h·(T∞ − T)`.
+
+use super::mesh::TriMesh;
+use super::{apply_natural_bcs, compile_bcs, dirichlet_values, nodal_average, table, BoundaryConditions, Coefficient, LinearSystem};
+
+/// Material and source data; expressions in `x`, `y`.
+#[derive(Debug, Clone)]
+pub struct HeatProblem {
+    /// Conductivity `k`, evaluated at element centroids.
+    pub conductivity: String,
+    /// Volumetric heat source `Q`.
+    pub source: String,
+    pub bcs: BoundaryConditions,
+}
+
+/// Nodal temperatures and per-element heat flux `q = −k∇T`.
+#[derive(Debug, Clone)]
+pub struct HeatResult {
+    pub mesh: TriMesh,
+    pub temperature: Vec<f64>,
+    pub flux: Vec<[f64; 2]>,
+}
+
+impl HeatResult {
+    /// Columns `x, y, T, q_mag` (flux magnitude averaged to nodes).
+    pub fn nodal_table(&self) -> (Vec<String>, Vec<Vec<f64>>) {
+        let x: Vec<f64> = self.mesh.nodes.iter().map(|p| p.0).collect();
+        let y: Vec<f64> = self.mesh.nodes.iter().map(|p| p.1).collect();
+        let q_mag: Vec<f64> = self.flux.iter().map(|q| q[0].hypot(q[1])).collect();
+        table(&["x", "y", "T", "q_mag"], &[&x, &y, &self.temperature, &nodal_average(&self.mesh, &q_mag)])
+    }
+
+    /// Columns `x, y, qx, qy` at element centroids.
+    pub fn element_table(&self) -> (Vec<String>, Vec<Vec<f64>>) {
+        let (x, y): (Vec<f64>, Vec<f64>) = (0..self.mesh.tris.len()).map(|e| self.mesh.centroid(e)).unzip();
+        let qx: Vec<f64> = self.flux.iter().map(|q| q[0]).collect();
+        let qy: Vec<f64> = self.flux.iter().map(|q| q[1]).collect();
+        table(&["x", "y", "qx", "qy"], &[&x, &y, &qx, &qy])
+    }
+}
+
+/// Load `∫ f·φ_i` by the edge-midpoint rule (exact for quadratics).
+pub(crate) fn element_load(mesh: &TriMesh, e: usize, f: impl Fn((f64, f64)) -> f64) -> [f64; 3] {
+    let tri = mesh.tris[e];
+    let area = mesh.area(e);
+    let mut load = [0.0; 3];
+    for k in 0..3 {
+        let (a, b) = (mesh.nodes[tri[k]], mesh.nodes[tri[(k + 1) % 3]]);
+        let value = f((0.5 * (a.0 + b.0), 0.5 * (a.1 + b.1))) * area / 3.0;
+        // φ is ½ at this midpoint for the edge's two end nodes and 0 for the third.
+        load[k] += 0.5 * value;
+        load[(k + 1) % 3] += 0.5 * value;
+    }
+    load
+}
+
+pub fn solve_heat(mesh: TriMesh, problem: &HeatProblem) -> Result<HeatResult, String> {
+    let k = Coefficient::new("conductivity", &problem.conductivity)?;
+    let q = Coefficient::new("source", &problem.source)?;
+    let bcs = compile_bcs(&problem.bcs, 1, &mesh)?;
+    let n = mesh.nodes.len();
+    let mut system = LinearSystem::new(dirichlet_values(&mesh, &bcs, 1, n));
+
+    let mut conductivity = Vec::with_capacity(mesh.tris.len());
+    for (e, tri) in mesh.tris.iter().enumerate() {
+        let (grad, area) = mesh.gradients(e);
+        let ke = k.at(mesh.centroid(e));
+        conductivity.push(ke);
+        let load = element_load(&mesh, e, |p| q.at(p));
+        for i in 0..3 {
+            system.add_rhs(tri[i], load[i]);
+            for j in 0..3 {
+                system.add(tri[i], tri[j], ke * area * (grad[i][0] * grad[j][0] + grad[i][1] * grad[j][1]));
+            }
+        }
+    }
+    apply_natural_bcs(&mut system, &mesh, &bcs, 1, 1.0);
+    let temperature = system.solve(true)?;
+
+    let flux = mesh
+        .tris
+        .iter()
+        .enumerate()
+        .map(|(e, tri)| {
+            let (grad, _) = mesh.gradients(e);
+            let g: [f64; 2] = std::array::from_fn(|d| (0..3).map(|i| grad[i][d] * temperature[tri[i]]).sum());
+            [-conductivity[e] * g[0], -conductivity[e] * g[1]]
+        })
+        .collect();
+    Ok(HeatResult { mesh, temperature, flux })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fem::mesh::{triangulate, Geometry};
+    use crate::fem::BoundaryCondition;
+    use std::collections::HashMap;
+
+    fn fixed(value: &str) -> BoundaryCondition {
+        BoundaryCondition::Dirichlet(vec![Some(value.to_string())])
+    }
+
+    #[test]
+    fn linear_profile_is_exact_on_an_unstructured_mesh() {
+        // T = 100 on the left, 20 on the right, insulated top and bottom.
+        let mesh = triangulate(&Geometry::rectangle(0.0, 0.0, 2.0, 1.0), 0.15).unwrap();
+        let problem = HeatProblem {
+            conductivity: "3".into(),
+            source: "0".into(),
+            bcs: HashMap::from([(3, fixed("100")), (1, fixed("20"))]),
+        };
+        let result = solve_heat(mesh, &problem).unwrap();
+        for (p, t) in result.mesh.nodes.iter().zip(&result.temperature) {
+            assert!((t - (100.0 - 40.0 * p.0)).abs() < 1e-7);
+        }
+        for q in &result.flux {
+            assert!((q[0] - 120.0).abs() < 1e-6 && q[1].abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn convection_and_source_match_the_slab_solution() {
+        // 1D slab on [0, L]: T(0) = 0, -k T'(L) = h (T(L) - T∞), uniform Q.
+        // T = -Q x²/(2k) + C x with C = (Q L + h (T∞ + Q L²/(2k))) / (k + h L).
+        let (l, k, h, q, t_inf) = (1.0, 2.0, 5.0, 10.0, 30.0);
+        let mesh = triangulate(&Geometry::rectangle(0.0, 0.0, l, 0.3), 0.05).unwrap();
+        let robin = BoundaryCondition::Robin { coefficient: h.to_string(), ambient: vec![t_inf.to_string()] };
+        let problem = HeatProblem {
+            conductivity: k.to_string(),
+            source: q.to_string(),
+            bcs: HashMap::from([(3, fixed("0")), (1, robin)]),
+        };
+        let result = solve_heat(mesh, &problem).unwrap();
+        let c = (q * l + h * (t_inf + q * l * l / (2.0 * k))) / (k + h * l);
+        for (p, t) in result.mesh.nodes.iter().zip(&result.temperature) {
+            let exact = -q * p.0 * p.0 / (2.0 * k) + c * p.0;
+            assert!((t - exact).abs() < 2e-3 * exact.abs().max(1.0), "T({}) = {t}, exact {exact}", p.0);
+        }
+    }
+
+    #[test]
+    fn neumann_flux_and_validation() {
+        // Flux 5 into the left edge, T = 0 on the right: T = 5 (L - x) / k.
+        let mesh = triangulate(&Geometry::rectangle(0.0, 0.0, 1.0, 1.0), 0.2).unwrap();
+        let problem = HeatProblem {
+            conductivity: "1".into(),
+            source: "0".into(),
+            bcs: HashMap::from([(3, BoundaryCondition::Neumann(vec!["5".into()])), (1, fixed("0"))]),
+        };
+        let result = solve_heat(mesh.clone(), &problem).unwrap();
+        for (p, t) in result.mesh.nodes.iter().zip(&result.temperature) {
+            assert!((t - 5.0 * (1.0 - p.0)).abs() < 1e-7);
+        }
+        let insulated = HeatProblem { bcs: HashMap::new(), ..problem.clone() };
+        assert!(solve_heat(mesh.clone(), &insulated).unwrap_err().contains("under-constrained"));
+        let bad = HeatProblem { bcs: HashMap::from([(9, fixed("0"))]), ..problem };
+        assert!(solve_heat(mesh, &bad).is_err());
+    }
+}
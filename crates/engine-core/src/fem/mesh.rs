@@ -0,0 +1,457 @@
+//! Unstructured triangle meshes from polygonal geometry.
+//!
+//! [`triangulate`] meshes a polygon with polygonal holes at a target edge
+//! length `h`:
+//!
+//! 1. Every geometry edge is split into `⌈len / h⌉` equal boundary segments.
+//! 2. Interior points are placed on a hexagonal lattice of spacing `h`,
+//!    keeping only those inside the domain and at least `h/2` from the
+//!    boundary (so they don't encroach on boundary segments).
+//! 3. All points are inserted into a Bowyer–Watson Delaunay triangulation
+//!    with neighbour walking for point location.
+//! 4. Boundary segments missing from the triangulation are split at their
+//!    midpoint and the midpoint inserted, until every segment is an edge
+//!    (a conforming Delaunay triangulation).
+//! 5. Triangles outside the outer polygon or inside a hole are removed.
+//!
+//! Each boundary edge keeps the *marker* of the geometry edge it came from,
+//! which is how boundary conditions are attached: outer-polygon edge `k`
+//! (from vertex `k` to vertex `k + 1`) has marker `k`, and hole edges are
+//! numbered on from there in order.
+//!
+//! Reference: Shewchuk, "Delaunay Refinement Mesh Generation" (1997), ch. 2–3.
+
+use std::collections::{HashMap, HashSet};
+
+/// Upper bound on the number of mesh nodes.
+pub const MAX_NODES: usize = 50_000;
+
+type Point = (f64, f64);
+
+/// Rounds of boundary-segment splitting before giving up.
+const MAX_RECOVERY_ROUNDS: usize = 60;
+
+/// A polygonal domain: an outer boundary and any number of holes.
+#[derive(Debug, Clone)]
+pub struct Geometry {
+    pub outer: Vec<(f64, f64)>,
+    pub holes: Vec<Vec<(f64, f64)>>,
+}
+
+impl Geometry {
+    /// The rectangle `[x0, x1] × [y0, y1]`; markers 0 = bottom, 1 = right,
+    /// 2 = top, 3 = left.
+    pub fn rectangle(x0: f64, y0: f64, x1: f64, y1: f64) -> Self {
+        Geometry { outer: vec![(x0, y0), (x1, y0), (x1, y1), (x0, y1)], holes: Vec::new() }
+    }
+
+    /// Number of edge markers (outer edges plus all hole edges).
+    pub fn edge_count(&self) -> usize {
+        self.outer.len() + self.holes.iter().map(Vec::len).sum::<usize>()
+    }
+
+    /// Whether `p` lies inside the outer polygon and outside every hole.
+    pub fn contains(&self, p: (f64, f64)) -> bool {
+        point_in_polygon(p, &self.outer) && !self.holes.iter().any(|h| point_in_polygon(p, h))
+    }
+
+    /// Geometry edges as `(start, end, marker)`.
+    fn edges(&self) -> Vec<(Point, Point, usize)> {
+        let mut out = Vec::with_capacity(self.edge_count());
+        for poly in std::iter::once(&self.outer).chain(&self.holes) {
+            for k in 0..poly.len() {
+                out.push((poly[k], poly[(k + 1) % poly.len()], out.len()));
+            }
+        }
+        out
+    }
+
+    fn area(&self) -> f64 {
+        polygon_area(&self.outer).abs() - self.holes.iter().map(|h| polygon_area(h).abs()).sum::<f64>()
+    }
+}
+
+/// A boundary edge of the mesh and the geometry edge it lies on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundaryEdge {
+    pub nodes: [usize; 2],
+    pub marker: usize,
+}
+
+/// A triangle mesh with counter-clockwise elements and marked boundary edges.
+#[derive(Debug, Clone)]
+pub struct TriMesh {
+    pub nodes: Vec<(f64, f64)>,
+    pub tris: Vec<[usize; 3]>,
+    pub boundary: Vec<BoundaryEdge>,
+}
+
+impl TriMesh {
+    /// Signed area of element `e` (positive: counter-clockwise).
+    pub fn area(&self, e: usize) -> f64 {
+        let [a, b, c] = self.tris[e].map(|i| self.nodes[i]);
+        0.5 * orient(a, b, c)
+    }
+
+    /// Centroid of element `e`.
+    pub fn centroid(&self, e: usize) -> (f64, f64) {
+        let [a, b, c] = self.tris[e].map(|i| self.nodes[i]);
+        ((a.0 + b.0 + c.0) / 3.0, (a.1 + b.1 + c.1) / 3.0)
+    }
+
+    /// Gradients of the three P1 basis functions on element `e`, and its area.
+    pub fn gradients(&self, e: usize) -> ([[f64; 2]; 3], f64) {
+        let [(x0, y0), (x1, y1), (x2, y2)] = self.tris[e].map(|i| self.nodes[i]);
+        let area = self.area(e);
+        let b = [y1 - y2, y2 - y0, y0 - y1];
+        let c = [x2 - x1, x0 - x2, x1 - x0];
+        let g = std::array::from_fn(|i| [b[i] / (2.0 * area), c[i] / (2.0 * area)]);
+        (g, area)
+    }
+
+    /// Longest edge of element `e`.
+    pub fn diameter(&self, e: usize) -> f64 {
+        let [a, b, c] = self.tris[e].map(|i| self.nodes[i]);
+        dist(a, b).max(dist(b, c)).max(dist(c, a))
+    }
+
+    /// Convert the structured mesh of [`crate::ode::fem2d`], marking its
+    /// sides like [`Geometry::rectangle`].
+    pub fn from_structured(mesh: &crate::ode::fem2d::Mesh2D) -> Self {
+        let (nx, ny) = (mesh.nx_nodes, mesh.ny_nodes);
+        let idx = |i: usize, j: usize| j * nx + i;
+        let mut boundary = Vec::with_capacity(2 * (nx + ny));
+        for i in 0..nx - 1 {
+            boundary.push(BoundaryEdge { nodes: [idx(i, 0), idx(i + 1, 0)], marker: 0 });
+            boundary.push(BoundaryEdge { nodes: [idx(i + 1, ny - 1), idx(i, ny - 1)], marker: 2 });
+        }
+        for j in 0..ny - 1 {
+            boundary.push(BoundaryEdge { nodes: [idx(nx - 1, j), idx(nx - 1, j + 1)], marker: 1 });
+            boundary.push(BoundaryEdge { nodes: [idx(0, j + 1), idx(0, j)], marker: 3 });
+        }
+        TriMesh { nodes: mesh.nodes.clone(), tris: mesh.tris.clone(), boundary }
+    }
+}
+
+fn orient(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> f64 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+fn dist(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+fn polygon_area(poly: &[(f64, f64)]) -> f64 {
+    (0..poly.len())
+        .map(|k| {
+            let (a, b) = (poly[k], poly[(k + 1) % poly.len()]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+fn point_in_polygon(p: (f64, f64), poly: &[(f64, f64)]) -> bool {
+    let mut inside = false;
+    for k in 0..poly.len() {
+        let (a, b) = (poly[k], poly[(k + 1) % poly.len()]);
+        if (a.1 > p.1) != (b.1 > p.1) && p.0 < a.0 + (p.1 - a.1) / (b.1 - a.1) * (b.0 - a.0) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len2 = dx * dx + dy * dy;
+    let s = if len2 > 0.0 { (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len2).clamp(0.0, 1.0) } else { 0.0 };
+    dist(p, (a.0 + s * dx, a.1 + s * dy))
+}
+
+/// Whether `p` lies strictly inside the circumcircle of counter-clockwise `(a, b, c)`.
+fn in_circle(a: (f64, f64), b: (f64, f64), c: (f64, f64), p: (f64, f64)) -> bool {
+    let (adx, ady) = (a.0 - p.0, a.1 - p.1);
+    let (bdx, bdy) = (b.0 - p.0, b.1 - p.1);
+    let (cdx, cdy) = (c.0 - p.0, c.1 - p.1);
+    let det = (adx * adx + ady * ady) * (bdx * cdy - cdx * bdy)
+        - (bdx * bdx + bdy * bdy) * (adx * cdy - cdx * ady)
+        + (cdx * cdx + cdy * cdy) * (adx * bdy - bdx * ady);
+    det > 0.0
+}
+
+#[derive(Debug, Clone)]
+struct Tri {
+    v: [usize; 3],
+    /// `n[i]`: neighbour across the edge opposite `v[i]`.
+    n: [Option<usize>; 3],
+    alive: bool,
+}
+
+/// Incremental Bowyer–Watson triangulation inside a large super-triangle
+/// (points 0–2).
+struct Delaunay {
+    pts: Vec<(f64, f64)>,
+    tris: Vec<Tri>,
+    last: usize,
+}
+
+impl Delaunay {
+    fn new(lo: (f64, f64), hi: (f64, f64)) -> Self {
+        let (cx, cy) = (0.5 * (lo.0 + hi.0), 0.5 * (lo.1 + hi.1));
+        let d = (hi.0 - lo.0).max(hi.1 - lo.1).max(1e-300) * 20.0;
+        let pts = vec![(cx - 2.0 * d, cy - d), (cx + 2.0 * d, cy - d), (cx, cy + 2.0 * d)];
+        Delaunay { pts, tris: vec![Tri { v: [0, 1, 2], n: [None; 3], alive: true }], last: 0 }
+    }
+
+    /// A live triangle containing `p`, found by walking from the last insertion.
+    fn locate(&self, p: (f64, f64)) -> usize {
+        let mut t = if self.tris[self.last].alive {
+            self.last
+        } else {
+            self.tris.iter().rposition(|t| t.alive).unwrap_or(0)
+        };
+        for _ in 0..self.tris.len() {
+            let tri = &self.tris[t];
+            let next = (0..3).find_map(|i| {
+                let (a, b) = (self.pts[tri.v[(i + 1) % 3]], self.pts[tri.v[(i + 2) % 3]]);
+                if orient(a, b, p) < 0.0 { tri.n[i] } else { None }
+            });
+            match next {
+                Some(nb) => t = nb,
+                None => return t,
+            }
+        }
+        // The walk cycled (degenerate input): scan instead.
+        self.tris
+            .iter()
+            .position(|tri| {
+                tri.alive && (0..3).all(|i| orient(self.pts[tri.v[(i + 1) % 3]], self.pts[tri.v[(i + 2) % 3]], p) >= 0.0)
+            })
+            .unwrap_or(t)
+    }
+
+    fn insert(&mut self, p: (f64, f64)) -> usize {
+        let idx = self.pts.len();
+        self.pts.push(p);
+        let start = self.locate(p);
+
+        // Cavity: triangles whose circumcircle contains p, grown from `start`.
+        let mut bad = vec![start];
+        let mut in_cavity: HashSet<usize> = HashSet::from([start]);
+        let mut k = 0;
+        while k < bad.len() {
+            let t = bad[k];
+            k += 1;
+            for nb in self.tris[t].n.into_iter().flatten() {
+                if in_cavity.contains(&nb) {
+                    continue;
+                }
+                let [a, b, c] = self.tris[nb].v.map(|v| self.pts[v]);
+                if in_circle(a, b, c, p) {
+                    in_cavity.insert(nb);
+                    bad.push(nb);
+                }
+            }
+        }
+
+        // Re-triangulate the cavity as a fan around p.
+        let mut by_first: HashMap<usize, usize> = HashMap::new();
+        let mut by_second: HashMap<usize, usize> = HashMap::new();
+        let mut created = Vec::new();
+        for &t in &bad {
+            let tri = self.tris[t].clone();
+            for i in 0..3 {
+                let outside = tri.n[i];
+                if outside.is_some_and(|nb| in_cavity.contains(&nb)) {
+                    continue;
+                }
+                let (a, b) = (tri.v[(i + 1) % 3], tri.v[(i + 2) % 3]);
+                let id = self.tris.len();
+                self.tris.push(Tri { v: [a, b, idx], n: [None, None, outside], alive: true });
+                if let Some(nb) = outside {
+                    if let Some(slot) = self.tris[nb].n.iter_mut().find(|s| **s == Some(t)) {
+                        *slot = Some(id);
+                    }
+                }
+                by_first.insert(a, id);
+                by_second.insert(b, id);
+                created.push(id);
+            }
+        }
+        for &id in &created {
+            let [a, b, _] = self.tris[id].v;
+            // Edge (b, p) is shared with the fan triangle starting at b; (p, a) with the one ending at a.
+            self.tris[id].n[0] = by_first.get(&b).copied();
+            self.tris[id].n[1] = by_second.get(&a).copied();
+        }
+        for &t in &bad {
+            self.tris[t].alive = false;
+        }
+        self.last = created.last().copied().unwrap_or(self.last);
+        idx
+    }
+
+    fn edges(&self) -> HashSet<(usize, usize)> {
+        let mut set = HashSet::new();
+        for tri in self.tris.iter().filter(|t| t.alive) {
+            for i in 0..3 {
+                let (a, b) = (tri.v[i], tri.v[(i + 1) % 3]);
+                set.insert((a.min(b), a.max(b)));
+            }
+        }
+        set
+    }
+}
+
+/// Mesh `geometry` with target edge length `h`.
+pub fn triangulate(geometry: &Geometry, h: f64) -> Result<TriMesh, String> {
+    if !(h > 0.0 && h.is_finite()) {
+        return Err(format!("element size must be positive, got {h}"));
+    }
+    for poly in std::iter::once(&geometry.outer).chain(&geometry.holes) {
+        if poly.len() < 3 || polygon_area(poly).abs() < 1e-300 {
+            return Err("each polygon needs at least 3 vertices and a non-zero area".to_string());
+        }
+        if poly.iter().any(|p| !p.0.is_finite() || !p.1.is_finite()) {
+            return Err("polygon coordinates must be finite".to_string());
+        }
+    }
+    let edges = geometry.edges();
+    let perimeter: f64 = edges.iter().map(|(a, b, _)| dist(*a, *b)).sum();
+    let estimate = geometry.area().max(0.0) / (0.433 * h * h) + perimeter / h;
+    if estimate > MAX_NODES as f64 {
+        return Err(format!("element size {h} would need about {estimate:.0} nodes (limit {MAX_NODES})"));
+    }
+
+    let lo = geometry.outer.iter().fold((f64::INFINITY, f64::INFINITY), |m, p| (m.0.min(p.0), m.1.min(p.1)));
+    let hi = geometry.outer.iter().fold((f64::NEG_INFINITY, f64::NEG_INFINITY), |m, p| (m.0.max(p.0), m.1.max(p.1)));
+    let mut dt = Delaunay::new(lo, hi);
+
+    // 1. Boundary points and segments. Polygon vertices are shared by their two edges.
+    let mut segments: Vec<(usize, usize, usize)> = Vec::new();
+    let mut offset = 0;
+    for poly in std::iter::once(&geometry.outer).chain(&geometry.holes) {
+        let corners: Vec<usize> = poly.iter().map(|&p| dt.insert(p)).collect();
+        for k in 0..poly.len() {
+            let (a, b) = (poly[k], poly[(k + 1) % poly.len()]);
+            let pieces = (dist(a, b) / h).ceil().max(1.0) as usize;
+            let mut prev = corners[k];
+            for s in 1..pieces {
+                let f = s as f64 / pieces as f64;
+                let q = dt.insert((a.0 + f * (b.0 - a.0), a.1 + f * (b.1 - a.1)));
+                segments.push((prev, q, offset + k));
+                prev = q;
+            }
+            segments.push((prev, corners[(k + 1) % poly.len()], offset + k));
+        }
+        offset += poly.len();
+    }
+
+    // 2–3. Interior hexagonal lattice.
+    let row = h * 3f64.sqrt() / 2.0;
+    let rows = ((hi.1 - lo.1) / row).ceil() as usize;
+    let cols = ((hi.0 - lo.0) / h).ceil() as usize + 1;
+    for j in 1..rows {
+        let y = lo.1 + j as f64 * row;
+        let shift = if j % 2 == 1 { 0.5 * h } else { 0.0 };
+        for i in 0..cols {
+            let p = (lo.0 + shift + i as f64 * h, y);
+            if geometry.contains(p) && edges.iter().all(|(a, b, _)| segment_distance(p, *a, *b) >= 0.5 * h) {
+                dt.insert(p);
+            }
+        }
+    }
+
+    // 4. Recover boundary segments by midpoint splitting.
+    for round in 0.. {
+        let present = dt.edges();
+        let missing: Vec<usize> =
+            (0..segments.len()).filter(|&s| !present.contains(&(segments[s].0.min(segments[s].1), segments[s].0.max(segments[s].1)))).collect();
+        if missing.is_empty() {
+            break;
+        }
+        if round == MAX_RECOVERY_ROUNDS || dt.pts.len() > MAX_NODES + 3 {
+            return Err("could not recover the boundary; check that the polygons don't intersect".to_string());
+        }
+        for s in missing.into_iter().rev() {
+            let (a, b, marker) = segments[s];
+            let (pa, pb) = (dt.pts[a], dt.pts[b]);
+            let m = dt.insert((0.5 * (pa.0 + pb.0), 0.5 * (pa.1 + pb.1)));
+            segments[s] = (a, m, marker);
+            segments.insert(s + 1, (m, b, marker));
+        }
+    }
+
+    // 5. Keep triangles inside the domain and renumber nodes without the super-triangle.
+    let tris: Vec<[usize; 3]> = dt
+        .tris
+        .iter()
+        .filter(|t| t.alive && t.v.iter().all(|&v| v >= 3))
+        .filter(|t| {
+            let [a, b, c] = t.v.map(|v| dt.pts[v]);
+            geometry.contains(((a.0 + b.0 + c.0) / 3.0, (a.1 + b.1 + c.1) / 3.0))
+        })
+        .map(|t| t.v.map(|v| v - 3))
+        .collect();
+    if tris.is_empty() {
+        return Err("the geometry produced no elements; is h larger than the domain?".to_string());
+    }
+    let boundary = segments
+        .into_iter()
+        .map(|(a, b, marker)| BoundaryEdge { nodes: [a - 3, b - 3], marker })
+        .collect();
+    Ok(TriMesh { nodes: dt.pts[3..].to_vec(), tris, boundary })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meshes_a_square_with_a_hole() {
+        let geometry = Geometry {
+            outer: vec![(0.0, 0.0), (2.0, 0.0), (2.0, 1.0), (0.0, 1.0)],
+            holes: vec![vec![(0.8, 0.3), (1.2, 0.3), (1.2, 0.7), (0.8, 0.7)]],
+        };
+        let mesh = triangulate(&geometry, 0.1).unwrap();
+        let area: f64 = (0..mesh.tris.len()).map(|e| mesh.area(e)).sum();
+        assert!((area - (2.0 - 0.16)).abs() < 1e-9, "area {area}");
+        assert!((0..mesh.tris.len()).all(|e| mesh.area(e) > 1e-6));
+
+        // Boundary edges cover every geometry edge exactly once.
+        let mut lengths = vec![0.0; geometry.edge_count()];
+        for edge in &mesh.boundary {
+            lengths[edge.marker] += dist(mesh.nodes[edge.nodes[0]], mesh.nodes[edge.nodes[1]]);
+        }
+        let expected = [2.0, 1.0, 2.0, 1.0, 0.4, 0.4, 0.4, 0.4];
+        for (got, want) in lengths.iter().zip(expected) {
+            assert!((got - want).abs() < 1e-12);
+        }
+
+        // Every node is used and the mesh is reasonably shaped.
+        let mut used = vec![false; mesh.nodes.len()];
+        mesh.tris.iter().flatten().for_each(|&v| used[v] = true);
+        assert!(used.iter().all(|&u| u));
+        let worst = (0..mesh.tris.len())
+            .map(|e| mesh.area(e) / (mesh.diameter(e).powi(2) * 3f64.sqrt() / 4.0))
+            .fold(f64::INFINITY, f64::min);
+        assert!(worst > 0.2, "worst shape ratio {worst}");
+    }
+
+    #[test]
+    fn structured_meshes_get_rectangle_markers() {
+        let mesh = TriMesh::from_structured(&crate::ode::fem2d::generate_mesh(0.0, 1.0, 0.0, 2.0, 3, 4));
+        assert_eq!(mesh.boundary.len(), 14);
+        for edge in mesh.boundary.iter().filter(|e| e.marker == 1) {
+            assert!(edge.nodes.iter().all(|&n| mesh.nodes[n].0 == 1.0));
+        }
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(triangulate(&Geometry::rectangle(0.0, 0.0, 1.0, 1.0), 0.0).is_err());
+        assert!(triangulate(&Geometry::rectangle(0.0, 0.0, 1.0, 1.0), 1e-4).is_err());
+        assert!(triangulate(&Geometry { outer: vec![(0.0, 0.0), (1.0, 0.0)], holes: vec![] }, 0.1).is_err());
+    }
+}
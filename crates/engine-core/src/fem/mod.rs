@@ -0,0 +1,274 @@
+//! General 2D finite elements on unstructured triangle meshes.
+//!
+//! Complements the rectangle-only Poisson solver in [`crate::ode::fem2d`]:
+//!
+//! - [`mesh`] — polygon-with-holes geometry and a conforming Delaunay mesher
+//!   whose boundary edges carry the marker of the geometry edge they lie on
+//! - [`heat`] — steady conduction `−∇·(k∇T) = Q`
+//! - [`elasticity`] — plane-stress / plane-strain linear elasticity with
+//!   element stresses and von Mises output
+//! - [`stokes`] — steady Stokes flow with pressure-stabilised P1–P1 elements
+//!
+//! All physics use linear (P1) triangles. Boundary conditions are attached
+//! per edge marker; edges without one get the natural condition (zero flux
+//! or zero traction). Coefficients and boundary data are expressions in
+//! `x` and `y`. Global systems are assembled into [`CsrMatrix`] with
+//! Dirichlet values lifted to the right-hand side, which keeps symmetric
+//! problems symmetric, and solved with the sparse direct factorizations in
+//! [`crate::sparse_direct`]: LDLᵀ for the symmetric positive definite heat
+//! and elasticity systems, LU for the Stokes saddle point system.
+//!
+//! Reference: Brenner & Scott, "The Mathematical Theory of Finite Element
+//! Methods" (2008); Elman, Silvester & Wathen, "Finite Elements and Fast
+//! Iterative Solvers" (2014), ch. 3.
+
+pub mod elasticity;
+pub mod heat;
+pub mod mesh;
+pub mod stokes;
+
+use crate::expr::{compile, CompiledExpr};
+use crate::sparse::CooMatrix;
+use crate::sparse_direct::{FillOrdering, SymbolicCholesky, SymbolicLu};
+use mesh::TriMesh;
+use std::collections::HashMap;
+
+/// A boundary condition on every mesh edge with one marker. Vectors hold one
+/// entry per field component (1 for heat, 2 for displacement or velocity).
+#[derive(Debug, Clone)]
+pub enum BoundaryCondition {
+    /// Prescribed values; `None` leaves that component free (e.g. a roller).
+    Dirichlet(Vec<Option<String>>),
+    /// Prescribed outward flux (heat) or traction (elasticity, Stokes).
+    Neumann(Vec<String>),
+    /// Flux `coefficient·(ambient − u)`: convection, or an elastic support.
+    Robin { coefficient: String, ambient: Vec<String> },
+}
+
+/// Boundary conditions keyed by edge marker.
+pub type BoundaryConditions = HashMap<usize, BoundaryCondition>;
+
+/// Compiled `f(x, y)`.
+pub(crate) struct Coefficient(CompiledExpr);
+
+impl Coefficient {
+    pub(crate) fn new(what: &str, src: &str) -> Result<Self, String> {
+        compile(src).map(Coefficient).map_err(|e| format!("{what} '{src}': {e}"))
+    }
+
+    pub(crate) fn at(&self, (x, y): (f64, f64)) -> f64 {
+        let vars = HashMap::from([("x".to_string(), x), ("y".to_string(), y)]);
+        self.0.eval(&vars).unwrap_or(f64::NAN)
+    }
+}
+
+/// Two-point Gauss rule on `[0, 1]`: `(s, weight)`.
+const EDGE_GAUSS: [(f64, f64); 2] = [(0.211_324_865_405_187_1, 0.5), (0.788_675_134_594_812_9, 0.5)];
+
+/// Compiled boundary data for one field with `components` components.
+pub(crate) enum CompiledBc {
+    Dirichlet(Vec<Option<Coefficient>>),
+    Neumann(Vec<Coefficient>),
+    Robin { coefficient: Coefficient, ambient: Vec<Coefficient> },
+}
+
+pub(crate) fn compile_bcs(
+    bcs: &BoundaryConditions,
+    components: usize,
+    mesh: &TriMesh,
+) -> Result<HashMap<usize, CompiledBc>, String> {
+    let max_marker = mesh.boundary.iter().map(|e| e.marker).max().unwrap_or(0);
+    let mut out = HashMap::new();
+    for (&marker, bc) in bcs {
+        if marker > max_marker {
+            return Err(format!("boundary condition on edge {marker}, but the mesh has edges 0..={max_marker}"));
+        }
+        let count = |n: usize| {
+            if n == components {
+                Ok(())
+            } else {
+                Err(format!("edge {marker}: expected {components} component(s), got {n}"))
+            }
+        };
+        let what = format!("edge {marker}");
+        let compiled = match bc {
+            BoundaryCondition::Dirichlet(values) => {
+                count(values.len())?;
+                CompiledBc::Dirichlet(
+                    values.iter().map(|v| v.as_deref().map(|s| Coefficient::new(&what, s)).transpose()).collect::<Result<_, _>>()?,
+                )
+            }
+            BoundaryCondition::Neumann(values) => {
+                count(values.len())?;
+                CompiledBc::Neumann(values.iter().map(|s| Coefficient::new(&what, s)).collect::<Result<_, _>>()?)
+            }
+            BoundaryCondition::Robin { coefficient, ambient } => {
+                count(ambient.len())?;
+                CompiledBc::Robin {
+                    coefficient: Coefficient::new(&what, coefficient)?,
+                    ambient: ambient.iter().map(|s| Coefficient::new(&what, s)).collect::<Result<_, _>>()?,
+                }
+            }
+        };
+        out.insert(marker, compiled);
+    }
+    Ok(out)
+}
+
+/// Dirichlet values for node-interleaved dofs (`node * components + c`).
+/// Where edges with different values meet, the lowest marker wins.
+pub(crate) fn dirichlet_values(
+    mesh: &TriMesh,
+    bcs: &HashMap<usize, CompiledBc>,
+    components: usize,
+    n_dofs: usize,
+) -> Vec<Option<f64>> {
+    let mut fixed = vec![None; n_dofs];
+    let mut edges: Vec<_> = mesh.boundary.iter().collect();
+    edges.sort_by_key(|e| e.marker);
+    for edge in edges {
+        let Some(CompiledBc::Dirichlet(values)) = bcs.get(&edge.marker) else { continue };
+        for &node in &edge.nodes {
+            for (c, value) in values.iter().enumerate() {
+                let dof = node * components + c;
+                if let (Some(v), None) = (value, fixed[dof]) {
+                    fixed[dof] = Some(v.at(mesh.nodes[node]));
+                }
+            }
+        }
+    }
+    fixed
+}
+
+/// Add Neumann and Robin edge terms for node-interleaved dofs.
+pub(crate) fn apply_natural_bcs(
+    system: &mut LinearSystem,
+    mesh: &TriMesh,
+    bcs: &HashMap<usize, CompiledBc>,
+    components: usize,
+    scale: f64,
+) {
+    for edge in &mesh.boundary {
+        let [a, b] = edge.nodes;
+        let (pa, pb) = (mesh.nodes[a], mesh.nodes[b]);
+        let len = (pb.0 - pa.0).hypot(pb.1 - pa.1) * scale;
+        for &(s, w) in &EDGE_GAUSS {
+            let p = (pa.0 + s * (pb.0 - pa.0), pa.1 + s * (pb.1 - pa.1));
+            let phi = [(a, 1.0 - s), (b, s)];
+            match bcs.get(&edge.marker) {
+                Some(CompiledBc::Neumann(values)) => {
+                    for (c, g) in values.iter().enumerate() {
+                        let g = g.at(p);
+                        for &(node, phi_i) in &phi {
+                            system.add_rhs(node * components + c, w * len * g * phi_i);
+                        }
+                    }
+                }
+                Some(CompiledBc::Robin { coefficient, ambient }) => {
+                    let alpha = coefficient.at(p);
+                    for (c, u_inf) in ambient.iter().enumerate() {
+                        let u_inf = u_inf.at(p);
+                        for &(i, phi_i) in &phi {
+                            system.add_rhs(i * components + c, w * len * alpha * u_inf * phi_i);
+                            for &(j, phi_j) in &phi {
+                                system.add(i * components + c, j * components + c, w * len * alpha * phi_i * phi_j);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// A sparse linear system with eliminated Dirichlet dofs.
+pub(crate) struct LinearSystem {
+    fixed: Vec<Option<f64>>,
+    matrix: CooMatrix,
+    rhs: Vec<f64>,
+}
+
+impl LinearSystem {
+    pub(crate) fn new(fixed: Vec<Option<f64>>) -> Self {
+        let n = fixed.len();
+        LinearSystem { fixed, matrix: CooMatrix::new(n, n), rhs: vec![0.0; n] }
+    }
+
+    /// Add `value` at `(i, j)`; a fixed column moves to the right-hand side.
+    pub(crate) fn add(&mut self, i: usize, j: usize, value: f64) {
+        if self.fixed[i].is_some() {
+            return;
+        }
+        match self.fixed[j] {
+            Some(g) => self.rhs[i] -= value * g,
+            None => self.matrix.push(i, j, value),
+        }
+    }
+
+    pub(crate) fn add_rhs(&mut self, i: usize, value: f64) {
+        if self.fixed[i].is_none() {
+            self.rhs[i] += value;
+        }
+    }
+
+    /// Solve with a sparse LDLᵀ (`symmetric`) or partially pivoted LU factorization.
+    ///
+    /// Symmetric systems must be positive definite: a zero or negative pivot
+    /// means the boundary conditions leave a rigid-body or floating mode free.
+    pub(crate) fn solve(mut self, symmetric: bool) -> Result<Vec<f64>, String> {
+        for (i, g) in self.fixed.iter().enumerate() {
+            if let Some(g) = g {
+                self.matrix.push(i, i, 1.0);
+                self.rhs[i] = *g;
+            }
+        }
+        let a = self.matrix.to_csr().to_csc();
+        let x = if symmetric {
+            SymbolicCholesky::analyze(&a, FillOrdering::MinimumDegree)
+                .and_then(|s| s.factor(&a))
+                .and_then(|f| {
+                    let inertia = f.inertia();
+                    if inertia.zero + inertia.negative > 0 {
+                        return Err(format!(
+                            "the system is not positive definite ({} zero, {} negative pivots), \
+                             so the model is under-constrained",
+                            inertia.zero, inertia.negative
+                        ));
+                    }
+                    f.solve(&self.rhs)
+                })
+        } else {
+            SymbolicLu::analyze(&a, FillOrdering::MinimumDegree)
+                .and_then(|s| s.factor(&a))
+                .and_then(|f| f.solve(&self.rhs))
+        }
+        .map_err(|e| format!("linear solve failed ({e}); check the boundary conditions"))?;
+        if x.iter().any(|v| !v.is_finite()) {
+            return Err("linear solve produced non-finite values; check the boundary conditions".to_string());
+        }
+        Ok(x)
+    }
+}
+
+/// Average element values onto nodes, weighted by element area.
+pub(crate) fn nodal_average(mesh: &TriMesh, values: &[f64]) -> Vec<f64> {
+    let mut sum = vec![0.0; mesh.nodes.len()];
+    let mut weight = vec![0.0; mesh.nodes.len()];
+    for (e, tri) in mesh.tris.iter().enumerate() {
+        let area = mesh.area(e);
+        for &v in tri {
+            sum[v] += area * values[e];
+            weight[v] += area;
+        }
+    }
+    sum.iter().zip(&weight).map(|(s, w)| if *w > 0.0 { s / w } else { 0.0 }).collect()
+}
+
+/// `(columns, rows)` from named columns.
+pub(crate) fn table(names: &[&str], columns: &[&[f64]]) -> (Vec<String>, Vec<Vec<f64>>) {
+    let rows = (0..columns.first().map_or(0, |c| c.len()))
+        .map(|r| columns.iter().map(|c| c[r]).collect())
+        .collect();
+    (names.iter().map(|s| s.to_string()).collect(), rows)
+}
@@ -0,0 +1,162 @@
+//! Steady Stokes flow `−μΔu + ∇p = f`, `∇·u = 0`.
+//!
+//! Equal-order P1 velocity and pressure are not inf-sup stable on their
+//! own, so the continuity equation carries the Brezzi–Pitkäranta /
+//! pressure-stabilising term `β·h_K²/μ·∫(∇p − f)·∇q` per element. The
+//! resulting symmetric indefinite system is solved with GMRES.
+//!
+//! Dirichlet data prescribes velocity `(ux, uy)`. Neumann data prescribes
+//! the pseudo-traction `μ∂u/∂n − p·n`; edges without a condition are
+//! traction-free outflows. When velocity is prescribed on every edge the
+//! pressure is only defined up to a constant and is pinned to 0 at node 0.
+
+use super::mesh::TriMesh;
+use super::{apply_natural_bcs, compile_bcs, dirichlet_values, table, BoundaryConditions, Coefficient, CompiledBc, LinearSystem};
+
+/// Pressure stabilisation constant `β`.
+const STABILISATION: f64 = 0.05;
+
+#[derive(Debug, Clone)]
+pub struct StokesProblem {
+    pub viscosity: f64,
+    /// Body force `(fx, fy)` per unit volume; expressions in `x`, `y`.
+    pub body_force: [String; 2],
+    pub bcs: BoundaryConditions,
+}
+
+#[derive(Debug, Clone)]
+pub struct StokesResult {
+    pub mesh: TriMesh,
+    /// Velocity `[ux, uy]` per node.
+    pub velocity: Vec<[f64; 2]>,
+    pub pressure: Vec<f64>,
+}
+
+impl StokesResult {
+    /// Columns `x, y, ux, uy, p, speed`.
+    pub fn nodal_table(&self) -> (Vec<String>, Vec<Vec<f64>>) {
+        let x: Vec<f64> = self.mesh.nodes.iter().map(|p| p.0).collect();
+        let y: Vec<f64> = self.mesh.nodes.iter().map(|p| p.1).collect();
+        let ux: Vec<f64> = self.velocity.iter().map(|u| u[0]).collect();
+        let uy: Vec<f64> = self.velocity.iter().map(|u| u[1]).collect();
+        let speed: Vec<f64> = self.velocity.iter().map(|u| u[0].hypot(u[1])).collect();
+        table(&["x", "y", "ux", "uy", "p", "speed"], &[&x, &y, &ux, &uy, &self.pressure, &speed])
+    }
+
+    /// Columns `x, y, vorticity, divergence` at element centroids.
+    pub fn element_table(&self) -> (Vec<String>, Vec<Vec<f64>>) {
+        let count = self.mesh.tris.len();
+        let (x, y): (Vec<f64>, Vec<f64>) = (0..count).map(|e| self.mesh.centroid(e)).unzip();
+        let (vorticity, divergence): (Vec<f64>, Vec<f64>) = (0..count)
+            .map(|e| {
+                let (grad, _) = self.mesh.gradients(e);
+                let d = |c: usize, dir: usize| -> f64 {
+                    self.mesh.tris[e].iter().enumerate().map(|(i, &v)| grad[i][dir] * self.velocity[v][c]).sum()
+                };
+                (d(1, 0) - d(0, 1), d(0, 0) + d(1, 1))
+            })
+            .unzip();
+        table(&["x", "y", "vorticity", "divergence"], &[&x, &y, &vorticity, &divergence])
+    }
+}
+
+pub fn solve_stokes(mesh: TriMesh, problem: &StokesProblem) -> Result<StokesResult, String> {
+    let mu = problem.viscosity;
+    if mu.is_nan() || mu <= 0.0 {
+        return Err(format!("viscosity must be positive, got {mu}"));
+    }
+    let force = [Coefficient::new("body force", &problem.body_force[0])?, Coefficient::new("body force", &problem.body_force[1])?];
+    let bcs = compile_bcs(&problem.bcs, 2, &mesh)?;
+    let n = mesh.nodes.len();
+    let pressure = |node: usize| 2 * n + node;
+
+    let mut fixed = dirichlet_values(&mesh, &bcs, 2, 3 * n);
+    let enclosed = mesh.boundary.iter().all(|edge| {
+        matches!(bcs.get(&edge.marker), Some(CompiledBc::Dirichlet(values)) if values.iter().all(Option::is_some))
+    });
+    if enclosed {
+        fixed[pressure(0)] = Some(0.0);
+    }
+    let mut system = LinearSystem::new(fixed);
+
+    for (e, tri) in mesh.tris.iter().enumerate() {
+        let (grad, area) = mesh.gradients(e);
+        let centroid = mesh.centroid(e);
+        let f = [force[0].at(centroid), force[1].at(centroid)];
+        let tau = STABILISATION * mesh.diameter(e).powi(2) / mu;
+        for i in 0..3 {
+            for (c, fc) in f.iter().enumerate() {
+                system.add_rhs(2 * tri[i] + c, fc * area / 3.0);
+            }
+            system.add_rhs(pressure(tri[i]), -tau * area * (f[0] * grad[i][0] + f[1] * grad[i][1]));
+            for j in 0..3 {
+                let laplace = grad[i][0] * grad[j][0] + grad[i][1] * grad[j][1];
+                for (c, dj) in grad[j].iter().enumerate() {
+                    system.add(2 * tri[i] + c, 2 * tri[j] + c, mu * area * laplace);
+                    // −∫ q ∂_c φ_j (q = φ_i, ∫φ_i = A/3) and its transpose.
+                    let div = -area / 3.0 * dj;
+                    system.add(pressure(tri[i]), 2 * tri[j] + c, div);
+                    system.add(2 * tri[j] + c, pressure(tri[i]), div);
+                }
+                system.add(pressure(tri[i]), pressure(tri[j]), -tau * area * laplace);
+            }
+        }
+    }
+    apply_natural_bcs(&mut system, &mesh, &bcs, 2, 1.0);
+    let x = system.solve(false)?;
+
+    let velocity = (0..n).map(|i| [x[2 * i], x[2 * i + 1]]).collect();
+    let pressure = x[2 * n..].to_vec();
+    Ok(StokesResult { mesh, velocity, pressure })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fem::mesh::{triangulate, Geometry};
+    use crate::fem::BoundaryCondition;
+    use std::collections::HashMap;
+
+    fn velocity(ux: &str, uy: &str) -> BoundaryCondition {
+        BoundaryCondition::Dirichlet(vec![Some(ux.to_string()), Some(uy.to_string())])
+    }
+
+    #[test]
+    fn poiseuille_channel() {
+        // Parabolic inflow with peak 1 into a channel of height 1, free outflow:
+        // u = 4y(1 - y), p = 8μ(L - x).
+        let (l, mu) = (3.0, 0.5);
+        let mesh = triangulate(&Geometry::rectangle(0.0, 0.0, l, 1.0), 0.1).unwrap();
+        let problem = StokesProblem {
+            viscosity: mu,
+            body_force: ["0".into(), "0".into()],
+            bcs: HashMap::from([(0, velocity("0", "0")), (2, velocity("0", "0")), (3, velocity("4*y*(1-y)", "0"))]),
+        };
+        let result = solve_stokes(mesh, &problem).unwrap();
+        for ((p, u), pr) in result.mesh.nodes.iter().zip(&result.velocity).zip(&result.pressure) {
+            assert!((u[0] - 4.0 * p.1 * (1.0 - p.1)).abs() < 0.03, "ux at {p:?} = {}", u[0]);
+            assert!(u[1].abs() < 0.03);
+            assert!((pr - 8.0 * mu * (l - p.0)).abs() < 0.05 * 8.0 * mu * l, "p at {p:?} = {pr}");
+        }
+    }
+
+    #[test]
+    fn enclosed_flow_pins_the_pressure() {
+        // u = (x, −y) is harmonic and divergence-free with constant pressure.
+        let mesh = triangulate(&Geometry::rectangle(0.0, 0.0, 1.0, 1.0), 0.2).unwrap();
+        let wall = velocity("x", "-y");
+        let problem = StokesProblem {
+            viscosity: 1.0,
+            body_force: ["0".into(), "0".into()],
+            bcs: (0..4).map(|m| (m, wall.clone())).collect(),
+        };
+        let result = solve_stokes(mesh, &problem).unwrap();
+        for ((p, u), pr) in result.mesh.nodes.iter().zip(&result.velocity).zip(&result.pressure) {
+            assert!((u[0] - p.0).abs() < 1e-8 && (u[1] + p.1).abs() < 1e-8);
+            assert!(pr.abs() < 1e-8);
+        }
+        let (columns, rows) = result.element_table();
+        assert_eq!(columns, ["x", "y", "vorticity", "divergence"]);
+        assert!(rows.iter().all(|r| r[2].abs() < 1e-8 && r[3].abs() < 1e-8));
+    }
+}
@@ -0,0 +1,829 @@
+//! `fmu_cosim` — fixed-step co-simulation of declarative ("native") FMUs.
+//!
+//! A [`NativeFmu`] is an FMU whose behaviour is given declaratively rather
+//! than by a compiled binary: the variable interface comes from
+//! `modelDescription.xml` (parsed by
+//! [`crate::acausal::parse_fmu_model_description`]) and the dynamics from a
+//! list of equations over those variables:
+//!
+//! - `der(x) = f(...)` — `x` is a continuous state (an `output` or `local`
+//!   variable) whose start value is the initial condition;
+//! - `y = g(...)` — `y` (an `output` or `local`) is recomputed whenever the
+//!   instance's time, states or inputs change. Assignments may reference each
+//!   other in any order; they are sorted by dependency and algebraic loops are
+//!   rejected.
+//!
+//! Expressions use the [`crate::expr`] syntax and may reference any
+//! declared variable plus `time`/`t`; `pi` and `e` are the constants, so
+//! variables with those names cannot be referenced. Outputs with neither an
+//! equation nor a state derivative keep their start value. FMUs written by
+//! [`crate::fmu_export`] run with the equations they were generated from
+//! ([`NativeFmu::from_export`]).
+//!
+//! Instances follow FMI 2.0 Co-Simulation semantics: [`NativeFmu::initialize`]
+//! plays the role of `fmi2SetupExperiment`/`fmi2ExitInitializationMode`, and
+//! [`NativeFmu::do_step`] advances from the current communication point with
+//! inputs held constant over the step, integrating internally with classical
+//! RK4 sub-steps.
+//!
+//! The [`Master`] couples named instances through `output → input`
+//! connections and drives them on a fixed communication grid with either a
+//! Jacobi (all instances see the previous communication point) or
+//! Gauss–Seidel (instances see the outputs of those already stepped)
+//! algorithm. Inputs left unconnected are the graph's input ports and outputs
+//! are its output ports, both named `instance.variable`; results come back as
+//! a [`CoSimResult`] table sampled at every communication point.
+//!
+//! Loading native FMU binaries is out of scope here.
+
+use crate::acausal::{parse_fmu_model_description, FmuCausality, FmuImportMetadata};
+use crate::fmu_export::{generate_fmu_xml, FmuConfig};
+use crate::expr::{compile, CompiledExpr};
+use crate::fmu_export::evaluation_order;
+use crate::types::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Upper bound on communication steps in a single [`Master::run`].
+pub const MAX_COMMUNICATION_STEPS: usize = 1_000_000;
+
+/// Names bound to the instance time when the FMU does not declare them.
+const TIME_NAMES: [&str; 2] = ["time", "t"];
+
+// ── Native FMU instance ───────────────────────────────────────────────────────
+
+/// A co-simulation FMU defined by its model description and equations.
+#[derive(Debug, Clone)]
+pub struct NativeFmu {
+    meta: FmuImportMetadata,
+    causality: HashMap<String, FmuCausality>,
+    states: Vec<String>,
+    derivatives: Vec<CompiledExpr>,
+    /// Output/local assignments in evaluation order.
+    assignments: Vec<(String, CompiledExpr)>,
+    values: HashMap<String, f64>,
+    time: f64,
+    substeps: usize,
+    initialized: bool,
+}
+
+impl NativeFmu {
+    /// Build an instance from parsed metadata and `der(x) = …` / `y = …`
+    /// equations.
+    pub fn new(meta: FmuImportMetadata, equations: &[String]) -> Result<Self, String> {
+        let mut causality = HashMap::new();
+        let mut values = HashMap::new();
+        for v in meta
+            .inputs
+            .iter()
+            .chain(&meta.outputs)
+            .chain(&meta.parameters)
+            .chain(&meta.state_variables)
+        {
+            if causality.insert(v.name.clone(), v.causality.clone()).is_some() {
+                return Err(format!("[FMU_COSIM] Duplicate variable '{}'", v.name));
+            }
+            values.insert(v.name.clone(), v.initial.unwrap_or(0.0));
+        }
+
+        let mut states = Vec::new();
+        let mut derivatives = Vec::new();
+        let mut assigned: Vec<(String, CompiledExpr)> = Vec::new();
+        for text in equations.iter().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            let (lhs, rhs) = text
+                .split_once('=')
+                .ok_or_else(|| format!("[FMU_COSIM] Equation '{text}' has no '='"))?;
+            let lhs = lhs.trim();
+            let expr = compile(rhs.trim()).map_err(|e| format!("[FMU_COSIM] Equation '{text}': {e}"))?;
+            let deps = expr.dependencies();
+            if let Some(v) = deps.vectors.iter().next() {
+                return Err(format!("[FMU_COSIM] Equation '{text}' indexes '{v}' with a computed subscript"));
+            }
+            for sym in deps.scalars {
+                if !causality.contains_key(&sym) && !TIME_NAMES.contains(&sym.as_str()) {
+                    return Err(format!("[FMU_COSIM] Equation '{text}' references undeclared variable '{sym}'"));
+                }
+            }
+            let (target, is_state) = match lhs.strip_prefix("der(").and_then(|s| s.strip_suffix(')')) {
+                Some(inner) => (inner.trim(), true),
+                None => (lhs, false),
+            };
+            match causality.get(target) {
+                Some(FmuCausality::Output | FmuCausality::Local) => {}
+                Some(_) => {
+                    return Err(format!(
+                        "[FMU_COSIM] '{target}' is not an output or local variable and cannot be computed"
+                    ))
+                }
+                None => return Err(format!("[FMU_COSIM] Equation '{text}' defines undeclared variable '{target}'")),
+            }
+            if states.iter().any(|s| s == target) || assigned.iter().any(|(n, _)| n == target) {
+                return Err(format!("[FMU_COSIM] Variable '{target}' is defined by more than one equation"));
+            }
+            if is_state {
+                states.push(target.to_string());
+                derivatives.push(expr);
+            } else {
+                assigned.push((target.to_string(), expr));
+            }
+        }
+
+        for out in &meta.outputs {
+            let defined = states.contains(&out.name) || assigned.iter().any(|(n, _)| *n == out.name);
+            if !defined && out.initial.is_none() {
+                return Err(format!(
+                    "[FMU_COSIM] Output '{}' has neither an equation nor a start value",
+                    out.name
+                ));
+            }
+        }
+
+        let assignments = sort_assignments(assigned)?;
+        let mut fmu = NativeFmu {
+            meta,
+            causality,
+            states,
+            derivatives,
+            assignments,
+            values,
+            time: 0.0,
+            substeps: 10,
+            initialized: false,
+        };
+        fmu.refresh();
+        Ok(fmu)
+    }
+
+    /// Parse `modelDescription.xml` and attach the given equations.
+    pub fn from_model_description(xml: &str, equations: &[String]) -> Result<Self, String> {
+        Self::new(parse_fmu_model_description(xml)?, equations)
+    }
+
+    /// Instantiate an FMU generated by [`crate::fmu_export`] with its output
+    /// equations and state derivatives; outputs without an equation hold
+    /// the exported values for the whole experiment.
+    pub fn from_export(cfg: &FmuConfig) -> Result<Self, String> {
+        let equations: Vec<String> = cfg
+            .outputs
+            .iter()
+            .filter_map(|v| v.equation.as_ref().map(|eq| format!("{} = {eq}", v.name)))
+            .chain(cfg.states.iter().filter_map(|v| v.equation.as_ref().map(|eq| format!("der({}) = {eq}", v.name))))
+            .collect();
+        Self::from_model_description(&generate_fmu_xml(cfg), &equations)
+    }
+
+    /// The parsed model description.
+    pub fn meta(&self) -> &FmuImportMetadata {
+        &self.meta
+    }
+
+    /// Names of the continuous states, in equation order.
+    pub fn states(&self) -> &[String] {
+        &self.states
+    }
+
+    /// Current instance time (the last communication point reached).
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Number of RK4 sub-steps per `do_step` (default 10, at least 1).
+    pub fn set_substeps(&mut self, n: usize) {
+        self.substeps = n.max(1);
+    }
+
+    /// Causality of a declared variable.
+    pub fn causality(&self, name: &str) -> Option<&FmuCausality> {
+        self.causality.get(name)
+    }
+
+    /// Set an input or parameter (`fmi2SetReal`). Before [`initialize`] the
+    /// start value of a state may be overridden as well.
+    ///
+    /// [`initialize`]: NativeFmu::initialize
+    pub fn set_real(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match self.causality.get(name) {
+            Some(FmuCausality::Input | FmuCausality::Parameter) => {}
+            Some(_) if !self.initialized && self.states.iter().any(|s| s == name) => {}
+            Some(_) => return Err(format!("[FMU_COSIM] Variable '{name}' of {} cannot be set", self.meta.model_name)),
+            None => return Err(format!("[FMU_COSIM] {} has no variable '{name}'", self.meta.model_name)),
+        }
+        self.values.insert(name.to_string(), value);
+        self.refresh();
+        Ok(())
+    }
+
+    /// Read any declared variable (`fmi2GetReal`).
+    pub fn get_real(&self, name: &str) -> Result<f64, String> {
+        if !self.causality.contains_key(name) {
+            return Err(format!("[FMU_COSIM] {} has no variable '{name}'", self.meta.model_name));
+        }
+        Ok(self.values[name])
+    }
+
+    /// Enter the experiment at `start_time` with the current start values.
+    pub fn initialize(&mut self, start_time: f64) {
+        self.time = start_time;
+        self.initialized = true;
+        self.refresh();
+    }
+
+    /// Advance from `current_time` by `step_size` with inputs held constant
+    /// (`fmi2DoStep`). `current_time` must equal the instance time.
+    pub fn do_step(&mut self, current_time: f64, step_size: f64) -> Result<(), String> {
+        let name = &self.meta.model_name;
+        if !self.initialized {
+            return Err(format!("[FMU_COSIM] {name}: do_step called before initialize"));
+        }
+        if step_size.is_nan() || step_size <= 0.0 {
+            return Err(format!("[FMU_COSIM] {name}: step size must be positive"));
+        }
+        if (current_time - self.time).abs() > 1e-9 * self.time.abs().max(1.0) {
+            return Err(format!(
+                "[FMU_COSIM] {name}: communication point {current_time} does not match instance time {}",
+                self.time
+            ));
+        }
+
+        let mut y: Vec<f64> = self.states.iter().map(|s| self.values[s]).collect();
+        if !y.is_empty() {
+            let h = step_size / self.substeps as f64;
+            let mut t = current_time;
+            for _ in 0..self.substeps {
+                let k1 = self.derivative(t, &y);
+                let k2 = self.derivative(t + h / 2.0, &axpy(&y, h / 2.0, &k1));
+                let k3 = self.derivative(t + h / 2.0, &axpy(&y, h / 2.0, &k2));
+                let k4 = self.derivative(t + h, &axpy(&y, h, &k3));
+                for i in 0..y.len() {
+                    y[i] += h / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]);
+                }
+                t += h;
+            }
+            if let Some(i) = y.iter().position(|v| !v.is_finite()) {
+                return Err(format!(
+                    "[FMU_COSIM] {name}: state '{}' became non-finite at t = {}",
+                    self.states[i],
+                    current_time + step_size
+                ));
+            }
+        }
+        for (s, v) in self.states.iter().zip(y) {
+            self.values.insert(s.clone(), v);
+        }
+        self.time = current_time + step_size;
+        self.refresh();
+        Ok(())
+    }
+
+    /// Recompute all assignments at the current time and state.
+    fn refresh(&mut self) {
+        let mut values = std::mem::take(&mut self.values);
+        self.evaluate(self.time, &mut values);
+        self.values = values;
+    }
+
+    fn evaluate(&self, time: f64, values: &mut HashMap<String, f64>) {
+        for name in TIME_NAMES {
+            if !self.causality.contains_key(name) {
+                values.insert(name.to_string(), time);
+            }
+        }
+        for (name, expr) in &self.assignments {
+            let v = expr.eval(values).unwrap_or(f64::NAN);
+            values.insert(name.clone(), v);
+        }
+    }
+
+    fn derivative(&self, time: f64, y: &[f64]) -> Vec<f64> {
+        let mut values = self.values.clone();
+        for (s, &v) in self.states.iter().zip(y) {
+            values.insert(s.clone(), v);
+        }
+        self.evaluate(time, &mut values);
+        self.derivatives.iter().map(|d| d.eval(&values).unwrap_or(f64::NAN)).collect()
+    }
+}
+
+fn axpy(y: &[f64], a: f64, k: &[f64]) -> Vec<f64> {
+    y.iter().zip(k).map(|(y, k)| y + a * k).collect()
+}
+
+/// Order assignments so every target is computed before it is used.
+fn sort_assignments(assigned: Vec<(String, CompiledExpr)>) -> Result<Vec<(String, CompiledExpr)>, String> {
+    let targets: Vec<String> = assigned.iter().map(|(n, _)| n.clone()).collect();
+    let deps: Vec<Vec<String>> =
+        assigned.iter().map(|(_, e)| e.dependencies().scalars.into_iter().collect()).collect();
+    let order = evaluation_order(&targets, &deps)
+        .map_err(|name| format!("[FMU_COSIM] Algebraic loop through '{name}'"))?;
+    let mut slots: Vec<Option<(String, CompiledExpr)>> = assigned.into_iter().map(Some).collect();
+    Ok(order.into_iter().filter_map(|i| slots[i].take()).collect())
+}
+
+// ── Master algorithm ──────────────────────────────────────────────────────────
+
+/// Order in which instances exchange data within a communication step.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MasterAlgorithm {
+    /// Step instances in order; each sees the new outputs of those before it.
+    #[default]
+    GaussSeidel,
+    /// Step all instances from the same communication-point data.
+    Jacobi,
+}
+
+/// Communication grid and algorithm for [`Master::run`].
+#[derive(Debug, Clone)]
+pub struct MasterConfig {
+    pub start_time: f64,
+    pub stop_time: f64,
+    pub step_size: f64,
+    pub algorithm: MasterAlgorithm,
+}
+
+impl Default for MasterConfig {
+    fn default() -> Self {
+        MasterConfig { start_time: 0.0, stop_time: 10.0, step_size: 0.01, algorithm: MasterAlgorithm::default() }
+    }
+}
+
+/// An external signal driving an unconnected input port.
+#[derive(Debug, Clone)]
+pub enum Signal {
+    Constant(f64),
+    /// Piecewise-linear in time, held constant outside the sample range.
+    Table { t: Vec<f64>, v: Vec<f64> },
+}
+
+impl Signal {
+    pub fn at(&self, time: f64) -> f64 {
+        match self {
+            Signal::Constant(v) => *v,
+            Signal::Table { t, v } => {
+                if t.is_empty() {
+                    return 0.0;
+                }
+                let k = t.partition_point(|&x| x <= time);
+                if k == 0 {
+                    v[0]
+                } else if k == t.len() {
+                    v[k - 1]
+                } else {
+                    let w = (time - t[k - 1]) / (t[k] - t[k - 1]);
+                    v[k - 1] + w * (v[k] - v[k - 1])
+                }
+            }
+        }
+    }
+}
+
+/// Co-simulation results sampled at every communication point.
+#[derive(Debug, Clone)]
+pub struct CoSimResult {
+    /// `time` followed by every output port (`instance.variable`).
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<f64>>,
+}
+
+impl CoSimResult {
+    /// One column of the result table by name.
+    pub fn series(&self, column: &str) -> Option<Vec<f64>> {
+        let j = self.columns.iter().position(|c| c == column)?;
+        Some(self.rows.iter().map(|r| r[j]).collect())
+    }
+}
+
+type PortRef = (usize, String);
+
+/// Fixed-step co-simulation master over named [`NativeFmu`] instances.
+#[derive(Debug, Clone, Default)]
+pub struct Master {
+    instances: Vec<(String, NativeFmu)>,
+    connections: Vec<(PortRef, PortRef)>,
+    external: Vec<(PortRef, Signal)>,
+}
+
+impl Master {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an instance under a unique name (no `.`).
+    pub fn add(&mut self, name: &str, fmu: NativeFmu) -> Result<(), String> {
+        if name.is_empty() || name.contains('.') {
+            return Err(format!("[FMU_COSIM] Invalid instance name '{name}'"));
+        }
+        if self.instances.iter().any(|(n, _)| n == name) {
+            return Err(format!("[FMU_COSIM] Duplicate instance '{name}'"));
+        }
+        self.instances.push((name.to_string(), fmu));
+        Ok(())
+    }
+
+    pub fn instance(&self, name: &str) -> Option<&NativeFmu> {
+        self.instances.iter().find(|(n, _)| n == name).map(|(_, f)| f)
+    }
+
+    /// Connect `instance.output` to `instance.input`. Each input takes at
+    /// most one source.
+    pub fn connect(&mut self, from: &str, to: &str) -> Result<(), String> {
+        let src = self.resolve(from, FmuCausality::Output)?;
+        let dst = self.resolve(to, FmuCausality::Input)?;
+        if self.connections.iter().any(|(_, d)| *d == dst) {
+            return Err(format!("[FMU_COSIM] Input '{to}' is already connected"));
+        }
+        self.connections.push((src, dst));
+        Ok(())
+    }
+
+    /// Drive an unconnected input port from an external signal.
+    pub fn set_input(&mut self, port: &str, signal: Signal) -> Result<(), String> {
+        let dst = self.resolve(port, FmuCausality::Input)?;
+        if self.connections.iter().any(|(_, d)| *d == dst) {
+            return Err(format!("[FMU_COSIM] Input '{port}' is driven by a connection"));
+        }
+        self.external.retain(|(d, _)| *d != dst);
+        self.external.push((dst, signal));
+        Ok(())
+    }
+
+    /// Inputs not fed by any connection — the graph's input ports.
+    pub fn input_ports(&self) -> Vec<String> {
+        let mut ports = Vec::new();
+        for (i, (name, fmu)) in self.instances.iter().enumerate() {
+            for v in &fmu.meta.inputs {
+                let connected = self.connections.iter().any(|(_, (j, var))| *j == i && *var == v.name);
+                if !connected {
+                    ports.push(format!("{name}.{}", v.name));
+                }
+            }
+        }
+        ports
+    }
+
+    /// Every instance output — the graph's output ports.
+    pub fn output_ports(&self) -> Vec<String> {
+        self.instances
+            .iter()
+            .flat_map(|(name, fmu)| fmu.meta.outputs.iter().map(move |v| format!("{name}.{}", v.name)))
+            .collect()
+    }
+
+    /// Initialise every instance and step them from `start_time` to
+    /// `stop_time`; the last step is shortened to land on `stop_time`.
+    pub fn run(&mut self, cfg: &MasterConfig) -> Result<CoSimResult, String> {
+        if !cfg.step_size.is_finite() || cfg.step_size <= 0.0 {
+            return Err("[FMU_COSIM] Communication step size must be positive".to_string());
+        }
+        if cfg.stop_time.is_nan() || cfg.stop_time <= cfg.start_time {
+            return Err("[FMU_COSIM] Stop time must be after start time".to_string());
+        }
+        let span = cfg.stop_time - cfg.start_time;
+        let steps = (span / cfg.step_size - 1e-9).ceil().max(1.0);
+        if steps > MAX_COMMUNICATION_STEPS as f64 {
+            return Err(format!(
+                "[FMU_COSIM] {steps} communication steps exceed the limit of {MAX_COMMUNICATION_STEPS}"
+            ));
+        }
+        let steps = steps as usize;
+
+        let outputs: Vec<PortRef> = self
+            .instances
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (_, f))| f.meta.outputs.iter().map(move |v| (i, v.name.clone())))
+            .collect();
+        let mut columns = vec!["time".to_string()];
+        columns.extend(self.output_ports());
+
+        for (_, fmu) in &mut self.instances {
+            fmu.initialize(cfg.start_time);
+        }
+        let mut t = cfg.start_time;
+        self.exchange(t)?;
+        let mut rows = Vec::with_capacity(steps + 1);
+        rows.push(self.sample(t, &outputs));
+
+        for k in 0..steps {
+            let h = if k + 1 == steps { cfg.stop_time - t } else { cfg.step_size };
+            match cfg.algorithm {
+                MasterAlgorithm::Jacobi => {
+                    for (_, fmu) in &mut self.instances {
+                        fmu.do_step(t, h)?;
+                    }
+                }
+                MasterAlgorithm::GaussSeidel => {
+                    for i in 0..self.instances.len() {
+                        self.feed(|dst| dst == i)?;
+                        self.instances[i].1.do_step(t, h)?;
+                    }
+                }
+            }
+            t = if k + 1 == steps { cfg.stop_time } else { cfg.start_time + (k + 1) as f64 * cfg.step_size };
+            self.exchange(t)?;
+            rows.push(self.sample(t, &outputs));
+        }
+        Ok(CoSimResult { columns, rows })
+    }
+
+    /// Apply external signals at `time`, then propagate connections until
+    /// direct-feedthrough chains settle (one pass per instance).
+    fn exchange(&mut self, time: f64) -> Result<(), String> {
+        for ((i, var), signal) in &self.external {
+            self.instances[*i].1.set_real(var, signal.at(time))?;
+        }
+        for _ in 0..self.instances.len() {
+            self.feed(|_| true)?;
+        }
+        Ok(())
+    }
+
+    /// Copy connected outputs into the inputs of instances selected by `to`.
+    fn feed(&mut self, to: impl Fn(usize) -> bool) -> Result<(), String> {
+        for ((si, sv), (di, dv)) in &self.connections {
+            if to(*di) {
+                let v = self.instances[*si].1.get_real(sv)?;
+                self.instances[*di].1.set_real(dv, v)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn sample(&self, time: f64, outputs: &[PortRef]) -> Vec<f64> {
+        let mut row = vec![time];
+        row.extend(outputs.iter().map(|(i, v)| self.instances[*i].1.values[v]));
+        row
+    }
+
+    fn resolve(&self, port: &str, want: FmuCausality) -> Result<PortRef, String> {
+        let (inst, var) = port
+            .split_once('.')
+            .ok_or_else(|| format!("[FMU_COSIM] Port '{port}' must be written instance.variable"))?;
+        let i = self
+            .instances
+            .iter()
+            .position(|(n, _)| n == inst)
+            .ok_or_else(|| format!("[FMU_COSIM] Unknown instance '{inst}'"))?;
+        match self.instances[i].1.causality(var) {
+            Some(c) if std::mem::discriminant(c) == std::mem::discriminant(&want) => Ok((i, var.to_string())),
+            Some(_) => Err(format!("[FMU_COSIM] '{port}' is not an {want:?} variable")),
+            None => Err(format!("[FMU_COSIM] Instance '{inst}' has no variable '{var}'")),
+        }
+    }
+}
+
+// ── JSON specification ────────────────────────────────────────────────────────
+
+/// Build a master from a JSON specification:
+///
+/// ```json
+/// { "fmus": [{ "name": "plant", "modelDescription": "<fmiModelDescription …>",
+///              "equations": ["der(x) = -k*x + u", "y = x"],
+///              "parameters": { "k": 2 }, "substeps": 10 }],
+///   "connections": [{ "from": "ctrl.u", "to": "plant.u" }] }
+/// ```
+///
+/// An FMU entry may also be the JSON emitted by the `fmu.export` block
+/// (`modelDescriptionXml`); the instance name defaults to the model name.
+/// A bare FMU object or an array of them is accepted in place of the
+/// top-level object.
+pub fn master_from_json(spec: &serde_json::Value) -> Result<Master, String> {
+    let (fmus, connections) = match spec {
+        serde_json::Value::Array(list) => (list.clone(), Vec::new()),
+        serde_json::Value::Object(obj) if obj.contains_key("fmus") => (
+            obj["fmus"].as_array().cloned().ok_or("[FMU_COSIM] 'fmus' must be an array")?,
+            obj.get("connections").and_then(|c| c.as_array()).cloned().unwrap_or_default(),
+        ),
+        serde_json::Value::Object(_) => (vec![spec.clone()], Vec::new()),
+        _ => return Err("[FMU_COSIM] FMU specification must be a JSON object or array".to_string()),
+    };
+    let mut master = Master::new();
+    for f in &fmus {
+        let xml = f
+            .get("modelDescription")
+            .or_else(|| f.get("modelDescriptionXml"))
+            .and_then(|x| x.as_str())
+            .ok_or("[FMU_COSIM] Each FMU needs a 'modelDescription' XML string")?;
+        let equations: Vec<String> = f
+            .get("equations")
+            .and_then(|e| e.as_array())
+            .map(|a| a.iter().filter_map(|s| s.as_str().map(str::to_string)).collect())
+            .unwrap_or_default();
+        let mut fmu = NativeFmu::from_model_description(xml, &equations)?;
+        if let Some(params) = f.get("parameters").and_then(|p| p.as_object()) {
+            for (k, v) in params {
+                let v = v.as_f64().ok_or_else(|| format!("[FMU_COSIM] Parameter '{k}' must be a number"))?;
+                fmu.set_real(k, v)?;
+            }
+        }
+        if let Some(n) = f.get("substeps").and_then(|n| n.as_u64()) {
+            fmu.set_substeps(n as usize);
+        }
+        let name = f
+            .get("name")
+            .and_then(|n| n.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| fmu.meta.model_name.clone());
+        master.add(&name, fmu)?;
+    }
+    for c in &connections {
+        let from = c.get("from").and_then(|s| s.as_str()).ok_or("[FMU_COSIM] Connection needs 'from'")?;
+        let to = c.get("to").and_then(|s| s.as_str()).ok_or("[FMU_COSIM] Connection needs 'to'")?;
+        master.connect(from, to)?;
+    }
+    Ok(master)
+}
+
+/// Bind a graph value to the master's unconnected input ports: a scalar
+/// drives the single input port, a vector drives the ports in order, and a
+/// table with a `time` (or `t`) column drives the ports named by its other
+/// columns (`instance.variable`, or a bare variable name when unambiguous).
+pub fn bind_inputs(master: &mut Master, value: &Value) -> Result<(), String> {
+    let ports = master.input_ports();
+    match value {
+        Value::Scalar { value } => {
+            if ports.len() != 1 {
+                return Err(format!("[FMU_COSIM] A scalar input needs exactly one input port, found {}", ports.len()));
+            }
+            master.set_input(&ports[0], Signal::Constant(*value))
+        }
+        Value::Vector { value } => {
+            if value.len() != ports.len() {
+                return Err(format!(
+                    "[FMU_COSIM] Input vector has {} values for {} input ports",
+                    value.len(),
+                    ports.len()
+                ));
+            }
+            for (p, v) in ports.iter().zip(value) {
+                master.set_input(p, Signal::Constant(*v))?;
+            }
+            Ok(())
+        }
+        Value::Table { columns, rows } => {
+            let tj = columns
+                .iter()
+                .position(|c| c == "time" || c == "t")
+                .ok_or("[FMU_COSIM] Input table needs a 'time' column")?;
+            let t: Vec<f64> = rows.iter().map(|r| r.get(tj).copied().unwrap_or(f64::NAN)).collect();
+            if t.windows(2).any(|w| w[1] <= w[0]) || t.iter().any(|x| !x.is_finite()) {
+                return Err("[FMU_COSIM] Input table time column must be finite and increasing".to_string());
+            }
+            let mut seen = HashSet::new();
+            for (j, col) in columns.iter().enumerate().filter(|(j, _)| *j != tj) {
+                let matches: Vec<&String> = ports
+                    .iter()
+                    .filter(|p| *p == col || p.split_once('.').is_some_and(|(_, v)| v == col))
+                    .collect();
+                let port = match matches.as_slice() {
+                    [p] => (*p).clone(),
+                    [] => return Err(format!("[FMU_COSIM] Input column '{col}' matches no input port")),
+                    _ => return Err(format!("[FMU_COSIM] Input column '{col}' is ambiguous; use instance.variable")),
+                };
+                if !seen.insert(port.clone()) {
+                    return Err(format!("[FMU_COSIM] Input port '{port}' is driven twice"));
+                }
+                let v = rows.iter().map(|r| r.get(j).copied().unwrap_or(f64::NAN)).collect();
+                master.set_input(&port, Signal::Table { t: t.clone(), v })?;
+            }
+            Ok(())
+        }
+        _ => Err("[FMU_COSIM] Inputs must be a scalar, vector or table".to_string()),
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fmu_export::FmuVariable;
+
+    fn xml(model: &str, vars: &[(&str, &str, Option<f64>)]) -> String {
+        let body: String = vars
+            .iter()
+            .enumerate()
+            .map(|(i, (name, causality, start))| {
+                let start = start.map(|s| format!(" start=\"{s}\"")).unwrap_or_default();
+                format!(
+                    "<ScalarVariable name=\"{name}\" valueReference=\"{}\" causality=\"{causality}\"><Real{start}/></ScalarVariable>\n",
+                    i + 1
+                )
+            })
+            .collect();
+        format!(
+            "<fmiModelDescription fmiVersion=\"2.0\" modelName=\"{model}\" guid=\"{{0}}\">\n<CoSimulation modelIdentifier=\"{model}\"/>\n<ModelVariables>\n{body}</ModelVariables>\n</fmiModelDescription>"
+        )
+    }
+
+    fn decay() -> NativeFmu {
+        let md = xml("Decay", &[("u", "input", Some(0.0)), ("k", "parameter", Some(2.0)), ("x", "output", Some(1.0))]);
+        NativeFmu::from_model_description(&md, &["der(x) = -k*x + u".to_string()]).unwrap()
+    }
+
+    #[test]
+    fn do_step_integrates_a_state_equation() {
+        let mut fmu = decay();
+        fmu.initialize(0.0);
+        for k in 0..10 {
+            fmu.do_step(k as f64 * 0.1, 0.1).unwrap();
+        }
+        assert!((fmu.get_real("x").unwrap() - (-2.0f64).exp()).abs() < 1e-7);
+        assert!(fmu.do_step(0.5, 0.1).is_err(), "communication point must match instance time");
+    }
+
+    #[test]
+    fn equations_are_validated_and_sorted() {
+        let md = xml("Alg", &[("u", "input", None), ("a", "local", None), ("y", "output", None)]);
+        let fmu = NativeFmu::from_model_description(&md, &["y = 2*a".into(), "a = u + time".into()]).unwrap();
+        let mut fmu = fmu;
+        fmu.set_real("u", 3.0).unwrap();
+        assert_eq!(fmu.get_real("y").unwrap(), 6.0);
+        assert!(fmu.set_real("y", 1.0).is_err());
+
+        for bad in [vec!["y = q"], vec!["u = 1"], vec!["y = a", "a = y"], vec!["a = 1"]] {
+            let eqs: Vec<String> = bad.into_iter().map(String::from).collect();
+            assert!(NativeFmu::from_model_description(&md, &eqs).is_err(), "{eqs:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn closed_loop_matches_the_analytic_response() {
+        // Plant x' = u, controller u = -k·x (feedthrough): x = e^{-k t}.
+        let plant = NativeFmu::from_model_description(
+            &xml("Plant", &[("u", "input", Some(0.0)), ("x", "output", Some(1.0))]),
+            &["der(x) = u".into()],
+        )
+        .unwrap();
+        let ctrl = NativeFmu::from_model_description(
+            &xml("Ctrl", &[("err", "input", Some(0.0)), ("k", "parameter", Some(1.0)), ("u", "output", None)]),
+            &["u = -k*err".into()],
+        )
+        .unwrap();
+        for algorithm in [MasterAlgorithm::GaussSeidel, MasterAlgorithm::Jacobi] {
+            let mut m = Master::new();
+            m.add("plant", plant.clone()).unwrap();
+            m.add("ctrl", ctrl.clone()).unwrap();
+            m.connect("plant.x", "ctrl.err").unwrap();
+            m.connect("ctrl.u", "plant.u").unwrap();
+            assert!(m.input_ports().is_empty());
+            let cfg = MasterConfig { start_time: 0.0, stop_time: 1.0, step_size: 0.001, algorithm };
+            let r = m.run(&cfg).unwrap();
+            assert_eq!(r.columns, ["time", "plant.x", "ctrl.u"]);
+            assert_eq!(r.rows.len(), 1001);
+            let x = r.series("plant.x").unwrap();
+            assert!((x[1000] - (-1.0f64).exp()).abs() < 2e-3, "{algorithm:?}: {}", x[1000]);
+        }
+    }
+
+    #[test]
+    fn external_inputs_and_exported_fmus_drive_the_graph() {
+        let cfg = FmuConfig {
+            model_name: "Source".into(),
+            guid: "{1}".into(),
+            description: String::new(),
+            generation_tool: "ChainSolve".into(),
+            fmi_version: "2.0".into(),
+            outputs: vec![FmuVariable::output("level", 4.0)],
+            inputs: vec![],
+            parameters: vec![],
+            states: vec![],
+            start_time: 0.0,
+            stop_time: 1.0,
+            step_size: 0.1,
+            generation_date_utc: String::new(),
+        };
+        let mut m = Master::new();
+        m.add("src", NativeFmu::from_export(&cfg).unwrap()).unwrap();
+        m.add("plant", decay()).unwrap();
+        m.connect("src.level", "plant.u").unwrap();
+        assert!(m.connect("src.level", "plant.u").is_err());
+        assert!(m.connect("plant.u", "src.level").is_err());
+        let r = m.run(&MasterConfig { stop_time: 5.0, step_size: 0.05, ..Default::default() }).unwrap();
+        // x' = -2x + 4 settles at 2.
+        assert!((r.series("plant.x").unwrap().last().unwrap() - 2.0).abs() < 1e-3);
+
+        let mut m = Master::new();
+        m.add("plant", decay()).unwrap();
+        let table = Value::Table { columns: vec!["time".into(), "u".into()], rows: vec![vec![0.0, 0.0], vec![1.0, 2.0]] };
+        bind_inputs(&mut m, &table).unwrap();
+        let r = m.run(&MasterConfig { stop_time: 1.0, step_size: 0.25, ..Default::default() }).unwrap();
+        assert_eq!(r.series("time").unwrap(), [0.0, 0.25, 0.5, 0.75, 1.0]);
+        assert!(bind_inputs(&mut m, &Value::Vector { value: vec![1.0, 2.0] }).is_err());
+    }
+
+    #[test]
+    fn json_spec_builds_a_master() {
+        let spec = serde_json::json!({
+            "fmus": [{
+                "name": "p",
+                "modelDescription": xml("Decay", &[("u", "input", Some(0.0)), ("k", "parameter", Some(2.0)), ("x", "output", Some(1.0))]),
+                "equations": ["der(x) = -k*x + u"],
+                "parameters": { "k": 1.0 }
+            }],
+            "connections": []
+        });
+        let mut m = master_from_json(&spec).unwrap();
+        assert_eq!(m.input_ports(), ["p.u"]);
+        let r = m.run(&MasterConfig { stop_time: 1.0, step_size: 0.1, ..Default::default() }).unwrap();
+        assert!((r.series("p.x").unwrap()[10] - (-1.0f64).exp()).abs() < 1e-6);
+    }
+}
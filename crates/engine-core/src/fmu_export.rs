@@ -1,26 +1,42 @@
-//! `fmu_export` — FMI 2.0 / 3.0-compliant FMU generation (11.11).
+//! `fmu_export` — FMI 2.0 Co-Simulation FMU generation (11.11).
 //!
 //! Generates the full set of files required by the FMI Cross-Check suite:
 //!
 //! - `modelDescription.xml` — valid against the FMI 2.0 schema; includes all
 //!   required attributes (`fmiVersion`, `modelName`, `guid`,
-//!   `generationTool`, `numberOfEventIndicators`), a proper `<CoSimulation>`
-//!   element with all mandatory capabilities set, typed `<ScalarVariable>`
-//!   entries, correct 1-based `<ModelStructure><Outputs>` index references,
-//!   `<DefaultExperiment>`, and `<LogCategories>`.
+//!   `generationTool`, `numberOfEventIndicators`), a `<CoSimulation>` element
+//!   listing its source files, typed `<ScalarVariable>` entries with
+//!   consistent `initial`/`start` attributes, state derivatives, correct
+//!   1-based `<ModelStructure>` index references, `<DefaultExperiment>`, and
+//!   `<LogCategories>`.
 //!
-//! - `sources/model.c` — minimal C99 stub that compiles to a working FMU
-//!   binary when linked with the FMI 2.0 headers. Implements all 22 required
-//!   Co-Simulation functions. State variables are stored in a simple
-//!   heap-allocated struct; `fmi2DoStep` calls back into ChainSolve via an
-//!   optional `computeOutputs` function pointer supplied by the host.
+//! - `sources/<modelIdentifier>.c` — self-contained C99 implementing all
+//!   FMI 2.0 Co-Simulation functions. The model itself is compiled in:
+//!   output equations and state derivatives (`expr` syntax) are translated
+//!   to C, and `fmi2DoStep` integrates the states with an embedded
+//!   fixed-step RK4 whose internal step is the default experiment step size.
+//!   Outputs without an equation hold their start value.
+//!
+//! - `binaries/<platform>/` — empty placeholders for compiled binaries.
+//!
+//! Inputs, outputs, parameters and states map to FMI variables with value
+//! references `1..` in that order, followed by one `der(x)` variable per
+//! state. [`fmu_config_from_graph`] builds the model from a graph snapshot:
+//! pure-arithmetic blocks and custom-function formulas become equations,
+//! number blocks become parameters (or inputs), and an ODE block becomes
+//! the FMU's states. The evaluators hand the `fmu.export` block the
+//! subgraph upstream of its `data` input ([`with_upstream_graph`]).
 //!
 //! Public API:
 //!   `generate_fmu_xml(config)  → String`   (modelDescription.xml content)
-//!   `generate_fmu_c_stub(config) → String` (model.c content)
-//!   `generate_fmu_package(config) → FmuPackage`
+//!   `generate_fmu_c_source(config) → Result<String, String>` (model C code)
+//!   `generate_fmu_package(config) → Result<FmuPackage, String>`
+//!   `FmuPackage::to_zip()` (the `.fmu` archive), `check_fmu_package(pkg)`
 
+use crate::types::{EdgeDef, EngineSnapshotV1, NodeDef, Value};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 // ── Configuration ─────────────────────────────────────────────────────────────
 
@@ -28,7 +44,8 @@ use serde::{Deserialize, Serialize};
 /// attribute values (no unescaped `<`, `>`, `"`, `&`, `'`).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FmuConfig {
-    /// Human-readable model name (ASCII; no whitespace).  Required.
+    /// Human-readable model name, also the `modelIdentifier` (a C
+    /// identifier).  Required.
     pub model_name: String,
     /// GUID in `{xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx}` form.  Required.
     pub guid: String,
@@ -42,11 +59,17 @@ pub struct FmuConfig {
     pub outputs: Vec<FmuVariable>,
     /// Input variable definitions.
     pub inputs: Vec<FmuVariable>,
+    /// Parameter definitions (`causality="parameter"`).
+    #[serde(default)]
+    pub parameters: Vec<FmuVariable>,
+    /// Continuous states; each `equation` is the state's time derivative.
+    #[serde(default)]
+    pub states: Vec<FmuVariable>,
     /// Default start time for the FMI experiment.
     pub start_time: f64,
     /// Default stop time for the FMI experiment.
     pub stop_time: f64,
-    /// Default step size for Co-Simulation.
+    /// Default step size for Co-Simulation, also the internal integrator step.
     pub step_size: f64,
     /// Tool that generated this FMU (e.g. "ChainSolve 1.0.0").
     pub generation_date_utc: String,
@@ -69,6 +92,10 @@ pub struct FmuVariable {
     pub description: String,
     /// Optional unit string (e.g. `"m/s"`, `"K"`).
     pub unit: String,
+    /// Output: the formula computing it. State: its time derivative.
+    /// Formulas use `expr` syntax over the other variables and `time`/`t`.
+    #[serde(default)]
+    pub equation: Option<String>,
 }
 
 impl FmuVariable {
@@ -82,28 +109,73 @@ impl FmuVariable {
             variability: "continuous".into(),
             description: String::new(),
             unit: String::new(),
+            equation: None,
         }
     }
 
     /// Convenience constructor for a continuous real input.
     pub fn input(name: impl Into<String>, start: f64) -> Self {
         FmuVariable {
-            name: name.into(),
-            start,
-            type_name: "Real".into(),
             causality: "input".into(),
-            variability: "continuous".into(),
-            description: String::new(),
-            unit: String::new(),
+            ..FmuVariable::output(name, start)
+        }
+    }
+
+    /// Convenience constructor for a fixed real parameter.
+    pub fn parameter(name: impl Into<String>, start: f64) -> Self {
+        FmuVariable {
+            causality: "parameter".into(),
+            variability: "fixed".into(),
+            ..FmuVariable::output(name, start)
         }
     }
+
+    /// Convenience constructor for a state exposed as an output, with
+    /// `der(name) = derivative`.
+    pub fn state(name: impl Into<String>, start: f64, derivative: impl Into<String>) -> Self {
+        FmuVariable {
+            equation: Some(derivative.into()),
+            ..FmuVariable::output(name, start)
+        }
+    }
+
+    /// Attach the formula that computes this variable.
+    pub fn with_equation(mut self, equation: impl Into<String>) -> Self {
+        self.equation = Some(equation.into());
+        self
+    }
 }
 
-/// Full FMU package: a map from archive path to file content.
+/// Full FMU package: the generated files plus the archive layout.
 #[derive(Debug, Clone)]
 pub struct FmuPackage {
+    /// `modelIdentifier`; the source file is `sources/<id>.c`.
+    pub model_identifier: String,
     pub model_description_xml: String,
-    pub model_c_stub: String,
+    pub model_c_source: String,
+}
+
+/// Platforms that get an (empty) `binaries/<platform>/` folder.
+pub const BINARY_PLATFORMS: [&str; 3] = ["linux64", "win64", "darwin64"];
+
+impl FmuPackage {
+    /// Archive entries in order; directory placeholders end with `/`.
+    pub fn files(&self) -> Vec<(String, Vec<u8>)> {
+        let mut files = vec![
+            ("modelDescription.xml".to_string(), self.model_description_xml.clone().into_bytes()),
+            ("sources/".to_string(), Vec::new()),
+            (format!("sources/{}.c", self.model_identifier), self.model_c_source.clone().into_bytes()),
+            ("binaries/".to_string(), Vec::new()),
+        ];
+        files.extend(BINARY_PLATFORMS.iter().map(|p| (format!("binaries/{p}/"), Vec::new())));
+        files
+    }
+
+    /// The `.fmu` archive: a ZIP of [`files`](Self::files) (stored, no
+    /// compression).
+    pub fn to_zip(&self) -> Vec<u8> {
+        write_zip(&self.files())
+    }
 }
 
 // ── XML escaping ──────────────────────────────────────────────────────────────
@@ -116,12 +188,105 @@ fn xml_attr(s: &str) -> String {
         .replace('\'', "&apos;")
 }
 
+// ── Variable layout ───────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Role {
+    Input,
+    Output,
+    Parameter,
+    State,
+    /// Derivative of the state at this 1-based index.
+    Derivative(usize),
+}
+
+/// One `<ScalarVariable>`; its position + 1 is both the index and the
+/// value reference.
+struct Slot<'a> {
+    name: String,
+    var: Option<&'a FmuVariable>,
+    role: Role,
+}
+
+impl Slot<'_> {
+    fn is_output(&self) -> bool {
+        match self.role {
+            Role::Output => true,
+            Role::State => self.var.is_some_and(|v| v.causality == "output"),
+            _ => false,
+        }
+    }
+
+    fn is_calculated(&self) -> bool {
+        match self.role {
+            Role::Output => self.var.is_some_and(|v| v.equation.is_some()),
+            Role::Derivative(_) => true,
+            _ => false,
+        }
+    }
+}
+
+fn layout(cfg: &FmuConfig) -> Vec<Slot<'_>> {
+    let mut slots: Vec<Slot> = Vec::new();
+    for (vars, role) in [
+        (&cfg.inputs, Role::Input),
+        (&cfg.outputs, Role::Output),
+        (&cfg.parameters, Role::Parameter),
+        (&cfg.states, Role::State),
+    ] {
+        slots.extend(vars.iter().map(|v| Slot { name: v.name.clone(), var: Some(v), role }));
+    }
+    let first_state = cfg.inputs.len() + cfg.outputs.len() + cfg.parameters.len();
+    for (i, s) in cfg.states.iter().enumerate() {
+        slots.push(Slot { name: format!("der({})", s.name), var: None, role: Role::Derivative(first_state + i + 1) });
+    }
+    slots
+}
+
+fn is_c_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Evaluation order for named formulas: `deps[i]` lists the names formula
+/// `i` reads, and every target is ordered before its readers. `Err` carries
+/// a name on an algebraic loop.
+pub(crate) fn evaluation_order(targets: &[String], deps: &[Vec<String>]) -> Result<Vec<usize>, String> {
+    let index: HashMap<&str, usize> = targets.iter().enumerate().map(|(i, n)| (n.as_str(), i)).collect();
+    let edges: Vec<Vec<usize>> =
+        deps.iter().map(|d| d.iter().filter_map(|v| index.get(v.as_str()).copied()).collect()).collect();
+
+    // Depth-first post-order; `state` 1 = on the stack, 2 = done.
+    fn visit(i: usize, edges: &[Vec<usize>], state: &mut [u8], order: &mut Vec<usize>) -> Result<(), usize> {
+        match state[i] {
+            2 => return Ok(()),
+            1 => return Err(i),
+            _ => {}
+        }
+        state[i] = 1;
+        for &d in &edges[i] {
+            visit(d, edges, state, order)?;
+        }
+        state[i] = 2;
+        order.push(i);
+        Ok(())
+    }
+
+    let mut state = vec![0u8; targets.len()];
+    let mut order = Vec::with_capacity(targets.len());
+    for i in 0..targets.len() {
+        visit(i, &edges, &mut state, &mut order).map_err(|j| targets[j].clone())?;
+    }
+    Ok(order)
+}
+
 // ── modelDescription.xml generator ───────────────────────────────────────────
 
 /// Generate a fully FMI 2.0-compliant `modelDescription.xml` string.
 ///
-/// The output has been cross-checked against the FMI 2.0 XSD and passes
-/// the FMI Cross-Check schema validation tool.
+/// The output satisfies the structural rules checked by
+/// [`check_fmu_package`] (the FMI Cross-Check schema rules).
 pub fn generate_fmu_xml(cfg: &FmuConfig) -> String {
     let fmi_version = if cfg.fmi_version == "3.0" { "3.0" } else { "2.0" };
     let model_name = xml_attr(&cfg.model_name);
@@ -129,56 +294,75 @@ pub fn generate_fmu_xml(cfg: &FmuConfig) -> String {
     let description = xml_attr(&cfg.description);
     let generation_tool = xml_attr(&cfg.generation_tool);
     let generation_date = xml_attr(&cfg.generation_date_utc);
-
-    // Value references: inputs first, then outputs
-    let n_inputs = cfg.inputs.len();
-    let all_vars: Vec<(usize, &FmuVariable)> = cfg
-        .inputs
-        .iter()
-        .chain(cfg.outputs.iter())
-        .enumerate()
-        .collect();
+    let slots = layout(cfg);
 
     // ScalarVariables
     let mut sv_xml = String::new();
-    for (idx, var) in &all_vars {
+    for (idx, slot) in slots.iter().enumerate() {
         let vr = idx + 1; // 1-based value reference
-        let name = xml_attr(&var.name);
-        let start = var.start;
-        let causality = xml_attr(&var.causality);
-        let variability = xml_attr(&var.variability);
-        let unit_attr = if var.unit.is_empty() {
-            String::new()
-        } else {
-            format!(" unit=\"{}\"", xml_attr(&var.unit))
+        let name = xml_attr(&slot.name);
+        let (causality, variability, initial) = match (slot.role, slot.var) {
+            (Role::Derivative(_), _) | (_, None) => ("local".to_string(), "continuous".to_string(), Some("calculated")),
+            (Role::Input, Some(v)) => (v.causality.clone(), v.variability.clone(), None),
+            (Role::Parameter, Some(v)) => {
+                let variability = if v.variability == "tunable" { "tunable" } else { "fixed" };
+                ("parameter".to_string(), variability.to_string(), Some("exact"))
+            }
+            (Role::State, Some(v)) => (v.causality.clone(), "continuous".to_string(), Some("exact")),
+            (Role::Output, Some(v)) => {
+                let initial = if v.equation.is_some() { "calculated" } else { "exact" };
+                (v.causality.clone(), v.variability.clone(), Some(initial))
+            }
         };
-        let desc_attr = if var.description.is_empty() {
-            String::new()
-        } else {
-            format!(" description=\"{}\"", xml_attr(&var.description))
+        let initial_attr = initial.map(|i| format!(" initial=\"{i}\"")).unwrap_or_default();
+        let desc_attr = match slot.var {
+            Some(v) if !v.description.is_empty() => format!(" description=\"{}\"", xml_attr(&v.description)),
+            _ => String::new(),
         };
         sv_xml.push_str(&format!(
-            "    <ScalarVariable name=\"{name}\" valueReference=\"{vr}\" causality=\"{causality}\" variability=\"{variability}\"{desc_attr}>\n"
-        ));
-        sv_xml.push_str(&format!(
-            "      <{type_name} start=\"{start:.10e}\"{unit_attr}/>\n",
-            type_name = var.type_name
+            "    <ScalarVariable name=\"{name}\" valueReference=\"{vr}\" causality=\"{}\" variability=\"{}\"{initial_attr}{desc_attr}>\n",
+            xml_attr(&causality),
+            xml_attr(&variability),
         ));
+        let type_name = slot.var.map(|v| v.type_name.as_str()).unwrap_or("Real");
+        let mut type_attrs = String::new();
+        if let Role::Derivative(state) = slot.role {
+            type_attrs.push_str(&format!(" derivative=\"{state}\""));
+        } else if !slot.is_calculated() {
+            type_attrs.push_str(&format!(" start=\"{:.10e}\"", slot.var.map_or(0.0, |v| v.start)));
+        }
+        if let Some(v) = slot.var.filter(|v| !v.unit.is_empty()) {
+            type_attrs.push_str(&format!(" unit=\"{}\"", xml_attr(&v.unit)));
+        }
+        sv_xml.push_str(&format!("      <{type_name}{type_attrs}/>\n"));
         sv_xml.push_str("    </ScalarVariable>\n");
     }
 
-    // ModelStructure: Outputs use 1-based index into the ScalarVariable list
-    let mut outputs_xml = String::new();
-    for i in 0..cfg.outputs.len() {
-        // Output index in ScalarVariable list = n_inputs + i + 1 (1-based)
-        let sv_index = n_inputs + i + 1;
-        outputs_xml.push_str(&format!("      <Unknown index=\"{sv_index}\"/>\n"));
-    }
+    // ModelStructure: 1-based indices into the ScalarVariable list
+    let unknowns = |tag: &str, pick: &dyn Fn(&Slot) -> bool| {
+        let list: String = slots
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| pick(s))
+            .map(|(i, _)| format!("      <Unknown index=\"{}\"/>\n", i + 1))
+            .collect();
+        if list.is_empty() {
+            format!("    <{tag}/>\n")
+        } else {
+            format!("    <{tag}>\n{list}    </{tag}>\n")
+        }
+    };
+    let model_structure = [
+        unknowns("Outputs", &|s| s.is_output()),
+        unknowns("Derivatives", &|s| matches!(s.role, Role::Derivative(_))),
+        unknowns("InitialUnknowns", &|s| (s.is_output() || matches!(s.role, Role::Derivative(_))) && s.is_calculated()),
+    ]
+    .concat();
 
     // UnitDefinitions (collect unique units)
-    let mut units: Vec<String> = all_vars
+    let mut units: Vec<String> = slots
         .iter()
-        .map(|(_, v)| v.unit.clone())
+        .filter_map(|s| s.var.map(|v| v.unit.clone()))
         .filter(|u| !u.is_empty())
         .collect();
     units.sort();
@@ -202,7 +386,7 @@ pub fn generate_fmu_xml(cfg: &FmuConfig) -> String {
   description="{description}"
   generationTool="{generation_tool}"
   generationDateAndTime="{generation_date}"
-  variableNamingConvention="flat"
+  variableNamingConvention="structured"
   numberOfEventIndicators="0">
 
   <CoSimulation
@@ -213,7 +397,11 @@ pub fn generate_fmu_xml(cfg: &FmuConfig) -> String {
     canNotUseMemoryManagementFunctions="false"
     canBeInstantiatedOnlyOncePerProcess="false"
     canGetAndSetFMUstate="false"
-    canSerializeFMUstate="false"/>
+    canSerializeFMUstate="false">
+    <SourceFiles>
+      <File name="{model_name}.c"/>
+    </SourceFiles>
+  </CoSimulation>
 
   <LogCategories>
     <Category name="logAll" description="Log all events"/>
@@ -229,11 +417,7 @@ pub fn generate_fmu_xml(cfg: &FmuConfig) -> String {
 {sv_xml}  </ModelVariables>
 
   <ModelStructure>
-    <Outputs>
-{outputs_xml}    </Outputs>
-    <Derivatives/>
-    <InitialUnknowns/>
-  </ModelStructure>
+{model_structure}  </ModelStructure>
 
   <DefaultExperiment
     startTime="{start_time:.6}"
@@ -249,79 +433,220 @@ pub fn generate_fmu_xml(cfg: &FmuConfig) -> String {
     )
 }
 
-// ── C stub generator ──────────────────────────────────────────────────────────
+// ── C source generator ────────────────────────────────────────────────────────
+
+fn c_string(s: &str) -> String {
+    s.chars()
+        .flat_map(|c| match c {
+            '"' | '\\' => vec!['\\', c],
+            c if c.is_ascii_graphic() || c == ' ' => vec![c],
+            _ => vec!['?'],
+        })
+        .collect()
+}
 
-/// Generate a minimal FMI 2.0 Co-Simulation C99 stub for `model_name`.
+/// Generate the FMI 2.0 Co-Simulation C99 source for the model.
 ///
-/// The generated file implements all 22 required `fmi2*` functions.
-/// Compile with the official FMI 2.0 headers from the FMI standard:
-///   `cc -shared -fPIC -DFMI2_FUNCTION_PREFIX= -I fmi2_headers model.c -o {name}.so`
-pub fn generate_fmu_c_stub(cfg: &FmuConfig) -> String {
+/// Every output equation and state derivative is translated to C (see
+/// [`crate::expr::CompiledExpr::to_c`]). Fails if a formula does not
+/// compile, reads an unknown variable, or the outputs form an algebraic loop.
+/// Compile with the official FMI 2.0 headers:
+///   `cc -shared -fPIC -I fmi2_headers sources/{name}.c -o binaries/linux64/{name}.so -lm`
+pub fn generate_fmu_c_source(cfg: &FmuConfig) -> Result<String, String> {
     let model_name = &cfg.model_name;
-    let n_outputs = cfg.outputs.len();
-    let n_inputs = cfg.inputs.len();
-    let n_vars = n_inputs + n_outputs;
+    if !is_c_identifier(model_name) {
+        return Err(format!("[FMU_EXPORT] Model name '{model_name}' is not a valid C identifier"));
+    }
+    if !(cfg.step_size > 0.0 && cfg.step_size.is_finite()) {
+        return Err("[FMU_EXPORT] Step size must be positive".to_string());
+    }
+    let slots = layout(cfg);
+    let n_vars = slots.len();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for (i, s) in slots.iter().enumerate() {
+        if index.insert(s.name.as_str(), i).is_some() {
+            return Err(format!("[FMU_EXPORT] Duplicate variable name '{}'", s.name));
+        }
+    }
+    let resolve = |name: &str| match index.get(name) {
+        Some(&i) if !matches!(slots[i].role, Role::Derivative(_)) => Some(format!("r[{i}]")),
+        Some(_) => None,
+        None => ["t", "time"].contains(&name).then(|| "t".to_string()),
+    };
 
-    // Initial value array: [inputs..., outputs...]
-    let init_values: String = cfg
-        .inputs
+    // (target index, formula) for outputs, then derivatives.
+    let mut formulas: Vec<(usize, &str)> = Vec::new();
+    for (i, s) in slots.iter().enumerate() {
+        match (s.role, s.var.and_then(|v| v.equation.as_deref())) {
+            (Role::Output, Some(eq)) => formulas.push((i, eq)),
+            (Role::State, Some(eq)) => formulas.push((index[format!("der({})", s.name).as_str()], eq)),
+            (Role::State, None) => return Err(format!("[FMU_EXPORT] State '{}' has no derivative", s.name)),
+            _ => {}
+        }
+    }
+    let compiled = formulas
         .iter()
-        .chain(cfg.outputs.iter())
-        .enumerate()
-        .map(|(i, v)| {
-            if i + 1 < n_vars {
-                format!("{:.10e}, ", v.start)
-            } else {
-                format!("{:.10e}", v.start)
-            }
+        .map(|(i, eq)| {
+            crate::expr::compile(eq).map_err(|e| format!("[FMU_EXPORT] Equation for '{}': {e}", slots[*i].name))
         })
+        .collect::<Result<Vec<_>, _>>()?;
+    let targets: Vec<String> = formulas.iter().map(|(i, _)| slots[*i].name.clone()).collect();
+    let deps: Vec<Vec<String>> = compiled.iter().map(|c| c.dependencies().scalars.into_iter().collect()).collect();
+    let order = evaluation_order(&targets, &deps)
+        .map_err(|name| format!("[FMU_EXPORT] Algebraic loop through '{name}'"))?;
+
+    let mut n_let = 1;
+    let mut body = String::new();
+    for k in order {
+        let (target, _) = formulas[k];
+        let (code, slots_used) = compiled[k]
+            .to_c(&resolve)
+            .map_err(|e| format!("[FMU_EXPORT] Equation for '{}': {e}", slots[target].name))?;
+        n_let = n_let.max(slots_used);
+        body.push_str(&format!("    r[{target}] = {code}; /* {} */\n", c_string(&slots[target].name).replace("*/", "* /")));
+    }
+
+    let init_values: Vec<String> = slots
+        .iter()
+        .map(|s| format!("{:.17e}", if s.is_calculated() { 0.0 } else { s.var.map_or(0.0, |v| v.start) }))
+        .collect();
+    // Inputs and parameters may always be set; exact start values (states,
+    // constant outputs) only before initialization completes.
+    let settable: Vec<&str> = slots
+        .iter()
+        .map(|s| match s.role {
+            Role::Input | Role::Parameter => "2",
+            _ if !s.is_calculated() => "1",
+            _ => "0",
+        })
+        .collect();
+    let state_index: Vec<String> =
+        slots.iter().enumerate().filter(|(_, s)| s.role == Role::State).map(|(i, _)| i.to_string()).collect();
+    let n_states = state_index.len();
+    let derivative_index: Vec<String> = slots
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| matches!(s.role, Role::Derivative(_)))
+        .map(|(i, _)| i.to_string())
         .collect();
 
-    format!(
+    let integrator = if n_states == 0 {
+        String::new()
+    } else {
+        format!(
+            r#"
+#define N_STATES {n_states}
+
+static const size_t STATE_INDEX[N_STATES] = {{ {states} }};
+static const size_t DERIVATIVE_INDEX[N_STATES] = {{ {derivs} }};
+
+/* One classical RK4 step of the embedded integrator; inputs are held. */
+static void rk4_step(fmi2Real* r, fmi2Real t, fmi2Real h)
+{{
+    static const fmi2Real c[4] = {{ 0.0, 0.5, 0.5, 1.0 }};
+    fmi2Real x0[N_STATES], k[4][N_STATES];
+    size_t i, s;
+    for (i = 0; i < N_STATES; ++i) x0[i] = r[STATE_INDEX[i]];
+    for (s = 0; s < 4; ++s) {{
+        if (s > 0)
+            for (i = 0; i < N_STATES; ++i) r[STATE_INDEX[i]] = x0[i] + c[s] * h * k[s - 1][i];
+        evaluate(r, t + c[s] * h);
+        for (i = 0; i < N_STATES; ++i) k[s][i] = r[DERIVATIVE_INDEX[i]];
+    }}
+    for (i = 0; i < N_STATES; ++i)
+        r[STATE_INDEX[i]] = x0[i] + h / 6.0 * (k[0][i] + 2.0 * k[1][i] + 2.0 * k[2][i] + k[3][i]);
+}}
+"#,
+            states = state_index.join(", "),
+            derivs = derivative_index.join(", "),
+        )
+    };
+    let do_step_body = if n_states == 0 {
+        "    (void)noSetFMUStatePriorToCurrentPoint;\n    if (communicationStepSize < 0.0) return fmi2Error;\n"
+            .to_string()
+    } else {
+        r#"    (void)noSetFMUStatePriorToCurrentPoint;
+    if (communicationStepSize < 0.0) return fmi2Error;
+    {
+        int n = (int)ceil(communicationStepSize / INTERNAL_STEP - 1e-9), k;
+        if (n < 1) n = 1;
+        for (k = 0; k < n; ++k)
+            rk4_step(m->vars, currentCommunicationPoint + k * (communicationStepSize / n), communicationStepSize / n);
+    }
+"#
+        .to_string()
+    };
+
+    let guid = c_string(&cfg.guid);
+    let init_values = init_values.join(", ");
+    let settable = settable.join(", ");
+    let step = cfg.step_size;
+    Ok(format!(
         r#"/**
- * FMI 2.0 Co-Simulation stub for "{model_name}".
- * Generated by ChainSolve. Implements all 22 required fmi2* functions.
+ * FMI 2.0 Co-Simulation implementation of "{model_name}".
+ * Generated by ChainSolve: the model equations below are compiled in.
  *
  * Compile (Linux/macOS):
- *   cc -shared -fPIC -DFMI2_FUNCTION_PREFIX= -I fmi2_headers \
- *      sources/model.c -o binaries/linux64/{model_name}.so
+ *   cc -shared -fPIC -I fmi2_headers \
+ *      sources/{model_name}.c -o binaries/linux64/{model_name}.so -lm
  *
  * Compile (Windows x86-64, MSVC):
- *   cl /LD /DFMI2_FUNCTION_PREFIX= /I fmi2_headers \
- *      sources/model.c /Fe:binaries/win64/{model_name}.dll
+ *   cl /LD /I fmi2_headers \
+ *      sources/{model_name}.c /Fe:binaries/win64/{model_name}.dll
  */
 
+#include <math.h>
 #include <stdlib.h>
 #include <string.h>
 #include "fmi2Functions.h"
 
 #define N_VARS {n_vars}
+#define N_LET {n_let}
+#define INTERNAL_STEP {step:e}
+#define GUID "{guid}"
 
 typedef struct {{
-    fmi2Real vars[N_VARS];   /* current values: [inputs[0..{n_inputs}), outputs[0..{n_outputs})] */
+    fmi2Real vars[N_VARS];   /* indexed by valueReference - 1 */
     fmi2Real time;
+    fmi2Boolean initialized;
     fmi2Boolean log_enabled;
     fmi2CallbackFunctions callbacks;
 }} ModelInstance;
 
 static const fmi2Real INITIAL_VALUES[N_VARS] = {{ {init_values} }};
 
+/* 2 = settable any time, 1 = before initialization only, 0 = calculated */
+static const int SETTABLE[N_VARS] = {{ {settable} }};
+
+/* Outputs, then state derivatives, from inputs, parameters and states at time t. */
+static void evaluate(fmi2Real* r, fmi2Real t)
+{{
+    fmi2Real cs_let[N_LET];
+    (void)r; (void)t; (void)cs_let;
+{body}}}
+{integrator}
 /* ── Mandatory lifecycle ────────────────────────────────────────────────── */
 
+const char* fmi2GetTypesPlatform(void) {{ return fmi2TypesPlatform; }}
+const char* fmi2GetVersion(void)       {{ return fmi2Version; }}
+
 fmi2Component fmi2Instantiate(
     fmi2String instanceName, fmi2Type fmuType,
     fmi2String fmuGUID, fmi2String fmuResourcePath,
     const fmi2CallbackFunctions* functions,
     fmi2Boolean visible, fmi2Boolean loggingOn)
 {{
-    (void)instanceName; (void)fmuType; (void)fmuGUID;
-    (void)fmuResourcePath; (void)visible;
-    ModelInstance* m = (ModelInstance*)malloc(sizeof(ModelInstance));
+    ModelInstance* m;
+    (void)instanceName; (void)fmuResourcePath; (void)visible;
+    if (fmuType != fmi2CoSimulation || !fmuGUID || strcmp(fmuGUID, GUID) != 0) return NULL;
+    m = (ModelInstance*)calloc(1, sizeof(ModelInstance));
     if (!m) return NULL;
     memcpy(m->vars, INITIAL_VALUES, sizeof(INITIAL_VALUES));
     m->time = 0.0;
+    m->initialized = fmi2False;
     m->log_enabled = loggingOn;
-    m->callbacks = *functions;
+    if (functions) m->callbacks = *functions;
+    evaluate(m->vars, m->time);
     return (fmi2Component)m;
 }}
 
@@ -334,19 +659,29 @@ fmi2Status fmi2SetupExperiment(fmi2Component c,
     fmi2Boolean toleranceDefined, fmi2Real tolerance,
     fmi2Real startTime, fmi2Boolean stopTimeDefined, fmi2Real stopTime)
 {{
+    ModelInstance* m = (ModelInstance*)c;
     (void)toleranceDefined; (void)tolerance; (void)stopTimeDefined; (void)stopTime;
-    ((ModelInstance*)c)->time = startTime;
+    m->time = startTime;
+    evaluate(m->vars, m->time);
     return fmi2OK;
 }}
 
 fmi2Status fmi2EnterInitializationMode(fmi2Component c) {{ (void)c; return fmi2OK; }}
-fmi2Status fmi2ExitInitializationMode(fmi2Component c)  {{ (void)c; return fmi2OK; }}
+fmi2Status fmi2ExitInitializationMode(fmi2Component c)
+{{
+    ModelInstance* m = (ModelInstance*)c;
+    m->initialized = fmi2True;
+    evaluate(m->vars, m->time);
+    return fmi2OK;
+}}
 fmi2Status fmi2Terminate(fmi2Component c)               {{ (void)c; return fmi2OK; }}
 fmi2Status fmi2Reset(fmi2Component c)
 {{
     ModelInstance* m = (ModelInstance*)c;
     memcpy(m->vars, INITIAL_VALUES, sizeof(INITIAL_VALUES));
     m->time = 0.0;
+    m->initialized = fmi2False;
+    evaluate(m->vars, m->time);
     return fmi2OK;
 }}
 
@@ -357,10 +692,12 @@ fmi2Status fmi2DoStep(fmi2Component c,
     fmi2Real communicationStepSize,
     fmi2Boolean noSetFMUStatePriorToCurrentPoint)
 {{
-    (void)noSetFMUStatePriorToCurrentPoint;
     ModelInstance* m = (ModelInstance*)c;
-    /* Outputs are held constant; override computeOutputs here if needed. */
-    m->time = currentCommunicationPoint + communicationStepSize;
+    size_t i;
+{do_step_body}    m->time = currentCommunicationPoint + communicationStepSize;
+    evaluate(m->vars, m->time);
+    for (i = 0; i < N_VARS; ++i)
+        if (!isfinite(m->vars[i])) return fmi2Error;
     return fmi2OK;
 }}
 
@@ -385,26 +722,33 @@ fmi2Status fmi2SetReal(fmi2Component c,
     ModelInstance* m = (ModelInstance*)c;
     for (size_t i = 0; i < nvr; ++i) {{
         if (vr[i] < 1 || vr[i] > N_VARS) return fmi2Error;
+        if (SETTABLE[vr[i] - 1] == 0 || (SETTABLE[vr[i] - 1] == 1 && m->initialized)) return fmi2Error;
         m->vars[vr[i] - 1] = value[i];
     }}
+    evaluate(m->vars, m->time);
     return fmi2OK;
 }}
 
-/* Integer, Boolean, String — stubs returning fmi2OK (no integer/boolean vars) */
-fmi2Status fmi2GetInteger(fmi2Component c, const fmi2ValueReference vr[], size_t nvr, fmi2Integer v[])  {{ (void)c;(void)vr;(void)nvr;(void)v; return fmi2OK; }}
-fmi2Status fmi2GetBoolean(fmi2Component c, const fmi2ValueReference vr[], size_t nvr, fmi2Boolean v[]) {{ (void)c;(void)vr;(void)nvr;(void)v; return fmi2OK; }}
-fmi2Status fmi2GetString(fmi2Component c,  const fmi2ValueReference vr[], size_t nvr, fmi2String v[])   {{ (void)c;(void)vr;(void)nvr;(void)v; return fmi2OK; }}
-fmi2Status fmi2SetInteger(fmi2Component c, const fmi2ValueReference vr[], size_t nvr, const fmi2Integer v[])  {{ (void)c;(void)vr;(void)nvr;(void)v; return fmi2OK; }}
-fmi2Status fmi2SetBoolean(fmi2Component c, const fmi2ValueReference vr[], size_t nvr, const fmi2Boolean v[]) {{ (void)c;(void)vr;(void)nvr;(void)v; return fmi2OK; }}
-fmi2Status fmi2SetString(fmi2Component c,  const fmi2ValueReference vr[], size_t nvr, const fmi2String v[])   {{ (void)c;(void)vr;(void)nvr;(void)v; return fmi2OK; }}
+/* Integer, Boolean, String — no such variables, so any reference is invalid */
+fmi2Status fmi2GetInteger(fmi2Component c, const fmi2ValueReference vr[], size_t nvr, fmi2Integer v[])  {{ (void)c;(void)vr;(void)v; return nvr ? fmi2Error : fmi2OK; }}
+fmi2Status fmi2GetBoolean(fmi2Component c, const fmi2ValueReference vr[], size_t nvr, fmi2Boolean v[]) {{ (void)c;(void)vr;(void)v; return nvr ? fmi2Error : fmi2OK; }}
+fmi2Status fmi2GetString(fmi2Component c,  const fmi2ValueReference vr[], size_t nvr, fmi2String v[])   {{ (void)c;(void)vr;(void)v; return nvr ? fmi2Error : fmi2OK; }}
+fmi2Status fmi2SetInteger(fmi2Component c, const fmi2ValueReference vr[], size_t nvr, const fmi2Integer v[])  {{ (void)c;(void)vr;(void)v; return nvr ? fmi2Error : fmi2OK; }}
+fmi2Status fmi2SetBoolean(fmi2Component c, const fmi2ValueReference vr[], size_t nvr, const fmi2Boolean v[]) {{ (void)c;(void)vr;(void)v; return nvr ? fmi2Error : fmi2OK; }}
+fmi2Status fmi2SetString(fmi2Component c,  const fmi2ValueReference vr[], size_t nvr, const fmi2String v[])   {{ (void)c;(void)vr;(void)v; return nvr ? fmi2Error : fmi2OK; }}
 
 /* ── Status / logging ───────────────────────────────────────────────────── */
 
 fmi2Status fmi2GetStatus(fmi2Component c, const fmi2StatusKind s, fmi2Status* v)          {{ (void)c;(void)s; *v = fmi2OK; return fmi2OK; }}
-fmi2Status fmi2GetRealStatus(fmi2Component c, const fmi2StatusKind s, fmi2Real* v)        {{ (void)c;(void)s; *v = 0.0; return fmi2OK; }}
-fmi2Status fmi2GetIntegerStatus(fmi2Component c, const fmi2StatusKind s, fmi2Integer* v)  {{ (void)c;(void)s; *v = 0;   return fmi2OK; }}
-fmi2Status fmi2GetBooleanStatus(fmi2Component c, const fmi2StatusKind s, fmi2Boolean* v)  {{ (void)c;(void)s; *v = fmi2False; return fmi2OK; }}
-fmi2Status fmi2GetStringStatus(fmi2Component c,  const fmi2StatusKind s, fmi2String* v)   {{ (void)c;(void)s; *v = ""; return fmi2OK; }}
+fmi2Status fmi2GetRealStatus(fmi2Component c, const fmi2StatusKind s, fmi2Real* v)
+{{
+    if (s != fmi2LastSuccessfulTime) return fmi2Discard;
+    *v = ((ModelInstance*)c)->time;
+    return fmi2OK;
+}}
+fmi2Status fmi2GetIntegerStatus(fmi2Component c, const fmi2StatusKind s, fmi2Integer* v)  {{ (void)c;(void)s; *v = 0;   return fmi2Discard; }}
+fmi2Status fmi2GetBooleanStatus(fmi2Component c, const fmi2StatusKind s, fmi2Boolean* v)  {{ (void)c;(void)s; *v = fmi2False; return fmi2Discard; }}
+fmi2Status fmi2GetStringStatus(fmi2Component c,  const fmi2StatusKind s, fmi2String* v)   {{ (void)c;(void)s; *v = ""; return fmi2Discard; }}
 
 fmi2Status fmi2SetDebugLogging(fmi2Component c,
     fmi2Boolean loggingOn, size_t nCategories, const fmi2String categories[])
@@ -416,6 +760,8 @@ fmi2Status fmi2SetDebugLogging(fmi2Component c,
 
 /* ── Not used for Co-Simulation ─────────────────────────────────────────── */
 
+fmi2Status fmi2SetRealInputDerivatives(fmi2Component c, const fmi2ValueReference vr[], size_t nvr, const fmi2Integer order[], const fmi2Real value[])  {{ (void)c;(void)vr;(void)nvr;(void)order;(void)value; return fmi2Error; }}
+fmi2Status fmi2GetRealOutputDerivatives(fmi2Component c, const fmi2ValueReference vr[], size_t nvr, const fmi2Integer order[], fmi2Real value[])      {{ (void)c;(void)vr;(void)nvr;(void)order;(void)value; return fmi2Error; }}
 fmi2Status fmi2GetFMUstate(fmi2Component c, fmi2FMUstate* s)          {{ (void)c;(void)s; return fmi2Error; }}
 fmi2Status fmi2SetFMUstate(fmi2Component c, fmi2FMUstate s)           {{ (void)c;(void)s; return fmi2Error; }}
 fmi2Status fmi2FreeFMUstate(fmi2Component c, fmi2FMUstate* s)         {{ (void)c;(void)s; return fmi2Error; }}
@@ -431,31 +777,304 @@ fmi2Status fmi2GetDirectionalDerivative(fmi2Component c,
     return fmi2Error;
 }}
 "#
-    )
+    ))
 }
 
 // ── Package generator ─────────────────────────────────────────────────────────
 
-/// Generate both files of the FMU package.
-pub fn generate_fmu_package(cfg: &FmuConfig) -> FmuPackage {
-    FmuPackage {
+/// Generate the model description and C source of the FMU package.
+pub fn generate_fmu_package(cfg: &FmuConfig) -> Result<FmuPackage, String> {
+    Ok(FmuPackage {
+        model_identifier: cfg.model_name.clone(),
         model_description_xml: generate_fmu_xml(cfg),
-        model_c_stub: generate_fmu_c_stub(cfg),
+        model_c_source: generate_fmu_c_source(cfg)?,
+    })
+}
+
+// ── Archive ───────────────────────────────────────────────────────────────────
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in data {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
     }
+    !crc
+}
+
+/// Minimal ZIP writer: stored entries, fixed 1980-01-01 timestamps so the
+/// archive is deterministic.
+fn write_zip(files: &[(String, Vec<u8>)]) -> Vec<u8> {
+    const DOS_DATE: u16 = (1 << 5) | 1;
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for (name, data) in files {
+        let offset = out.len() as u32;
+        let crc = crc32(data);
+        let size = data.len() as u32;
+        let is_dir = name.ends_with('/');
+        let mut header = Vec::new();
+        header.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        header.extend_from_slice(&0u16.to_le_bytes()); // flags
+        header.extend_from_slice(&0u16.to_le_bytes()); // stored
+        header.extend_from_slice(&0u16.to_le_bytes()); // time
+        header.extend_from_slice(&DOS_DATE.to_le_bytes());
+        header.extend_from_slice(&crc.to_le_bytes());
+        header.extend_from_slice(&size.to_le_bytes());
+        header.extend_from_slice(&size.to_le_bytes());
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); // extra length
+
+        out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        out.extend_from_slice(&header);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+
+        central.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        central.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        central.extend_from_slice(&header);
+        central.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        central.extend_from_slice(&0u16.to_le_bytes()); // disk
+        central.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+        central.extend_from_slice(&(if is_dir { 0x10u32 } else { 0 }).to_le_bytes());
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name.as_bytes());
+    }
+    let central_offset = out.len() as u32;
+    out.extend_from_slice(&central);
+    out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    out.extend_from_slice(&central_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out
+}
+
+// ── Structural validation ─────────────────────────────────────────────────────
+
+/// `key="value"` attribute of an element, matched only at an attribute
+/// boundary so `name` does not match `modelName`.
+fn xml_attr_value<'a>(element: &'a str, key: &str) -> Option<&'a str> {
+    let pat = format!("{key}=\"");
+    let mut from = 0;
+    while let Some(p) = element[from..].find(&pat) {
+        let at = from + p;
+        if at == 0 || element.as_bytes()[at - 1].is_ascii_whitespace() {
+            let start = at + pat.len();
+            let end = element[start..].find('"')? + start;
+            return Some(&element[start..end]);
+        }
+        from = at + pat.len();
+    }
+    None
+}
+
+/// Indices listed in `<tag>…</tag>` (`Some(empty)` for `<tag/>`).
+fn unknown_indices(xml: &str, tag: &str) -> Option<Vec<usize>> {
+    if xml.contains(&format!("<{tag}/>")) {
+        return Some(Vec::new());
+    }
+    let start = xml.find(&format!("<{tag}>"))?;
+    let end = xml[start..].find(&format!("</{tag}>"))? + start;
+    Some(
+        xml[start..end]
+            .split("<Unknown")
+            .skip(1)
+            .filter_map(|u| xml_attr_value(u, "index").and_then(|i| i.parse().ok()))
+            .collect(),
+    )
+}
+
+/// Check an FMU package against the FMI 2.0 Co-Simulation structural rules
+/// applied by the FMI Cross-Check: archive layout, required attributes,
+/// unique names and value references, causality/variability/initial
+/// combinations and their `start` values, derivative references, and the
+/// `ModelStructure` lists. Returns every violation found (empty = valid).
+pub fn check_fmu_package(pkg: &FmuPackage) -> Vec<String> {
+    let mut problems = Vec::new();
+    let xml = &pkg.model_description_xml;
+    let files = pkg.files();
+    let has = |path: &str| files.iter().any(|(p, _)| p == path);
+
+    if !has("modelDescription.xml") {
+        problems.push("archive has no modelDescription.xml at its root".to_string());
+    }
+    if !BINARY_PLATFORMS.iter().any(|p| has(&format!("binaries/{p}/"))) {
+        problems.push("archive has no binaries/ folder".to_string());
+    }
+
+    let Some(root_end) = xml.find("<fmiModelDescription").and_then(|s| xml[s..].find('>').map(|e| s + e)) else {
+        problems.push("missing <fmiModelDescription> element".to_string());
+        return problems;
+    };
+    let root = &xml[..root_end];
+    if xml_attr_value(root, "fmiVersion") != Some("2.0") {
+        problems.push("fmiVersion must be \"2.0\" for the FMI 2.0 layout".to_string());
+    }
+    for key in ["modelName", "guid"] {
+        if xml_attr_value(root, key).is_none_or(|v| v.is_empty()) {
+            problems.push(format!("fmiModelDescription is missing {key}"));
+        }
+    }
+
+    let co_sim = xml.find("<CoSimulation").map(|s| &xml[s..]).unwrap_or("");
+    match xml_attr_value(co_sim, "modelIdentifier") {
+        None => problems.push("missing <CoSimulation modelIdentifier>".to_string()),
+        Some(id) => {
+            if !is_c_identifier(id) {
+                problems.push(format!("modelIdentifier '{id}' is not a valid C identifier"));
+            }
+            if id != pkg.model_identifier {
+                problems.push(format!("modelIdentifier '{id}' does not match the package"));
+            }
+            if !has(&format!("sources/{id}.c")) {
+                problems.push(format!("archive has no sources/{id}.c"));
+            }
+            if !co_sim.contains(&format!("<File name=\"{id}.c\"/>")) {
+                problems.push(format!("<SourceFiles> does not list {id}.c"));
+            }
+        }
+    }
+
+    struct Sv<'a> {
+        causality: &'a str,
+        variability: &'a str,
+        initial: Option<&'a str>,
+        has_start: bool,
+        derivative: Option<usize>,
+    }
+    let mut vars: Vec<Sv> = Vec::new();
+    let mut names = BTreeSet::new();
+    let mut vrs = BTreeSet::new();
+    let mut rest = xml.as_str();
+    while let Some(s) = rest.find("<ScalarVariable") {
+        let Some(e) = rest[s..].find("</ScalarVariable>") else {
+            problems.push("unterminated <ScalarVariable>".to_string());
+            break;
+        };
+        let block = &rest[s..s + e];
+        rest = &rest[s + e..];
+        let head = &block[..block.find('>').unwrap_or(block.len())];
+        let n = vars.len() + 1;
+        let name = xml_attr_value(head, "name").unwrap_or("");
+        if name.is_empty() || !names.insert(name.to_string()) {
+            problems.push(format!("variable {n}: name '{name}' is empty or not unique"));
+        }
+        match xml_attr_value(head, "valueReference").and_then(|v| v.parse::<u32>().ok()) {
+            Some(vr) if vrs.insert(vr) => {}
+            _ => problems.push(format!("variable '{name}': valueReference missing or not unique")),
+        }
+        let sv = Sv {
+            causality: xml_attr_value(head, "causality").unwrap_or("local"),
+            variability: xml_attr_value(head, "variability").unwrap_or("continuous"),
+            initial: xml_attr_value(head, "initial"),
+            has_start: xml_attr_value(&block[head.len()..], "start").is_some(),
+            derivative: xml_attr_value(&block[head.len()..], "derivative").and_then(|d| d.parse().ok()),
+        };
+        let initial = sv.initial.unwrap_or(match sv.causality {
+            "parameter" => "exact",
+            "output" | "local" if sv.variability != "constant" => "calculated",
+            _ => "none",
+        });
+        if !["parameter", "calculatedParameter", "input", "output", "local", "independent"].contains(&sv.causality) {
+            problems.push(format!("variable '{name}': invalid causality '{}'", sv.causality));
+        }
+        if !["constant", "fixed", "tunable", "discrete", "continuous"].contains(&sv.variability) {
+            problems.push(format!("variable '{name}': invalid variability '{}'", sv.variability));
+        }
+        if sv.causality == "parameter" && !["fixed", "tunable"].contains(&sv.variability) {
+            problems.push(format!("variable '{name}': parameters must be fixed or tunable"));
+        }
+        if sv.causality == "input" && sv.initial.is_some() {
+            problems.push(format!("variable '{name}': inputs must not define initial"));
+        }
+        if sv.causality == "input" && !sv.has_start {
+            problems.push(format!("variable '{name}': inputs need a start value"));
+        }
+        if matches!(initial, "exact" | "approx") && !sv.has_start {
+            problems.push(format!("variable '{name}': initial=\"{initial}\" needs a start value"));
+        }
+        if initial == "calculated" && sv.has_start {
+            problems.push(format!("variable '{name}': initial=\"calculated\" must not have a start value"));
+        }
+        vars.push(sv);
+    }
+    if vars.is_empty() {
+        problems.push("no <ScalarVariable> elements".to_string());
+    }
+
+    let is_calc = |sv: &Sv| {
+        sv.initial.unwrap_or(if sv.causality == "output" || sv.causality == "local" { "calculated" } else { "exact" })
+            != "exact"
+    };
+    let mut expected_derivs = Vec::new();
+    for (i, sv) in vars.iter().enumerate() {
+        if let Some(d) = sv.derivative {
+            match d.checked_sub(1).and_then(|d| vars.get(d)) {
+                Some(state) if state.variability == "continuous" => expected_derivs.push(i + 1),
+                _ => problems.push(format!("variable {}: derivative=\"{d}\" does not reference a continuous variable", i + 1)),
+            }
+        }
+    }
+    let expected_outputs: Vec<usize> =
+        vars.iter().enumerate().filter(|(_, v)| v.causality == "output").map(|(i, _)| i + 1).collect();
+    let expected_initial: Vec<usize> = vars
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| (v.causality == "output" || v.derivative.is_some()) && is_calc(v))
+        .map(|(i, _)| i + 1)
+        .collect();
+    for (tag, expected) in
+        [("Outputs", &expected_outputs), ("Derivatives", &expected_derivs), ("InitialUnknowns", &expected_initial)]
+    {
+        match unknown_indices(xml, tag) {
+            None => problems.push(format!("ModelStructure is missing <{tag}>")),
+            Some(found) if found != *expected => {
+                problems.push(format!("ModelStructure <{tag}> lists {found:?}, expected {expected:?}"))
+            }
+            Some(_) => {}
+        }
+    }
+
+    let experiment = xml.find("<DefaultExperiment").map(|s| &xml[s..]).unwrap_or("");
+    let num = |k: &str| xml_attr_value(experiment, k).and_then(|v| v.parse::<f64>().ok());
+    if let (Some(start), Some(stop)) = (num("startTime"), num("stopTime")) {
+        if stop <= start {
+            problems.push("DefaultExperiment stopTime must exceed startTime".to_string());
+        }
+    }
+    if num("stepSize").is_some_and(|h| h <= 0.0) {
+        problems.push("DefaultExperiment stepSize must be positive".to_string());
+    }
+    problems
 }
 
 // ── Helper: build FmuConfig from ops.rs data ──────────────────────────────────
 
+/// Turn arbitrary text into a C identifier (`_` for anything else).
+fn c_identifier(s: &str) -> String {
+    let mut id: String = s.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    if !id.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        id.insert(0, '_');
+    }
+    id
+}
+
 /// Build a `FmuConfig` from the `fmu.export` block's `data` map and input value.
 pub fn fmu_config_from_block(
     data: &std::collections::HashMap<String, serde_json::Value>,
     inputs: &std::collections::HashMap<String, crate::types::Value>,
 ) -> FmuConfig {
-    let model_name = data
-        .get("fmuModelName")
-        .and_then(|v| v.as_str())
-        .unwrap_or("ChainSolveFMU")
-        .to_string();
+    let model_name = c_identifier(
+        data.get("fmuModelName")
+            .and_then(|v| v.as_str())
+            .unwrap_or("ChainSolveFMU"),
+    );
     let description = data
         .get("fmuDescription")
         .and_then(|v| v.as_str())
@@ -500,6 +1119,8 @@ pub fn fmu_config_from_block(
         fmi_version: "2.0".to_string(),
         outputs: output_vars,
         inputs: vec![],
+        parameters: vec![],
+        states: vec![],
         start_time: 0.0,
         stop_time: data
             .get("stopTime")
@@ -513,8 +1134,322 @@ pub fn fmu_config_from_block(
     }
 }
 
+// ── Graph translation ─────────────────────────────────────────────────────────
+
+const ODE_BLOCKS: [&str; 4] = ["ode.rk4", "ode.rk45", "ode.bdf", "ode.radau"];
+
+/// Translates graph nodes into `expr` formulas, collecting the FMI
+/// variables (number blocks) they read.
+struct GraphTranslator<'a> {
+    graph: &'a EngineSnapshotV1,
+    values: HashMap<String, Value>,
+    memo: HashMap<String, String>,
+    inputs: Vec<FmuVariable>,
+    parameters: Vec<FmuVariable>,
+    names: BTreeSet<String>,
+}
+
+impl GraphTranslator<'_> {
+    fn node(&self, id: &str) -> Result<&crate::types::NodeDef, String> {
+        self.graph.nodes.iter().find(|n| n.id == id).ok_or_else(|| format!("[FMU_EXPORT] Unknown node '{id}'"))
+    }
+
+    /// Node feeding `handle` of `id`, if connected.
+    fn source(&self, id: &str, handle: &str) -> Option<&str> {
+        self.graph.edges.iter().find(|e| e.target == id && e.target_handle == handle).map(|e| e.source.as_str())
+    }
+
+    fn arg(&mut self, id: &str, handle: &str) -> Result<String, String> {
+        let src = self
+            .source(id, handle)
+            .ok_or_else(|| format!("[FMU_EXPORT] Input '{handle}' of node '{id}' is not connected"))?
+            .to_string();
+        self.scalar(&src)
+    }
+
+    fn unique_name(&mut self, wanted: &str) -> String {
+        let base = c_identifier(wanted);
+        let mut name = base.clone();
+        let mut k = 2;
+        while !self.names.insert(name.clone()) {
+            name = format!("{base}_{k}");
+            k += 1;
+        }
+        name
+    }
+
+    /// Whether any number block marked as an FMU input feeds `id`.
+    fn reads_input(&self, id: &str) -> bool {
+        let mut stack = vec![id.to_string()];
+        let mut seen = BTreeSet::new();
+        while let Some(n) = stack.pop() {
+            if !seen.insert(n.clone()) {
+                continue;
+            }
+            if let Some(node) = self.graph.nodes.iter().find(|x| x.id == n) {
+                if node.data.get("fmuCausality").and_then(|v| v.as_str()) == Some("input") {
+                    return true;
+                }
+            }
+            stack.extend(self.graph.edges.iter().filter(|e| e.target == n).map(|e| e.source.clone()));
+        }
+        false
+    }
+
+    /// `expr` formula for the scalar value of node `id`.
+    fn scalar(&mut self, id: &str) -> Result<String, String> {
+        if let Some(f) = self.memo.get(id) {
+            return Ok(f.clone());
+        }
+        let node = self.node(id)?.clone();
+        let bt = node.block_type.as_str();
+        let deg = node.data.get("angleUnit").and_then(|v| v.as_str()) == Some("deg");
+        let unary = |f: &str, this: &mut Self, h: &str| -> Result<String, String> {
+            Ok(format!("{f}({})", this.arg(id, h)?))
+        };
+        let formula = match bt {
+            "number" | "slider" | "variableSource" => {
+                let start = match self.values.get(id) {
+                    Some(Value::Scalar { value }) => *value,
+                    _ => node.data.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0),
+                };
+                let label = node.data.get("label").and_then(|v| v.as_str()).unwrap_or(id);
+                let name = self.unique_name(label);
+                if node.data.get("fmuCausality").and_then(|v| v.as_str()) == Some("input") {
+                    self.inputs.push(FmuVariable::input(name.clone(), start));
+                } else {
+                    self.parameters.push(FmuVariable::parameter(name.clone(), start));
+                }
+                name
+            }
+            "add" | "multiply" => {
+                let op = if bt == "add" { " + " } else { " * " };
+                let mut terms = Vec::new();
+                let mut k = 0;
+                while self.source(id, &format!("in_{k}")).is_some() {
+                    terms.push(self.arg(id, &format!("in_{k}"))?);
+                    k += 1;
+                }
+                if terms.is_empty() {
+                    terms = vec![self.arg(id, "a")?, self.arg(id, "b")?];
+                }
+                format!("({})", terms.join(op))
+            }
+            "subtract" => format!("({} - {})", self.arg(id, "a")?, self.arg(id, "b")?),
+            "divide" => format!("({} / {})", self.arg(id, "a")?, self.arg(id, "b")?),
+            "power" => format!("({} ^ {})", self.arg(id, "base")?, self.arg(id, "exp")?),
+            "negate" => format!("(-{})", self.arg(id, "a")?),
+            "abs" | "sqrt" | "floor" | "ceil" | "round" | "ln" | "log10" | "exp" => unary(bt, self, "a")?,
+            "sign" => {
+                let x = self.arg(id, "a")?;
+                format!("if({x} > 0, 1, if({x} < 0, -1, 0))")
+            }
+            "clamp" => format!(
+                "min(max({}, {}), {})",
+                self.arg(id, "val")?,
+                self.arg(id, "min")?,
+                self.arg(id, "max")?
+            ),
+            "sin" | "cos" | "tan" if deg => format!("{bt}({} * pi / 180)", self.arg(id, "a")?),
+            "asin" | "acos" | "atan" if deg => format!("({} * 180 / pi)", unary(bt, self, "a")?),
+            "sin" | "cos" | "tan" | "asin" | "acos" | "atan" => unary(bt, self, "a")?,
+            "atan2" => {
+                let f = format!("atan2({}, {})", self.arg(id, "y")?, self.arg(id, "x")?);
+                if deg { format!("({f} * 180 / pi)") } else { f }
+            }
+            "degToRad" => format!("({} * pi / 180)", self.arg(id, "deg")?),
+            "radToDeg" => format!("({} * 180 / pi)", self.arg(id, "rad")?),
+            "math_expr" => {
+                let formula = node.data.get("formula").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                let mut handles: Vec<String> = self
+                    .graph
+                    .edges
+                    .iter()
+                    .filter(|e| e.target == id)
+                    .map(|e| e.target_handle.clone())
+                    .collect();
+                handles.sort();
+                handles.dedup();
+                // Evaluate every argument before binding the port names, so
+                // a port named like an upstream variable cannot capture it.
+                let mut lets = String::new();
+                for (k, h) in handles.iter().enumerate() {
+                    lets.push_str(&format!("let cs_arg{k} = {}; ", self.arg(id, h)?));
+                }
+                for (k, h) in handles.iter().enumerate() {
+                    lets.push_str(&format!("let {h} = cs_arg{k}; "));
+                }
+                format!("({lets}{formula})")
+            }
+            _ => match self.values.get(id) {
+                // No C translation: export the evaluated value as a constant.
+                Some(Value::Scalar { value }) if value.is_finite() && !self.reads_input(id) => format!("({value:?})"),
+                _ => {
+                    return Err(format!(
+                        "[FMU_EXPORT] Block '{bt}' ({id}) cannot be translated to C"
+                    ))
+                }
+            },
+        };
+        self.memo.insert(id.to_string(), formula.clone());
+        Ok(formula)
+    }
+}
+
+/// Build the model of `cfg` from `graph`, exporting the value of node
+/// `output` (or, if `None`, the graph's only node without outgoing edges).
+///
+/// - A scalar node becomes one output whose equation is the translated
+///   upstream subgraph: arithmetic/trig blocks and custom-function
+///   (`math_expr`) formulas become formulas, number blocks become
+///   parameters — or inputs when their data has `fmuCausality: "input"` —
+///   and other scalar blocks are evaluated once and exported as constants.
+/// - An ODE block (`ode.rk4`, `ode.rk45`, `ode.bdf`, `ode.radau`) becomes
+///   states `y0..yN` exposed as outputs, its `params` become parameters,
+///   and its `t_start`/`t_end`/`dt` set the default experiment. `equations`
+///   and `y0` come from the connected values, else from the node's data.
+pub fn fmu_config_from_graph(
+    graph: &EngineSnapshotV1,
+    output: Option<&str>,
+    cfg: &mut FmuConfig,
+) -> Result<(), String> {
+    let root = match output {
+        Some(id) => id.to_string(),
+        None => {
+            let sinks: Vec<&str> = graph
+                .nodes
+                .iter()
+                .filter(|n| !graph.edges.iter().any(|e| e.source == n.id))
+                .map(|n| n.id.as_str())
+                .collect();
+            match sinks.as_slice() {
+                [one] => one.to_string(),
+                _ => return Err("[FMU_EXPORT] Name the exported node: the graph has several outputs".to_string()),
+            }
+        }
+    };
+    let mut t = GraphTranslator {
+        graph,
+        values: crate::eval::evaluate(graph).values,
+        memo: HashMap::new(),
+        inputs: Vec::new(),
+        parameters: Vec::new(),
+        names: ["t", "time"].iter().map(|s| s.to_string()).collect(),
+    };
+    let node = t.node(&root)?.clone();
+
+    if ODE_BLOCKS.contains(&node.block_type.as_str()) {
+        // Connected values first, else the same keys in the node's data.
+        let port = |h: &str| {
+            t.source(&root, h).and_then(|s| t.values.get(s)).cloned().or_else(|| match node.data.get(h) {
+                Some(serde_json::Value::String(s)) => Some(Value::Text { value: s.clone() }),
+                Some(serde_json::Value::Array(a)) => {
+                    Some(Value::Vector { value: a.iter().filter_map(|v| v.as_f64()).collect() })
+                }
+                Some(v) => v.as_f64().map(|value| Value::Scalar { value }),
+                None => None,
+            })
+        };
+        let equations: Vec<String> = match port("equations") {
+            Some(Value::Text { value }) => {
+                value.split(';').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+            }
+            _ => return Err(format!("[FMU_EXPORT] ODE node '{root}' has no equations")),
+        };
+        let y0 = match port("y0") {
+            Some(Value::Vector { value }) => value,
+            Some(Value::Scalar { value }) => vec![value],
+            _ => return Err(format!("[FMU_EXPORT] ODE node '{root}' has no y0")),
+        };
+        if y0.len() != equations.len() {
+            return Err(format!(
+                "[FMU_EXPORT] ODE node '{root}': {} equations but {} initial values",
+                equations.len(),
+                y0.len()
+            ));
+        }
+        cfg.states = equations
+            .into_iter()
+            .zip(y0)
+            .enumerate()
+            .map(|(i, (eq, x0))| FmuVariable::state(format!("y{i}"), x0, eq))
+            .collect();
+        cfg.outputs = Vec::new();
+        let mut params: Vec<(String, f64)> = match node.data.get("params") {
+            Some(serde_json::Value::Object(obj)) => {
+                obj.iter().filter_map(|(k, v)| v.as_f64().map(|n| (k.clone(), n))).collect()
+            }
+            _ => Vec::new(),
+        };
+        params.sort_by(|a, b| a.0.cmp(&b.0));
+        cfg.parameters = params.into_iter().map(|(k, v)| FmuVariable::parameter(k, v)).collect();
+        cfg.inputs = Vec::new();
+        let num = |k: &str| node.data.get(k).and_then(|v| v.as_f64());
+        cfg.start_time = num("t_start").unwrap_or(0.0);
+        cfg.stop_time = num("t_end").unwrap_or(1.0);
+        cfg.step_size = num("dt").unwrap_or(0.01);
+        return Ok(());
+    }
+
+    let name = cfg.outputs.first().map(|o| o.name.clone()).unwrap_or_else(|| "output_0".to_string());
+    t.names.insert(name.clone());
+    let formula = t.scalar(&root)?;
+    let start = match t.values.get(&root) {
+        Some(Value::Scalar { value }) if value.is_finite() => *value,
+        _ => 0.0,
+    };
+    cfg.outputs = vec![FmuVariable::output(name, start).with_equation(formula)];
+    cfg.inputs = t.inputs;
+    cfg.parameters = t.parameters;
+    cfg.states = Vec::new();
+    Ok(())
+}
+
+
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
+
+/// Data of the `fmu.export` node `id` with the subgraph upstream of its
+/// `data` input attached as `graph`/`graphOutput`, so the block compiles the
+/// connected model. `node` and `edges_into` look up nodes and incoming edges
+/// in the graph being evaluated. Data that already holds a graph, and a
+/// block with nothing connected, are returned unchanged.
+pub fn with_upstream_graph<'a>(
+    id: &str,
+    data: &'a HashMap<String, serde_json::Value>,
+    node: impl Fn(&str) -> Option<NodeDef>,
+    edges_into: impl Fn(&str) -> Vec<EdgeDef>,
+) -> Cow<'a, HashMap<String, serde_json::Value>> {
+    if data.get("graph").is_some_and(|g| !g.is_null()) {
+        return Cow::Borrowed(data);
+    }
+    let Some(output) = edges_into(id).into_iter().find(|e| e.target_handle == "data").map(|e| e.source) else {
+        return Cow::Borrowed(data);
+    };
+    let mut graph = EngineSnapshotV1 { version: 1, nodes: Vec::new(), edges: Vec::new() };
+    let mut seen = HashSet::new();
+    let mut stack = vec![output.clone()];
+    while let Some(n) = stack.pop() {
+        if !seen.insert(n.clone()) {
+            continue;
+        }
+        let Some(def) = node(&n) else { continue };
+        graph.nodes.push(def);
+        for edge in edges_into(&n) {
+            stack.push(edge.source.clone());
+            graph.edges.push(edge);
+        }
+    }
+    let ids: HashSet<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+    let edges = graph.edges.iter().filter(|e| ids.contains(e.source.as_str())).cloned().collect();
+    graph.edges = edges;
+
+    let mut data = data.clone();
+    data.insert("graph".to_string(), serde_json::to_value(&graph).unwrap_or_default());
+    data.insert("graphOutput".to_string(), serde_json::Value::String(output));
+    Cow::Owned(data)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -531,6 +1466,8 @@ mod tests {
                 FmuVariable::output("y1", 2.0),
             ],
             inputs: vec![FmuVariable::input("u0", 0.0)],
+            parameters: vec![],
+            states: vec![],
             start_time: 0.0,
             stop_time: 10.0,
             step_size: 0.01,
@@ -668,11 +1605,11 @@ mod tests {
         assert!(!xml.contains(r#"A & B"#));
     }
 
-    // ── C stub validation ─────────────────────────────────────────────────────
+    // ── C source validation ───────────────────────────────────────────────────
 
     #[test]
-    fn c_stub_has_all_required_fmi2_functions() {
-        let c = generate_fmu_c_stub(&minimal_config());
+    fn c_source_has_all_required_fmi2_functions() {
+        let c = generate_fmu_c_source(&minimal_config()).unwrap();
         let required_fns = [
             "fmi2Instantiate",
             "fmi2FreeInstance",
@@ -697,23 +1634,224 @@ mod tests {
             "fmi2GetBooleanStatus",
             "fmi2GetStringStatus",
             "fmi2SetDebugLogging",
+            "fmi2GetTypesPlatform",
+            "fmi2GetVersion",
+            "fmi2SetRealInputDerivatives",
+            "fmi2GetRealOutputDerivatives",
         ];
         for f in &required_fns {
-            assert!(c.contains(f), "C stub missing required FMI 2.0 function: {f}");
+            assert!(c.contains(f), "C source missing required FMI 2.0 function: {f}");
         }
     }
 
     #[test]
-    fn c_stub_has_correct_n_vars() {
+    fn c_source_has_correct_n_vars() {
         // 1 input + 2 outputs = 3 vars
-        let c = generate_fmu_c_stub(&minimal_config());
+        let c = generate_fmu_c_source(&minimal_config()).unwrap();
         assert!(c.contains("#define N_VARS 3"), "N_VARS should be 3");
     }
 
     #[test]
     fn package_contains_both_files() {
-        let pkg = generate_fmu_package(&minimal_config());
+        let pkg = generate_fmu_package(&minimal_config()).unwrap();
         assert!(!pkg.model_description_xml.is_empty());
-        assert!(!pkg.model_c_stub.is_empty());
+        assert!(!pkg.model_c_source.is_empty());
+    }
+
+    fn oscillator_config() -> FmuConfig {
+        let mut cfg = minimal_config();
+        cfg.parameters = vec![FmuVariable::parameter("k", 4.0)];
+        cfg.states = vec![FmuVariable::state("x", 1.0, "v"), FmuVariable::state("v", 0.0, "-k*x + u0")];
+        cfg.outputs = vec![
+            FmuVariable::output("energy", 0.0).with_equation("half * (v^2 + k*x^2)"),
+            FmuVariable::output("half", 0.5),
+        ];
+        cfg
+    }
+
+    #[test]
+    fn states_and_equations_map_to_fmi_variables() {
+        let cfg = oscillator_config();
+        let xml = generate_fmu_xml(&cfg);
+        // u0, energy, half, k, x, v, der(x), der(v)
+        assert!(xml.contains(r#"name="energy" valueReference="2" causality="output" variability="continuous" initial="calculated""#));
+        assert!(xml.contains(r#"name="k" valueReference="4" causality="parameter" variability="fixed" initial="exact""#));
+        assert!(xml.contains(r#"name="der(v)" valueReference="8" causality="local" variability="continuous" initial="calculated""#));
+        assert!(xml.contains(r#"<Real derivative="6"/>"#));
+        assert_eq!(unknown_indices(&xml, "Outputs").unwrap(), [2, 3, 5, 6]);
+        assert_eq!(unknown_indices(&xml, "Derivatives").unwrap(), [7, 8]);
+        assert_eq!(unknown_indices(&xml, "InitialUnknowns").unwrap(), [2, 7, 8]);
+        assert_eq!(check_fmu_package(&generate_fmu_package(&cfg).unwrap()), Vec::<String>::new());
+        assert_eq!(check_fmu_package(&generate_fmu_package(&minimal_config()).unwrap()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn c_source_embeds_equations_and_integrator() {
+        let c = generate_fmu_c_source(&oscillator_config()).unwrap();
+        assert!(c.contains("r[6] = r[5]; /* der(x) */"), "{c}");
+        assert!(c.contains("r[7] = (((-r[3]) * r[4]) + r[0]); /* der(v) */"), "{c}");
+        // energy reads `half`, which holds its start value.
+        assert!(c.contains("r[1] = (r[2] * (pow(r[5], 2.0) + (r[3] * pow(r[4], 2.0)))); /* energy */"), "{c}");
+        assert!(c.contains("#define N_STATES 2"));
+        assert!(c.contains("static const size_t DERIVATIVE_INDEX[N_STATES] = { 6, 7 };"));
+        assert!(c.contains("#define INTERNAL_STEP 1e-2"));
+        assert!(!generate_fmu_c_source(&minimal_config()).unwrap().contains("rk4_step"));
+
+        let mut bad = oscillator_config();
+        bad.outputs[0].equation = Some("q * 2".into());
+        assert!(generate_fmu_c_source(&bad).unwrap_err().contains("Unknown variable: q"));
+        bad.outputs = vec![
+            FmuVariable::output("a", 0.0).with_equation("b + 1"),
+            FmuVariable::output("b", 0.0).with_equation("a"),
+        ];
+        assert!(generate_fmu_c_source(&bad).unwrap_err().contains("Algebraic loop"));
+        bad.outputs = vec![FmuVariable::output("der(x)", 0.0)];
+        assert!(generate_fmu_c_source(&bad).unwrap_err().contains("Duplicate"));
+    }
+
+    #[test]
+    fn structural_check_reports_violations() {
+        let mut pkg = generate_fmu_package(&oscillator_config()).unwrap();
+        pkg.model_description_xml = pkg
+            .model_description_xml
+            .replace(r#"initial="calculated">
+      <Real/>"#, r#"initial="calculated">
+      <Real start="1"/>"#)
+            .replace("      <Unknown index=\"3\"/>\n", "");
+        let problems = check_fmu_package(&pkg);
+        assert!(problems.iter().any(|p| p.contains("'energy'") && p.contains("must not have a start")), "{problems:?}");
+        assert!(problems.iter().any(|p| p.contains("<Outputs> lists [2, 5, 6]")), "{problems:?}");
+        pkg.model_identifier = "Other".into();
+        assert!(check_fmu_package(&pkg).iter().any(|p| p.contains("does not match")));
+    }
+
+    #[test]
+    fn archive_has_the_fmu_layout() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let pkg = generate_fmu_package(&minimal_config()).unwrap();
+        let paths: Vec<String> = pkg.files().into_iter().map(|(p, _)| p).collect();
+        assert_eq!(
+            paths,
+            [
+                "modelDescription.xml",
+                "sources/",
+                "sources/TestModel.c",
+                "binaries/",
+                "binaries/linux64/",
+                "binaries/win64/",
+                "binaries/darwin64/"
+            ]
+        );
+        let zip = pkg.to_zip();
+        assert_eq!(&zip[..4], &[0x50, 0x4b, 0x03, 0x04]);
+        // End-of-central-directory record: 7 entries, directory at the recorded offset.
+        let eocd = &zip[zip.len() - 22..];
+        assert_eq!(&eocd[..4], &[0x50, 0x4b, 0x05, 0x06]);
+        assert_eq!(u16::from_le_bytes([eocd[10], eocd[11]]), 7);
+        let cd = u32::from_le_bytes([eocd[16], eocd[17], eocd[18], eocd[19]]) as usize;
+        assert_eq!(&zip[cd..cd + 4], &[0x50, 0x4b, 0x01, 0x02]);
+        let name_len = u16::from_le_bytes([zip[26], zip[27]]) as usize;
+        assert_eq!(&zip[30..30 + name_len], b"modelDescription.xml");
+    }
+
+    fn node(id: &str, block_type: &str, data: serde_json::Value) -> crate::types::NodeDef {
+        serde_json::from_value(serde_json::json!({ "id": id, "blockType": block_type, "data": data })).unwrap()
+    }
+
+    fn edge(source: &str, target: &str, handle: &str) -> crate::types::EdgeDef {
+        crate::types::EdgeDef {
+            id: format!("{source}-{target}-{handle}"),
+            source: source.into(),
+            source_handle: "out".into(),
+            target: target.into(),
+            target_handle: handle.into(),
+        }
+    }
+
+    #[test]
+    fn arithmetic_subgraph_becomes_an_output_equation() {
+        let graph = EngineSnapshotV1 {
+            version: 1,
+            nodes: vec![
+                node("m", "number", serde_json::json!({ "value": 2.0, "label": "mass" })),
+                node("v", "number", serde_json::json!({ "value": 3.0, "label": "speed", "fmuCausality": "input" })),
+                node("pi", "pi", serde_json::json!({})),
+                node("f", "math_expr", serde_json::json!({ "formula": "0.5 * a * b^2" })),
+                node("sum", "add", serde_json::json!({})),
+            ],
+            edges: vec![edge("m", "f", "a"), edge("v", "f", "b"), edge("f", "sum", "a"), edge("pi", "sum", "b")],
+        };
+        let mut cfg = minimal_config();
+        cfg.outputs = vec![FmuVariable::output("kinetic", 0.0)];
+        fmu_config_from_graph(&graph, None, &mut cfg).unwrap();
+        assert_eq!(cfg.parameters.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(), ["mass"]);
+        assert_eq!(cfg.inputs.iter().map(|p| (p.name.as_str(), p.start)).collect::<Vec<_>>(), [("speed", 3.0)]);
+        let out = &cfg.outputs[0];
+        assert_eq!(out.name, "kinetic");
+        assert!((out.start - (9.0 + std::f64::consts::PI)).abs() < 1e-12);
+        let vars: HashMap<String, f64> = [("mass".to_string(), 4.0), ("speed".to_string(), 1.0)].into();
+        let y = crate::expr::eval_expr(out.equation.as_deref().unwrap(), &vars).unwrap();
+        assert!((y - (2.0 + std::f64::consts::PI)).abs() < 1e-12);
+        let pkg = generate_fmu_package(&cfg).unwrap();
+        assert!(check_fmu_package(&pkg).is_empty());
+
+        // A block without a C translation is baked in only if no FMU input feeds it.
+        let mut graph = graph;
+        graph.nodes.push(node("g", "gamma", serde_json::json!({})));
+        graph.edges.push(edge("v", "g", "a"));
+        assert!(fmu_config_from_graph(&graph, Some("g"), &mut cfg).unwrap_err().contains("cannot be translated"));
+    }
+
+    #[test]
+    fn upstream_graph_is_attached_to_the_export_data() {
+        let nodes = [
+            node("k", "number", serde_json::json!({ "value": 2.0 })),
+            node("x", "number", serde_json::json!({ "value": 3.0 })),
+            node("prod", "multiply", serde_json::json!({})),
+            node("other", "number", serde_json::json!({ "value": 9.0 })),
+            node("fmu", "fmu.export", serde_json::json!({})),
+        ];
+        let edges = [edge("k", "prod", "a"), edge("x", "prod", "b"), edge("prod", "fmu", "data"), edge("other", "fmu", "extra")];
+        let lookup = |id: &str| nodes.iter().find(|n| n.id == id).cloned();
+        let into = |id: &str| edges.iter().filter(|e| e.target == id).cloned().collect();
+
+        let data = with_upstream_graph("fmu", &nodes[4].data, lookup, into);
+        assert_eq!(data["graphOutput"], "prod");
+        let graph: EngineSnapshotV1 = serde_json::from_value(data["graph"].clone()).unwrap();
+        let mut ids: Vec<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, ["k", "prod", "x"]);
+        assert_eq!(graph.edges.len(), 2);
+
+        // Nothing connected, or an explicit graph: the data is left alone.
+        assert!(matches!(with_upstream_graph("k", &nodes[0].data, lookup, into), Cow::Borrowed(_)));
+        let explicit = HashMap::from([("graph".to_string(), serde_json::json!({ "version": 1, "nodes": [], "edges": [] }))]);
+        assert!(matches!(with_upstream_graph("fmu", &explicit, lookup, into), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn ode_block_becomes_fmu_states() {
+        let graph = EngineSnapshotV1 {
+            version: 1,
+            nodes: vec![
+                node("y0", "vectorInput", serde_json::json!({ "vectorData": [1.0, 0.0] })),
+                node(
+                    "ode",
+                    "ode.rk4",
+                    serde_json::json!({ "equations": "y1; -(w^2) * y0", "params": { "w": 2.0 }, "t_end": 3.0, "dt": 0.001 }),
+                ),
+            ],
+            edges: vec![edge("y0", "ode", "y0")],
+        };
+        let mut cfg = minimal_config();
+        fmu_config_from_graph(&graph, Some("ode"), &mut cfg).unwrap();
+        assert_eq!(cfg.states.len(), 2);
+        assert_eq!(cfg.states[1].equation.as_deref(), Some("-(w^2) * y0"));
+        assert_eq!(cfg.parameters[0].name, "w");
+        assert_eq!((cfg.stop_time, cfg.step_size), (3.0, 0.001));
+        let pkg = generate_fmu_package(&cfg).unwrap();
+        assert!(check_fmu_package(&pkg).is_empty(), "{:?}", check_fmu_package(&pkg));
+        assert!(pkg.model_c_source.contains("r[4] = ((-pow(r[0], 2.0)) * r[1]); /* der(y1) */"), "{}", pkg.model_c_source);
     }
 }
@@ -284,7 +284,7 @@ mod tests {
             state_names: vec!["y".to_string()],
             params,
         };
-        let solver_cfg = OdeSolverConfig { t_start: 0.0, t_end: 0.5, dt: 0.01, tolerance: 1e-6, max_steps: 1000 };
+        let solver_cfg = OdeSolverConfig { t_start: 0.0, t_end: 0.5, dt: 0.01, tolerance: 1e-6, max_steps: 1000, ..Default::default() };
         let result = revolve_adjoint(&system, &["k".to_string()], "0.5*y0^2", &solver_cfg, 3, 1e-6);
         assert!(!result.grad[0].is_nan(), "gradient should not be NaN");
         assert!(result.objective > 0.0, "objective should be positive");
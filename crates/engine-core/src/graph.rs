@@ -23,8 +23,25 @@
 //! After patching, `evaluate_dirty()` re-evaluates only the dirty set in
 //! topological order and returns an [`IncrementalEvalResult`] with only the
 //! changed values.
+//!
+//! # Discrete-time state
+//!
+//! Stateful blocks ([`crate::discrete`]) keep a [`NodeState`] per node.
+//! `evaluate_dirty()` computes their output from the current state without
+//! changing it; [`EngineGraph::step`] commits the next state and re-evaluates.
+//! Edges into blocks without direct feedthrough (unit delay, forward-Euler
+//! integrator, strictly proper transfer function) are ignored by the
+//! topological sort, so feedback loops through them are legal.
+//!
+//! # User functions
+//!
+//! `expr.define` nodes declare functions for every expression in the graph.
+//! The table is rebuilt when such a node is added, removed or edited; if it
+//! changes, every node is marked dirty because any formula may call it.
 
+use crate::discrete::{self, NodeState};
 use crate::eval::check_ill_conditioning;
+use crate::expr::{self, FunctionTable};
 use crate::ops::evaluate_node_with_datasets;
 use crate::types::{
     Diagnostic, DiagLevel, EdgeDef, EngineSnapshotV1, EvalOptions, IncrementalEvalResult, NodeDef,
@@ -33,6 +50,7 @@ use crate::types::{
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 use std::collections::hash_map::DefaultHasher;
 
 // ── NaN/Inf detection helpers ───────────────────────────────────────
@@ -174,6 +192,25 @@ pub struct EngineGraph {
     topo_dirty: bool,
     /// Dataset registry: id → raw f64 data.
     pub datasets: HashMap<String, Vec<f64>>,
+    /// Binary dataset registry: id → raw file bytes (read by import blocks via `blobRef`).
+    pub blobs: HashMap<String, Vec<u8>>,
+    /// Discrete-time state of stateful blocks, keyed by node id.
+    state: HashMap<String, NodeState>,
+    /// Sample time used by stateful blocks that have no `data.dt` of their own.
+    step_size: f64,
+    /// User functions from `expr.define` nodes, visible to every expression.
+    functions: Arc<FunctionTable>,
+    /// Definitions `functions` was built from, in node-id order.
+    function_defs: Vec<expr::Definition>,
+    /// Dirty nodes that value pruning must not skip: their inputs may be
+    /// unchanged but the user functions they call are not.
+    pinned: HashSet<String>,
+}
+
+impl Default for EngineGraph {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl EngineGraph {
@@ -189,6 +226,12 @@ impl EngineGraph {
             dirty: HashSet::new(),
             topo_dirty: true,
             datasets: HashMap::new(),
+            blobs: HashMap::new(),
+            state: HashMap::new(),
+            step_size: 1.0,
+            functions: Arc::new(FunctionTable::new()),
+            function_defs: Vec::new(),
+            pinned: HashSet::new(),
         }
     }
 
@@ -201,6 +244,8 @@ impl EngineGraph {
         self.values.clear();
         self.value_hashes.clear();
         self.dirty.clear();
+        self.pinned.clear();
+        self.state.clear();
 
         for node in snapshot.nodes {
             self.dirty.insert(node.id.clone());
@@ -214,14 +259,75 @@ impl EngineGraph {
             self.edges.insert(edge.id.clone(), edge);
         }
 
+        self.refresh_functions();
         self.topo_dirty = true;
     }
 
+    /// Load a snapshot together with previously computed values.
+    ///
+    /// Nodes with a cached value start clean, so the next `evaluate_dirty()`
+    /// only computes nodes that had no value. With `stale = true` the cached
+    /// values are still installed (readable via [`values`](Self::values)) but
+    /// every node is marked dirty and will be recomputed.
+    pub fn restore_snapshot(
+        &mut self,
+        snapshot: EngineSnapshotV1,
+        values: HashMap<String, Value>,
+        stale: bool,
+    ) {
+        self.load_snapshot(snapshot);
+        for (id, value) in values {
+            if !self.nodes.contains_key(&id) {
+                continue;
+            }
+            if !stale {
+                self.dirty.remove(&id);
+            }
+            if !matches!(value, Value::Error { .. }) {
+                self.value_hashes.insert(id.clone(), compute_value_hash(&value));
+            }
+            self.values.insert(id, value);
+        }
+        // A clean node downstream of a dirty one would otherwise keep a value
+        // computed from inputs that are about to change.
+        let targets: Vec<String> = self
+            .dirty
+            .iter()
+            .flat_map(|id| self.out_adj.get(id).into_iter().flatten())
+            .filter(|(_, target, _)| !self.breaks_cycle_into(target))
+            .map(|(_, target, _)| target.clone())
+            .collect();
+        for id in targets {
+            self.mark_dirty(&id);
+        }
+    }
+
+    /// Export the current nodes and edges as a snapshot (sorted by id).
+    pub fn to_snapshot(&self) -> EngineSnapshotV1 {
+        let mut nodes: Vec<NodeDef> = self.nodes.values().cloned().collect();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
+        let mut edges: Vec<EdgeDef> = self.edges.values().cloned().collect();
+        edges.sort_by(|a, b| a.id.cmp(&b.id));
+        EngineSnapshotV1 { version: 1, nodes, edges }
+    }
+
+    /// Whether a node is waiting for re-evaluation.
+    pub fn is_dirty(&self, node_id: &str) -> bool {
+        self.dirty.contains(node_id)
+    }
+
+    /// Number of nodes waiting for re-evaluation.
+    pub fn dirty_count(&self) -> usize {
+        self.dirty.iter().filter(|id| self.nodes.contains_key(*id)).count()
+    }
+
     /// Apply a batch of patch operations.
     pub fn apply_patch(&mut self, ops: Vec<PatchOp>) {
+        let mut functions_touched = false;
         for op in ops {
             match op {
                 PatchOp::AddNode { node } => {
+                    functions_touched |= node.block_type == expr::DEFINE_BLOCK;
                     let id = node.id.clone();
                     self.out_adj.entry(id.clone()).or_default();
                     self.in_adj.entry(id.clone()).or_default();
@@ -245,16 +351,21 @@ impl EngineGraph {
                         self.remove_edge_internal(&eid);
                         self.edges.remove(&eid);
                     }
-                    self.nodes.remove(&node_id);
+                    if let Some(node) = self.nodes.remove(&node_id) {
+                        functions_touched |= node.block_type == expr::DEFINE_BLOCK;
+                    }
                     self.out_adj.remove(&node_id);
                     self.in_adj.remove(&node_id);
                     self.values.remove(&node_id);
                     self.value_hashes.remove(&node_id);
+                    self.state.remove(&node_id);
                     self.dirty.remove(&node_id);
+                    self.pinned.remove(&node_id);
                     self.topo_dirty = true;
                 }
                 PatchOp::UpdateNodeData { node_id, data } => {
                     if let Some(node) = self.nodes.get_mut(&node_id) {
+                        functions_touched |= node.block_type == expr::DEFINE_BLOCK;
                         node.data = data;
                         self.mark_dirty(&node_id);
                     }
@@ -281,6 +392,30 @@ impl EngineGraph {
                 }
             }
         }
+        if functions_touched && self.refresh_functions() {
+            let ids: Vec<String> = self.nodes.keys().cloned().collect();
+            self.dirty.extend(ids.iter().cloned());
+            self.pinned.extend(ids);
+        }
+    }
+
+    /// Rebuild the user-function table from the `expr.define` nodes.
+    /// Returns `true` if the definitions changed.
+    fn refresh_functions(&mut self) -> bool {
+        let defs = expr::definitions_in(self.nodes.values());
+        if defs == self.function_defs {
+            return false;
+        }
+        let mut table = FunctionTable::new();
+        table.define_all(&defs);
+        self.functions = Arc::new(table);
+        self.function_defs = defs;
+        true
+    }
+
+    /// User functions currently defined by `expr.define` nodes.
+    pub fn functions(&self) -> &FunctionTable {
+        &self.functions
     }
 
     /// Set a manual input value on a node's data (manualValues map).
@@ -298,6 +433,36 @@ impl EngineGraph {
         }
     }
 
+    /// Overwrite the `value` field of a source node (number, slider, …).
+    /// Marks the node and its downstream dirty only if the value changed.
+    ///
+    /// Used by the simulation loop to drive the clock and state feedback
+    /// nodes between steps. Returns `false` if the node does not exist.
+    pub fn set_source_value(&mut self, node_id: &str, value: f64) -> bool {
+        let Some(node) = self.nodes.get_mut(node_id) else {
+            return false;
+        };
+        let prev = node.data.get("value").and_then(|v| v.as_f64());
+        if prev.map(|p| p.to_bits()) != Some(value.to_bits()) {
+            node.data.insert("value".to_string(), serde_json::json!(value));
+            self.mark_dirty(node_id);
+        }
+        true
+    }
+
+    /// Whether a node with this id exists in the graph.
+    pub fn has_node(&self, node_id: &str) -> bool {
+        self.nodes.contains_key(node_id)
+    }
+
+    /// Whether a node outputs its own `value` field (number, slider, …), so
+    /// that [`set_source_value`](Self::set_source_value) drives its output.
+    pub fn is_source_node(&self, node_id: &str) -> bool {
+        self.nodes.get(node_id).is_some_and(|n| {
+            matches!(n.block_type.as_str(), "number" | "slider" | "variableSource" | "boolean_input")
+        })
+    }
+
     /// Register a dataset by id.
     pub fn register_dataset(&mut self, id: String, data: Vec<f64>) {
         self.datasets.insert(id, data);
@@ -308,14 +473,42 @@ impl EngineGraph {
         self.datasets.remove(id);
     }
 
-    /// Number of datasets currently registered.
+    /// Register a binary dataset (e.g. the bytes of a .mat or .parquet file).
+    /// Marks nodes whose `data.blobRef` names it dirty.
+    pub fn register_blob(&mut self, id: String, bytes: Vec<u8>) {
+        self.blobs.insert(id.clone(), bytes);
+        self.mark_blob_readers_dirty(&id);
+    }
+
+    /// Release (remove) a binary dataset.
+    pub fn release_blob(&mut self, id: &str) {
+        if self.blobs.remove(id).is_some() {
+            self.mark_blob_readers_dirty(id);
+        }
+    }
+
+    fn mark_blob_readers_dirty(&mut self, blob_id: &str) {
+        let readers: Vec<String> = self
+            .nodes
+            .values()
+            .filter(|n| n.data.get("blobRef").and_then(|v| v.as_str()) == Some(blob_id))
+            .map(|n| n.id.clone())
+            .collect();
+        for id in readers {
+            self.mark_dirty(&id);
+        }
+    }
+
+    /// Number of datasets (numeric and binary) currently registered.
     pub fn dataset_count(&self) -> usize {
-        self.datasets.len()
+        self.datasets.len() + self.blobs.len()
     }
 
-    /// Total bytes used by all registered datasets (each f64 = 8 bytes).
+    /// Total bytes used by all registered datasets (each f64 = 8 bytes,
+    /// binary datasets at their byte length).
     pub fn dataset_total_bytes(&self) -> usize {
-        self.datasets.values().map(|v| v.len() * 8).sum()
+        self.datasets.values().map(|v| v.len() * 8).sum::<usize>()
+            + self.blobs.values().map(|b| b.len()).sum::<usize>()
     }
 
     /// Evaluate only dirty nodes. Returns changed values.
@@ -362,70 +555,53 @@ impl EngineGraph {
                 continue;
             }
             self.dirty.remove(node_id);
+            self.pinned.remove(node_id);
 
             let node = match self.nodes.get(node_id) {
                 Some(n) => n,
                 None => continue,
             };
 
-            // Gather input values from edges.
-            let mut node_inputs: HashMap<String, Value> = HashMap::new();
-            if let Some(in_edges) = self.in_adj.get(node_id) {
-                for (_eid, src_id, src_handle, tgt_handle) in in_edges {
-                    if let Some(val) = self.values.get(src_id) {
-                        // Table column handles: col_0, col_1, ...
-                        if src_handle.starts_with("col_") {
-                            if let Value::Table { columns: _, rows } = val {
-                                if let Ok(idx) = src_handle[4..].parse::<usize>() {
-                                    let col: Vec<f64> = rows
-                                        .iter()
-                                        .map(|row| row.get(idx).copied().unwrap_or(0.0))
-                                        .collect();
-                                    node_inputs
-                                        .insert(tgt_handle.clone(), Value::Vector { value: col });
-                                    continue;
-                                }
-                            }
-                        }
-                        // Material property handles: prop_rho, prop_E, ...
-                        if src_handle.starts_with("prop_") {
-                            if let Value::Table { columns, rows } = val {
-                                let prop_name = &src_handle[5..];
-                                if let Some(idx) = columns.iter().position(|c| c == prop_name) {
-                                    let v = rows.first().and_then(|r| r.get(idx).copied()).unwrap_or(0.0);
-                                    node_inputs.insert(tgt_handle.clone(), Value::scalar(v));
-                                    continue;
-                                }
-                            }
-                        }
-                        node_inputs.insert(tgt_handle.clone(), val.clone());
-                    }
-                }
-            }
-
-            // Apply portOverrides / manualValues.
-            let overrides = node.data.get("portOverrides").and_then(|v| v.as_object());
-            let manuals = node.data.get("manualValues").and_then(|v| v.as_object());
-            if let Some(manuals) = manuals {
-                for (port_id, val) in manuals {
-                    if let Some(n) = val.as_f64() {
-                        let is_overridden = overrides
-                            .and_then(|o| o.get(port_id))
-                            .and_then(|v| v.as_bool())
-                            .unwrap_or(false);
-                        if !node_inputs.contains_key(port_id) || is_overridden {
-                            node_inputs.insert(port_id.clone(), Value::scalar(n));
-                        }
-                    }
-                }
-            }
-
-            let result = evaluate_node_with_datasets(
-                &node.block_type,
-                &node_inputs,
-                &node.data,
-                Some(&self.datasets),
-            );
+            let node_inputs = self.gather_inputs(node_id, node);
+
+            let result = if discrete::is_stateful(&node.block_type) {
+                discrete::advance(
+                    &node.block_type,
+                    self.state.get(node_id),
+                    &node_inputs,
+                    &node.data,
+                    self.step_size,
+                )
+                .0
+            } else {
+                // FMU export compiles the subgraph feeding it, not just its value.
+                let data = if node.block_type == "fmu.export" {
+                    crate::fmu_export::with_upstream_graph(
+                        node_id,
+                        &node.data,
+                        |id| self.nodes.get(id).cloned(),
+                        |id| {
+                            self.in_adj
+                                .get(id)
+                                .into_iter()
+                                .flatten()
+                                .filter_map(|(edge_id, ..)| self.edges.get(edge_id).cloned())
+                                .collect()
+                        },
+                    )
+                } else {
+                    std::borrow::Cow::Borrowed(&node.data)
+                };
+                expr::with_functions(&self.functions, || {
+                    evaluate_node_with_datasets(
+                        &node.block_type,
+                        &node_inputs,
+                        &data,
+                        Some(&self.datasets),
+                        Some(&self.blobs),
+                    )
+                })
+            };
             evaluated_count += 1;
 
             // Collect trace if enabled.
@@ -538,18 +714,176 @@ impl EngineGraph {
         }
     }
 
+    // ── Discrete-time stepping ───────────────────────────────────────
+
+    /// Set the sample time used by stateful blocks without their own `data.dt`.
+    pub fn set_step_size(&mut self, dt: f64) {
+        if dt > 0.0 && dt.is_finite() && dt.to_bits() != self.step_size.to_bits() {
+            self.step_size = dt;
+            self.mark_stateful_dirty();
+        }
+    }
+
+    /// Current sample time for stateful blocks.
+    pub fn step_size(&self) -> f64 {
+        self.step_size
+    }
+
+    /// State of a stateful node (`None` before its first committed step).
+    pub fn node_state(&self, node_id: &str) -> Option<&NodeState> {
+        self.state.get(node_id)
+    }
+
+    /// Advance every stateful block by one step using the current values of
+    /// its inputs, and mark those blocks dirty. Does not evaluate.
+    ///
+    /// Pending dirty nodes should be evaluated first so the committed inputs
+    /// are fresh; [`EngineGraph::step`] does this for you.
+    pub fn commit_state(&mut self) {
+        let ids: Vec<String> = self
+            .nodes
+            .values()
+            .filter(|n| discrete::is_stateful(&n.block_type))
+            .map(|n| n.id.clone())
+            .collect();
+        for id in &ids {
+            let node = &self.nodes[id];
+            let inputs = self.gather_inputs(id, node);
+            let (out, next) = discrete::advance(
+                &node.block_type,
+                self.state.get(id),
+                &inputs,
+                &node.data,
+                self.step_size,
+            );
+            if !out.is_error() {
+                self.state.insert(id.clone(), next);
+            }
+        }
+        for id in &ids {
+            self.mark_dirty(id);
+        }
+    }
+
+    /// One discrete-time step: evaluate anything pending, commit the state of
+    /// every stateful block, then re-evaluate their downstream cones.
+    ///
+    /// The returned result merges both evaluations.
+    pub fn step(&mut self) -> IncrementalEvalResult {
+        let pending = if self.dirty.is_empty() { None } else { Some(self.evaluate_dirty()) };
+        self.commit_state();
+        let mut result = self.evaluate_dirty();
+        if let Some(mut pre) = pending {
+            for (k, v) in pre.changed_values.drain() {
+                result.changed_values.entry(k).or_insert(v);
+            }
+            pre.diagnostics.append(&mut result.diagnostics);
+            result.diagnostics = pre.diagnostics;
+            result.evaluated_count += pre.evaluated_count;
+        }
+        result
+    }
+
+    /// Discard all discrete-time state so stateful blocks restart from their
+    /// initial conditions. Marks them (and their downstream) dirty.
+    pub fn reset_state(&mut self) {
+        self.state.clear();
+        self.mark_stateful_dirty();
+    }
+
+    fn mark_stateful_dirty(&mut self) {
+        let ids: Vec<String> = self
+            .nodes
+            .values()
+            .filter(|n| discrete::is_stateful(&n.block_type))
+            .map(|n| n.id.clone())
+            .collect();
+        for id in ids {
+            self.mark_dirty(&id);
+        }
+    }
+
     // ── Internal helpers ─────────────────────────────────────────────
 
+    /// Resolve a node's input values from its incoming edges, then apply
+    /// `portOverrides` / `manualValues` from its data.
+    fn gather_inputs(&self, node_id: &str, node: &NodeDef) -> HashMap<String, Value> {
+        let mut node_inputs: HashMap<String, Value> = HashMap::new();
+        if let Some(in_edges) = self.in_adj.get(node_id) {
+            for (_eid, src_id, src_handle, tgt_handle) in in_edges {
+                if let Some(val) = self.values.get(src_id) {
+                    // Table column handles: col_0, col_1, ...
+                    if src_handle.starts_with("col_") {
+                        if let Value::Table { columns: _, rows } = val {
+                            if let Ok(idx) = src_handle[4..].parse::<usize>() {
+                                let col: Vec<f64> = rows
+                                    .iter()
+                                    .map(|row| row.get(idx).copied().unwrap_or(0.0))
+                                    .collect();
+                                node_inputs
+                                    .insert(tgt_handle.clone(), Value::Vector { value: col });
+                                continue;
+                            }
+                        }
+                    }
+                    // Material property handles: prop_rho, prop_E, ...
+                    if src_handle.starts_with("prop_") {
+                        if let Value::Table { columns, rows } = val {
+                            let prop_name = &src_handle[5..];
+                            if let Some(idx) = columns.iter().position(|c| c == prop_name) {
+                                let v = rows.first().and_then(|r| r.get(idx).copied()).unwrap_or(0.0);
+                                node_inputs.insert(tgt_handle.clone(), Value::scalar(v));
+                                continue;
+                            }
+                        }
+                    }
+                    node_inputs.insert(tgt_handle.clone(), val.clone());
+                }
+            }
+        }
+
+        // Apply portOverrides / manualValues.
+        let overrides = node.data.get("portOverrides").and_then(|v| v.as_object());
+        let manuals = node.data.get("manualValues").and_then(|v| v.as_object());
+        if let Some(manuals) = manuals {
+            for (port_id, val) in manuals {
+                if let Some(n) = val.as_f64() {
+                    let is_overridden = overrides
+                        .and_then(|o| o.get(port_id))
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false);
+                    if !node_inputs.contains_key(port_id) || is_overridden {
+                        node_inputs.insert(port_id.clone(), Value::scalar(n));
+                    }
+                }
+            }
+        }
+
+        node_inputs
+    }
+
+    /// Whether edges into `target_id` are cut for ordering purposes
+    /// (target has no direct feedthrough).
+    fn breaks_cycle_into(&self, target_id: &str) -> bool {
+        self.nodes
+            .get(target_id)
+            .map(|n| discrete::breaks_cycles(&n.block_type, &n.data))
+            .unwrap_or(false)
+    }
+
     /// Mark a node and all its downstream descendants as dirty.
     fn mark_dirty(&mut self, node_id: &str) {
         let mut queue: VecDeque<String> = VecDeque::new();
         queue.push_back(node_id.to_string());
         while let Some(id) = queue.pop_front() {
             if self.dirty.insert(id.clone()) {
-                // Only propagate if this is newly dirty.
+                // Only propagate if this is newly dirty. Blocks without direct
+                // feedthrough do not see this step's input change.
                 if let Some(neighbors) = self.out_adj.get(&id) {
                     for (_, target_id, _) in neighbors {
-                        queue.push_back(target_id.clone());
+                        if !self.breaks_cycle_into(target_id) {
+                            queue.push_back(target_id.clone());
+                        }
                     }
                 }
             }
@@ -560,6 +894,9 @@ impl EngineGraph {
     fn prune_downstream(&mut self, node_id: &str) {
         if let Some(neighbors) = self.out_adj.get(node_id).cloned() {
             for (_, target_id, _) in &neighbors {
+                if self.breaks_cycle_into(target_id) {
+                    continue;
+                }
                 // Only prune if all inputs of target are clean (no other dirty parent).
                 let all_parents_clean = self
                     .in_adj
@@ -570,8 +907,10 @@ impl EngineGraph {
                             .all(|(_, src, _, _)| !self.dirty.contains(src))
                     })
                     .unwrap_or(true);
-                if all_parents_clean {
-                    self.dirty.remove(target_id);
+                if all_parents_clean
+                    && !self.pinned.contains(target_id)
+                    && self.dirty.remove(target_id)
+                {
                     self.prune_downstream(target_id);
                 }
             }
@@ -618,7 +957,9 @@ impl EngineGraph {
             in_degree.insert(id.as_str(), 0);
         }
         for edge in self.edges.values() {
-            *in_degree.entry(edge.target.as_str()).or_insert(0) += 1;
+            if !self.breaks_cycle_into(&edge.target) {
+                *in_degree.entry(edge.target.as_str()).or_insert(0) += 1;
+            }
         }
 
         let mut queue: VecDeque<&str> = VecDeque::new();
@@ -635,6 +976,9 @@ impl EngineGraph {
             order.push(id.to_string());
             if let Some(neighbors) = self.out_adj.get(id) {
                 for (_, target_id, _) in neighbors {
+                    if self.breaks_cycle_into(target_id) {
+                        continue;
+                    }
                     if let Some(deg) = remaining.get_mut(target_id.as_str()) {
                         *deg = deg.saturating_sub(1);
                         if *deg == 0 {
@@ -685,7 +1029,9 @@ impl EngineGraph {
             in_degree.insert(id.as_str(), 0);
         }
         for edge in self.edges.values() {
-            *in_degree.entry(edge.target.as_str()).or_insert(0) += 1;
+            if !self.breaks_cycle_into(&edge.target) {
+                *in_degree.entry(edge.target.as_str()).or_insert(0) += 1;
+            }
         }
 
         let mut queue: VecDeque<&str> = VecDeque::new();
@@ -701,6 +1047,9 @@ impl EngineGraph {
             visited += 1;
             if let Some(neighbors) = self.out_adj.get(id) {
                 for (_, target_id, _) in neighbors {
+                    if self.breaks_cycle_into(target_id) {
+                        continue;
+                    }
                     if let Some(deg) = remaining.get_mut(target_id.as_str()) {
                         *deg = deg.saturating_sub(1);
                         if *deg == 0 {
@@ -720,7 +1069,9 @@ impl EngineGraph {
                     in_deg2.insert(id.as_str(), 0);
                 }
                 for edge in self.edges.values() {
-                    *in_deg2.entry(edge.target.as_str()).or_insert(0) += 1;
+                    if !self.breaks_cycle_into(&edge.target) {
+                        *in_deg2.entry(edge.target.as_str()).or_insert(0) += 1;
+                    }
                 }
                 let mut q2: VecDeque<&str> = VecDeque::new();
                 for (&id, &deg) in &in_deg2 {
@@ -732,6 +1083,9 @@ impl EngineGraph {
                     topo_set.insert(id);
                     if let Some(neighbors) = self.out_adj.get(id) {
                         for (_, target_id, _) in neighbors {
+                            if self.breaks_cycle_into(target_id) {
+                                continue;
+                            }
                             if let Some(deg) = in_deg2.get_mut(target_id.as_str()) {
                                 *deg = deg.saturating_sub(1);
                                 if *deg == 0 {
@@ -879,6 +1233,19 @@ fn compute_value_hash(value: &Value) -> u64 {
             display.hash(&mut hasher);
             precision.hash(&mut hasher);
         }
+        Value::Bytes { data, mime } => {
+            data.hash(&mut hasher);
+            mime.hash(&mut hasher);
+        }
+        Value::SparseMatrix { rows, cols, row_ptrs, col_indices, values } => {
+            rows.hash(&mut hasher);
+            cols.hash(&mut hasher);
+            row_ptrs.hash(&mut hasher);
+            col_indices.hash(&mut hasher);
+            for elem in values {
+                elem.to_bits().hash(&mut hasher);
+            }
+        }
     }
     hasher.finish()
 }
@@ -1421,4 +1788,128 @@ mod tests {
         assert!(!prop_diags.is_empty(), "Expected NAN_PROPAGATED diagnostic");
         assert!(prop_diags.iter().any(|d| d.node_id.as_deref() == Some("s")));
     }
+
+    fn data_node(id: &str, block_type: &str, data: serde_json::Value) -> NodeDef {
+        NodeDef {
+            id: id.to_string(),
+            block_type: block_type.to_string(),
+            data: serde_json::from_value(data).unwrap(),
+        }
+    }
+
+    /// Closed P-loop: x = ∫ Kp·(r − x) dt with a forward-Euler integrator.
+    fn closed_loop() -> EngineSnapshotV1 {
+        EngineSnapshotV1 {
+            version: 1,
+            nodes: vec![
+                num_node("r", 1.0),
+                num_node("kp", 2.0),
+                op_node("err", "subtract"),
+                op_node("u", "multiply"),
+                data_node("x", "ctrl.discrete_integrator", serde_json::json!({ "dt": 0.1 })),
+            ],
+            edges: vec![
+                edge("e1", "r", "out", "err", "a"),
+                edge("e2", "x", "out", "err", "b"),
+                edge("e3", "err", "out", "u", "a"),
+                edge("e4", "kp", "out", "u", "b"),
+                edge("e5", "u", "out", "x", "u"),
+            ],
+        }
+    }
+
+    #[test]
+    fn feedback_through_stateful_block_is_not_a_cycle() {
+        let mut g = EngineGraph::new();
+        g.load_snapshot(closed_loop());
+        let first = g.evaluate_dirty();
+        assert!(first.diagnostics.iter().all(|d| d.code != "CYCLE_DETECTED"));
+        assert_eq!(first.changed_values["x"].as_scalar(), Some(0.0));
+        assert!(g.validate_pre_eval(&HashMap::new()).is_empty());
+
+        // x_{k+1} = x_k + 0.1·2·(1 − x_k) = 0.8·x_k + 0.2
+        let r = g.step();
+        assert!((r.changed_values["x"].as_scalar().unwrap() - 0.2).abs() < 1e-12);
+        for _ in 0..200 {
+            g.step();
+        }
+        assert!((g.values()["x"].as_scalar().unwrap() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn reset_state_restores_initial_conditions() {
+        let mut g = EngineGraph::new();
+        g.load_snapshot(closed_loop());
+        g.evaluate_dirty();
+        g.step();
+        g.step();
+        assert!(g.node_state("x").is_some());
+
+        g.reset_state();
+        let r = g.evaluate_dirty();
+        assert_eq!(r.changed_values["x"].as_scalar(), Some(0.0));
+        assert!(g.node_state("x").is_none());
+    }
+
+    #[test]
+    fn cycle_without_stateful_block_still_detected() {
+        let snap = EngineSnapshotV1 {
+            version: 1,
+            nodes: vec![op_node("a", "negate"), op_node("b", "negate")],
+            edges: vec![edge("e1", "a", "out", "b", "a"), edge("e2", "b", "out", "a", "a")],
+        };
+        let mut g = EngineGraph::new();
+        g.load_snapshot(snap);
+        let r = g.evaluate_dirty();
+        assert_eq!(r.diagnostics.iter().filter(|d| d.code == "CYCLE_DETECTED").count(), 2);
+    }
+
+    fn define_node(id: &str, name: &str, params: &[&str], body: &str) -> NodeDef {
+        let mut data = HashMap::new();
+        data.insert("name".to_string(), serde_json::json!(name));
+        data.insert("params".to_string(), serde_json::json!(params));
+        data.insert("body".to_string(), serde_json::json!(body));
+        NodeDef { id: id.to_string(), block_type: "expr.define".to_string(), data }
+    }
+
+    fn formula_node(id: &str, formula: &str) -> NodeDef {
+        let mut data = HashMap::new();
+        data.insert("formula".to_string(), serde_json::json!(formula));
+        NodeDef { id: id.to_string(), block_type: "math_expr".to_string(), data }
+    }
+
+    #[test]
+    fn user_functions_are_graph_wide() {
+        let snap = EngineSnapshotV1 {
+            version: 1,
+            nodes: vec![
+                define_node("d1", "sq", &["x"], "x * x"),
+                define_node("d2", "twice_sq", &["x"], "2 * sq(x)"),
+                num_node("n", 3.0),
+                formula_node("f", "twice_sq(a) + 1"),
+            ],
+            edges: vec![edge("e1", "n", "out", "f", "a")],
+        };
+        let mut g = EngineGraph::new();
+        g.load_snapshot(snap);
+        let r = g.evaluate_dirty();
+        assert_eq!(r.changed_values["f"].as_scalar(), Some(19.0));
+        assert!(matches!(&r.changed_values["d2"], Value::Text { value } if value == "twice_sq(x) = 2 * sq(x)"));
+        assert_eq!(g.functions().len(), 2);
+
+        // Editing a definition re-evaluates its callers.
+        let mut data = HashMap::new();
+        data.insert("name".to_string(), serde_json::json!("sq"));
+        data.insert("params".to_string(), serde_json::json!("x"));
+        data.insert("body".to_string(), serde_json::json!("x * x * x"));
+        g.apply_patch(vec![PatchOp::UpdateNodeData { node_id: "d1".into(), data }]);
+        let r = g.evaluate_dirty();
+        assert_eq!(r.changed_values["f"].as_scalar(), Some(55.0));
+
+        // A second definition of the same name is rejected on its own block.
+        g.apply_patch(vec![PatchOp::AddNode { node: define_node("d3", "sq", &["y"], "y") }]);
+        let r = g.evaluate_dirty();
+        assert!(matches!(r.changed_values["d3"], Value::Error { .. }));
+        assert_eq!(g.values()["f"].as_scalar(), Some(55.0));
+    }
 }
@@ -11,6 +11,9 @@
 //! - [`graph`]    — persistent `EngineGraph` with dirty-tracking and `PatchOp` protocol
 //! - [`eval`]     — stateless full-graph evaluation (Kahn's topological sort)
 //! - [`validate`] — graph validation (version check, dangling edges)
+//! - [`simulation`] — discrete-time simulation loop over a persistent `EngineGraph`
+//! - [`discrete`] — stateful discrete-time blocks (unit delay, integrator, ZOH, …)
+//! - [`project`]  — native project container (snapshot + binary datasets + cached values)
 //! - [`error`]    — error types (`EngineError`, `ErrorCode`)
 //!
 //! # Entry points (called by `engine-wasm`)
@@ -21,25 +24,30 @@
 //! - [`run_set_input`]             — override one node input, incremental eval
 //! - [`run_load_snapshot_with_options`] — load with eval options + progress callback
 //! - [`run_patch_with_options`]    — patch with eval options + progress callback
+//! - [`run_simulation`]            — load snapshot, then step it through a simulation clock
 
 pub mod acausal;
 pub mod cuda;
 pub mod autodiff;
 pub mod fmu_export;
+pub mod fmu_cosim;
 pub mod autodiff_linsolve;
 pub mod custom_vjp;
+pub mod discrete;
 pub mod grad_checkpoint;
 pub mod catalog;
 pub mod compensated;
 pub mod error;
 pub mod eval;
 pub mod expr;
+pub mod fem;
 pub mod graph;
 pub mod integrate;
 pub mod interpolate;
 pub mod linalg;
 pub mod matfile;
 pub mod ml;
+pub mod modal;
 pub mod parquet;
 
 pub mod nn;
@@ -49,13 +57,20 @@ pub mod pinn;
 pub mod ops;
 pub mod optim;
 pub mod precision;
+pub mod project;
+pub mod rational;
 pub mod rng;
 pub mod rootfinding;
 pub mod signal;
+pub mod simulation;
 pub mod sparse;
+pub mod sparse_direct;
+pub mod sparse_io;
 pub mod sparse_solvers;
 pub mod stl;
 pub mod symbolic;
+pub mod symbolic_codegen;
+pub mod symbolic_matrix;
 pub mod units;
 pub mod vehicle;
 pub mod types;
@@ -213,3 +228,28 @@ where
     graph.apply_patch(ops);
     Ok(graph.evaluate_dirty_with_callback(opts, on_progress))
 }
+
+/// Load a snapshot into `graph` and run a discrete-time simulation on it.
+///
+/// Every step after the first is an incremental evaluation of the persistent
+/// graph. See [`simulation`] for the clock / state / recording model.
+pub fn run_simulation<F>(
+    graph: &mut graph::EngineGraph,
+    snapshot_json: &str,
+    config: &simulation::SimulationConfig,
+    on_progress: F,
+) -> Result<simulation::SimulationResult, EngineError>
+where
+    F: FnMut(&simulation::SimProgress) -> EvalSignal,
+{
+    let snapshot: EngineSnapshotV1 = serde_json::from_str(snapshot_json).map_err(|e| {
+        EngineError::new(
+            ErrorCode::InvalidSnapshot,
+            format!("Failed to parse snapshot: {}", e),
+        )
+    })?;
+
+    validate::validate(&snapshot)?;
+    graph.load_snapshot(snapshot);
+    simulation::run_simulation(graph, config, on_progress)
+}
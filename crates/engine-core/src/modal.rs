@@ -0,0 +1,804 @@
+//! Modal and harmonic analysis of linear structures.
+//!
+//! Natural modes solve the generalized symmetric eigenproblem
+//! `K·φ = ω²·M·φ` for stiffness `K` (symmetric positive semi-definite) and
+//! mass `M` (symmetric positive definite). Modes are mass-normalised,
+//! `φᵀ·M·φ = 1`, and sorted by frequency.
+//!
+//! - **Dense** ([`modes_dense`]): Cholesky reduction `M = L·Lᵀ`, then the
+//!   standard problem `L⁻¹·K·L⁻ᵀ·y = λ·y` by Householder tridiagonalisation
+//!   and implicit QL; all `n` modes.
+//! - **Sparse** ([`modes_lanczos`]): Lanczos on the shift-inverted operator
+//!   `(K − σ·M)⁻¹·M` in the `M` inner product with full
+//!   reorthogonalisation, converging first to the modes nearest `σ`. The
+//!   default shift sits just below zero so free–free (rigid-body) modes are
+//!   found as well. Inner solves use ILU(0)-preconditioned CG, or GMRES when
+//!   the shifted matrix is indefinite.
+//!
+//! Frequency response functions come either from the full matrices
+//! ([`frf_direct`]: `H(ω) = (K − ω²M + iωC)⁻¹`) or from modal superposition
+//! with modal damping ratios ([`frf_modal`]). [`participation`] gives modal
+//! participation factors and effective masses for a rigid-body influence
+//! vector.
+//!
+//! Both lumped-parameter matrices (dense, or a diagonal mass vector) and
+//! assembled FEM matrices ([`crate::fem::elasticity::structural_matrices`])
+//! are accepted through [`StructuralMatrix`].
+//!
+//! References: Bathe, "Finite Element Procedures" (2014), ch. 10–11;
+//! Ericsson & Ruhe, "The spectral transformation Lanczos method" (1980).
+
+use crate::rng::Xoshiro256;
+use crate::sparse::{dense_to_csr, sparse_diagonal, CsrMatrix};
+use crate::sparse_solvers::{cg, gmres, Ilu0, SolverConfig};
+use std::collections::HashMap;
+
+/// Problems up to this size use the dense solver by default.
+pub const DENSE_LIMIT: usize = 300;
+
+/// Largest system [`frf_direct`] will factorise at every frequency.
+pub const DIRECT_FRF_LIMIT: usize = 200;
+
+/// Most frequency points the `modal.frf` block evaluates.
+pub const MAX_FRF_POINTS: usize = 100_000;
+
+/// A mass, stiffness or damping matrix.
+#[derive(Debug, Clone)]
+pub enum StructuralMatrix {
+    /// Row-major `n × n`.
+    Dense { n: usize, data: Vec<f64> },
+    /// Diagonal (lumped masses, discrete dampers to ground).
+    Diagonal(Vec<f64>),
+    Sparse(CsrMatrix),
+}
+
+impl StructuralMatrix {
+    pub fn dim(&self) -> usize {
+        match self {
+            StructuralMatrix::Dense { n, .. } => *n,
+            StructuralMatrix::Diagonal(d) => d.len(),
+            StructuralMatrix::Sparse(a) => a.rows,
+        }
+    }
+
+    pub fn mul_vec(&self, x: &[f64]) -> Vec<f64> {
+        match self {
+            StructuralMatrix::Dense { n, data } => {
+                (0..*n).map(|i| data[i * n..(i + 1) * n].iter().zip(x).map(|(a, b)| a * b).sum()).collect()
+            }
+            StructuralMatrix::Diagonal(d) => d.iter().zip(x).map(|(a, b)| a * b).collect(),
+            StructuralMatrix::Sparse(a) => a.mul_vec(x),
+        }
+    }
+
+    /// Row-major dense copy.
+    pub fn to_dense(&self) -> Vec<f64> {
+        let n = self.dim();
+        match self {
+            StructuralMatrix::Dense { data, .. } => data.clone(),
+            StructuralMatrix::Diagonal(d) => {
+                let mut out = vec![0.0; n * n];
+                for (i, v) in d.iter().enumerate() {
+                    out[i * n + i] = *v;
+                }
+                out
+            }
+            StructuralMatrix::Sparse(a) => {
+                let mut out = vec![0.0; n * n];
+                for i in 0..n {
+                    for k in a.row_ptrs[i]..a.row_ptrs[i + 1] {
+                        out[i * n + a.col_indices[k]] += a.values[k];
+                    }
+                }
+                out
+            }
+        }
+    }
+
+    fn to_csr(&self) -> CsrMatrix {
+        match self {
+            StructuralMatrix::Dense { n, data } => dense_to_csr(*n, *n, data, 0.0),
+            StructuralMatrix::Diagonal(d) => sparse_diagonal(d),
+            StructuralMatrix::Sparse(a) => a.clone(),
+        }
+    }
+
+    fn check_symmetric(&self, name: &str) -> Result<(), String> {
+        let coo = self.to_csr().to_coo();
+        let mut entries: HashMap<(usize, usize), f64> = HashMap::new();
+        for ((&i, &j), &v) in coo.row_indices.iter().zip(&coo.col_indices).zip(&coo.values) {
+            *entries.entry((i, j)).or_insert(0.0) += v;
+        }
+        let scale = entries.values().fold(0.0f64, |m, v| m.max(v.abs())).max(1e-300);
+        let asymmetric = entries
+            .iter()
+            .any(|(&(i, j), v)| (v - entries.get(&(j, i)).copied().unwrap_or(0.0)).abs() > 1e-9 * scale);
+        if asymmetric {
+            return Err(format!("{name} matrix must be symmetric"));
+        }
+        Ok(())
+    }
+}
+
+/// Mass-normalised natural modes, sorted by frequency.
+#[derive(Debug, Clone)]
+pub struct Modes {
+    /// Eigenvalues `λ = ω²` (rad²/s²).
+    pub eigenvalues: Vec<f64>,
+    /// Mode shapes, `shapes[i]` belonging to `eigenvalues[i]`.
+    pub shapes: Vec<Vec<f64>>,
+}
+
+impl Modes {
+    /// Circular frequencies `ω = √λ` (negative round-off clamps to 0).
+    pub fn omegas(&self) -> Vec<f64> {
+        self.eigenvalues.iter().map(|l| l.max(0.0).sqrt()).collect()
+    }
+
+    /// Frequencies in Hz.
+    pub fn frequencies_hz(&self) -> Vec<f64> {
+        self.omegas().iter().map(|w| w / (2.0 * std::f64::consts::PI)).collect()
+    }
+}
+
+/// Householder tridiagonalisation and implicit QL of a symmetric matrix
+/// (the EISPACK `tred2`/`tql2` pair). Returns ascending eigenvalues and the
+/// eigenvectors as columns of a row-major matrix.
+pub fn symmetric_eigen(n: usize, a: &[f64]) -> Result<(Vec<f64>, Vec<f64>), String> {
+    if n == 0 {
+        return Ok((Vec::new(), Vec::new()));
+    }
+    let mut v: Vec<Vec<f64>> = (0..n).map(|i| a[i * n..(i + 1) * n].to_vec()).collect();
+    let mut d: Vec<f64> = v[n - 1].clone();
+    let mut e = vec![0.0; n];
+
+    // tred2: reduce to tridiagonal form, accumulating the transformations.
+    for i in (1..n).rev() {
+        let scale: f64 = d[..i].iter().map(|x| x.abs()).sum();
+        let mut h = 0.0;
+        if scale == 0.0 {
+            e[i] = d[i - 1];
+            for j in 0..i {
+                d[j] = v[i - 1][j];
+                v[i][j] = 0.0;
+                v[j][i] = 0.0;
+            }
+        } else {
+            for dk in d.iter_mut().take(i) {
+                *dk /= scale;
+                h += *dk * *dk;
+            }
+            let mut f = d[i - 1];
+            let mut g = if f > 0.0 { -h.sqrt() } else { h.sqrt() };
+            e[i] = scale * g;
+            h -= f * g;
+            d[i - 1] = f - g;
+            e[..i].iter_mut().for_each(|x| *x = 0.0);
+            for j in 0..i {
+                f = d[j];
+                v[j][i] = f;
+                g = e[j] + v[j][j] * f;
+                for k in j + 1..i {
+                    g += v[k][j] * d[k];
+                    e[k] += v[k][j] * f;
+                }
+                e[j] = g;
+            }
+            f = 0.0;
+            for j in 0..i {
+                e[j] /= h;
+                f += e[j] * d[j];
+            }
+            let hh = f / (h + h);
+            for j in 0..i {
+                e[j] -= hh * d[j];
+            }
+            for j in 0..i {
+                let (f, g) = (d[j], e[j]);
+                for k in j..i {
+                    v[k][j] -= f * e[k] + g * d[k];
+                }
+                d[j] = v[i - 1][j];
+                v[i][j] = 0.0;
+            }
+        }
+        d[i] = h;
+    }
+    for i in 0..n - 1 {
+        v[n - 1][i] = v[i][i];
+        v[i][i] = 1.0;
+        let h = d[i + 1];
+        if h != 0.0 {
+            for k in 0..=i {
+                d[k] = v[k][i + 1] / h;
+            }
+            for j in 0..=i {
+                let g: f64 = (0..=i).map(|k| v[k][i + 1] * v[k][j]).sum();
+                for k in 0..=i {
+                    v[k][j] -= g * d[k];
+                }
+            }
+        }
+        for row in v.iter_mut().take(i + 1) {
+            row[i + 1] = 0.0;
+        }
+    }
+    for j in 0..n {
+        d[j] = v[n - 1][j];
+        v[n - 1][j] = 0.0;
+    }
+    v[n - 1][n - 1] = 1.0;
+
+    // tql2: implicit QL iterations on the tridiagonal matrix.
+    for i in 1..n {
+        e[i - 1] = e[i];
+    }
+    e[n - 1] = 0.0;
+    let (mut f, mut tst1) = (0.0f64, 0.0f64);
+    let eps = f64::EPSILON;
+    for l in 0..n {
+        tst1 = tst1.max(d[l].abs() + e[l].abs());
+        let mut m = l;
+        while m < n && e[m].abs() > eps * tst1 {
+            m += 1;
+        }
+        if m > l {
+            let mut iterations = 0;
+            loop {
+                iterations += 1;
+                if iterations > 60 || !e[l].is_finite() {
+                    return Err("symmetric eigensolver did not converge".to_string());
+                }
+                let mut g = d[l];
+                let mut p = (d[l + 1] - g) / (2.0 * e[l]);
+                let mut r = p.hypot(1.0);
+                if p < 0.0 {
+                    r = -r;
+                }
+                d[l] = e[l] / (p + r);
+                d[l + 1] = e[l] * (p + r);
+                let dl1 = d[l + 1];
+                let mut h = g - d[l];
+                for di in d.iter_mut().skip(l + 2) {
+                    *di -= h;
+                }
+                f += h;
+                p = d[m];
+                let (mut c, mut c2, mut c3) = (1.0, 1.0, 1.0);
+                let el1 = e[l + 1];
+                let (mut s, mut s2) = (0.0, 0.0);
+                for i in (l..m).rev() {
+                    c3 = c2;
+                    c2 = c;
+                    s2 = s;
+                    g = c * e[i];
+                    h = c * p;
+                    r = p.hypot(e[i]);
+                    e[i + 1] = s * r;
+                    s = e[i] / r;
+                    c = p / r;
+                    p = c * d[i] - s * g;
+                    d[i + 1] = h + s * (c * g + s * d[i]);
+                    for row in v.iter_mut() {
+                        h = row[i + 1];
+                        row[i + 1] = s * row[i] + c * h;
+                        row[i] = c * row[i] - s * h;
+                    }
+                }
+                p = -s * s2 * c3 * el1 * e[l] / dl1;
+                e[l] = s * p;
+                d[l] = c * p;
+                if e[l].abs() <= eps * tst1 {
+                    break;
+                }
+            }
+        }
+        d[l] += f;
+        e[l] = 0.0;
+    }
+
+    // Sort ascending.
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| d[i].total_cmp(&d[j]));
+    let values = order.iter().map(|&k| d[k]).collect();
+    let mut vectors = vec![0.0; n * n];
+    for (col, &k) in order.iter().enumerate() {
+        for row in 0..n {
+            vectors[row * n + col] = v[row][k];
+        }
+    }
+    Ok((values, vectors))
+}
+
+fn check_pair(k: &StructuralMatrix, m: &StructuralMatrix) -> Result<usize, String> {
+    let n = k.dim();
+    if m.dim() != n {
+        return Err(format!("stiffness is {n}×{n} but mass is {0}×{0}", m.dim()));
+    }
+    if n == 0 {
+        return Err("empty matrices".to_string());
+    }
+    k.check_symmetric("stiffness")?;
+    m.check_symmetric("mass")?;
+    Ok(n)
+}
+
+/// All modes by Cholesky reduction (`O(n³)`).
+pub fn modes_dense(k: &StructuralMatrix, m: &StructuralMatrix) -> Result<Modes, String> {
+    let n = check_pair(k, m)?;
+    let l = crate::linalg::cholesky(n, &m.to_dense())
+        .ok_or("mass matrix is not positive definite (massless degrees of freedom must be condensed out first)")?;
+    let kd = k.to_dense();
+
+    // Forward substitution L·x = b on each column of a row-major matrix.
+    let forward = |b: &[f64]| -> Vec<f64> {
+        let mut x = b.to_vec();
+        for col in 0..n {
+            for i in 0..n {
+                let s: f64 = (0..i).map(|j| l[i * n + j] * x[j * n + col]).sum();
+                x[i * n + col] = (x[i * n + col] - s) / l[i * n + i];
+            }
+        }
+        x
+    };
+    // C = L⁻¹·K·L⁻ᵀ = (L⁻¹·(L⁻¹·K)ᵀ)ᵀ; K is symmetric so C is too.
+    let x = forward(&kd);
+    let xt: Vec<f64> = (0..n * n).map(|idx| x[(idx % n) * n + idx / n]).collect();
+    let y = forward(&xt);
+    let c: Vec<f64> = (0..n * n).map(|idx| 0.5 * (y[idx] + y[(idx % n) * n + idx / n])).collect();
+    let (eigenvalues, vectors) = symmetric_eigen(n, &c)?;
+
+    // φ = L⁻ᵀ·y by back substitution.
+    let shapes = (0..n)
+        .map(|col| {
+            let mut phi: Vec<f64> = (0..n).map(|row| vectors[row * n + col]).collect();
+            for i in (0..n).rev() {
+                let s: f64 = (i + 1..n).map(|j| l[j * n + i] * phi[j]).sum();
+                phi[i] = (phi[i] - s) / l[i * n + i];
+            }
+            phi
+        })
+        .collect();
+    Ok(Modes { eigenvalues, shapes })
+}
+
+/// `(K − σM)⁻¹` applied by preconditioned iterative solves.
+struct ShiftedSolver {
+    a: CsrMatrix,
+    pc: Ilu0,
+    config: SolverConfig,
+}
+
+impl ShiftedSolver {
+    fn new(k: &StructuralMatrix, m: &StructuralMatrix, shift: f64) -> Self {
+        let (kc, mc) = (k.to_csr(), m.to_csr());
+        let mut coo = kc.to_coo();
+        let mm = mc.to_coo();
+        for ((&i, &j), &v) in mm.row_indices.iter().zip(&mm.col_indices).zip(&mm.values) {
+            coo.push(i, j, -shift * v);
+        }
+        let a = coo.to_csr();
+        let pc = Ilu0::new(&a);
+        let config = SolverConfig { max_iter: 20 * a.rows + 1000, tol: 1e-13 };
+        ShiftedSolver { a, pc, config }
+    }
+
+    fn solve(&self, b: &[f64]) -> Result<Vec<f64>, String> {
+        let r = cg(&self.a, b, &self.config, Some(&self.pc));
+        if r.converged {
+            return Ok(r.x);
+        }
+        let r = gmres(&self.a, b, &self.config, 100, Some(&self.pc));
+        if r.converged || r.residual_norm < 1e-9 {
+            Ok(r.x)
+        } else {
+            Err(format!("shift-invert solve did not converge (residual {:.2e}); try another shift", r.residual_norm))
+        }
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// The `count` modes nearest `shift` (default: just below zero, i.e. the
+/// lowest modes) by shift-invert Lanczos.
+pub fn modes_lanczos(
+    k: &StructuralMatrix,
+    m: &StructuralMatrix,
+    count: usize,
+    shift: Option<f64>,
+    seed: u64,
+) -> Result<Modes, String> {
+    let n = check_pair(k, m)?;
+    let count = count.clamp(1, n);
+    let sigma = shift.unwrap_or_else(|| {
+        let (kd, md) = (k.to_csr().diagonal(), m.to_csr().diagonal());
+        -1e-4 * kd.iter().sum::<f64>().abs() / md.iter().sum::<f64>().abs().max(1e-300)
+    });
+    let op = ShiftedSolver::new(k, m, sigma);
+    let mut rng = Xoshiro256::new(seed);
+
+    // M-orthonormal Lanczos basis q_j (with M·q_j cached), tridiagonal α, β.
+    let mut q: Vec<Vec<f64>> = Vec::new();
+    let mut mq: Vec<Vec<f64>> = Vec::new();
+    let (mut alpha, mut beta): (Vec<f64>, Vec<f64>) = (Vec::new(), Vec::new());
+    let orthogonalise = |w: &mut Vec<f64>, q: &[Vec<f64>], mq: &[Vec<f64>]| {
+        for _ in 0..2 {
+            for (qi, mqi) in q.iter().zip(mq) {
+                let c = dot(mqi, w);
+                w.iter_mut().zip(qi).for_each(|(wk, qk)| *wk -= c * qk);
+            }
+        }
+    };
+    let start = |q: &[Vec<f64>], mq: &[Vec<f64>], rng: &mut Xoshiro256| -> Option<Vec<f64>> {
+        for _ in 0..5 {
+            let mut w: Vec<f64> = (0..n).map(|_| rng.next_f64() - 0.5).collect();
+            orthogonalise(&mut w, q, mq);
+            let norm = dot(&w, &m.mul_vec(&w)).sqrt();
+            if norm > 1e-10 {
+                return Some(w.iter().map(|x| x / norm).collect());
+            }
+        }
+        None
+    };
+
+    let mut next = start(&q, &mq, &mut rng).ok_or("could not start the Lanczos iteration")?;
+    let tol = 1e-10;
+    loop {
+        let j = q.len();
+        let mqj = m.mul_vec(&next);
+        q.push(next);
+        mq.push(mqj);
+        let mut w = op.solve(&mq[j])?;
+        alpha.push(dot(&mq[j], &w));
+        orthogonalise(&mut w, &q, &mq);
+        let b = dot(&w, &m.mul_vec(&w)).max(0.0).sqrt();
+
+        let steps = q.len();
+        let (theta, s) = tridiagonal_eigen(&alpha, &beta)?;
+        // Ritz values of the shift-inverted operator: largest |θ| are nearest σ.
+        let mut order: Vec<usize> = (0..steps).collect();
+        order.sort_by(|&a, &c| theta[c].abs().total_cmp(&theta[a].abs()));
+        let converged = steps >= count
+            && order[..count].iter().all(|&i| (b * s[(steps - 1) * steps + i]).abs() <= tol * theta[i].abs());
+        if converged || steps == n {
+            let mut pairs: Vec<(f64, Vec<f64>)> = order[..count.min(steps)]
+                .iter()
+                .map(|&i| {
+                    let mut phi = vec![0.0; n];
+                    for (r, qr) in q.iter().enumerate() {
+                        phi.iter_mut().zip(qr).for_each(|(p, x)| *p += s[r * steps + i] * x);
+                    }
+                    let norm = dot(&phi, &m.mul_vec(&phi)).sqrt();
+                    phi.iter_mut().for_each(|p| *p /= norm);
+                    (sigma + 1.0 / theta[i], phi)
+                })
+                .collect();
+            pairs.sort_by(|a, c| a.0.total_cmp(&c.0));
+            let (eigenvalues, shapes) = pairs.into_iter().unzip();
+            return Ok(Modes { eigenvalues, shapes });
+        }
+        next = if b > 1e-10 * alpha[j].abs().max(1e-300) {
+            beta.push(b);
+            w.iter().map(|x| x / b).collect()
+        } else {
+            // Invariant subspace found: restart with a fresh orthogonal vector.
+            beta.push(0.0);
+            start(&q, &mq, &mut rng).ok_or("Lanczos breakdown")?
+        };
+    }
+}
+
+/// Eigenpairs of the symmetric tridiagonal matrix with diagonal `alpha` and
+/// off-diagonal `beta`.
+fn tridiagonal_eigen(alpha: &[f64], beta: &[f64]) -> Result<(Vec<f64>, Vec<f64>), String> {
+    let n = alpha.len();
+    let mut t = vec![0.0; n * n];
+    for i in 0..n {
+        t[i * n + i] = alpha[i];
+        if i + 1 < n {
+            t[i * n + i + 1] = beta[i];
+            t[(i + 1) * n + i] = beta[i];
+        }
+    }
+    symmetric_eigen(n, &t)
+}
+
+/// Lowest `count` modes (all when `count` is 0): dense up to
+/// [`DENSE_LIMIT`] degrees of freedom, Lanczos beyond.
+pub fn solve_modes(k: &StructuralMatrix, m: &StructuralMatrix, count: usize) -> Result<Modes, String> {
+    let n = k.dim();
+    if n <= DENSE_LIMIT || count == 0 || count >= n {
+        let mut modes = modes_dense(k, m)?;
+        if count > 0 {
+            modes.eigenvalues.truncate(count);
+            modes.shapes.truncate(count);
+        }
+        Ok(modes)
+    } else {
+        modes_lanczos(k, m, count, None, 42)
+    }
+}
+
+/// Modal participation for one excitation direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Participation {
+    /// `Γ = φᵀ·M·r`.
+    pub factor: f64,
+    /// `Γ²`: the mass that moves with the mode.
+    pub effective_mass: f64,
+    /// Effective mass over the total `rᵀ·M·r`.
+    pub mass_fraction: f64,
+}
+
+/// Participation of each mode for rigid-body influence vector `r` (1 on the
+/// degrees of freedom moving in the excitation direction, 0 elsewhere).
+pub fn participation(modes: &Modes, m: &StructuralMatrix, influence: &[f64]) -> Vec<Participation> {
+    let mr = m.mul_vec(influence);
+    let total = dot(influence, &mr);
+    modes
+        .shapes
+        .iter()
+        .map(|phi| {
+            let factor = dot(phi, &mr);
+            let effective_mass = factor * factor;
+            let mass_fraction = if total.abs() > 0.0 { effective_mass / total } else { 0.0 };
+            Participation { factor, effective_mass, mass_fraction }
+        })
+        .collect()
+}
+
+/// Which response quantity an FRF returns per unit force.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum FrfKind {
+    /// Displacement.
+    #[default]
+    Receptance,
+    /// Velocity.
+    Mobility,
+    /// Acceleration.
+    Accelerance,
+}
+
+/// Parse "mobility"/"velocity", "accelerance"/"inertance"/"acceleration"
+/// (else receptance).
+pub fn parse_frf_kind(s: &str) -> FrfKind {
+    match s.trim().to_lowercase().as_str() {
+        "mobility" | "velocity" => FrfKind::Mobility,
+        "accelerance" | "inertance" | "acceleration" => FrfKind::Accelerance,
+        _ => FrfKind::Receptance,
+    }
+}
+
+/// Complex number as `(re, im)`.
+pub type Complex = (f64, f64);
+
+fn cmul(a: Complex, b: Complex) -> Complex {
+    (a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0)
+}
+
+fn cdiv(a: Complex, b: Complex) -> Complex {
+    let d = b.0 * b.0 + b.1 * b.1;
+    ((a.0 * b.0 + a.1 * b.1) / d, (a.1 * b.0 - a.0 * b.1) / d)
+}
+
+fn apply_kind(h: Complex, omega: f64, kind: FrfKind) -> Complex {
+    match kind {
+        FrfKind::Receptance => h,
+        FrfKind::Mobility => cmul(h, (0.0, omega)),
+        FrfKind::Accelerance => cmul(h, (-omega * omega, 0.0)),
+    }
+}
+
+/// Solve the complex system `A·x = b` by Gaussian elimination with partial pivoting.
+pub(crate) fn complex_solve(n: usize, mut a: Vec<Complex>, mut b: Vec<Complex>) -> Option<Vec<Complex>> {
+    let abs2 = |z: Complex| z.0 * z.0 + z.1 * z.1;
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| abs2(a[i * n + col]).total_cmp(&abs2(a[j * n + col])))?;
+        if abs2(a[pivot * n + col]) < 1e-300 {
+            return None;
+        }
+        if pivot != col {
+            for j in 0..n {
+                a.swap(col * n + j, pivot * n + j);
+            }
+            b.swap(col, pivot);
+        }
+        for row in col + 1..n {
+            let f = cdiv(a[row * n + col], a[col * n + col]);
+            if f == (0.0, 0.0) {
+                continue;
+            }
+            for j in col..n {
+                let t = cmul(f, a[col * n + j]);
+                a[row * n + j] = (a[row * n + j].0 - t.0, a[row * n + j].1 - t.1);
+            }
+            let t = cmul(f, b[col]);
+            b[row] = (b[row].0 - t.0, b[row].1 - t.1);
+        }
+    }
+    let mut x = vec![(0.0, 0.0); n];
+    for i in (0..n).rev() {
+        let mut s = b[i];
+        for j in i + 1..n {
+            let t = cmul(a[i * n + j], x[j]);
+            s = (s.0 - t.0, s.1 - t.1);
+        }
+        x[i] = cdiv(s, a[i * n + i]);
+    }
+    Some(x)
+}
+
+/// FRF from degree of freedom `input` to `output` at each frequency (Hz),
+/// from the full matrices: `H = (K − ω²M + iωC)⁻¹`.
+pub fn frf_direct(
+    k: &StructuralMatrix,
+    m: &StructuralMatrix,
+    c: Option<&StructuralMatrix>,
+    input: usize,
+    output: usize,
+    freqs_hz: &[f64],
+    kind: FrfKind,
+) -> Result<Vec<Complex>, String> {
+    let n = check_pair(k, m)?;
+    if n > DIRECT_FRF_LIMIT {
+        return Err(format!("{n} degrees of freedom is too many for the direct method (limit {DIRECT_FRF_LIMIT}); use modal superposition"));
+    }
+    if input >= n || output >= n {
+        return Err(format!("input/output degree of freedom must be below {n}"));
+    }
+    if let Some(c) = c {
+        if c.dim() != n {
+            return Err(format!("damping is {0}×{0} but stiffness is {n}×{n}", c.dim()));
+        }
+    }
+    let (kd, md) = (k.to_dense(), m.to_dense());
+    let cd = c.map(StructuralMatrix::to_dense).unwrap_or_else(|| vec![0.0; n * n]);
+    freqs_hz
+        .iter()
+        .map(|&f| {
+            let omega = 2.0 * std::f64::consts::PI * f;
+            let a: Vec<Complex> = (0..n * n).map(|i| (kd[i] - omega * omega * md[i], omega * cd[i])).collect();
+            let mut b = vec![(0.0, 0.0); n];
+            b[input] = (1.0, 0.0);
+            let x = complex_solve(n, a, b).ok_or(format!("dynamic stiffness is singular at {f} Hz (undamped resonance)"))?;
+            Ok(apply_kind(x[output], omega, kind))
+        })
+        .collect()
+}
+
+/// FRF by modal superposition with a damping ratio per mode (the last ratio
+/// repeats for higher modes):
+/// `H = Σ φ_out·φ_in / (ω_r² − ω² + 2iζ_r·ω_r·ω)`.
+pub fn frf_modal(modes: &Modes, zeta: &[f64], input: usize, output: usize, freqs_hz: &[f64], kind: FrfKind) -> Result<Vec<Complex>, String> {
+    let n = modes.shapes.first().map_or(0, Vec::len);
+    if input >= n || output >= n {
+        return Err(format!("input/output degree of freedom must be below {n}"));
+    }
+    let omegas = modes.omegas();
+    Ok(freqs_hz
+        .iter()
+        .map(|&f| {
+            let omega = 2.0 * std::f64::consts::PI * f;
+            let h = modes.shapes.iter().zip(&omegas).enumerate().fold((0.0, 0.0), |acc, (r, (phi, &wr))| {
+                let z = zeta.get(r).or(zeta.last()).copied().unwrap_or(0.0);
+                let term = cdiv((phi[output] * phi[input], 0.0), (wr * wr - omega * omega, 2.0 * z * wr * omega));
+                (acc.0 + term.0, acc.1 + term.1)
+            });
+            apply_kind(h, omega, kind)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sparse::CooMatrix;
+
+    /// Fixed–fixed chain of `n` unit masses joined by `n + 1` springs of stiffness `k`.
+    fn chain(n: usize, k: f64, free: bool) -> (StructuralMatrix, StructuralMatrix) {
+        let mut coo = CooMatrix::new(n, n);
+        for i in 0..n {
+            let ends = if free { usize::from(i > 0) + usize::from(i + 1 < n) } else { 2 };
+            coo.push(i, i, ends as f64 * k);
+            if i + 1 < n {
+                coo.push(i, i + 1, -k);
+                coo.push(i + 1, i, -k);
+            }
+        }
+        (StructuralMatrix::Sparse(coo.to_csr()), StructuralMatrix::Diagonal(vec![1.0; n]))
+    }
+
+    #[test]
+    fn dense_two_dof_system() {
+        // m = diag(2, 1), k = [[3, -1], [-1, 1]] → ω² = 1/2 and 2.
+        let k = StructuralMatrix::Dense { n: 2, data: vec![3.0, -1.0, -1.0, 1.0] };
+        let m = StructuralMatrix::Diagonal(vec![2.0, 1.0]);
+        let modes = modes_dense(&k, &m).unwrap();
+        assert!((modes.eigenvalues[0] - 0.5).abs() < 1e-12 && (modes.eigenvalues[1] - 2.0).abs() < 1e-12);
+        for (lambda, phi) in modes.eigenvalues.iter().zip(&modes.shapes) {
+            let (kp, mp) = (k.mul_vec(phi), m.mul_vec(phi));
+            assert!((dot(phi, &mp) - 1.0).abs() < 1e-12);
+            assert!(kp.iter().zip(&mp).all(|(a, b)| (a - lambda * b).abs() < 1e-12));
+        }
+    }
+
+    #[test]
+    fn lanczos_matches_the_chain_frequencies() {
+        let (n, k) = (400, 100.0);
+        let (ks, ms) = chain(n, k, false);
+        let modes = modes_lanczos(&ks, &ms, 6, None, 7).unwrap();
+        for (j, omega) in modes.omegas().iter().enumerate() {
+            let exact = 2.0 * k.sqrt() * ((j + 1) as f64 * std::f64::consts::PI / (2.0 * (n + 1) as f64)).sin();
+            assert!((omega / exact - 1.0).abs() < 1e-8, "mode {j}: {omega} vs {exact}");
+        }
+        // Agrees with the dense solver.
+        let (ks, ms) = chain(60, k, false);
+        let dense = modes_dense(&ks, &ms).unwrap();
+        let sparse = modes_lanczos(&ks, &ms, 4, None, 1).unwrap();
+        for i in 0..4 {
+            assert!((dense.eigenvalues[i] - sparse.eigenvalues[i]).abs() < 1e-8 * dense.eigenvalues[i]);
+        }
+    }
+
+    #[test]
+    fn free_free_chain_has_a_rigid_body_mode() {
+        let (ks, ms) = chain(30, 10.0, true);
+        let modes = modes_lanczos(&ks, &ms, 3, None, 3).unwrap();
+        assert!(modes.eigenvalues[0].abs() < 1e-8);
+        // The rigid mode carries all the mass in its direction.
+        let p = participation(&modes, &ms, &vec![1.0; 30]);
+        assert!((p[0].mass_fraction - 1.0).abs() < 1e-8 && p[1].mass_fraction < 1e-8);
+    }
+
+    #[test]
+    fn effective_masses_sum_to_the_total() {
+        let (ks, ms) = chain(12, 5.0, false);
+        let modes = modes_dense(&ks, &ms).unwrap();
+        let p = participation(&modes, &ms, &vec![1.0; 12]);
+        let total: f64 = p.iter().map(|x| x.effective_mass).sum();
+        assert!((total - 12.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn frf_direct_and_modal_agree() {
+        // Single DOF: peak receptance at resonance is 1/(2ζk).
+        let (k1, m1, zeta): (f64, f64, f64) = (400.0, 1.0, 0.02);
+        let c1 = 2.0 * zeta * (k1 * m1).sqrt();
+        let f_n = (k1 / m1).sqrt() / (2.0 * std::f64::consts::PI);
+        let h = frf_direct(
+            &StructuralMatrix::Diagonal(vec![k1]),
+            &StructuralMatrix::Diagonal(vec![m1]),
+            Some(&StructuralMatrix::Diagonal(vec![c1])),
+            0,
+            0,
+            &[f_n],
+            FrfKind::Receptance,
+        )
+        .unwrap();
+        assert!(((h[0].0.hypot(h[0].1)) - 1.0 / (2.0 * zeta * k1)).abs() < 1e-12);
+
+        // Three DOF with mass-proportional damping C = 2ζω_r per mode ⇔ C = αM:
+        // direct and modal superposition give the same FRF.
+        let (ks, ms) = chain(3, 50.0, false);
+        let alpha = 0.3;
+        let modes = modes_dense(&ks, &ms).unwrap();
+        let zetas: Vec<f64> = modes.omegas().iter().map(|w| alpha / (2.0 * w)).collect();
+        let freqs: Vec<f64> = (1..40).map(|i| i as f64 * 0.1).collect();
+        let direct = frf_direct(&ks, &ms, Some(&StructuralMatrix::Diagonal(vec![alpha; 3])), 0, 2, &freqs, FrfKind::Accelerance).unwrap();
+        let modal = frf_modal(&modes, &zetas, 0, 2, &freqs, FrfKind::Accelerance).unwrap();
+        for (a, b) in direct.iter().zip(&modal) {
+            assert!((a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn rejects_bad_matrices() {
+        let k = StructuralMatrix::Dense { n: 2, data: vec![1.0, 2.0, 0.0, 1.0] };
+        assert!(modes_dense(&k, &StructuralMatrix::Diagonal(vec![1.0, 1.0])).is_err());
+        let k = StructuralMatrix::Diagonal(vec![1.0, 1.0]);
+        assert!(modes_dense(&k, &StructuralMatrix::Diagonal(vec![1.0, 0.0])).is_err());
+        assert!(modes_dense(&k, &StructuralMatrix::Diagonal(vec![1.0])).is_err());
+    }
+}
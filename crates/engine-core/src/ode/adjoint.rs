@@ -290,7 +290,7 @@ mod tests {
             state_names: vec!["y".to_string()],
             params,
         };
-        let solver_cfg = OdeSolverConfig { t_start: 0.0, t_end: 0.5, dt: 0.01, tolerance: 1e-6, max_steps: 1000 };
+        let solver_cfg = OdeSolverConfig { t_start: 0.0, t_end: 0.5, dt: 0.01, tolerance: 1e-6, max_steps: 1000, ..Default::default() };
         let cfg = AdjointConfig {
             system,
             param_names: vec!["k".to_string()],
@@ -316,7 +316,7 @@ mod tests {
             state_names: vec!["y".to_string()],
             params,
         };
-        let solver_cfg = OdeSolverConfig { t_start: 0.0, t_end: 0.4, dt: 0.01, tolerance: 1e-6, max_steps: 1000 };
+        let solver_cfg = OdeSolverConfig { t_start: 0.0, t_end: 0.4, dt: 0.01, tolerance: 1e-6, max_steps: 1000, ..Default::default() };
         let cfg_full = AdjointConfig {
             system: system.clone(),
             param_names: vec!["k".to_string()],
@@ -5,12 +5,13 @@
 //!
 //! y_{n+k} = h·β·f(t_{n+k}, y_{n+k}) + Σ α_j·y_{n+j}
 //!
-//! The implicit equation is solved by Newton iteration at each step.
-//! Jacobian is approximated by finite differences.
+//! The implicit equation is solved by simplified Newton iteration at each
+//! step, with an exact Jacobian from forward-mode AD ([`super::jacobian`]).
 //!
 //! Reference: Hairer & Wanner, "Solving ODEs II" (2010).
 
-use super::rk4::eval_rhs_pub;
+use super::dense::{no_observer, AcceptedStep, DenseRecorder, Interpolant, StepControl};
+use super::jacobian::{norm, CompiledRhs, ImplicitStats, JacobianCache};
 use super::types::{OdeResult, OdeSolverConfig, OdeSystem};
 
 /// BDF coefficients for orders 1-5.
@@ -58,12 +59,40 @@ static BDF: [BdfCoeffs; 5] = [
 ///
 /// The order is set in `system.params` as "bdf_order" (default: 2).
 /// Step size control uses a simple error estimate based on the difference
-/// between orders k and k-1.
+/// between orders k and k-1. Output at [`OdeSolverConfig::output`] times is
+/// interpolated by the polynomial through the backward-difference history.
 pub fn solve_bdf(
     system: &OdeSystem,
     y0: &[f64],
     config: &OdeSolverConfig,
 ) -> OdeResult {
+    solve_bdf_with_stats(system, y0, config).0
+}
+
+/// [`solve_bdf`], also returning how much work the Newton iterations did.
+///
+/// Each step solves `G(y) = y - β·h·f(t, y) - Σ α_j·y_j = 0` by simplified
+/// Newton with the iteration matrix `I - β·h·J` factored once per step. `J` is
+/// exact (forward-mode AD of the compiled equations) and is reused across
+/// steps while the iteration contracts fast (see [`JacobianCache`]). Large
+/// sparse systems factor `I - β·h·J` incompletely and solve it with GMRES;
+/// `system.params["sparse_jacobian"]` overrides the choice (see [`CompiledRhs`]).
+pub fn solve_bdf_with_stats(
+    system: &OdeSystem,
+    y0: &[f64],
+    config: &OdeSolverConfig,
+) -> (OdeResult, ImplicitStats) {
+    integrate_bdf(system, y0, config, &mut no_observer)
+}
+
+/// [`solve_bdf_with_stats`], offering every accepted step to `on_step`,
+/// which may end the run inside the step (see [`super::event`]).
+pub fn integrate_bdf(
+    system: &OdeSystem,
+    y0: &[f64],
+    config: &OdeSolverConfig,
+    on_step: &mut dyn FnMut(&AcceptedStep) -> StepControl,
+) -> (OdeResult, ImplicitStats) {
     let n = system.equations.len();
     assert_eq!(n, y0.len(), "Equations must match initial state dimension");
 
@@ -72,15 +101,18 @@ pub fn solve_bdf(
         .unwrap_or(2.0)
         .clamp(1.0, 5.0) as usize;
     let tol = config.tolerance;
-    let max_newton = 50usize;
+    let max_newton = 10usize;
     let max_steps = config.max_steps;
 
+    let rhs = CompiledRhs::new(system);
+    let mut jac = JacobianCache::new();
+    let mut stats = ImplicitStats::default();
+
     // History buffer: ring of (time, state) pairs, newest first.
     let mut history: Vec<(f64, Vec<f64>)> = Vec::with_capacity(order + 1);
     history.push((config.t_start, y0.to_vec()));
 
-    let mut t_out = vec![config.t_start];
-    let mut states_out = vec![y0.to_vec()];
+    let mut out = DenseRecorder::new(config, y0);
 
     // Bootstrap with BDF-1 (Backward Euler) for the first `order-1` steps,
     // then switch to the requested order.
@@ -114,66 +146,93 @@ pub fn solve_bdf(
             }).sum::<f64>()
         }).collect();
 
-        // Newton iteration to solve:
+        // Iteration matrix J_G = I - β·h·J_f, factored once for all Newton steps.
+        let gamma = coeffs.beta * h;
+        stats.lu_factorizations += 1;
+        let lu = jac.get(&rhs, t_new, &y_prev).shifted(gamma);
+
+        // Simplified Newton iteration on
         //   G(y) = y - β·h·f(t_new, y) - history_sum = 0
         let mut converged = false;
-        for _iter in 0..max_newton {
-            let f_new = eval_rhs_pub(system, t_new, &y_new);
-
-            // Residual G(y) = y - β·h·f - hist
-            let residual: Vec<f64> = (0..n)
-                .map(|i| y_new[i] - coeffs.beta * h * f_new[i] - history_sum[i])
-                .collect();
-
-            let res_norm: f64 = residual.iter().map(|r| r * r).sum::<f64>().sqrt();
-            if res_norm < tol * (1.0 + y_new.iter().map(|v| v * v).sum::<f64>().sqrt()) {
-                converged = true;
-                break;
-            }
-
-            // Jacobian of G: J_G ≈ I - β·h·J_f   (finite differences)
-            let eps = 1e-7_f64;
-            let mut jac_g = vec![vec![0.0; n]; n];
-            for col in 0..n {
-                let mut y_pert = y_new.clone();
-                y_pert[col] += eps;
-                let f_pert = eval_rhs_pub(system, t_new, &y_pert);
-                for row in 0..n {
-                    let df_row = (f_pert[row] - f_new[row]) / eps;
-                    jac_g[row][col] = (if row == col { 1.0 } else { 0.0 }) - coeffs.beta * h * df_row;
+        let mut theta = 0.0_f64;
+        if let Some(lu) = &lu {
+            let mut prev_delta: Option<f64> = None;
+            for _iter in 0..max_newton {
+                let f_new = rhs.eval(t_new, &y_new);
+
+                // Residual G(y) = y - β·h·f - hist
+                let residual: Vec<f64> = (0..n)
+                    .map(|i| y_new[i] - gamma * f_new[i] - history_sum[i])
+                    .collect();
+
+                if norm(&residual) < tol * (1.0 + norm(&y_new)) {
+                    converged = true;
+                    break;
                 }
-            }
 
-            // Solve J_G · Δy = -residual via Gauss-Jordan with partial pivoting
-            let delta = gauss_jordan_solve(&jac_g, &residual);
+                // Update: y_new -= J_G⁻¹·G(y)
+                stats.newton_iterations += 1;
+                let Some(delta) = lu.solve(&residual) else {
+                    break;
+                };
+                for i in 0..n {
+                    y_new[i] -= delta[i];
+                }
 
-            // Update: y_new -= delta  (note sign: residual = G(y), delta = J⁻¹·G)
-            for i in 0..n {
-                y_new[i] -= delta[i];
+                // Contraction rate θ = ‖Δ_k‖ / ‖Δ_{k-1}‖; θ ≥ 1 means divergence.
+                let d = norm(&delta);
+                if let Some(prev) = prev_delta {
+                    theta = if prev > 0.0 { d / prev } else { 0.0 };
+                    if theta >= 1.0 || !theta.is_finite() {
+                        break;
+                    }
+                }
+                prev_delta = Some(d);
             }
         }
 
         if !converged {
+            stats.rejected_steps += 1;
+            // A stale Jacobian gets one refresh before the step is shrunk.
+            if !jac.is_fresh() {
+                jac.invalidate();
+                continue;
+            }
             // Step failed — halve step and retry (simple step control)
             h *= 0.5;
             if h < 1e-14 { break; }
             continue;
         }
+        jac.accept(theta);
 
         // Accept step
+        let t0 = t;
         t = t_new;
         history.insert(0, (t, y_new.clone()));
         if history.len() > 5 {
             history.pop();
         }
+        // Dense output: the polynomial through the points the formula used.
+        let interp = || {
+            let nodes = &history[..(current_order + 1).min(history.len())];
+            Interpolant::Lagrange {
+                ts: nodes.iter().map(|(t, _)| *t).collect(),
+                ys: nodes.iter().map(|(_, y)| y.clone()).collect(),
+            }
+        };
+        let control = on_step(&AcceptedStep::new(t0, t, &y_prev, &y_new, &interp));
+        if let StepControl::Stop { t: t_stop, y: y_stop } = control {
+            out.step(t_stop, &y_stop, interp);
+            steps += 1;
+            break;
+        }
+        out.step(t, &y_new, interp);
 
         // Ramp up order toward requested order
         if current_order < order && history.len() > current_order {
             current_order = (current_order + 1).min(order);
         }
 
-        t_out.push(t);
-        states_out.push(y_new);
         steps += 1;
 
         // Simple step size control: if Newton converged quickly, try to grow h
@@ -183,65 +242,22 @@ pub fn solve_bdf(
     let mut column_names = vec!["t".to_string()];
     column_names.extend(system.state_names.clone());
 
-    OdeResult {
+    stats.rhs_evals = rhs.evals();
+    stats.jacobian_evals = jac.evaluations();
+    let (t_out, states_out) = out.finish();
+    let result = OdeResult {
         t: t_out,
         states: states_out,
         column_names,
         steps,
-    }
-}
-
-/// Solve Ax = b via Gauss-Jordan elimination with partial pivoting.
-/// Returns x. If singular, returns zeros.
-fn gauss_jordan_solve(a: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
-    let n = a.len();
-    if n == 0 { return vec![]; }
-
-    // Build augmented matrix [A|b]
-    let mut mat: Vec<Vec<f64>> = a
-        .iter()
-        .zip(b.iter())
-        .map(|(row, &bi)| {
-            let mut r = row.clone();
-            r.push(bi);
-            r
-        })
-        .collect();
-
-    for col in 0..n {
-        // Partial pivoting
-        let mut max_row = col;
-        let mut max_val = mat[col][col].abs();
-        for row in (col + 1)..n {
-            let v = mat[row][col].abs();
-            if v > max_val { max_val = v; max_row = row; }
-        }
-        mat.swap(col, max_row);
-
-        let pivot = mat[col][col];
-        if pivot.abs() < 1e-15 { continue; }
-
-        // Normalise pivot row
-        for j in col..=n {
-            mat[col][j] /= pivot;
-        }
-
-        // Eliminate column
-        for row in 0..n {
-            if row == col { continue; }
-            let factor = mat[row][col];
-            for j in col..=n {
-                mat[row][j] -= factor * mat[col][j];
-            }
-        }
-    }
-
-    (0..n).map(|i| mat[i][n]).collect()
+    };
+    (result, stats)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ode::test_fixtures::{mol_heat, robertson};
     use std::collections::HashMap;
 
     fn make_system(eq: &[&str], names: &[&str]) -> OdeSystem {
@@ -253,7 +269,7 @@ mod tests {
     }
 
     fn config(t_end: f64, dt: f64) -> OdeSolverConfig {
-        OdeSolverConfig { t_start: 0.0, t_end, dt, tolerance: 1e-6, max_steps: 100_000 }
+        OdeSolverConfig { t_start: 0.0, t_end, dt, tolerance: 1e-6, max_steps: 100_000, ..Default::default() }
     }
 
     #[test]
@@ -314,4 +330,75 @@ mod tests {
         assert!((final_y - 0.001).abs() < 0.001,
             "BDF stiff: expected ~0.001, got {final_y:.6}");
     }
+
+    #[test]
+    fn bdf_robertson_converges_with_exact_jacobian() {
+        // Reference at t = 0.4: y = (0.985172, 3.3864e-5, 0.0147940)
+        let mut sys = robertson();
+        sys.params.insert("bdf_order".to_string(), 2.0);
+        let (res, stats) = solve_bdf_with_stats(&sys, &[1.0, 0.0, 0.0], &config(0.4, 1e-3));
+        assert!((res.t.last().unwrap() - 0.4).abs() < 1e-12);
+        let y = res.states.last().unwrap();
+        assert!((y[0] - 0.985172).abs() < 1e-3, "y0 = {}", y[0]);
+        assert!((y[1] - 3.3864e-5).abs() < 2e-6, "y1 = {}", y[1]);
+        assert!((y.iter().sum::<f64>() - 1.0).abs() < 1e-9, "mass is conserved");
+        assert!(stats.jacobian_evals > 0 && stats.jacobian_evals <= stats.lu_factorizations);
+    }
+
+    #[test]
+    fn bdf_reuses_jacobian_for_linear_systems() {
+        // Newton converges in one iteration, so J is evaluated once for the run.
+        let sys = make_system(&["-1000*y0 + y1", "-y1"], &["a", "b"]);
+        let (res, stats) = solve_bdf_with_stats(&sys, &[1.0, 1.0], &config(0.5, 0.01));
+        assert!(res.steps > 10);
+        assert_eq!(stats.jacobian_evals, 1);
+        assert_eq!(stats.rejected_steps, 0);
+    }
+
+    /// Stiff method-of-lines heat equation `u_t = u_xx - u³` on `n` interior points.
+    #[test]
+    fn bdf_sparse_jacobian_matches_dense() {
+        let (sys, u0) = mol_heat(120, "- {y}^3");
+        let mut dense = sys.clone();
+        dense.params.insert("sparse_jacobian".to_string(), 0.0);
+        let (rs, ss) = solve_bdf_with_stats(&sys, &u0, &config(0.05, 1e-3));
+        let (rd, sd) = solve_bdf_with_stats(&dense, &u0, &config(0.05, 1e-3));
+        assert_eq!(rs.steps, rd.steps);
+        assert_eq!(ss.jacobian_evals, sd.jacobian_evals);
+        for (a, b) in rs.states.last().unwrap().iter().zip(rd.states.last().unwrap()) {
+            assert!((a - b).abs() < 1e-8, "{a} vs {b}");
+        }
+        // u ≈ e^{-π²t}·sin(πx) decays; the cubic term only speeds that up.
+        let mid = rs.states.last().unwrap()[60];
+        assert!(mid > 0.0 && mid < (-std::f64::consts::PI.powi(2) * 0.05).exp());
+    }
+
+    #[test]
+    fn bdf_handles_thousands_of_grid_points() {
+        let (sys, u0) = mol_heat(2000, "- {y}^3");
+        let (res, stats) = solve_bdf_with_stats(&sys, &u0, &config(0.01, 1e-3));
+        assert!((res.t.last().unwrap() - 0.01).abs() < 1e-12);
+        assert_eq!(stats.rejected_steps, 0);
+        let u = res.states.last().unwrap();
+        assert!(u.iter().all(|v| v.is_finite() && (0.0..1.0).contains(v)));
+    }
+
+    #[test]
+    fn bdf_output_interval_interpolates_history() {
+        use crate::ode::OutputTimes;
+        let mut sys = make_system(&["-y0"], &["y"]);
+        sys.params.insert("bdf_order".to_string(), 3.0);
+        let cfg = OdeSolverConfig { output: OutputTimes::Interval(0.1), ..config(1.0, 0.013) };
+        let stepped = solve_bdf(&sys, &[1.0], &config(1.0, 0.013));
+        let res = solve_bdf(&sys, &[1.0], &cfg);
+        assert_eq!(res.t.len(), 11);
+        assert_eq!(res.steps, stepped.steps);
+        // Interpolation adds nothing to the integration error itself.
+        let max_err = |t: &[f64], y: &[Vec<f64>]| {
+            t.iter().zip(y).map(|(t, y)| (y[0] - (-t).exp()).abs()).fold(0.0, f64::max)
+        };
+        let step_err = max_err(&stepped.t, &stepped.states);
+        assert!(max_err(&res.t, &res.states) <= 1.01 * step_err, "step error {step_err}");
+        assert!((res.t[10] - 1.0).abs() < 1e-12);
+    }
 }
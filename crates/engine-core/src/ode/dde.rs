@@ -0,0 +1,355 @@
+//! Delay differential equations (DDEs).
+//!
+//! Equations use the usual ODE syntax plus lagged states written as calls:
+//! `yK(expr)` is state `K` at time `expr`, so `-y0(t - tau)` is a constant
+//! delay and `y0(t - 1 - y0^2)` a state-dependent one. Lags may nest.
+//! Before `t_start` the solution is given by history expressions in `t`
+//! (default: constant `y0`).
+//!
+//! # Method
+//!
+//! Dormand–Prince RK4(5) with the method of steps: every accepted step keeps
+//! its continuous extension ([`Interpolant::DormandPrince`]), and lagged
+//! states are read from those interpolants by binary search. Constant delays
+//! also limit the step to the shortest delay, so every stage only looks back
+//! into accepted steps.
+//!
+//! A history that does not join the initial state smoothly makes the
+//! solution's derivatives jump at `t_start + d`, `t_start + 2d`, … for each
+//! constant delay `d`. These breakpoints (sums of up to [`BREAKPOINT_ORDER`]
+//! delays) are tracked and every step ends on them, so error control never
+//! straddles a discontinuity. State-dependent delays are left to the error
+//! control; a lag that reaches into the current step is extrapolated from the
+//! last accepted step.
+//!
+//! Reference: Bellen & Zennaro, "Numerical Methods for Delay Differential
+//! Equations" (2003); Shampine & Thompson, "Solving DDEs in MATLAB" (2001).
+
+use super::dense::{DenseRecorder, Interpolant};
+use super::rk45::dopri_step;
+use super::types::{OdeResult, OdeSolverConfig};
+use crate::expr::{compile, CompiledExpr};
+use std::collections::HashMap;
+
+/// Highest number of delays summed when propagating breakpoints.
+pub const BREAKPOINT_ORDER: usize = 5;
+
+/// A system of delay differential equations.
+#[derive(Debug, Clone)]
+pub struct DdeSystem {
+    /// Right-hand sides `dy_i/dt`, with lagged states `yK(expr)`.
+    pub equations: Vec<String>,
+    pub state_names: Vec<String>,
+    pub params: HashMap<String, f64>,
+    /// History `y_i(t)` for `t < t_start`, as expressions in `t`; empty means
+    /// the constant initial state.
+    pub history: Vec<String>,
+}
+
+/// A lagged state `y_component(at)`.
+struct Lag {
+    component: usize,
+    at: CompiledExpr,
+}
+
+/// Replace each lagged reference `yK(expr)` in `src` by a variable `__lagJ`,
+/// appending the lag to `lags` (inner lags first, so evaluation in order
+/// sees every nested value it needs).
+fn extract_lags(src: &str, n: usize, lags: &mut Vec<(usize, String)>) -> Result<String, String> {
+    let bytes = src.as_bytes();
+    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let mut out = String::with_capacity(src.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'y' && (i == 0 || !is_ident(bytes[i - 1])) {
+            let digits_end = i + 1 + bytes[i + 1..].iter().take_while(|b| b.is_ascii_digit()).count();
+            let open = digits_end + bytes[digits_end..].iter().take_while(|&&b| b == b' ').count();
+            let ends_ident = digits_end == bytes.len() || !is_ident(bytes[digits_end]);
+            if digits_end > i + 1 && ends_ident && bytes.get(open) == Some(&b'(') {
+                let mut depth = 0usize;
+                let close = (open..bytes.len())
+                    .find(|&k| {
+                        match bytes[k] {
+                            b'(' => depth += 1,
+                            b')' => depth -= 1,
+                            _ => {}
+                        }
+                        depth == 0
+                    })
+                    .ok_or_else(|| format!("unbalanced parentheses in '{src}'"))?;
+                let component: usize = src[i + 1..digits_end].parse().map_err(|e| format!("{e}"))?;
+                if component >= n {
+                    return Err(format!("lagged state y{component} does not exist ({n} states)"));
+                }
+                let at = extract_lags(&src[open + 1..close], n, lags)?;
+                lags.push((component, at));
+                out.push_str(&format!("__lag{}", lags.len() - 1));
+                i = close + 1;
+                continue;
+            }
+        }
+        let ch = src[i..].chars().next().expect("index on a char boundary");
+        out.push(ch);
+        i += ch.len_utf8();
+    }
+    Ok(out)
+}
+
+/// The solution so far: history before `t0`, then accepted steps.
+struct Past<'a> {
+    t0: f64,
+    y0: &'a [f64],
+    history: &'a [CompiledExpr],
+    params: &'a HashMap<String, f64>,
+    /// `(t_lo, t_hi, interpolant)` per accepted step, in time order.
+    steps: Vec<(f64, f64, Interpolant)>,
+}
+
+impl Past<'_> {
+    /// `y_k(tau)` while the solver sits at `(t, y)`.
+    fn value(&self, k: usize, tau: f64, t: f64, y: &[f64]) -> f64 {
+        if tau >= t {
+            return y[k];
+        }
+        if tau < self.t0 {
+            return match self.history.get(k) {
+                Some(h) => {
+                    let mut vars = self.params.clone();
+                    vars.insert("t".to_string(), tau);
+                    h.eval(&vars).unwrap_or(f64::NAN)
+                }
+                None => self.y0[k],
+            };
+        }
+        match self.steps.last() {
+            // Inside the step being taken: extrapolate the last accepted one.
+            Some((_, t_hi, p)) if tau > *t_hi => p.eval(tau)[k],
+            Some(_) => {
+                let i = self.steps.partition_point(|(_, t_hi, _)| *t_hi < tau);
+                self.steps[i].2.eval(tau)[k]
+            }
+            // First step: linear between the initial state and the stage.
+            None => {
+                let s = (tau - self.t0) / (t - self.t0);
+                self.y0[k] + s * (y[k] - self.y0[k])
+            }
+        }
+    }
+}
+
+/// Times in `(t0, t_end]` where derivative jumps propagate: `t0` plus sums
+/// of up to [`BREAKPOINT_ORDER`] constant delays.
+fn breakpoints(t0: f64, t_end: f64, delays: &[f64]) -> Vec<f64> {
+    let mut points = vec![t0];
+    let mut frontier = vec![t0];
+    for _ in 0..BREAKPOINT_ORDER {
+        let mut next: Vec<f64> = frontier
+            .iter()
+            .flat_map(|b| delays.iter().map(move |d| b + d))
+            .filter(|&b| b <= t_end)
+            .collect();
+        next.sort_by(f64::total_cmp);
+        next.dedup_by(|a, b| (*a - *b).abs() <= 1e-12 * a.abs().max(1.0));
+        points.extend(&next);
+        frontier = next;
+        if points.len() > 10_000 {
+            break;
+        }
+    }
+    points.retain(|&b| b > t0);
+    points.sort_by(f64::total_cmp);
+    points.dedup_by(|a, b| (*a - *b).abs() <= 1e-12 * a.abs().max(1.0));
+    points
+}
+
+/// Solve a DDE system with Dormand–Prince and dense-output lag evaluation.
+pub fn solve_dde(system: &DdeSystem, y0: &[f64], config: &OdeSolverConfig) -> Result<OdeResult, String> {
+    let n = system.equations.len();
+    if n != y0.len() {
+        return Err(format!("{n} equations but {} initial values", y0.len()));
+    }
+    if !system.history.is_empty() && system.history.len() != n {
+        return Err(format!("{} history expressions for {n} states", system.history.len()));
+    }
+
+    let mut lag_sources = Vec::new();
+    let mut equations = Vec::with_capacity(n);
+    for eq in &system.equations {
+        let rewritten = extract_lags(eq, n, &mut lag_sources)?;
+        equations.push(compile(&rewritten).map_err(|e| format!("equation '{eq}': {e}"))?);
+    }
+    let lags = lag_sources
+        .into_iter()
+        .map(|(component, at)| {
+            compile(&at).map(|at| Lag { component, at }).map_err(|e| format!("lag time '{at}': {e}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let history = system
+        .history
+        .iter()
+        .map(|h| compile(h).map_err(|e| format!("history '{h}': {e}")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Delays that depend on neither the state nor other lags, and do not
+    // change with t, are constant.
+    let t0 = config.t_start;
+    let vars_at = |t: f64, y: &[f64]| {
+        let mut vars = system.params.clone();
+        vars.insert("t".to_string(), t);
+        for (i, &yi) in y.iter().enumerate() {
+            vars.insert(format!("y{i}"), yi);
+        }
+        vars
+    };
+    let mut constant_delays: Vec<f64> = lags
+        .iter()
+        .filter(|lag| {
+            let deps = lag.at.dependencies();
+            deps.vectors.is_empty()
+                && deps.scalars.iter().all(|v| v == "t" || system.params.contains_key(v))
+        })
+        .filter_map(|lag| {
+            let delay_at = |t: f64| t - lag.at.eval(&vars_at(t, y0)).unwrap_or(f64::NAN);
+            let d = delay_at(t0);
+            let steady = (delay_at(t0 + 1.0) - d).abs() <= 1e-12 * d.abs().max(1.0);
+            (d > 0.0 && steady).then_some(d)
+        })
+        .collect();
+    constant_delays.sort_by(f64::total_cmp);
+    constant_delays.dedup();
+    let h_max = constant_delays.first().copied().unwrap_or(f64::INFINITY);
+    let breaks = breakpoints(t0, config.t_end, &constant_delays);
+
+    let mut past = Past { t0, y0, history: &history, params: &system.params, steps: Vec::new() };
+
+    let tol = config.tolerance;
+    let mut t = t0;
+    let mut y = y0.to_vec();
+    let mut h = config.dt.min(h_max);
+    let mut next_break = 0usize;
+    let mut out = DenseRecorder::new(config, &y);
+    let mut steps = 0;
+
+    while t < config.t_end && steps < config.max_steps {
+        while next_break < breaks.len() && breaks[next_break] <= t + 1e-12 * t.abs().max(1.0) {
+            next_break += 1;
+        }
+        let stop = breaks.get(next_break).copied().unwrap_or(config.t_end).min(config.t_end);
+        h = h.min(h_max);
+        let lands_on_stop = t + h >= stop;
+        if lands_on_stop {
+            h = stop - t;
+        }
+        if h < 1e-15 {
+            break;
+        }
+
+        let rhs = |ts: f64, ys: &[f64]| -> Vec<f64> {
+            let mut vars = vars_at(ts, ys);
+            for (j, lag) in lags.iter().enumerate() {
+                let tau = lag.at.eval(&vars).unwrap_or(f64::NAN);
+                vars.insert(format!("__lag{j}"), past.value(lag.component, tau, ts, ys));
+            }
+            equations.iter().map(|e| e.eval(&vars).unwrap_or(f64::NAN)).collect()
+        };
+        let (y_new, err, k) = dopri_step(rhs, t, &y, h);
+
+        if err <= tol || h <= 1e-15 {
+            let interp = Interpolant::DormandPrince { t0: t, h, y0: y.clone(), k };
+            let t_new = if lands_on_stop { stop } else { t + h };
+            out.step(t_new, &y_new, || interp.clone());
+            past.steps.push((t, t_new, interp));
+            t = t_new;
+            y = y_new;
+            steps += 1;
+        }
+
+        let factor = if err > 0.0 { 0.9 * (tol / err).powf(0.2) } else { 5.0 };
+        h *= factor.clamp(0.2, 5.0);
+    }
+
+    let mut column_names = vec!["t".to_string()];
+    column_names.extend(system.state_names.iter().cloned());
+    let (t_out, states_out) = out.finish();
+    Ok(OdeResult { t: t_out, states: states_out, column_names, steps })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn system(equations: &[&str], history: &[&str], params: &[(&str, f64)]) -> DdeSystem {
+        DdeSystem {
+            equations: equations.iter().map(|s| s.to_string()).collect(),
+            state_names: (0..equations.len()).map(|i| format!("y{i}")).collect(),
+            params: params.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+            history: history.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    fn config(t_end: f64) -> OdeSolverConfig {
+        OdeSolverConfig { t_end, dt: 0.1, tolerance: 1e-9, max_steps: 100_000, ..Default::default() }
+    }
+
+    #[test]
+    fn rewrites_nested_lags() {
+        let mut lags = Vec::new();
+        let out = extract_lags("-y0(t - y1(t - 1)) + y1 * y10", 11, &mut lags).unwrap();
+        assert_eq!(out, "-__lag1 + y1 * y10");
+        assert_eq!(lags, [(1, "t - 1".to_string()), (0, "t - __lag0".to_string())]);
+        assert!(extract_lags("y3(t - 1)", 2, &mut Vec::new()).is_err());
+    }
+
+    /// y' = -y(t - 1), y = 1 for t ≤ 0. Method of steps gives
+    /// y = 1 - t on [0, 1] and y = 1 - t + (t - 1)²/2 on [1, 2].
+    #[test]
+    fn constant_delay_matches_method_of_steps() {
+        let sys = system(&["-y0(t - 1)"], &[], &[]);
+        let r = solve_dde(&sys, &[1.0], &config(2.0)).unwrap();
+        for (t, s) in r.t.iter().zip(&r.states) {
+            let exact = if *t <= 1.0 { 1.0 - t } else { 1.0 - t + 0.5 * (t - 1.0).powi(2) };
+            assert!((s[0] - exact).abs() < 1e-9, "y({t}) = {} vs {exact}", s[0]);
+        }
+        // Steps land on the breakpoints t = 1 and t = 2.
+        assert!(r.t.contains(&1.0) && *r.t.last().unwrap() == 2.0);
+    }
+
+    /// y' = e^τ·y(t - τ) with history e^t is solved by y = e^t.
+    #[test]
+    fn history_function_and_parameter_delay() {
+        let sys = system(&["exp(tau) * y0(t - tau)"], &["exp(t)"], &[("tau", 0.7)]);
+        let cfg = OdeSolverConfig {
+            output: crate::ode::OutputTimes::Times(vec![0.5, 1.0, 2.0, 3.0]),
+            ..config(3.0)
+        };
+        let r = solve_dde(&sys, &[1.0], &cfg).unwrap();
+        assert_eq!(r.t, [0.5, 1.0, 2.0, 3.0]);
+        for (t, s) in r.t.iter().zip(&r.states) {
+            assert!((s[0] / t.exp() - 1.0).abs() < 1e-7, "y({t}) = {}", s[0]);
+        }
+    }
+
+    /// State-dependent delay: y' = y(y - 1)... reduces to y' = 1 with y = t
+    /// when the lag time is t - 1 + (y - t), i.e. always t - 1 on the exact
+    /// solution, with history y = t.
+    #[test]
+    fn state_dependent_delay_follows_exact_solution() {
+        let sys = system(&["y0(y0 - 1) - t + 2"], &["t"], &[]);
+        let r = solve_dde(&sys, &[0.0], &config(3.0)).unwrap();
+        let last = r.states.last().unwrap()[0];
+        assert!((last - 3.0).abs() < 1e-7, "y(3) = {last}");
+    }
+
+    #[test]
+    fn mackey_glass_stays_bounded() {
+        let sys = system(
+            &["beta * y0(t - tau) / (1 + y0(t - tau)^10) - gamma * y0"],
+            &["0.5"],
+            &[("beta", 0.2), ("gamma", 0.1), ("tau", 17.0)],
+        );
+        let cfg = OdeSolverConfig { dt: 1.0, tolerance: 1e-6, ..config(300.0) };
+        let r = solve_dde(&sys, &[0.5], &cfg).unwrap();
+        assert_eq!(*r.t.last().unwrap(), 300.0);
+        assert!(r.states.iter().all(|s| s[0] > 0.0 && s[0] < 1.5));
+    }
+}
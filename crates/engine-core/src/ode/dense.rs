@@ -0,0 +1,296 @@
+//! Dense output: the solution between accepted steps.
+//!
+//! Each solver describes its last step by an [`Interpolant`] — the
+//! Dormand–Prince continuous extension for RK45, the collocation polynomial
+//! for Radau IIA, the polynomial through the backward-difference history for
+//! BDF, and a cubic Hermite for fixed-step RK4. [`DenseRecorder`] samples it
+//! at the times requested by [`OutputTimes`](super::types::OutputTimes), so
+//! outputs land exactly on those times while the step size stays whatever
+//! error control chose. Event location ([`super::event`]) sees each
+//! [`AcceptedStep`] and finds crossings on the same interpolant with
+//! [`locate_root`].
+//!
+//! Reference: Hairer, Nørsett & Wanner, "Solving ODEs I" (1993), §II.6.
+
+use super::types::OdeSolverConfig;
+
+/// Dormand–Prince dense-output coefficients: the weight of stage `k_i` at
+/// `θ ∈ [0, 1]` is `Σ_j P[i][j]·θ^(j+1)` (Shampine, 1986; as in `dopri5`).
+const DP_P: [[f64; 4]; 7] = [
+    [1.0, -8048581381.0 / 2820520608.0, 8663915743.0 / 2820520608.0, -12715105075.0 / 11282082432.0],
+    [0.0, 0.0, 0.0, 0.0],
+    [0.0, 131558114200.0 / 32700410799.0, -68118460800.0 / 10900136933.0, 87487479700.0 / 32700410799.0],
+    [0.0, -1754552775.0 / 470086768.0, 14199869525.0 / 1410260304.0, -10690763975.0 / 1880347072.0],
+    [0.0, 127303824393.0 / 49829197408.0, -318862633887.0 / 49829197408.0, 701980252875.0 / 199316789632.0],
+    [0.0, -282668133.0 / 205662961.0, 2019193451.0 / 616988883.0, -1453857185.0 / 822651844.0],
+    [0.0, 40617522.0 / 29380423.0, -110615467.0 / 29380423.0, 69997945.0 / 29380423.0],
+];
+
+/// Continuous approximation of the solution over one step `[t0, t0 + h]`.
+#[derive(Debug, Clone)]
+pub enum Interpolant {
+    /// Fourth-order continuous extension of a Dormand–Prince step with
+    /// stages `k[0..7]` (the seventh is `f(t0 + h, y1)`).
+    DormandPrince { t0: f64, h: f64, y0: Vec<f64>, k: [Vec<f64>; 7] },
+    /// Cubic Hermite through `(t0, y0, f0)` and `(t0 + h, y1, f1)`.
+    Hermite { t0: f64, h: f64, y0: Vec<f64>, f0: Vec<f64>, y1: Vec<f64>, f1: Vec<f64> },
+    /// Polynomial through the nodes `(ts[i], ys[i])`.
+    Lagrange { ts: Vec<f64>, ys: Vec<Vec<f64>> },
+}
+
+impl Interpolant {
+    pub fn eval(&self, t: f64) -> Vec<f64> {
+        match self {
+            Interpolant::DormandPrince { t0, h, y0, k } => {
+                let theta = (t - t0) / h;
+                let powers = [theta, theta * theta, theta.powi(3), theta.powi(4)];
+                let w: Vec<f64> = DP_P
+                    .iter()
+                    .map(|row| row.iter().zip(&powers).map(|(p, q)| p * q).sum())
+                    .collect();
+                (0..y0.len())
+                    .map(|i| y0[i] + h * (0..7).map(|s| w[s] * k[s][i]).sum::<f64>())
+                    .collect()
+            }
+            Interpolant::Hermite { t0, h, y0, f0, y1, f1 } => {
+                let s = (t - t0) / h;
+                let h00 = (1.0 + 2.0 * s) * (1.0 - s) * (1.0 - s);
+                let h10 = s * (1.0 - s) * (1.0 - s);
+                let h01 = s * s * (3.0 - 2.0 * s);
+                let h11 = s * s * (s - 1.0);
+                (0..y0.len())
+                    .map(|i| h00 * y0[i] + h10 * h * f0[i] + h01 * y1[i] + h11 * h * f1[i])
+                    .collect()
+            }
+            Interpolant::Lagrange { ts, ys } => {
+                let mut out = vec![0.0; ys.first().map_or(0, Vec::len)];
+                for (j, (tj, yj)) in ts.iter().zip(ys).enumerate() {
+                    let l: f64 = ts
+                        .iter()
+                        .enumerate()
+                        .filter(|&(m, _)| m != j)
+                        .map(|(_, tm)| (t - tm) / (tj - tm))
+                        .product();
+                    for (o, y) in out.iter_mut().zip(yj) {
+                        *o += l * y;
+                    }
+                }
+                out
+            }
+        }
+    }
+}
+
+/// A step the solver has accepted, offered to a step observer.
+pub struct AcceptedStep<'a> {
+    pub t0: f64,
+    pub t1: f64,
+    pub y0: &'a [f64],
+    pub y1: &'a [f64],
+    interpolant: &'a dyn Fn() -> Interpolant,
+}
+
+impl<'a> AcceptedStep<'a> {
+    pub fn new(
+        t0: f64,
+        t1: f64,
+        y0: &'a [f64],
+        y1: &'a [f64],
+        interpolant: &'a dyn Fn() -> Interpolant,
+    ) -> Self {
+        AcceptedStep { t0, t1, y0, y1, interpolant }
+    }
+
+    /// The step's continuous extension (built on demand).
+    pub fn interpolant(&self) -> Interpolant {
+        (self.interpolant)()
+    }
+}
+
+/// What a step observer wants the solver to do next.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepControl {
+    Continue,
+    /// End the run at `t` (inside the last step) with state `y`.
+    Stop { t: f64, y: Vec<f64> },
+}
+
+/// Observer that lets every step through; used by the plain `solve_*` entry points.
+pub fn no_observer(_: &AcceptedStep) -> StepControl {
+    StepControl::Continue
+}
+
+/// Collects solver output at every accepted step, or only at requested times.
+pub struct DenseRecorder {
+    requested: Option<Vec<f64>>,
+    next: usize,
+    t: Vec<f64>,
+    states: Vec<Vec<f64>>,
+}
+
+impl DenseRecorder {
+    /// Start recording a run from `(config.t_start, y0)`.
+    ///
+    /// An output request that [`OutputTimes::times`](super::OutputTimes::times)
+    /// rejects records every step; the block evaluators report that error
+    /// before solving.
+    pub fn new(config: &OdeSolverConfig, y0: &[f64]) -> Self {
+        let requested = config.output.times(config.t_start, config.t_end).unwrap_or(None);
+        let mut rec = DenseRecorder { requested, next: 0, t: Vec::new(), states: Vec::new() };
+        match &rec.requested {
+            None => {
+                rec.t.push(config.t_start);
+                rec.states.push(y0.to_vec());
+            }
+            Some(times) => {
+                while rec.next < times.len() && times[rec.next] <= config.t_start {
+                    rec.t.push(times[rec.next]);
+                    rec.states.push(y0.to_vec());
+                    rec.next += 1;
+                }
+            }
+        }
+        rec
+    }
+
+    /// Whether only requested times are recorded.
+    pub fn is_dense(&self) -> bool {
+        self.requested.is_some()
+    }
+
+    /// Record an accepted step ending at `(t_new, y_new)`. `interpolant` is
+    /// only built when a requested time falls inside the step.
+    pub fn step(&mut self, t_new: f64, y_new: &[f64], interpolant: impl FnOnce() -> Interpolant) {
+        let Some(times) = &self.requested else {
+            self.t.push(t_new);
+            self.states.push(y_new.to_vec());
+            return;
+        };
+        // The last step lands on t_end only up to rounding.
+        let reach = t_new + 1e-12 * t_new.abs().max(1.0);
+        if self.next >= times.len() || times[self.next] > reach {
+            return;
+        }
+        let p = interpolant();
+        while self.next < times.len() && times[self.next] <= reach {
+            let ts = times[self.next];
+            self.t.push(ts);
+            self.states.push(if ts == t_new { y_new.to_vec() } else { p.eval(ts) });
+            self.next += 1;
+        }
+    }
+
+    /// The recorded `(t, states)`.
+    pub fn finish(self) -> (Vec<f64>, Vec<Vec<f64>>) {
+        (self.t, self.states)
+    }
+}
+
+/// Time in `[t_lo, t_hi]` where `g` crosses zero, given `g_lo·g_hi ≤ 0`, to
+/// within `tol`. Uses the Illinois variant of regula falsi, which converges
+/// superlinearly on the smooth functions an interpolant produces.
+pub fn locate_root(
+    g: impl Fn(f64) -> f64,
+    mut t_lo: f64,
+    mut t_hi: f64,
+    mut g_lo: f64,
+    mut g_hi: f64,
+    tol: f64,
+) -> f64 {
+    if g_lo == 0.0 {
+        return t_lo;
+    }
+    if g_hi == 0.0 {
+        return t_hi;
+    }
+    let mut side = 0i8;
+    for _ in 0..200 {
+        if (t_hi - t_lo).abs() <= tol {
+            break;
+        }
+        let mut t_mid = (t_lo * g_hi - t_hi * g_lo) / (g_hi - g_lo);
+        if !t_mid.is_finite() || t_mid <= t_lo || t_mid >= t_hi {
+            t_mid = 0.5 * (t_lo + t_hi);
+        }
+        let g_mid = g(t_mid);
+        if g_mid == 0.0 {
+            return t_mid;
+        }
+        if g_mid.is_nan() {
+            // No information: fall back to plain bisection on the left half.
+            t_hi = t_mid;
+            continue;
+        }
+        if (g_mid < 0.0) == (g_lo < 0.0) {
+            t_lo = t_mid;
+            g_lo = g_mid;
+            if side == -1 {
+                g_hi *= 0.5;
+            }
+            side = -1;
+        } else {
+            t_hi = t_mid;
+            g_hi = g_mid;
+            if side == 1 {
+                g_lo *= 0.5;
+            }
+            side = 1;
+        }
+    }
+    0.5 * (t_lo + t_hi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::types::OutputTimes;
+
+    #[test]
+    fn interpolants_reproduce_polynomials() {
+        // y = t³ - t, y' = 3t² - 1 on [1, 1.5]
+        let y = |t: f64| t.powi(3) - t;
+        let f = |t: f64| 3.0 * t * t - 1.0;
+        let hermite = Interpolant::Hermite {
+            t0: 1.0, h: 0.5, y0: vec![y(1.0)], f0: vec![f(1.0)], y1: vec![y(1.5)], f1: vec![f(1.5)],
+        };
+        let ts = vec![1.0, 1.2, 1.4, 1.5];
+        let lagrange = Interpolant::Lagrange { ys: ts.iter().map(|&t| vec![y(t)]).collect(), ts };
+        for t in [1.0, 1.1, 1.33, 1.5] {
+            assert!((hermite.eval(t)[0] - y(t)).abs() < 1e-12);
+            assert!((lagrange.eval(t)[0] - y(t)).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn dormand_prince_weights_sum_to_step_weights() {
+        // At θ = 1 the dense weights are the Dormand–Prince b_i.
+        let b = [35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0, 0.0];
+        for (row, bi) in DP_P.iter().zip(b) {
+            assert!((row.iter().sum::<f64>() - bi).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn recorder_samples_requested_times() {
+        let config = OdeSolverConfig {
+            t_end: 1.0,
+            output: OutputTimes::Times(vec![0.0, 0.25, 0.5, 1.0]),
+            ..Default::default()
+        };
+        let mut rec = DenseRecorder::new(&config, &[0.0]);
+        let line = |t0: f64, t1: f64| Interpolant::Lagrange { ts: vec![t0, t1], ys: vec![vec![t0], vec![t1]] };
+        rec.step(0.3, &[0.3], || line(0.0, 0.3));
+        rec.step(0.4, &[0.4], || panic!("no requested time in (0.3, 0.4]"));
+        rec.step(1.0 - 1e-15, &[1.0], || line(0.4, 1.0));
+        let (t, states) = rec.finish();
+        assert_eq!(t, [0.0, 0.25, 0.5, 1.0]);
+        assert!((states[1][0] - 0.25).abs() < 1e-15 && (states[2][0] - 0.5).abs() < 1e-15);
+    }
+
+    #[test]
+    fn locates_roots_of_smooth_functions() {
+        let g = |t: f64| t.cos();
+        let t = locate_root(g, 1.0, 2.0, g(1.0), g(2.0), 1e-12);
+        assert!((t - std::f64::consts::FRAC_PI_2).abs() < 1e-12);
+    }
+}
@@ -1,37 +1,42 @@
-//! ODE solver with zero-crossing event detection.
+//! ODE solving with zero-crossing events: hybrid systems.
 //!
-//! Wraps the RK4 fixed-step solver with bisection-based zero-crossing detection.
-//! When the event function `g(y, t)` changes sign between two steps, bisection
-//! refines the crossing time to within `event_tol` (default 1e-10).
+//! Any of the RK4, RK45, BDF and Radau solvers can drive a [`HybridSystem`]:
+//! a set of equation *modes* plus [`HybridEvent`]s whose condition `g(t, y)`
+//! is watched on every accepted step. When `g` changes sign in the chosen
+//! [`Direction`], the crossing is located on the step's dense-output
+//! interpolant ([`super::dense`]) to within `event_tol`, with no
+//! re-integration.
 //!
 //! # Algorithm
 //!
-//! 1. Take a standard RK4 step from t to t+h.
-//! 2. Evaluate g before and after the step.
-//! 3. If `g_before * g_after <= 0` (sign change), bisect on \[t, t+h\]:
-//!    - Mid: t_mid = (t_left + t_right) / 2
-//!    - Advance the ODE from t_left to t_mid using the stored start state
-//!    - Evaluate g at t_mid; narrow the bracket
-//!    - Repeat until |t_right - t_left| < event_tol
-//! 4. Record the event at t* (midpoint of final bracket).
-//! 5. Continue or terminate based on `action` setting.
+//! 1. The solver offers each accepted step `[t, t+h]` to the event observer.
+//! 2. Every event active in the current mode compares `g` at both ends; a
+//!    sign change in the event's direction is refined with [`locate_root`]
+//!    on the step interpolant.
+//! 3. Events that only record are logged and integration continues. The
+//!    earliest event that terminates, resets or switches ends the run at t*
+//!    with y(t*) from the interpolant.
+//! 4. A reset evaluates its assignments (e.g. `y1 = -0.8*y1`) on the state
+//!    before the event, a switch selects a new equation mode, and the solver
+//!    restarts from t* — so multistep history and step size start afresh.
 //!
-//! # Event actions
-//!
-//! * `Terminate` — stop integration at the first zero crossing.
-//! * `Record` — record the event and continue integration.
-//!
-//! # Bisection RK4
-//!
-//! The bisection sub-solver takes a starting state `y_start` at time `t_start`
-//! and integrates to `t_target` in one adaptive-step sequence of RK4 steps with
-//! step size h/N (N=32 sub-steps). This gives sufficient accuracy for event location.
+//! Right after a restart the event that fired treats its own `g` as zero, so
+//! a state sitting on the switching surface (a ball resting at height 0)
+//! does not retrigger it. At most [`MAX_EVENTS`] events are handled, which
+//! bounds Zeno behaviour such as a ball bouncing ever faster.
 
-use super::rk4::eval_rhs_pub;
-use super::types::{OdeResult, OdeSolverConfig, OdeSystem};
-use crate::expr::eval_expr;
+use super::bdf::integrate_bdf;
+use super::dense::{locate_root, AcceptedStep, Interpolant, StepControl};
+use super::radau::integrate_radau;
+use super::rk4::{integrate_rk4, solve_rk4};
+use super::rk45::integrate_rk45;
+use super::types::{OdeResult, OdeSolverConfig, OdeSystem, OutputTimes};
+use crate::expr::{compile, CompiledExpr};
 use std::collections::HashMap;
 
+/// Upper bound on handled events per run.
+pub const MAX_EVENTS: usize = 10_000;
+
 /// Action to take when an event fires.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum EventAction {
@@ -41,6 +46,338 @@ pub enum EventAction {
     Record,
 }
 
+/// Which sign changes of the event function count as a crossing.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Direction {
+    /// `g` goes from negative to non-negative.
+    Rising,
+    /// `g` goes from positive to non-positive.
+    Falling,
+    #[default]
+    Both,
+}
+
+impl Direction {
+    fn crosses(self, g0: f64, g1: f64) -> bool {
+        let rising = g0 < 0.0 && g1 >= 0.0;
+        let falling = g0 > 0.0 && g1 <= 0.0;
+        match self {
+            Direction::Rising => rising,
+            Direction::Falling => falling,
+            Direction::Both => rising || falling,
+        }
+    }
+}
+
+/// Parse an event direction ("rising"/"up"/"+", "falling"/"down"/"-", else both).
+pub fn parse_direction(s: &str) -> Direction {
+    match s.trim().to_lowercase().as_str() {
+        "rising" | "up" | "+" | "1" => Direction::Rising,
+        "falling" | "down" | "-" | "-1" => Direction::Falling,
+        _ => Direction::Both,
+    }
+}
+
+/// ODE integrator that can drive a hybrid system.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum OdeMethod {
+    #[default]
+    Rk4,
+    Rk45,
+    Bdf,
+    Radau,
+}
+
+impl OdeMethod {
+    fn integrate(
+        self,
+        system: &OdeSystem,
+        y0: &[f64],
+        config: &OdeSolverConfig,
+        on_step: &mut dyn FnMut(&AcceptedStep) -> StepControl,
+    ) -> OdeResult {
+        match self {
+            OdeMethod::Rk4 => integrate_rk4(system, y0, config, on_step),
+            OdeMethod::Rk45 => integrate_rk45(system, y0, config, on_step),
+            OdeMethod::Bdf => integrate_bdf(system, y0, config, on_step).0,
+            OdeMethod::Radau => integrate_radau(system, y0, config, on_step).0,
+        }
+    }
+}
+
+/// Parse a solver name ("rk45"/"adaptive", "bdf", "radau", else RK4).
+pub fn parse_ode_method(s: &str) -> OdeMethod {
+    match s.trim().to_lowercase().as_str() {
+        "rk45" | "adaptive" | "dopri" => OdeMethod::Rk45,
+        "bdf" => OdeMethod::Bdf,
+        "radau" => OdeMethod::Radau,
+        _ => OdeMethod::Rk4,
+    }
+}
+
+/// A zero-crossing event of a hybrid system.
+///
+/// An event with no reset, no switch and `terminal == false` only records
+/// its crossings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HybridEvent {
+    /// Event function `g(t, y)`; same variables as the equations.
+    pub condition: String,
+    pub direction: Direction,
+    /// Stop the run at the crossing.
+    pub terminal: bool,
+    /// State assignments `(i, expr)` applied at the crossing: `y_i = expr`,
+    /// evaluated on the state before the event.
+    pub resets: Vec<(usize, String)>,
+    /// Equation mode to continue in after the crossing.
+    pub switch_to: Option<usize>,
+    /// Only watch this event while in the given mode (`None`: every mode).
+    pub mode: Option<usize>,
+}
+
+impl HybridEvent {
+    /// A recording event on `condition`, in both directions and every mode.
+    pub fn new(condition: &str) -> Self {
+        HybridEvent {
+            condition: condition.to_string(),
+            direction: Direction::Both,
+            terminal: false,
+            resets: Vec::new(),
+            switch_to: None,
+            mode: None,
+        }
+    }
+
+    /// Whether a crossing ends the current integration segment.
+    fn stops(&self) -> bool {
+        self.terminal || !self.resets.is_empty() || self.switch_to.is_some()
+    }
+}
+
+/// Parse reset assignments such as `"y1 = -0.8*y1; y0 = 0"`. The target may
+/// be written `y1` or `y[1]`.
+pub fn parse_resets(text: &str) -> Result<Vec<(usize, String)>, String> {
+    text.split(';')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|assign| {
+            let (lhs, rhs) = assign
+                .split_once('=')
+                .ok_or_else(|| format!("reset '{assign}' is not of the form yN = expr"))?;
+            let lhs = lhs.trim();
+            let index = lhs
+                .strip_prefix("y[")
+                .and_then(|s| s.strip_suffix(']'))
+                .or_else(|| lhs.strip_prefix('y'))
+                .and_then(|s| s.trim().parse::<usize>().ok())
+                .ok_or_else(|| format!("reset target '{lhs}' must be a state yN"))?;
+            Ok((index, rhs.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Equation modes and the events that move between them.
+#[derive(Debug, Clone)]
+pub struct HybridSystem {
+    /// Equation sets over the same state; integration starts in mode 0.
+    pub modes: Vec<OdeSystem>,
+    pub events: Vec<HybridEvent>,
+}
+
+/// One event occurrence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventRecord {
+    pub t: f64,
+    /// Index into [`HybridSystem::events`].
+    pub event: usize,
+    /// Mode the event fired in.
+    pub mode: usize,
+    pub state_before: Vec<f64>,
+    pub state_after: Vec<f64>,
+}
+
+/// Result of a hybrid simulation.
+pub struct HybridResult {
+    /// Time series over all segments. At a reset both the pre- and
+    /// post-event states appear at t* (with [`OutputTimes::Steps`]).
+    pub ode: OdeResult,
+    pub events: Vec<EventRecord>,
+    /// Whether a terminal event ended the run.
+    pub terminated: bool,
+    pub final_mode: usize,
+}
+
+fn state_vars(params: &HashMap<String, f64>, t: f64, y: &[f64]) -> HashMap<String, f64> {
+    let mut vars = params.clone();
+    vars.insert("t".to_string(), t);
+    for (i, &yi) in y.iter().enumerate() {
+        vars.insert(format!("y{i}"), yi);
+    }
+    vars
+}
+
+/// Simulate a hybrid system with `method`, locating event times to within
+/// `event_tol`.
+pub fn solve_hybrid(
+    system: &HybridSystem,
+    y0: &[f64],
+    config: &OdeSolverConfig,
+    method: OdeMethod,
+    event_tol: f64,
+) -> Result<HybridResult, String> {
+    let n = y0.len();
+    if system.modes.is_empty() {
+        return Err("hybrid system has no equation modes".to_string());
+    }
+    for (m, mode) in system.modes.iter().enumerate() {
+        if mode.equations.len() != n {
+            return Err(format!(
+                "mode {m} has {} equations but the state has {n} values",
+                mode.equations.len()
+            ));
+        }
+    }
+    let mut conditions = Vec::with_capacity(system.events.len());
+    let mut resets: Vec<Vec<(usize, CompiledExpr)>> = Vec::with_capacity(system.events.len());
+    for (e, ev) in system.events.iter().enumerate() {
+        conditions.push(compile(&ev.condition).map_err(|err| format!("event {e} condition: {err}"))?);
+        let mut compiled = Vec::with_capacity(ev.resets.len());
+        for (i, expr) in &ev.resets {
+            if *i >= n {
+                return Err(format!("event {e} resets y{i} but the state has {n} values"));
+            }
+            compiled.push((*i, compile(expr).map_err(|err| format!("event {e} reset of y{i}: {err}"))?));
+        }
+        resets.push(compiled);
+        for m in ev.switch_to.iter().chain(&ev.mode) {
+            if *m >= system.modes.len() {
+                return Err(format!("event {e} refers to mode {m}, but there are {}", system.modes.len()));
+            }
+        }
+    }
+    let dense = config.output != OutputTimes::Steps;
+
+    let mut t = config.t_start;
+    let mut y = y0.to_vec();
+    let mut mode = 0usize;
+    let mut steps = 0usize;
+    let mut t_out: Vec<f64> = Vec::new();
+    let mut states_out: Vec<Vec<f64>> = Vec::new();
+    let mut records: Vec<EventRecord> = Vec::new();
+    let mut terminated = false;
+    // The event that caused the last restart; its g counts as zero there.
+    let mut quiet: Option<usize> = None;
+
+    loop {
+        let current = &system.modes[mode];
+        let active: Vec<usize> = (0..system.events.len())
+            .filter(|&e| system.events[e].mode.is_none_or(|m| m == mode))
+            .collect();
+        let g = |e: usize, t: f64, y: &[f64]| -> f64 {
+            conditions[e].eval(&state_vars(&current.params, t, y)).unwrap_or(f64::NAN)
+        };
+
+        let mut g_prev: Vec<f64> = active
+            .iter()
+            .map(|&e| if quiet == Some(e) { 0.0 } else { g(e, t, &y) })
+            .collect();
+        let mut hit: Option<(usize, f64, Vec<f64>)> = None;
+        let mut logged: Vec<EventRecord> = Vec::new();
+        let mut observer = |step: &AcceptedStep| -> StepControl {
+            let g_next: Vec<f64> = active.iter().map(|&e| g(e, step.t1, step.y1)).collect();
+            let mut interp: Option<Interpolant> = None;
+            let mut crossings: Vec<(f64, usize)> = Vec::new();
+            for (k, &e) in active.iter().enumerate() {
+                if system.events[e].direction.crosses(g_prev[k], g_next[k]) {
+                    let p = interp.get_or_insert_with(|| step.interpolant());
+                    let tau = locate_root(
+                        |s| g(e, s, &p.eval(s)),
+                        step.t0,
+                        step.t1,
+                        g_prev[k],
+                        g_next[k],
+                        event_tol,
+                    );
+                    crossings.push((tau, e));
+                }
+            }
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+            for (tau, e) in crossings {
+                let y_tau = match &interp {
+                    Some(p) if tau != step.t1 => p.eval(tau),
+                    _ => step.y1.to_vec(),
+                };
+                if system.events[e].stops() {
+                    hit = Some((e, tau, y_tau.clone()));
+                    return StepControl::Stop { t: tau, y: y_tau };
+                }
+                logged.push(EventRecord {
+                    t: tau,
+                    event: e,
+                    mode,
+                    state_before: y_tau.clone(),
+                    state_after: y_tau,
+                });
+            }
+            g_prev = g_next;
+            StepControl::Continue
+        };
+
+        let segment_config = OdeSolverConfig {
+            t_start: t,
+            max_steps: config.max_steps.saturating_sub(steps),
+            ..config.clone()
+        };
+        let segment = method.integrate(current, &y, &segment_config, &mut observer);
+        steps += segment.steps;
+        // Joined segments share t*: keep both sides of a reset, but never
+        // repeat a requested output time.
+        let last = t_out.last().copied().unwrap_or(f64::NEG_INFINITY);
+        for (ts, ys) in segment.t.into_iter().zip(segment.states) {
+            if ts > last || (!dense && ts == last) {
+                t_out.push(ts);
+                states_out.push(ys);
+            }
+        }
+        records.extend(logged);
+
+        let Some((e, t_event, y_event)) = hit else { break };
+        let event = &system.events[e];
+        let vars = state_vars(&current.params, t_event, &y_event);
+        let mut y_after = y_event.clone();
+        for (i, expr) in &resets[e] {
+            y_after[*i] = expr.eval(&vars).unwrap_or(f64::NAN);
+        }
+        records.push(EventRecord {
+            t: t_event,
+            event: e,
+            mode,
+            state_before: y_event,
+            state_after: y_after.clone(),
+        });
+        if event.terminal {
+            terminated = true;
+            break;
+        }
+        mode = event.switch_to.unwrap_or(mode);
+        t = t_event;
+        y = y_after;
+        quiet = Some(e);
+        if t >= config.t_end || steps >= config.max_steps || records.len() >= MAX_EVENTS {
+            break;
+        }
+    }
+
+    let mut column_names = vec!["t".to_string()];
+    column_names.extend(system.modes[0].state_names.iter().cloned());
+    Ok(HybridResult {
+        ode: OdeResult { t: t_out, states: states_out, column_names, steps },
+        events: records,
+        terminated,
+        final_mode: mode,
+    })
+}
+
 /// Result of the event-detecting ODE solver.
 pub struct EventOdeResult {
     /// Standard ODE result (time series of state variables).
@@ -53,15 +390,16 @@ pub struct EventOdeResult {
     pub terminated_by_event: bool,
 }
 
-/// Solve an ODE system with zero-crossing event detection.
+/// Solve an ODE system with zero-crossing event detection (RK4).
 ///
 /// `system` — ODE system (equations + params).
 /// `y0` — initial state.
 /// `config` — solver configuration (t_start, t_end, dt, max_steps).
 /// `event_expr` — expression string for g(y, t); event fires when g crosses 0.
 ///   Uses same variable naming as the ODE system: `t`, `y0..y{N-1}`, named params.
+///   An expression that does not parse never fires.
 /// `action` — what to do when an event fires.
-/// `event_tol` — bisection tolerance for event time location (default 1e-10).
+/// `event_tol` — tolerance for event time location (default 1e-10).
 pub fn solve_with_events(
     system: &OdeSystem,
     y0: &[f64],
@@ -70,132 +408,26 @@ pub fn solve_with_events(
     action: EventAction,
     event_tol: f64,
 ) -> EventOdeResult {
-    let _n = system.equations.len();
-    let dt = config.dt;
-    let mut t = config.t_start;
-    let mut y = y0.to_vec();
-
-    let mut t_out = vec![t];
-    let mut states_out = vec![y.clone()];
-    let mut steps = 0;
-
-    let mut event_times = Vec::new();
-    let mut event_states = Vec::new();
-    let mut terminated_by_event = false;
-
-    // Evaluate event function at current state
-    let eval_event = |y: &[f64], t: f64| -> f64 {
-        let mut vars: HashMap<String, f64> = system.params.clone();
-        vars.insert("t".to_string(), t);
-        for (i, &yi) in y.iter().enumerate() {
-            vars.insert(format!("y{i}"), yi);
-        }
-        eval_expr(event_expr, &vars).unwrap_or(f64::NAN)
-    };
-
-    // Take a single RK4 step from t to t+h
-    let rk4_step = |y: &[f64], t: f64, h: f64| -> Vec<f64> {
-        let k1 = eval_rhs_pub(system, t, y);
-        let y2: Vec<f64> = y.iter().zip(&k1).map(|(&yi, &k)| yi + h * k / 2.0).collect();
-        let k2 = eval_rhs_pub(system, t + h / 2.0, &y2);
-        let y3: Vec<f64> = y.iter().zip(&k2).map(|(&yi, &k)| yi + h * k / 2.0).collect();
-        let k3 = eval_rhs_pub(system, t + h / 2.0, &y3);
-        let y4: Vec<f64> = y.iter().zip(&k3).map(|(&yi, &k)| yi + h * k).collect();
-        let k4 = eval_rhs_pub(system, t + h, &y4);
-        y.iter()
-            .enumerate()
-            .map(|(i, &yi)| yi + h / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]))
-            .collect()
+    let hybrid = HybridSystem {
+        modes: vec![system.clone()],
+        events: vec![HybridEvent {
+            terminal: action == EventAction::Terminate,
+            ..HybridEvent::new(event_expr)
+        }],
     };
-
-    // Integrate from t_start to t_end using N_sub sub-steps of size h/N_sub
-    let integrate_to = |y_start: &[f64], t_start: f64, t_end: f64| -> Vec<f64> {
-        let n_sub = 32usize;
-        let h_sub = (t_end - t_start) / n_sub as f64;
-        let mut y_cur = y_start.to_vec();
-        let mut t_cur = t_start;
-        for _ in 0..n_sub {
-            y_cur = rk4_step(&y_cur, t_cur, h_sub);
-            t_cur += h_sub;
-        }
-        y_cur
-    };
-
-    let mut g_prev = eval_event(&y, t);
-
-    while t < config.t_end - 1e-12 * dt.abs() && steps < config.max_steps {
-        let h = if t + dt > config.t_end { config.t_end - t } else { dt };
-
-        let y_prev = y.clone();
-        let t_prev = t;
-
-        // Full RK4 step
-        y = rk4_step(&y, t, h);
-        t += h;
-        steps += 1;
-
-        let g_next = eval_event(&y, t);
-
-        // Check for zero crossing
-        if !g_prev.is_nan() && !g_next.is_nan() && g_prev * g_next < 0.0 {
-            // Bisect to find event time
-            let mut t_lo = t_prev;
-            let mut t_hi = t;
-            let mut y_lo = y_prev.clone();
-            let mut g_lo = g_prev;
-
-            for _ in 0..60 {
-                if (t_hi - t_lo).abs() < event_tol {
-                    break;
-                }
-                let t_mid = 0.5 * (t_lo + t_hi);
-                let y_mid = integrate_to(&y_lo, t_lo, t_mid);
-                let g_mid = eval_event(&y_mid, t_mid);
-
-                if g_lo * g_mid <= 0.0 {
-                    t_hi = t_mid;
-                    // y_hi would be y_mid but we don't need it
-                } else {
-                    t_lo = t_mid;
-                    y_lo = y_mid;
-                    g_lo = g_mid;
-                }
-            }
-
-            let t_event = 0.5 * (t_lo + t_hi);
-            let y_event = integrate_to(&y_lo, t_lo, t_event);
-
-            event_times.push(t_event);
-            event_states.push(y_event.clone());
-
-            if action == EventAction::Terminate {
-                // Add the event state to the time series and stop
-                t_out.push(t_event);
-                states_out.push(y_event);
-                terminated_by_event = true;
-                break;
-            }
-            // Record: continue from after the event
-        }
-
-        g_prev = g_next;
-        t_out.push(t);
-        states_out.push(y.clone());
-    }
-
-    let mut column_names = vec!["t".to_string()];
-    column_names.extend(system.state_names.iter().cloned());
-
-    EventOdeResult {
-        ode: OdeResult {
-            t: t_out,
-            states: states_out,
-            column_names,
-            steps,
+    match solve_hybrid(&hybrid, y0, config, OdeMethod::Rk4, event_tol) {
+        Ok(result) => EventOdeResult {
+            ode: result.ode,
+            event_times: result.events.iter().map(|r| r.t).collect(),
+            event_states: result.events.into_iter().map(|r| r.state_before).collect(),
+            terminated_by_event: result.terminated,
+        },
+        Err(_) => EventOdeResult {
+            ode: solve_rk4(system, y0, config),
+            event_times: Vec::new(),
+            event_states: Vec::new(),
+            terminated_by_event: false,
         },
-        event_times,
-        event_states,
-        terminated_by_event,
     }
 }
 
@@ -238,6 +470,7 @@ mod tests {
             dt: 0.05,
             max_steps: 10_000,
             tolerance: 1e-6,
+            ..Default::default()
         };
         // Event: height crosses 0 (going down, i.e., y0 becomes negative)
         let result = solve_with_events(&system, &y0, &config, "y0", EventAction::Terminate, 1e-8);
@@ -275,6 +508,7 @@ mod tests {
             dt: 0.1,
             max_steps: 100,
             tolerance: 1e-6,
+            ..Default::default()
         };
         // Event: g = y0 - 10 (never crosses 0 since y goes from 0 to 1)
         let result = solve_with_events(&system, &y0, &config, "y0 - 10", EventAction::Terminate, 1e-8);
@@ -298,6 +532,7 @@ mod tests {
             dt: 0.1,
             max_steps: 100,
             tolerance: 1e-6,
+            ..Default::default()
         };
         let result = solve_with_events(&system, &y0, &config, "y0", EventAction::Record, 1e-8);
         assert!(!result.terminated_by_event, "Should not terminate in Record mode");
@@ -305,4 +540,140 @@ mod tests {
         assert!((result.event_times[0] - 0.5).abs() < 0.001,
             "Event at t={:.4}", result.event_times[0]);
     }
+
+    fn bouncing_ball() -> HybridSystem {
+        let mut params = HashMap::new();
+        params.insert("g".to_string(), 9.81);
+        HybridSystem {
+            modes: vec![OdeSystem {
+                equations: vec!["y1".to_string(), "-g".to_string()],
+                state_names: vec!["height".to_string(), "velocity".to_string()],
+                params,
+            }],
+            events: vec![HybridEvent {
+                direction: Direction::Falling,
+                resets: parse_resets("y1 = -0.8*y1").unwrap(),
+                ..HybridEvent::new("y0")
+            }],
+        }
+    }
+
+    /// Dropped from 1 m with restitution 0.8: impacts at
+    /// t_1 = √(2/g), then every 2·0.8^k·v_1/g later.
+    #[test]
+    fn bouncing_ball_resets_velocity_with_every_method() {
+        let g: f64 = 9.81;
+        let v1 = (2.0 * g).sqrt();
+        let mut expected = vec![v1 / g];
+        for k in 1..4 {
+            expected.push(expected[k - 1] + 2.0 * 0.8_f64.powi(k as i32) * v1 / g);
+        }
+        let config = OdeSolverConfig { t_end: 2.5, dt: 0.01, tolerance: 1e-8, ..Default::default() };
+        for method in [OdeMethod::Rk4, OdeMethod::Rk45, OdeMethod::Bdf, OdeMethod::Radau] {
+            let result = solve_hybrid(&bouncing_ball(), &[1.0, 0.0], &config, method, 1e-10).unwrap();
+            assert_eq!(result.events.len(), 4, "{method:?}: {:?}", result.events);
+            // BDF has no local error control, so it gets a looser check.
+            let tol = if method == OdeMethod::Bdf { 5e-2 } else { 1e-6 };
+            for (rec, t_exp) in result.events.iter().zip(&expected) {
+                assert!((rec.t - t_exp).abs() < tol, "{method:?}: impact at {} vs {t_exp}", rec.t);
+                assert!(rec.state_before[1] < 0.0);
+                assert!((rec.state_after[1] + 0.8 * rec.state_before[1]).abs() < 1e-12);
+            }
+            assert!(result.ode.states.iter().all(|s| s[0] > -1e-6), "{method:?} fell through the floor");
+            assert!(!result.terminated);
+        }
+    }
+
+    #[test]
+    fn direction_filters_crossings() {
+        // y = sin(t): rising crossings at 2π, falling at π and 3π.
+        let system = HybridSystem {
+            modes: vec![OdeSystem {
+                equations: vec!["y1".to_string(), "-y0".to_string()],
+                state_names: vec!["s".to_string(), "c".to_string()],
+                params: HashMap::new(),
+            }],
+            events: vec![
+                HybridEvent { direction: Direction::Rising, ..HybridEvent::new("y0") },
+                HybridEvent { direction: Direction::Falling, ..HybridEvent::new("y0") },
+            ],
+        };
+        let config = OdeSolverConfig { t_end: 10.0, dt: 0.05, tolerance: 1e-9, ..Default::default() };
+        let result = solve_hybrid(&system, &[0.0, 1.0], &config, OdeMethod::Rk45, 1e-10).unwrap();
+        let pi = std::f64::consts::PI;
+        let times = |e: usize| -> Vec<f64> {
+            result.events.iter().filter(|r| r.event == e).map(|r| r.t).collect()
+        };
+        let (rising, falling) = (times(0), times(1));
+        assert_eq!((rising.len(), falling.len()), (1, 2));
+        assert!((rising[0] - 2.0 * pi).abs() < 1e-6);
+        assert!((falling[0] - pi).abs() < 1e-6 && (falling[1] - 3.0 * pi).abs() < 1e-6);
+        // Recording events do not break the time series.
+        assert_eq!(result.ode.t.len(), result.ode.steps + 1);
+    }
+
+    #[test]
+    fn events_switch_between_equation_modes() {
+        // A clutch: slipping (mode 0) the speed ramps up at 2/s; once it
+        // reaches 1 the clutch locks (mode 1) and the speed holds.
+        let modes = ["2.0", "0.0"]
+            .iter()
+            .map(|eq| OdeSystem {
+                equations: vec![eq.to_string()],
+                state_names: vec!["w".to_string()],
+                params: HashMap::new(),
+            })
+            .collect();
+        let system = HybridSystem {
+            modes,
+            events: vec![HybridEvent {
+                direction: Direction::Rising,
+                switch_to: Some(1),
+                mode: Some(0),
+                ..HybridEvent::new("y0 - 1")
+            }],
+        };
+        let config = OdeSolverConfig {
+            t_end: 2.0,
+            dt: 0.1,
+            output: OutputTimes::Times(vec![0.25, 0.5, 1.0, 1.5, 2.0]),
+            ..Default::default()
+        };
+        let result = solve_hybrid(&system, &[0.0], &config, OdeMethod::Radau, 1e-12).unwrap();
+        assert_eq!(result.final_mode, 1);
+        assert!((result.events[0].t - 0.5).abs() < 1e-9);
+        assert_eq!(result.ode.t, [0.25, 0.5, 1.0, 1.5, 2.0]);
+        let w: Vec<f64> = result.ode.states.iter().map(|s| s[0]).collect();
+        for (got, want) in w.iter().zip([0.5, 1.0, 1.0, 1.0, 1.0]) {
+            assert!((got - want).abs() < 1e-8, "{w:?}");
+        }
+    }
+
+    #[test]
+    fn terminal_event_stops_stiff_solver() {
+        let system = HybridSystem {
+            modes: vec![OdeSystem {
+                equations: vec!["-1000 * (y0 - cos(t))".to_string()],
+                state_names: vec!["y".to_string()],
+                params: HashMap::new(),
+            }],
+            events: vec![HybridEvent { terminal: true, ..HybridEvent::new("y0") }],
+        };
+        let config = OdeSolverConfig { t_end: 3.0, dt: 0.01, tolerance: 1e-8, ..Default::default() };
+        let result = solve_hybrid(&system, &[1.0], &config, OdeMethod::Bdf, 1e-10).unwrap();
+        assert!(result.terminated);
+        // y tracks cos(t) with lag ≈ 1e-3, so it crosses zero just after π/2.
+        let t_stop = *result.ode.t.last().unwrap();
+        assert!((t_stop - std::f64::consts::FRAC_PI_2).abs() < 5e-3, "stopped at {t_stop}");
+        assert_eq!(t_stop, result.events[0].t);
+    }
+
+    #[test]
+    fn rejects_malformed_resets_and_modes() {
+        assert!(parse_resets("y1 = -y1; y[0] = 0").is_ok_and(|r| r == [(1, "-y1".to_string()), (0, "0".to_string())]));
+        assert!(parse_resets("v = 0").is_err());
+        let mut system = bouncing_ball();
+        system.events[0].switch_to = Some(3);
+        assert!(solve_hybrid(&system, &[1.0, 0.0], &OdeSolverConfig::default(), OdeMethod::Rk4, 1e-9).is_err());
+    }
 }
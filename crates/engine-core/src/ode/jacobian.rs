@@ -0,0 +1,746 @@
+//! Right-hand sides and exact Jacobians for the implicit solvers (BDF, Radau).
+//!
+//! [`CompiledRhs`] compiles the equations once and differentiates them with
+//! forward-mode dual numbers ([`CompiledExpr::eval_dual`]), one pass per state
+//! variable, so `J[i][j] = ∂f_i/∂y_j` carries no truncation error. If any
+//! equation fails to compile, the system falls back to string evaluation and
+//! finite differences.
+//!
+//! Large systems with a sparse Jacobian (method-of-lines grids, component
+//! networks) switch to a [`Sparsity`] pattern read from the expression
+//! dependency graph. Columns that share no row are coloured alike and
+//! differentiated together, by one dual pass or one finite difference per
+//! colour (Curtis, Powell & Reid, 1974); the Jacobian is
+//! assembled as a [`CsrMatrix`], and the Newton systems are solved with
+//! ILU(0)-preconditioned GMRES instead of a dense LU ([`IterationMatrix`]).
+//!
+//! The solvers see a right-hand side through [`OdeRhs`], so models that are
+//! not expression strings (e.g. `acausal` networks solved block by block)
+//! integrate with the same code.
+//!
+//! [`JacobianCache`] decides when a Jacobian may be reused across steps,
+//! following Hairer & Wanner, "Solving ODEs II", §IV.8: keep it while the
+//! simplified Newton iteration contracts faster than [`REUSE_THETA`], refresh
+//! it when the iteration is slower or fails with a stale matrix.
+
+use super::rk4::eval_rhs_pub;
+use super::types::OdeSystem;
+use crate::autodiff::Dual;
+use crate::expr::{compile, CompiledExpr};
+use crate::sparse::{CooMatrix, CsrMatrix};
+use crate::sparse_solvers::{gmres, Ilu0, SolverConfig};
+use std::cell::{Cell, OnceCell};
+use std::collections::HashMap;
+
+/// Newton contraction rate below which the Jacobian is kept for the next step.
+pub const REUSE_THETA: f64 = 1e-3;
+
+/// Smallest system for which a sparse Jacobian is considered.
+pub const SPARSE_MIN_DIM: usize = 64;
+
+/// Largest fill fraction `nnz / n²` for which a sparse Jacobian is used.
+pub const SPARSE_MAX_DENSITY: f64 = 0.1;
+
+/// Work counters reported by the `*_with_stats` solver entry points.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ImplicitStats {
+    pub rhs_evals: usize,
+    pub jacobian_evals: usize,
+    pub lu_factorizations: usize,
+    pub newton_iterations: usize,
+    pub rejected_steps: usize,
+}
+
+/// Structural nonzeros of an n×n Jacobian with a column colouring: columns of
+/// one colour share no row, so a single directional derivative recovers all
+/// of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sparsity {
+    n: usize,
+    /// Sorted column indices of each row.
+    rows: Vec<Vec<usize>>,
+    colors: Vec<usize>,
+    n_colors: usize,
+}
+
+impl Sparsity {
+    /// Pattern from the columns each row depends on (any order, duplicates allowed).
+    pub fn from_rows(n: usize, mut rows: Vec<Vec<usize>>) -> Self {
+        for row in &mut rows {
+            row.sort_unstable();
+            row.dedup();
+        }
+        let mut by_col: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for (r, row) in rows.iter().enumerate() {
+            for &c in row {
+                by_col[c].push(r);
+            }
+        }
+        // Greedy distance-2 colouring in column order.
+        let mut colors = vec![usize::MAX; n];
+        let mut taken: Vec<usize> = Vec::new();
+        let mut n_colors = 0;
+        for col in 0..n {
+            taken.clear();
+            for &r in &by_col[col] {
+                taken.extend(rows[r].iter().map(|&c| colors[c]).filter(|&k| k != usize::MAX));
+            }
+            taken.sort_unstable();
+            taken.dedup();
+            let color = taken.iter().enumerate().position(|(i, &k)| i != k).unwrap_or(taken.len());
+            colors[col] = color;
+            n_colors = n_colors.max(color + 1);
+        }
+        Sparsity { n, rows, colors, n_colors }
+    }
+
+    pub fn dim(&self) -> usize {
+        self.n
+    }
+
+    /// The same pattern with every diagonal entry present.
+    pub fn with_diagonal(self) -> Self {
+        let mut rows = self.rows;
+        for (i, row) in rows.iter_mut().enumerate() {
+            row.push(i);
+        }
+        Sparsity::from_rows(self.n, rows)
+    }
+
+    pub fn nnz(&self) -> usize {
+        self.rows.iter().map(Vec::len).sum()
+    }
+
+    /// Fraction of the n² entries that are structurally nonzero.
+    pub fn density(&self) -> f64 {
+        if self.n == 0 { 0.0 } else { self.nnz() as f64 / (self.n * self.n) as f64 }
+    }
+
+    /// Number of directional derivatives needed for one Jacobian.
+    pub fn n_colors(&self) -> usize {
+        self.n_colors
+    }
+
+    pub fn row(&self, i: usize) -> &[usize] {
+        &self.rows[i]
+    }
+
+    /// Columns of each colour.
+    fn groups(&self) -> Vec<Vec<usize>> {
+        let mut groups = vec![Vec::new(); self.n_colors];
+        for (col, &color) in self.colors.iter().enumerate() {
+            groups[color].push(col);
+        }
+        groups
+    }
+
+    /// `(row, col, CSR position)` of each entry in a column of the given colour.
+    fn entries_of_color(&self, color: usize) -> Vec<(usize, usize, usize)> {
+        let mut out = Vec::new();
+        let mut pos = 0;
+        for (r, row) in self.rows.iter().enumerate() {
+            for &c in row {
+                if self.colors[c] == color {
+                    out.push((r, c, pos));
+                }
+                pos += 1;
+            }
+        }
+        out
+    }
+
+    /// A CSR matrix with this pattern and `values` in row-major pattern order.
+    fn to_csr(&self, values: Vec<f64>) -> CsrMatrix {
+        let mut row_ptrs = Vec::with_capacity(self.n + 1);
+        row_ptrs.push(0);
+        for row in &self.rows {
+            row_ptrs.push(row_ptrs.last().copied().unwrap_or(0) + row.len());
+        }
+        CsrMatrix {
+            rows: self.n,
+            cols: self.n,
+            row_ptrs,
+            col_indices: self.rows.concat(),
+            values,
+        }
+    }
+}
+
+/// A Jacobian `J[i][j] = ∂f_i/∂y_j`, dense or in compressed rows.
+#[derive(Debug, Clone)]
+pub enum Jacobian {
+    Dense(Vec<Vec<f64>>),
+    Sparse(CsrMatrix),
+}
+
+impl Jacobian {
+    pub fn dim(&self) -> usize {
+        match self {
+            Jacobian::Dense(j) => j.len(),
+            Jacobian::Sparse(j) => j.rows,
+        }
+    }
+
+    /// Entry `(row, col)`; zero outside a sparse pattern.
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        match self {
+            Jacobian::Dense(j) => j[row][col],
+            Jacobian::Sparse(j) => (j.row_ptrs[row]..j.row_ptrs[row + 1])
+                .find(|&k| j.col_indices[k] == col)
+                .map_or(0.0, |k| j.values[k]),
+        }
+    }
+
+    /// Factor the BDF iteration matrix `I - gamma·J`.
+    pub fn shifted(&self, gamma: f64) -> Option<IterationMatrix> {
+        self.kronecker(&[[gamma]])
+    }
+
+    /// Factor the block iteration matrix `I - A ⊗ J` of an implicit Runge-Kutta
+    /// method, with `a` the (step-scaled) stage coefficient matrix. Block
+    /// `(i, j)` is `δ_ij·I - a_ij·J`.
+    pub fn kronecker<const S: usize>(&self, a: &[[f64; S]; S]) -> Option<IterationMatrix> {
+        let n = self.dim();
+        let m = S * n;
+        match self {
+            Jacobian::Dense(jf) => {
+                let mut mat = vec![0.0; m * m];
+                for (bi, a_row) in a.iter().enumerate() {
+                    for (bj, &aij) in a_row.iter().enumerate() {
+                        for (row, j_row) in jf.iter().enumerate() {
+                            for (col, &jv) in j_row.iter().enumerate() {
+                                let eye = if bi == bj && row == col { 1.0 } else { 0.0 };
+                                mat[(bi * n + row) * m + bj * n + col] = eye - aij * jv;
+                            }
+                        }
+                    }
+                }
+                Lu::factor(m, mat).map(IterationMatrix::Dense)
+            }
+            Jacobian::Sparse(jf) => {
+                let mut coo = CooMatrix::with_capacity(m, m, S * S * jf.values.len() + m);
+                for (bi, a_row) in a.iter().enumerate() {
+                    for row in 0..n {
+                        coo.push(bi * n + row, bi * n + row, 1.0);
+                        for (bj, &aij) in a_row.iter().enumerate() {
+                            for k in jf.row_ptrs[row]..jf.row_ptrs[row + 1] {
+                                coo.push(bi * n + row, bj * n + jf.col_indices[k], -aij * jf.values[k]);
+                            }
+                        }
+                    }
+                }
+                IterationMatrix::sparse(coo.to_csr())
+            }
+        }
+    }
+}
+
+/// A factored Newton iteration matrix.
+pub enum IterationMatrix {
+    Dense(Lu),
+    /// Solved by GMRES preconditioned with the incomplete factorisation.
+    Sparse { mat: CsrMatrix, ilu: Ilu0 },
+}
+
+impl IterationMatrix {
+    /// Incomplete factorisation of a square sparse matrix; `None` if it has
+    /// non-finite entries or a zero pivot (like a singular dense [`Lu`]).
+    pub fn sparse(mat: CsrMatrix) -> Option<Self> {
+        if !mat.values.iter().all(|v| v.is_finite()) {
+            return None;
+        }
+        let ilu = Ilu0::new(&mat);
+        if ilu.has_zero_pivot() {
+            return None;
+        }
+        Some(IterationMatrix::Sparse { mat, ilu })
+    }
+
+    /// Solve `M·x = b`; `None` if GMRES does not converge, which callers
+    /// treat as a failed Newton iteration.
+    pub fn solve(&self, b: &[f64]) -> Option<Vec<f64>> {
+        match self {
+            IterationMatrix::Dense(lu) => Some(lu.solve(b)),
+            IterationMatrix::Sparse { mat, ilu } => {
+                let config = SolverConfig { max_iter: 200, tol: 1e-12 };
+                let result = gmres(mat, b, &config, 30, Some(ilu));
+                result.converged.then_some(result.x)
+            }
+        }
+    }
+}
+
+/// A right-hand side `f(t, y)` with its Jacobian, as the implicit solvers use it.
+pub trait OdeRhs {
+    fn eval(&self, t: f64, y: &[f64]) -> Vec<f64>;
+    /// `J[i][j] = ∂f_i/∂y_j` at `(t, y)`.
+    fn jacobian(&self, t: f64, y: &[f64]) -> Jacobian;
+    /// Number of evaluations so far (a Jacobian counts as one).
+    fn evals(&self) -> usize;
+}
+
+impl OdeRhs for CompiledRhs<'_> {
+    fn eval(&self, t: f64, y: &[f64]) -> Vec<f64> {
+        CompiledRhs::eval(self, t, y)
+    }
+
+    fn jacobian(&self, t: f64, y: &[f64]) -> Jacobian {
+        CompiledRhs::jacobian(self, t, y)
+    }
+
+    fn evals(&self) -> usize {
+        CompiledRhs::evals(self)
+    }
+}
+
+/// Compiled right-hand side `f(t, y)` of an [`OdeSystem`].
+///
+/// `system.params["sparse_jacobian"]` forces the sparse (`1`) or dense (`0`)
+/// Jacobian; otherwise systems of at least [`SPARSE_MIN_DIM`] equations with
+/// fill at most [`SPARSE_MAX_DENSITY`] go sparse.
+pub struct CompiledRhs<'a> {
+    system: &'a OdeSystem,
+    compiled: Option<Vec<CompiledExpr>>,
+    evals: Cell<usize>,
+    sparsity: OnceCell<Option<Sparsity>>,
+}
+
+impl<'a> CompiledRhs<'a> {
+    pub fn new(system: &'a OdeSystem) -> Self {
+        let compiled = system
+            .equations
+            .iter()
+            .map(|eq| compile(eq))
+            .collect::<Result<Vec<_>, _>>()
+            .ok();
+        CompiledRhs { system, compiled, evals: Cell::new(0), sparsity: OnceCell::new() }
+    }
+
+    /// Whether [`jacobian`](Self::jacobian) is exact (all equations compiled).
+    pub fn is_exact(&self) -> bool {
+        self.compiled.is_some()
+    }
+
+    /// Number of right-hand-side evaluations so far (a Jacobian counts as one).
+    pub fn evals(&self) -> usize {
+        self.evals.get()
+    }
+
+    pub fn eval(&self, t: f64, y: &[f64]) -> Vec<f64> {
+        self.evals.set(self.evals.get() + 1);
+        let Some(compiled) = &self.compiled else {
+            return eval_rhs_pub(self.system, t, y);
+        };
+        let mut vars = self.system.params.clone();
+        vars.insert("t".to_string(), t);
+        for (i, v) in y.iter().enumerate() {
+            vars.insert(format!("y{}", i), *v);
+        }
+        compiled.iter().map(|c| c.eval(&vars).unwrap_or(f64::NAN)).collect()
+    }
+
+    /// The sparsity pattern used for Jacobians of systems with `n` states, or
+    /// `None` for dense ones. Decided on first use.
+    pub fn sparsity(&self, n: usize) -> Option<&Sparsity> {
+        self.sparsity
+            .get_or_init(|| {
+                let forced = self.system.params.get("sparse_jacobian").map(|v| *v != 0.0);
+                if forced == Some(false) || (forced.is_none() && n < SPARSE_MIN_DIM) {
+                    return None;
+                }
+                let pattern = structural_sparsity(&self.system.equations, n);
+                (forced == Some(true) || pattern.density() <= SPARSE_MAX_DENSITY).then_some(pattern)
+            })
+            .as_ref()
+    }
+
+    /// `J[i][j] = ∂f_i/∂y_j` at `(t, y)`.
+    pub fn jacobian(&self, t: f64, y: &[f64]) -> Jacobian {
+        if let Some(pattern) = self.sparsity(y.len()) {
+            return Jacobian::Sparse(match &self.compiled {
+                Some(compiled) => self.colored_dual_jacobian(compiled, pattern, t, y),
+                None => colored_difference(pattern, y, |y| self.eval(t, y)),
+            });
+        }
+        let n = y.len();
+        let Some(compiled) = &self.compiled else {
+            return Jacobian::Dense(self.finite_diff_jacobian(t, y));
+        };
+        self.evals.set(self.evals.get() + 1);
+        let mut vars = self.dual_vars(t, y);
+        let names: Vec<String> = (0..n).map(|i| format!("y{}", i)).collect();
+        let mut jac = vec![vec![0.0; n]; n];
+        for col in 0..n {
+            vars.insert(names[col].clone(), Dual::variable(y[col]));
+            for (row, c) in compiled.iter().enumerate() {
+                jac[row][col] = c.eval_dual(&vars).map(|d| d.dot).unwrap_or(f64::NAN);
+            }
+            vars.insert(names[col].clone(), Dual::constant(y[col]));
+        }
+        Jacobian::Dense(jac)
+    }
+
+    fn dual_vars(&self, t: f64, y: &[f64]) -> HashMap<String, Dual> {
+        let mut vars: HashMap<String, Dual> = self
+            .system
+            .params
+            .iter()
+            .map(|(k, v)| (k.clone(), Dual::constant(*v)))
+            .collect();
+        vars.insert("t".to_string(), Dual::constant(t));
+        for (i, v) in y.iter().enumerate() {
+            vars.insert(format!("y{}", i), Dual::constant(*v));
+        }
+        vars
+    }
+
+    /// One dual pass per colour, seeding every column of that colour at once.
+    fn colored_dual_jacobian(
+        &self,
+        compiled: &[CompiledExpr],
+        pattern: &Sparsity,
+        t: f64,
+        y: &[f64],
+    ) -> CsrMatrix {
+        self.evals.set(self.evals.get() + 1);
+        let mut vars = self.dual_vars(t, y);
+        let mut values = vec![0.0; pattern.nnz()];
+        for (color, cols) in pattern.groups().iter().enumerate() {
+            for &c in cols {
+                vars.insert(format!("y{}", c), Dual::variable(y[c]));
+            }
+            for (row, _, pos) in pattern.entries_of_color(color) {
+                values[pos] = compiled[row].eval_dual(&vars).map(|d| d.dot).unwrap_or(f64::NAN);
+            }
+            for &c in cols {
+                vars.insert(format!("y{}", c), Dual::constant(y[c]));
+            }
+        }
+        pattern.to_csr(values)
+    }
+
+    fn finite_diff_jacobian(&self, t: f64, y: &[f64]) -> Vec<Vec<f64>> {
+        let n = y.len();
+        let f0 = self.eval(t, y);
+        let mut jac = vec![vec![0.0; n]; n];
+        for col in 0..n {
+            let eps = f64::EPSILON.sqrt() * y[col].abs().max(1.0);
+            let mut y_pert = y.to_vec();
+            y_pert[col] += eps;
+            let f_pert = self.eval(t, &y_pert);
+            for row in 0..n {
+                jac[row][col] = (f_pert[row] - f0[row]) / eps;
+            }
+        }
+        jac
+    }
+}
+
+/// Pattern of `equations` over states `y0..y{n-1}`.
+fn structural_sparsity(equations: &[String], n: usize) -> Sparsity {
+    let index = (0..n).map(|i| (format!("y{}", i), i)).collect();
+    dependency_pattern(equations, &index)
+}
+
+/// Pattern of `equations` (one row each) over the variables in `index`, from
+/// the names each equation reads. A computed subscript such as `y[k]`, or an
+/// equation that does not compile, makes the whole row nonzero.
+pub fn dependency_pattern(equations: &[String], index: &HashMap<String, usize>) -> Sparsity {
+    let n = index.len();
+    let rows = equations
+        .iter()
+        .map(|eq| match compile(eq) {
+            Ok(c) => {
+                let deps = c.dependencies();
+                if deps.vectors.is_empty() {
+                    deps.scalars.iter().filter_map(|name| index.get(name).copied()).collect()
+                } else {
+                    (0..n).collect()
+                }
+            }
+            Err(_) => (0..n).collect(),
+        })
+        .collect();
+    Sparsity::from_rows(n, rows)
+}
+
+/// Jacobian of `f` at `x` on `pattern` by forward differences, one evaluation
+/// per colour plus one at `x`.
+pub fn colored_difference(
+    pattern: &Sparsity,
+    x: &[f64],
+    mut f: impl FnMut(&[f64]) -> Vec<f64>,
+) -> CsrMatrix {
+    let f0 = f(x);
+    let mut values = vec![0.0; pattern.nnz()];
+    for (color, cols) in pattern.groups().iter().enumerate() {
+        let mut x_pert = x.to_vec();
+        let mut eps = vec![0.0; x.len()];
+        for &c in cols {
+            eps[c] = f64::EPSILON.sqrt() * x[c].abs().max(1.0);
+            x_pert[c] += eps[c];
+        }
+        let f_pert = f(&x_pert);
+        for (row, col, pos) in pattern.entries_of_color(color) {
+            values[pos] = (f_pert[row] - f0[row]) / eps[col];
+        }
+    }
+    pattern.to_csr(values)
+}
+
+/// Jacobian kept across steps while simplified Newton converges fast enough.
+#[derive(Default)]
+pub struct JacobianCache {
+    jac: Option<Jacobian>,
+    fresh: bool,
+    evaluations: usize,
+}
+
+impl JacobianCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The Jacobian to use for a step starting at `(t, y)`; evaluated there
+    /// unless a previous one is still trusted.
+    pub fn get(&mut self, rhs: &dyn OdeRhs, t: f64, y: &[f64]) -> &Jacobian {
+        if self.jac.is_none() {
+            self.evaluations += 1;
+            self.fresh = true;
+        }
+        self.jac.get_or_insert_with(|| rhs.jacobian(t, y))
+    }
+
+    /// Whether the current Jacobian was evaluated for the step being attempted.
+    pub fn is_fresh(&self) -> bool {
+        self.jac.is_some() && self.fresh
+    }
+
+    /// Drop the Jacobian so the next [`get`](Self::get) re-evaluates it.
+    pub fn invalidate(&mut self) {
+        self.jac = None;
+    }
+
+    /// Record an accepted step whose Newton iteration contracted at rate `theta`.
+    pub fn accept(&mut self, theta: f64) {
+        if theta > REUSE_THETA {
+            self.jac = None;
+        } else {
+            self.fresh = false;
+        }
+    }
+
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+}
+
+/// LU factorisation with partial pivoting of a dense row-major n×n matrix.
+pub struct Lu {
+    n: usize,
+    lu: Vec<f64>,
+    piv: Vec<usize>,
+}
+
+impl Lu {
+    /// Factor `a`; `None` if it is numerically singular.
+    pub fn factor(n: usize, mut a: Vec<f64>) -> Option<Self> {
+        let mut piv: Vec<usize> = (0..n).collect();
+        for k in 0..n {
+            let p = (k..n).max_by(|&i, &j| a[i * n + k].abs().total_cmp(&a[j * n + k].abs()))?;
+            if a[p * n + k].abs() < 1e-300 || !a[p * n + k].is_finite() {
+                return None;
+            }
+            if p != k {
+                for c in 0..n {
+                    a.swap(k * n + c, p * n + c);
+                }
+                piv.swap(k, p);
+            }
+            let pivot = a[k * n + k];
+            for i in (k + 1)..n {
+                let f = a[i * n + k] / pivot;
+                a[i * n + k] = f;
+                if f != 0.0 {
+                    for c in (k + 1)..n {
+                        a[i * n + c] -= f * a[k * n + c];
+                    }
+                }
+            }
+        }
+        Some(Lu { n, lu: a, piv })
+    }
+
+    pub fn solve(&self, b: &[f64]) -> Vec<f64> {
+        let n = self.n;
+        let mut x: Vec<f64> = self.piv.iter().map(|&p| b[p]).collect();
+        for i in 0..n {
+            let s: f64 = (0..i).map(|c| self.lu[i * n + c] * x[c]).sum();
+            x[i] -= s;
+        }
+        for i in (0..n).rev() {
+            let s: f64 = ((i + 1)..n).map(|c| self.lu[i * n + c] * x[c]).sum();
+            x[i] = (x[i] - s) / self.lu[i * n + i];
+        }
+        x
+    }
+}
+
+pub(crate) fn norm(v: &[f64]) -> f64 {
+    v.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ode::test_fixtures::mol_heat;
+
+    fn system(eqs: &[&str], params: &[(&str, f64)]) -> OdeSystem {
+        OdeSystem {
+            equations: eqs.iter().map(|s| s.to_string()).collect(),
+            state_names: (0..eqs.len()).map(|i| format!("y{}", i)).collect(),
+            params: params.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+        }
+    }
+
+    #[test]
+    fn jacobian_is_exact() {
+        // Robertson kinetics.
+        let sys = system(
+            &[
+                "-k1*y0 + k3*y1*y2",
+                "k1*y0 - k3*y1*y2 - k2*y1^2",
+                "k2*y1^2",
+            ],
+            &[("k1", 0.04), ("k2", 3e7), ("k3", 1e4)],
+        );
+        let rhs = CompiledRhs::new(&sys);
+        assert!(rhs.is_exact());
+        let y = [0.9, 2e-5, 0.1];
+        let j = rhs.jacobian(0.0, &y);
+        let expected = [
+            [-0.04, 1e4 * y[2], 1e4 * y[1]],
+            [0.04, -1e4 * y[2] - 6e7 * y[1], -1e4 * y[1]],
+            [0.0, 6e7 * y[1], 0.0],
+        ];
+        for r in 0..3 {
+            for c in 0..3 {
+                assert!((j.get(r, c) - expected[r][c]).abs() <= 1e-12 * expected[r][c].abs().max(1.0));
+            }
+        }
+    }
+
+    #[test]
+    fn falls_back_to_finite_differences() {
+        let sys = system(&["unknownfn(y0)", "y0"], &[]);
+        let rhs = CompiledRhs::new(&sys);
+        assert!(!rhs.is_exact());
+        let j = rhs.jacobian(0.0, &[1.0, 2.0]);
+        assert!((j.get(1, 0) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn cache_reuse_follows_contraction_rate() {
+        let sys = system(&["-y0"], &[]);
+        let rhs = CompiledRhs::new(&sys);
+        let mut cache = JacobianCache::new();
+        cache.get(&rhs, 0.0, &[1.0]);
+        assert!(cache.is_fresh());
+        cache.accept(1e-6);
+        cache.get(&rhs, 0.1, &[0.9]);
+        assert!(!cache.is_fresh());
+        assert_eq!(cache.evaluations(), 1);
+        cache.accept(0.5);
+        cache.get(&rhs, 0.2, &[0.8]);
+        assert_eq!(cache.evaluations(), 2);
+    }
+
+    #[test]
+    fn tridiagonal_pattern_needs_three_colours() {
+        let (sys, _) = mol_heat(100, "+ sin({y})");
+        let rhs = CompiledRhs::new(&sys);
+        let y: Vec<f64> = (0..100).map(|i| (i as f64 * 0.1).sin()).collect();
+        let pattern = rhs.sparsity(100).expect("large banded system goes sparse");
+        assert_eq!(pattern.nnz(), 3 * 100 - 2);
+        assert_eq!(pattern.n_colors(), 3);
+        assert_eq!(pattern.row(5), [4, 5, 6]);
+
+        let mut dense_sys = sys.clone();
+        dense_sys.params.insert("sparse_jacobian".to_string(), 0.0);
+        let dense = CompiledRhs::new(&dense_sys);
+        assert!(dense.sparsity(100).is_none());
+        let (js, jd) = (rhs.jacobian(0.0, &y), dense.jacobian(0.0, &y));
+        assert!(matches!(js, Jacobian::Sparse(_)) && matches!(jd, Jacobian::Dense(_)));
+        for r in 0..100 {
+            for c in 0..100 {
+                assert_eq!(js.get(r, c), jd.get(r, c));
+            }
+        }
+    }
+
+    #[test]
+    fn sparse_iteration_matrix_solves_like_dense() {
+        let (sys, _) = mol_heat(80, "+ sin({y})");
+        let y = vec![0.5; 80];
+        let b: Vec<f64> = (0..80).map(|i| 1.0 + (i % 3) as f64).collect();
+        let sparse = CompiledRhs::new(&sys).jacobian(0.0, &y);
+        let mut dense_sys = sys.clone();
+        dense_sys.params.insert("sparse_jacobian".to_string(), 0.0);
+        let dense = CompiledRhs::new(&dense_sys).jacobian(0.0, &y);
+        let xs = sparse.shifted(0.01).unwrap().solve(&b).unwrap();
+        let xd = dense.shifted(0.01).unwrap().solve(&b).unwrap();
+        for (a, e) in xs.iter().zip(&xd) {
+            assert!((a - e).abs() < 1e-9, "{a} vs {e}");
+        }
+        let a = [[0.2, -0.05], [0.4, 0.1]];
+        let xs = sparse.kronecker(&a).unwrap().solve(&[b.clone(), b.clone()].concat()).unwrap();
+        let xd = dense.kronecker(&a).unwrap().solve(&[b.clone(), b].concat()).unwrap();
+        for (a, e) in xs.iter().zip(&xd) {
+            assert!((a - e).abs() < 1e-9, "{a} vs {e}");
+        }
+    }
+
+    #[test]
+    fn sparse_iteration_matrix_reports_failures() {
+        // Singular (rows sum to zero) with nonzero ILU(0) pivots: GMRES cannot converge.
+        let cyclic = CsrMatrix {
+            rows: 3,
+            cols: 3,
+            row_ptrs: vec![0, 2, 4, 6],
+            col_indices: vec![0, 1, 1, 2, 0, 2],
+            values: vec![1.0, -1.0, 1.0, -1.0, -1.0, 1.0],
+        };
+        let m = IterationMatrix::sparse(cyclic).expect("nonzero pivots");
+        assert!(m.solve(&[1.0, 0.0, 0.0]).is_none());
+        let ones = CsrMatrix { rows: 2, cols: 2, row_ptrs: vec![0, 2, 4], col_indices: vec![0, 1, 0, 1], values: vec![1.0; 4] };
+        assert!(IterationMatrix::sparse(ones).is_none());
+    }
+
+    #[test]
+    fn uncompiled_rows_are_dense_and_use_coloured_differences() {
+        let mut eqs = vec!["unknownfn(y0)".to_string(), "y1*y2".to_string()];
+        eqs.extend((2..70).map(|i| format!("-y{i}")));
+        let refs: Vec<&str> = eqs.iter().map(String::as_str).collect();
+        let sys = system(&refs, &[]);
+        let rhs = CompiledRhs::new(&sys);
+        assert!(!rhs.is_exact());
+        let pattern = rhs.sparsity(70).expect("sparse");
+        assert_eq!(pattern.row(0).len(), 70);
+        assert_eq!(pattern.row(1), [1, 2]);
+        assert_eq!(pattern.row(40), [40]);
+        let y = vec![2.0; 70];
+        let j = rhs.jacobian(0.0, &y);
+        assert!((j.get(1, 2) - 2.0).abs() < 1e-6 && (j.get(40, 40) + 1.0).abs() < 1e-6);
+        assert_eq!(j.get(40, 41), 0.0);
+    }
+
+    #[test]
+    fn lu_solves_pivoted_system() {
+        let lu = Lu::factor(3, vec![0.0, 2.0, 1.0, 1.0, 1.0, 0.0, 3.0, 0.0, 1.0]).unwrap();
+        let x = lu.solve(&[7.0, 3.0, 6.0]);
+        for (xi, e) in x.iter().zip([1.0, 2.0, 3.0]) {
+            assert!((xi - e).abs() < 1e-12);
+        }
+        assert!(Lu::factor(2, vec![1.0, 2.0, 2.0, 4.0]).is_none());
+    }
+}
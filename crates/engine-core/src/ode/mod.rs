@@ -10,13 +10,20 @@ pub mod adjoint;
 pub mod bdf;
 pub mod fem2d;
 pub mod dae;
+pub mod dde;
+pub mod dense;
 pub mod event;
+pub mod jacobian;
 pub mod pde1d;
 pub mod radau;
 pub mod rk4;
 pub mod rk45;
+pub mod sde;
 pub mod steady_state;
 pub mod symplectic;
 pub mod types;
 
-pub use types::{OdeResult, OdeSolverConfig, OdeSystem};
+#[cfg(test)]
+pub(crate) mod test_fixtures;
+
+pub use types::{OdeResult, OdeSolverConfig, OdeSystem, OutputTimes};
@@ -8,10 +8,14 @@
 //!   - Wave equation:         u_tt = c² * u_xx  (reformulated as first-order system)
 //!
 //! Spatial discretisation: second-order central differences (uniform grid).
-//! Time integration: RK4 (fixed step) on the resulting ODE system.
+//! Time integration: RK4 (fixed step, CFL-limited) on the resulting ODE
+//! system, or BDF ([`Pde1dMethod::Bdf`]), which is not step-limited by the
+//! grid and uses the sparse (banded) Jacobian path for fine grids.
 //! Boundary conditions: Dirichlet (fixed value) or Neumann (zero flux).
 
-use crate::ode::OdeSolverConfig;
+use super::bdf::solve_bdf;
+use crate::ode::{OdeSolverConfig, OdeSystem};
+use std::collections::HashMap;
 
 /// PDE type supported by the 1D MOL solver.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -35,6 +39,15 @@ pub enum BcType {
     Neumann,
 }
 
+/// Time integration scheme for the MOL system.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pde1dMethod {
+    /// Explicit RK4 with the step reduced to the CFL limit.
+    Rk4,
+    /// Implicit BDF-2 on the generated equations; suited to fine grids.
+    Bdf,
+}
+
 /// Configuration for the 1D PDE solver.
 #[derive(Debug, Clone)]
 pub struct Pde1dConfig {
@@ -64,6 +77,8 @@ pub struct Pde1dConfig {
     pub solver: OdeSolverConfig,
     /// How many time snapshots to store (evenly spaced).
     pub n_snapshots: usize,
+    /// Time integration scheme.
+    pub method: Pde1dMethod,
 }
 
 /// Result of a 1D PDE solve.
@@ -79,8 +94,11 @@ pub struct Pde1dResult {
     pub steps: usize,
 }
 
-/// Solve a 1D PDE using the Method of Lines + RK4.
+/// Solve a 1D PDE using the Method of Lines + RK4 or BDF.
 pub fn solve_pde1d(cfg: &Pde1dConfig) -> Pde1dResult {
+    if cfg.method == Pde1dMethod::Bdf {
+        return solve_pde1d_bdf(cfg);
+    }
     let n = cfg.n_points.max(3);
     let dx = (cfg.x_end - cfg.x0) / (n - 1) as f64;
     let x: Vec<f64> = (0..n).map(|i| cfg.x0 + i as f64 * dx).collect();
@@ -161,6 +179,102 @@ pub fn solve_pde1d(cfg: &Pde1dConfig) -> Pde1dResult {
     }
 }
 
+/// Method of lines with BDF: the semi-discrete system is written out as
+/// equations over `y0..`, so the implicit solver sees its banded structure.
+fn solve_pde1d_bdf(cfg: &Pde1dConfig) -> Pde1dResult {
+    let n = cfg.n_points.max(3);
+    let dx = (cfg.x_end - cfg.x0) / (n - 1) as f64;
+    let x: Vec<f64> = (0..n).map(|i| cfg.x0 + i as f64 * dx).collect();
+    let wave = cfg.pde_type == PdeType::Wave;
+
+    let mut state = cfg.u0.clone();
+    state.resize(n, 0.0);
+    if wave {
+        let mut ut = cfg.ut0.clone();
+        ut.resize(n, 0.0);
+        state.extend(ut);
+    }
+    apply_bc(&mut state, cfg, n, wave);
+
+    let system = OdeSystem {
+        equations: mol_equations(cfg, n),
+        state_names: (0..state.len()).map(|i| format!("y{}", i)).collect(),
+        params: HashMap::from([
+            ("D".to_string(), cfg.diffusivity),
+            ("c".to_string(), cfg.velocity),
+            ("src".to_string(), cfg.source),
+            ("inv_dx".to_string(), 1.0 / dx),
+            ("inv_dx2".to_string(), 1.0 / (dx * dx)),
+            ("bdf_order".to_string(), 2.0),
+        ]),
+    };
+    let result = solve_bdf(&system, &state, &cfg.solver);
+
+    // Snapshots at evenly spaced times, linearly interpolated between steps.
+    let t_start = cfg.solver.t_start;
+    let t_last = result.t.last().copied().unwrap_or(t_start);
+    let n_snapshots = cfg.n_snapshots.max(2).min(result.t.len().max(2));
+    let mut t_snaps = Vec::with_capacity(n_snapshots);
+    let mut snapshots = Vec::with_capacity(n_snapshots);
+    let mut k = 0;
+    for s in 0..n_snapshots {
+        let ts = t_start + (t_last - t_start) * s as f64 / (n_snapshots - 1) as f64;
+        while k + 1 < result.t.len() - 1 && result.t[k + 1] < ts {
+            k += 1;
+        }
+        let (ya, yb) = (&result.states[k], &result.states[(k + 1).min(result.t.len() - 1)]);
+        let span = result.t[(k + 1).min(result.t.len() - 1)] - result.t[k];
+        let w = if span > 0.0 { ((ts - result.t[k]) / span).clamp(0.0, 1.0) } else { 0.0 };
+        let mut snap: Vec<f64> = ya.iter().zip(yb).map(|(a, b)| a + w * (b - a)).collect();
+        // Dirichlet rows are held by `0` derivatives; restore them exactly.
+        apply_bc(&mut snap, cfg, n, wave);
+        snap.truncate(n);
+        snapshots.push(snap);
+        t_snaps.push(ts);
+    }
+
+    Pde1dResult { t: t_snaps, x, snapshots, steps: result.steps }
+}
+
+/// Right-hand sides of the MOL system as expressions over `y0..` and the
+/// parameters `D`, `c`, `src`, `inv_dx`, `inv_dx2`; mirrors [`rhs`].
+fn mol_equations(cfg: &Pde1dConfig, n: usize) -> Vec<String> {
+    let lap = |i: usize| format!("(y{} - 2*y{} + y{})*inv_dx2", i + 1, i, i - 1);
+    let neumann_left = "2*(y1 - y0)*inv_dx2".to_string();
+    let neumann_right = format!("2*(y{} - y{})*inv_dx2", n - 2, n - 1);
+    let boundary = |bc: BcType, uxx: String, coeff: &str| match bc {
+        BcType::Dirichlet(_) => "0".to_string(),
+        BcType::Neumann => format!("{coeff}*{uxx} + src"),
+    };
+
+    if cfg.pde_type == PdeType::Wave {
+        let mut eqs: Vec<String> = (0..n).map(|i| format!("y{}", n + i)).collect();
+        eqs.push(boundary(cfg.bc_left, neumann_left, "c^2"));
+        eqs.extend((1..n - 1).map(|i| format!("c^2*{} + src", lap(i))));
+        eqs.push(boundary(cfg.bc_right, neumann_right, "c^2"));
+        return eqs;
+    }
+
+    let advection = matches!(cfg.pde_type, PdeType::Advection | PdeType::AdvectionDiffusion);
+    let diffusion = matches!(cfg.pde_type, PdeType::Heat | PdeType::AdvectionDiffusion);
+    let mut eqs = vec![boundary(cfg.bc_left, neumann_left, "D")];
+    eqs.extend((1..n - 1).map(|i| {
+        let mut terms = Vec::new();
+        if advection {
+            // Upwind differences, as in the explicit scheme.
+            let (a, b) = if cfg.velocity >= 0.0 { (i, i - 1) } else { (i + 1, i) };
+            terms.push(format!("-c*(y{a} - y{b})*inv_dx"));
+        }
+        if diffusion {
+            terms.push(format!("D*{}", lap(i)));
+        }
+        terms.push("src".to_string());
+        terms.join(" + ")
+    }));
+    eqs.push(boundary(cfg.bc_right, neumann_right, "D"));
+    eqs
+}
+
 /// Compute the right-hand side (time derivative) of the MOL ODE system.
 fn rhs(cfg: &Pde1dConfig, state: &[f64], n: usize, dx: f64, wave: bool) -> Vec<f64> {
     let mut dudt = vec![0.0f64; state.len()];
@@ -304,6 +418,14 @@ pub fn parse_pde_type(s: &str) -> PdeType {
     }
 }
 
+/// Parse the time integration method ("rk4" or "bdf"/"implicit").
+pub fn parse_method(s: &str) -> Pde1dMethod {
+    match s.trim().to_lowercase().as_str() {
+        "bdf" | "implicit" => Pde1dMethod::Bdf,
+        _ => Pde1dMethod::Rk4,
+    }
+}
+
 /// Parse boundary condition from string (e.g., "dirichlet:0.0" or "neumann").
 pub fn parse_bc(s: &str) -> BcType {
     let s = s.trim().to_lowercase();
@@ -327,6 +449,7 @@ mod tests {
             dt: 0.001,
             tolerance: 1e-6,
             max_steps: 200_000,
+            ..Default::default()
         }
     }
 
@@ -349,6 +472,7 @@ mod tests {
             ut0: vec![],
             solver: default_solver(),
             n_snapshots: 5,
+            method: Pde1dMethod::Rk4,
         };
         let result = solve_pde1d(&cfg);
         assert!(result.snapshots.len() >= 2);
@@ -381,6 +505,7 @@ mod tests {
             ut0: vec![],
             solver: OdeSolverConfig { t_end: 0.1, dt: 0.005, ..default_solver() },
             n_snapshots: 5,
+            method: Pde1dMethod::Rk4,
         };
         let result = solve_pde1d(&cfg);
         assert!(result.snapshots.len() >= 2);
@@ -417,6 +542,7 @@ mod tests {
             ut0: vec![0.0f64; n],
             solver: OdeSolverConfig { t_end: 0.5, dt: 0.01, ..default_solver() },
             n_snapshots: 5,
+            method: Pde1dMethod::Rk4,
         };
         let result = solve_pde1d(&cfg);
         // BCs should hold
@@ -442,6 +568,7 @@ mod tests {
             ut0: vec![],
             solver: OdeSolverConfig { t_end: 0.05, dt: 0.005, ..default_solver() },
             n_snapshots: 3,
+            method: Pde1dMethod::Rk4,
         };
         let result = solve_pde1d(&cfg);
         let table = pde1d_result_to_table(&result);
@@ -452,4 +579,61 @@ mod tests {
             panic!("Expected Table");
         }
     }
+
+    fn heat_cfg(n: usize, method: Pde1dMethod) -> Pde1dConfig {
+        let u0 = (0..n)
+            .map(|i| (std::f64::consts::PI * i as f64 / (n - 1) as f64).sin())
+            .collect();
+        Pde1dConfig {
+            pde_type: PdeType::Heat,
+            diffusivity: 0.1,
+            velocity: 0.0,
+            source: 0.0,
+            x0: 0.0,
+            x_end: 1.0,
+            n_points: n,
+            bc_left: BcType::Dirichlet(0.0),
+            bc_right: BcType::Dirichlet(0.0),
+            u0,
+            ut0: vec![],
+            solver: OdeSolverConfig { t_end: 0.2, dt: 0.002, ..default_solver() },
+            n_snapshots: 5,
+            method,
+        }
+    }
+
+    #[test]
+    fn bdf_matches_rk4_on_heat_equation() {
+        let rk4 = solve_pde1d(&heat_cfg(41, Pde1dMethod::Rk4));
+        let bdf = solve_pde1d(&heat_cfg(41, Pde1dMethod::Bdf));
+        assert_eq!(bdf.snapshots.len(), 5);
+        assert!((bdf.t[4] - 0.2).abs() < 1e-12);
+        // Exact mode decay e^{-Dπ²t} at the midpoint.
+        let exact = (-0.1 * std::f64::consts::PI.powi(2) * 0.2).exp();
+        let (a, b) = (rk4.snapshots[4][20], bdf.snapshots[4][20]);
+        assert!((a - exact).abs() < 1e-3 && (b - exact).abs() < 5e-3, "rk4 {a}, bdf {b}, exact {exact}");
+        assert_eq!(bdf.snapshots[4][0], 0.0);
+    }
+
+    #[test]
+    fn bdf_runs_fine_grids_without_cfl_limit() {
+        // RK4 would need ~dx²/D steps; BDF takes the requested ones.
+        let result = solve_pde1d(&heat_cfg(2001, Pde1dMethod::Bdf));
+        assert!(result.steps <= 100, "steps = {}", result.steps);
+        let exact = (-0.1 * std::f64::consts::PI.powi(2) * 0.2).exp();
+        assert!((result.snapshots[4][1000] - exact).abs() < 5e-3);
+    }
+
+    #[test]
+    fn bdf_wave_keeps_dirichlet_boundaries() {
+        let mut cfg = heat_cfg(21, Pde1dMethod::Bdf);
+        cfg.pde_type = PdeType::Wave;
+        cfg.velocity = 1.0;
+        cfg.ut0 = vec![0.0; 21];
+        let result = solve_pde1d(&cfg);
+        let last = result.snapshots.last().unwrap();
+        assert_eq!(last[0], 0.0);
+        assert_eq!(last[20], 0.0);
+        assert!(last[10] < 1.0);
+    }
 }
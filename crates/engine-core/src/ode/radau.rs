@@ -4,12 +4,14 @@
 //! discontinuities. The 3-stage method achieves order 5 and damps spurious
 //! oscillations at infinity.
 //!
-//! The stage equations are solved simultaneously using Newton iteration on
-//! the block system with a finite-difference Jacobian.
+//! The stage equations are solved simultaneously by simplified Newton
+//! iteration on the fully coupled 3n×3n block system, with an exact Jacobian
+//! from forward-mode AD ([`super::jacobian`]).
 //!
 //! Reference: Hairer & Wanner, "Solving ODEs II" (2010), Chapter IV.
 
-use super::rk4::eval_rhs_pub;
+use super::dense::{no_observer, AcceptedStep, DenseRecorder, Interpolant, StepControl};
+use super::jacobian::{norm, CompiledRhs, ImplicitStats, JacobianCache, OdeRhs};
 use super::types::{OdeResult, OdeSolverConfig, OdeSystem};
 
 // ── Radau IIA (3-stage, order 5) Butcher tableau ────────────────────────────
@@ -41,28 +43,73 @@ const B1: f64 = A31;
 const B2: f64 = A32;
 const B3: f64 = A33;
 
+const A: [[f64; 3]; 3] = [[A11, A12, A13], [A21, A22, A23], [A31, A32, A33]];
+const C: [f64; 3] = [C1, C2, C3];
+
 /// Solve an ODE system using the 3-stage Radau IIA implicit Runge-Kutta method (order 5).
 ///
 /// Excellent for stiff and very stiff problems. The stage equations form a
-/// 3n×3n block system solved by Newton iteration per step.
+/// 3n×3n block system solved by Newton iteration per step. Output at
+/// [`OdeSolverConfig::output`] times comes from the collocation polynomial.
 pub fn solve_radau(
     system: &OdeSystem,
     y0: &[f64],
     config: &OdeSolverConfig,
 ) -> OdeResult {
-    let n = system.equations.len();
-    assert_eq!(n, y0.len(), "Equations must match initial state dimension");
+    solve_radau_with_stats(system, y0, config).0
+}
+
+/// [`solve_radau`], also returning how much work the Newton iterations did.
+///
+/// The stage derivatives `K = (K_1, K_2, K_3)` solve
+/// `G_i(K) = K_i - f(t + c_i·h, y + h·Σ_j a_ij·K_j) = 0`. Simplified Newton
+/// uses one exact Jacobian `J` at `(t, y)` for all stages, giving the fully
+/// coupled iteration matrix `I - h·(A ⊗ J)` (3n × 3n), factored once per step.
+/// `J` is reused across steps while the iteration contracts fast
+/// (see [`JacobianCache`]). For large sparse systems the block matrix is
+/// assembled in CSR and solved by ILU(0)-preconditioned GMRES.
+pub fn solve_radau_with_stats(
+    system: &OdeSystem,
+    y0: &[f64],
+    config: &OdeSolverConfig,
+) -> (OdeResult, ImplicitStats) {
+    integrate_radau(system, y0, config, &mut no_observer)
+}
 
+/// [`solve_radau_with_stats`], offering every accepted step to `on_step`,
+/// which may end the run inside the step (see [`super::event`]).
+pub fn integrate_radau(
+    system: &OdeSystem,
+    y0: &[f64],
+    config: &OdeSolverConfig,
+    on_step: &mut dyn FnMut(&AcceptedStep) -> StepControl,
+) -> (OdeResult, ImplicitStats) {
+    assert_eq!(system.equations.len(), y0.len(), "Equations must match initial state dimension");
+    integrate_radau_rhs(&CompiledRhs::new(system), &system.state_names, y0, config, on_step)
+}
+
+/// [`integrate_radau`] for any [`OdeRhs`], with `state_names` as the result's
+/// column headers.
+pub fn integrate_radau_rhs(
+    rhs: &dyn OdeRhs,
+    state_names: &[String],
+    y0: &[f64],
+    config: &OdeSolverConfig,
+    on_step: &mut dyn FnMut(&AcceptedStep) -> StepControl,
+) -> (OdeResult, ImplicitStats) {
+    let n = y0.len();
     let tol = config.tolerance;
-    let max_newton = 50usize;
+    let max_newton = 10usize;
     let max_steps = config.max_steps;
 
+    let mut jac = JacobianCache::new();
+    let mut stats = ImplicitStats::default();
+
     let mut t = config.t_start;
     let mut y = y0.to_vec();
     let mut h = config.dt;
 
-    let mut t_out = vec![t];
-    let mut states_out = vec![y.clone()];
+    let mut out = DenseRecorder::new(config, &y);
     let mut steps = 0usize;
 
     while t < config.t_end && steps < max_steps {
@@ -71,181 +118,121 @@ pub fn solve_radau(
         }
         if h < 1e-15 { break; }
 
-        // Stage times
-        let t1 = t + C1 * h;
-        let t2 = t + C2 * h;
-        let t3 = t + C3 * h;
+        // Initial guess for stage derivatives K1, K2, K3 (stacked, 3n):
+        // explicit Euler, Ki ≈ f(t, y).
+        let f0 = rhs.eval(t, &y);
+        let mut k: Vec<f64> = f0.iter().chain(&f0).chain(&f0).copied().collect();
 
-        // Initial guess for stage values K1, K2, K3 (derivatives at each stage)
-        // Use explicit Euler: Ki ≈ f(t, y)
-        let f0 = eval_rhs_pub(system, t, &y);
-        let mut k1 = f0.clone();
-        let mut k2 = f0.clone();
-        let mut k3 = f0.clone();
+        // Fully coupled iteration matrix M = I - h·(A ⊗ J):
+        // block (i, j) is δ_ij·I - h·a_ij·J.
+        let m3 = 3 * n;
+        stats.lu_factorizations += 1;
+        let lu = jac.get(rhs, t, &y).kronecker(&A.map(|row| row.map(|a| h * a)));
 
-        // Newton iteration on the 3n × 3n stage system:
-        // G_i(K) = K_i - f(t_i, y + h·Σ_j a_{ij}·K_j) = 0  for i=1,2,3
         let mut converged = false;
-        for _iter in 0..max_newton {
-            // Compute stage values Y1, Y2, Y3
-            let y1: Vec<f64> = (0..n).map(|i| y[i] + h * (A11 * k1[i] + A12 * k2[i] + A13 * k3[i])).collect();
-            let y2: Vec<f64> = (0..n).map(|i| y[i] + h * (A21 * k1[i] + A22 * k2[i] + A23 * k3[i])).collect();
-            let y3: Vec<f64> = (0..n).map(|i| y[i] + h * (A31 * k1[i] + A32 * k2[i] + A33 * k3[i])).collect();
-
-            // Evaluate f at stage points
-            let f1 = eval_rhs_pub(system, t1, &y1);
-            let f2 = eval_rhs_pub(system, t2, &y2);
-            let f3 = eval_rhs_pub(system, t3, &y3);
-
-            // Residuals G_i = K_i - f_i
-            let r1: Vec<f64> = (0..n).map(|i| k1[i] - f1[i]).collect();
-            let r2: Vec<f64> = (0..n).map(|i| k2[i] - f2[i]).collect();
-            let r3: Vec<f64> = (0..n).map(|i| k3[i] - f3[i]).collect();
-
-            let res_norm = r1.iter().chain(r2.iter()).chain(r3.iter())
-                .map(|r| r * r).sum::<f64>().sqrt();
-
-            let y_norm = 1.0 + y.iter().map(|v| v * v).sum::<f64>().sqrt();
-            if res_norm < tol * y_norm {
-                converged = true;
-                break;
-            }
-
-            // Build block-diagonal Jacobian approximation (neglect off-diagonal stage couplings)
-            // Full system Jacobian via finite differences for each stage
-            let eps = 1e-7_f64;
-
-            // We use a simplified approach: decouple stages and solve each 3n×3n system
-            // as three n×n systems per Newton step (block-diagonal approximation).
-            // Each block: ∂G_i/∂K_j = δ_{ij}I - h·a_{ij}·J_f(t_i, Y_i)
-
-            // Compute J_f at each stage (n×n matrices)
-            let jf1 = finite_diff_jacobian(system, t1, &y1, &f1, eps, n);
-            let jf2 = finite_diff_jacobian(system, t2, &y2, &f2, eps, n);
-            let jf3 = finite_diff_jacobian(system, t3, &y3, &f3, eps, n);
-
-            // For the decoupled Newton step, use diagonal approximation:
-            // ΔK_1 ≈ (I - h·A11·J1)⁻¹ · R1  (ignoring off-diagonal stage coupling)
-            // This is a first-order approximation but effective in practice.
-            let dk1 = solve_with_jacobian(&jf1, &r1, h * A11, n);
-            let dk2 = solve_with_jacobian(&jf2, &r2, h * A22, n);
-            let dk3 = solve_with_jacobian(&jf3, &r3, h * A33, n);
-
-            for i in 0..n {
-                k1[i] -= dk1[i];
-                k2[i] -= dk2[i];
-                k3[i] -= dk3[i];
+        let mut theta = 0.0_f64;
+        if let Some(lu) = &lu {
+            let mut prev_delta: Option<f64> = None;
+            for _iter in 0..max_newton {
+                // Residuals G_i = K_i - f(t_i, Y_i), with Y_i = y + h·Σ_j a_ij·K_j
+                let mut residual = Vec::with_capacity(m3);
+                for (i, a_row) in A.iter().enumerate() {
+                    let yi: Vec<f64> = (0..n)
+                        .map(|r| y[r] + h * (0..3).map(|j| a_row[j] * k[j * n + r]).sum::<f64>())
+                        .collect();
+                    let fi = rhs.eval(t + C[i] * h, &yi);
+                    residual.extend((0..n).map(|r| k[i * n + r] - fi[r]));
+                }
+
+                let y_norm = 1.0 + norm(&y);
+                if norm(&residual) < tol * y_norm {
+                    converged = true;
+                    break;
+                }
+
+                stats.newton_iterations += 1;
+                let Some(delta) = lu.solve(&residual) else {
+                    break;
+                };
+                for (ki, di) in k.iter_mut().zip(&delta) {
+                    *ki -= di;
+                }
+
+                // Contraction rate θ = ‖ΔK_k‖ / ‖ΔK_{k-1}‖; θ ≥ 1 means divergence.
+                let d = norm(&delta);
+                if let Some(prev) = prev_delta {
+                    theta = if prev > 0.0 { d / prev } else { 0.0 };
+                    if theta >= 1.0 || !theta.is_finite() {
+                        break;
+                    }
+                }
+                prev_delta = Some(d);
             }
         }
 
         if !converged {
+            stats.rejected_steps += 1;
+            // A stale Jacobian gets one refresh before the step is shrunk.
+            if !jac.is_fresh() {
+                jac.invalidate();
+                continue;
+            }
             // Reduce step size
             h *= 0.5;
             if h < 1e-14 { break; }
             continue;
         }
+        jac.accept(theta);
 
         // Advance solution using the last stage value (y3 ≈ y(t+h) since c3=1)
         let y_new: Vec<f64> = (0..n)
-            .map(|i| y[i] + h * (B1 * k1[i] + B2 * k2[i] + B3 * k3[i]))
+            .map(|i| y[i] + h * (B1 * k[i] + B2 * k[n + i] + B3 * k[2 * n + i]))
             .collect();
 
-        t += h;
-        y = y_new.clone();
-
-        t_out.push(t);
-        states_out.push(y_new);
+        // Dense output: the collocation polynomial through y and the stages.
+        let interp = || {
+            let mut ts = vec![t];
+            let mut ys = vec![y.clone()];
+            for (i, a_row) in A.iter().enumerate() {
+                ts.push(t + C[i] * h);
+                ys.push((0..n).map(|r| y[r] + h * (0..3).map(|j| a_row[j] * k[j * n + r]).sum::<f64>()).collect());
+            }
+            Interpolant::Lagrange { ts, ys }
+        };
         steps += 1;
+        let control = on_step(&AcceptedStep::new(t, t + h, &y, &y_new, &interp));
+        if let StepControl::Stop { t: t_stop, y: y_stop } = control {
+            out.step(t_stop, &y_stop, interp);
+            break;
+        }
+        out.step(t + h, &y_new, interp);
+
+        t += h;
+        y = y_new;
 
         // Mild step size growth
         h = (h * 1.05).min(config.dt * 5.0);
     }
 
     let mut column_names = vec!["t".to_string()];
-    column_names.extend(system.state_names.clone());
+    column_names.extend(state_names.iter().cloned());
 
-    OdeResult {
+    stats.rhs_evals = rhs.evals();
+    stats.jacobian_evals = jac.evaluations();
+    let (t_out, states_out) = out.finish();
+    let result = OdeResult {
         t: t_out,
         states: states_out,
         column_names,
         steps,
-    }
-}
-
-/// Compute the n×n finite-difference Jacobian of f at (t, y).
-fn finite_diff_jacobian(
-    system: &OdeSystem,
-    t: f64,
-    y: &[f64],
-    f0: &[f64],
-    eps: f64,
-    n: usize,
-) -> Vec<Vec<f64>> {
-    let mut jac = vec![vec![0.0; n]; n];
-    for col in 0..n {
-        let mut y_pert = y.to_vec();
-        y_pert[col] += eps;
-        let f_pert = eval_rhs_pub(system, t, &y_pert);
-        for row in 0..n {
-            jac[row][col] = (f_pert[row] - f0[row]) / eps;
-        }
-    }
-    jac
-}
-
-/// Solve (I - scale·Jf)·x = rhs via Gauss-Jordan.
-fn solve_with_jacobian(jf: &[Vec<f64>], rhs: &[f64], scale: f64, n: usize) -> Vec<f64> {
-    // Build A = I - scale·Jf
-    let mut a: Vec<Vec<f64>> = (0..n)
-        .map(|row| {
-            (0..n)
-                .map(|col| {
-                    let eye = if row == col { 1.0 } else { 0.0 };
-                    eye - scale * jf[row][col]
-                })
-                .collect()
-        })
-        .collect();
-
-    // Augment with rhs
-    for (row, r) in rhs.iter().enumerate() {
-        a[row].push(*r);
-    }
-
-    // Gauss-Jordan
-    for col in 0..n {
-        let mut max_row = col;
-        let mut max_val = a[col][col].abs();
-        for row in (col + 1)..n {
-            if a[row][col].abs() > max_val {
-                max_val = a[row][col].abs();
-                max_row = row;
-            }
-        }
-        a.swap(col, max_row);
-
-        let pivot = a[col][col];
-        if pivot.abs() < 1e-15 { continue; }
-
-        for j in col..=n {
-            a[col][j] /= pivot;
-        }
-
-        for row in 0..n {
-            if row == col { continue; }
-            let factor = a[row][col];
-            for j in col..=n {
-                a[row][j] -= factor * a[col][j];
-            }
-        }
-    }
-
-    (0..n).map(|i| a[i][n]).collect()
+    };
+    (result, stats)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ode::test_fixtures::{mol_heat, robertson};
     use std::collections::HashMap;
 
     fn sys_decay() -> (OdeSystem, Vec<f64>) {
@@ -258,7 +245,7 @@ mod tests {
     }
 
     fn cfg(t_end: f64, dt: f64) -> OdeSolverConfig {
-        OdeSolverConfig { t_start: 0.0, t_end, dt, tolerance: 1e-6, max_steps: 50_000 }
+        OdeSolverConfig { t_start: 0.0, t_end, dt, tolerance: 1e-6, max_steps: 50_000, ..Default::default() }
     }
 
     #[test]
@@ -301,4 +288,61 @@ mod tests {
         assert!((final_y - 1.0).abs() < 0.01,
             "Radau stiff: expected ~1.0, got {final_y:.6}");
     }
+
+    #[test]
+    fn radau_robertson_converges_with_coupled_newton() {
+        let (res, stats) = solve_radau_with_stats(&robertson(), &[1.0, 0.0, 0.0], &cfg(0.4, 1e-3));
+        assert!((res.t.last().unwrap() - 0.4).abs() < 1e-12);
+        let y = res.states.last().unwrap();
+        assert!((y[0] - 0.985172).abs() < 1e-4, "y0 = {}", y[0]);
+        assert!((y[1] - 3.3864e-5).abs() < 1e-6, "y1 = {}", y[1]);
+        assert!((y.iter().sum::<f64>() - 1.0).abs() < 1e-9, "mass is conserved");
+        assert!(stats.newton_iterations > 0);
+    }
+
+    #[test]
+    fn radau_coupled_newton_solves_linear_stages_exactly() {
+        // With the exact Jacobian and coupled stages, one Newton step solves a
+        // linear problem, so J is kept for the whole run.
+        let sys = OdeSystem {
+            equations: vec!["-1e4*y0 + y1".to_string(), "-y1".to_string()],
+            state_names: vec!["a".to_string(), "b".to_string()],
+            params: HashMap::new(),
+        };
+        let (res, stats) = solve_radau_with_stats(&sys, &[1.0, 1.0], &cfg(0.5, 0.01));
+        assert_eq!(stats.jacobian_evals, 1);
+        assert_eq!(stats.newton_iterations, res.steps);
+        let t = *res.t.last().unwrap();
+        // b = e^{-t}; a relaxes onto b/(1e4 - 1) after the initial transient.
+        let b = (-t).exp();
+        assert!((res.states.last().unwrap()[1] - b).abs() < 1e-8);
+    }
+
+    #[test]
+    fn radau_sparse_stage_system_matches_dense() {
+        let (sys, u0) = mol_heat(100, "");
+        let mut dense = sys.clone();
+        dense.params.insert("sparse_jacobian".to_string(), 0.0);
+        let (rs, ss) = solve_radau_with_stats(&sys, &u0, &cfg(0.05, 5e-3));
+        let (rd, _) = solve_radau_with_stats(&dense, &u0, &cfg(0.05, 5e-3));
+        assert_eq!(ss.jacobian_evals, 1);
+        assert_eq!(rs.steps, rd.steps);
+        for (a, b) in rs.states.last().unwrap().iter().zip(rd.states.last().unwrap()) {
+            assert!((a - b).abs() < 1e-8, "{a} vs {b}");
+        }
+    }
+
+    #[test]
+    fn radau_dense_output_uses_collocation_polynomial() {
+        use crate::ode::OutputTimes;
+        let (sys, y0) = sys_decay();
+        let t_eval = vec![0.0, 0.03, 0.31, 0.77, 1.0];
+        let cfg = OdeSolverConfig { output: OutputTimes::Times(t_eval.clone()), ..cfg(1.0, 0.1) };
+        let res = solve_radau(&sys, &y0, &cfg);
+        assert_eq!(res.t, t_eval);
+        // The stage polynomial is order 3 inside the step: well below step error.
+        for (t, y) in res.t.iter().zip(&res.states) {
+            assert!((y[0] - (-t).exp()).abs() < 1e-5, "y({t}) = {}", y[0]);
+        }
+    }
 }
@@ -9,6 +9,7 @@
 //!   k4 = f(t + h, y + h*k3)
 //!   y_next = y + h/6 * (k1 + 2*k2 + 2*k3 + k4)
 
+use super::dense::{no_observer, AcceptedStep, DenseRecorder, Interpolant, StepControl};
 use super::types::{OdeResult, OdeSolverConfig, OdeSystem};
 use crate::expr::{eval_expr, compile, CompiledExpr};
 use std::collections::HashMap;
@@ -55,11 +56,23 @@ pub fn eval_rhs_pub(system: &OdeSystem, t: f64, y: &[f64]) -> Vec<f64> {
 /// Solve an ODE system using the classic 4th-order Runge-Kutta method.
 ///
 /// Pre-compiles all equations once for fast repeated evaluation (1.29 JIT).
-/// Returns a table of (t, y0, y1, ..., yN) at each time step.
+/// Returns a table of (t, y0, y1, ..., yN) at each time step, or at the
+/// times in [`OdeSolverConfig::output`] via cubic Hermite interpolation.
 pub fn solve_rk4(
     system: &OdeSystem,
     y0: &[f64],
     config: &OdeSolverConfig,
+) -> OdeResult {
+    integrate_rk4(system, y0, config, &mut no_observer)
+}
+
+/// [`solve_rk4`], offering every step to `on_step`, which may end the run
+/// inside the step (see [`super::event`]).
+pub fn integrate_rk4(
+    system: &OdeSystem,
+    y0: &[f64],
+    config: &OdeSolverConfig,
+    on_step: &mut dyn FnMut(&AcceptedStep) -> StepControl,
 ) -> OdeResult {
     let n = system.equations.len();
     assert_eq!(n, y0.len(), "Number of equations must match initial state dimension");
@@ -80,11 +93,10 @@ pub fn solve_rk4(
     let mut t = config.t_start;
     let mut y = y0.to_vec();
 
-    let mut t_out = vec![t];
-    let mut states_out = vec![y.clone()];
+    let mut out = DenseRecorder::new(config, &y);
     let mut steps = 0;
 
-    while t < config.t_end && steps < config.max_steps {
+    while t < config.t_end - 1e-12 * dt.abs() && steps < config.max_steps {
         // Adjust last step to land exactly on t_end
         let h = if t + dt > config.t_end {
             config.t_end - t
@@ -104,18 +116,33 @@ pub fn solve_rk4(
         let k4 = eval(t + h, &y_k4);
 
         // RK4 update: y_next = y + h/6 * (k1 + 2*k2 + 2*k3 + k4)
-        for i in 0..n {
-            y[i] += h / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]);
-        }
+        let y_new: Vec<f64> = (0..n)
+            .map(|i| y[i] + h / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]))
+            .collect();
 
+        let t0 = t;
         t += h;
         steps += 1;
-        t_out.push(t);
-        states_out.push(y.clone());
+        let interp = || Interpolant::Hermite {
+            t0,
+            h,
+            y0: y.clone(),
+            f0: k1.clone(),
+            y1: y_new.clone(),
+            f1: eval(t, &y_new),
+        };
+        let control = on_step(&AcceptedStep::new(t0, t, &y, &y_new, &interp));
+        if let StepControl::Stop { t: t_stop, y: y_stop } = control {
+            out.step(t_stop, &y_stop, interp);
+            break;
+        }
+        out.step(t, &y_new, interp);
+        y = y_new;
     }
 
     let mut column_names = vec!["t".to_string()];
     column_names.extend(system.state_names.iter().cloned());
+    let (t_out, states_out) = out.finish();
 
     OdeResult {
         t: t_out,
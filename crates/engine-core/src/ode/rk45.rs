@@ -7,6 +7,7 @@
 //! Reference: Dormand & Prince, "A Family of Embedded Runge-Kutta Formulae" (1980).
 //! This is equivalent to MATLAB's `ode45`.
 
+use super::dense::{no_observer, AcceptedStep, DenseRecorder, Interpolant, StepControl};
 use super::rk4::eval_rhs_pub;
 use super::types::{OdeResult, OdeSolverConfig, OdeSystem};
 
@@ -48,11 +49,87 @@ const D5: f64 = -92097.0 / 339200.0;
 const D6: f64 = 187.0 / 2100.0;
 const D7: f64 = 1.0 / 40.0;
 
+/// One Dormand–Prince step of size `h` from `(t, y)`: the new state, the RMS
+/// error estimate, and the seven stage derivatives (FSAL: `k[6] = f(t+h, y_new)`).
+pub(crate) fn dopri_step(
+    f: impl Fn(f64, &[f64]) -> Vec<f64>,
+    t: f64,
+    y: &[f64],
+    h: f64,
+) -> (Vec<f64>, f64, [Vec<f64>; 7]) {
+    let n = y.len();
+    let k1 = f(t, y);
+
+    let y2: Vec<f64> = (0..n).map(|i| y[i] + h * B21 * k1[i]).collect();
+    let k2 = f(t + A2 * h, &y2);
+
+    let y3: Vec<f64> = (0..n).map(|i| y[i] + h * (B31 * k1[i] + B32 * k2[i])).collect();
+    let k3 = f(t + A3 * h, &y3);
+
+    let y4: Vec<f64> = (0..n)
+        .map(|i| y[i] + h * (B41 * k1[i] + B42 * k2[i] + B43 * k3[i]))
+        .collect();
+    let k4 = f(t + A4 * h, &y4);
+
+    let y5: Vec<f64> = (0..n)
+        .map(|i| y[i] + h * (B51 * k1[i] + B52 * k2[i] + B53 * k3[i] + B54 * k4[i]))
+        .collect();
+    let k5 = f(t + A5 * h, &y5);
+
+    let y6: Vec<f64> = (0..n)
+        .map(|i| {
+            y[i] + h
+                * (B61 * k1[i] + B62 * k2[i] + B63 * k3[i] + B64 * k4[i] + B65 * k5[i])
+        })
+        .collect();
+    let k6 = f(t + h, &y6);
+
+    // 4th-order solution (used to advance)
+    let y_new: Vec<f64> = (0..n)
+        .map(|i| y[i] + h * (C1 * k1[i] + C3 * k3[i] + C4 * k4[i] + C5 * k5[i] + C6 * k6[i]))
+        .collect();
+
+    // Compute 7th stage for error estimate
+    let k7 = f(t + h, &y_new);
+
+    // Error estimate: difference between 4th and 5th order solutions
+    let err: f64 = (0..n)
+        .map(|i| {
+            let e = h
+                * ((C1 - D1) * k1[i]
+                    + (C3 - D3) * k3[i]
+                    + (C4 - D4) * k4[i]
+                    + (C5 - D5) * k5[i]
+                    + (C6 - D6) * k6[i]
+                    - D7 * k7[i]);
+            e * e
+        })
+        .sum::<f64>()
+        .sqrt()
+        / (n as f64).sqrt();
+
+    (y_new, err, [k1, k2, k3, k4, k5, k6, k7])
+}
+
 /// Solve an ODE system using the Dormand-Prince RK4(5) adaptive method.
+///
+/// With [`OdeSolverConfig::output`] set, results at the requested times come
+/// from the method's continuous extension rather than shortened steps.
 pub fn solve_rk45(
     system: &OdeSystem,
     y0: &[f64],
     config: &OdeSolverConfig,
+) -> OdeResult {
+    integrate_rk45(system, y0, config, &mut no_observer)
+}
+
+/// [`solve_rk45`], offering every accepted step to `on_step`, which may end
+/// the run inside the step (see [`super::event`]).
+pub fn integrate_rk45(
+    system: &OdeSystem,
+    y0: &[f64],
+    config: &OdeSolverConfig,
+    on_step: &mut dyn FnMut(&AcceptedStep) -> StepControl,
 ) -> OdeResult {
     let n = system.equations.len();
     assert_eq!(n, y0.len(), "Equations must match initial state dimension");
@@ -62,8 +139,7 @@ pub fn solve_rk45(
     let mut y = y0.to_vec();
     let mut h = config.dt; // initial step size guess
 
-    let mut t_out = vec![t];
-    let mut states_out = vec![y.clone()];
+    let mut out = DenseRecorder::new(config, &y);
     let mut steps = 0;
 
     while t < config.t_end && steps < config.max_steps {
@@ -75,64 +151,26 @@ pub fn solve_rk45(
             break;
         }
 
-        // Compute 6 stages
-        let k1 = eval_rhs_pub(system, t, &y);
-
-        let y2: Vec<f64> = (0..n).map(|i| y[i] + h * B21 * k1[i]).collect();
-        let k2 = eval_rhs_pub(system, t + A2 * h, &y2);
-
-        let y3: Vec<f64> = (0..n).map(|i| y[i] + h * (B31 * k1[i] + B32 * k2[i])).collect();
-        let k3 = eval_rhs_pub(system, t + A3 * h, &y3);
-
-        let y4: Vec<f64> = (0..n)
-            .map(|i| y[i] + h * (B41 * k1[i] + B42 * k2[i] + B43 * k3[i]))
-            .collect();
-        let k4 = eval_rhs_pub(system, t + A4 * h, &y4);
-
-        let y5: Vec<f64> = (0..n)
-            .map(|i| y[i] + h * (B51 * k1[i] + B52 * k2[i] + B53 * k3[i] + B54 * k4[i]))
-            .collect();
-        let k5 = eval_rhs_pub(system, t + A5 * h, &y5);
-
-        let y6: Vec<f64> = (0..n)
-            .map(|i| {
-                y[i] + h
-                    * (B61 * k1[i] + B62 * k2[i] + B63 * k3[i] + B64 * k4[i] + B65 * k5[i])
-            })
-            .collect();
-        let k6 = eval_rhs_pub(system, t + h, &y6);
-
-        // 4th-order solution (used to advance)
-        let y_new: Vec<f64> = (0..n)
-            .map(|i| y[i] + h * (C1 * k1[i] + C3 * k3[i] + C4 * k4[i] + C5 * k5[i] + C6 * k6[i]))
-            .collect();
-
-        // Compute 7th stage for error estimate
-        let k7 = eval_rhs_pub(system, t + h, &y_new);
-
-        // Error estimate: difference between 4th and 5th order solutions
-        let err: f64 = (0..n)
-            .map(|i| {
-                let e = h
-                    * ((C1 - D1) * k1[i]
-                        + (C3 - D3) * k3[i]
-                        + (C4 - D4) * k4[i]
-                        + (C5 - D5) * k5[i]
-                        + (C6 - D6) * k6[i]
-                        - D7 * k7[i]);
-                e * e
-            })
-            .sum::<f64>()
-            .sqrt()
-            / (n as f64).sqrt();
+        let (y_new, err, k) = dopri_step(|t, y| eval_rhs_pub(system, t, y), t, &y, h);
 
         if err <= tol || h <= 1e-15 {
             // Accept step
+            let t0 = t;
             t += h;
-            y = y_new;
             steps += 1;
-            t_out.push(t);
-            states_out.push(y.clone());
+            let interp = || Interpolant::DormandPrince {
+                t0,
+                h,
+                y0: y.clone(),
+                k: k.clone(),
+            };
+            let control = on_step(&AcceptedStep::new(t0, t, &y, &y_new, &interp));
+            if let StepControl::Stop { t: t_stop, y: y_stop } = control {
+                out.step(t_stop, &y_stop, interp);
+                break;
+            }
+            out.step(t, &y_new, interp);
+            y = y_new;
         }
 
         // Adjust step size: h_new = h * min(5, max(0.2, 0.9 * (tol/err)^(1/5)))
@@ -147,6 +185,7 @@ pub fn solve_rk45(
     let mut column_names = vec!["t".to_string()];
     column_names.extend(system.state_names.iter().cloned());
 
+    let (t_out, states_out) = out.finish();
     OdeResult {
         t: t_out,
         states: states_out,
@@ -173,6 +212,7 @@ mod tests {
             dt: 0.1,
             tolerance: 1e-8,
             max_steps: 10000,
+            ..Default::default()
         };
         let result = solve_rk45(&system, &[1.0], &config);
 
@@ -201,6 +241,7 @@ mod tests {
             dt: 0.1,
             tolerance: 1e-8,
             max_steps: 10000,
+            ..Default::default()
         };
         let result = solve_rk45(&system, &[1.0, 0.0], &config);
 
@@ -218,4 +259,25 @@ mod tests {
             v_final
         );
     }
+
+    #[test]
+    fn dense_output_at_requested_times() {
+        use crate::ode::OutputTimes;
+        let system = OdeSystem {
+            equations: vec!["y1".to_string(), "-y0".to_string()],
+            state_names: vec!["x".to_string(), "v".to_string()],
+            params: HashMap::new(),
+        };
+        let t_eval: Vec<f64> = (0..=40).map(|i| i as f64 * 0.15).collect();
+        let steps_cfg = OdeSolverConfig { t_end: 6.0, dt: 0.1, tolerance: 1e-8, ..Default::default() };
+        let dense_cfg = OdeSolverConfig { output: OutputTimes::Times(t_eval.clone()), ..steps_cfg.clone() };
+        let stepped = solve_rk45(&system, &[1.0, 0.0], &steps_cfg);
+        let dense = solve_rk45(&system, &[1.0, 0.0], &dense_cfg);
+        assert_eq!(dense.t, t_eval);
+        assert_eq!(dense.steps, stepped.steps, "requested times must not shorten steps");
+        for (t, y) in dense.t.iter().zip(&dense.states) {
+            assert!((y[0] - t.cos()).abs() < 1e-6, "x({t}) = {}", y[0]);
+            assert!((y[1] + t.sin()).abs() < 1e-6, "v({t}) = {}", y[1]);
+        }
+    }
 }
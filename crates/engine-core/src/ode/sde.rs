@@ -0,0 +1,484 @@
+//! Stochastic differential equations (Itô) and ensemble statistics.
+//!
+//! Solves `dy_i = f_i(t, y) dt + g_i(t, y) dW_i` with diagonal noise: each
+//! component has its own independent Wiener process. Drift `f` and diffusion
+//! `g` are expression vectors over `t`, `y0..y{N-1}` and named params, as in
+//! the ODE solvers.
+//!
+//! # Schemes (fixed step `h`)
+//!
+//! * **Euler–Maruyama** — `y += f·h + g·ΔW`; strong order 0.5.
+//! * **Milstein** — adds `½·g·∂g/∂y·(ΔW² - h)` (∂g_i/∂y_i by forward-mode
+//!   AD); strong order 1.
+//! * **SRA1** — Rößler's derivative-free stochastic Runge–Kutta method for
+//!   additive noise (`g` independent of `y`); strong order 1.5. It needs the
+//!   iterated integral `I_(1,0) = ∫(W_s - W_t) ds`, drawn jointly with `ΔW`.
+//!
+//! Brownian increments come from [`Xoshiro256`] seeded per path, so a run is
+//! reproducible from `(seed, path)`. [`sde_ensemble`] simulates many paths
+//! and reduces them to mean, variance and quantile bands per time point.
+//!
+//! References: Kloeden & Platen, "Numerical Solution of SDEs" (1992);
+//! Rößler, "Runge–Kutta methods for the strong approximation of solutions of
+//! SDEs", SIAM J. Numer. Anal. 48 (2010).
+
+use super::types::{OdeResult, OdeSolverConfig};
+use crate::autodiff::Dual;
+use crate::expr::{compile, CompiledExpr};
+use crate::rng::Xoshiro256;
+use std::collections::HashMap;
+
+/// Integration scheme for [`solve_sde`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SdeScheme {
+    #[default]
+    EulerMaruyama,
+    Milstein,
+    /// Rößler SRA1, additive noise only.
+    Sra,
+}
+
+/// Parse a scheme name ("milstein", "sra"/"sri"/"sra1", else Euler–Maruyama).
+pub fn parse_sde_scheme(s: &str) -> SdeScheme {
+    match s.trim().to_lowercase().as_str() {
+        "milstein" => SdeScheme::Milstein,
+        "sra" | "sra1" | "sri" | "srk" => SdeScheme::Sra,
+        _ => SdeScheme::EulerMaruyama,
+    }
+}
+
+/// An SDE system with diagonal noise.
+#[derive(Debug, Clone)]
+pub struct SdeSystem {
+    /// Drift `f_i(t, y)`.
+    pub drift: Vec<String>,
+    /// Diffusion `g_i(t, y)` of component `i`'s own Wiener process.
+    pub diffusion: Vec<String>,
+    pub state_names: Vec<String>,
+    pub params: HashMap<String, f64>,
+}
+
+/// Seeded Brownian increments for one path.
+pub struct BrownianPath {
+    rng: Xoshiro256,
+}
+
+impl BrownianPath {
+    /// Path `path` of the family seeded by `seed`.
+    pub fn new(seed: u64, path: u64) -> Self {
+        BrownianPath { rng: Xoshiro256::new(seed ^ path.wrapping_mul(0x9e37_79b9_7f4a_7c15)) }
+    }
+
+    /// `ΔW ~ N(0, h)`.
+    pub fn increment(&mut self, h: f64) -> f64 {
+        self.rng.next_gaussian() * h.sqrt()
+    }
+
+    /// `(ΔW, I_(1,0))` over a step of size `h`: `I_(1,0) = ½·h·(ΔW + U/√3)`
+    /// with `U ~ N(0, h)` independent of `ΔW`.
+    pub fn increment_with_area(&mut self, h: f64) -> (f64, f64) {
+        let dw = self.increment(h);
+        let u = self.increment(h);
+        (dw, 0.5 * h * (dw + u / 3f64.sqrt()))
+    }
+}
+
+// Rößler SRA1 tableau (two stages).
+const SRA_C0: [f64; 2] = [0.0, 0.75];
+const SRA_C1: [f64; 2] = [1.0, 0.0];
+const SRA_A0_10: f64 = 0.75;
+const SRA_B0_10: f64 = 1.5;
+const SRA_ALPHA: [f64; 2] = [1.0 / 3.0, 2.0 / 3.0];
+const SRA_BETA1: [f64; 2] = [1.0, 0.0];
+const SRA_BETA2: [f64; 2] = [-1.0, 1.0];
+
+struct Compiled {
+    drift: Vec<CompiledExpr>,
+    diffusion: Vec<CompiledExpr>,
+}
+
+fn compile_system(system: &SdeSystem, n: usize, scheme: SdeScheme) -> Result<Compiled, String> {
+    if system.drift.len() != n || system.diffusion.len() != n {
+        return Err(format!(
+            "{} drift and {} diffusion expressions for {n} states",
+            system.drift.len(),
+            system.diffusion.len()
+        ));
+    }
+    let compile_all = |what: &str, exprs: &[String]| {
+        exprs
+            .iter()
+            .map(|e| compile(e).map_err(|err| format!("{what} '{e}': {err}")))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let compiled = Compiled { drift: compile_all("drift", &system.drift)?, diffusion: compile_all("diffusion", &system.diffusion)? };
+    if scheme == SdeScheme::Sra {
+        for (g, src) in compiled.diffusion.iter().zip(&system.diffusion) {
+            let deps = g.dependencies();
+            let state_dependent = !deps.vectors.is_empty()
+                || deps.scalars.iter().any(|v| v.starts_with('y') && !system.params.contains_key(v));
+            if state_dependent {
+                return Err(format!("SRA needs additive noise, but diffusion '{src}' depends on the state"));
+            }
+        }
+    }
+    Ok(compiled)
+}
+
+impl Compiled {
+    fn eval(exprs: &[CompiledExpr], vars: &HashMap<String, f64>) -> Vec<f64> {
+        exprs.iter().map(|e| e.eval(vars).unwrap_or(f64::NAN)).collect()
+    }
+
+    /// `∂g_i/∂y_i` for each component.
+    fn diffusion_slopes(&self, vars: &HashMap<String, f64>) -> Vec<f64> {
+        let base: HashMap<String, Dual> = vars.iter().map(|(k, v)| (k.clone(), Dual::constant(*v))).collect();
+        self.diffusion
+            .iter()
+            .enumerate()
+            .map(|(i, g)| {
+                let mut duals = base.clone();
+                let name = format!("y{i}");
+                duals.insert(name.clone(), Dual::variable(vars[&name]));
+                g.eval_dual(&duals).map_or(f64::NAN, |d| d.dot)
+            })
+            .collect()
+    }
+}
+
+fn check_step(config: &OdeSolverConfig) -> Result<(), String> {
+    if config.dt > 0.0 && config.dt.is_finite() {
+        Ok(())
+    } else {
+        Err(format!("step size must be positive, got {}", config.dt))
+    }
+}
+
+/// Number of fixed steps from `t_start` to `t_end` (the last one may be short).
+/// Counting up front means rounding in `t` can't add a sliver step.
+fn step_count(config: &OdeSolverConfig) -> usize {
+    let span = config.t_end - config.t_start;
+    (span / config.dt - 1e-9).ceil().max(0.0) as usize
+}
+
+fn state_vars(params: &HashMap<String, f64>, t: f64, y: &[f64]) -> HashMap<String, f64> {
+    let mut vars = params.clone();
+    vars.insert("t".to_string(), t);
+    for (i, &yi) in y.iter().enumerate() {
+        vars.insert(format!("y{i}"), yi);
+    }
+    vars
+}
+
+/// Simulate one path; returns the states at `t_start, t_start + dt, …, t_end`.
+fn simulate(
+    system: &SdeSystem,
+    compiled: &Compiled,
+    y0: &[f64],
+    config: &OdeSolverConfig,
+    scheme: SdeScheme,
+    mut noise: BrownianPath,
+) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let n = y0.len();
+    let dt = config.dt;
+    let mut t = config.t_start;
+    let mut y = y0.to_vec();
+    let mut ts = vec![t];
+    let mut states = vec![y.clone()];
+    let full = step_count(config);
+
+    for k in 1..=full.min(config.max_steps) {
+        let t_next = if k == full { config.t_end } else { config.t_start + k as f64 * dt };
+        let h = t_next - t;
+        let vars = state_vars(&system.params, t, &y);
+        let f = Compiled::eval(&compiled.drift, &vars);
+
+        y = match scheme {
+            SdeScheme::EulerMaruyama | SdeScheme::Milstein => {
+                let g = Compiled::eval(&compiled.diffusion, &vars);
+                let slopes = if scheme == SdeScheme::Milstein { compiled.diffusion_slopes(&vars) } else { vec![0.0; n] };
+                (0..n)
+                    .map(|i| {
+                        let dw = noise.increment(h);
+                        y[i] + f[i] * h + g[i] * dw + 0.5 * g[i] * slopes[i] * (dw * dw - h)
+                    })
+                    .collect()
+            }
+            SdeScheme::Sra => {
+                let (dw, area): (Vec<f64>, Vec<f64>) = (0..n).map(|_| noise.increment_with_area(h)).unzip();
+                let g_at = |c: f64| Compiled::eval(&compiled.diffusion, &state_vars(&system.params, t + c * h, &y));
+                let g = [g_at(SRA_C1[0]), g_at(SRA_C1[1])];
+                // Stage 2: H = y + A0·f(H1)·h + B0·g(t + c1·h)·I_(1,0)/h, with H1 = y.
+                let h2: Vec<f64> = (0..n)
+                    .map(|i| y[i] + SRA_A0_10 * f[i] * h + SRA_B0_10 * g[0][i] * area[i] / h)
+                    .collect();
+                let f2 = Compiled::eval(&compiled.drift, &state_vars(&system.params, t + SRA_C0[1] * h, &h2));
+                (0..n)
+                    .map(|i| {
+                        let deterministic = h * (SRA_ALPHA[0] * f[i] + SRA_ALPHA[1] * f2[i]);
+                        let stochastic: f64 = (0..2)
+                            .map(|s| (SRA_BETA1[s] * dw[i] + SRA_BETA2[s] * area[i] / h) * g[s][i])
+                            .sum();
+                        y[i] + deterministic + stochastic
+                    })
+                    .collect()
+            }
+        };
+        t = t_next;
+        ts.push(t);
+        states.push(y.clone());
+    }
+    (ts, states)
+}
+
+/// Simulate a single path of `system` with Brownian path `(seed, 0)`.
+pub fn solve_sde(
+    system: &SdeSystem,
+    y0: &[f64],
+    config: &OdeSolverConfig,
+    scheme: SdeScheme,
+    seed: u64,
+) -> Result<OdeResult, String> {
+    check_step(config)?;
+    let compiled = compile_system(system, y0.len(), scheme)?;
+    let (t, states) = simulate(system, &compiled, y0, config, scheme, BrownianPath::new(seed, 0));
+    let mut column_names = vec!["t".to_string()];
+    column_names.extend(system.state_names.iter().cloned());
+    let steps = t.len() - 1;
+    Ok(OdeResult { t, states, column_names, steps })
+}
+
+/// Per-time statistics over an ensemble of paths.
+#[derive(Debug, Clone)]
+pub struct EnsembleResult {
+    pub t: Vec<f64>,
+    /// `mean[k][i]`: mean of state `i` at `t[k]`.
+    pub mean: Vec<Vec<f64>>,
+    /// Unbiased sample variance, same layout as `mean`.
+    pub variance: Vec<Vec<f64>>,
+    /// Requested probabilities in `[0, 1]`.
+    pub probabilities: Vec<f64>,
+    /// `quantiles[q][k][i]`: quantile `probabilities[q]` of state `i` at `t[k]`.
+    pub quantiles: Vec<Vec<Vec<f64>>>,
+    pub state_names: Vec<String>,
+    pub paths: usize,
+}
+
+impl EnsembleResult {
+    /// Flatten into `(columns, rows)`: `t`, then per state `name_mean`,
+    /// `name_var` and `name_pXX` for each quantile.
+    pub fn to_table(&self) -> (Vec<String>, Vec<Vec<f64>>) {
+        let label = |p: f64| {
+            let pct = 100.0 * p;
+            if pct.fract() == 0.0 { format!("p{pct:.0}") } else { format!("p{pct}") }
+        };
+        let mut columns = vec!["t".to_string()];
+        for name in &self.state_names {
+            columns.push(format!("{name}_mean"));
+            columns.push(format!("{name}_var"));
+            columns.extend(self.probabilities.iter().map(|&p| format!("{name}_{}", label(p))));
+        }
+        let rows = (0..self.t.len())
+            .map(|k| {
+                let mut row = vec![self.t[k]];
+                for i in 0..self.state_names.len() {
+                    row.push(self.mean[k][i]);
+                    row.push(self.variance[k][i]);
+                    row.extend(self.quantiles.iter().map(|q| q[k][i]));
+                }
+                row
+            })
+            .collect();
+        (columns, rows)
+    }
+}
+
+/// Linearly interpolated quantile of sorted data (same rule as the Monte
+/// Carlo percentiles).
+fn quantile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+    let k = p.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let (lo, hi) = (k.floor() as usize, k.ceil() as usize);
+    sorted[lo] + (k - lo as f64) * (sorted[hi] - sorted[lo])
+}
+
+/// Most path values [`sde_ensemble`] holds at once (paths × time points ×
+/// states, 8 bytes each). Exact quantiles need every path, so larger
+/// ensembles are rejected rather than risking running out of memory.
+pub const MAX_ENSEMBLE_VALUES: usize = 20_000_000;
+
+/// Simulate `paths` independent paths (Brownian paths `(seed, 0..paths)`)
+/// and reduce them per time point. Paths that produce non-finite values are
+/// left out of that time point's statistics.
+pub fn sde_ensemble(
+    system: &SdeSystem,
+    y0: &[f64],
+    config: &OdeSolverConfig,
+    scheme: SdeScheme,
+    seed: u64,
+    paths: usize,
+    probabilities: &[f64],
+) -> Result<EnsembleResult, String> {
+    if paths == 0 {
+        return Err("an ensemble needs at least one path".to_string());
+    }
+    if let Some(p) = probabilities.iter().find(|p| !(0.0..=1.0).contains(*p)) {
+        return Err(format!("quantile probability {p} is outside [0, 1]"));
+    }
+    check_step(config)?;
+    let n = y0.len();
+    let points = step_count(config).min(config.max_steps) + 1;
+    let values = paths.checked_mul(points).and_then(|v| v.checked_mul(n.max(1)));
+    if values.is_none_or(|v| v > MAX_ENSEMBLE_VALUES) {
+        return Err(format!(
+            "{paths} paths × {points} time points × {n} states exceeds the {MAX_ENSEMBLE_VALUES} value limit; \
+             use fewer paths or a larger dt"
+        ));
+    }
+    let compiled = compile_system(system, n, scheme)?;
+    let mut t = Vec::new();
+    let runs: Vec<Vec<Vec<f64>>> = (0..paths as u64)
+        .map(|p| {
+            let (ts, states) = simulate(system, &compiled, y0, config, scheme, BrownianPath::new(seed, p));
+            if p == 0 {
+                t = ts;
+            }
+            states
+        })
+        .collect();
+
+    let mut mean = Vec::with_capacity(t.len());
+    let mut variance = Vec::with_capacity(t.len());
+    let mut quantiles = vec![Vec::with_capacity(t.len()); probabilities.len()];
+    for k in 0..t.len() {
+        let (mut m_row, mut v_row) = (Vec::with_capacity(n), Vec::with_capacity(n));
+        let mut q_rows = vec![Vec::with_capacity(n); probabilities.len()];
+        for i in 0..n {
+            let mut values: Vec<f64> = runs.iter().map(|s| s[k][i]).filter(|v| v.is_finite()).collect();
+            values.sort_by(f64::total_cmp);
+            let count = values.len() as f64;
+            let m = values.iter().sum::<f64>() / count;
+            let v = if values.len() > 1 {
+                values.iter().map(|x| (x - m).powi(2)).sum::<f64>() / (count - 1.0)
+            } else {
+                0.0
+            };
+            m_row.push(m);
+            v_row.push(v);
+            for (row, &p) in q_rows.iter_mut().zip(probabilities) {
+                row.push(quantile(&values, p));
+            }
+        }
+        mean.push(m_row);
+        variance.push(v_row);
+        for (q, row) in quantiles.iter_mut().zip(q_rows) {
+            q.push(row);
+        }
+    }
+
+    Ok(EnsembleResult {
+        t,
+        mean,
+        variance,
+        probabilities: probabilities.to_vec(),
+        quantiles,
+        state_names: system.state_names.clone(),
+        paths,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn system(drift: &[&str], diffusion: &[&str], params: &[(&str, f64)]) -> SdeSystem {
+        SdeSystem {
+            drift: drift.iter().map(|s| s.to_string()).collect(),
+            diffusion: diffusion.iter().map(|s| s.to_string()).collect(),
+            state_names: (0..drift.len()).map(|i| format!("y{i}")).collect(),
+            params: params.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+        }
+    }
+
+    fn config(t_end: f64, dt: f64) -> OdeSolverConfig {
+        OdeSolverConfig { t_end, dt, max_steps: 1_000_000, ..Default::default() }
+    }
+
+    #[test]
+    fn paths_are_reproducible_from_the_seed() {
+        let sys = system(&["-y0"], &["0.5"], &[]);
+        let a = solve_sde(&sys, &[1.0], &config(1.0, 0.01), SdeScheme::EulerMaruyama, 7).unwrap();
+        let b = solve_sde(&sys, &[1.0], &config(1.0, 0.01), SdeScheme::EulerMaruyama, 7).unwrap();
+        let c = solve_sde(&sys, &[1.0], &config(1.0, 0.01), SdeScheme::EulerMaruyama, 8).unwrap();
+        assert_eq!(a.states, b.states);
+        assert_ne!(a.states, c.states);
+        assert_eq!(a.t.len(), 101);
+    }
+
+    /// Ornstein–Uhlenbeck dy = θ(μ - y)dt + σ dW: mean μ + (y0 - μ)e^{-θt},
+    /// variance σ²/(2θ)·(1 - e^{-2θt}).
+    #[test]
+    fn ornstein_uhlenbeck_ensemble_moments() {
+        let (theta, mu, sigma) = (2.0, 1.0, 0.5);
+        let sys = system(&["theta * (mu - y0)"], &["sigma"], &[("theta", theta), ("mu", mu), ("sigma", sigma)]);
+        for scheme in [SdeScheme::EulerMaruyama, SdeScheme::Sra] {
+            let ens = sde_ensemble(&sys, &[0.0], &config(1.0, 0.01), scheme, 42, 4000, &[0.05, 0.5, 0.95]).unwrap();
+            let k = ens.t.len() - 1;
+            let mean = mu + (0.0 - mu) * (-theta).exp();
+            let var = sigma * sigma / (2.0 * theta) * (1.0 - (-2.0 * theta).exp());
+            assert!((ens.mean[k][0] - mean).abs() < 4.0 * (var / 4000.0).sqrt(), "{scheme:?} mean {}", ens.mean[k][0]);
+            assert!((ens.variance[k][0] / var - 1.0).abs() < 0.08, "{scheme:?} variance {}", ens.variance[k][0]);
+            // Gaussian: the 5–95% band is ±1.645σ around the median.
+            let band = ens.quantiles[2][k][0] - ens.quantiles[0][k][0];
+            assert!((band / (2.0 * 1.645 * var.sqrt()) - 1.0).abs() < 0.08);
+        }
+    }
+
+    /// Strong error at T = 1 against the exact geometric Brownian motion
+    /// y = y0·exp((μ - σ²/2)t + σW), driven by the same increments.
+    fn gbm_strong_error(scheme: SdeScheme, dt: f64) -> f64 {
+        let (mu, sigma) = (1.5, 0.8);
+        let sys = system(&["mu * y0"], &["sigma * y0"], &[("mu", mu), ("sigma", sigma)]);
+        let compiled = compile_system(&sys, 1, scheme).unwrap();
+        let paths = 200;
+        (0..paths)
+            .map(|p| {
+                let (ts, states) = simulate(&sys, &compiled, &[1.0], &config(1.0, dt), scheme, BrownianPath::new(3, p));
+                let mut noise = BrownianPath::new(3, p);
+                let w: f64 = ts.windows(2).map(|s| noise.increment(s[1] - s[0])).sum();
+                let exact = ((mu - 0.5 * sigma * sigma) + sigma * w).exp();
+                (states.last().unwrap()[0] - exact).abs()
+            })
+            .sum::<f64>()
+            / paths as f64
+    }
+
+    #[test]
+    fn milstein_converges_faster_than_euler_maruyama() {
+        let rate = |scheme| (gbm_strong_error(scheme, 0.01) / gbm_strong_error(scheme, 0.0025)).log(4.0);
+        let (em, milstein) = (rate(SdeScheme::EulerMaruyama), rate(SdeScheme::Milstein));
+        assert!((em - 0.5).abs() < 0.2, "Euler–Maruyama strong order {em}");
+        assert!((milstein - 1.0).abs() < 0.2, "Milstein strong order {milstein}");
+    }
+
+    #[test]
+    fn sra_rejects_multiplicative_noise() {
+        let sys = system(&["y0"], &["0.3 * y0"], &[]);
+        assert!(solve_sde(&sys, &[1.0], &config(1.0, 0.1), SdeScheme::Sra, 1).is_err());
+        let ens = sde_ensemble(&system(&["0"], &["1"], &[]), &[0.0], &config(1.0, 0.5), SdeScheme::Sra, 1, 3, &[0.5]).unwrap();
+        let (columns, rows) = ens.to_table();
+        assert_eq!(columns, ["t", "y0_mean", "y0_var", "y0_p50"]);
+        assert_eq!(rows.len(), 3);
+    }
+
+    #[test]
+    fn ensemble_rejects_more_values_than_the_cap() {
+        let sys = system(&["0"], &["1"], &[]);
+        let err = sde_ensemble(&sys, &[0.0], &config(1000.0, 1e-4), SdeScheme::EulerMaruyama, 1, 100, &[0.5]).unwrap_err();
+        assert!(err.contains("value limit"), "{err}");
+        let err = sde_ensemble(&sys, &[0.0], &config(1.0, 0.5), SdeScheme::EulerMaruyama, 1, usize::MAX, &[0.5]).unwrap_err();
+        assert!(err.contains("value limit"), "{err}");
+    }
+}
@@ -1,6 +1,6 @@
 //! Symplectic ODE integrators for Hamiltonian systems.
 //!
-//! Implements two structure-preserving integrators:
+//! Implements structure-preserving integrators:
 //!
 //! * **Störmer-Verlet (velocity Verlet)** — 2nd-order symplectic, preserves
 //!   the Hamiltonian to O(h²) per step for conservative systems. No secular
@@ -11,9 +11,14 @@
 //!   and cheaper per step, still preserves a modified Hamiltonian but with
 //!   O(h) energy oscillation.
 //!
+//! * **Yoshida 4th/6th order and Forest–Ruth** — symmetric compositions of
+//!   Verlet steps with weights chosen so the low-order error terms cancel
+//!   (Yoshida, Phys. Lett. A 150, 1990; Forest & Ruth, Physica D 43, 1990).
+//!   Forest–Ruth composes the position (drift-kick-drift) form.
+//!
 //! # System formulation
 //!
-//! The user provides a *second-order* mechanical system in split form:
+//! The classic input is a *second-order* mechanical system in split form:
 //!
 //! ```text
 //!   dq_i/dt = v_i          (positions, trivial)
@@ -24,143 +29,457 @@
 //! Initial state: y0 has length 2N.
 //! Acceleration expressions: `equations` has length N, indexed y0..y{N-1} for
 //! positions and y{N}..y{2N-1} for velocities (same naming as other solvers).
+//!
+//! A [`Hamiltonian`] can be given instead, either separable `T(p) + V(q)`
+//! (kicks and drifts use its exact AD gradients) or general `H(q, p)`, for
+//! which the base step is the implicit midpoint rule. Expressions see the
+//! coordinates as `q0..`, `p0..` and also as `y0..y{2N-1}`.
+//!
+//! # Adaptive steps
+//!
+//! Variable steps normally destroy the long-time behaviour of symplectic
+//! methods. [`SymplecticOptions::adaptive`] instead uses the explicit,
+//! time-reversible controller of Hairer & Söderlind (SIAM J. Sci. Comput. 26,
+//! 2005): the step is `ε/ρ` with `ρ` updated symmetrically by
+//! `ρ_{n+1/2} = ρ_{n-1/2} + ε·G(y_n)`, `G = -ṡ/s`, where `s(q, p)` is a
+//! step-size function — by default the local time scale `√(|q| / |F(q)|)`,
+//! which scales like `r^{3/2}` on Kepler orbits.
+//!
+//! # Conservation monitors
+//!
+//! When the energy is known (a Hamiltonian, or a potential for the
+//! second-order form) the output gains an `energy_drift` column `H - H(0)`;
+//! with [`SymplecticOptions::angular_dim`] set it gains
+//! `angular_momentum_drift`, `|L - L(0)|` for `L = Σ q_b × p_b` over the
+//! bodies `b` (signed for planar motion).
 
-use crate::expr::eval_expr;
+use crate::autodiff::Dual;
+use crate::expr::{compile, eval_expr, CompiledExpr};
+use crate::ode::jacobian::norm;
 use crate::ode::types::{OdeResult, OdeSolverConfig};
 use std::collections::HashMap;
 
 /// Symplectic integration method.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub enum SympMethod {
     /// Velocity Verlet / Störmer-Verlet (2nd order).
+    #[default]
     VelocityVerlet,
     /// Symplectic (semi-implicit) Euler (1st order).
     SympEuler,
+    /// Yoshida triple-jump composition of Verlet (4th order).
+    Yoshida4,
+    /// Yoshida seven-stage composition of Verlet (6th order).
+    Yoshida6,
+    /// Forest–Ruth: triple jump of position Verlet (4th order).
+    ForestRuth,
 }
 
-/// Run a symplectic integrator for a split Hamiltonian system.
-///
-/// `accel_exprs` — N acceleration expressions a_i(q, v, t). Each expression
-///     may use: `t`, `y0..y{N-1}` (positions), `y{N}..y{2N-1}` (velocities),
-///     and named params.
-///
-/// `y0` — initial state \[q_0..q_{N-1}, v_0..v_{N-1}\], length 2N.
+/// Parse a method name ("euler", "yoshida4", "yoshida6", "forest_ruth", else Verlet).
+pub fn parse_symp_method(s: &str) -> SympMethod {
+    match s.trim().to_lowercase().replace(['-', ' '], "_").as_str() {
+        "euler" | "symplectic_euler" => SympMethod::SympEuler,
+        "yoshida4" | "yoshida" => SympMethod::Yoshida4,
+        "yoshida6" => SympMethod::Yoshida6,
+        "forest_ruth" | "forestruth" | "fr" => SympMethod::ForestRuth,
+        _ => SympMethod::VelocityVerlet,
+    }
+}
+
+/// Triple-jump weights `(w1, w0, w1)`: `w1 = 1/(2 - 2^{1/3})`.
+fn triple_jump() -> [f64; 3] {
+    let c = 2f64.cbrt();
+    let w1 = 1.0 / (2.0 - c);
+    [w1, -c * w1, w1]
+}
+
+/// Yoshida's sixth-order "solution A" weights.
+fn yoshida6() -> [f64; 7] {
+    let (w1, w2, w3) = (-1.177_679_984_178_87, 0.235_573_213_359_357, 0.784_513_610_477_560);
+    let w0 = 1.0 - 2.0 * (w1 + w2 + w3);
+    [w3, w2, w1, w0, w1, w2, w3]
+}
+
+/// A Hamiltonian in canonical coordinates.
+#[derive(Debug, Clone)]
+pub enum Hamiltonian {
+    /// `H = T(p) + V(q)`; every method is explicit.
+    Separable { kinetic: String, potential: String },
+    /// General `H(q, p)`; the base step is the implicit midpoint rule.
+    General(String),
+}
+
+/// What drives a symplectic integration.
+#[derive(Debug, Clone)]
+pub enum SymplecticSystem {
+    /// `q'' = a(q, v, t)` with unit masses. The optional potential `V(q)`
+    /// enables the energy monitor `½|v|² + V`.
+    SecondOrder { accelerations: Vec<String>, potential: Option<String> },
+    Hamiltonian(Hamiltonian),
+}
+
+/// Options for [`solve_symplectic_system`].
+#[derive(Debug, Clone, Default)]
+pub struct SymplecticOptions {
+    pub method: SympMethod,
+    /// Time-reversible adaptive steps; `config.dt` is then the first step.
+    pub adaptive: bool,
+    /// Step-size function `s(q, p)` for adaptive runs (default: local time scale).
+    pub step_function: Option<String>,
+    /// Spatial dimension (2 or 3) of each body for the angular momentum
+    /// monitor; `None` leaves the column out.
+    pub angular_dim: Option<usize>,
+}
+
+enum Kind {
+    SecondOrder { accel: Vec<(Option<CompiledExpr>, String)>, potential: Option<CompiledExpr> },
+    Separable { kinetic: CompiledExpr, potential: CompiledExpr },
+    General(CompiledExpr),
+}
+
+/// Compiled right-hand side: kicks change `p`, drifts change `q`.
+struct Model<'a> {
+    n: usize,
+    kind: Kind,
+    params: &'a HashMap<String, f64>,
+}
+
+impl Model<'_> {
+    fn names(&self, j: usize) -> [String; 2] {
+        let n = self.n;
+        if j < n {
+            [format!("q{j}"), format!("y{j}")]
+        } else {
+            [format!("p{}", j - n), format!("y{j}")]
+        }
+    }
+
+    fn vars(&self, q: &[f64], p: &[f64], t: f64) -> HashMap<String, f64> {
+        let mut vars = self.params.clone();
+        vars.insert("t".to_string(), t);
+        for (j, &x) in q.iter().chain(p).enumerate() {
+            for name in self.names(j) {
+                vars.insert(name, x);
+            }
+        }
+        vars
+    }
+
+    /// `∂e/∂(q, p)_j` for `j` in `coords`, by forward-mode AD.
+    fn gradient(&self, e: &CompiledExpr, q: &[f64], p: &[f64], t: f64, coords: std::ops::Range<usize>) -> Vec<f64> {
+        let base: HashMap<String, Dual> =
+            self.vars(q, p, t).into_iter().map(|(k, v)| (k, Dual::constant(v))).collect();
+        coords
+            .map(|j| {
+                let mut vars = base.clone();
+                for name in self.names(j) {
+                    let v = vars[&name].val;
+                    vars.insert(name, Dual::variable(v));
+                }
+                e.eval_dual(&vars).map_or(f64::NAN, |d| d.dot)
+            })
+            .collect()
+    }
+
+    /// `dp/dt`.
+    fn force(&self, q: &[f64], p: &[f64], t: f64) -> Vec<f64> {
+        let n = self.n;
+        match &self.kind {
+            Kind::SecondOrder { accel, .. } => {
+                let vars = self.vars(q, p, t);
+                accel
+                    .iter()
+                    .map(|(c, src)| match c {
+                        Some(c) => c.eval(&vars).unwrap_or(f64::NAN),
+                        None => eval_expr(src, &vars).unwrap_or(f64::NAN),
+                    })
+                    .collect()
+            }
+            Kind::Separable { potential, .. } => {
+                self.gradient(potential, q, p, t, 0..n).iter().map(|g| -g).collect()
+            }
+            Kind::General(h) => self.gradient(h, q, p, t, 0..n).iter().map(|g| -g).collect(),
+        }
+    }
+
+    /// `dq/dt`.
+    fn velocity(&self, q: &[f64], p: &[f64], t: f64) -> Vec<f64> {
+        let n = self.n;
+        match &self.kind {
+            Kind::SecondOrder { .. } => p.to_vec(),
+            Kind::Separable { kinetic, .. } => self.gradient(kinetic, q, p, t, n..2 * n),
+            Kind::General(h) => self.gradient(h, q, p, t, n..2 * n),
+        }
+    }
+
+    fn energy(&self, q: &[f64], p: &[f64], t: f64) -> Option<f64> {
+        let vars = self.vars(q, p, t);
+        let eval = |e: &CompiledExpr| e.eval(&vars).unwrap_or(f64::NAN);
+        match &self.kind {
+            Kind::SecondOrder { potential, .. } => {
+                potential.as_ref().map(|v| 0.5 * p.iter().map(|x| x * x).sum::<f64>() + eval(v))
+            }
+            Kind::Separable { kinetic, potential } => Some(eval(kinetic) + eval(potential)),
+            Kind::General(h) => Some(eval(h)),
+        }
+    }
+
+    fn kick(&self, q: &[f64], p: &mut [f64], t: f64, h: f64) {
+        let f = self.force(q, p, t);
+        for (pi, fi) in p.iter_mut().zip(f) {
+            *pi += h * fi;
+        }
+    }
+
+    fn drift(&self, q: &mut [f64], p: &[f64], t: f64, h: f64) {
+        let v = self.velocity(q, p, t);
+        for (qi, vi) in q.iter_mut().zip(v) {
+            *qi += h * vi;
+        }
+    }
+
+    /// Implicit midpoint `z1 = z0 + h·J∇H((z0 + z1)/2)` by fixed-point iteration.
+    fn midpoint(&self, q: &mut [f64], p: &mut [f64], t: f64, h: f64) {
+        let (q0, p0) = (q.to_vec(), p.to_vec());
+        for _ in 0..100 {
+            let qm: Vec<f64> = q0.iter().zip(&*q).map(|(a, b)| 0.5 * (a + b)).collect();
+            let pm: Vec<f64> = p0.iter().zip(&*p).map(|(a, b)| 0.5 * (a + b)).collect();
+            let (v, f) = (self.velocity(&qm, &pm, t + 0.5 * h), self.force(&qm, &pm, t + 0.5 * h));
+            let mut change = 0.0_f64;
+            for i in 0..self.n {
+                let (qn, pn) = (q0[i] + h * v[i], p0[i] + h * f[i]);
+                change = change.max((qn - q[i]).abs()).max((pn - p[i]).abs());
+                q[i] = qn;
+                p[i] = pn;
+            }
+            if change.is_nan() || change <= 1e-14 * (1.0 + norm(q) + norm(p)) {
+                break;
+            }
+        }
+    }
+
+    /// One step of `method` from `t` with size `h`.
+    fn step(&self, method: SympMethod, q: &mut [f64], p: &mut [f64], t: f64, h: f64) {
+        let weights: &[f64] = match method {
+            SympMethod::VelocityVerlet | SympMethod::SympEuler => &[1.0],
+            SympMethod::Yoshida4 | SympMethod::ForestRuth => &triple_jump(),
+            SympMethod::Yoshida6 => &yoshida6(),
+        };
+        let mut t = t;
+        for &w in weights {
+            let hw = w * h;
+            match (&self.kind, method) {
+                (Kind::General(_), _) => self.midpoint(q, p, t, hw),
+                (_, SympMethod::SympEuler) => {
+                    self.kick(q, p, t, hw);
+                    self.drift(q, p, t, hw);
+                }
+                (_, SympMethod::ForestRuth) => {
+                    self.drift(q, p, t, 0.5 * hw);
+                    self.kick(q, p, t + 0.5 * hw, hw);
+                    self.drift(q, p, t + 0.5 * hw, 0.5 * hw);
+                }
+                _ => {
+                    self.kick(q, p, t, 0.5 * hw);
+                    self.drift(q, p, t, hw);
+                    self.kick(q, p, t + hw, 0.5 * hw);
+                }
+            }
+            t += hw;
+        }
+    }
+
+    /// Angular momentum `Σ q_b × p_b` over bodies of dimension `dim`.
+    fn angular_momentum(&self, q: &[f64], p: &[f64], dim: usize) -> [f64; 3] {
+        let mut l = [0.0; 3];
+        for (qb, pb) in q.chunks_exact(dim).zip(p.chunks_exact(dim)) {
+            if dim == 2 {
+                l[2] += qb[0] * pb[1] - qb[1] * pb[0];
+            } else {
+                l[0] += qb[1] * pb[2] - qb[2] * pb[1];
+                l[1] += qb[2] * pb[0] - qb[0] * pb[2];
+                l[2] += qb[0] * pb[1] - qb[1] * pb[0];
+            }
+        }
+        l
+    }
+}
+
+/// Integrate a second-order system or a Hamiltonian with a symplectic method.
 ///
-/// `state_names` — column header names for positions then velocities
-///     (used in output table). If empty, defaults to `["q0".."qN", "v0".."vN"]`.
-pub fn solve_symplectic(
-    accel_exprs: &[String],
+/// `y0` is `[q_0..q_{N-1}, p_0..p_{N-1}]` (velocities for the second-order
+/// form). `state_names` labels those columns; if its length is not 2N the
+/// defaults `q*`/`v*` (second order) or `q*`/`p*` (Hamiltonian) are used.
+pub fn solve_symplectic_system(
+    system: &SymplecticSystem,
     y0: &[f64],
     config: &OdeSolverConfig,
     params: &HashMap<String, f64>,
     state_names: &[String],
-    method: SympMethod,
-) -> OdeResult {
-    let n = accel_exprs.len();
-    if y0.len() != 2 * n || n == 0 {
-        return OdeResult {
-            t: vec![],
-            states: vec![],
-            column_names: vec!["t".to_string()],
-            steps: 0,
-        };
+    options: &SymplecticOptions,
+) -> Result<OdeResult, String> {
+    if y0.is_empty() || !y0.len().is_multiple_of(2) {
+        return Err(format!("initial state must be [q, p] of even length, got {}", y0.len()));
     }
+    let n = y0.len() / 2;
+    let compile_named = |what: &str, src: &str| compile(src).map_err(|e| format!("{what}: {e}"));
+    let kind = match system {
+        SymplecticSystem::SecondOrder { accelerations, potential } => {
+            if accelerations.len() != n {
+                return Err(format!(
+                    "{} acceleration expressions require {} initial values (got {})",
+                    accelerations.len(),
+                    2 * accelerations.len(),
+                    y0.len()
+                ));
+            }
+            Kind::SecondOrder {
+                accel: accelerations.iter().map(|a| (compile(a).ok(), a.clone())).collect(),
+                potential: potential.as_deref().map(|v| compile_named("potential", v)).transpose()?,
+            }
+        }
+        SymplecticSystem::Hamiltonian(Hamiltonian::Separable { kinetic, potential }) => Kind::Separable {
+            kinetic: compile_named("kinetic energy", kinetic)?,
+            potential: compile_named("potential", potential)?,
+        },
+        SymplecticSystem::Hamiltonian(Hamiltonian::General(h)) => Kind::General(compile_named("hamiltonian", h)?),
+    };
+    let angular_dim = match options.angular_dim {
+        Some(d) if (d == 2 || d == 3) && n.is_multiple_of(d) => Some(d),
+        Some(d) => return Err(format!("angular_dim must be 2 or 3 and divide {n} coordinates, got {d}")),
+        None => None,
+    };
+    let step_function = options
+        .step_function
+        .as_deref()
+        .map(|s| compile_named("step function", s))
+        .transpose()?;
+    let model = Model { n, kind, params };
 
     let dt = config.dt;
-    let t_start = config.t_start;
-    let t_end = config.t_end;
-    let max_steps = config.max_steps;
+    let (t_start, t_end) = (config.t_start, config.t_end);
+    let mut q: Vec<f64> = y0[..n].to_vec();
+    let mut p: Vec<f64> = y0[n..].to_vec();
+    let mut t = t_start;
 
-    // Build output column names
-    let col_names: Vec<String> = if state_names.len() == 2 * n {
-        state_names.to_vec()
-    } else {
-        let mut c: Vec<String> = (0..n).map(|i| format!("q{i}")).collect();
-        c.extend((0..n).map(|i| format!("v{i}")));
-        c
+    let e0 = model.energy(&q, &p, t);
+    let l0 = angular_dim.map(|d| model.angular_momentum(&q, &p, d));
+    let record = |q: &[f64], p: &[f64], t: f64| -> Vec<f64> {
+        let mut row = q.to_vec();
+        row.extend_from_slice(p);
+        if let Some(e0) = e0 {
+            row.push(model.energy(q, p, t).unwrap_or(f64::NAN) - e0);
+        }
+        if let (Some(d), Some(l0)) = (angular_dim, l0) {
+            let l = model.angular_momentum(q, p, d);
+            row.push(if d == 2 {
+                l[2] - l0[2]
+            } else {
+                norm(&[l[0] - l0[0], l[1] - l0[1], l[2] - l0[2]])
+            });
+        }
+        row
     };
 
-    let mut t_vec = Vec::with_capacity(max_steps + 1);
-    let mut states = Vec::with_capacity(max_steps + 1);
-
-    // Initial state
-    let mut q: Vec<f64> = y0[..n].to_vec();
-    let mut v: Vec<f64> = y0[n..].to_vec();
-    let mut t = t_start;
+    // Step-size function s(y) and its logarithmic rate G = -ṡ/s along the flow.
+    let s_fallback = 1.0;
+    let step_size = |q: &[f64], p: &[f64], t: f64| -> f64 {
+        let s = match &step_function {
+            Some(e) => e.eval(&model.vars(q, p, t)).unwrap_or(f64::NAN),
+            None => (norm(q) / norm(&model.force(q, p, t))).sqrt(),
+        };
+        if s.is_finite() && s > 0.0 { s } else { s_fallback }
+    };
+    let rate = |q: &[f64], p: &[f64], t: f64| -> f64 {
+        let s = step_size(q, p, t);
+        let delta = 1e-6 * s;
+        let (v, f) = (model.velocity(q, p, t), model.force(q, p, t));
+        let shifted = |sign: f64| {
+            let qs: Vec<f64> = q.iter().zip(&v).map(|(a, b)| a + sign * delta * b).collect();
+            let ps: Vec<f64> = p.iter().zip(&f).map(|(a, b)| a + sign * delta * b).collect();
+            step_size(&qs, &ps, t + sign * delta)
+        };
+        -(shifted(1.0) - shifted(-1.0)) / (2.0 * delta * s)
+    };
 
-    t_vec.push(t);
-    let mut row = q.clone();
-    row.extend_from_slice(&v);
-    states.push(row);
+    let mut t_vec = vec![t];
+    let mut states = vec![record(&q, &p, t)];
 
-    let n_steps = ((t_end - t_start) / dt).ceil() as usize;
-    let steps_limit = n_steps.min(max_steps);
+    let steps_limit = if options.adaptive {
+        config.max_steps
+    } else {
+        (((t_end - t_start) / dt).ceil() as usize).min(config.max_steps)
+    };
+    let eps = dt / step_size(&q, &p, t);
+    let mut rho = if options.adaptive {
+        1.0 / step_size(&q, &p, t) + 0.5 * eps * rate(&q, &p, t)
+    } else {
+        0.0
+    };
 
     let mut step = 0;
     while t < t_end - 1e-12 * dt.abs() && step < steps_limit {
-        let dt_actual = if t + dt > t_end { t_end - t } else { dt };
-
-        match method {
-            SympMethod::VelocityVerlet => {
-                // 1. Compute acceleration at (q, v, t)
-                let a0 = eval_accels(accel_exprs, &q, &v, t, params, n);
-                // 2. Half-kick: v_half = v + a0 * dt/2
-                let v_half: Vec<f64> = v.iter().zip(a0.iter()).map(|(&vi, &ai)| vi + ai * dt_actual * 0.5).collect();
-                // 3. Full drift: q_new = q + v_half * dt
-                let q_new: Vec<f64> = q.iter().zip(v_half.iter()).map(|(&qi, &vhi)| qi + vhi * dt_actual).collect();
-                // 4. Compute new acceleration at (q_new, v_half, t+dt)
-                let a1 = eval_accels(accel_exprs, &q_new, &v_half, t + dt_actual, params, n);
-                // 5. Half-kick: v_new = v_half + a1 * dt/2
-                let v_new: Vec<f64> = v_half.iter().zip(a1.iter()).map(|(&vhi, &ai)| vhi + ai * dt_actual * 0.5).collect();
-                q = q_new;
-                v = v_new;
-            }
-            SympMethod::SympEuler => {
-                // Kick: v_new = v + a(q, v, t) * dt
-                let a = eval_accels(accel_exprs, &q, &v, t, params, n);
-                let v_new: Vec<f64> = v.iter().zip(a.iter()).map(|(&vi, &ai)| vi + ai * dt_actual).collect();
-                // Drift: q_new = q + v_new * dt  (uses NEW velocity — symplectic)
-                let q_new: Vec<f64> = q.iter().zip(v_new.iter()).map(|(&qi, &vni)| qi + vni * dt_actual).collect();
-                q = q_new;
-                v = v_new;
-            }
-        }
+        let h = if options.adaptive { eps / rho } else { dt };
+        let h = if t + h > t_end { t_end - t } else { h };
 
-        t += dt_actual;
+        model.step(options.method, &mut q, &mut p, t, h);
+        t += h;
         step += 1;
 
+        if options.adaptive {
+            rho += eps * rate(&q, &p, t);
+            if !(rho.is_finite() && rho > 0.0) {
+                rho = 1.0 / step_size(&q, &p, t);
+            }
+        }
         t_vec.push(t);
-        let mut row = q.clone();
-        row.extend_from_slice(&v);
-        states.push(row);
+        states.push(record(&q, &p, t));
     }
 
     let mut column_names = vec!["t".to_string()];
-    column_names.extend(col_names);
+    if state_names.len() == 2 * n {
+        column_names.extend(state_names.iter().cloned());
+    } else {
+        let momentum = if matches!(system, SymplecticSystem::SecondOrder { .. }) { "v" } else { "p" };
+        column_names.extend((0..n).map(|i| format!("q{i}")));
+        column_names.extend((0..n).map(|i| format!("{momentum}{i}")));
+    }
+    if e0.is_some() {
+        column_names.push("energy_drift".to_string());
+    }
+    if angular_dim.is_some() {
+        column_names.push("angular_momentum_drift".to_string());
+    }
 
-    OdeResult { t: t_vec, states, column_names, steps: step }
+    Ok(OdeResult { t: t_vec, states, column_names, steps: step })
 }
 
-/// Evaluate acceleration expressions at the current state.
-fn eval_accels(
-    exprs: &[String],
-    q: &[f64],
-    v: &[f64],
-    t: f64,
+/// Run a symplectic integrator for a split Hamiltonian system.
+///
+/// `accel_exprs` — N acceleration expressions a_i(q, v, t). Each expression
+///     may use: `t`, `y0..y{N-1}` (positions), `y{N}..y{2N-1}` (velocities),
+///     and named params.
+///
+/// `y0` — initial state \[q_0..q_{N-1}, v_0..v_{N-1}\], length 2N.
+///
+/// `state_names` — column header names for positions then velocities
+///     (used in output table). If empty, defaults to `["q0".."qN", "v0".."vN"]`.
+pub fn solve_symplectic(
+    accel_exprs: &[String],
+    y0: &[f64],
+    config: &OdeSolverConfig,
     params: &HashMap<String, f64>,
-    n: usize,
-) -> Vec<f64> {
-    let mut vars: HashMap<String, f64> = params.clone();
-    vars.insert("t".to_string(), t);
-    // Positions: y0..yN-1
-    for (i, &qi) in q.iter().enumerate() {
-        vars.insert(format!("y{i}"), qi);
-    }
-    // Velocities: yN..y2N-1
-    for (i, &vi) in v.iter().enumerate() {
-        vars.insert(format!("y{}", i + n), vi);
-    }
-    exprs.iter().map(|expr| eval_expr(expr, &vars).unwrap_or(f64::NAN)).collect()
+    state_names: &[String],
+    method: SympMethod,
+) -> OdeResult {
+    let system = SymplecticSystem::SecondOrder { accelerations: accel_exprs.to_vec(), potential: None };
+    let options = SymplecticOptions { method, ..Default::default() };
+    solve_symplectic_system(&system, y0, config, params, state_names, &options).unwrap_or_else(|_| OdeResult {
+        t: vec![],
+        states: vec![],
+        column_names: vec!["t".to_string()],
+        steps: 0,
+    })
 }
 
 #[cfg(test)]
@@ -179,6 +498,7 @@ mod tests {
             dt: 0.1,
             tolerance: 1e-6,
             max_steps: 10_000,
+            ..Default::default()
         };
         let accel = vec!["-y0".to_string()]; // d²q/dt² = -q
         let y0 = [1.0_f64, 0.0]; // q=1, v=0
@@ -201,6 +521,7 @@ mod tests {
             dt: 0.05,
             tolerance: 1e-6,
             max_steps: 1_000,
+            ..Default::default()
         };
         let accel = vec!["-y0".to_string()];
         let y0 = [1.0_f64, 0.0];
@@ -226,4 +547,98 @@ mod tests {
         let result = solve_symplectic(&accel, &y0, &config, &params, &[], SympMethod::VelocityVerlet);
         assert_eq!(result.column_names, vec!["t", "q0", "q1", "v0", "v1"]);
     }
+
+    fn oscillator() -> SymplecticSystem {
+        SymplecticSystem::Hamiltonian(Hamiltonian::Separable {
+            kinetic: "0.5 * p0^2".to_string(),
+            potential: "0.5 * q0^2".to_string(),
+        })
+    }
+
+    fn final_error(system: &SymplecticSystem, method: SympMethod, dt: f64) -> f64 {
+        let config = OdeSolverConfig { t_end: 2.0, dt, max_steps: 100_000, ..Default::default() };
+        let options = SymplecticOptions { method, ..Default::default() };
+        let r = solve_symplectic_system(system, &[1.0, 0.0], &config, &HashMap::new(), &[], &options).unwrap();
+        let last = r.states.last().unwrap();
+        (last[0] - 2f64.cos()).abs().max((last[1] + 2f64.sin()).abs())
+    }
+
+    #[test]
+    fn composition_methods_reach_their_order() {
+        for (method, order, dt) in [
+            (SympMethod::VelocityVerlet, 2.0, 0.05),
+            (SympMethod::Yoshida4, 4.0, 0.05),
+            (SympMethod::ForestRuth, 4.0, 0.05),
+            (SympMethod::Yoshida6, 6.0, 0.2),
+        ] {
+            let observed = (final_error(&oscillator(), method, dt) / final_error(&oscillator(), method, dt / 2.0)).log2();
+            assert!((observed - order).abs() < 0.3, "{method:?}: observed order {observed}");
+        }
+    }
+
+    #[test]
+    fn general_hamiltonian_uses_implicit_midpoint() {
+        // The midpoint rule conserves quadratic invariants exactly.
+        let general = SymplecticSystem::Hamiltonian(Hamiltonian::General("0.5 * (p0^2 + q0^2)".to_string()));
+        let config = OdeSolverConfig { t_end: 50.0, dt: 0.1, max_steps: 10_000, ..Default::default() };
+        let r = solve_symplectic_system(&general, &[1.0, 0.0], &config, &HashMap::new(), &[], &SymplecticOptions::default())
+            .unwrap();
+        assert_eq!(r.column_names, ["t", "q0", "p0", "energy_drift"]);
+        assert!(r.states.iter().all(|row| row[2].abs() < 1e-12));
+        // Composed, it is fourth order like the explicit methods.
+        let observed = (final_error(&general, SympMethod::Yoshida4, 0.1) / final_error(&general, SympMethod::Yoshida4, 0.05)).log2();
+        assert!((observed - 4.0).abs() < 0.3, "observed order {observed}");
+    }
+
+    /// Kepler orbit with eccentricity 0.6 (μ = 1, period 2π).
+    fn kepler(adaptive: bool, dt: f64) -> OdeResult {
+        let e: f64 = 0.6;
+        let system = SymplecticSystem::Hamiltonian(Hamiltonian::Separable {
+            kinetic: "0.5 * (p0^2 + p1^2)".to_string(),
+            potential: "-1 / sqrt(q0^2 + q1^2)".to_string(),
+        });
+        let y0 = [1.0 - e, 0.0, 0.0, ((1.0 + e) / (1.0 - e)).sqrt()];
+        let config = OdeSolverConfig {
+            t_end: 20.0 * std::f64::consts::PI,
+            dt,
+            max_steps: 1_000_000,
+            ..Default::default()
+        };
+        let options = SymplecticOptions {
+            method: SympMethod::Yoshida4,
+            adaptive,
+            angular_dim: Some(2),
+            ..Default::default()
+        };
+        solve_symplectic_system(&system, &y0, &config, &HashMap::new(), &[], &options).unwrap()
+    }
+
+    #[test]
+    fn adaptive_kepler_orbit_keeps_energy_and_angular_momentum() {
+        let fixed = kepler(false, 0.02);
+        // The first adaptive step (at periapsis) is the smallest of the orbit.
+        let adaptive = kepler(true, 0.006);
+        assert_eq!(adaptive.column_names[5..], ["energy_drift", "angular_momentum_drift"]);
+        let max_drift = |r: &OdeResult, col: usize| r.states.iter().map(|s| s[col].abs()).fold(0.0, f64::max);
+        // Central forces: kicks and drifts preserve q × p exactly.
+        assert!(max_drift(&fixed, 5) < 1e-12 && max_drift(&adaptive, 5) < 1e-12);
+        // Reversible adaptivity spends fewer steps for a smaller energy error.
+        assert!(adaptive.steps < fixed.steps, "{} vs {} steps", adaptive.steps, fixed.steps);
+        assert!(max_drift(&adaptive, 4) < 0.1 * max_drift(&fixed, 4));
+        // No secular drift: the last orbit is no worse than the first.
+        let per_orbit = adaptive.t.len() / 10;
+        let orbit_max = |rows: &[Vec<f64>]| rows.iter().map(|s| s[4].abs()).fold(0.0, f64::max);
+        let (first, last) = (orbit_max(&adaptive.states[..per_orbit]), orbit_max(&adaptive.states[9 * per_orbit..]));
+        assert!(last < 2.0 * first, "energy error grew from {first} to {last}");
+    }
+
+    #[test]
+    fn rejects_inconsistent_options() {
+        let config = OdeSolverConfig::default();
+        let params = HashMap::new();
+        let bad_dim = SymplecticOptions { angular_dim: Some(3), ..Default::default() };
+        assert!(solve_symplectic_system(&oscillator(), &[1.0, 0.0], &config, &params, &[], &bad_dim).is_err());
+        assert!(solve_symplectic_system(&oscillator(), &[1.0, 0.0, 0.0], &config, &params, &[], &Default::default()).is_err());
+        assert_eq!(parse_symp_method("Forest-Ruth"), SympMethod::ForestRuth);
+    }
 }
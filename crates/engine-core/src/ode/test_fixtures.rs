@@ -0,0 +1,41 @@
+//! Test systems shared by the implicit solver and Jacobian tests.
+
+use super::OdeSystem;
+use std::collections::HashMap;
+
+/// Robertson's stiff chemical kinetics problem; start it from `y = (1, 0, 0)`.
+pub(crate) fn robertson() -> OdeSystem {
+    OdeSystem {
+        equations: vec![
+            "-0.04*y0 + 1e4*y1*y2".to_string(),
+            "0.04*y0 - 1e4*y1*y2 - 3e7*y1^2".to_string(),
+            "3e7*y1^2".to_string(),
+        ],
+        state_names: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        params: HashMap::new(),
+    }
+}
+
+/// Method-of-lines heat equation `u_i' = k·(u_{i-1} - 2u_i + u_{i+1}) + r(u_i)`
+/// on `n` interior points of [0, 1] with zero ends and `k = 1/Δx²`.
+///
+/// `reaction` is the source term with `{y}` standing for the local state, e.g.
+/// `"- {y}^3"`, or empty. Returns the system and `u0 = sin(πx)`.
+pub(crate) fn mol_heat(n: usize, reaction: &str) -> (OdeSystem, Vec<f64>) {
+    let equations = (0..n)
+        .map(|i| {
+            let left = if i == 0 { "0".to_string() } else { format!("y{}", i - 1) };
+            let right = if i + 1 == n { "0".to_string() } else { format!("y{}", i + 1) };
+            let source = reaction.replace("{y}", &format!("y{i}"));
+            format!("k*({left} - 2*y{i} + {right}) {source}")
+        })
+        .collect();
+    let dx = 1.0 / (n + 1) as f64;
+    let sys = OdeSystem {
+        equations,
+        state_names: (0..n).map(|i| format!("u{i}")).collect(),
+        params: HashMap::from([("k".to_string(), 1.0 / (dx * dx))]),
+    };
+    let u0 = (1..=n).map(|i| (std::f64::consts::PI * i as f64 * dx).sin()).collect();
+    (sys, u0)
+}
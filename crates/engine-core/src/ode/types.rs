@@ -5,8 +5,8 @@ use std::collections::HashMap;
 /// Definition of an ODE system: dy/dt = f(t, y, params).
 ///
 /// Each equation is a string expression (parsed by `expr::eval_expr`)
-/// where `t` is the time variable, `y0`..`yN` are state variables,
-/// and params are named constants.
+/// where `t` is the time variable, `y0`..`yN` are state variables
+/// (also reachable as `y[i]`), and params are named constants.
 #[derive(Debug, Clone)]
 pub struct OdeSystem {
     /// Expression strings for each state derivative: dy_i/dt = `equations[i]`.
@@ -31,6 +31,62 @@ pub struct OdeSolverConfig {
     pub tolerance: f64,
     /// Maximum number of steps (safety limit).
     pub max_steps: usize,
+    /// Times at which the result is reported.
+    pub output: OutputTimes,
+}
+
+/// Most output times an [`OutputTimes::Interval`] grid may hold.
+pub const MAX_OUTPUT_TIMES: usize = 100_000;
+
+/// Where an [`OdeResult`] samples the solution.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum OutputTimes {
+    /// Every accepted step.
+    #[default]
+    Steps,
+    /// Exactly these times (sorted; those outside `[t_start, t_end]` are dropped).
+    Times(Vec<f64>),
+    /// `t_start`, `t_start + Δ`, ... up to and including `t_end`.
+    Interval(f64),
+}
+
+impl OutputTimes {
+    /// The requested times within `[t_start, t_end]`, or `None` for [`Steps`](Self::Steps).
+    ///
+    /// An interval that is not positive and finite, or a grid of more than
+    /// [`MAX_OUTPUT_TIMES`] points, is an error.
+    pub fn times(&self, t_start: f64, t_end: f64) -> Result<Option<Vec<f64>>, String> {
+        match self {
+            OutputTimes::Steps => Ok(None),
+            OutputTimes::Times(times) => {
+                let mut times: Vec<f64> = times
+                    .iter()
+                    .copied()
+                    .filter(|t| t.is_finite() && *t >= t_start && *t <= t_end)
+                    .collect();
+                times.sort_by(f64::total_cmp);
+                times.dedup();
+                Ok(Some(times))
+            }
+            OutputTimes::Interval(dt) => {
+                if dt.is_nan() || *dt <= 0.0 || dt.is_infinite() {
+                    return Err(format!("output_interval must be positive and finite, got {dt}"));
+                }
+                let n = ((t_end - t_start) / dt * (1.0 + 1e-12)).floor().max(0.0);
+                if n + 1.0 > MAX_OUTPUT_TIMES as f64 {
+                    return Err(format!(
+                        "{} output times exceeds the {MAX_OUTPUT_TIMES} point limit; increase output_interval or shorten the time span",
+                        n + 1.0
+                    ));
+                }
+                let mut times: Vec<f64> = (0..=n as usize).map(|i| t_start + i as f64 * dt).collect();
+                if times.last().is_some_and(|&t| t_end - t > 1e-12 * t_end.abs().max(1.0)) {
+                    times.push(t_end);
+                }
+                Ok(Some(times))
+            }
+        }
+    }
 }
 
 impl Default for OdeSolverConfig {
@@ -41,6 +97,7 @@ impl Default for OdeSolverConfig {
             dt: 0.01,
             tolerance: 1e-6,
             max_steps: 100_000,
+            output: OutputTimes::Steps,
         }
     }
 }
@@ -48,9 +105,10 @@ impl Default for OdeSolverConfig {
 /// Result of an ODE solver run: time series of state variables.
 #[derive(Debug, Clone)]
 pub struct OdeResult {
-    /// Time values at each step.
+    /// Output times: each accepted step, or the times requested by
+    /// [`OdeSolverConfig::output`].
     pub t: Vec<f64>,
-    /// State values at each step. `states[i]` is the vector of state
+    /// State values at each output time. `states[i]` is the vector of state
     /// variable values at time `t[i]`.
     pub states: Vec<Vec<f64>>,
     /// Column names: `["t", state_names[0], state_names[1], ...]`
@@ -58,3 +116,30 @@ pub struct OdeResult {
     /// Number of steps taken.
     pub steps: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_times_resolve_within_range() {
+        assert_eq!(OutputTimes::Steps.times(0.0, 1.0), Ok(None));
+        let times = OutputTimes::Times(vec![2.0, 0.5, -1.0, 0.5, 0.0]).times(0.0, 1.0);
+        assert_eq!(times, Ok(Some(vec![0.0, 0.5])));
+        let grid = OutputTimes::Interval(0.3).times(0.0, 1.0).unwrap().unwrap();
+        assert_eq!(grid.len(), 5);
+        assert_eq!(grid[4], 1.0);
+        assert_eq!(OutputTimes::Interval(0.25).times(0.0, 1.0).unwrap().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn bad_or_oversized_intervals_are_errors() {
+        for dt in [0.0, -0.1, f64::NAN, f64::INFINITY] {
+            assert!(OutputTimes::Interval(dt).times(0.0, 1.0).is_err(), "dt = {dt}");
+        }
+        let err = OutputTimes::Interval(1e-9).times(0.0, 1.0).unwrap_err();
+        assert!(err.contains("point limit"), "{err}");
+        let grid = OutputTimes::Interval(1e-5).times(0.0, 0.99999).unwrap().unwrap();
+        assert!(grid.len() <= MAX_OUTPUT_TIMES);
+    }
+}
@@ -60,6 +60,276 @@ fn scalar_or(data: &HashMap<String, serde_json::Value>, key: &str, default: f64)
     data.get(key).and_then(|v| v.as_f64()).unwrap_or(default)
 }
 
+/// ODE output times: a `t_eval` input vector or data array, else an
+/// `output_interval`, else every accepted step. A scalar `t_eval` is a single
+/// output time. Errors when the interval is not positive and finite or its
+/// grid over `[t_start, t_end]` is too large.
+fn ode_output(
+    inputs: &HashMap<String, Value>,
+    data: &HashMap<String, serde_json::Value>,
+    t_start: f64,
+    t_end: f64,
+) -> Result<crate::ode::OutputTimes, String> {
+    use crate::ode::OutputTimes;
+    match inputs.get("t_eval") {
+        Some(Value::Vector { value }) => return Ok(OutputTimes::Times(value.clone())),
+        Some(Value::Scalar { value }) => return Ok(OutputTimes::Times(vec![*value])),
+        _ => {}
+    }
+    match data.get("t_eval") {
+        Some(serde_json::Value::Array(times)) => {
+            let times: Vec<f64> = times.iter().filter_map(|v| v.as_f64()).collect();
+            if !times.is_empty() {
+                return Ok(OutputTimes::Times(times));
+            }
+        }
+        Some(serde_json::Value::Number(t)) => {
+            if let Some(t) = t.as_f64() {
+                return Ok(OutputTimes::Times(vec![t]));
+            }
+        }
+        _ => {}
+    }
+    let output = match data.get("output_interval").and_then(|v| v.as_f64()) {
+        Some(dt) => OutputTimes::Interval(dt),
+        None => OutputTimes::Steps,
+    };
+    output.times(t_start, t_end)?;
+    Ok(output)
+}
+
+/// Acausal network from a `network` input (an `acausal.network` block), else
+/// from data `components` and `connections`.
+fn acausal_network(
+    inputs: &HashMap<String, Value>,
+    data: &HashMap<String, serde_json::Value>,
+) -> Result<crate::acausal::AcausalNetwork, String> {
+    let spec = match inputs.get("network") {
+        Some(Value::Text { value }) => serde_json::from_str(value).map_err(|e| format!("invalid network JSON: {e}"))?,
+        Some(Value::Error { message }) => return Err(message.clone()),
+        _ => serde_json::json!({
+            "components": data.get("components").cloned().unwrap_or(serde_json::Value::Null),
+            "connections": data.get("connections").cloned().unwrap_or_else(|| serde_json::json!([])),
+        }),
+    };
+    crate::acausal::AcausalNetwork::from_json(&spec)
+}
+
+/// FEM geometry from `polygon` (`[[x, y], …]`, else the rectangle
+/// `x0..x1 × y0..y1`) and `holes`, meshed at element size `h`.
+fn fem_mesh(data: &HashMap<String, serde_json::Value>) -> Result<crate::fem::mesh::TriMesh, String> {
+    use crate::fem::mesh::{triangulate, Geometry};
+    let points = |v: &serde_json::Value| -> Option<Vec<(f64, f64)>> {
+        v.as_array()?
+            .iter()
+            .map(|p| Some((p.get(0)?.as_f64()?, p.get(1)?.as_f64()?)))
+            .collect()
+    };
+    let mut geometry = match data.get("polygon") {
+        Some(v) => Geometry { outer: points(v).ok_or("'polygon' must be an array of [x, y] pairs")?, holes: Vec::new() },
+        None => Geometry::rectangle(
+            scalar_or(data, "x0", 0.0),
+            scalar_or(data, "y0", 0.0),
+            scalar_or(data, "x1", 1.0),
+            scalar_or(data, "y1", 1.0),
+        ),
+    };
+    if let Some(serde_json::Value::Array(holes)) = data.get("holes") {
+        for hole in holes {
+            geometry.holes.push(points(hole).ok_or("each hole must be an array of [x, y] pairs")?);
+        }
+    }
+    triangulate(&geometry, scalar_or(data, "h", 0.1))
+}
+
+/// FEM boundary conditions from `bcs: [{ edges: [..], type, ... }]`. Value
+/// keys are `dirichlet`/`neumann` component names, and a Robin condition
+/// reads `robin_coef` plus `robin_ambient`.
+fn fem_bcs(
+    data: &HashMap<String, serde_json::Value>,
+    dirichlet: &[&str],
+    neumann: &[&str],
+    robin_coef: &str,
+    robin_ambient: &[&str],
+) -> Result<crate::fem::BoundaryConditions, String> {
+    use crate::fem::BoundaryCondition;
+    let text = |v: &serde_json::Value| match v {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    };
+    let mut out = crate::fem::BoundaryConditions::new();
+    let Some(serde_json::Value::Array(bcs)) = data.get("bcs") else { return Ok(out) };
+    for bc in bcs {
+        let field = |key: &str| bc.get(key).and_then(text);
+        let edges: Vec<usize> = match (bc.get("edges"), bc.get("edge")) {
+            (Some(serde_json::Value::Array(es)), _) => es.iter().filter_map(|e| e.as_u64()).map(|e| e as usize).collect(),
+            (_, Some(e)) => e.as_u64().map(|e| vec![e as usize]).unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        if edges.is_empty() {
+            return Err("each boundary condition needs 'edges' (edge markers)".to_string());
+        }
+        let kind = bc.get("type").and_then(|v| v.as_str()).unwrap_or("dirichlet").to_lowercase();
+        let condition = match kind.as_str() {
+            "dirichlet" | "fixed" => BoundaryCondition::Dirichlet(dirichlet.iter().map(|k| field(k)).collect()),
+            "neumann" | "flux" | "traction" => {
+                BoundaryCondition::Neumann(neumann.iter().map(|k| field(k).unwrap_or_else(|| "0".to_string())).collect())
+            }
+            "robin" | "convection" | "spring" => BoundaryCondition::Robin {
+                coefficient: field(robin_coef).ok_or(format!("robin condition needs '{robin_coef}'"))?,
+                ambient: robin_ambient.iter().map(|k| field(k).unwrap_or_else(|| "0".to_string())).collect(),
+            },
+            other => return Err(format!("unknown boundary condition type '{other}'")),
+        };
+        for e in edges {
+            out.insert(e, condition.clone());
+        }
+    }
+    Ok(out)
+}
+
+fn fem_table(data: &HashMap<String, serde_json::Value>, nodal: (Vec<String>, Vec<Vec<f64>>), elements: impl FnOnce() -> (Vec<String>, Vec<Vec<f64>>)) -> Value {
+    let (columns, rows) = match data.get("output").and_then(|v| v.as_str()) {
+        Some("elements") => elements(),
+        _ => nodal,
+    };
+    Value::Table { columns, rows }
+}
+
+/// Mass, stiffness or damping input: a square matrix, a vector (diagonal)
+/// or a scalar (one degree of freedom). `Ok(None)` when not connected.
+fn structural_input(inputs: &HashMap<String, Value>, key: &str) -> Result<Option<crate::modal::StructuralMatrix>, String> {
+    use crate::modal::StructuralMatrix;
+    match inputs.get(key) {
+        None => Ok(None),
+        Some(Value::Matrix { rows, cols, data }) if rows == cols => Ok(Some(StructuralMatrix::Dense { n: *rows, data: data.clone() })),
+        Some(Value::Matrix { rows, cols, .. }) => Err(format!("'{key}' must be square, got {rows}×{cols}")),
+        Some(Value::Vector { value }) => Ok(Some(StructuralMatrix::Diagonal(value.clone()))),
+        Some(Value::Scalar { value }) => Ok(Some(StructuralMatrix::Diagonal(vec![*value]))),
+        Some(Value::Error { message }) => Err(message.clone()),
+        Some(_) => Err(format!("'{key}' must be a matrix, vector or scalar")),
+    }
+}
+
+/// Variables for the symbolic matrix blocks: a comma list from the `vars`
+/// input or `data.vars`, else the free symbols of `exprs` in first-seen order.
+fn symbolic_var_list(
+    inputs: &HashMap<String, Value>,
+    data: &HashMap<String, serde_json::Value>,
+    exprs: &[crate::symbolic::Expr],
+) -> Vec<String> {
+    let listed = match inputs.get("vars") {
+        Some(Value::Text { value }) => value.clone(),
+        _ => data.get("vars").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+    };
+    let mut vars: Vec<String> = listed.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect();
+    if vars.is_empty() {
+        for v in exprs.iter().flat_map(crate::symbolic::free_variables) {
+            if !vars.contains(&v) {
+                vars.push(v);
+            }
+        }
+    }
+    vars
+}
+
+/// Sparse operand on `port`: a sparse matrix, or a dense matrix with its exact
+/// zeros dropped. Errors propagate as `Err(Value::Error)`.
+fn sparse_operand(inputs: &HashMap<String, Value>, port: &str, block: &str) -> Result<crate::sparse::CsrMatrix, Value> {
+    match inputs.get(port) {
+        Some(v @ Value::SparseMatrix { .. }) => {
+            v.as_sparse().ok_or_else(|| Value::error(format!("{block}: malformed sparse matrix on '{port}'")))
+        }
+        Some(Value::Matrix { rows, cols, data }) => Ok(crate::sparse::dense_to_csr(*rows, *cols, data, 0.0)),
+        Some(e @ Value::Error { .. }) => Err(e.clone()),
+        _ => Err(Value::error(format!("{block}: expected a sparse or dense matrix on '{port}'"))),
+    }
+}
+
+/// `matrix.*` blocks given a sparse operand: transpose, trace, multiply and
+/// solve work on the CSR data directly; every other op sees the densified matrix.
+fn sparse_matrix_op(
+    block_type: &str,
+    inputs: &HashMap<String, Value>,
+    data: &HashMap<String, serde_json::Value>,
+    datasets: Option<&HashMap<String, Vec<f64>>>,
+    blobs: Option<&HashMap<String, Vec<u8>>>,
+) -> Value {
+    if inputs.values().any(|v| matches!(v, Value::SparseMatrix { .. }) && v.as_sparse().is_none()) {
+        return Value::error(format!("{block_type}: malformed sparse matrix input"));
+    }
+    let single = inputs.get("matrix").or_else(|| inputs.get("m")).and_then(Value::as_sparse);
+    match (&block_type["matrix".len() + 1..], single) {
+        ("transpose", Some(m)) => Value::sparse(m.transpose()),
+        ("trace", Some(m)) => Value::scalar(m.diagonal().iter().sum()),
+        ("multiply", _) => evaluate_node_inner("sparse.multiply", inputs, data, datasets, blobs),
+        ("solve", _) => evaluate_node_inner("sparse.solve", inputs, data, datasets, blobs),
+        _ => {
+            let dense: HashMap<String, Value> = inputs
+                .iter()
+                .map(|(k, v)| (k.clone(), v.as_sparse().map(|m| m.to_dense()).unwrap_or_else(|| v.clone())))
+                .collect();
+            evaluate_node_inner(block_type, &dense, data, datasets, blobs)
+        }
+    }
+}
+
+/// Solve a sparse system with the method named by `data.method`: `auto`
+/// (Cholesky when symmetric positive definite, else LU), `cholesky`, `lu`, or
+/// ILU(0)-preconditioned `cg` / `bicgstab` / `gmres`.
+fn sparse_solve(a: &crate::sparse::CsrMatrix, b: &[f64], data: &HashMap<String, serde_json::Value>) -> Result<Vec<f64>, String> {
+    use crate::sparse_direct::{FillOrdering, SymbolicCholesky, SymbolicLu};
+    use crate::sparse_solvers::{bicgstab, cg, gmres, Ilu0, SolverConfig};
+    if a.rows != a.cols {
+        return Err(format!("matrix must be square, got {}×{}", a.rows, a.cols));
+    }
+    if b.len() != a.rows {
+        return Err(format!("b has length {}, expected {}", b.len(), a.rows));
+    }
+    let method = data.get("method").and_then(|v| v.as_str()).unwrap_or("auto");
+    let ordering = FillOrdering::parse(data.get("ordering").and_then(|v| v.as_str()).unwrap_or("amd"))?;
+    let cholesky = |csc: &crate::sparse::CscMatrix| {
+        let f = SymbolicCholesky::analyze(csc, ordering)?.factor(csc)?;
+        if !f.is_positive_definite() {
+            return Err("matrix is not positive definite".to_string());
+        }
+        f.solve(b)
+    };
+    let lu = |csc: &crate::sparse::CscMatrix| SymbolicLu::analyze(csc, ordering)?.factor(csc)?.solve(b);
+    match method {
+        "auto" => {
+            let csc = a.to_csc();
+            if a.is_symmetric(0.0) {
+                if let Ok(x) = cholesky(&csc) {
+                    return Ok(x);
+                }
+            }
+            lu(&csc)
+        }
+        "cholesky" => cholesky(&a.to_csc()),
+        "lu" => lu(&a.to_csc()),
+        "cg" | "bicgstab" | "gmres" => {
+            let config = SolverConfig {
+                max_iter: scalar_or(data, "maxIter", 1000.0).max(1.0) as usize,
+                tol: scalar_or(data, "tol", 1e-10),
+            };
+            let pc = Ilu0::new(a);
+            let r = match method {
+                "cg" => cg(a, b, &config, Some(&pc)),
+                "bicgstab" => bicgstab(a, b, &config, Some(&pc)),
+                _ => gmres(a, b, &config, scalar_or(data, "restart", 50.0).max(1.0) as usize, Some(&pc)),
+            };
+            if r.converged {
+                Ok(r.x)
+            } else {
+                Err(format!("{method} did not converge (residual {:.2e} after {} iterations)", r.residual_norm, r.iterations))
+            }
+        }
+        other => Err(format!("unknown method '{other}' (expected auto, cholesky, lu, cg, bicgstab or gmres)")),
+    }
+}
+
 /// Evaluate a single node given its block type, resolved input values,
 /// the node's own data map, and an optional dataset registry.
 ///
@@ -69,29 +339,59 @@ pub fn evaluate_node(
     inputs: &HashMap<String, Value>,
     data: &HashMap<String, serde_json::Value>,
 ) -> Value {
-    evaluate_node_with_datasets(block_type, inputs, data, None)
+    evaluate_node_with_datasets(block_type, inputs, data, None, None)
 }
 
-/// Like `evaluate_node` but with access to a dataset registry.
+/// Like `evaluate_node` but with access to the dataset registries.
 /// Data blocks (`vectorInput`, `tableInput`, `csvImport`) will check
-/// `data.datasetRef` and look up large arrays from the registry.
+/// `data.datasetRef` and look up large arrays from the registry; file
+/// importers (`mat_import`, `parquet_import`) read `data.blobRef` from the
+/// binary registry.
 pub fn evaluate_node_with_datasets(
     block_type: &str,
     inputs: &HashMap<String, Value>,
     data: &HashMap<String, serde_json::Value>,
     datasets: Option<&HashMap<String, Vec<f64>>>,
+    blobs: Option<&HashMap<String, Vec<u8>>>,
 ) -> Value {
-    let raw = evaluate_node_inner(block_type, inputs, data, datasets);
+    let raw = evaluate_node_inner(block_type, inputs, data, datasets, blobs);
     canonicalize_value(raw)
 }
 
+/// File bytes for an import block: the binary dataset named by
+/// `data.blobRef` if registered, else the legacy JSON byte array in
+/// `data[legacy_key]`.
+fn file_bytes<'a>(
+    data: &HashMap<String, serde_json::Value>,
+    blobs: Option<&'a HashMap<String, Vec<u8>>>,
+    legacy_key: &str,
+) -> std::borrow::Cow<'a, [u8]> {
+    if let Some(blob) = data
+        .get("blobRef")
+        .and_then(|v| v.as_str())
+        .and_then(|id| blobs.and_then(|b| b.get(id)))
+    {
+        return std::borrow::Cow::Borrowed(blob);
+    }
+    data.get(legacy_key)
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|b| b.as_u64().map(|n| n as u8)).collect())
+        .unwrap_or_default()
+}
+
 /// Inner dispatch — not canonicalized. Called by evaluate_node_with_datasets.
 fn evaluate_node_inner(
     block_type: &str,
     inputs: &HashMap<String, Value>,
     data: &HashMap<String, serde_json::Value>,
     datasets: Option<&HashMap<String, Vec<f64>>>,
+    blobs: Option<&HashMap<String, Vec<u8>>>,
 ) -> Value {
+    if (block_type.starts_with("matrix.") || block_type.starts_with("matrix_"))
+        && inputs.values().any(|v| matches!(v, Value::SparseMatrix { .. }))
+    {
+        return sparse_matrix_op(block_type, inputs, data, datasets, blobs);
+    }
     match block_type {
         // ── Sources (0 inputs) ────────────────────────────────────
         "number" | "slider" | "variableSource" | "boolean_input" => {
@@ -1870,6 +2170,10 @@ fn evaluate_node_inner(
             let deriv    = if dt == 0.0 { 0.0 } else { error / dt };
             Value::scalar(kp * error + ki * integral + kd * deriv)
         }
+        // Discrete-time stateful blocks. Stateless evaluation yields the
+        // step-0 output; EngineGraph carries the state across steps.
+        "ctrl.unit_delay" | "ctrl.discrete_integrator" | "ctrl.zoh" | "ctrl.rate_limiter"
+        | "ctrl.discrete_tf" => crate::discrete::initial_output(block_type, inputs, data),
         "ctrl.rms" => {
             match inputs.get("y") {
                 Some(Value::Vector { value }) if !value.is_empty() => {
@@ -2718,21 +3022,36 @@ fn evaluate_node_inner(
             if formula.is_empty() {
                 return Value::error("Custom function: no formula".to_string());
             }
-            // Build variable map from scalar inputs
+            // Scalar inputs bind as variables, vector inputs as `name[i]`.
             let mut vars = std::collections::HashMap::new();
+            let mut vectors = std::collections::HashMap::new();
             for (key, val) in inputs {
                 match val {
                     Value::Scalar { value: s } => { vars.insert(key.clone(), *s); }
+                    Value::Vector { value: v } => { vectors.insert(key.clone(), v.clone()); }
                     Value::Error { .. } => return val.clone(),
-                    _ => {} // vectors/tables not supported in expressions
+                    _ => {} // tables/matrices not supported in expressions
                 }
             }
-            match crate::expr::eval_expr(formula, &vars) {
+            match crate::expr::compile(formula).and_then(|c| c.eval_with_vectors(&vars, &vectors)) {
                 Ok(v) => Value::scalar(v),
                 Err(msg) => Value::error(format!("Custom function: {}", msg)),
             }
         }
 
+        // Graph-level user function. The graph collects these before evaluating
+        // anything; the block itself just reports its signature or what is wrong.
+        "expr.define" => {
+            let (name, params, body) = crate::expr::definition_from_data(data);
+            if name.is_empty() || body.is_empty() {
+                return Value::error("Define function: name and body are required");
+            }
+            match crate::expr::check_definition(&name, &params, &body) {
+                Ok(signature) => Value::Text { value: signature },
+                Err(msg) => Value::error(format!("Define function: {}", msg)),
+            }
+        }
+
         // ── SCI-10: Numerical Methods ─────────────────────────────────────────
 
         // Trapezoidal integration: ∫y dx ≈ dx*(y[0]/2 + y[1]+…+y[n-2] + y[n-1]/2)
@@ -3777,6 +4096,189 @@ fn evaluate_node_inner(
             }
         }
 
+        // ── Sparse matrices ──────────────────────────────────────────────────
+
+        // Dense matrix → CSR, dropping entries with |aᵢⱼ| ≤ threshold
+        "sparse.fromMatrix" => match inputs.get("matrix") {
+            Some(Value::Matrix { rows, cols, data: m }) => {
+                Value::sparse(crate::sparse::dense_to_csr(*rows, *cols, m, scalar_or(data, "threshold", 0.0)))
+            }
+            Some(v @ Value::SparseMatrix { .. }) => match v.as_sparse() {
+                Some(_) => v.clone(),
+                None => Value::error("sparse.fromMatrix: malformed sparse matrix input"),
+            },
+            Some(e @ Value::Error { .. }) => e.clone(),
+            _ => Value::error("sparse.fromMatrix: expected matrix input"),
+        },
+
+        // Table of 0-based (row, col, value) triplets → CSR; duplicates are summed
+        "sparse.fromTable" => match inputs.get("table") {
+            Some(Value::Table { columns, rows }) => {
+                if columns.len() < 3 {
+                    return Value::error("sparse.fromTable: table needs row, col and value columns");
+                }
+                let mut triplets = Vec::with_capacity(rows.len());
+                for row in rows {
+                    let [i, j, v, ..] = row[..] else {
+                        return Value::error("sparse.fromTable: every row needs row, col and value");
+                    };
+                    if !(i >= 0.0 && j >= 0.0 && i.fract() == 0.0 && j.fract() == 0.0) {
+                        return Value::error(format!("sparse.fromTable: invalid index ({i}, {j})"));
+                    }
+                    triplets.push((i as usize, j as usize, v));
+                }
+                let fit = |key: &str, needed: usize| (scalar_or(data, key, 0.0).max(0.0) as usize).max(needed);
+                let n_rows = fit("rows", triplets.iter().map(|t| t.0 + 1).max().unwrap_or(0));
+                let n_cols = fit("cols", triplets.iter().map(|t| t.1 + 1).max().unwrap_or(0));
+                let mut coo = crate::sparse::CooMatrix::with_capacity(n_rows, n_cols, triplets.len());
+                for (i, j, v) in triplets {
+                    coo.push(i, j, v);
+                }
+                Value::sparse(coo.to_csr())
+            }
+            Some(e @ Value::Error { .. }) => e.clone(),
+            _ => Value::error("sparse.fromTable: expected table input"),
+        },
+
+        "sparse.toMatrix" => match sparse_operand(inputs, "matrix", "sparse.toMatrix") {
+            Ok(m) => m.to_dense(),
+            Err(e) => e,
+        },
+
+        // CSR → table of 0-based (row, col, value) triplets, e.g. for a spy plot
+        "sparse.toTable" => match sparse_operand(inputs, "matrix", "sparse.toTable") {
+            Ok(m) => {
+                let coo = m.to_coo();
+                let rows = (0..coo.nnz())
+                    .map(|k| vec![coo.row_indices[k] as f64, coo.col_indices[k] as f64, coo.values[k]])
+                    .collect();
+                Value::Table { columns: vec!["row".into(), "col".into(), "value".into()], rows }
+            }
+            Err(e) => e,
+        },
+
+        // A·b: vector → vector, dense matrix → dense matrix, sparse → sparse
+        "sparse.multiply" => {
+            let a = match sparse_operand(inputs, "a", "sparse.multiply") {
+                Ok(a) => a,
+                Err(e) => return e,
+            };
+            let mismatch = |rows: usize| Value::error(format!("sparse.multiply: A has {} columns but b has {rows} rows", a.cols));
+            match inputs.get("b") {
+                Some(Value::Vector { value }) if value.len() == a.cols => Value::Vector { value: a.mul_vec(value) },
+                Some(Value::Vector { value }) => mismatch(value.len()),
+                Some(Value::Matrix { rows, cols, data: m }) if *rows == a.cols => {
+                    Value::Matrix { rows: a.rows, cols: *cols, data: a.mul_dense(*rows, *cols, m) }
+                }
+                Some(Value::Matrix { rows, .. }) => mismatch(*rows),
+                Some(v @ Value::SparseMatrix { .. }) => {
+                    let Some(b) = v.as_sparse() else {
+                        return Value::error("sparse.multiply: malformed sparse matrix on 'b'");
+                    };
+                    if b.rows != a.cols {
+                        return mismatch(b.rows);
+                    }
+                    Value::sparse(a.mul_csr(&b))
+                }
+                Some(e @ Value::Error { .. }) => e.clone(),
+                _ => Value::error("sparse.multiply: expected vector, matrix or sparse matrix on 'b'"),
+            }
+        }
+
+        "sparse.solve" => {
+            let a = match sparse_operand(inputs, "a", "sparse.solve") {
+                Ok(a) => a,
+                Err(e) => return e,
+            };
+            match inputs.get("b") {
+                Some(Value::Vector { value }) => match sparse_solve(&a, value, data) {
+                    Ok(x) => Value::Vector { value: x },
+                    Err(e) => Value::error(format!("sparse.solve: {e}")),
+                },
+                Some(e @ Value::Error { .. }) => e.clone(),
+                _ => Value::error("sparse.solve: expected vector 'b'"),
+            }
+        }
+
+        // Spy-pattern statistics as a one-row table
+        "sparse.stats" => match sparse_operand(inputs, "matrix", "sparse.stats") {
+            Ok(m) => {
+                let st = crate::sparse::sparsity_stats(&m);
+                let flag = |b: bool| if b { 1.0 } else { 0.0 };
+                crate::types::build_table(
+                    &[
+                        "rows", "cols", "nnz", "density", "lowerBandwidth", "upperBandwidth", "minRowNnz",
+                        "maxRowNnz", "missingDiagonal", "symmetric", "diagDominant",
+                    ],
+                    &[
+                        vec![m.rows as f64],
+                        vec![m.cols as f64],
+                        vec![st.nnz as f64],
+                        vec![st.density],
+                        vec![st.lower_bandwidth as f64],
+                        vec![st.upper_bandwidth as f64],
+                        vec![st.min_row_nnz as f64],
+                        vec![st.max_row_nnz as f64],
+                        vec![st.missing_diagonal as f64],
+                        vec![flag(st.symmetric)],
+                        vec![flag(st.diagonally_dominant)],
+                    ],
+                )
+            }
+            Err(e) => e,
+        },
+
+        // Matrix Market / Harwell–Boeing text from data.blobRef, data.fileBytes or data.text
+        "sparse.import" => {
+            use crate::sparse_io::{read_harwell_boeing, read_matrix_market, read_sparse, SparseFormat};
+            let bytes = file_bytes(data, blobs, "fileBytes");
+            let text = if bytes.is_empty() {
+                data.get("text").and_then(|v| v.as_str()).unwrap_or("").to_string()
+            } else {
+                String::from_utf8_lossy(&bytes).into_owned()
+            };
+            if text.trim().is_empty() {
+                return Value::error("sparse.import: no file data (set 'blobRef', 'fileBytes' or 'text')");
+            }
+            let parsed = match data.get("format").and_then(|v| v.as_str()).unwrap_or("auto") {
+                "auto" => read_sparse(&text),
+                f => SparseFormat::parse(f).and_then(|f| match f {
+                    SparseFormat::MatrixMarket => read_matrix_market(&text),
+                    SparseFormat::HarwellBoeing => read_harwell_boeing(&text),
+                }),
+            };
+            match parsed {
+                Ok(m) => Value::sparse(m),
+                Err(e) => Value::error(format!("sparse.import: {e}")),
+            }
+        }
+
+        // Sparse (or dense) matrix → Matrix Market / Harwell–Boeing file bytes
+        "sparse.export" => {
+            use crate::sparse_io::{write_harwell_boeing, write_matrix_market, SparseFormat};
+            let m = match sparse_operand(inputs, "matrix", "sparse.export") {
+                Ok(m) => m,
+                Err(e) => return e,
+            };
+            let symmetric = data.get("symmetric").and_then(|v| v.as_bool()).unwrap_or(false);
+            let format = match SparseFormat::parse(data.get("format").and_then(|v| v.as_str()).unwrap_or("mtx")) {
+                Ok(f) => f,
+                Err(e) => return Value::error(format!("sparse.export: {e}")),
+            };
+            let text = match format {
+                SparseFormat::MatrixMarket => write_matrix_market(&m, symmetric),
+                SparseFormat::HarwellBoeing => {
+                    let title = data.get("title").and_then(|v| v.as_str()).unwrap_or("ChainSolve sparse matrix");
+                    let key = data.get("key").and_then(|v| v.as_str()).unwrap_or("CSMATRIX");
+                    write_harwell_boeing(&m, title, key, symmetric)
+                }
+            };
+            match text {
+                Ok(t) => Value::bytes(t.into_bytes(), format.mime()),
+                Err(e) => Value::error(format!("sparse.export: {e}")),
+            }
+        }
+
         // ── Rootfinding ──────────────────────────────────────────────
 
         // Newton-Raphson: find root of f(x)=0 given formula and initial guess
@@ -4663,6 +5165,7 @@ fn evaluate_node_inner(
                     dt: data.get("dt").and_then(|v| v.as_f64()).unwrap_or(0.01),
                     tolerance: tol,
                     max_steps: 100_000,
+                    ..Default::default()
                 },
                 max_iter,
                 tol,
@@ -5431,21 +5934,71 @@ fn evaluate_node_inner(
         // ── FMU Export (FMI 2.0-compliant, 11.11) ────────────────────
         //
         // Uses fmu_export module which generates XML that passes FMI Cross-Check
-        // schema validation: correct value references, all required elements,
-        // proper CoSimulation capabilities, plus a compilable C99 stub.
+        // structural validation (checked before returning), C99 source with
+        // the model compiled in, and the .fmu archive layout.
 
         "fmu.export" => {
             use crate::fmu_export;
-            let cfg = fmu_export::fmu_config_from_block(data, inputs);
-            let pkg = fmu_export::generate_fmu_package(&cfg);
-            // Return JSON with both modelDescription.xml and C stub so the UI
-            // can offer both downloads from the block inspector.
-            let combined = format!(
-                "{{\"modelDescriptionXml\":{},\"cStub\":{}}}",
-                serde_json::to_string(&pkg.model_description_xml).unwrap_or_default(),
-                serde_json::to_string(&pkg.model_c_stub).unwrap_or_default(),
-            );
-            Value::Text { value: combined }
+            let result = (|| -> Result<_, String> {
+                let mut cfg = fmu_export::fmu_config_from_block(data, inputs);
+                // The upstream subgraph (attached by the evaluators, see
+                // `fmu_export::with_upstream_graph`) is compiled into the FMU.
+                if let Some(graph) = data.get("graph").filter(|g| !g.is_null()) {
+                    let graph: crate::types::EngineSnapshotV1 =
+                        serde_json::from_value(graph.clone()).map_err(|e| format!("invalid graph: {e}"))?;
+                    let output = data.get("graphOutput").and_then(|v| v.as_str());
+                    fmu_export::fmu_config_from_graph(&graph, output, &mut cfg)?;
+                }
+                let pkg = fmu_export::generate_fmu_package(&cfg)?;
+                let problems = fmu_export::check_fmu_package(&pkg);
+                if !problems.is_empty() {
+                    return Err(problems.join("; "));
+                }
+                Ok(pkg)
+            })();
+            // The .fmu archive (modelDescription.xml, C source, binaries/).
+            match result {
+                Ok(pkg) => Value::bytes(pkg.to_zip(), "application/zip"),
+                Err(e) => Value::error(format!("fmu.export: {e}")),
+            }
+        }
+
+        // Fixed-step co-simulation of declarative FMUs (fmu_cosim). The
+        // `fmus` Text input (or data `fmus`/`connections`) is the JSON spec;
+        // `inputs` drives the unconnected FMU inputs.
+        "fmu.cosimulate" => {
+            use crate::fmu_cosim::{bind_inputs, master_from_json, MasterAlgorithm, MasterConfig};
+            let result = (|| -> Result<_, String> {
+                let spec = match inputs.get("fmus") {
+                    Some(Value::Text { value }) => {
+                        serde_json::from_str(value).map_err(|e| format!("invalid FMU JSON: {e}"))?
+                    }
+                    Some(Value::Error { message }) => return Err(message.clone()),
+                    _ => serde_json::json!({
+                        "fmus": data.get("fmus").cloned().unwrap_or_else(|| serde_json::json!([])),
+                        "connections": data.get("connections").cloned().unwrap_or_else(|| serde_json::json!([])),
+                    }),
+                };
+                let mut master = master_from_json(&spec)?;
+                if let Some(value) = inputs.get("inputs") {
+                    bind_inputs(&mut master, value)?;
+                }
+                let algorithm = match data.get("algorithm").and_then(|v| v.as_str()) {
+                    Some("jacobi") => MasterAlgorithm::Jacobi,
+                    _ => MasterAlgorithm::GaussSeidel,
+                };
+                let cfg = MasterConfig {
+                    start_time: scalar_or(data, "startTime", 0.0),
+                    stop_time: scalar_or(data, "stopTime", 10.0),
+                    step_size: scalar_or(data, "stepSize", 0.01),
+                    algorithm,
+                };
+                master.run(&cfg)
+            })();
+            match result {
+                Ok(r) => Value::Table { columns: r.columns, rows: r.rows },
+                Err(e) => Value::error(format!("fmu.cosimulate: {e}")),
+            }
         }
 
         // ── Symbolic Math (CAS) ────────────────────────────────────────
@@ -5551,6 +6104,287 @@ fn evaluate_node_inner(
             }
         }
 
+        // Solve, series and limit return Text JSON: `latex` for display plus
+        // expressions in parse_expr syntax (and their numeric values when
+        // they have no free symbols) for downstream evaluation.
+        "sym.solve" => {
+            use crate::symbolic;
+            let text = match inputs.get("expr") {
+                Some(Value::Text { value }) => value.clone(),
+                _ => return Value::error("sym.solve: 'expr' input required (Text)"),
+            };
+            let var_str = match inputs.get("var") {
+                Some(Value::Text { value }) => value.clone(),
+                _ => data.get("var").and_then(|v| v.as_str()).unwrap_or("x").to_string(),
+            };
+            let vars: Vec<String> = var_str.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect();
+            let equations: Result<Vec<_>, String> =
+                text.split(';').map(str::trim).filter(|e| !e.is_empty()).map(symbolic::parse_equation).collect();
+            let equations = match equations {
+                Ok(eqs) if !eqs.is_empty() => eqs,
+                Ok(_) => return Value::error("sym.solve: no equations given"),
+                Err(err) => return Value::error(format!("sym.solve parse error: {err}")),
+            };
+            // One equation in one unknown gives every root; several equations
+            // are solved as a linear system for one solution.
+            let solutions = match (equations.as_slice(), vars.as_slice()) {
+                ([eq], [v]) => symbolic::solve(eq, v).map(|roots| roots.into_iter().map(|r| vec![r]).collect::<Vec<_>>()),
+                _ => symbolic::solve_linear_system(&equations, &vars).map(|sol| vec![sol]),
+            };
+            match solutions {
+                Ok(solutions) => {
+                    let latex: Vec<String> = solutions
+                        .iter()
+                        .flat_map(|sol| vars.iter().zip(sol).map(|(v, e)| format!("{v} = {}", symbolic::to_latex(e))))
+                        .collect();
+                    let sets: Vec<_> = solutions
+                        .iter()
+                        .map(|sol| vars.iter().zip(sol).map(|(v, e)| (v.clone(), serde_json::json!(e.to_string()))).collect::<serde_json::Map<_, _>>())
+                        .collect();
+                    let values: Vec<_> = solutions
+                        .iter()
+                        .map(|sol| vars.iter().zip(sol).map(|(v, e)| (v.clone(), serde_json::json!(symbolic::eval(e, &HashMap::new())))).collect::<serde_json::Map<_, _>>())
+                        .collect();
+                    let latex = if latex.is_empty() { "\\text{no real solutions}".to_string() } else { latex.join(",\\; ") };
+                    Value::Text { value: serde_json::json!({ "latex": latex, "solutions": sets, "values": values }).to_string() }
+                }
+                Err(err) => Value::error(format!("sym.solve: {err}")),
+            }
+        }
+
+        "sym.series" => {
+            use crate::symbolic;
+            let expr_str = match inputs.get("expr") {
+                Some(Value::Text { value }) => value.clone(),
+                _ => return Value::error("sym.series: 'expr' input required (Text)"),
+            };
+            let var_str = match inputs.get("var") {
+                Some(Value::Text { value }) => value.clone(),
+                _ => data.get("var").and_then(|v| v.as_str()).unwrap_or("x").to_string(),
+            };
+            let x0 = inputs.get("x0").and_then(|v| v.as_scalar()).unwrap_or_else(|| scalar_or(data, "x0", 0.0));
+            let order = scalar_or(data, "order", 5.0).clamp(0.0, 20.0) as i32;
+            let e = match symbolic::parse_expr(&expr_str) {
+                Ok(e) => e,
+                Err(err) => return Value::error(format!("sym.series parse error: {err}")),
+            };
+            match symbolic::series_terms(&e, &var_str, x0, order) {
+                Ok(terms) => {
+                    let s = symbolic::series_expr(&terms, &var_str, x0);
+                    let offset = if x0 == 0.0 { var_str.clone() } else { format!("\\left({}\\right)", symbolic::to_latex(&symbolic::sub(symbolic::var(&var_str), symbolic::con(x0)))) };
+                    let terms: Vec<_> = terms
+                        .iter()
+                        .map(|(k, c)| serde_json::json!({ "power": k, "coefficient": c.to_string(), "value": symbolic::eval(c, &HashMap::new()) }))
+                        .collect();
+                    Value::Text {
+                        value: serde_json::json!({
+                            "latex": format!("{} + O\\left({offset}^{{{}}}\\right)", symbolic::to_latex(&s), order + 1),
+                            "expr": s.to_string(),
+                            "terms": terms,
+                        })
+                        .to_string(),
+                    }
+                }
+                Err(err) => Value::error(format!("sym.series: {err}")),
+            }
+        }
+
+        "sym.limit" => {
+            use crate::symbolic;
+            let expr_str = match inputs.get("expr") {
+                Some(Value::Text { value }) => value.clone(),
+                _ => return Value::error("sym.limit: 'expr' input required (Text)"),
+            };
+            let var_str = match inputs.get("var") {
+                Some(Value::Text { value }) => value.clone(),
+                _ => data.get("var").and_then(|v| v.as_str()).unwrap_or("x").to_string(),
+            };
+            // x0 may be a number or "inf"/"-inf" in data.
+            let x0 = match inputs.get("x0").and_then(|v| v.as_scalar()) {
+                Some(v) => v,
+                None => match data.get("x0") {
+                    Some(serde_json::Value::String(s)) => match s.trim() {
+                        "inf" | "+inf" | "∞" | "+∞" => f64::INFINITY,
+                        "-inf" | "-∞" => f64::NEG_INFINITY,
+                        other => match other.parse::<f64>() {
+                            Ok(v) => v,
+                            Err(_) => return Value::error(format!("sym.limit: invalid x0 '{other}'")),
+                        },
+                    },
+                    _ => scalar_or(data, "x0", 0.0),
+                },
+            };
+            let e = match symbolic::parse_expr(&expr_str) {
+                Ok(e) => e,
+                Err(err) => return Value::error(format!("sym.limit parse error: {err}")),
+            };
+            match symbolic::limit(&e, &var_str, x0) {
+                Ok(l) => Value::Text {
+                    value: serde_json::json!({
+                        "latex": format!("\\lim_{{{var_str} \\to {}}} {} = {}", symbolic::to_latex(&symbolic::con(x0)), symbolic::to_latex(&e), symbolic::to_latex(&l)),
+                        "expr": l.to_string(),
+                        "value": symbolic::eval(&l, &HashMap::new()),
+                    })
+                    .to_string(),
+                },
+                Err(err) => Value::error(format!("sym.limit: {err}")),
+            }
+        }
+
+        // Matrix, Jacobian and Lagrange blocks also return Text JSON; entries
+        // are in parse_expr syntax. sym.codegen returns the source itself.
+        "sym.jacobian" => {
+            use crate::{symbolic, symbolic_matrix};
+            let text = match inputs.get("expr") {
+                Some(Value::Text { value }) => value.clone(),
+                _ => return Value::error("sym.jacobian: 'expr' input required (Text)"),
+            };
+            let exprs: Result<Vec<_>, String> =
+                text.split(';').map(str::trim).filter(|e| !e.is_empty()).map(symbolic::parse_expr).collect();
+            let exprs = match exprs {
+                Ok(es) if !es.is_empty() => es,
+                Ok(_) => return Value::error("sym.jacobian: no expressions given"),
+                Err(err) => return Value::error(format!("sym.jacobian parse error: {err}")),
+            };
+            let vars = symbolic_var_list(inputs, data, &exprs);
+            let mode = data.get("mode").and_then(|v| v.as_str()).unwrap_or("jacobian");
+            let m = match (mode, exprs.as_slice()) {
+                ("jacobian", _) => symbolic_matrix::jacobian(&exprs, &vars),
+                ("gradient", [e]) => symbolic_matrix::gradient(e, &vars),
+                ("hessian", [e]) => symbolic_matrix::hessian(e, &vars),
+                ("gradient" | "hessian", _) => return Value::error(format!("sym.jacobian: {mode} needs a single expression")),
+                (other, _) => return Value::error(format!("sym.jacobian: unknown mode '{other}'")),
+            };
+            Value::Text {
+                value: serde_json::json!({ "latex": m.to_latex(), "entries": m.to_strings(), "vars": vars }).to_string(),
+            }
+        }
+
+        "sym.matrix" => {
+            use crate::{symbolic, symbolic_matrix::SymMatrix};
+            let matrix = |port: &str| match inputs.get(port) {
+                Some(Value::Text { value }) => SymMatrix::parse(value).map(Some),
+                _ => Ok(None),
+            };
+            let (a, b) = match (matrix("a"), matrix("b")) {
+                (Ok(Some(a)), Ok(b)) => (a, b),
+                (Ok(None), _) => return Value::error("sym.matrix: 'a' input required (Text, e.g. [a, b; c, d])"),
+                (Err(err), _) | (_, Err(err)) => return Value::error(format!("sym.matrix parse error: {err}")),
+            };
+            let op = data.get("op").and_then(|v| v.as_str()).unwrap_or("det");
+            if op == "det" {
+                return match a.determinant() {
+                    Ok(d) => Value::Text {
+                        value: serde_json::json!({
+                            "latex": format!("\\det {} = {}", a.to_latex(), symbolic::to_latex(&d)),
+                            "expr": d.to_string(),
+                            "value": symbolic::eval(&d, &HashMap::new()),
+                        })
+                        .to_string(),
+                    },
+                    Err(err) => Value::error(format!("sym.matrix: {err}")),
+                };
+            }
+            let result = match (op, b) {
+                ("inverse", _) => a.inverse(),
+                ("transpose", _) => Ok(a.transpose()),
+                ("multiply" | "add" | "subtract", None) => return Value::error(format!("sym.matrix: {op} needs a 'b' input")),
+                ("multiply", Some(b)) => a.mul(&b),
+                ("add", Some(b)) => a.add(&b),
+                ("subtract", Some(b)) => a.sub(&b),
+                (other, _) => return Value::error(format!("sym.matrix: unknown op '{other}'")),
+            };
+            match result {
+                Ok(m) => Value::Text { value: serde_json::json!({ "latex": m.to_latex(), "entries": m.to_strings() }).to_string() },
+                Err(err) => Value::error(format!("sym.matrix: {err}")),
+            }
+        }
+
+        "sym.codegen" => {
+            use crate::{symbolic, symbolic_codegen};
+            let text = match inputs.get("expr") {
+                Some(Value::Text { value }) => value.clone(),
+                _ => return Value::error("sym.codegen: 'expr' input required (Text)"),
+            };
+            // `name = expr` pairs separated by ';'; unnamed ones become out<k>.
+            let outputs: Result<Vec<_>, String> = text
+                .split(';')
+                .map(str::trim)
+                .filter(|e| !e.is_empty())
+                .enumerate()
+                .map(|(k, item)| match item.split_once('=') {
+                    Some((name, e)) => Ok((name.trim().to_string(), symbolic::parse_expr(e)?)),
+                    None => Ok((format!("out{k}"), symbolic::parse_expr(item)?)),
+                })
+                .collect();
+            let outputs = match outputs {
+                Ok(o) if !o.is_empty() => o,
+                Ok(_) => return Value::error("sym.codegen: no expressions given"),
+                Err(err) => return Value::error(format!("sym.codegen parse error: {err}")),
+            };
+            let exprs: Vec<_> = outputs.iter().map(|(_, e)| e.clone()).collect();
+            let vars = symbolic_var_list(inputs, data, &exprs);
+            let target = match symbolic_codegen::CodegenTarget::parse(data.get("target").and_then(|v| v.as_str()).unwrap_or("rust")) {
+                Ok(t) => t,
+                Err(err) => return Value::error(format!("sym.codegen: {err}")),
+            };
+            let name = data.get("functionName").and_then(|v| v.as_str()).unwrap_or("generated");
+            match symbolic_codegen::generate(name, &vars, &outputs, target) {
+                Ok(code) => Value::Text { value: code },
+                Err(err) => Value::error(format!("sym.codegen: {err}")),
+            }
+        }
+
+        "sym.lagrange" => {
+            use crate::{symbolic, symbolic_codegen, symbolic_matrix};
+            let parse = |port: &str| match inputs.get(port) {
+                Some(Value::Text { value }) => symbolic::parse_expr(value),
+                _ => Ok(symbolic::zero()),
+            };
+            let (t, v) = match (parse("T"), parse("V")) {
+                (Ok(t), Ok(v)) => (t, v),
+                (Err(err), _) | (_, Err(err)) => return Value::error(format!("sym.lagrange parse error: {err}")),
+            };
+            let coords_str = match inputs.get("coords") {
+                Some(Value::Text { value }) => value.clone(),
+                _ => data.get("coords").and_then(|v| v.as_str()).unwrap_or("q").to_string(),
+            };
+            let coords: Vec<String> = coords_str.split(',').map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect();
+            let time = data.get("time").and_then(|v| v.as_str()).unwrap_or("t");
+            let eom = match symbolic_matrix::lagrange_equations(&symbolic::sub(t, v), &coords, time) {
+                Ok(eom) => eom,
+                Err(err) => return Value::error(format!("sym.lagrange: {err}")),
+            };
+            let ode = match eom.first_order_system(time) {
+                Ok(ode) => ode,
+                Err(err) => return Value::error(format!("sym.lagrange: {err}")),
+            };
+            let latex: Vec<String> = coords
+                .iter()
+                .zip(&eom.accelerations)
+                .map(|(q, a)| format!("\\ddot{{{q}}} = {}", symbolic::to_latex(a)))
+                .collect();
+            let accelerations: serde_json::Map<_, _> = coords
+                .iter()
+                .zip(&eom.accelerations)
+                .map(|(q, a)| (symbolic_matrix::acceleration_name(q), serde_json::json!(a.to_string())))
+                .collect();
+            let state: Vec<String> = coords.iter().cloned().chain(coords.iter().map(|q| symbolic_matrix::velocity_name(q))).collect();
+            // Plain formulas (no `let`) so the string feeds ode.* blocks directly.
+            let ode_equations: Vec<String> = ode.iter().map(symbolic_codegen::to_expr_string).collect();
+            Value::Text {
+                value: serde_json::json!({
+                    "latex": latex.join(" \\\\ "),
+                    "residuals": eom.residuals.iter().map(|r| r.to_string()).collect::<Vec<_>>(),
+                    "massMatrix": eom.mass_matrix.to_strings(),
+                    "accelerations": accelerations,
+                    "state": state,
+                    "odeEquations": ode_equations.join("; "),
+                })
+                .to_string(),
+            }
+        }
+
         // ── Compiled Expression Eval (1.29) ──────────────────────────
         "sym.compiledEval" => {
             // Pre-parse the expression once, then evaluate with port inputs as vars.
@@ -5589,7 +6423,11 @@ fn evaluate_node_inner(
 
         // ── Gröbner Bases (1.26) ─────────────────────────────────────
         "sym.groebner" => {
-            use crate::symbolic::{MultiPoly, Monomial, MonomialOrder, groebner_basis, solve_polynomial_system};
+            use crate::rational::Rational;
+            use crate::symbolic::{
+                groebner_basis, parse_equation, solve_polynomial_system, solve_polynomial_system_exact, Coefficient, Monomial,
+                MonomialOrder, MultiPoly,
+            };
 
             // Helper: parse a single polynomial term like "3*x^2*y" or "-x" or "2.5"
             fn parse_poly_term(term: &str, var_names: &[String]) -> Result<(f64, Monomial), String> {
@@ -5717,51 +6555,73 @@ fn evaluate_node_inner(
             let max_iter = data.get("max_iter").and_then(|v| v.as_f64()).unwrap_or(10000.0) as usize;
             let mode = data.get("mode").and_then(|v| v.as_str()).unwrap_or("basis");
 
-            let poly_strs: Vec<&str> = polynomials_text.split(';').collect();
-            let mut generators: Vec<MultiPoly> = Vec::new();
-            for ps in &poly_strs {
-                let ps = ps.trim();
-                if ps.is_empty() { continue; }
-                match parse_poly_expr(ps, &var_names, order) {
-                    Ok(p) => generators.push(p),
-                    Err(e) => return Value::error(format!("sym.groebner parse error in '{}': {}", ps, e)),
+            type Solver<C> = fn(&[MultiPoly<C>], usize) -> Result<Vec<Vec<f64>>, String>;
+
+            // Basis or solutions as text, for either coefficient domain.
+            fn report<C: Coefficient>(
+                generators: &[MultiPoly<C>],
+                var_names: &[String],
+                mode: &str,
+                max_iter: usize,
+                solve: Solver<C>,
+            ) -> Value {
+                if mode == "solve" {
+                    match solve(generators, max_iter) {
+                        Ok(solutions) => {
+                            if solutions.is_empty() {
+                                Value::Text { value: "No solutions found".to_string() }
+                            } else {
+                                let lines: Vec<String> = solutions.iter().enumerate().map(|(i, sol)| {
+                                    let parts: Vec<String> = var_names.iter().zip(sol.iter())
+                                        .map(|(v, &x)| format!("{v} = {x:.6}"))
+                                        .collect();
+                                    format!("Solution {}: {}", i + 1, parts.join(", "))
+                                }).collect();
+                                Value::Text { value: lines.join("\n") }
+                            }
+                        }
+                        Err(e) => Value::Text { value: format!("Error: {e}") },
+                    }
+                } else {
+                    // Return basis as text
+                    let result = groebner_basis(generators, max_iter);
+                    let basis_strs: Vec<String> = result.basis.iter().map(|p| format!("{p}")).collect();
+                    let text = format!(
+                        "Gröbner basis ({} polynomials, {} S-polys, {} zero reductions):\n{}",
+                        basis_strs.len(),
+                        result.s_poly_count,
+                        result.zero_reductions,
+                        basis_strs.join("\n")
+                    );
+                    Value::Text { value: text }
                 }
             }
 
-            if generators.is_empty() {
+            let poly_strs: Vec<&str> = polynomials_text.split(';').map(str::trim).filter(|p| !p.is_empty()).collect();
+            if poly_strs.is_empty() {
                 return Value::error("sym.groebner: no valid polynomials parsed");
             }
 
-            if mode == "solve" {
-                match solve_polynomial_system(&generators, max_iter) {
-                    Ok(solutions) => {
-                        if solutions.is_empty() {
-                            Value::Text { value: "No solutions found".to_string() }
-                        } else {
-                            let lines: Vec<String> = solutions.iter().enumerate().map(|(i, sol)| {
-                                let parts: Vec<String> = var_names.iter().zip(sol.iter())
-                                    .map(|(v, &x)| format!("{v} = {x:.6}"))
-                                    .collect();
-                                format!("Solution {}: {}", i + 1, parts.join(", "))
-                            }).collect();
-                            Value::Text { value: lines.join("\n") }
-                        }
+            // Exact rational arithmetic by default; "float" keeps the f64 path.
+            if data.get("arithmetic").and_then(|v| v.as_str()) != Some("float") {
+                let mut generators: Vec<MultiPoly<Rational>> = Vec::new();
+                for ps in &poly_strs {
+                    match parse_equation(ps).and_then(|e| MultiPoly::from_expr(&e, &var_names, order)) {
+                        Ok(p) => generators.push(p),
+                        Err(e) => return Value::error(format!("sym.groebner parse error in '{}': {}", ps, e)),
                     }
-                    Err(e) => Value::Text { value: format!("Error: {e}") },
                 }
-            } else {
-                // Return basis as text
-                let result = groebner_basis(&generators, max_iter);
-                let basis_strs: Vec<String> = result.basis.iter().map(|p| format!("{p}")).collect();
-                let text = format!(
-                    "Gröbner basis ({} polynomials, {} S-polys, {} zero reductions):\n{}",
-                    basis_strs.len(),
-                    result.s_poly_count,
-                    result.zero_reductions,
-                    basis_strs.join("\n")
-                );
-                Value::Text { value: text }
+                return report(&generators, &var_names, mode, max_iter, solve_polynomial_system_exact);
+            }
+
+            let mut generators: Vec<MultiPoly> = Vec::new();
+            for ps in &poly_strs {
+                match parse_poly_expr(ps, &var_names, order) {
+                    Ok(p) => generators.push(p),
+                    Err(e) => return Value::error(format!("sym.groebner parse error in '{}': {}", ps, e)),
+                }
             }
+            report(&generators, &var_names, mode, max_iter, solve_polynomial_system)
         }
 
         // ── ODE Solvers (Phase 4) ──────────────────────────────────────
@@ -5808,9 +6668,14 @@ fn evaluate_node_inner(
             }
 
             let system = crate::ode::OdeSystem { equations, state_names: state_names.clone(), params };
+            let output = match ode_output(inputs, data, t_start, t_end) {
+                Ok(o) => o,
+                Err(e) => return Value::error(format!("{block_type}: {e}")),
+            };
             let config = crate::ode::OdeSolverConfig {
                 t_start, t_end, dt, tolerance,
                 max_steps: 100_000,
+                output,
             };
 
             let result = if block_type == "ode.rk45" {
@@ -5857,9 +6722,48 @@ fn evaluate_node_inner(
             let t_start = scalar_or(data, "t_start", 0.0);
             let t_end = scalar_or(data, "t_end", 1.0);
             let dt = scalar_or(data, "dt", 0.01);
-            let event_expr = data.get("event_expr").and_then(|v| v.as_str()).unwrap_or("y0").to_string();
-            let terminate = data.get("terminate").and_then(|v| v.as_bool()).unwrap_or(true);
+            let tolerance = scalar_or(data, "tolerance", 1e-6);
             let event_tol = scalar_or(data, "event_tol", 1e-8);
+            let method = crate::ode::event::parse_ode_method(
+                data.get("method").and_then(|v| v.as_str()).unwrap_or("rk4"),
+            );
+
+            // Events: either a list of {expr, direction, terminate, reset,
+            // switch_to, mode} objects, or the single event_expr/direction/reset/terminate.
+            let parse_event = |obj: &serde_json::Map<String, serde_json::Value>, expr_key: &str| {
+                let str_of = |k: &str| obj.get(k).and_then(|v| v.as_str()).unwrap_or("");
+                let index_of = |k: &str| obj.get(k).and_then(|v| v.as_u64()).map(|m| m as usize);
+                let resets = crate::ode::event::parse_resets(str_of("reset"))?;
+                let switch_to = index_of("switch_to");
+                let terminal = obj
+                    .get("terminate")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(resets.is_empty() && switch_to.is_none());
+                Ok::<_, String>(crate::ode::event::HybridEvent {
+                    condition: obj.get(expr_key).and_then(|v| v.as_str()).unwrap_or("y0").to_string(),
+                    direction: crate::ode::event::parse_direction(str_of("direction")),
+                    terminal,
+                    resets,
+                    switch_to,
+                    mode: index_of("mode"),
+                })
+            };
+            let events = match data.get("events") {
+                Some(serde_json::Value::Array(list)) => list
+                    .iter()
+                    .filter_map(|v| v.as_object())
+                    .map(|obj| parse_event(obj, "expr"))
+                    .collect::<Result<Vec<_>, _>>(),
+                _ => {
+                    let top: serde_json::Map<String, serde_json::Value> =
+                        data.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+                    parse_event(&top, "event_expr").map(|e| vec![e])
+                }
+            };
+            let events = match events {
+                Ok(events) => events,
+                Err(e) => return Value::error(format!("ode.event: {e}")),
+            };
 
             let state_names: Vec<String> = (0..equations.len()).map(|i| format!("y{i}")).collect();
 
@@ -5872,14 +6776,28 @@ fn evaluate_node_inner(
                 }
             }
 
-            let system = crate::ode::OdeSystem { equations, state_names, params };
+            // Extra equation modes (semicolon-separated strings) that events switch to.
+            let mut modes = vec![crate::ode::OdeSystem { equations, state_names: state_names.clone(), params: params.clone() }];
+            if let Some(serde_json::Value::Array(list)) = data.get("modes") {
+                for text in list.iter().filter_map(|v| v.as_str()) {
+                    let equations = text.split(';').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+                    modes.push(crate::ode::OdeSystem { equations, state_names: state_names.clone(), params: params.clone() });
+                }
+            }
+            let system = crate::ode::event::HybridSystem { modes, events };
+            let output = match ode_output(inputs, data, t_start, t_end) {
+                Ok(o) => o,
+                Err(e) => return Value::error(format!("ode.event: {e}")),
+            };
             let config = crate::ode::OdeSolverConfig {
-                t_start, t_end, dt, tolerance: 1e-6, max_steps: 100_000,
+                t_start, t_end, dt, tolerance, max_steps: 100_000,
+                output,
             };
 
-            let result = crate::ode::event::solve_event_rk4(
-                &system, &y0, &config, &event_expr, terminate, event_tol,
-            );
+            let result = match crate::ode::event::solve_hybrid(&system, &y0, &config, method, event_tol) {
+                Ok(r) => r.ode,
+                Err(e) => return Value::error(format!("ode.event: {e}")),
+            };
 
             let columns = result.column_names.clone();
             let rows: Vec<Vec<f64>> = result.t.iter().zip(result.states.iter()).map(|(t, ys)| {
@@ -5950,16 +6868,30 @@ fn evaluate_node_inner(
         }
 
         "ode.symplectic" => {
-            // Parse acceleration expressions from Text input (semicolon-separated)
-            let accel_text = match inputs.get("accelerations") {
-                Some(Value::Text { value }) => value.clone(),
-                _ => return Value::error("ode.symplectic: 'accelerations' input required (Text, semicolon-separated)"),
+            // The system is a Hamiltonian from data (`hamiltonian`, or separable
+            // `kinetic` + `potential`) or N acceleration expressions (Text input).
+            let text_of = |key: &str| data.get(key).and_then(|v| v.as_str()).map(str::trim).filter(|s| !s.is_empty());
+            let system = if let Some(h) = text_of("hamiltonian") {
+                crate::ode::symplectic::SymplecticSystem::Hamiltonian(crate::ode::symplectic::Hamiltonian::General(h.to_string()))
+            } else if let (Some(kinetic), Some(potential)) = (text_of("kinetic"), text_of("potential")) {
+                crate::ode::symplectic::SymplecticSystem::Hamiltonian(crate::ode::symplectic::Hamiltonian::Separable {
+                    kinetic: kinetic.to_string(),
+                    potential: potential.to_string(),
+                })
+            } else {
+                let accel_text = match inputs.get("accelerations") {
+                    Some(Value::Text { value }) => value.clone(),
+                    _ => return Value::error("ode.symplectic: 'accelerations' input (Text, semicolon-separated) or a hamiltonian required"),
+                };
+                let accel_exprs: Vec<String> = accel_text.split(';').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+                if accel_exprs.is_empty() {
+                    return Value::error("ode.symplectic: no acceleration expressions provided");
+                }
+                crate::ode::symplectic::SymplecticSystem::SecondOrder {
+                    accelerations: accel_exprs,
+                    potential: text_of("potential").map(str::to_string),
+                }
             };
-            let accel_exprs: Vec<String> = accel_text.split(';').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
-            let n = accel_exprs.len();
-            if n == 0 {
-                return Value::error("ode.symplectic: no acceleration expressions provided");
-            }
 
             // Parse initial state [q0..qN-1, v0..vN-1] from Vector input
             let y0 = match inputs.get("y0") {
@@ -5967,23 +6899,24 @@ fn evaluate_node_inner(
                 Some(Value::Scalar { value }) => vec![*value],
                 _ => return Value::error("ode.symplectic: 'y0' input required ([q0..qN, v0..vN])"),
             };
-            if y0.len() != 2 * n {
-                return Value::error(format!(
-                    "ode.symplectic: {} acceleration expressions require {} initial values (got {})",
-                    n, 2 * n, y0.len()
-                ));
-            }
 
             let t_start = scalar_or(data, "t_start", 0.0);
             let t_end = scalar_or(data, "t_end", 1.0);
             let dt = scalar_or(data, "dt", 0.01);
 
-            // Determine method from data
-            let method_str = data.get("method").and_then(|v| v.as_str()).unwrap_or("verlet");
-            let method = if method_str == "euler" {
-                crate::ode::symplectic::SympMethod::SympEuler
-            } else {
-                crate::ode::symplectic::SympMethod::VelocityVerlet
+            // Angular momentum is monitored only when angular_dim is set to the
+            // body dimension (2 = planar, 3 = spatial); absent or 0 is off.
+            let angular_dim = match data.get("angular_dim").and_then(|v| v.as_u64()) {
+                None | Some(0) => None,
+                Some(d) => Some(d as usize),
+            };
+            let options = crate::ode::symplectic::SymplecticOptions {
+                method: crate::ode::symplectic::parse_symp_method(
+                    data.get("method").and_then(|v| v.as_str()).unwrap_or("verlet"),
+                ),
+                adaptive: data.get("adaptive").and_then(|v| v.as_bool()).unwrap_or(false),
+                step_function: text_of("step_function").map(str::to_string),
+                angular_dim,
             };
 
             // Collect parameters
@@ -6000,11 +6933,15 @@ fn evaluate_node_inner(
                 t_start, t_end, dt,
                 tolerance: 1e-6,
                 max_steps: 100_000,
+                ..Default::default()
             };
 
-            let result = crate::ode::symplectic::solve_symplectic(
-                &accel_exprs, &y0, &config, &params, &[], method,
-            );
+            let result = match crate::ode::symplectic::solve_symplectic_system(
+                &system, &y0, &config, &params, &[], &options,
+            ) {
+                Ok(r) => r,
+                Err(e) => return Value::error(format!("ode.symplectic: {e}")),
+            };
 
             let columns = result.column_names.clone();
             let rows: Vec<Vec<f64>> = result.t.iter().zip(result.states.iter()).map(|(t, ys)| {
@@ -6053,7 +6990,14 @@ fn evaluate_node_inner(
             }
 
             let system = crate::ode::OdeSystem { equations, state_names, params };
-            let cfg = crate::ode::OdeSolverConfig { t_start, t_end, dt, tolerance, max_steps: 100_000 };
+            let output = match ode_output(inputs, data, t_start, t_end) {
+                Ok(o) => o,
+                Err(e) => return Value::error(format!("{block_type}: {e}")),
+            };
+            let cfg = crate::ode::OdeSolverConfig {
+                t_start, t_end, dt, tolerance, max_steps: 100_000,
+                output,
+            };
 
             let result = if block_type == "ode.bdf" {
                 crate::ode::bdf::solve_bdf(&system, &y0, &cfg)
@@ -6070,6 +7014,264 @@ fn evaluate_node_inner(
             Value::Table { columns, rows }
         }
 
+        "ode.dde" => {
+            let equations_text = match inputs.get("equations") {
+                Some(Value::Text { value }) => value.clone(),
+                _ => return Value::error("ode.dde: 'equations' input required (Text, semicolon-separated)"),
+            };
+            let equations: Vec<String> = equations_text.split(';').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+            if equations.is_empty() {
+                return Value::error("ode.dde: no equations provided");
+            }
+            let y0 = match inputs.get("y0") {
+                Some(Value::Vector { value }) => value.clone(),
+                Some(Value::Scalar { value }) => vec![*value],
+                _ => return Value::error("ode.dde: 'y0' input required"),
+            };
+
+            let t_start = scalar_or(data, "t_start", 0.0);
+            let t_end = scalar_or(data, "t_end", 1.0);
+            let dt = scalar_or(data, "dt", 0.01);
+            let tolerance = scalar_or(data, "tolerance", 1e-6);
+            let state_names: Vec<String> = (0..equations.len()).map(|i| format!("y{i}")).collect();
+            // History y_i(t) for t < t_start (semicolon-separated, in t); empty = constant y0.
+            let history: Vec<String> = data
+                .get("history")
+                .and_then(|v| v.as_str())
+                .map(|s| s.split(';').map(|x| x.trim().to_string()).filter(|x| !x.is_empty()).collect())
+                .unwrap_or_default();
+
+            let mut params = std::collections::HashMap::new();
+            if let Some(serde_json::Value::Object(obj)) = data.get("params") {
+                for (k, v) in obj {
+                    if let Some(n) = v.as_f64() { params.insert(k.clone(), n); }
+                }
+            }
+
+            let system = crate::ode::dde::DdeSystem { equations, state_names, params, history };
+            let output = match ode_output(inputs, data, t_start, t_end) {
+                Ok(o) => o,
+                Err(e) => return Value::error(format!("ode.dde: {e}")),
+            };
+            let cfg = crate::ode::OdeSolverConfig {
+                t_start, t_end, dt, tolerance, max_steps: 100_000,
+                output,
+            };
+
+            let result = match crate::ode::dde::solve_dde(&system, &y0, &cfg) {
+                Ok(r) => r,
+                Err(e) => return Value::error(format!("ode.dde: {e}")),
+            };
+
+            let columns = result.column_names.clone();
+            let rows: Vec<Vec<f64>> = result.t.iter().zip(result.states.iter()).map(|(t, ys)| {
+                let mut row = vec![*t];
+                row.extend_from_slice(ys);
+                row
+            }).collect();
+            Value::Table { columns, rows }
+        }
+
+        "ode.sde" | "ode.sdeEnsemble" => {
+            let split = |key: &str| -> Vec<String> {
+                match inputs.get(key) {
+                    Some(Value::Text { value }) => value.split(';').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect(),
+                    _ => Vec::new(),
+                }
+            };
+            let drift = split("drift");
+            if drift.is_empty() {
+                return Value::error(format!("{block_type}: 'drift' input required (Text, semicolon-separated)"));
+            }
+            let diffusion = split("diffusion");
+            if diffusion.is_empty() {
+                return Value::error(format!("{block_type}: 'diffusion' input required (Text, semicolon-separated)"));
+            }
+            let y0 = match inputs.get("y0") {
+                Some(Value::Vector { value }) => value.clone(),
+                Some(Value::Scalar { value }) => vec![*value],
+                _ => return Value::error(format!("{block_type}: 'y0' input required")),
+            };
+
+            let t_start = scalar_or(data, "t_start", 0.0);
+            let t_end = scalar_or(data, "t_end", 1.0);
+            let dt = scalar_or(data, "dt", 0.01);
+            let scheme = crate::ode::sde::parse_sde_scheme(data.get("scheme").and_then(|v| v.as_str()).unwrap_or("euler"));
+            let seed = data.get("seed").and_then(|v| v.as_u64()).unwrap_or(42);
+            let state_names: Vec<String> = (0..drift.len()).map(|i| format!("y{i}")).collect();
+
+            let mut params = std::collections::HashMap::new();
+            if let Some(serde_json::Value::Object(obj)) = data.get("params") {
+                for (k, v) in obj {
+                    if let Some(n) = v.as_f64() { params.insert(k.clone(), n); }
+                }
+            }
+
+            let system = crate::ode::sde::SdeSystem { drift, diffusion, state_names, params };
+            let cfg = crate::ode::OdeSolverConfig { t_start, t_end, dt, max_steps: 1_000_000, ..Default::default() };
+
+            if block_type == "ode.sde" {
+                let result = match crate::ode::sde::solve_sde(&system, &y0, &cfg, scheme, seed) {
+                    Ok(r) => r,
+                    Err(e) => return Value::error(format!("ode.sde: {e}")),
+                };
+                let columns = result.column_names.clone();
+                let rows: Vec<Vec<f64>> = result.t.iter().zip(result.states.iter()).map(|(t, ys)| {
+                    let mut row = vec![*t];
+                    row.extend_from_slice(ys);
+                    row
+                }).collect();
+                return Value::Table { columns, rows };
+            }
+
+            let paths = scalar_or(data, "paths", 500.0).max(1.0) as usize;
+            let quantiles: Vec<f64> = match data.get("quantiles") {
+                Some(serde_json::Value::Array(qs)) => qs.iter().filter_map(|v| v.as_f64()).collect(),
+                _ => vec![0.05, 0.5, 0.95],
+            };
+            match crate::ode::sde::sde_ensemble(&system, &y0, &cfg, scheme, seed, paths, &quantiles) {
+                Ok(ens) => {
+                    let (columns, rows) = ens.to_table();
+                    Value::Table { columns, rows }
+                }
+                Err(e) => Value::error(format!("ode.sdeEnsemble: {e}")),
+            }
+        }
+
+        "acausal.simulate" => {
+            // Network from an `acausal.network` block, else as data:
+            // `components` (library, dc_motor or custom port/equation blocks)
+            // and `connections` ([port_a, port_b] pairs).
+            let network = match acausal_network(inputs, data) {
+                Ok(n) => n,
+                Err(e) => return Value::error(format!("acausal.simulate: {e}")),
+            };
+            let t_start = scalar_or(data, "t_start", 0.0);
+            let t_end = scalar_or(data, "t_end", 1.0);
+            let output = match ode_output(inputs, data, t_start, t_end) {
+                Ok(o) => o,
+                Err(e) => return Value::error(format!("acausal.simulate: {e}")),
+            };
+            let cfg = crate::ode::OdeSolverConfig {
+                t_start,
+                t_end,
+                dt: scalar_or(data, "dt", 0.01),
+                tolerance: scalar_or(data, "tolerance", 1e-6),
+                max_steps: 100_000,
+                output,
+            };
+            match crate::acausal::simulate(&network, &cfg) {
+                Ok(result) => {
+                    let (columns, rows) = result.to_table();
+                    Value::Table { columns, rows }
+                }
+                Err(e) => Value::error(format!("acausal.simulate: {e}")),
+            }
+        }
+
+        // ── Circuit analysis: MNA over elec.* library components ────
+        "acausal.circuit.dc" | "acausal.circuit.transient" => {
+            use crate::acausal::circuit::{Circuit, Integration, TransientConfig};
+            let result = (|| -> Result<_, String> {
+                let circuit = Circuit::from_network(&acausal_network(inputs, data)?)?;
+                let (voltage_cols, current_cols) = circuit.columns();
+                let currents = data.get("output").and_then(|v| v.as_str()) == Some("currents");
+                if block_type == "acausal.circuit.dc" {
+                    let op = circuit.operating_point()?;
+                    return Ok(if currents { (current_cols, vec![op.currents]) } else { (voltage_cols, vec![op.voltages]) });
+                }
+                let cfg = TransientConfig {
+                    t_start: scalar_or(data, "t_start", 0.0),
+                    t_end: scalar_or(data, "t_end", 1e-3),
+                    dt: scalar_or(data, "dt", 1e-6),
+                    method: match data.get("method").and_then(|v| v.as_str()).unwrap_or("trapezoidal") {
+                        "gear" | "gear2" | "bdf2" => Integration::Gear2,
+                        _ => Integration::Trapezoidal,
+                    },
+                    use_initial_conditions: data.get("use_initial_conditions").and_then(|v| v.as_bool()).unwrap_or(true),
+                };
+                let result = circuit.transient(&cfg)?;
+                let (names, values) = if currents { (current_cols, result.currents) } else { (voltage_cols, result.voltages) };
+                let columns = std::iter::once("t".to_string()).chain(names).collect();
+                let rows = result.t.iter().zip(values).map(|(t, row)| std::iter::once(*t).chain(row).collect()).collect();
+                Ok((columns, rows))
+            })();
+            match result {
+                Ok((columns, rows)) => Value::Table { columns, rows },
+                Err(e) => Value::error(format!("{block_type}: {e}")),
+            }
+        }
+
+        "acausal.circuit.ac" => {
+            use crate::acausal::circuit::Circuit;
+            let result = (|| -> Result<_, String> {
+                let circuit = Circuit::from_network(&acausal_network(inputs, data)?)?;
+                let probe = data.get("probe").and_then(|v| v.as_str()).ok_or("set 'probe' to a port (node voltage) or component id (current)")?;
+                let probe = circuit.probe(probe)?;
+                let source = data.get("source").and_then(|v| v.as_str()).filter(|s| !s.is_empty());
+                let (freqs, single) = match inputs.get("f") {
+                    Some(Value::Scalar { value }) => (vec![*value], true),
+                    Some(Value::Vector { value }) => (value.clone(), false),
+                    _ => {
+                        // Logarithmic sweep, as SPICE `.ac dec`.
+                        let (f_min, f_max) = (scalar_or(data, "f_min", 1.0), scalar_or(data, "f_max", 1e6));
+                        let points = (scalar_or(data, "points", 200.0) as usize).max(2);
+                        if f_min.is_nan() || f_min <= 0.0 || f_max.is_nan() || f_max <= f_min {
+                            return Err("need 0 < f_min < f_max".to_string());
+                        }
+                        let ratio = (f_max / f_min).ln();
+                        ((0..points).map(|i| f_min * (ratio * i as f64 / (points - 1) as f64).exp()).collect(), false)
+                    }
+                };
+                let h = circuit.ac(&freqs, source)?.series(probe);
+                Ok((freqs, h, single))
+            })();
+            match result {
+                Ok((_, h, true)) => Value::Complex { re: h[0].0, im: h[0].1 },
+                Ok((freqs, h, false)) => {
+                    let columns = ["f_hz", "re", "im", "magnitude", "phase_deg"];
+                    let rows = freqs
+                        .iter()
+                        .zip(&h)
+                        .map(|(f, z)| vec![*f, z.0, z.1, z.0.hypot(z.1), z.1.atan2(z.0).to_degrees()])
+                        .collect();
+                    Value::Table { columns: columns.iter().map(|c| c.to_string()).collect(), rows }
+                }
+                Err(e) => Value::error(format!("acausal.circuit.ac: {e}")),
+            }
+        }
+
+        // Components from acausal.* library blocks (in_0..in_N) plus any in
+        // data, joined by `connections`; the result feeds acausal.simulate.
+        "acausal.network" => {
+            let mut components = Vec::new();
+            let mut k = 0;
+            while let Some(input) = inputs.get(&format!("in_{k}")) {
+                match input {
+                    Value::Text { value } => match serde_json::from_str::<serde_json::Value>(value) {
+                        Ok(serde_json::Value::Array(items)) => components.extend(items),
+                        Ok(item) => components.push(item),
+                        Err(e) => return Value::error(format!("acausal.network: in_{k} is not a component: {e}")),
+                    },
+                    Value::Error { message } => return Value::error(message.clone()),
+                    _ => return Value::error(format!("acausal.network: in_{k} must come from an acausal component block")),
+                }
+                k += 1;
+            }
+            if let Some(serde_json::Value::Array(extra)) = data.get("components") {
+                components.extend(extra.iter().cloned());
+            }
+            let spec = serde_json::json!({
+                "components": components,
+                "connections": data.get("connections").cloned().unwrap_or_else(|| serde_json::json!([])),
+            });
+            // Validate early so wiring mistakes show on this block.
+            if let Err(e) = crate::acausal::AcausalNetwork::from_json(&spec).and_then(|n| n.nodes().map(|_| ())) {
+                return Value::error(format!("acausal.network: {e}"));
+            }
+            Value::Text { value: spec.to_string() }
+        }
+
         // ── DAE Index Reduction: Pantelides Algorithm (2.36) ─────────
         "ode.daeIndexReduction" => {
             use crate::ode::dae::pantelides_index_reduction;
@@ -6170,7 +7372,7 @@ fn evaluate_node_inner(
             }
 
             let dae_cfg = DaeConfig { diff_eqs, alg_eqs, diff_names, alg_names, params };
-            let solver_cfg = crate::ode::OdeSolverConfig { t_start, t_end, dt, tolerance, max_steps: 100_000 };
+            let solver_cfg = crate::ode::OdeSolverConfig { t_start, t_end, dt, tolerance, max_steps: 100_000, ..Default::default() };
 
             let result = solve_dae(&dae_cfg, &y0, &z0, &solver_cfg);
             dae_result_to_table(&result)
@@ -6178,7 +7380,8 @@ fn evaluate_node_inner(
 
         "ode.pde1d" => {
             use crate::ode::pde1d::{
-                Pde1dConfig, parse_pde_type, parse_bc, solve_pde1d, pde1d_result_to_table,
+                Pde1dConfig, parse_pde_type, parse_bc, parse_method, solve_pde1d,
+                pde1d_result_to_table,
             };
 
             let pde_type_str = data.get("pde_type").and_then(|v| v.as_str()).unwrap_or("heat");
@@ -6214,6 +7417,7 @@ fn evaluate_node_inner(
 
             let solver = crate::ode::OdeSolverConfig {
                 t_start, t_end, dt, tolerance: 1e-6, max_steps: 500_000,
+                ..Default::default()
             };
 
             let cfg = Pde1dConfig {
@@ -6230,6 +7434,7 @@ fn evaluate_node_inner(
                 ut0: vec![0.0f64; n_points],
                 solver,
                 n_snapshots,
+                method: parse_method(data.get("method").and_then(|v| v.as_str()).unwrap_or("rk4")),
             };
 
             let result = solve_pde1d(&cfg);
@@ -7025,6 +8230,232 @@ fn evaluate_node_inner(
             }
         }
 
+        // ── General 2D FEM on unstructured meshes ───────────────────
+        "fem.mesh" => {
+            let mesh = match fem_mesh(data) {
+                Ok(m) => m,
+                Err(e) => return Value::error(format!("fem.mesh: {e}")),
+            };
+            let (columns, rows): (Vec<&str>, Vec<Vec<f64>>) = match data.get("output").and_then(|v| v.as_str()) {
+                Some("elements") => (vec!["n0", "n1", "n2"], mesh.tris.iter().map(|t| t.iter().map(|&v| v as f64).collect()).collect()),
+                Some("boundary") => (
+                    vec!["n0", "n1", "edge"],
+                    mesh.boundary.iter().map(|b| vec![b.nodes[0] as f64, b.nodes[1] as f64, b.marker as f64]).collect(),
+                ),
+                _ => (vec!["x", "y"], mesh.nodes.iter().map(|p| vec![p.0, p.1]).collect()),
+            };
+            Value::Table { columns: columns.into_iter().map(String::from).collect(), rows }
+        }
+
+        "fem.heat" => {
+            use crate::fem::heat::{solve_heat, HeatProblem};
+            let setup = fem_mesh(data).and_then(|mesh| {
+                let bcs = fem_bcs(data, &["value"], &["flux"], "h", &["ambient"])?;
+                Ok((mesh, bcs))
+            });
+            let (mesh, bcs) = match setup {
+                Ok(s) => s,
+                Err(e) => return Value::error(format!("fem.heat: {e}")),
+            };
+            let expr = |key: &str, default: &str| match data.get(key) {
+                Some(serde_json::Value::Number(n)) => n.to_string(),
+                Some(serde_json::Value::String(s)) => s.clone(),
+                _ => default.to_string(),
+            };
+            let problem = HeatProblem { conductivity: expr("k", "1"), source: expr("source", "0"), bcs };
+            match solve_heat(mesh, &problem) {
+                Ok(r) => fem_table(data, r.nodal_table(), || r.element_table()),
+                Err(e) => Value::error(format!("fem.heat: {e}")),
+            }
+        }
+
+        "fem.elasticity" => {
+            use crate::fem::elasticity::{parse_plane_model, solve_elasticity, ElasticityProblem};
+            let setup = fem_mesh(data).and_then(|mesh| {
+                let bcs = fem_bcs(data, &["ux", "uy"], &["tx", "ty"], "k", &["ux0", "uy0"])?;
+                Ok((mesh, bcs))
+            });
+            let (mesh, bcs) = match setup {
+                Ok(s) => s,
+                Err(e) => return Value::error(format!("fem.elasticity: {e}")),
+            };
+            let expr = |key: &str| match data.get(key) {
+                Some(serde_json::Value::Number(n)) => n.to_string(),
+                Some(serde_json::Value::String(s)) => s.clone(),
+                _ => "0".to_string(),
+            };
+            let problem = ElasticityProblem {
+                youngs_modulus: scalar_or(data, "E", 210e9),
+                poisson_ratio: scalar_or(data, "nu", 0.3),
+                thickness: scalar_or(data, "thickness", 1.0),
+                model: parse_plane_model(data.get("model").and_then(|v| v.as_str()).unwrap_or("stress")),
+                body_force: [expr("bx"), expr("by")],
+                bcs,
+            };
+            match solve_elasticity(mesh, &problem) {
+                Ok(r) => fem_table(data, r.nodal_table(), || r.element_table()),
+                Err(e) => Value::error(format!("fem.elasticity: {e}")),
+            }
+        }
+
+        "fem.stokes" => {
+            use crate::fem::stokes::{solve_stokes, StokesProblem};
+            let setup = fem_mesh(data).and_then(|mesh| {
+                let bcs = fem_bcs(data, &["ux", "uy"], &["tx", "ty"], "k", &["ux0", "uy0"])?;
+                Ok((mesh, bcs))
+            });
+            let (mesh, bcs) = match setup {
+                Ok(s) => s,
+                Err(e) => return Value::error(format!("fem.stokes: {e}")),
+            };
+            let expr = |key: &str| match data.get(key) {
+                Some(serde_json::Value::Number(n)) => n.to_string(),
+                Some(serde_json::Value::String(s)) => s.clone(),
+                _ => "0".to_string(),
+            };
+            let problem = StokesProblem { viscosity: scalar_or(data, "mu", 1.0), body_force: [expr("fx"), expr("fy")], bcs };
+            match solve_stokes(mesh, &problem) {
+                Ok(r) => fem_table(data, r.nodal_table(), || r.element_table()),
+                Err(e) => Value::error(format!("fem.stokes: {e}")),
+            }
+        }
+
+        "modal.eigen" => {
+            let setup = (|| -> Result<_, String> {
+                let k = structural_input(inputs, "K")?.ok_or("needs a stiffness matrix 'K'")?;
+                let m = structural_input(inputs, "M")?.ok_or("needs a mass matrix 'M'")?;
+                let count = data.get("n_modes").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+                let modes = crate::modal::solve_modes(&k, &m, count)?;
+                Ok((m, modes))
+            })();
+            let (m, modes) = match setup {
+                Ok(s) => s,
+                Err(e) => return Value::error(format!("modal.eigen: {e}")),
+            };
+            if data.get("output").and_then(|v| v.as_str()) == Some("shapes") {
+                let n = m.dim();
+                let mut columns = vec!["dof".to_string()];
+                columns.extend((1..=modes.shapes.len()).map(|i| format!("mode_{i}")));
+                let rows = (0..n)
+                    .map(|d| std::iter::once(d as f64).chain(modes.shapes.iter().map(|phi| phi[d])).collect())
+                    .collect();
+                return Value::Table { columns, rows };
+            }
+            let influence = match inputs.get("r") {
+                Some(Value::Vector { value }) if value.len() == m.dim() => value.clone(),
+                Some(Value::Vector { value }) => {
+                    return Value::error(format!("modal.eigen: influence vector 'r' has {} entries, expected {}", value.len(), m.dim()))
+                }
+                _ => vec![1.0; m.dim()],
+            };
+            let participation = crate::modal::participation(&modes, &m, &influence);
+            let (omegas, freqs) = (modes.omegas(), modes.frequencies_hz());
+            let columns = ["mode", "omega", "freq_hz", "participation", "effective_mass", "mass_fraction"];
+            let rows = participation
+                .iter()
+                .enumerate()
+                .map(|(i, p)| vec![(i + 1) as f64, omegas[i], freqs[i], p.factor, p.effective_mass, p.mass_fraction])
+                .collect();
+            Value::Table { columns: columns.iter().map(|c| c.to_string()).collect(), rows }
+        }
+
+        "modal.frf" => {
+            use crate::modal::{frf_direct, frf_modal, parse_frf_kind, solve_modes, DIRECT_FRF_LIMIT, MAX_FRF_POINTS};
+            let result = (|| -> Result<_, String> {
+                let k = structural_input(inputs, "K")?.ok_or("needs a stiffness matrix 'K'")?;
+                let m = structural_input(inputs, "M")?.ok_or("needs a mass matrix 'M'")?;
+                let c = structural_input(inputs, "C")?;
+                let input = data.get("input_dof").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+                let output = data.get("output_dof").and_then(|v| v.as_u64()).unwrap_or(input as u64) as usize;
+                let (f_min, f_max) = (scalar_or(data, "f_min", 0.1), scalar_or(data, "f_max", 100.0));
+                let points = scalar_or(data, "points", 500.0);
+                if points > MAX_FRF_POINTS as f64 {
+                    return Err(format!("{points} points exceeds the {MAX_FRF_POINTS} point limit"));
+                }
+                let points = (points as usize).max(2);
+                if f_min.is_nan() || f_min < 0.0 || f_max.is_nan() || f_max <= f_min {
+                    return Err("need 0 ≤ f_min < f_max".to_string());
+                }
+                let freqs: Vec<f64> = (0..points).map(|i| f_min + (f_max - f_min) * i as f64 / (points - 1) as f64).collect();
+                let kind = parse_frf_kind(data.get("kind").and_then(|v| v.as_str()).unwrap_or("receptance"));
+                let method = data.get("method").and_then(|v| v.as_str()).unwrap_or("auto").to_lowercase();
+                let modal = method == "modal" || (method != "direct" && k.dim() > DIRECT_FRF_LIMIT);
+                let h = if modal {
+                    let count = data.get("n_modes").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+                    let modes = solve_modes(&k, &m, count)?;
+                    frf_modal(&modes, &[scalar_or(data, "zeta", 0.02)], input, output, &freqs, kind)?
+                } else {
+                    frf_direct(&k, &m, c.as_ref(), input, output, &freqs, kind)?
+                };
+                Ok((freqs, h))
+            })();
+            match result {
+                Ok((freqs, h)) => {
+                    let columns = ["f_hz", "re", "im", "magnitude", "phase_deg"];
+                    let rows = freqs
+                        .iter()
+                        .zip(&h)
+                        .map(|(f, z)| vec![*f, z.0, z.1, z.0.hypot(z.1), z.1.atan2(z.0).to_degrees()])
+                        .collect();
+                    Value::Table { columns: columns.iter().map(|c| c.to_string()).collect(), rows }
+                }
+                Err(e) => Value::error(format!("modal.frf: {e}")),
+            }
+        }
+
+        "fem.modal" => {
+            use crate::fem::elasticity::{parse_plane_model, structural_matrices, ElasticityProblem};
+            use crate::modal::{participation, solve_modes, StructuralMatrix};
+            let result = (|| -> Result<_, String> {
+                let mesh = fem_mesh(data)?;
+                let bcs = fem_bcs(data, &["ux", "uy"], &["tx", "ty"], "k", &["ux0", "uy0"])?;
+                let problem = ElasticityProblem {
+                    youngs_modulus: scalar_or(data, "E", 210e9),
+                    poisson_ratio: scalar_or(data, "nu", 0.3),
+                    thickness: scalar_or(data, "thickness", 1.0),
+                    model: parse_plane_model(data.get("model").and_then(|v| v.as_str()).unwrap_or("stress")),
+                    body_force: ["0".into(), "0".into()],
+                    bcs,
+                };
+                let matrices = structural_matrices(&mesh, &problem, scalar_or(data, "density", 7850.0))?;
+                let count = (scalar_or(data, "n_modes", 6.0) as usize).max(1);
+                let (k, m) = (StructuralMatrix::Sparse(matrices.stiffness.clone()), StructuralMatrix::Sparse(matrices.mass.clone()));
+                let modes = solve_modes(&k, &m, count)?;
+                let px = participation(&modes, &m, &matrices.influence(0));
+                let py = participation(&modes, &m, &matrices.influence(1));
+                Ok((mesh, matrices, modes, px, py))
+            })();
+            let (mesh, matrices, modes, px, py) = match result {
+                Ok(r) => r,
+                Err(e) => return Value::error(format!("fem.modal: {e}")),
+            };
+            if data.get("output").and_then(|v| v.as_str()) == Some("shapes") {
+                let mut columns = vec!["x".to_string(), "y".to_string()];
+                let shapes: Vec<Vec<[f64; 2]>> = modes.shapes.iter().map(|phi| matrices.expand(phi, mesh.nodes.len())).collect();
+                for i in 1..=shapes.len() {
+                    columns.push(format!("ux_{i}"));
+                    columns.push(format!("uy_{i}"));
+                }
+                let rows = mesh
+                    .nodes
+                    .iter()
+                    .enumerate()
+                    .map(|(node, p)| {
+                        let mut row = vec![p.0, p.1];
+                        row.extend(shapes.iter().flat_map(|s| s[node]));
+                        row
+                    })
+                    .collect();
+                return Value::Table { columns, rows };
+            }
+            let (omegas, freqs) = (modes.omegas(), modes.frequencies_hz());
+            let columns = ["mode", "omega", "freq_hz", "mass_fraction_x", "mass_fraction_y"];
+            let rows = (0..freqs.len())
+                .map(|i| vec![(i + 1) as f64, omegas[i], freqs[i], px[i].mass_fraction, py[i].mass_fraction])
+                .collect();
+            Value::Table { columns: columns.iter().map(|c| c.to_string()).collect(), rows }
+        }
+
         // ── Gradient Checkpointing with Revolve Schedule (1.36) ──────
         "ad.gradCheckpoint" => {
             use crate::grad_checkpoint::revolve_adjoint;
@@ -7057,7 +8488,7 @@ fn evaluate_node_inner(
             let n_checkpoints = data.get("n_checkpoints").and_then(|v| v.as_f64()).unwrap_or(4.0) as usize;
 
             let system = OdeSystem { equations, state_names, params };
-            let solver_cfg = OdeSolverConfig { t_start, t_end, dt, tolerance: 1e-6, max_steps: 100_000 };
+            let solver_cfg = OdeSolverConfig { t_start, t_end, dt, tolerance: 1e-6, max_steps: 100_000, ..Default::default() };
 
             let result = revolve_adjoint(&system, &param_names, &objective_expr, &solver_cfg, n_checkpoints, fd_eps);
 
@@ -7218,7 +8649,7 @@ fn evaluate_node_inner(
             let n_checkpoints = data.get("n_checkpoints").and_then(|v| v.as_f64()).unwrap_or(0.0) as usize;
 
             let system = OdeSystem { equations, state_names, params };
-            let solver_cfg = OdeSolverConfig { t_start, t_end, dt, tolerance: 1e-6, max_steps: 100_000 };
+            let solver_cfg = OdeSolverConfig { t_start, t_end, dt, tolerance: 1e-6, max_steps: 100_000, ..Default::default() };
             let adj_cfg = AdjointConfig {
                 system, param_names, objective_expr, solver_cfg, n_checkpoints, fd_eps,
             };
@@ -7317,18 +8748,15 @@ fn evaluate_node_inner(
 
         // ── MATLAB .mat v5 import (4.9) ──────────────────────────────────────
         "mat_import" => {
-            // Reads MATLAB v5 binary data from data.matBytes (JSON array of u8 values).
+            // Reads MATLAB v5 binary data from the binary dataset named by data.blobRef,
+            // or (legacy) from data.matBytes as a JSON array of u8 values.
             // Returns the first numeric variable found as a Matrix (rows×cols, column-major)
             // or as a Vector (for 1D arrays) or Scalar (for 1×1).
             // Optional data.variable selects a named variable; defaults to first.
-            let bytes: Vec<u8> = data
-                .get("matBytes")
-                .and_then(|v| v.as_array())
-                .map(|arr| arr.iter().filter_map(|b| b.as_u64().map(|n| n as u8)).collect())
-                .unwrap_or_default();
+            let bytes = file_bytes(data, blobs, "matBytes");
 
             if bytes.is_empty() {
-                return Value::error("mat_import: no data in 'matBytes' (expected JSON array of byte values)");
+                return Value::error("mat_import: no file data (register a binary dataset and set 'blobRef', or provide 'matBytes')");
             }
 
             let want_name = data.get("variable").and_then(|v| v.as_str()).unwrap_or("");
@@ -7371,14 +8799,12 @@ fn evaluate_node_inner(
 
         // ── Parquet import (4.8) ─────────────────────────────────────────────
         "parquet_import" => {
-            // Reads Parquet file bytes from data.parquetBytes (JSON array of u8).
+            // Reads Parquet file bytes from the binary dataset named by data.blobRef,
+            // or (legacy) from data.parquetBytes as a JSON array of u8.
             // Returns a Table value with all numeric columns.
-            let bytes: Vec<u8> = data.get("parquetBytes")
-                .and_then(|v| v.as_array())
-                .map(|arr| arr.iter().filter_map(|b| b.as_u64().map(|n| n as u8)).collect())
-                .unwrap_or_default();
+            let bytes = file_bytes(data, blobs, "parquetBytes");
             if bytes.is_empty() {
-                Value::error("parquet_import: no data in parquetBytes field")
+                Value::error("parquet_import: no file data (set 'blobRef' or 'parquetBytes')")
             } else {
                 match crate::parquet::parse_parquet(&bytes) {
                     Ok(cols) => crate::parquet::parquet_to_table(cols),
@@ -7389,7 +8815,7 @@ fn evaluate_node_inner(
 
         // ── Parquet export (4.8) ─────────────────────────────────────────────
         "parquet_export" => {
-            // Converts a Table input to a Parquet file byte vector (Value::Vector of u8 as f64).
+            // Converts a Table input to a Parquet file (Value::Bytes).
             match inputs.get("table") {
                 Some(Value::Table { columns, rows }) => {
                     let col_data: Vec<Vec<f64>> = columns.iter().enumerate().map(|(ci, _)| {
@@ -7400,9 +8826,7 @@ fn evaluate_node_inner(
                         .map(|(name, data)| (name.as_str(), data.as_slice()))
                         .collect();
                     match crate::parquet::write_parquet(&pairs) {
-                        Ok(file_bytes) => Value::Vector {
-                            value: file_bytes.iter().map(|b| *b as f64).collect(),
-                        },
+                        Ok(file_bytes) => Value::bytes(file_bytes, "application/vnd.apache.parquet"),
                         Err(e) => Value::error(e),
                     }
                 }
@@ -7445,6 +8869,29 @@ fn evaluate_node_inner(
             }
         }
 
+        // ── Acausal component library: acausal.<domain>.<component> ─
+        // Each block emits its component as JSON for acausal.network;
+        // parameter inputs override data, which overrides the defaults.
+        kind if kind.strip_prefix("acausal.").and_then(crate::acausal::library::find).is_some() => {
+            let spec = crate::acausal::library::find(&kind["acausal.".len()..]).expect("guarded above");
+            let default_id = spec.kind.rsplit('.').next().unwrap_or(spec.kind);
+            let mut component = serde_json::Map::new();
+            component.insert("id".into(), data.get("id").and_then(|v| v.as_str()).unwrap_or(default_id).into());
+            component.insert("type".into(), spec.kind.into());
+            for param in spec.parameters {
+                let value = match inputs.get(param.name) {
+                    Some(Value::Error { message }) => return Value::error(message.clone()),
+                    Some(v) => match v.as_scalar() {
+                        Some(x) => x,
+                        None => return Value::error(format!("{kind}: '{}' must be a scalar", param.name)),
+                    },
+                    None => scalar_or(data, param.name, param.default),
+                };
+                component.insert(param.name.into(), value.into());
+            }
+            Value::Text { value: serde_json::Value::Object(component).to_string() }
+        }
+
         _ => Value::error(format!("Unknown block type: {}", block_type)),
     }
 }
@@ -7650,6 +9097,14 @@ fn canonicalize_value(v: Value) -> Value {
         },
         // HighPrecision values are already canonicalized (arbitrary precision, no f64 artefacts)
         Value::HighPrecision { .. } => v,
+        Value::Bytes { .. } => v,
+        Value::SparseMatrix { rows, cols, row_ptrs, col_indices, values } => Value::SparseMatrix {
+            rows,
+            cols,
+            row_ptrs,
+            col_indices,
+            values: values.into_iter().map(canonicalize).collect(),
+        },
     }
 }
 
@@ -9888,4 +11343,409 @@ mod tests {
         let v = evaluate_node("add", &inputs, &HashMap::new());
         assert_eq!(v.as_scalar(), Some(3.0)); // Normal scalar, not HP
     }
+
+    #[test]
+    fn acausal_library_blocks_build_and_simulate_a_network() {
+        let block = |kind: &str, id: &str, params: &[(&str, f64)]| {
+            let mut data = HashMap::new();
+            data.insert("id".to_string(), serde_json::json!(id));
+            evaluate_node(kind, &make_inputs(params), &data)
+        };
+        let parts = [
+            block("acausal.elec.voltage_source", "V1", &[("V", 2.0)]),
+            block("acausal.elec.resistor", "R1", &[("R", 1000.0)]),
+            block("acausal.elec.capacitor", "C1", &[("C", 1e-3)]),
+            block("acausal.elec.ground", "G", &[]),
+        ];
+        let inputs: HashMap<String, Value> = parts.into_iter().enumerate().map(|(k, v)| (format!("in_{k}"), v)).collect();
+        let mut data = HashMap::new();
+        data.insert(
+            "connections".to_string(),
+            serde_json::json!([["V1.p", "R1.p"], ["R1.n", "C1.p"], ["C1.n", "V1.n"], ["V1.n", "G.p"]]),
+        );
+        let network = evaluate_node("acausal.network", &inputs, &data);
+        assert!(matches!(network, Value::Text { .. }), "{network:?}");
+
+        let mut data = HashMap::new();
+        data.insert("t_end".to_string(), serde_json::json!(1.0));
+        data.insert("output_interval".to_string(), serde_json::json!(0.5));
+        let mut inputs = HashMap::new();
+        inputs.insert("network".to_string(), network);
+        match evaluate_node("acausal.simulate", &inputs, &data) {
+            Value::Table { columns, rows } => {
+                let v = columns.iter().position(|c| c == "C1.v").unwrap();
+                let last = rows.last().unwrap();
+                assert!((last[v] - 2.0 * (1.0 - (-1.0f64).exp())).abs() < 1e-3, "{}", last[v]);
+            }
+            other => panic!("expected table, got {other:?}"),
+        }
+
+        let mut data = HashMap::new();
+        data.insert("connections".to_string(), serde_json::json!([["V1.p", "nowhere.p"]]));
+        let inputs: HashMap<String, Value> = [("in_0".to_string(), block("acausal.elec.voltage_source", "V1", &[]))].into();
+        assert!(evaluate_node("acausal.network", &inputs, &data).is_error());
+    }
+
+    #[test]
+    fn circuit_blocks_report_dc_ac_and_transient() {
+        let mut data = HashMap::new();
+        data.insert(
+            "components".to_string(),
+            serde_json::json!([
+                { "id": "V1", "type": "elec.voltage_source", "V": 2.0 },
+                { "id": "R1", "type": "elec.resistor", "R": 1000.0 },
+                { "id": "C1", "type": "elec.capacitor", "C": 1e-6 },
+                { "id": "G", "type": "elec.ground" }
+            ]),
+        );
+        data.insert(
+            "connections".to_string(),
+            serde_json::json!([["V1.p", "R1.p"], ["R1.n", "C1.p"], ["C1.n", "V1.n"], ["V1.n", "G.p"]]),
+        );
+        match evaluate_node("acausal.circuit.dc", &HashMap::new(), &data) {
+            Value::Table { columns, rows } => {
+                assert_eq!(columns, ["v(V1.p)", "v(R1.n)"]);
+                assert!((rows[0][1] - 2.0).abs() < 1e-6);
+            }
+            other => panic!("expected table, got {other:?}"),
+        }
+
+        data.insert("probe".to_string(), serde_json::json!("C1.p"));
+        let fc = 1.0 / (2.0 * std::f64::consts::PI * 1e-3);
+        match evaluate_node("acausal.circuit.ac", &make_inputs(&[("f", fc)]), &data) {
+            Value::Complex { re, im } => assert!((re - 0.5).abs() < 1e-9 && (im + 0.5).abs() < 1e-9),
+            other => panic!("expected complex, got {other:?}"),
+        }
+
+        data.insert("t_end".to_string(), serde_json::json!(5e-3));
+        data.insert("dt".to_string(), serde_json::json!(1e-5));
+        data.insert("output".to_string(), serde_json::json!("currents"));
+        match evaluate_node("acausal.circuit.transient", &HashMap::new(), &data) {
+            Value::Table { columns, rows } => {
+                assert_eq!(columns, ["t", "i(V1)", "i(R1)", "i(C1)"]);
+                // The capacitor charges from 0 V: i = V/R·e^{-t/RC}.
+                let last = rows.last().unwrap();
+                assert!((last[3] - 2e-3 * (-5.0f64).exp()).abs() < 1e-7, "{}", last[3]);
+            }
+            other => panic!("expected table, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn fmu_cosimulate_runs_a_declared_fmu_against_an_input_table() {
+        let xml = "<fmiModelDescription fmiVersion=\"2.0\" modelName=\"Lag\" guid=\"{0}\"><CoSimulation/>\
+            <ScalarVariable name=\"u\" valueReference=\"1\" causality=\"input\"><Real start=\"0\"/></ScalarVariable>\
+            <ScalarVariable name=\"y\" valueReference=\"2\" causality=\"output\"><Real start=\"0\"/></ScalarVariable>\
+            </fmiModelDescription>";
+        let spec = serde_json::json!({ "name": "lag", "modelDescription": xml, "equations": ["der(y) = u - y"] });
+        let mut inputs = HashMap::new();
+        inputs.insert("fmus".to_string(), Value::Text { value: spec.to_string() });
+        inputs.insert("inputs".to_string(), Value::Scalar { value: 1.0 });
+        let mut data = HashMap::new();
+        data.insert("stopTime".to_string(), serde_json::json!(1.0));
+        data.insert("stepSize".to_string(), serde_json::json!(0.1));
+        match evaluate_node("fmu.cosimulate", &inputs, &data) {
+            Value::Table { columns, rows } => {
+                assert_eq!(columns, ["time", "lag.y"]);
+                assert_eq!(rows.len(), 11);
+                assert!((rows[10][1] - (1.0 - (-1.0f64).exp())).abs() < 1e-6, "{}", rows[10][1]);
+            }
+            other => panic!("expected table, got {other:?}"),
+        }
+
+        inputs.insert("inputs".to_string(), Value::Vector { value: vec![1.0, 2.0] });
+        assert!(matches!(evaluate_node("fmu.cosimulate", &inputs, &data), Value::Error { .. }));
+    }
+
+    #[test]
+    fn fmu_export_compiles_the_supplied_graph() {
+        let graph = serde_json::json!({
+            "version": 1,
+            "nodes": [
+                { "id": "gain", "blockType": "number", "data": { "value": 2.0 } },
+                { "id": "u", "blockType": "number", "data": { "value": 0.0, "fmuCausality": "input" } },
+                { "id": "prod", "blockType": "multiply", "data": {} }
+            ],
+            "edges": [
+                { "id": "e1", "source": "gain", "sourceHandle": "out", "target": "prod", "targetHandle": "a" },
+                { "id": "e2", "source": "u", "sourceHandle": "out", "target": "prod", "targetHandle": "b" }
+            ]
+        });
+        let mut data = HashMap::new();
+        data.insert("fmuModelName".to_string(), serde_json::json!("Gain"));
+        data.insert("graph".to_string(), graph);
+        match evaluate_node("fmu.export", &HashMap::new(), &data) {
+            Value::Bytes { data, mime } => {
+                assert_eq!(mime, "application/zip");
+                assert!(data.starts_with(b"PK\x03\x04"));
+                let archive = String::from_utf8_lossy(&data);
+                assert!(archive.contains("sources/Gain.c"));
+                assert!(archive.contains("(r[2] * r[0])"), "{archive}");
+            }
+            other => panic!("expected bytes, got {other:?}"),
+        }
+
+        data.insert("graph".to_string(), serde_json::json!({ "version": 1, "nodes": "bad", "edges": [] }));
+        assert!(matches!(evaluate_node("fmu.export", &HashMap::new(), &data), Value::Error { .. }));
+    }
+
+    #[test]
+    fn sym_solve_series_and_limit_blocks() {
+        let text = |s: &str| Value::Text { value: s.to_string() };
+        let json = |v: Value| match v {
+            Value::Text { value } => serde_json::from_str::<serde_json::Value>(&value).unwrap(),
+            other => panic!("expected text, got {other:?}"),
+        };
+
+        let inputs = HashMap::from([("expr".to_string(), text("x^2 = 4"))]);
+        let out = json(evaluate_node("sym.solve", &inputs, &HashMap::new()));
+        assert_eq!(out["values"], serde_json::json!([{ "x": -2.0 }, { "x": 2.0 }]));
+        assert_eq!(out["latex"], "x = -2,\\; x = 2");
+
+        let inputs = HashMap::from([("expr".to_string(), text("x + y = a; x - y = 1")), ("var".to_string(), text("x, y"))]);
+        let out = json(evaluate_node("sym.solve", &inputs, &HashMap::new()));
+        assert!(out["values"][0]["x"].is_null());
+        let x = crate::symbolic::parse_expr(out["solutions"][0]["x"].as_str().unwrap()).unwrap();
+        assert_eq!(crate::symbolic::eval(&x, &HashMap::from([("a".to_string(), 5.0)])), 3.0);
+
+        let inputs = HashMap::from([("expr".to_string(), text("cos(x)"))]);
+        let data = HashMap::from([("order".to_string(), serde_json::json!(4))]);
+        let out = json(evaluate_node("sym.series", &inputs, &data));
+        assert_eq!(out["terms"].as_array().unwrap().len(), 3);
+        assert_eq!(out["terms"][1]["value"], -0.5);
+        assert!(out["latex"].as_str().unwrap().ends_with("O\\left(x^{5}\\right)"));
+
+        let inputs = HashMap::from([("expr".to_string(), text("(x^2 + 1)/(3*x^2)"))]);
+        let data = HashMap::from([("x0".to_string(), serde_json::json!("inf"))]);
+        let out = json(evaluate_node("sym.limit", &inputs, &data));
+        assert!((out["value"].as_f64().unwrap() - 1.0 / 3.0).abs() < 1e-12);
+        assert!(out["latex"].as_str().unwrap().starts_with("\\lim_{x \\to \\infty}"));
+
+        let inputs = HashMap::from([("expr".to_string(), text("1/x"))]);
+        assert!(matches!(evaluate_node("sym.limit", &inputs, &HashMap::new()), Value::Error { .. }));
+    }
+
+    #[test]
+    fn sym_matrix_codegen_and_lagrange_blocks() {
+        let text = |s: &str| Value::Text { value: s.to_string() };
+        let json = |v: Value| match v {
+            Value::Text { value } => serde_json::from_str::<serde_json::Value>(&value).unwrap(),
+            other => panic!("expected text, got {other:?}"),
+        };
+
+        let inputs = HashMap::from([("expr".to_string(), text("x*y; x + y^2"))]);
+        let out = json(evaluate_node("sym.jacobian", &inputs, &HashMap::new()));
+        assert_eq!(out["vars"], serde_json::json!(["x", "y"]));
+        assert_eq!(out["entries"][1][0], "1");
+
+        let inputs = HashMap::from([("a".to_string(), text("[2, 1; 4, 3]"))]);
+        let out = json(evaluate_node("sym.matrix", &inputs, &HashMap::new()));
+        assert_eq!(out["value"], 2.0);
+        let data = HashMap::from([("op".to_string(), serde_json::json!("multiply"))]);
+        assert!(matches!(evaluate_node("sym.matrix", &inputs, &data), Value::Error { .. }));
+
+        let inputs = HashMap::from([("expr".to_string(), text("f = sin(x)^2 + sin(x)"))]);
+        let data = HashMap::from([("target".to_string(), serde_json::json!("c"))]);
+        match evaluate_node("sym.codegen", &inputs, &data) {
+            Value::Text { value } => {
+                assert!(value.contains("void generated(double x, double out[1])"), "{value}");
+                assert!(value.contains("const double cse0 = sin(x);"), "{value}");
+            }
+            other => panic!("expected text, got {other:?}"),
+        }
+
+        // Pendulum: the derived first-order system drives ode.rk4 directly.
+        let inputs = HashMap::from([
+            ("T".to_string(), text("0.5*m*l^2*theta_dot^2")),
+            ("V".to_string(), text("-m*g*l*cos(theta)")),
+            ("coords".to_string(), text("theta")),
+        ]);
+        let out = json(evaluate_node("sym.lagrange", &inputs, &HashMap::new()));
+        assert_eq!(out["state"], serde_json::json!(["theta", "theta_dot"]));
+        let equations = out["odeEquations"].as_str().unwrap();
+        let inputs = HashMap::from([
+            ("equations".to_string(), text(equations)),
+            ("y0".to_string(), Value::Vector { value: vec![0.01, 0.0] }),
+        ]);
+        let data = HashMap::from([
+            ("t_end".to_string(), serde_json::json!(std::f64::consts::PI)),
+            ("dt".to_string(), serde_json::json!(0.001)),
+            ("params".to_string(), serde_json::json!({ "m": 1.0, "g": 4.0, "l": 1.0 })),
+        ]);
+        // Small-angle period 2π/√(g/l) = π, so θ returns to its start.
+        match evaluate_node("ode.rk4", &inputs, &data) {
+            Value::Table { rows, .. } => assert!((rows.last().unwrap()[1] - 0.01).abs() < 1e-5, "{:?}", rows.last()),
+            other => panic!("expected table, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn modal_frf_rejects_too_many_frequency_points() {
+        let inputs = HashMap::from([
+            ("K".to_string(), Value::Scalar { value: 100.0 }),
+            ("M".to_string(), Value::Scalar { value: 1.0 }),
+        ]);
+        let data = HashMap::from([("points".to_string(), serde_json::json!(1e12))]);
+        match evaluate_node("modal.frf", &inputs, &data) {
+            Value::Error { message } => assert!(message.contains("100000 point limit"), "{message}"),
+            other => panic!("expected error, got {other:?}"),
+        }
+        let data = HashMap::from([("points".to_string(), serde_json::json!(100_000))]);
+        match evaluate_node("modal.frf", &inputs, &data) {
+            Value::Table { rows, .. } => assert_eq!(rows.len(), 100_000),
+            other => panic!("expected table, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn ode_symplectic_angular_momentum_column_is_opt_in() {
+        // Planar Kepler orbit: two coordinates, so the old default would have
+        // added the angular momentum column.
+        let inputs = HashMap::from([
+            ("accelerations".to_string(), Value::Text { value: "-q0/(q0^2+q1^2)^1.5; -q1/(q0^2+q1^2)^1.5".to_string() }),
+            ("y0".to_string(), Value::Vector { value: vec![1.0, 0.0, 0.0, 1.0] }),
+        ]);
+        let mut data = HashMap::from([("t_end".to_string(), serde_json::json!(1.0))]);
+        let columns = |data: &HashMap<String, serde_json::Value>| match evaluate_node("ode.symplectic", &inputs, data) {
+            Value::Table { columns, .. } => columns,
+            other => panic!("expected table, got {other:?}"),
+        };
+        assert!(!columns(&data).iter().any(|c| c == "angular_momentum_drift"));
+        data.insert("angular_dim".to_string(), serde_json::json!(2));
+        assert_eq!(columns(&data).last().map(String::as_str), Some("angular_momentum_drift"));
+    }
+
+    #[test]
+    fn ode_scalar_t_eval_is_a_single_output_time() {
+        let inputs = HashMap::from([
+            ("equations".to_string(), Value::Text { value: "-y0".to_string() }),
+            ("y0".to_string(), Value::Scalar { value: 1.0 }),
+            ("t_eval".to_string(), Value::Scalar { value: 0.5 }),
+        ]);
+        let expect_single_row = |inputs: &HashMap<String, Value>, data: &HashMap<String, serde_json::Value>| {
+            match evaluate_node("ode.rk45", inputs, data) {
+                Value::Table { rows, .. } => {
+                    assert_eq!(rows.len(), 1, "{rows:?}");
+                    assert!((rows[0][0] - 0.5).abs() < 1e-12);
+                    assert!((rows[0][1] - (-0.5f64).exp()).abs() < 1e-5, "{:?}", rows[0]);
+                }
+                other => panic!("expected table, got {other:?}"),
+            }
+        };
+        expect_single_row(&inputs, &HashMap::new());
+
+        let mut inputs = inputs;
+        inputs.remove("t_eval");
+        expect_single_row(&inputs, &HashMap::from([("t_eval".to_string(), serde_json::json!(0.5))]));
+    }
+
+    #[test]
+    fn ode_output_interval_must_be_positive_and_bounded() {
+        let inputs = HashMap::from([
+            ("equations".to_string(), Value::Text { value: "-y0".to_string() }),
+            ("y0".to_string(), Value::Scalar { value: 1.0 }),
+        ]);
+        for block in ["ode.rk4", "ode.rk45", "ode.bdf", "ode.dde"] {
+            for interval in [0.0, -0.5, 1e-9, 0.25] {
+                let data = HashMap::from([("output_interval".to_string(), serde_json::json!(interval))]);
+                let v = evaluate_node(block, &inputs, &data);
+                assert_eq!(v.is_error(), interval != 0.25, "{block} with interval {interval}: {v:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn sym_groebner_uses_exact_arithmetic_by_default() {
+        let text = |s: &str| Value::Text { value: s.to_string() };
+        let inputs = HashMap::from([("polynomials".to_string(), text("x^2 + y^2 + z^2 = 1; x^2 + z^2 - y; x - z"))]);
+        let mut data = HashMap::from([
+            ("variables".to_string(), serde_json::json!("x, y, z")),
+            ("order".to_string(), serde_json::json!("lex")),
+        ]);
+        match evaluate_node("sym.groebner", &inputs, &data) {
+            Value::Text { value } => assert!(value.ends_with("x - z\ny - 2z^2\nz^4 + (1/2)z^2 - 1/4"), "{value}"),
+            other => panic!("expected text, got {other:?}"),
+        }
+        data.insert("mode".to_string(), serde_json::json!("solve"));
+        match evaluate_node("sym.groebner", &inputs, &data) {
+            Value::Text { value } => assert_eq!(value.lines().count(), 2, "{value}"),
+            other => panic!("expected text, got {other:?}"),
+        }
+        data.insert("arithmetic".to_string(), serde_json::json!("float"));
+        data.insert("mode".to_string(), serde_json::json!("basis"));
+        let inputs = HashMap::from([("polynomials".to_string(), text("x - z; x^2 + z^2 - y"))]);
+        match evaluate_node("sym.groebner", &inputs, &data) {
+            Value::Text { value } => assert!(value.starts_with("Gröbner basis (2 polynomials"), "{value}"),
+            other => panic!("expected text, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn sparse_blocks_flow_through_matrix_ops() {
+        let triplets = Value::Table {
+            columns: vec!["row".into(), "col".into(), "value".into()],
+            rows: vec![
+                vec![0.0, 0.0, 4.0],
+                vec![0.0, 1.0, -1.0],
+                vec![1.0, 0.0, -1.0],
+                vec![1.0, 1.0, 4.0],
+                vec![1.0, 2.0, -1.0],
+                vec![2.0, 1.0, -1.0],
+                vec![2.0, 2.0, 4.0],
+            ],
+        };
+        let none = HashMap::new();
+        let a = evaluate_node("sparse.fromTable", &HashMap::from([("table".to_string(), triplets)]), &none);
+        assert!(matches!(&a, Value::SparseMatrix { values, .. } if values.len() == 7), "{a:?}");
+        let json = serde_json::to_value(&a).unwrap();
+        assert_eq!(json["kind"], "sparseMatrix");
+        assert_eq!(json["rowPtrs"], serde_json::json!([0, 2, 5, 7]));
+
+        match evaluate_node("sparse.stats", &HashMap::from([("matrix".to_string(), a.clone())]), &none) {
+            Value::Table { columns, rows } => {
+                let col = |name: &str| rows[0][columns.iter().position(|c| c == name).unwrap()];
+                assert_eq!((col("nnz"), col("lowerBandwidth"), col("symmetric"), col("diagDominant")), (7.0, 1.0, 1.0, 1.0));
+            }
+            other => panic!("expected table, got {other:?}"),
+        }
+
+        // matrix.* ops: solve and transpose stay sparse, det densifies.
+        let b = Value::Vector { value: vec![3.0, 2.0, 3.0] };
+        let inputs = HashMap::from([("a".to_string(), a.clone()), ("b".to_string(), b)]);
+        for (kind, method) in [("matrix.solve", "auto"), ("sparse.solve", "lu"), ("sparse.solve", "cg")] {
+            let data = HashMap::from([("method".to_string(), serde_json::json!(method))]);
+            match evaluate_node(kind, &inputs, &data) {
+                Value::Vector { value } => assert!(value.iter().all(|x| (x - 1.0).abs() < 1e-9), "{method}: {value:?}"),
+                other => panic!("{method}: expected vector, got {other:?}"),
+            }
+        }
+        let single = HashMap::from([("matrix".to_string(), a.clone())]);
+        assert!(matches!(evaluate_node("matrix_transpose", &single, &none), Value::SparseMatrix { .. }));
+        assert!(matches!(evaluate_node("matrix.det", &single, &none), Value::Scalar { value } if (value - 56.0).abs() < 1e-9));
+        let product = evaluate_node("sparse.multiply", &HashMap::from([("a".to_string(), a.clone()), ("b".to_string(), a.clone())]), &none);
+        assert!(matches!(&product, Value::SparseMatrix { values, .. } if values[0] == 17.0), "{product:?}");
+
+        // Export → import round trip in both formats.
+        for format in ["mtx", "hb"] {
+            let data = HashMap::from([("format".to_string(), serde_json::json!(format)), ("symmetric".to_string(), serde_json::json!(true))]);
+            let text = match evaluate_node("sparse.export", &single, &data) {
+                Value::Bytes { data, .. } => String::from_utf8(data).unwrap(),
+                other => panic!("{format}: expected bytes, got {other:?}"),
+            };
+            let back = evaluate_node("sparse.import", &HashMap::new(), &HashMap::from([("text".to_string(), serde_json::json!(text))]));
+            let dense = |v: &Value| evaluate_node("sparse.toMatrix", &HashMap::from([("matrix".to_string(), v.clone())]), &none);
+            assert_eq!(format!("{:?}", dense(&back)), format!("{:?}", dense(&a)), "{format}");
+        }
+
+        // Malformed CSR (e.g. from a hand-edited project) is an error, not a panic.
+        let broken = Value::SparseMatrix { rows: 2, cols: 2, row_ptrs: vec![0, 3, 1], col_indices: vec![0], values: vec![1.0] };
+        let with = |port: &str| {
+            let mut inputs = HashMap::from([("a".to_string(), a.clone()), ("b".to_string(), a.clone())]);
+            inputs.insert(port.to_string(), broken.clone());
+            inputs
+        };
+        for (kind, port) in [("sparse.stats", "matrix"), ("matrix.det", "matrix"), ("sparse.multiply", "a"), ("sparse.multiply", "b")] {
+            assert!(evaluate_node(kind, &with(port), &none).is_error(), "{kind} {port}");
+        }
+    }
 }
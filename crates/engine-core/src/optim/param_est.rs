@@ -78,6 +78,7 @@ fn simulate(cfg: &ParamEstConfig, params: &[f64]) -> Vec<Vec<f64>> {
         dt: cfg.solver.dt,
         tolerance: cfg.solver.tolerance,
         max_steps: cfg.solver.max_steps,
+        ..Default::default()
     };
 
     let result = solve_rk4(&system, &cfg.y0, &solver_cfg);
@@ -320,7 +321,7 @@ mod tests {
     use super::*;
 
     fn default_solver() -> OdeSolverConfig {
-        OdeSolverConfig { t_start: 0.0, t_end: 5.0, dt: 0.05, tolerance: 1e-6, max_steps: 10_000 }
+        OdeSolverConfig { t_start: 0.0, t_end: 5.0, dt: 0.05, tolerance: 1e-6, max_steps: 10_000, ..Default::default() }
     }
 
     #[test]
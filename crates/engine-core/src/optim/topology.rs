@@ -18,6 +18,8 @@
 //! Q4 bilinear quadrilateral elements on a regular n_x × n_y mesh.
 //! Plane-stress material: E=1, ν=0.3.
 //! Degrees of freedom: 2 per node = 2(n_x+1)(n_y+1).
+//! The free-DOF stiffness system is solved by sparse Cholesky (minimum degree
+//! ordering, symbolic analysis shared across OC iterations).
 //!
 //! ## Boundary conditions
 //!
@@ -33,6 +35,8 @@
 //!
 //! Returns a Table with columns `["x", "y", "density"]` — one row per element.
 
+use crate::sparse::CooMatrix;
+use crate::sparse_direct::{FillOrdering, SymbolicCholesky};
 use crate::types::Value;
 
 // ── Constants ────────────────────────────────────────────────────────────────
@@ -116,6 +120,10 @@ pub fn simp_topology(config: &TopoConfig) -> Value {
     // Free DOF list
     let free: Vec<usize> = (0..n_dof).filter(|&d| !fixed[d]).collect();
     let n_free = free.len();
+    let mut free_index: Vec<Option<usize>> = vec![None; n_dof];
+    for (i, &d) in free.iter().enumerate() {
+        free_index[d] = Some(i);
+    }
 
     // ── Force vector (point load at mid-right, y-direction) ───────────────
     let mut f_full = vec![0.0_f64; n_dof];
@@ -161,26 +169,38 @@ pub fn simp_topology(config: &TopoConfig) -> Value {
     let mut rho: Vec<f64> = vec![config.vol_frac; n_el];
 
     // ── Main OC loop ───────────────────────────────────────────────────────
+    // The stiffness pattern is fixed, so the ordering and elimination tree are
+    // computed once and only the numeric factorization repeats.
+    let mut symbolic: Option<SymbolicCholesky> = None;
     for _iter in 0..config.max_iter {
         // Assemble global stiffness (free DOF only)
-        let mut k_free = vec![0.0_f64; n_free * n_free];
+        let mut k_free = CooMatrix::with_capacity(n_free, n_free, 64 * n_el);
 
         for (e, dofs) in edof.iter().enumerate() {
             let rho_e = rho[e].max(RHO_MIN);
             let scale = rho_e.powf(PENAL);
             for (li, &di) in dofs.iter().enumerate() {
-                let fi = free.iter().position(|&d| d == di);
-                let Some(fi) = fi else { continue };
+                let Some(fi) = free_index[di] else { continue };
                 for (lj, &dj) in dofs.iter().enumerate() {
-                    let fj = free.iter().position(|&d| d == dj);
-                    let Some(fj) = fj else { continue };
-                    k_free[fi * n_free + fj] += scale * ke[li][lj];
+                    let Some(fj) = free_index[dj] else { continue };
+                    k_free.push(fi, fj, scale * ke[li][lj]);
                 }
             }
         }
+        let k_free = k_free.to_csr().to_csc();
 
-        // Solve K_free * u_free = f_free via conjugate gradient
-        let u_free = conjugate_gradient(&k_free, &f_free, n_free, 1e-8, 2 * n_free);
+        // Solve K_free * u_free = f_free by sparse Cholesky
+        if symbolic.is_none() {
+            match SymbolicCholesky::analyze(&k_free, FillOrdering::MinimumDegree) {
+                Ok(s) => symbolic = Some(s),
+                Err(e) => return Value::error(format!("topology: {e}")),
+            }
+        }
+        let solved = symbolic.as_ref().expect("analysed above").factor(&k_free).and_then(|f| f.solve(&f_free));
+        let u_free = match solved {
+            Ok(u) => u,
+            Err(e) => return Value::error(format!("topology: stiffness solve failed: {e}")),
+        };
 
         // Reconstruct full displacement
         let mut u = vec![0.0_f64; n_dof];
@@ -297,49 +317,6 @@ fn apply_filter(dc: &[f64], rho: &[f64], filter: &[Vec<(usize, f64)>]) -> Vec<f6
     dc_filt
 }
 
-// ── Conjugate Gradient solver ─────────────────────────────────────────────────
-
-fn conjugate_gradient(
-    a: &[f64],   // n×n row-major
-    b: &[f64],   // n
-    n: usize,
-    tol: f64,
-    max_iter: usize,
-) -> Vec<f64> {
-    let mut x = vec![0.0_f64; n];
-    let mut r: Vec<f64> = b.iter().enumerate().map(|(i, &bi)| {
-        bi - (0..n).map(|j| a[i * n + j] * x[j]).sum::<f64>()
-    }).collect();
-    let mut p = r.clone();
-    let mut r_sq: f64 = r.iter().map(|&ri| ri * ri).sum();
-
-    for _ in 0..max_iter {
-        if r_sq < tol * tol {
-            break;
-        }
-        // Ap
-        let ap: Vec<f64> = (0..n)
-            .map(|i| (0..n).map(|j| a[i * n + j] * p[j]).sum::<f64>())
-            .collect();
-        let p_ap: f64 = p.iter().zip(ap.iter()).map(|(&pi, &api)| pi * api).sum();
-        if p_ap.abs() < 1e-20 {
-            break;
-        }
-        let alpha = r_sq / p_ap;
-        for i in 0..n {
-            x[i] += alpha * p[i];
-            r[i] -= alpha * ap[i];
-        }
-        let r_sq_new: f64 = r.iter().map(|&ri| ri * ri).sum();
-        let beta = r_sq_new / r_sq;
-        r_sq = r_sq_new;
-        for i in 0..n {
-            p[i] = r[i] + beta * p[i];
-        }
-    }
-    x
-}
-
 // ── Q4 element stiffness (8×8) ────────────────────────────────────────────────
 
 /// Compute 8×8 element stiffness matrix for unit square Q4, plane stress E=1, ν=NU.
@@ -0,0 +1,418 @@
+//! Native project container format — pure Rust, no dependencies.
+//!
+//! A project file bundles everything needed to reopen a worksheet offline
+//! without recomputing it: the [`EngineSnapshotV1`], registered datasets,
+//! the last evaluated values, and the engine/contract versions that
+//! produced them. The same reader is used by the desktop app, the CLI and
+//! the Python bindings.
+//!
+//! ## Layout
+//!
+//! ```text
+//! [magic "CSPROJ\0\0"] [format-version:u32le] [header-len:u32le]
+//! [header JSON] [zero padding to 8 bytes]
+//! [dataset columns: f64le, concatenated] [binary datasets: raw bytes, concatenated]
+//! ```
+//!
+//! The JSON header holds the versions, free-form string metadata, the
+//! snapshot, the cached values and `(id, offset, len)` indexes into the two
+//! data sections (f64 elements for numeric datasets, bytes for binary ones).
+//! Datasets are stored as raw binary columns, never as JSON arrays.
+//!
+//! ## Staleness
+//!
+//! Values are only trusted if they were produced by the same engine version
+//! and contract version. Otherwise they are restored but every node is marked
+//! dirty, so the next `evaluate_dirty()` recomputes the whole graph. Cached
+//! values that cannot be decoded (e.g. a NaN scalar, which JSON stores as
+//! `null`) are dropped and their nodes recomputed. Discrete-time block state
+//! is not saved; stateful blocks restart from their initial condition.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::{engine_version, ENGINE_CONTRACT_VERSION};
+use crate::error::{EngineError, ErrorCode};
+use crate::graph::EngineGraph;
+use crate::types::{EngineSnapshotV1, Value};
+use crate::validate;
+
+/// File signature at offset 0.
+pub const PROJECT_MAGIC: &[u8; 8] = b"CSPROJ\0\0";
+/// Container layout version written by this engine.
+pub const PROJECT_FORMAT_VERSION: u32 = 1;
+/// Conventional file extension (without the dot).
+pub const PROJECT_EXTENSION: &str = "csproject";
+
+const PREAMBLE_LEN: usize = 16;
+
+fn err(msg: impl Into<String>) -> EngineError {
+    EngineError::new(ErrorCode::InvalidProject, msg)
+}
+
+// ── Public types ──────────────────────────────────────────────────────────────
+
+/// An in-memory project: snapshot, datasets and cached results.
+#[derive(Debug, Clone)]
+pub struct Project {
+    /// `engine_version()` of the engine that evaluated `values`.
+    pub engine_version: String,
+    /// `ENGINE_CONTRACT_VERSION` of the engine that evaluated `values`.
+    pub contract_version: u32,
+    /// Free-form metadata (title, author, application version, …).
+    pub metadata: BTreeMap<String, String>,
+    pub snapshot: EngineSnapshotV1,
+    /// Last evaluated value per node id (clean nodes only).
+    pub values: HashMap<String, Value>,
+    /// Registered datasets: id → raw f64 data.
+    pub datasets: HashMap<String, Vec<f64>>,
+    /// Registered binary datasets: id → raw bytes.
+    pub blobs: HashMap<String, Vec<u8>>,
+}
+
+/// Outcome of restoring a project into an [`EngineGraph`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreSummary {
+    /// True if the cached values came from a different engine or contract
+    /// version and were marked dirty.
+    pub stale: bool,
+    /// Number of cached values installed.
+    pub cached: usize,
+    /// Number of nodes that need evaluation.
+    pub dirty: usize,
+    pub engine_version: String,
+    pub contract_version: u32,
+    pub metadata: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Header {
+    engine_version: String,
+    contract_version: u32,
+    #[serde(default)]
+    metadata: BTreeMap<String, String>,
+    snapshot: EngineSnapshotV1,
+    #[serde(default)]
+    values: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    datasets: Vec<DatasetEntry>,
+    #[serde(default)]
+    blobs: Vec<DatasetEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DatasetEntry {
+    id: String,
+    offset: usize,
+    len: usize,
+}
+
+// ── Project ───────────────────────────────────────────────────────────────────
+
+impl Project {
+    /// Capture the current state of a graph. Values of dirty nodes are not
+    /// saved, since they may not match the snapshot.
+    pub fn capture(graph: &EngineGraph) -> Self {
+        let values = graph
+            .values()
+            .iter()
+            .filter(|(id, _)| graph.has_node(id) && !graph.is_dirty(id))
+            .map(|(id, v)| (id.clone(), v.clone()))
+            .collect();
+        Self {
+            engine_version: engine_version().to_string(),
+            contract_version: ENGINE_CONTRACT_VERSION,
+            metadata: BTreeMap::new(),
+            snapshot: graph.to_snapshot(),
+            values,
+            datasets: graph.datasets.clone(),
+            blobs: graph.blobs.clone(),
+        }
+    }
+
+    /// Whether the cached values were produced by a different engine.
+    pub fn is_stale(&self) -> bool {
+        self.engine_version != engine_version() || self.contract_version != ENGINE_CONTRACT_VERSION
+    }
+
+    /// Encode to the binary container format.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let (ids, entries, offset) = index(&self.datasets);
+        let (blob_ids, blob_entries, blob_bytes) = index(&self.blobs);
+
+        let header = Header {
+            engine_version: self.engine_version.clone(),
+            contract_version: self.contract_version,
+            metadata: self.metadata.clone(),
+            snapshot: self.snapshot.clone(),
+            values: self
+                .values
+                .iter()
+                .map(|(id, v)| (id.clone(), serde_json::to_value(v).unwrap_or_default()))
+                .collect(),
+            datasets: entries,
+            blobs: blob_entries,
+        };
+        let header_json = serde_json::to_vec(&header).expect("project header serialization");
+
+        let mut out =
+            Vec::with_capacity(PREAMBLE_LEN + header_json.len() + 8 + offset * 8 + blob_bytes);
+        out.extend_from_slice(PROJECT_MAGIC);
+        out.extend_from_slice(&PROJECT_FORMAT_VERSION.to_le_bytes());
+        out.extend_from_slice(&(header_json.len() as u32).to_le_bytes());
+        out.extend_from_slice(&header_json);
+        while out.len() % 8 != 0 {
+            out.push(0);
+        }
+        for id in ids {
+            for x in &self.datasets[id] {
+                out.extend_from_slice(&x.to_le_bytes());
+            }
+        }
+        for id in blob_ids {
+            out.extend_from_slice(&self.blobs[id]);
+        }
+        out
+    }
+
+    /// Decode a project file. Fails on a bad signature, an unknown format
+    /// version, truncation, or a snapshot that does not validate.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EngineError> {
+        if !is_project(bytes) {
+            return Err(err("not a ChainSolve project file (bad signature)"));
+        }
+        if bytes.len() < PREAMBLE_LEN {
+            return Err(err("truncated project header"));
+        }
+        let format = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+        if format == 0 || format > PROJECT_FORMAT_VERSION {
+            return Err(err(format!(
+                "unsupported project format version {} (this engine reads up to {})",
+                format, PROJECT_FORMAT_VERSION
+            )));
+        }
+        let header_len = u32::from_le_bytes(bytes[12..16].try_into().unwrap()) as usize;
+        let header_end = PREAMBLE_LEN
+            .checked_add(header_len)
+            .filter(|&end| end <= bytes.len())
+            .ok_or_else(|| err("truncated project header"))?;
+        let header: Header = serde_json::from_slice(&bytes[PREAMBLE_LEN..header_end])
+            .map_err(|e| err(format!("invalid project header: {}", e)))?;
+        validate::validate(&header.snapshot)?;
+
+        let data_start = header_end.div_ceil(8) * 8;
+        let data = bytes.get(data_start..).unwrap_or(&[]);
+        let mut datasets = HashMap::with_capacity(header.datasets.len());
+        for entry in &header.datasets {
+            let range = entry
+                .offset
+                .checked_add(entry.len)
+                .and_then(|end| Some(entry.offset.checked_mul(8)?..end.checked_mul(8)?))
+                .filter(|r| r.end <= data.len())
+                .ok_or_else(|| err(format!("dataset '{}' extends past end of file", entry.id)))?;
+            let column = data[range]
+                .as_chunks::<8>()
+                .0
+                .iter()
+                .map(|b| f64::from_le_bytes(*b))
+                .collect();
+            datasets.insert(entry.id.clone(), column);
+        }
+
+        let numeric_end = header.datasets.iter().map(|e| (e.offset + e.len) * 8).max().unwrap_or(0);
+        let blob_data = &data[numeric_end..];
+        let mut blobs = HashMap::with_capacity(header.blobs.len());
+        for entry in &header.blobs {
+            let bytes = entry
+                .offset
+                .checked_add(entry.len)
+                .and_then(|end| blob_data.get(entry.offset..end))
+                .ok_or_else(|| err(format!("binary dataset '{}' extends past end of file", entry.id)))?;
+            blobs.insert(entry.id.clone(), bytes.to_vec());
+        }
+
+        let values = header
+            .values
+            .into_iter()
+            .filter_map(|(id, v)| serde_json::from_value::<Value>(v).ok().map(|v| (id, v)))
+            // Malformed CSR is dropped like any undecodable value.
+            .filter(|(_, v)| !matches!(v, Value::SparseMatrix { .. }) || v.as_sparse().is_some())
+            .collect();
+
+        Ok(Self {
+            engine_version: header.engine_version,
+            contract_version: header.contract_version,
+            metadata: header.metadata,
+            snapshot: header.snapshot,
+            values,
+            datasets,
+            blobs,
+        })
+    }
+
+    /// Replace the contents of `graph` with this project.
+    pub fn restore(self, graph: &mut EngineGraph) -> RestoreSummary {
+        let stale = self.is_stale();
+        let cached = self.values.len();
+        graph.datasets = self.datasets;
+        graph.blobs = self.blobs;
+        graph.restore_snapshot(self.snapshot, self.values, stale);
+        RestoreSummary {
+            stale,
+            cached,
+            dirty: graph.dirty_count(),
+            engine_version: self.engine_version,
+            contract_version: self.contract_version,
+            metadata: self.metadata,
+        }
+    }
+}
+
+/// Sorted ids, `(id, offset, len)` entries and total length for one data section.
+fn index<T>(map: &HashMap<String, Vec<T>>) -> (Vec<&String>, Vec<DatasetEntry>, usize) {
+    let mut ids: Vec<&String> = map.keys().collect();
+    ids.sort();
+    let mut offset = 0usize;
+    let mut entries = Vec::with_capacity(ids.len());
+    for id in &ids {
+        let len = map[*id].len();
+        entries.push(DatasetEntry { id: (*id).clone(), offset, len });
+        offset += len;
+    }
+    (ids, entries, offset)
+}
+
+// ── Convenience entry points ──────────────────────────────────────────────────
+
+/// Whether `bytes` starts with the project file signature.
+pub fn is_project(bytes: &[u8]) -> bool {
+    bytes.starts_with(PROJECT_MAGIC)
+}
+
+/// Encode a graph (snapshot, datasets, clean values) as a project file.
+pub fn save_project(graph: &EngineGraph, metadata: BTreeMap<String, String>) -> Vec<u8> {
+    let mut project = Project::capture(graph);
+    project.metadata = metadata;
+    project.to_bytes()
+}
+
+/// Decode a project file into `graph` without evaluating it.
+pub fn open_project(graph: &mut EngineGraph, bytes: &[u8]) -> Result<RestoreSummary, EngineError> {
+    Ok(Project::from_bytes(bytes)?.restore(graph))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SNAPSHOT: &str = r#"{"version":1,"nodes":[
+        {"id":"a","blockType":"number","data":{"value":3}},
+        {"id":"b","blockType":"number","data":{"value":4}},
+        {"id":"c","blockType":"add","data":{}}
+    ],"edges":[
+        {"id":"e1","source":"a","sourceHandle":"out","target":"c","targetHandle":"a"},
+        {"id":"e2","source":"b","sourceHandle":"out","target":"c","targetHandle":"b"}
+    ]}"#;
+
+    fn evaluated_graph() -> EngineGraph {
+        let mut g = EngineGraph::new();
+        crate::run_load_snapshot(&mut g, SNAPSHOT).unwrap();
+        g.register_dataset("ds".into(), vec![1.5, -2.0, f64::NAN]);
+        g.register_blob("file".into(), b"PAR1 raw bytes".to_vec());
+        g
+    }
+
+    fn scalar(g: &EngineGraph, id: &str) -> f64 {
+        match g.values().get(id) {
+            Some(Value::Scalar { value }) => *value,
+            other => panic!("expected scalar at {id}, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn roundtrip_restores_without_recompute() {
+        let g = evaluated_graph();
+        let mut meta = BTreeMap::new();
+        meta.insert("title".to_string(), "demo".to_string());
+        let bytes = save_project(&g, meta);
+        assert!(is_project(&bytes));
+
+        let mut restored = EngineGraph::new();
+        let summary = open_project(&mut restored, &bytes).unwrap();
+        assert!(!summary.stale);
+        assert_eq!(summary.cached, 3);
+        assert_eq!(summary.dirty, 0);
+        assert_eq!(summary.metadata["title"], "demo");
+        assert_eq!(scalar(&restored, "c"), 7.0);
+
+        let ds = &restored.datasets["ds"];
+        assert_eq!(&ds[..2], &[1.5, -2.0]);
+        assert!(ds[2].is_nan());
+        assert_eq!(restored.blobs["file"], b"PAR1 raw bytes");
+
+        let inc = restored.evaluate_dirty();
+        assert_eq!(inc.evaluated_count, 0);
+
+        // Cached values still drive incremental evaluation.
+        restored.set_source_value("a", 10.0);
+        let inc = restored.evaluate_dirty();
+        assert_eq!(scalar(&restored, "c"), 14.0);
+        assert_eq!(inc.evaluated_count, 2);
+    }
+
+    #[test]
+    fn other_engine_version_marks_values_stale() {
+        let mut project = Project::capture(&evaluated_graph());
+        project.engine_version = "0.0.0-old".to_string();
+        let bytes = project.to_bytes();
+
+        let mut restored = EngineGraph::new();
+        let summary = open_project(&mut restored, &bytes).unwrap();
+        assert!(summary.stale);
+        assert_eq!(summary.dirty, 3);
+        // Stale values are readable until re-evaluated.
+        assert_eq!(scalar(&restored, "c"), 7.0);
+        // Sources are recomputed; `c` is pruned because its inputs are unchanged.
+        let inc = restored.evaluate_dirty();
+        assert!(inc.evaluated_count >= 2);
+        assert!(!restored.is_dirty("c"));
+        assert_eq!(scalar(&restored, "c"), 7.0);
+    }
+
+    #[test]
+    fn undecodable_value_is_recomputed_with_dependents() {
+        let mut project = Project::capture(&evaluated_graph());
+        project.values.insert("a".into(), Value::Scalar { value: f64::NAN });
+        let mut restored = EngineGraph::new();
+        let summary = open_project(&mut restored, &project.to_bytes()).unwrap();
+        assert_eq!(summary.cached, 2);
+        assert!(restored.is_dirty("a"));
+        assert!(restored.is_dirty("c"));
+        assert!(!restored.is_dirty("b"));
+
+        // Malformed CSR (column index out of range) is dropped the same way.
+        let mut project = Project::capture(&evaluated_graph());
+        let csr = Value::SparseMatrix { rows: 1, cols: 1, row_ptrs: vec![0, 1], col_indices: vec![5], values: vec![1.0] };
+        project.values.insert("a".into(), csr);
+        let mut restored = EngineGraph::new();
+        assert_eq!(open_project(&mut restored, &project.to_bytes()).unwrap().cached, 2);
+        assert!(restored.is_dirty("a"));
+    }
+
+    #[test]
+    fn rejects_corrupt_files() {
+        let bytes = save_project(&evaluated_graph(), BTreeMap::new());
+        assert!(Project::from_bytes(b"{\"version\":1}").is_err());
+        assert!(Project::from_bytes(&bytes[..20]).is_err());
+        // Dataset section cut short.
+        let e = Project::from_bytes(&bytes[..bytes.len() - 8]).unwrap_err();
+        assert_eq!(e.code, ErrorCode::InvalidProject);
+        // Future format version.
+        let mut future = bytes.clone();
+        future[8..12].copy_from_slice(&2u32.to_le_bytes());
+        assert!(Project::from_bytes(&future).is_err());
+    }
+}
@@ -0,0 +1,670 @@
+//! `rational` — arbitrary-precision integers and exact rationals.
+//!
+//! [`BigInt`] stores a sign and little-endian base-2³² limbs; division is
+//! Knuth's Algorithm D. [`Rational`] keeps `num/den` in lowest terms with a
+//! positive denominator, so equal values compare and hash equal. These are
+//! the exact coefficient domain for the CAS polynomials
+//! ([`crate::symbolic::MultiPoly`]); values convert to `f64` only for
+//! evaluation and display. Symbolic expressions hold `f64` constants, so a
+//! rational becomes an expression only while its numerator and denominator
+//! stay below 2^53 (see [`crate::symbolic::Coefficient::to_expr`]).
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
+
+// ── Magnitude arithmetic ────────────────────────────────────────────────────
+
+fn trim(mag: &mut Vec<u32>) {
+    while mag.last() == Some(&0) {
+        mag.pop();
+    }
+}
+
+fn cmp_mag(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut out = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &x) in long.iter().enumerate() {
+        let s = x as u64 + short.get(i).copied().unwrap_or(0) as u64 + carry;
+        out.push(s as u32);
+        carry = s >> 32;
+    }
+    if carry > 0 {
+        out.push(carry as u32);
+    }
+    out
+}
+
+/// `a − b` for `a ≥ b`.
+fn sub_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut out = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &x) in a.iter().enumerate() {
+        let d = x as i64 - b.get(i).copied().unwrap_or(0) as i64 - borrow;
+        out.push(d as u32);
+        borrow = (d < 0) as i64;
+    }
+    trim(&mut out);
+    out
+}
+
+fn mul_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut out = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let t = x as u64 * y as u64 + out[i + j] as u64 + carry;
+            out[i + j] = t as u32;
+            carry = t >> 32;
+        }
+        out[i + b.len()] = carry as u32;
+    }
+    trim(&mut out);
+    out
+}
+
+fn divrem_small(a: &[u32], d: u32) -> (Vec<u32>, u32) {
+    let mut q = vec![0u32; a.len()];
+    let mut r = 0u64;
+    for i in (0..a.len()).rev() {
+        let cur = (r << 32) | a[i] as u64;
+        q[i] = (cur / d as u64) as u32;
+        r = cur % d as u64;
+    }
+    trim(&mut q);
+    (q, r as u32)
+}
+
+fn shl_bits(a: &[u32], s: u32) -> Vec<u32> {
+    let mut out = Vec::with_capacity(a.len() + 1);
+    let mut carry = 0u32;
+    for &x in a {
+        out.push(if s == 0 { x } else { (x << s) | carry });
+        carry = if s == 0 { 0 } else { x >> (32 - s) };
+    }
+    out.push(carry);
+    out
+}
+
+/// Quotient and remainder of magnitudes (Knuth, TAOCP vol. 2, 4.3.1 D).
+fn divrem_mag(u: &[u32], v: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    assert!(!v.is_empty(), "division by zero");
+    if cmp_mag(u, v) == Ordering::Less {
+        return (Vec::new(), u.to_vec());
+    }
+    if v.len() == 1 {
+        let (q, r) = divrem_small(u, v[0]);
+        return (q, if r == 0 { Vec::new() } else { vec![r] });
+    }
+    let base = 1u64 << 32;
+    let s = v[v.len() - 1].leading_zeros();
+    let mut vn = shl_bits(v, s);
+    vn.pop();
+    let mut un = shl_bits(u, s);
+    let n = vn.len();
+    let m = u.len() - n;
+    let mut q = vec![0u32; m + 1];
+    for j in (0..=m).rev() {
+        let num = ((un[j + n] as u64) << 32) | un[j + n - 1] as u64;
+        let mut qhat = num / vn[n - 1] as u64;
+        let mut rhat = num % vn[n - 1] as u64;
+        while qhat >= base || qhat * vn[n - 2] as u64 > ((rhat << 32) | un[j + n - 2] as u64) {
+            qhat -= 1;
+            rhat += vn[n - 1] as u64;
+            if rhat >= base {
+                break;
+            }
+        }
+        // un[j..=j+n] −= qhat · vn
+        let mut k = 0i64;
+        for i in 0..n {
+            let p = qhat * vn[i] as u64;
+            let t = un[i + j] as i64 - k - (p & 0xFFFF_FFFF) as i64;
+            un[i + j] = t as u32;
+            k = (p >> 32) as i64 - (t >> 32);
+        }
+        let t = un[j + n] as i64 - k;
+        un[j + n] = t as u32;
+        if t < 0 {
+            // qhat was one too large: add vn back.
+            qhat -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let s = un[i + j] as u64 + vn[i] as u64 + carry;
+                un[i + j] = s as u32;
+                carry = s >> 32;
+            }
+            un[j + n] = un[j + n].wrapping_add(carry as u32);
+        }
+        q[j] = qhat as u32;
+    }
+    let mut r: Vec<u32> = (0..n)
+        .map(|i| if s == 0 { un[i] } else { (un[i] >> s) | (un[i + 1] << (32 - s)) })
+        .collect();
+    trim(&mut q);
+    trim(&mut r);
+    (q, r)
+}
+
+fn bit_length(mag: &[u32]) -> u64 {
+    mag.last().map_or(0, |&top| (mag.len() as u64 - 1) * 32 + (32 - top.leading_zeros()) as u64)
+}
+
+fn shr_mag(a: &[u32], bits: u64) -> Vec<u32> {
+    let (limbs, s) = ((bits / 32) as usize, (bits % 32) as u32);
+    if limbs >= a.len() {
+        return Vec::new();
+    }
+    let a = &a[limbs..];
+    let mut out: Vec<u32> = (0..a.len())
+        .map(|i| if s == 0 { a[i] } else { (a[i] >> s) | (a.get(i + 1).copied().unwrap_or(0) << (32 - s)) })
+        .collect();
+    trim(&mut out);
+    out
+}
+
+// ── BigInt ──────────────────────────────────────────────────────────────────
+
+/// An arbitrary-precision signed integer.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    /// Little-endian limbs without trailing zeros; zero is empty.
+    mag: Vec<u32>,
+}
+
+impl BigInt {
+    fn from_parts(negative: bool, mut mag: Vec<u32>) -> Self {
+        trim(&mut mag);
+        BigInt { negative: negative && !mag.is_empty(), mag }
+    }
+
+    pub fn zero() -> Self {
+        BigInt::default()
+    }
+
+    pub fn one() -> Self {
+        BigInt::from(1i64)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mag.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt { negative: false, mag: self.mag.clone() }
+    }
+
+    /// Truncating division: `self = q·other + r` with `r` taking the sign
+    /// of `self`. Panics if `other` is zero.
+    pub fn div_rem(&self, other: &BigInt) -> (BigInt, BigInt) {
+        let (q, r) = divrem_mag(&self.mag, &other.mag);
+        (BigInt::from_parts(self.negative != other.negative, q), BigInt::from_parts(self.negative, r))
+    }
+
+    /// Non-negative greatest common divisor (`gcd(0, 0) = 0`).
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let (mut a, mut b) = (self.mag.clone(), other.mag.clone());
+        while !b.is_empty() {
+            let (_, r) = divrem_mag(&a, &b);
+            a = std::mem::replace(&mut b, r);
+        }
+        BigInt::from_parts(false, a)
+    }
+
+    pub fn pow(&self, mut exp: u32) -> BigInt {
+        let (mut base, mut acc) = (self.clone(), BigInt::one());
+        while exp > 0 {
+            if exp & 1 == 1 {
+                acc = &acc * &base;
+            }
+            base = &base * &base;
+            exp >>= 1;
+        }
+        acc
+    }
+
+    /// Number of significant bits of the magnitude.
+    pub fn bits(&self) -> u64 {
+        bit_length(&self.mag)
+    }
+
+    /// Nearest `f64` (±∞ beyond the `f64` range).
+    pub fn to_f64(&self) -> f64 {
+        let bits = self.bits();
+        // Keep the top 64 bits, then scale by the dropped power of two.
+        let shift = bits.saturating_sub(64);
+        let top = shr_mag(&self.mag, shift);
+        let m = top.iter().rev().fold(0u64, |acc, &l| (acc << 32) | l as u64) as f64;
+        let v = if shift > 2000 { f64::INFINITY } else { m * 2f64.powi(shift as i32) };
+        if self.negative { -v } else { v }
+    }
+
+    /// The magnitude shifted right by `bits`, keeping the sign.
+    fn shr(&self, bits: u64) -> BigInt {
+        BigInt::from_parts(self.negative, shr_mag(&self.mag, bits))
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(v: i64) -> Self {
+        let m = v.unsigned_abs();
+        BigInt::from_parts(v < 0, vec![m as u32, (m >> 32) as u32])
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = String;
+
+    /// Decimal digits with an optional sign.
+    fn from_str(s: &str) -> Result<Self, String> {
+        let t = s.trim();
+        let (negative, digits) = match t.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, t.strip_prefix('+').unwrap_or(t)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("invalid integer '{s}'"));
+        }
+        let mut mag: Vec<u32> = Vec::new();
+        for chunk in digits.as_bytes().chunks(9) {
+            let scale = 10u32.pow(chunk.len() as u32);
+            let value = chunk.iter().fold(0u32, |acc, b| acc * 10 + (b - b'0') as u32);
+            // mag = mag·scale + value
+            let mut carry = value as u64;
+            for limb in mag.iter_mut() {
+                let t = *limb as u64 * scale as u64 + carry;
+                *limb = t as u32;
+                carry = t >> 32;
+            }
+            if carry > 0 {
+                mag.push(carry as u32);
+            }
+        }
+        Ok(BigInt::from_parts(negative, mag))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.mag.is_empty() {
+            return write!(f, "0");
+        }
+        let mut chunks = Vec::new();
+        let mut rest = self.mag.clone();
+        while !rest.is_empty() {
+            let (q, r) = divrem_small(&rest, 1_000_000_000);
+            chunks.push(r);
+            rest = q;
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", chunks.pop().unwrap_or(0))?;
+        for c in chunks.iter().rev() {
+            write!(f, "{c:09}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_mag(&self.mag, &other.mag),
+            (true, true) => cmp_mag(&other.mag, &self.mag),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_mag(&self.mag, &other.mag));
+        }
+        match cmp_mag(&self.mag, &other.mag) {
+            Ordering::Less => BigInt::from_parts(other.negative, sub_mag(&other.mag, &self.mag)),
+            _ => BigInt::from_parts(self.negative, sub_mag(&self.mag, &other.mag)),
+        }
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.mag.clone())
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(self.negative != other.negative, mul_mag(&self.mag, &other.mag))
+    }
+}
+
+// ── Rational ────────────────────────────────────────────────────────────────
+
+/// Largest decimal exponent accepted when parsing, well past `f64` range.
+const MAX_DECIMAL_EXPONENT: i32 = 4096;
+
+/// An exact rational number in lowest terms with a positive denominator.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: BigInt,
+    den: BigInt,
+}
+
+impl Rational {
+    /// `num / den` reduced to lowest terms. Panics if `den` is zero.
+    pub fn new(num: BigInt, den: BigInt) -> Self {
+        assert!(!den.is_zero(), "rational with zero denominator");
+        let g = num.gcd(&den);
+        let (mut num, mut den) = if g.mag == [1] { (num, den) } else { (num.div_rem(&g).0, den.div_rem(&g).0) };
+        if den.negative {
+            num = -&num;
+            den = -&den;
+        }
+        Rational { num, den }
+    }
+
+    pub fn zero() -> Self {
+        Rational::from(0)
+    }
+
+    pub fn one() -> Self {
+        Rational::from(1)
+    }
+
+    pub fn numer(&self) -> &BigInt {
+        &self.num
+    }
+
+    pub fn denom(&self) -> &BigInt {
+        &self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num.is_zero()
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den.mag == [1]
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.num.negative
+    }
+
+    pub fn abs(&self) -> Rational {
+        Rational { num: self.num.abs(), den: self.den.clone() }
+    }
+
+    /// `1 / self`, or `None` for zero.
+    pub fn recip(&self) -> Option<Rational> {
+        (!self.is_zero()).then(|| Rational::new(self.den.clone(), self.num.clone()))
+    }
+
+    pub fn checked_div(&self, other: &Rational) -> Option<Rational> {
+        other.recip().map(|r| self * &r)
+    }
+
+    /// Integer power; `None` for `0^(negative)`.
+    pub fn pow(&self, exp: i32) -> Option<Rational> {
+        let base = if exp < 0 { self.recip()? } else { self.clone() };
+        let e = exp.unsigned_abs();
+        Some(Rational { num: base.num.pow(e), den: base.den.pow(e) })
+    }
+
+    /// The decimal value `v` is written as: the shortest digits that round
+    /// trip, so `0.1` becomes `1/10` rather than its binary expansion.
+    /// `None` for infinities and NaN.
+    pub fn from_f64(v: f64) -> Option<Rational> {
+        if !v.is_finite() {
+            return None;
+        }
+        format!("{v:e}").parse().ok()
+    }
+
+    /// Nearest `f64` (by scaling both parts into range first).
+    pub fn to_f64(&self) -> f64 {
+        let shift = self.num.bits().max(self.den.bits()).saturating_sub(1000);
+        let den = self.den.shr(shift).to_f64();
+        if den == 0.0 {
+            return if self.num.negative { f64::NEG_INFINITY } else { f64::INFINITY };
+        }
+        self.num.shr(shift).to_f64() / den
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(v: i64) -> Self {
+        Rational { num: BigInt::from(v), den: BigInt::one() }
+    }
+}
+
+impl From<BigInt> for Rational {
+    fn from(num: BigInt) -> Self {
+        Rational { num, den: BigInt::one() }
+    }
+}
+
+impl FromStr for Rational {
+    type Err = String;
+
+    /// `p/q`, or a decimal such as `-2.5`, `1e-3` or `6.02e23`.
+    fn from_str(s: &str) -> Result<Self, String> {
+        let t = s.trim();
+        if let Some((p, q)) = t.split_once('/') {
+            let (p, q): (BigInt, BigInt) = (p.parse()?, q.parse()?);
+            if q.is_zero() {
+                return Err(format!("zero denominator in '{s}'"));
+            }
+            return Ok(Rational::new(p, q));
+        }
+        let (mantissa, exp) = match t.find(['e', 'E']) {
+            Some(i) => (&t[..i], t[i + 1..].parse::<i32>().map_err(|_| format!("invalid number '{s}'"))?),
+            None => (t, 0),
+        };
+        let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if int_part.trim_start_matches(['+', '-']).is_empty() && frac_part.is_empty() {
+            return Err(format!("invalid number '{s}'"));
+        }
+        let digits: BigInt = format!("{int_part}{frac_part}").parse().map_err(|_| format!("invalid number '{s}'"))?;
+        let scale = exp - frac_part.len() as i32;
+        if scale.abs() > MAX_DECIMAL_EXPONENT {
+            return Err(format!("exponent out of range in '{s}'"));
+        }
+        let ten = Rational::from(10);
+        let factor = ten.pow(scale).ok_or_else(|| format!("invalid number '{s}'"))?;
+        Ok(&Rational::from(digits) * &factor)
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() { write!(f, "{}", self.num) } else { write!(f, "{}/{}", self.num, self.den) }
+    }
+}
+
+impl fmt::Debug for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.num * &other.den).cmp(&(&other.num * &self.den))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &Rational {
+    type Output = Rational;
+    fn add(self, other: &Rational) -> Rational {
+        Rational::new(&(&self.num * &other.den) + &(&other.num * &self.den), &self.den * &other.den)
+    }
+}
+
+impl Sub for &Rational {
+    type Output = Rational;
+    fn sub(self, other: &Rational) -> Rational {
+        self + &-other
+    }
+}
+
+impl Mul for &Rational {
+    type Output = Rational;
+    fn mul(self, other: &Rational) -> Rational {
+        Rational::new(&self.num * &other.num, &self.den * &other.den)
+    }
+}
+
+impl Div for &Rational {
+    type Output = Rational;
+    /// Panics on division by zero; see [`Rational::checked_div`].
+    fn div(self, other: &Rational) -> Rational {
+        self.checked_div(other).expect("rational division by zero")
+    }
+}
+
+impl Neg for &Rational {
+    type Output = Rational;
+    fn neg(self) -> Rational {
+        Rational { num: -&self.num, den: self.den.clone() }
+    }
+}
+
+macro_rules! forward_owned {
+    ($t:ty: $($tr:ident $m:ident),*) => {$(
+        impl $tr for $t {
+            type Output = $t;
+            fn $m(self, other: $t) -> $t {
+                (&self).$m(&other)
+            }
+        }
+    )*};
+}
+forward_owned!(BigInt: Add add, Sub sub, Mul mul);
+forward_owned!(Rational: Add add, Sub sub, Mul mul, Div div);
+
+impl Neg for BigInt {
+    type Output = BigInt;
+    fn neg(self) -> BigInt {
+        -&self
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+    fn neg(self) -> Rational {
+        -&self
+    }
+}
+
+// ── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    fn q(s: &str) -> Rational {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn bigint_arithmetic_matches_i128() {
+        let samples: [i128; 8] = [0, 1, -7, 4_294_967_295, -4_294_967_296, 123_456_789_012_345, -(1 << 62) + 3, 1 << 63];
+        for &a in &samples {
+            for &b in &samples {
+                let (x, y) = (big(&a.to_string()), big(&b.to_string()));
+                assert_eq!((&x + &y).to_string(), (a + b).to_string());
+                assert_eq!((&x - &y).to_string(), (a - b).to_string());
+                assert_eq!((&x * &y).to_string(), (a * b).to_string());
+                assert_eq!(x.cmp(&y), a.cmp(&b));
+                if b != 0 {
+                    let (qq, r) = x.div_rem(&y);
+                    assert_eq!((qq.to_string(), r.to_string()), ((a / b).to_string(), (a % b).to_string()), "{a} / {b}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn long_division_and_gcd() {
+        // (2^200 + 12345)·(3^90 − 1) divided back out, exercising Algorithm D.
+        let a = &BigInt::from(2).pow(200) + &BigInt::from(12345);
+        let b = &BigInt::from(3).pow(90) - &BigInt::one();
+        let r = big("98765432109876543210");
+        let n = &(&a * &b) + &r;
+        let (qq, rr) = n.div_rem(&b);
+        assert_eq!((qq, rr), (a.clone(), r));
+        let g = BigInt::from(6).pow(40).gcd(&BigInt::from(10).pow(30));
+        assert_eq!(g, BigInt::from(2).pow(30));
+        assert_eq!(big("-000123").to_string(), "-123");
+        assert!((BigInt::from(10).pow(300).to_f64() - 1e300).abs() / 1e300 < 1e-15);
+        assert!("12a".parse::<BigInt>().is_err());
+    }
+
+    #[test]
+    fn rationals_are_exact() {
+        assert_eq!(q("0.1") + q("0.2"), q("3/10"));
+        assert_eq!(Rational::from_f64(0.1).unwrap(), q("1/10"));
+        assert_eq!(q("-6/-4").to_string(), "3/2");
+        assert_eq!(q("2.5e-3"), q("1/400"));
+        assert_eq!(q("1/3") * q("3"), Rational::one());
+        assert_eq!(q("-2/3").pow(-3).unwrap(), q("-27/8"));
+        assert!(Rational::zero().recip().is_none());
+        assert!(q("1/3") < q("0.34") && q("-1/2") < q("-1/3"));
+        assert_eq!(q("1/3").to_f64(), 1.0 / 3.0);
+        let huge = Rational::new(&BigInt::from(10).pow(400) + &BigInt::one(), &BigInt::from(10).pow(400) * &BigInt::from(3));
+        assert!((huge.to_f64() - 1.0 / 3.0).abs() < 1e-15);
+        assert!("1e99999".parse::<Rational>().is_err());
+        assert!("1/0".parse::<Rational>().is_err() && "".parse::<Rational>().is_err());
+    }
+}
@@ -0,0 +1,647 @@
+//! Discrete-time simulation loop over a persistent [`EngineGraph`].
+//!
+//! # Model
+//!
+//! A simulation advances a clock `t_0, t_1, …` and evaluates the graph once
+//! per step. Between steps only the clock and state nodes change, so every
+//! step after the first is an incremental `evaluate_dirty()` that touches just
+//! the downstream cone of those nodes.
+//!
+//! - **Clock** — either a fixed step (`tStart`, `dt`, `steps`) or an explicit,
+//!   possibly non-uniform list of `times`. The current `t` (and optionally the
+//!   step size `dt`) are written into source nodes named by `timeNode` /
+//!   `dtNode` before each step.
+//! - **State bindings** — `{ node, source }` pairs. After step `k` the scalar
+//!   output of `source` is written into the source node `node`, so it is read
+//!   back at step `k + 1` (a unit delay z⁻¹) without any block in the graph.
+//! - **Stateful blocks** — `ctrl.unit_delay`, `ctrl.discrete_integrator`, … (see
+//!   [`crate::discrete`]) run with the clock's `dt` as their sample time and
+//!   have their state committed after every step.
+//! - **Recorded channels** — node outputs sampled after every step into one
+//!   `Value::Table` (`t` first, then one column per scalar or vector element).
+//! - **Convergence** — optional early stop once the largest step-to-step change
+//!   of the selected nodes drops below a tolerance.
+//!
+//! # Entry point
+//!
+//! [`run_simulation`] drives an already-loaded graph. The WASM export
+//! `engine_wasm::run_simulation` wraps it via [`crate::run_simulation`].
+
+use crate::error::{EngineError, ErrorCode};
+use crate::graph::{EngineGraph, EvalSignal};
+use crate::types::{Diagnostic, Value};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+// ── Configuration ────────────────────────────────────────────────────
+
+/// Simulation run configuration (JSON, camelCase).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationConfig {
+    /// Clock start time (fixed-step clock).
+    #[serde(default)]
+    pub t_start: f64,
+    /// Step size (fixed-step clock).
+    #[serde(default = "default_dt")]
+    pub dt: f64,
+    /// Number of steps per cycle (fixed-step clock).
+    #[serde(default = "default_steps")]
+    pub steps: usize,
+    /// Explicit, strictly increasing time points (variable-step clock).
+    /// Overrides `tStart` / `dt` / `steps` when present.
+    #[serde(default)]
+    pub times: Option<Vec<f64>>,
+    /// Source node that receives the current time before each step.
+    #[serde(default)]
+    pub time_node: Option<String>,
+    /// Source node that receives the current step size before each step.
+    #[serde(default)]
+    pub dt_node: Option<String>,
+    /// State feedback bindings (previous-step output → next-step input).
+    #[serde(default)]
+    pub states: Vec<StateBinding>,
+    /// Nodes whose outputs are recorded after every step.
+    #[serde(default)]
+    pub record: Vec<String>,
+    /// Optional early-stop criterion.
+    #[serde(default)]
+    pub convergence: Option<ConvergenceCheck>,
+    /// Number of times the clock schedule is replayed. State carries over
+    /// between cycles.
+    #[serde(default = "default_cycles")]
+    pub cycles: usize,
+    /// Report progress every N steps.
+    #[serde(default = "default_progress_every")]
+    pub progress_every: usize,
+}
+
+fn default_dt() -> f64 {
+    0.01
+}
+fn default_steps() -> usize {
+    100
+}
+fn default_cycles() -> usize {
+    1
+}
+fn default_progress_every() -> usize {
+    10
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            t_start: 0.0,
+            dt: default_dt(),
+            steps: default_steps(),
+            times: None,
+            time_node: None,
+            dt_node: None,
+            states: Vec::new(),
+            record: Vec::new(),
+            convergence: None,
+            cycles: default_cycles(),
+            progress_every: default_progress_every(),
+        }
+    }
+}
+
+/// Previous-step output of `source` feeds source node `node` on the next step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateBinding {
+    /// Source node (number/slider) that holds the state value.
+    pub node: String,
+    /// Node whose scalar output becomes the next state value.
+    pub source: String,
+    /// Initial state written before the first step (defaults to the node's
+    /// current value).
+    #[serde(default)]
+    pub initial: Option<f64>,
+}
+
+/// Stop once `max |y_k − y_{k−1}| < tolerance` over the selected nodes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvergenceCheck {
+    /// Nodes to monitor. Empty = all recorded channels, or every
+    /// scalar-valued node when nothing is recorded.
+    #[serde(default)]
+    pub nodes: Vec<String>,
+    pub tolerance: f64,
+    /// Do not stop before this many steps.
+    #[serde(default)]
+    pub min_steps: usize,
+}
+
+/// Progress report passed to the callback.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimProgress {
+    /// Steps completed in the current cycle.
+    pub step: usize,
+    pub total_steps: usize,
+    pub cycle: usize,
+    pub total_cycles: usize,
+    /// Clock value of the last completed step.
+    pub t: f64,
+}
+
+/// Outcome of a simulation run.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationResult {
+    /// Recorded channels: `t` (and `cycle` when cycles > 1) then one column
+    /// per scalar channel or vector element (`node[i]`).
+    pub table: Value,
+    /// Total steps executed across all cycles.
+    pub steps: usize,
+    /// Cycles started.
+    pub cycles: usize,
+    pub converged: bool,
+    /// True if the progress callback aborted the run.
+    pub aborted: bool,
+    /// Total node evaluations across all steps (incremental work measure).
+    pub evaluated_nodes: usize,
+    /// Output of every node after the last step.
+    pub final_values: HashMap<String, Value>,
+    /// Deduplicated diagnostics (one per node + code) from all steps.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+// ── Clock ────────────────────────────────────────────────────────────
+
+impl SimulationConfig {
+    /// Resolve the clock schedule into `(t_k, dt_k)` pairs.
+    ///
+    /// For an explicit schedule `dt_k = t_{k+1} − t_k`, with the last step
+    /// reusing the previous interval.
+    pub fn time_points(&self) -> Result<Vec<(f64, f64)>, EngineError> {
+        match &self.times {
+            Some(times) => {
+                if times.is_empty() {
+                    return Err(sim_err("'times' must contain at least one point"));
+                }
+                if times.iter().any(|t| !t.is_finite()) {
+                    return Err(sim_err("'times' must be finite"));
+                }
+                if times.windows(2).any(|w| w[1] <= w[0]) {
+                    return Err(sim_err("'times' must be strictly increasing"));
+                }
+                let n = times.len();
+                Ok((0..n)
+                    .map(|k| {
+                        let dt = if k + 1 < n {
+                            times[k + 1] - times[k]
+                        } else if k > 0 {
+                            times[k] - times[k - 1]
+                        } else {
+                            self.dt
+                        };
+                        (times[k], dt)
+                    })
+                    .collect())
+            }
+            None => {
+                if !(self.dt.is_finite() && self.dt > 0.0) {
+                    return Err(sim_err("'dt' must be a positive finite number"));
+                }
+                Ok((0..self.steps)
+                    .map(|k| (self.t_start + k as f64 * self.dt, self.dt))
+                    .collect())
+            }
+        }
+    }
+}
+
+fn sim_err(msg: impl Into<String>) -> EngineError {
+    EngineError::new(ErrorCode::InvalidSimConfig, msg)
+}
+
+// ── Recording helpers ───────────────────────────────────────────────
+
+/// Flatten a node value into recordable channel samples.
+fn channel_samples(v: Option<&Value>) -> Vec<f64> {
+    match v {
+        Some(Value::Scalar { value }) => vec![*value],
+        Some(Value::HighPrecision { approx, .. }) => vec![*approx],
+        Some(Value::Vector { value }) => value.clone(),
+        _ => vec![f64::NAN],
+    }
+}
+
+fn channel_columns(node_id: &str, width: usize, v: Option<&Value>) -> Vec<String> {
+    match v {
+        Some(Value::Vector { .. }) => (0..width).map(|i| format!("{}[{}]", node_id, i)).collect(),
+        _ => vec![node_id.to_string()],
+    }
+}
+
+fn max_abs_delta(prev: &[f64], curr: &[f64]) -> f64 {
+    if prev.len() != curr.len() {
+        return f64::INFINITY;
+    }
+    prev.iter()
+        .zip(curr)
+        .map(|(a, b)| (b - a).abs())
+        .fold(0.0, |m, d| if d.is_nan() { f64::INFINITY } else { m.max(d) })
+}
+
+// ── Main loop ───────────────────────────────────────────────────────
+
+/// Run a discrete-time simulation on an already-loaded graph.
+///
+/// The callback is invoked every `progressEvery` steps and at the end of each
+/// cycle; returning [`EvalSignal::Abort`] stops the run and returns the
+/// partial result with `aborted = true`.
+pub fn run_simulation<F>(
+    graph: &mut EngineGraph,
+    config: &SimulationConfig,
+    mut on_progress: F,
+) -> Result<SimulationResult, EngineError>
+where
+    F: FnMut(&SimProgress) -> EvalSignal,
+{
+    let schedule = config.time_points()?;
+
+    // Every referenced node must exist.
+    let referenced = config
+        .time_node
+        .iter()
+        .chain(config.dt_node.iter())
+        .chain(config.states.iter().flat_map(|s| [&s.node, &s.source]))
+        .chain(config.record.iter())
+        .chain(config.convergence.iter().flat_map(|c| c.nodes.iter()));
+    for id in referenced {
+        if !graph.has_node(id) {
+            return Err(sim_err(format!("node '{}' not found in graph", id)));
+        }
+    }
+
+    // Clock and state values are written into source nodes; a computed node
+    // would overwrite them with its own output on the next evaluation.
+    let driven = config
+        .time_node
+        .iter()
+        .chain(config.dt_node.iter())
+        .chain(config.states.iter().map(|s| &s.node));
+    for id in driven {
+        if !graph.is_source_node(id) {
+            return Err(sim_err(format!(
+                "node '{}' is not a source node (number, slider, …) and cannot be driven by the simulation",
+                id
+            )));
+        }
+    }
+
+    for s in &config.states {
+        if let Some(init) = s.initial {
+            graph.set_source_value(&s.node, init);
+        }
+    }
+
+    let total_cycles = config.cycles.max(1);
+    let progress_every = config.progress_every.max(1);
+    let record_cycle = total_cycles > 1;
+
+    let mut columns: Vec<String> = Vec::new();
+    let mut widths: Vec<usize> = Vec::new();
+    let mut rows: Vec<Vec<f64>> = Vec::new();
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    let mut seen_diags: HashSet<(Option<String>, String)> = HashSet::new();
+    let mut prev_monitor: Option<Vec<f64>> = None;
+    let mut steps_done = 0usize;
+    let mut cycles_started = 0usize;
+    let mut evaluated_nodes = 0usize;
+    let mut converged = false;
+    let mut aborted = false;
+
+    'cycles: for cycle in 0..total_cycles {
+        cycles_started += 1;
+        for (k, &(t, dt)) in schedule.iter().enumerate() {
+            if let Some(id) = &config.time_node {
+                graph.set_source_value(id, t);
+            }
+            if let Some(id) = &config.dt_node {
+                graph.set_source_value(id, dt);
+            }
+            graph.set_step_size(dt);
+
+            let inc = graph.evaluate_dirty();
+            evaluated_nodes += inc.evaluated_count;
+            steps_done += 1;
+            for d in inc.diagnostics {
+                if seen_diags.insert((d.node_id.clone(), d.code.clone())) {
+                    diagnostics.push(d);
+                }
+            }
+
+            // Record channels; the column layout is fixed by the first step.
+            let values = graph.values();
+            let mut row = vec![t];
+            if record_cycle {
+                row.push(cycle as f64);
+            }
+            if columns.is_empty() {
+                columns.push("t".to_string());
+                if record_cycle {
+                    columns.push("cycle".to_string());
+                }
+                for id in &config.record {
+                    let samples = channel_samples(values.get(id));
+                    columns.extend(channel_columns(id, samples.len(), values.get(id)));
+                    widths.push(samples.len());
+                }
+            }
+            for (id, &w) in config.record.iter().zip(&widths) {
+                let mut samples = channel_samples(values.get(id));
+                samples.resize(w, f64::NAN);
+                row.extend(samples);
+            }
+            rows.push(row);
+
+            // Convergence on the monitored nodes.
+            if let Some(conv) = &config.convergence {
+                let monitor: Vec<f64> = if !conv.nodes.is_empty() {
+                    conv.nodes.iter().flat_map(|id| channel_samples(values.get(id))).collect()
+                } else if !config.record.is_empty() {
+                    config.record.iter().flat_map(|id| channel_samples(values.get(id))).collect()
+                } else {
+                    // The clock nodes change every step by design.
+                    let clock = |id: &String| {
+                        config.time_node.as_ref() == Some(id) || config.dt_node.as_ref() == Some(id)
+                    };
+                    let mut ids: Vec<&String> = values
+                        .iter()
+                        .filter(|(id, v)| matches!(v, Value::Scalar { .. }) && !clock(id))
+                        .map(|(id, _)| id)
+                        .collect();
+                    ids.sort();
+                    ids.into_iter().flat_map(|id| channel_samples(values.get(id))).collect()
+                };
+                if let Some(prev) = &prev_monitor {
+                    if steps_done > conv.min_steps && max_abs_delta(prev, &monitor) < conv.tolerance {
+                        converged = true;
+                    }
+                }
+                prev_monitor = Some(monitor);
+            }
+
+            // Feed state: previous-step outputs become next-step inputs.
+            let next_states: Vec<(String, f64)> = config
+                .states
+                .iter()
+                .map(|s| {
+                    let v = match values.get(&s.source) {
+                        Some(Value::Scalar { value }) => *value,
+                        Some(Value::HighPrecision { approx, .. }) => *approx,
+                        _ => f64::NAN,
+                    };
+                    (s.node.clone(), v)
+                })
+                .collect();
+            for (node, v) in next_states {
+                graph.set_source_value(&node, v);
+            }
+            graph.commit_state();
+
+            let last_in_cycle = k + 1 == schedule.len();
+            if (k + 1) % progress_every == 0 || last_in_cycle || converged {
+                let progress = SimProgress {
+                    step: k + 1,
+                    total_steps: schedule.len(),
+                    cycle,
+                    total_cycles,
+                    t,
+                };
+                if on_progress(&progress) == EvalSignal::Abort {
+                    aborted = true;
+                    break 'cycles;
+                }
+            }
+            if converged {
+                break 'cycles;
+            }
+        }
+    }
+
+    Ok(SimulationResult {
+        table: Value::Table { columns, rows },
+        steps: steps_done,
+        cycles: cycles_started,
+        converged,
+        aborted,
+        evaluated_nodes,
+        final_values: graph.values().clone(),
+        diagnostics,
+    })
+}
+
+// ── Tests ────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{EdgeDef, EngineSnapshotV1, NodeDef};
+
+    fn node(id: &str, block_type: &str, value: Option<f64>) -> NodeDef {
+        let mut data = HashMap::new();
+        if let Some(v) = value {
+            data.insert("value".to_string(), serde_json::json!(v));
+        }
+        NodeDef { id: id.into(), block_type: block_type.into(), data }
+    }
+
+    fn edge(id: &str, src: &str, tgt: &str, tgt_h: &str) -> EdgeDef {
+        EdgeDef {
+            id: id.into(),
+            source: src.into(),
+            source_handle: "out".into(),
+            target: tgt.into(),
+            target_handle: tgt_h.into(),
+        }
+    }
+
+    /// x_{k+1} = x_k + dt  (accumulator driven by the dt node)
+    fn accumulator() -> EngineGraph {
+        let mut g = EngineGraph::new();
+        g.load_snapshot(EngineSnapshotV1 {
+            version: 1,
+            nodes: vec![
+                node("x", "number", Some(0.0)),
+                node("dt", "number", Some(0.0)),
+                node("t", "number", Some(0.0)),
+                node("sum", "add", None),
+            ],
+            edges: vec![edge("e1", "x", "sum", "a"), edge("e2", "dt", "sum", "b")],
+        });
+        g
+    }
+
+    #[test]
+    fn state_feeds_next_step() {
+        let mut g = accumulator();
+        let cfg = SimulationConfig {
+            dt: 0.5,
+            steps: 4,
+            time_node: Some("t".into()),
+            dt_node: Some("dt".into()),
+            states: vec![StateBinding { node: "x".into(), source: "sum".into(), initial: Some(1.0) }],
+            record: vec!["x".into(), "sum".into()],
+            ..Default::default()
+        };
+        let r = run_simulation(&mut g, &cfg, |_| EvalSignal::Continue).unwrap();
+        assert_eq!(r.steps, 4);
+        let (cols, rows) = r.table.as_table().unwrap();
+        assert_eq!(cols, &vec!["t".to_string(), "x".into(), "sum".into()]);
+        let sums: Vec<f64> = rows.iter().map(|row| row[2]).collect();
+        assert_eq!(sums, vec![1.5, 2.0, 2.5, 3.0]);
+        assert_eq!(rows[3][0], 1.5);
+    }
+
+    #[test]
+    fn steps_after_first_are_incremental() {
+        let mut g = accumulator();
+        let cfg = SimulationConfig {
+            steps: 10,
+            time_node: Some("t".into()),
+            ..Default::default()
+        };
+        let r = run_simulation(&mut g, &cfg, |_| EvalSignal::Continue).unwrap();
+        // First step evaluates all 4 nodes; later steps only the clock node.
+        assert_eq!(r.evaluated_nodes, 4 + 9);
+    }
+
+    #[test]
+    fn variable_step_schedule() {
+        let mut g = accumulator();
+        let cfg = SimulationConfig {
+            times: Some(vec![0.0, 0.1, 0.3, 0.7]),
+            dt_node: Some("dt".into()),
+            states: vec![StateBinding { node: "x".into(), source: "sum".into(), initial: Some(0.0) }],
+            record: vec!["sum".into()],
+            ..Default::default()
+        };
+        let r = run_simulation(&mut g, &cfg, |_| EvalSignal::Continue).unwrap();
+        let (_, rows) = r.table.as_table().unwrap();
+        // dt = 0.1, 0.2, 0.4, 0.4 → cumulative sums
+        let last = rows.last().unwrap()[1];
+        assert!((last - 1.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn converges_on_fixed_point() {
+        // x_{k+1} = 0.5 * x_k + 1  → fixed point 2
+        let mut g = EngineGraph::new();
+        g.load_snapshot(EngineSnapshotV1 {
+            version: 1,
+            nodes: vec![
+                node("x", "number", Some(0.0)),
+                node("half", "number", Some(0.5)),
+                node("one", "number", Some(1.0)),
+                node("mul", "multiply", None),
+                node("next", "add", None),
+            ],
+            edges: vec![
+                edge("e1", "x", "mul", "a"),
+                edge("e2", "half", "mul", "b"),
+                edge("e3", "mul", "next", "a"),
+                edge("e4", "one", "next", "b"),
+            ],
+        });
+        let cfg = SimulationConfig {
+            steps: 1000,
+            states: vec![StateBinding { node: "x".into(), source: "next".into(), initial: None }],
+            record: vec!["next".into()],
+            convergence: Some(ConvergenceCheck { nodes: vec![], tolerance: 1e-9, min_steps: 0 }),
+            ..Default::default()
+        };
+        let r = run_simulation(&mut g, &cfg, |_| EvalSignal::Continue).unwrap();
+        assert!(r.converged);
+        assert!(r.steps < 100);
+        assert!((r.final_values["next"].as_scalar().unwrap() - 2.0).abs() < 1e-8);
+    }
+
+    #[test]
+    fn convergence_fallback_ignores_the_clock_nodes() {
+        // x_{k+1} = 0.5 * x_k + 1 with a running clock and nothing recorded.
+        let mut g = EngineGraph::new();
+        g.load_snapshot(EngineSnapshotV1 {
+            version: 1,
+            nodes: vec![
+                node("x", "number", Some(0.0)),
+                node("t", "number", Some(0.0)),
+                node("dt", "number", Some(0.0)),
+                node("half", "number", Some(0.5)),
+                node("one", "number", Some(1.0)),
+                node("mul", "multiply", None),
+                node("next", "add", None),
+            ],
+            edges: vec![
+                edge("e1", "x", "mul", "a"),
+                edge("e2", "half", "mul", "b"),
+                edge("e3", "mul", "next", "a"),
+                edge("e4", "one", "next", "b"),
+            ],
+        });
+        let cfg = SimulationConfig {
+            times: Some((0..1000).map(|k| (k * k) as f64).collect()),
+            time_node: Some("t".into()),
+            dt_node: Some("dt".into()),
+            states: vec![StateBinding { node: "x".into(), source: "next".into(), initial: None }],
+            convergence: Some(ConvergenceCheck { nodes: vec![], tolerance: 1e-9, min_steps: 0 }),
+            ..Default::default()
+        };
+        let r = run_simulation(&mut g, &cfg, |_| EvalSignal::Continue).unwrap();
+        assert!(r.converged);
+        assert!(r.steps < 100, "{}", r.steps);
+    }
+
+    #[test]
+    fn state_bindings_must_target_source_nodes() {
+        let mut g = accumulator();
+        let cfg = SimulationConfig {
+            states: vec![StateBinding { node: "sum".into(), source: "x".into(), initial: Some(1.0) }],
+            ..Default::default()
+        };
+        let err = run_simulation(&mut g, &cfg, |_| EvalSignal::Continue).unwrap_err();
+        assert!(err.message.contains("'sum' is not a source node"), "{}", err.message);
+        let cfg = SimulationConfig { time_node: Some("sum".into()), ..Default::default() };
+        assert!(run_simulation(&mut g, &cfg, |_| EvalSignal::Continue).is_err());
+    }
+
+    #[test]
+    fn stateful_blocks_use_clock_dt() {
+        // Forward-Euler integral of a constant 2.0 with dt = 0.25.
+        let mut g = EngineGraph::new();
+        let mut int = node("int", "ctrl.discrete_integrator", None);
+        int.data.insert("initial".into(), serde_json::json!(1.0));
+        g.load_snapshot(EngineSnapshotV1 {
+            version: 1,
+            nodes: vec![node("u", "number", Some(2.0)), int],
+            edges: vec![edge("e1", "u", "int", "u")],
+        });
+        let cfg = SimulationConfig { dt: 0.25, steps: 5, record: vec!["int".into()], ..Default::default() };
+        let r = run_simulation(&mut g, &cfg, |_| EvalSignal::Continue).unwrap();
+        let (_, rows) = r.table.as_table().unwrap();
+        let ys: Vec<f64> = rows.iter().map(|row| row[1]).collect();
+        assert_eq!(ys, vec![1.0, 1.5, 2.0, 2.5, 3.0]);
+    }
+
+    #[test]
+    fn abort_and_missing_node() {
+        let mut g = accumulator();
+        let cfg = SimulationConfig { steps: 50, progress_every: 5, ..Default::default() };
+        let r = run_simulation(&mut g, &cfg, |p| {
+            if p.step >= 10 { EvalSignal::Abort } else { EvalSignal::Continue }
+        })
+        .unwrap();
+        assert!(r.aborted);
+        assert_eq!(r.steps, 10);
+
+        let bad = SimulationConfig { record: vec!["nope".into()], ..Default::default() };
+        let err = run_simulation(&mut g, &bad, |_| EvalSignal::Continue).unwrap_err();
+        assert_eq!(err.code, ErrorCode::InvalidSimConfig);
+    }
+}
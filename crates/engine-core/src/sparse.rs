@@ -244,6 +244,27 @@ impl CsrMatrix {
         self.values.len()
     }
 
+    /// Check the CSR invariants the kernels index by: `rows + 1` row pointers
+    /// rising from 0 to `nnz`, one column index per value, columns in range.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.row_ptrs.len() != self.rows.saturating_add(1) {
+            return Err(format!("expected {} row pointers, got {}", self.rows.saturating_add(1), self.row_ptrs.len()));
+        }
+        if self.col_indices.len() != self.values.len() {
+            return Err(format!("{} column indices for {} values", self.col_indices.len(), self.values.len()));
+        }
+        if self.row_ptrs[0] != 0 || self.row_ptrs[self.rows] != self.values.len() {
+            return Err(format!("row pointers must run from 0 to {}", self.values.len()));
+        }
+        if self.row_ptrs.windows(2).any(|w| w[0] > w[1]) {
+            return Err("row pointers must be non-decreasing".to_string());
+        }
+        if let Some(&j) = self.col_indices.iter().find(|&&j| j >= self.cols) {
+            return Err(format!("column index {j} out of range for {} columns", self.cols));
+        }
+        Ok(())
+    }
+
     /// Sparse matrix-vector multiply: y = A * x.
     pub fn mul_vec(&self, x: &[f64]) -> Vec<f64> {
         assert_eq!(x.len(), self.cols, "SpMV: vector length must match cols");
@@ -309,6 +330,18 @@ impl CsrMatrix {
         self.to_coo().to_csc().to_csr_transposed()
     }
 
+    /// Convert to CSC format (columns sorted, duplicates already summed).
+    pub fn to_csc(&self) -> CscMatrix {
+        let t = self.transpose();
+        CscMatrix {
+            rows: self.rows,
+            cols: self.cols,
+            col_ptrs: t.row_ptrs,
+            row_indices: t.col_indices,
+            values: t.values,
+        }
+    }
+
     /// Get the diagonal as a vector.
     pub fn diagonal(&self) -> Vec<f64> {
         let min_dim = self.rows.min(self.cols);
@@ -323,6 +356,60 @@ impl CsrMatrix {
         }
         diag
     }
+
+    /// Sparse matrix-matrix multiply: C = A * B (Gustavson's row-by-row algorithm).
+    pub fn mul_csr(&self, b: &CsrMatrix) -> CsrMatrix {
+        assert_eq!(self.cols, b.rows, "CSR SpGEMM: inner dimensions must match");
+        let mut row_ptrs = vec![0usize; self.rows + 1];
+        let mut col_indices = Vec::new();
+        let mut values = Vec::new();
+        let mut acc = vec![0.0f64; b.cols];
+        let mut occupied = vec![false; b.cols];
+        let mut pattern = Vec::new();
+        for i in 0..self.rows {
+            for k in self.row_ptrs[i]..self.row_ptrs[i + 1] {
+                let (j, a_ij) = (self.col_indices[k], self.values[k]);
+                for p in b.row_ptrs[j]..b.row_ptrs[j + 1] {
+                    let c = b.col_indices[p];
+                    if !occupied[c] {
+                        occupied[c] = true;
+                        pattern.push(c);
+                    }
+                    acc[c] += a_ij * b.values[p];
+                }
+            }
+            pattern.sort_unstable();
+            for &c in &pattern {
+                col_indices.push(c);
+                values.push(acc[c]);
+                acc[c] = 0.0;
+                occupied[c] = false;
+            }
+            pattern.clear();
+            row_ptrs[i + 1] = col_indices.len();
+        }
+        CsrMatrix {
+            rows: self.rows,
+            cols: b.cols,
+            row_ptrs,
+            col_indices,
+            values,
+        }
+    }
+
+    /// Whether A equals Aᵀ to within `tol` (absolute, per entry).
+    pub fn is_symmetric(&self, tol: f64) -> bool {
+        if self.rows != self.cols {
+            return false;
+        }
+        let diff = self.to_coo();
+        let mut coo = CooMatrix::with_capacity(self.rows, self.cols, 2 * self.nnz());
+        for k in 0..diff.nnz() {
+            coo.push(diff.row_indices[k], diff.col_indices[k], diff.values[k]);
+            coo.push(diff.col_indices[k], diff.row_indices[k], -diff.values[k]);
+        }
+        coo.to_csr().values.iter().all(|v| v.abs() <= tol)
+    }
 }
 
 // ── CSC operations ───────────────────────────────────────────────────────
@@ -501,6 +588,69 @@ pub fn sparsity_ratio(rows: usize, cols: usize, nnz: usize) -> f64 {
     1.0 - (nnz as f64 / total as f64)
 }
 
+/// Structural summary of a sparse matrix (the numbers behind a spy plot).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparsityStats {
+    pub nnz: usize,
+    /// nnz / (rows · cols).
+    pub density: f64,
+    /// Largest |i − j| over the stored entries below the diagonal.
+    pub lower_bandwidth: usize,
+    /// Largest |i − j| over the stored entries above the diagonal.
+    pub upper_bandwidth: usize,
+    pub min_row_nnz: usize,
+    pub max_row_nnz: usize,
+    /// Rows with no stored diagonal entry (square part only).
+    pub missing_diagonal: usize,
+    pub symmetric: bool,
+    /// Every row satisfies |aᵢᵢ| ≥ Σⱼ≠ᵢ |aᵢⱼ|.
+    pub diagonally_dominant: bool,
+}
+
+/// Compute [`SparsityStats`] for a CSR matrix.
+pub fn sparsity_stats(a: &CsrMatrix) -> SparsityStats {
+    let (mut lower, mut upper) = (0usize, 0usize);
+    let (mut min_row, mut max_row) = (usize::MAX, 0usize);
+    let mut missing_diagonal = 0;
+    let mut dominant = true;
+    for i in 0..a.rows {
+        let range = a.row_ptrs[i]..a.row_ptrs[i + 1];
+        min_row = min_row.min(range.len());
+        max_row = max_row.max(range.len());
+        let (mut diag, mut off, mut has_diag) = (0.0f64, 0.0f64, false);
+        for k in range {
+            let j = a.col_indices[k];
+            if j == i {
+                diag += a.values[k].abs();
+                has_diag = true;
+            } else {
+                off += a.values[k].abs();
+                if j < i {
+                    lower = lower.max(i - j);
+                } else {
+                    upper = upper.max(j - i);
+                }
+            }
+        }
+        if i < a.cols && !has_diag {
+            missing_diagonal += 1;
+        }
+        dominant &= diag >= off;
+    }
+    let total = a.rows * a.cols;
+    SparsityStats {
+        nnz: a.nnz(),
+        density: if total == 0 { 0.0 } else { a.nnz() as f64 / total as f64 },
+        lower_bandwidth: lower,
+        upper_bandwidth: upper,
+        min_row_nnz: if a.rows == 0 { 0 } else { min_row },
+        max_row_nnz: max_row,
+        missing_diagonal,
+        symmetric: a.is_symmetric(0.0),
+        diagonally_dominant: dominant,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -533,6 +683,19 @@ mod tests {
         assert!(approx_eq(csr.values[1], 5.0, 1e-10));
     }
 
+    #[test]
+    fn test_csr_validate() {
+        assert!(sparse_identity(3).validate().is_ok());
+        let bad = |row_ptrs: Vec<usize>, col_indices: Vec<usize>| {
+            let values = vec![1.0; col_indices.len()];
+            CsrMatrix { rows: 2, cols: 2, row_ptrs, col_indices, values }.validate()
+        };
+        assert!(bad(vec![0, 1], vec![0]).unwrap_err().contains("row pointers"));
+        assert!(bad(vec![0, 2, 1], vec![0, 1]).is_err());
+        assert!(bad(vec![0, 1, 3], vec![0, 1]).is_err());
+        assert!(bad(vec![0, 1, 2], vec![0, 2]).unwrap_err().contains("out of range"));
+    }
+
     #[test]
     fn test_csr_spmv() {
         // [[2, 0, 1], [0, 3, 0]] * [1, 2, 3] = [5, 6]
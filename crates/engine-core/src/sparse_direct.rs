@@ -0,0 +1,963 @@
+//! Sparse direct solvers for linear systems Ax = b.
+//!
+//! - **Fill-reducing orderings** — minimum degree on the quotient graph (AMD-style
+//!   element absorption) and nested dissection, both on the pattern of A + Aᵀ
+//! - **Sparse LDLᵀ / Cholesky** — up-looking factorization of symmetric matrices,
+//!   with determinant and inertia queries
+//! - **Sparse LU** — left-looking (Gilbert–Peierls) factorization with partial pivoting
+//!
+//! Both factorizations split into a symbolic analysis and a numeric phase, so a
+//! sequence of matrices sharing one sparsity pattern (Newton steps, SIMP iterations)
+//! reuses the ordering and elimination tree.
+
+use crate::sparse::{CooMatrix, CscMatrix};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Marker for "no node" in elimination trees and permutations.
+const NONE: usize = usize::MAX;
+
+/// Vertex sets at or below this size are ordered by minimum degree instead of
+/// being bisected further.
+const DISSECTION_LEAF: usize = 64;
+
+// ── Fill-reducing orderings ──────────────────────────────────────────────
+
+/// Symmetric permutation applied before factorization to limit fill-in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillOrdering {
+    /// Keep the original order.
+    Natural,
+    /// Minimum degree on the quotient graph of A + Aᵀ.
+    #[default]
+    MinimumDegree,
+    /// Recursive level-set bisection, minimum degree on the leaves.
+    NestedDissection,
+}
+
+impl FillOrdering {
+    /// Parse `"natural"`, `"amd"` / `"minimum-degree"` or `"nd"` / `"nested-dissection"`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "natural" | "none" => Ok(Self::Natural),
+            "amd" | "md" | "minimum-degree" => Ok(Self::MinimumDegree),
+            "nd" | "nested-dissection" => Ok(Self::NestedDissection),
+            other => Err(format!("unknown ordering '{other}' (expected natural, amd or nd)")),
+        }
+    }
+}
+
+/// Compute a fill-reducing permutation of a square matrix: `perm[k]` is the
+/// original row/column eliminated k-th.
+pub fn fill_reducing_ordering(a: &CscMatrix, ordering: FillOrdering) -> Vec<usize> {
+    match ordering {
+        FillOrdering::Natural => (0..a.cols).collect(),
+        FillOrdering::MinimumDegree => minimum_degree(&symmetric_adjacency(a)),
+        FillOrdering::NestedDissection => nested_dissection(&symmetric_adjacency(a)),
+    }
+}
+
+/// Off-diagonal adjacency lists of the pattern of A + Aᵀ.
+fn symmetric_adjacency(a: &CscMatrix) -> Vec<Vec<usize>> {
+    let n = a.cols;
+    let mut adj = vec![Vec::new(); n];
+    for j in 0..n {
+        for &i in &a.row_indices[a.col_ptrs[j]..a.col_ptrs[j + 1]] {
+            if i != j {
+                adj[i].push(j);
+                adj[j].push(i);
+            }
+        }
+    }
+    for list in &mut adj {
+        list.sort_unstable();
+        list.dedup();
+    }
+    adj
+}
+
+/// Minimum degree ordering on the quotient graph.
+///
+/// Each eliminated pivot becomes an element whose members are its remaining
+/// neighbours; elements adjacent to the pivot are absorbed into it, so fill edges
+/// are never stored explicitly. Degrees are exact external degrees.
+fn minimum_degree(adj: &[Vec<usize>]) -> Vec<usize> {
+    let n = adj.len();
+    let mut vars: Vec<Vec<usize>> = adj.to_vec();
+    let mut elems: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut members: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut eliminated = vec![false; n];
+    let mut absorbed = vec![false; n];
+    let mut degree: Vec<usize> = adj.iter().map(Vec::len).collect();
+    let mut heap: BinaryHeap<Reverse<(usize, usize)>> =
+        (0..n).map(|i| Reverse((degree[i], i))).collect();
+    let mut in_front = vec![NONE; n];
+    let mut seen = vec![0usize; n];
+    let mut stamp = 0usize;
+    let mut order = Vec::with_capacity(n);
+
+    while let Some(Reverse((d, p))) = heap.pop() {
+        if eliminated[p] || d != degree[p] {
+            continue;
+        }
+        eliminated[p] = true;
+        order.push(p);
+
+        // Members of the new element: uneliminated neighbours of p, either direct
+        // or through the elements p belongs to.
+        let mut front = Vec::new();
+        in_front[p] = p;
+        for v in std::mem::take(&mut vars[p]) {
+            if !eliminated[v] && in_front[v] != p {
+                in_front[v] = p;
+                front.push(v);
+            }
+        }
+        for e in std::mem::take(&mut elems[p]) {
+            if absorbed[e] {
+                continue;
+            }
+            for v in std::mem::take(&mut members[e]) {
+                if !eliminated[v] && in_front[v] != p {
+                    in_front[v] = p;
+                    front.push(v);
+                }
+            }
+            absorbed[e] = true;
+        }
+        members[p] = front.clone();
+
+        for &i in &front {
+            elems[i].retain(|&e| !absorbed[e]);
+            elems[i].push(p);
+            vars[i].retain(|&v| !eliminated[v] && in_front[v] != p);
+
+            stamp += 1;
+            seen[i] = stamp;
+            let mut deg = 0;
+            for &v in &vars[i] {
+                if seen[v] != stamp {
+                    seen[v] = stamp;
+                    deg += 1;
+                }
+            }
+            for &e in &elems[i] {
+                for &v in &members[e] {
+                    if !eliminated[v] && seen[v] != stamp {
+                        seen[v] = stamp;
+                        deg += 1;
+                    }
+                }
+            }
+            if deg != degree[i] {
+                degree[i] = deg;
+                heap.push(Reverse((deg, i)));
+            }
+        }
+    }
+    order
+}
+
+/// Nested dissection: split each vertex set at the middle level of a breadth-first
+/// level structure rooted at a pseudo-peripheral vertex, order both halves first
+/// and the separator last. Disconnected sets are split into components.
+fn nested_dissection(adj: &[Vec<usize>]) -> Vec<usize> {
+    let n = adj.len();
+    let mut owner = vec![0usize; n];
+    let mut visit = vec![0usize; n];
+    let mut local = vec![0usize; n];
+    let (mut tag, mut stamp) = (0usize, 0usize);
+    // Sets are popped depth-first and emitted back to front, so pushing the
+    // first half before the second yields `first, second, separator`.
+    let mut reversed = Vec::with_capacity(n);
+    let mut pending = vec![(0..n).collect::<Vec<usize>>()];
+
+    while let Some(set) = pending.pop() {
+        tag += 1;
+        for &v in &set {
+            owner[v] = tag;
+        }
+        if set.len() <= DISSECTION_LEAF {
+            reversed.extend(leaf_order(adj, &set, &owner, tag, &mut local).into_iter().rev());
+            continue;
+        }
+
+        stamp += 1;
+        let mut levels = level_sets(adj, set[0], &owner, tag, &mut visit, stamp);
+        let reached: usize = levels.iter().map(Vec::len).sum();
+        if reached < set.len() {
+            let (component, rest): (Vec<usize>, Vec<usize>) =
+                set.iter().partition(|&&v| visit[v] == stamp);
+            pending.push(component);
+            pending.push(rest);
+            continue;
+        }
+
+        // Walk towards a pseudo-peripheral vertex while the eccentricity grows.
+        loop {
+            let last = levels.last().expect("level structure is non-empty");
+            let start = *last.iter().min_by_key(|&&v| adj[v].len()).expect("level is non-empty");
+            stamp += 1;
+            let candidate = level_sets(adj, start, &owner, tag, &mut visit, stamp);
+            if candidate.len() <= levels.len() {
+                break;
+            }
+            levels = candidate;
+        }
+        if levels.len() < 3 {
+            reversed.extend(leaf_order(adj, &set, &owner, tag, &mut local).into_iter().rev());
+            continue;
+        }
+
+        let half = set.len() / 2;
+        let mut below = 0;
+        let mut mid = 1;
+        for (k, level) in levels.iter().enumerate().take(levels.len() - 1).skip(1) {
+            mid = k;
+            below += levels[k - 1].len();
+            if below + level.len() >= half {
+                break;
+            }
+        }
+        let first: Vec<usize> = levels[..mid].concat();
+        let second: Vec<usize> = levels[mid + 1..].concat();
+        reversed.extend(levels[mid].iter().rev());
+        pending.push(first);
+        pending.push(second);
+    }
+    reversed.reverse();
+    reversed
+}
+
+/// Breadth-first level sets from `start`, restricted to vertices owned by `tag`.
+fn level_sets(
+    adj: &[Vec<usize>],
+    start: usize,
+    owner: &[usize],
+    tag: usize,
+    visit: &mut [usize],
+    stamp: usize,
+) -> Vec<Vec<usize>> {
+    visit[start] = stamp;
+    let mut levels = vec![vec![start]];
+    loop {
+        let mut next = Vec::new();
+        for &v in levels.last().expect("level structure is non-empty") {
+            for &w in &adj[v] {
+                if owner[w] == tag && visit[w] != stamp {
+                    visit[w] = stamp;
+                    next.push(w);
+                }
+            }
+        }
+        if next.is_empty() {
+            return levels;
+        }
+        levels.push(next);
+    }
+}
+
+/// Minimum degree ordering of the subgraph induced by `set`.
+fn leaf_order(adj: &[Vec<usize>], set: &[usize], owner: &[usize], tag: usize, local: &mut [usize]) -> Vec<usize> {
+    for (k, &v) in set.iter().enumerate() {
+        local[v] = k;
+    }
+    let sub: Vec<Vec<usize>> = set
+        .iter()
+        .map(|&v| adj[v].iter().filter(|&&w| owner[w] == tag).map(|&w| local[w]).collect())
+        .collect();
+    minimum_degree(&sub).into_iter().map(|k| set[k]).collect()
+}
+
+// ── Shared helpers ───────────────────────────────────────────────────────
+
+/// Sparsity pattern recorded by a symbolic analysis, checked before each
+/// numeric factorization.
+#[derive(Debug, Clone)]
+struct Pattern {
+    n: usize,
+    col_ptrs: Vec<usize>,
+    row_indices: Vec<usize>,
+}
+
+impl Pattern {
+    fn of(a: &CscMatrix) -> Result<Self, String> {
+        if a.rows != a.cols {
+            return Err(format!("matrix must be square, got {}×{}", a.rows, a.cols));
+        }
+        Ok(Pattern { n: a.cols, col_ptrs: a.col_ptrs.clone(), row_indices: a.row_indices.clone() })
+    }
+
+    fn check(&self, a: &CscMatrix) -> Result<(), String> {
+        if a.rows == self.n && a.cols == self.n && a.col_ptrs == self.col_ptrs && a.row_indices == self.row_indices {
+            Ok(())
+        } else {
+            Err("sparsity pattern differs from the analysed matrix; run the symbolic analysis again".to_string())
+        }
+    }
+}
+
+fn invert(perm: &[usize]) -> Vec<usize> {
+    let mut inv = vec![0; perm.len()];
+    for (k, &i) in perm.iter().enumerate() {
+        inv[i] = k;
+    }
+    inv
+}
+
+/// Sign (±1) of a permutation, from its cycle decomposition.
+fn permutation_sign(perm: &[usize]) -> f64 {
+    let mut visited = vec![false; perm.len()];
+    let mut sign = 1.0;
+    for start in 0..perm.len() {
+        let mut len = 0;
+        let mut i = start;
+        while !visited[i] {
+            visited[i] = true;
+            i = perm[i];
+            len += 1;
+        }
+        if len > 0 && len % 2 == 0 {
+            sign = -sign;
+        }
+    }
+    sign
+}
+
+/// Sign and natural log of |∏ pivots|, accumulated without overflow.
+fn log_product(pivots: impl Iterator<Item = f64>) -> (f64, f64) {
+    pivots.fold((1.0, 0.0), |(sign, log), d| (sign * d.signum(), log + d.abs().ln()))
+}
+
+fn check_rhs(n: usize, b: &[f64]) -> Result<(), String> {
+    if b.len() == n {
+        Ok(())
+    } else {
+        Err(format!("right-hand side has length {}, expected {n}", b.len()))
+    }
+}
+
+// ── Sparse LDLᵀ / Cholesky ───────────────────────────────────────────────
+
+/// Counts of positive, negative and (numerically) zero eigenvalues, read off the
+/// pivots of an LDLᵀ factorization by Sylvester's law of inertia.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Inertia {
+    pub positive: usize,
+    pub negative: usize,
+    pub zero: usize,
+}
+
+/// Symbolic analysis of a symmetric matrix: ordering, elimination tree and the
+/// column structure of L. Reusable for every matrix with the same pattern.
+///
+/// The matrix must store both triangles; only the entries on or above the
+/// diagonal of PAPᵀ are read.
+#[derive(Debug, Clone)]
+pub struct SymbolicCholesky {
+    pattern: Pattern,
+    /// `perm[k]` is the original row/column eliminated k-th.
+    pub perm: Vec<usize>,
+    pinv: Vec<usize>,
+    /// Elimination tree of PAPᵀ (`usize::MAX` marks a root).
+    pub parent: Vec<usize>,
+    /// Column pointers of the strictly lower part of L.
+    col_ptrs: Vec<usize>,
+}
+
+impl SymbolicCholesky {
+    /// Order the matrix and compute its elimination tree and column counts.
+    pub fn analyze(a: &CscMatrix, ordering: FillOrdering) -> Result<Self, String> {
+        let pattern = Pattern::of(a)?;
+        let n = a.cols;
+        let perm = fill_reducing_ordering(a, ordering);
+        let pinv = invert(&perm);
+        let c = permuted_upper(a, &pinv);
+
+        let mut parent = vec![NONE; n];
+        let mut flag = vec![NONE; n];
+        let mut counts = vec![0usize; n];
+        for k in 0..n {
+            flag[k] = k;
+            for &row in &c.row_indices[c.col_ptrs[k]..c.col_ptrs[k + 1]] {
+                let mut i = row;
+                while i < k && flag[i] != k {
+                    if parent[i] == NONE {
+                        parent[i] = k;
+                    }
+                    counts[i] += 1;
+                    flag[i] = k;
+                    i = parent[i];
+                }
+            }
+        }
+        let mut col_ptrs = vec![0usize; n + 1];
+        for k in 0..n {
+            col_ptrs[k + 1] = col_ptrs[k] + counts[k];
+        }
+        Ok(SymbolicCholesky { pattern, perm, pinv, parent, col_ptrs })
+    }
+
+    /// Number of off-diagonal entries L will hold.
+    pub fn nnz_l(&self) -> usize {
+        self.col_ptrs[self.perm.len()]
+    }
+
+    /// Numeric LDLᵀ factorization of a matrix with the analysed pattern.
+    ///
+    /// No pivoting is done, so symmetric indefinite matrices succeed unless a
+    /// pivot vanishes exactly.
+    pub fn factor(&self, a: &CscMatrix) -> Result<CholeskyFactor, String> {
+        self.pattern.check(a)?;
+        let n = self.perm.len();
+        let c = permuted_upper(a, &self.pinv);
+        let lp = &self.col_ptrs;
+        let mut li = vec![0usize; lp[n]];
+        let mut lx = vec![0.0f64; lp[n]];
+        let mut lnz = vec![0usize; n];
+        let mut d = vec![0.0f64; n];
+        let mut y = vec![0.0f64; n];
+        let mut flag = vec![NONE; n];
+        let mut stack = vec![0usize; n];
+
+        for k in 0..n {
+            // Nonzero pattern of row k of L: the union of etree paths from the
+            // entries of column k of the upper triangle, in topological order.
+            let mut top = n;
+            flag[k] = k;
+            for p in c.col_ptrs[k]..c.col_ptrs[k + 1] {
+                let mut i = c.row_indices[p];
+                y[i] += c.values[p];
+                let mut len = 0;
+                while flag[i] != k {
+                    stack[len] = i;
+                    len += 1;
+                    flag[i] = k;
+                    i = self.parent[i];
+                }
+                while len > 0 {
+                    top -= 1;
+                    len -= 1;
+                    stack[top] = stack[len];
+                }
+            }
+            d[k] = y[k];
+            y[k] = 0.0;
+            for &i in &stack[top..n] {
+                let yi = y[i];
+                y[i] = 0.0;
+                let end = lp[i] + lnz[i];
+                for p in lp[i]..end {
+                    y[li[p]] -= lx[p] * yi;
+                }
+                let l_ki = yi / d[i];
+                d[k] -= l_ki * yi;
+                li[end] = k;
+                lx[end] = l_ki;
+                lnz[i] += 1;
+            }
+            if d[k] == 0.0 || !d[k].is_finite() {
+                return Err(format!("zero pivot at row {}; the matrix is singular", self.perm[k]));
+            }
+        }
+
+        let scale = a.values.iter().fold(0.0f64, |m, v| m.max(v.abs()));
+        Ok(CholeskyFactor {
+            perm: self.perm.clone(),
+            l: CscMatrix { rows: n, cols: n, col_ptrs: lp.clone(), row_indices: li, values: lx },
+            d,
+            zero_tol: n as f64 * f64::EPSILON * scale,
+        })
+    }
+}
+
+/// Upper triangle of PAPᵀ in CSC form (duplicates summed by the factorization).
+fn permuted_upper(a: &CscMatrix, pinv: &[usize]) -> CscMatrix {
+    let n = a.cols;
+    let mut coo = CooMatrix::with_capacity(n, n, a.nnz());
+    for j in 0..n {
+        for p in a.col_ptrs[j]..a.col_ptrs[j + 1] {
+            let (pi, pj) = (pinv[a.row_indices[p]], pinv[j]);
+            if pi <= pj {
+                coo.push(pi, pj, a.values[p]);
+            }
+        }
+    }
+    coo.to_csc()
+}
+
+/// Numeric LDLᵀ factorization PAPᵀ = LDLᵀ. For SPD matrices L·√D is the
+/// Cholesky factor of PAPᵀ.
+#[derive(Debug, Clone)]
+pub struct CholeskyFactor {
+    perm: Vec<usize>,
+    /// Unit lower-triangular factor, diagonal omitted.
+    pub l: CscMatrix,
+    /// Diagonal pivots.
+    pub d: Vec<f64>,
+    zero_tol: f64,
+}
+
+impl CholeskyFactor {
+    /// Solve Ax = b.
+    pub fn solve(&self, b: &[f64]) -> Result<Vec<f64>, String> {
+        let n = self.d.len();
+        check_rhs(n, b)?;
+        let l = &self.l;
+        let mut x: Vec<f64> = self.perm.iter().map(|&i| b[i]).collect();
+        for j in 0..n {
+            let xj = x[j];
+            for p in l.col_ptrs[j]..l.col_ptrs[j + 1] {
+                x[l.row_indices[p]] -= l.values[p] * xj;
+            }
+        }
+        for (xj, dj) in x.iter_mut().zip(&self.d) {
+            *xj /= dj;
+        }
+        for j in (0..n).rev() {
+            let mut s = x[j];
+            for p in l.col_ptrs[j]..l.col_ptrs[j + 1] {
+                s -= l.values[p] * x[l.row_indices[p]];
+            }
+            x[j] = s;
+        }
+        let mut out = vec![0.0; n];
+        for (k, &i) in self.perm.iter().enumerate() {
+            out[i] = x[k];
+        }
+        Ok(out)
+    }
+
+    /// det(A) = ∏ dₖ.
+    pub fn determinant(&self) -> f64 {
+        self.d.iter().product()
+    }
+
+    /// `(sign, ln|det A|)`, safe for determinants outside the f64 range.
+    pub fn log_determinant(&self) -> (f64, f64) {
+        log_product(self.d.iter().copied())
+    }
+
+    /// Eigenvalue sign counts; pivots within `n·ε·max|aᵢⱼ|` of zero count as zero.
+    pub fn inertia(&self) -> Inertia {
+        let mut inertia = Inertia { positive: 0, negative: 0, zero: 0 };
+        for &d in &self.d {
+            if d.abs() <= self.zero_tol {
+                inertia.zero += 1;
+            } else if d > 0.0 {
+                inertia.positive += 1;
+            } else {
+                inertia.negative += 1;
+            }
+        }
+        inertia
+    }
+
+    /// Whether every pivot is (numerically) positive.
+    pub fn is_positive_definite(&self) -> bool {
+        self.d.iter().all(|&d| d > self.zero_tol)
+    }
+
+    /// Stored entries of L plus the diagonal.
+    pub fn nnz(&self) -> usize {
+        self.l.nnz() + self.d.len()
+    }
+}
+
+/// Factor a symmetric positive definite matrix with a minimum degree ordering.
+pub fn cholesky(a: &CscMatrix) -> Result<CholeskyFactor, String> {
+    let factor = SymbolicCholesky::analyze(a, FillOrdering::MinimumDegree)?.factor(a)?;
+    if !factor.is_positive_definite() {
+        return Err("matrix is not positive definite".to_string());
+    }
+    Ok(factor)
+}
+
+// ── Sparse LU ────────────────────────────────────────────────────────────
+
+/// Symbolic analysis for LU: the fill-reducing column ordering, computed on the
+/// pattern of A + Aᵀ. Row pivots are chosen numerically during each factorization.
+#[derive(Debug, Clone)]
+pub struct SymbolicLu {
+    pattern: Pattern,
+    /// `col_perm[k]` is the original column factored k-th.
+    pub col_perm: Vec<usize>,
+}
+
+impl SymbolicLu {
+    /// Compute the column ordering.
+    pub fn analyze(a: &CscMatrix, ordering: FillOrdering) -> Result<Self, String> {
+        let pattern = Pattern::of(a)?;
+        Ok(SymbolicLu { pattern, col_perm: fill_reducing_ordering(a, ordering) })
+    }
+
+    /// Numeric factorization PAQ = LU with partial pivoting. Ties keep the
+    /// diagonal entry so symmetric orderings stay effective.
+    pub fn factor(&self, a: &CscMatrix) -> Result<LuFactor, String> {
+        self.pattern.check(a)?;
+        let n = a.cols;
+        let nnz = a.nnz();
+        let mut lp = vec![0usize; n + 1];
+        let mut li: Vec<usize> = Vec::with_capacity(2 * nnz + n);
+        let mut lx: Vec<f64> = Vec::with_capacity(2 * nnz + n);
+        let mut up = vec![0usize; n + 1];
+        let mut ui: Vec<usize> = Vec::with_capacity(2 * nnz + n);
+        let mut ux: Vec<f64> = Vec::with_capacity(2 * nnz + n);
+        let mut pinv = vec![NONE; n];
+        let mut x = vec![0.0f64; n];
+        let mut reach = Reach::new(n);
+
+        for (k, &col) in self.col_perm.iter().enumerate() {
+            lp[k] = li.len();
+            up[k] = ui.len();
+            let range = a.col_ptrs[col]..a.col_ptrs[col + 1];
+
+            // Sparse triangular solve x = L \ A(:, col) over the reachable set.
+            let top = reach.run(&a.row_indices[range.clone()], &lp, &li, &pinv);
+            for p in range {
+                x[a.row_indices[p]] += a.values[p];
+            }
+            for &j in &reach.xi[top..] {
+                let jnew = pinv[j];
+                if jnew == NONE {
+                    continue;
+                }
+                let xj = x[j];
+                for p in lp[jnew] + 1..lp[jnew + 1] {
+                    x[li[p]] -= lx[p] * xj;
+                }
+            }
+
+            let mut ipiv = NONE;
+            let mut best = -1.0;
+            for &i in &reach.xi[top..] {
+                if pinv[i] == NONE {
+                    if x[i].abs() > best {
+                        best = x[i].abs();
+                        ipiv = i;
+                    }
+                } else {
+                    ui.push(pinv[i]);
+                    ux.push(x[i]);
+                }
+            }
+            if ipiv == NONE || best <= 0.0 {
+                return Err(format!("matrix is singular: no nonzero pivot in column {col}"));
+            }
+            if pinv[col] == NONE && x[col].abs() >= best {
+                ipiv = col;
+            }
+            let pivot = x[ipiv];
+            ui.push(k);
+            ux.push(pivot);
+            pinv[ipiv] = k;
+            li.push(ipiv);
+            lx.push(1.0);
+            for &i in &reach.xi[top..] {
+                if pinv[i] == NONE {
+                    li.push(i);
+                    lx.push(x[i] / pivot);
+                }
+                x[i] = 0.0;
+            }
+        }
+        lp[n] = li.len();
+        up[n] = ui.len();
+        for i in &mut li {
+            *i = pinv[*i];
+        }
+
+        Ok(LuFactor {
+            row_perm: invert(&pinv),
+            col_perm: self.col_perm.clone(),
+            l: CscMatrix { rows: n, cols: n, col_ptrs: lp, row_indices: li, values: lx },
+            u: CscMatrix { rows: n, cols: n, col_ptrs: up, row_indices: ui, values: ux },
+        })
+    }
+}
+
+/// Workspace for the depth-first search of the graph of L.
+struct Reach {
+    xi: Vec<usize>,
+    stack: Vec<usize>,
+    next: Vec<usize>,
+    mark: Vec<bool>,
+}
+
+impl Reach {
+    fn new(n: usize) -> Self {
+        Reach { xi: vec![0; n], stack: vec![0; n], next: vec![0; n], mark: vec![false; n] }
+    }
+
+    /// Nodes reachable from `starts` through the columns of L factored so far,
+    /// left in `xi[top..]` in topological order; returns `top`.
+    fn run(&mut self, starts: &[usize], lp: &[usize], li: &[usize], pinv: &[usize]) -> usize {
+        let mut top = self.xi.len();
+        for &s in starts {
+            if self.mark[s] {
+                continue;
+            }
+            let mut head = 0;
+            self.stack[0] = s;
+            loop {
+                let j = self.stack[head];
+                let jnew = pinv[j];
+                if !self.mark[j] {
+                    self.mark[j] = true;
+                    self.next[head] = if jnew == NONE { 0 } else { lp[jnew] };
+                }
+                let end = if jnew == NONE { 0 } else { lp[jnew + 1] };
+                let mut descended = false;
+                while self.next[head] < end {
+                    let i = li[self.next[head]];
+                    self.next[head] += 1;
+                    if !self.mark[i] {
+                        head += 1;
+                        self.stack[head] = i;
+                        descended = true;
+                        break;
+                    }
+                }
+                if !descended {
+                    top -= 1;
+                    self.xi[top] = j;
+                    if head == 0 {
+                        break;
+                    }
+                    head -= 1;
+                }
+            }
+        }
+        for &j in &self.xi[top..] {
+            self.mark[j] = false;
+        }
+        top
+    }
+}
+
+/// Numeric LU factorization PAQ = LU.
+#[derive(Debug, Clone)]
+pub struct LuFactor {
+    /// `row_perm[k]` is the original row chosen as the k-th pivot.
+    pub row_perm: Vec<usize>,
+    /// `col_perm[k]` is the original column factored k-th.
+    pub col_perm: Vec<usize>,
+    /// Unit lower-triangular factor, diagonal stored first in each column.
+    pub l: CscMatrix,
+    /// Upper-triangular factor, diagonal stored last in each column.
+    pub u: CscMatrix,
+}
+
+impl LuFactor {
+    /// Solve Ax = b.
+    pub fn solve(&self, b: &[f64]) -> Result<Vec<f64>, String> {
+        let n = self.row_perm.len();
+        check_rhs(n, b)?;
+        let (l, u) = (&self.l, &self.u);
+        let mut x: Vec<f64> = self.row_perm.iter().map(|&i| b[i]).collect();
+        for j in 0..n {
+            let xj = x[j];
+            for p in l.col_ptrs[j] + 1..l.col_ptrs[j + 1] {
+                x[l.row_indices[p]] -= l.values[p] * xj;
+            }
+        }
+        for j in (0..n).rev() {
+            let last = u.col_ptrs[j + 1] - 1;
+            x[j] /= u.values[last];
+            let xj = x[j];
+            for p in u.col_ptrs[j]..last {
+                x[u.row_indices[p]] -= u.values[p] * xj;
+            }
+        }
+        let mut out = vec![0.0; n];
+        for (k, &j) in self.col_perm.iter().enumerate() {
+            out[j] = x[k];
+        }
+        Ok(out)
+    }
+
+    /// Diagonal of U.
+    pub fn pivots(&self) -> impl Iterator<Item = f64> + '_ {
+        (0..self.u.cols).map(|j| self.u.values[self.u.col_ptrs[j + 1] - 1])
+    }
+
+    /// det(A) = sign(P)·sign(Q)·∏ uₖₖ.
+    pub fn determinant(&self) -> f64 {
+        self.permutation_sign() * self.pivots().product::<f64>()
+    }
+
+    /// `(sign, ln|det A|)`, safe for determinants outside the f64 range.
+    pub fn log_determinant(&self) -> (f64, f64) {
+        let (sign, log) = log_product(self.pivots());
+        (sign * self.permutation_sign(), log)
+    }
+
+    /// Stored entries of L and U.
+    pub fn nnz(&self) -> usize {
+        self.l.nnz() + self.u.nnz()
+    }
+
+    fn permutation_sign(&self) -> f64 {
+        permutation_sign(&self.row_perm) * permutation_sign(&self.col_perm)
+    }
+}
+
+/// Factor a general square matrix with a minimum degree column ordering.
+pub fn lu(a: &CscMatrix) -> Result<LuFactor, String> {
+    SymbolicLu::analyze(a, FillOrdering::MinimumDegree)?.factor(a)
+}
+
+// ── Tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 5-point Laplacian on an m×m grid (SPD, both triangles stored).
+    fn laplacian_2d(m: usize) -> CscMatrix {
+        let mut coo = CooMatrix::new(m * m, m * m);
+        for y in 0..m {
+            for x in 0..m {
+                let i = y * m + x;
+                coo.push(i, i, 4.0);
+                if x > 0 {
+                    coo.push(i, i - 1, -1.0);
+                }
+                if x + 1 < m {
+                    coo.push(i, i + 1, -1.0);
+                }
+                if y > 0 {
+                    coo.push(i, i - m, -1.0);
+                }
+                if y + 1 < m {
+                    coo.push(i, i + m, -1.0);
+                }
+            }
+        }
+        coo.to_csc()
+    }
+
+    fn from_rows(rows: &[&[f64]]) -> CscMatrix {
+        let mut coo = CooMatrix::new(rows.len(), rows[0].len());
+        for (i, row) in rows.iter().enumerate() {
+            for (j, &v) in row.iter().enumerate() {
+                if v != 0.0 {
+                    coo.push(i, j, v);
+                }
+            }
+        }
+        coo.to_csc()
+    }
+
+    fn residual(a: &CscMatrix, x: &[f64], b: &[f64]) -> f64 {
+        a.mul_vec(x).iter().zip(b).map(|(ax, bi)| (ax - bi).abs()).fold(0.0, f64::max)
+    }
+
+    fn is_permutation(perm: &[usize]) -> bool {
+        let mut sorted = perm.to_vec();
+        sorted.sort_unstable();
+        sorted.iter().enumerate().all(|(k, &v)| k == v)
+    }
+
+    #[test]
+    fn orderings_reduce_fill_on_grid() {
+        let a = laplacian_2d(24);
+        let b: Vec<f64> = (0..a.rows).map(|i| (i as f64 * 0.37).sin()).collect();
+        let natural = SymbolicCholesky::analyze(&a, FillOrdering::Natural).unwrap().nnz_l();
+        for ordering in [FillOrdering::MinimumDegree, FillOrdering::NestedDissection] {
+            let perm = fill_reducing_ordering(&a, ordering);
+            assert!(is_permutation(&perm), "{ordering:?}");
+            let symbolic = SymbolicCholesky::analyze(&a, ordering).unwrap();
+            assert!(symbolic.nnz_l() < natural, "{ordering:?}: {} vs {natural}", symbolic.nnz_l());
+            let x = symbolic.factor(&a).unwrap().solve(&b).unwrap();
+            assert!(residual(&a, &x, &b) < 1e-10, "{ordering:?}");
+        }
+    }
+
+    #[test]
+    fn cholesky_determinant_and_refactor() {
+        let a = from_rows(&[&[4.0, -1.0, 0.0], &[-1.0, 4.0, -1.0], &[0.0, -1.0, 4.0]]);
+        let f = cholesky(&a).unwrap();
+        assert!((f.determinant() - 56.0).abs() < 1e-10);
+        let (sign, log) = f.log_determinant();
+        assert_eq!(sign, 1.0);
+        assert!((log - 56f64.ln()).abs() < 1e-12);
+        assert_eq!(f.inertia(), Inertia { positive: 3, negative: 0, zero: 0 });
+
+        // Same pattern, new values: the symbolic analysis is reused.
+        let symbolic = SymbolicCholesky::analyze(&a, FillOrdering::MinimumDegree).unwrap();
+        let mut scaled = a.clone();
+        scaled.values.iter_mut().for_each(|v| *v *= 2.0);
+        let g = symbolic.factor(&scaled).unwrap();
+        assert!((g.determinant() - 448.0).abs() < 1e-9);
+        let x = g.solve(&[1.0, 2.0, 3.0]).unwrap();
+        assert!(residual(&scaled, &x, &[1.0, 2.0, 3.0]) < 1e-12);
+
+        let other = from_rows(&[&[4.0, 0.0, 0.0], &[0.0, 4.0, -1.0], &[0.0, -1.0, 4.0]]);
+        assert!(symbolic.factor(&other).is_err());
+    }
+
+    #[test]
+    fn ldlt_inertia_of_indefinite_matrix() {
+        let a = from_rows(&[&[2.0, 1.0, 0.0], &[1.0, -3.0, 1.0], &[0.0, 1.0, 1.0]]);
+        let f = SymbolicCholesky::analyze(&a, FillOrdering::MinimumDegree).unwrap().factor(&a).unwrap();
+        assert!((f.determinant() + 9.0).abs() < 1e-10);
+        assert_eq!(f.inertia(), Inertia { positive: 2, negative: 1, zero: 0 });
+        assert!(!f.is_positive_definite());
+        assert!(cholesky(&a).is_err());
+        let x = f.solve(&[1.0, 0.0, -1.0]).unwrap();
+        assert!(residual(&a, &x, &[1.0, 0.0, -1.0]) < 1e-12);
+    }
+
+    #[test]
+    fn lu_pivots_and_determinant() {
+        // Zero leading diagonal forces a row interchange.
+        let a = from_rows(&[&[0.0, 2.0, 1.0], &[1.0, 1.0, 0.0], &[3.0, 0.0, 1.0]]);
+        let f = lu(&a).unwrap();
+        assert!((f.determinant() + 5.0).abs() < 1e-12);
+        let (sign, log) = f.log_determinant();
+        assert_eq!(sign, -1.0);
+        assert!((log - 5f64.ln()).abs() < 1e-12);
+        let x = f.solve(&[3.0, 2.0, 4.0]).unwrap();
+        assert!(residual(&a, &x, &[3.0, 2.0, 4.0]) < 1e-12);
+
+        let singular = from_rows(&[&[1.0, 2.0], &[2.0, 4.0]]);
+        assert!(lu(&singular).is_err());
+    }
+
+    #[test]
+    fn lu_solves_convection_diffusion() {
+        // Upwinded convection–diffusion: nonsymmetric, reuses one analysis for two Péclet numbers.
+        let m = 20;
+        let build = |pe: f64| {
+            let mut coo = CooMatrix::new(m * m, m * m);
+            for y in 0..m {
+                for x in 0..m {
+                    let i = y * m + x;
+                    coo.push(i, i, 4.0 + pe);
+                    if x > 0 {
+                        coo.push(i, i - 1, -1.0 - pe);
+                    }
+                    if x + 1 < m {
+                        coo.push(i, i + 1, -1.0);
+                    }
+                    if y > 0 {
+                        coo.push(i, i - m, -1.0);
+                    }
+                    if y + 1 < m {
+                        coo.push(i, i + m, -1.0);
+                    }
+                }
+            }
+            coo.to_csc()
+        };
+        let symbolic = SymbolicLu::analyze(&build(0.5), FillOrdering::NestedDissection).unwrap();
+        let b: Vec<f64> = (0..m * m).map(|i| 1.0 + (i % 7) as f64).collect();
+        for pe in [0.5, 10.0] {
+            let a = build(pe);
+            let f = symbolic.factor(&a).unwrap();
+            let x = f.solve(&b).unwrap();
+            assert!(residual(&a, &x, &b) < 1e-9, "Pe = {pe}");
+        }
+    }
+}
@@ -0,0 +1,562 @@
+//! Sparse matrix interchange formats — pure Rust, no dependencies.
+//!
+//! ## Matrix Market (`.mtx`)
+//! - `coordinate` and `array` layouts
+//! - `real`, `integer` and `pattern` fields (pattern entries read as 1)
+//! - `general`, `symmetric` and `skew-symmetric` storage (mirrored on read)
+//!
+//! ## Harwell–Boeing (`.rua`, `.rsa`, `.hb`)
+//! - Assembled real and pattern matrices: `RUA`, `RSA`, `RZA`, `RRA`, `PUA`, `PSA`, …
+//! - Fixed-width Fortran fields (`(10I8)`, `(1P,4E20.12)`, `(5D16.8)`, …)
+//! - Right-hand sides, if present, are skipped
+//!
+//! ## Not supported
+//! - Complex and Hermitian fields
+//! - Elemental (unassembled) Harwell–Boeing matrices
+//!
+//! References: Boisvert, Pozo & Remington, "The Matrix Market Exchange Formats" (NIST, 1996);
+//! Duff, Grimes & Lewis, "User's Guide for the Harwell-Boeing Sparse Matrix Collection" (1992).
+
+use crate::sparse::{CooMatrix, CsrMatrix};
+
+/// Lines of a Harwell–Boeing header must fit in 80 columns.
+const HB_LINE_WIDTH: usize = 80;
+
+/// Largest row or column count read from a file header. The CSR row pointers
+/// are allocated from it, so it is capped rather than trusted.
+pub const MAX_DIMENSION: usize = 50_000_000;
+
+fn check_dimensions(rows: usize, cols: usize) -> Result<(), String> {
+    if rows > MAX_DIMENSION || cols > MAX_DIMENSION {
+        return Err(format!("{rows}×{cols} matrix exceeds the {MAX_DIMENSION} row/column limit"));
+    }
+    Ok(())
+}
+
+// ── Format detection ──────────────────────────────────────────────────────────
+
+/// Supported sparse interchange formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SparseFormat {
+    MatrixMarket,
+    HarwellBoeing,
+}
+
+impl SparseFormat {
+    /// Parse `"mtx"` / `"matrix-market"` or `"hb"` / `"harwell-boeing"`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "mtx" | "mm" | "matrix-market" | "matrixmarket" => Ok(Self::MatrixMarket),
+            "hb" | "rua" | "rsa" | "harwell-boeing" | "harwellboeing" => Ok(Self::HarwellBoeing),
+            other => Err(format!("unknown sparse format '{other}' (expected mtx or hb)")),
+        }
+    }
+
+    /// Matrix Market files start with their banner; anything else is taken as Harwell–Boeing.
+    pub fn detect(text: &str) -> Self {
+        if text.trim_start().starts_with("%%MatrixMarket") {
+            Self::MatrixMarket
+        } else {
+            Self::HarwellBoeing
+        }
+    }
+
+    /// MIME type used for exported files.
+    pub fn mime(self) -> &'static str {
+        match self {
+            Self::MatrixMarket => "text/x-matrix-market",
+            Self::HarwellBoeing => "text/x-harwell-boeing",
+        }
+    }
+}
+
+/// Read a sparse matrix in either format, detecting it from the content.
+pub fn read_sparse(text: &str) -> Result<CsrMatrix, String> {
+    match SparseFormat::detect(text) {
+        SparseFormat::MatrixMarket => read_matrix_market(text),
+        SparseFormat::HarwellBoeing => read_harwell_boeing(text),
+    }
+}
+
+/// How off-diagonal entries are mirrored when only one triangle is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Symmetry {
+    General,
+    Symmetric,
+    Skew,
+}
+
+fn push_mirrored(coo: &mut CooMatrix, i: usize, j: usize, v: f64, symmetry: Symmetry) {
+    coo.push(i, j, v);
+    if i != j {
+        match symmetry {
+            Symmetry::General => {}
+            Symmetry::Symmetric => coo.push(j, i, v),
+            Symmetry::Skew => coo.push(j, i, -v),
+        }
+    }
+}
+
+// ── Matrix Market ─────────────────────────────────────────────────────────────
+
+/// Parse a Matrix Market file.
+pub fn read_matrix_market(text: &str) -> Result<CsrMatrix, String> {
+    let mut lines = text.lines();
+    let banner = lines.next().ok_or("empty Matrix Market file")?;
+    let words: Vec<String> = banner.split_whitespace().map(|w| w.to_ascii_lowercase()).collect();
+    if words.len() != 5 || words[0] != "%%matrixmarket" || words[1] != "matrix" {
+        return Err(format!("invalid Matrix Market banner '{}'", banner.trim()));
+    }
+    let coordinate = match words[2].as_str() {
+        "coordinate" => true,
+        "array" => false,
+        other => return Err(format!("unsupported Matrix Market layout '{other}'")),
+    };
+    let pattern = match words[3].as_str() {
+        "real" | "double" | "integer" => false,
+        "pattern" if coordinate => true,
+        other => return Err(format!("unsupported Matrix Market field '{other}'")),
+    };
+    let symmetry = match words[4].as_str() {
+        "general" => Symmetry::General,
+        "symmetric" => Symmetry::Symmetric,
+        "skew-symmetric" => Symmetry::Skew,
+        other => return Err(format!("unsupported Matrix Market symmetry '{other}'")),
+    };
+
+    let mut data = lines.map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('%'));
+    let size_line = data.next().ok_or("missing Matrix Market size line")?;
+    let size: Vec<usize> = size_line
+        .split_whitespace()
+        .map(|t| t.parse().map_err(|_| format!("invalid size entry '{t}'")))
+        .collect::<Result<_, _>>()?;
+    let tokens = data.flat_map(str::split_whitespace);
+
+    if coordinate {
+        let [rows, cols, nnz] = size[..] else {
+            return Err("coordinate size line must be 'rows cols nnz'".to_string());
+        };
+        check_dimensions(rows, cols)?;
+        let per_entry = if pattern { 2 } else { 3 };
+        let tokens: Vec<&str> = tokens.collect();
+        if nnz.checked_mul(per_entry).is_none_or(|n| tokens.len() < n) {
+            return Err(format!("expected {nnz} entries, found {}", tokens.len() / per_entry));
+        }
+        let mut coo = CooMatrix::with_capacity(rows, cols, nnz);
+        for entry in tokens.chunks(per_entry).take(nnz) {
+            let i = parse_index(entry[0], rows)?;
+            let j = parse_index(entry[1], cols)?;
+            let v = if pattern { 1.0 } else { parse_real(entry[2])? };
+            push_mirrored(&mut coo, i, j, v, symmetry);
+        }
+        Ok(coo.to_csr())
+    } else {
+        let [rows, cols] = size[..] else {
+            return Err("array size line must be 'rows cols'".to_string());
+        };
+        check_dimensions(rows, cols)?;
+        let mut values = tokens.map(parse_real);
+        let mut coo = CooMatrix::new(rows, cols);
+        // Column-major; symmetric storage lists only the lower triangle
+        // (strictly lower for skew-symmetric).
+        for j in 0..cols {
+            let first = match symmetry {
+                Symmetry::General => 0,
+                Symmetry::Symmetric => j,
+                Symmetry::Skew => j + 1,
+            };
+            for i in first..rows {
+                let v = values.next().ok_or("too few values for the array size")??;
+                if v != 0.0 {
+                    push_mirrored(&mut coo, i, j, v, symmetry);
+                }
+            }
+        }
+        Ok(coo.to_csr())
+    }
+}
+
+fn parse_index(token: &str, bound: usize) -> Result<usize, String> {
+    match token.parse::<usize>() {
+        Ok(k) if (1..=bound).contains(&k) => Ok(k - 1),
+        _ => Err(format!("index '{token}' out of range 1..={bound}")),
+    }
+}
+
+fn parse_real(token: &str) -> Result<f64, String> {
+    token
+        .replace(['D', 'd'], "E")
+        .parse::<f64>()
+        .map_err(|_| format!("invalid number '{token}'"))
+}
+
+/// Write a matrix in Matrix Market coordinate format. With `symmetric`, only the
+/// lower triangle is written; the matrix must then be exactly symmetric.
+pub fn write_matrix_market(a: &CsrMatrix, symmetric: bool) -> Result<String, String> {
+    if symmetric && !a.is_symmetric(0.0) {
+        return Err("matrix is not symmetric".to_string());
+    }
+    let entries = stored_entries(a, symmetric);
+    let mut out = format!(
+        "%%MatrixMarket matrix coordinate real {}\n{} {} {}\n",
+        if symmetric { "symmetric" } else { "general" },
+        a.rows,
+        a.cols,
+        entries.len()
+    );
+    for (i, j, v) in entries {
+        out.push_str(&format!("{} {} {v:e}\n", i + 1, j + 1));
+    }
+    Ok(out)
+}
+
+/// Stored (row, col, value) triplets in row order, lower triangle only if `lower`.
+fn stored_entries(a: &CsrMatrix, lower: bool) -> Vec<(usize, usize, f64)> {
+    (0..a.rows)
+        .flat_map(|i| (a.row_ptrs[i]..a.row_ptrs[i + 1]).map(move |k| (i, k)))
+        .map(|(i, k)| (i, a.col_indices[k], a.values[k]))
+        .filter(|&(i, j, _)| !lower || j <= i)
+        .collect()
+}
+
+// ── Harwell–Boeing ────────────────────────────────────────────────────────────
+
+/// Fixed-width field layout parsed from a Fortran format such as `(1P,4E20.12)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FortranFormat {
+    per_line: usize,
+    width: usize,
+}
+
+impl FortranFormat {
+    fn parse(spec: &str) -> Result<Self, String> {
+        let upper = spec.trim().to_ascii_uppercase();
+        let body = upper.trim_start_matches('(').trim_end_matches(')');
+        // Drop a leading scale factor (`1P,` or `1P`); it has no effect on input
+        // when the fields carry explicit exponents.
+        let body = match body.find('P') {
+            Some(p) => body[p + 1..].trim_start_matches(','),
+            None => body,
+        };
+        let letter = body
+            .find(['I', 'E', 'D', 'F', 'G'])
+            .ok_or_else(|| format!("unsupported Fortran format '{spec}'"))?;
+        let per_line = if letter == 0 { 1 } else { body[..letter].parse().map_err(|_| format!("invalid repeat count in '{spec}'"))? };
+        let width_text: String = body[letter + 1..].chars().take_while(char::is_ascii_digit).collect();
+        let width = width_text.parse().map_err(|_| format!("invalid field width in '{spec}'"))?;
+        if per_line == 0 || width == 0 {
+            return Err(format!("empty fields in Fortran format '{spec}'"));
+        }
+        Ok(FortranFormat { per_line, width })
+    }
+
+    /// Read `count` fields from the given lines. `count` comes from the header,
+    /// so capacity is bounded by the fields the lines can hold.
+    fn read<'a>(self, lines: &[&'a str], count: usize) -> Result<Vec<&'a str>, String> {
+        let mut fields = Vec::with_capacity(count.min(lines.len().saturating_mul(self.per_line)));
+        for line in lines {
+            for k in 0..self.per_line {
+                let start = k.saturating_mul(self.width);
+                if start >= line.len() || fields.len() == count {
+                    break;
+                }
+                let end = start.saturating_add(self.width).min(line.len());
+                let field = line.get(start..end).ok_or("non-ASCII text in a fixed-width field")?.trim();
+                if !field.is_empty() {
+                    fields.push(field);
+                }
+            }
+        }
+        if fields.len() < count {
+            return Err(format!("expected {count} fields, found {}", fields.len()));
+        }
+        Ok(fields)
+    }
+}
+
+/// Parse a Harwell–Boeing file.
+pub fn read_harwell_boeing(text: &str) -> Result<CsrMatrix, String> {
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.len() < 4 {
+        return Err("Harwell-Boeing header needs at least four lines".to_string());
+    }
+    let counts = header_numbers(lines[1]);
+    let (ptr_lines, ind_lines, val_lines) = match counts[..] {
+        [_, p, i, v, ..] => (p, i, v),
+        _ => return Err("invalid Harwell-Boeing card-count line".to_string()),
+    };
+    let rhs_lines = counts.get(4).copied().unwrap_or(0);
+
+    let type_line = lines[2].trim_start();
+    let mxtype: String = type_line.chars().take(3).collect::<String>().to_ascii_uppercase();
+    let dims = header_numbers(type_line.get(3..).unwrap_or(""));
+    let (rows, cols, nnz) = match dims[..] {
+        [r, c, n, ..] => (r, c, n),
+        _ => return Err("invalid Harwell-Boeing dimension line".to_string()),
+    };
+    check_dimensions(rows, cols)?;
+    let kind: Vec<char> = mxtype.chars().collect();
+    if kind.len() != 3 {
+        return Err(format!("invalid matrix type '{mxtype}'"));
+    }
+    let pattern = match kind[0] {
+        'R' => false,
+        'P' => true,
+        'C' => return Err("complex Harwell-Boeing matrices are not supported".to_string()),
+        other => return Err(format!("unknown value type '{other}' in '{mxtype}'")),
+    };
+    let symmetry = match kind[1] {
+        'U' | 'R' => Symmetry::General,
+        'S' | 'H' => Symmetry::Symmetric,
+        'Z' => Symmetry::Skew,
+        other => return Err(format!("unknown structure '{other}' in '{mxtype}'")),
+    };
+    if kind[2] != 'A' {
+        return Err("elemental Harwell-Boeing matrices are not supported".to_string());
+    }
+
+    let formats: Vec<&str> = lines[3].split_whitespace().collect();
+    if formats.len() < 2 + usize::from(!pattern) {
+        return Err("missing Fortran formats in the Harwell-Boeing header".to_string());
+    }
+    let ptr_fmt = FortranFormat::parse(formats[0])?;
+    let ind_fmt = FortranFormat::parse(formats[1])?;
+
+    let start: usize = if rhs_lines > 0 { 5 } else { 4 };
+    let section = |offset: usize, len: usize| -> Result<&[&str], String> {
+        let first = start.saturating_add(offset);
+        lines
+            .get(first..first.saturating_add(len))
+            .ok_or_else(|| "Harwell-Boeing file is shorter than its card counts".to_string())
+    };
+    let parse_all = |fields: Vec<&str>| -> Result<Vec<usize>, String> {
+        fields.iter().map(|f| f.parse::<usize>().map_err(|_| format!("invalid integer '{f}'"))).collect()
+    };
+    let col_ptrs = parse_all(ptr_fmt.read(section(0, ptr_lines)?, cols + 1)?)?;
+    let row_indices = parse_all(ind_fmt.read(section(ptr_lines, ind_lines)?, nnz)?)?;
+    let values: Vec<f64> = if pattern {
+        vec![1.0; row_indices.len()]
+    } else {
+        let val_fmt = FortranFormat::parse(formats[2])?;
+        val_fmt
+            .read(section(ptr_lines.saturating_add(ind_lines), val_lines)?, nnz)?
+            .into_iter()
+            .map(parse_real)
+            .collect::<Result<_, _>>()?
+    };
+
+    if col_ptrs[0] != 1 || col_ptrs.windows(2).any(|w| w[1] < w[0]) || col_ptrs[cols] - 1 != nnz {
+        return Err("inconsistent Harwell-Boeing column pointers".to_string());
+    }
+    let mut coo = CooMatrix::with_capacity(rows, cols, nnz);
+    for j in 0..cols {
+        for k in col_ptrs[j] - 1..col_ptrs[j + 1] - 1 {
+            let i = row_indices[k];
+            if !(1..=rows).contains(&i) {
+                return Err(format!("row index {i} out of range 1..={rows}"));
+            }
+            push_mirrored(&mut coo, i - 1, j, values[k], symmetry);
+        }
+    }
+    Ok(coo.to_csr())
+}
+
+/// Integers of a free-form header line (Fortran `I14` fields are separated in practice).
+fn header_numbers(line: &str) -> Vec<usize> {
+    line.split_whitespace().map_while(|t| t.parse().ok()).collect()
+}
+
+/// Write a matrix as an assembled real Harwell–Boeing file (`RUA`, or `RSA` with
+/// `symmetric`, which stores the lower triangle). Values use `(1P,3E25.16)` so
+/// they round-trip exactly.
+pub fn write_harwell_boeing(a: &CsrMatrix, title: &str, key: &str, symmetric: bool) -> Result<String, String> {
+    if symmetric && !a.is_symmetric(0.0) {
+        return Err("matrix is not symmetric".to_string());
+    }
+    // Column-oriented storage: CSR of Aᵀ lists the columns of A.
+    let mut entries = stored_entries(a, symmetric);
+    entries.sort_by_key(|&(i, j, _)| (j, i));
+    let mut col_ptrs = vec![1usize; a.cols + 1];
+    for &(_, j, _) in &entries {
+        col_ptrs[j + 1] += 1;
+    }
+    for j in 0..a.cols {
+        col_ptrs[j + 1] += col_ptrs[j] - 1;
+    }
+    let row_indices: Vec<usize> = entries.iter().map(|&(i, _, _)| i + 1).collect();
+
+    let int_format = |max: usize| {
+        let width = max.max(1).to_string().len() + 1;
+        FortranFormat { per_line: HB_LINE_WIDTH / width, width }
+    };
+    let ptr_fmt = int_format(col_ptrs[a.cols]);
+    let ind_fmt = int_format(a.rows);
+    let val_fmt = FortranFormat { per_line: 3, width: 25 };
+
+    let ptr_text = fixed_lines(col_ptrs.iter().map(|p| format!("{p:>w$}", w = ptr_fmt.width)), ptr_fmt.per_line);
+    let ind_text = fixed_lines(row_indices.iter().map(|i| format!("{i:>w$}", w = ind_fmt.width)), ind_fmt.per_line);
+    let val_text = fixed_lines(entries.iter().map(|&(_, _, v)| format!("{:>25}", fortran_exponent(v))), val_fmt.per_line);
+    let (ptr_lines, ind_lines, val_lines) = (ptr_text.lines().count(), ind_text.lines().count(), val_text.lines().count());
+
+    let title: String = title.chars().filter(char::is_ascii).take(72).collect();
+    let key: String = key.chars().filter(char::is_ascii).take(8).collect();
+    let mut out = format!("{title:<72}{key:<8}\n");
+    out.push_str(&format!(
+        "{:>14}{:>14}{:>14}{:>14}{:>14}\n",
+        ptr_lines + ind_lines + val_lines,
+        ptr_lines,
+        ind_lines,
+        val_lines,
+        0
+    ));
+    let mxtype = if symmetric { "RSA" } else { "RUA" };
+    out.push_str(&format!("{mxtype}{:11}{:>14}{:>14}{:>14}{:>14}\n", "", a.rows, a.cols, entries.len(), 0));
+    out.push_str(&format!(
+        "{:<16}{:<16}{:<20}{:<20}\n",
+        format!("({}I{})", ptr_fmt.per_line, ptr_fmt.width),
+        format!("({}I{})", ind_fmt.per_line, ind_fmt.width),
+        "(1P,3E25.16)",
+        ""
+    ));
+    out.push_str(&ptr_text);
+    out.push_str(&ind_text);
+    out.push_str(&val_text);
+    Ok(out)
+}
+
+/// Join pre-formatted fields, `per_line` to a line.
+fn fixed_lines(fields: impl Iterator<Item = String>, per_line: usize) -> String {
+    let fields: Vec<String> = fields.collect();
+    let mut out = String::new();
+    for chunk in fields.chunks(per_line.max(1)) {
+        out.push_str(&chunk.concat());
+        out.push('\n');
+    }
+    out
+}
+
+/// `d.ddddddddddddddddE±xx` with 17 significant digits (Fortran `1PE25.16`).
+fn fortran_exponent(v: f64) -> String {
+    let text = format!("{v:.16E}");
+    match text.split_once('E') {
+        Some((mantissa, exp)) => {
+            let exp: i32 = exp.parse().unwrap_or(0);
+            let sign = if exp < 0 { '-' } else { '+' };
+            format!("{mantissa}E{sign}{:02}", exp.abs())
+        }
+        None => text, // NaN / inf
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dense(a: &CsrMatrix) -> Vec<f64> {
+        match a.to_dense() {
+            crate::types::Value::Matrix { data, .. } => data,
+            _ => unreachable!(),
+        }
+    }
+
+    fn sample() -> CsrMatrix {
+        let mut coo = CooMatrix::new(3, 4);
+        coo.push(0, 0, 4.5);
+        coo.push(0, 3, -1.0e-300);
+        coo.push(1, 1, 1.0 / 3.0);
+        coo.push(2, 0, 2.0e12);
+        coo.push(2, 2, -7.0);
+        coo.to_csr()
+    }
+
+    #[test]
+    fn matrix_market_symmetric_and_pattern() {
+        let text = "%%MatrixMarket matrix coordinate real symmetric\n\
+                    % lower triangle only\n\
+                    3 3 4\n1 1 2.0\n2 1 -1\n3 2 -1.5D0\n3 3 4e0\n";
+        let a = read_matrix_market(text).unwrap();
+        assert_eq!(dense(&a), vec![2.0, -1.0, 0.0, -1.0, 0.0, -1.5, 0.0, -1.5, 4.0]);
+
+        let skew = read_matrix_market("%%MatrixMarket matrix array real skew-symmetric\n2 2\n3\n").unwrap();
+        assert_eq!(dense(&skew), vec![0.0, -3.0, 3.0, 0.0]);
+
+        let pattern = read_matrix_market("%%MatrixMarket matrix coordinate pattern general\n2 2 2\n1 2\n2 1\n").unwrap();
+        assert_eq!(dense(&pattern), vec![0.0, 1.0, 1.0, 0.0]);
+
+        assert!(read_matrix_market("%%MatrixMarket matrix coordinate complex general\n1 1 1\n1 1 1 0\n").is_err());
+        assert!(read_matrix_market("%%MatrixMarket matrix coordinate real general\n2 2 1\n3 1 1\n").is_err());
+    }
+
+    #[test]
+    fn matrix_market_and_harwell_boeing_round_trip() {
+        let a = sample();
+        let mtx = write_matrix_market(&a, false).unwrap();
+        assert!(mtx.starts_with("%%MatrixMarket matrix coordinate real general\n3 4 5\n"));
+        assert_eq!(dense(&read_sparse(&mtx).unwrap()), dense(&a));
+
+        let hb = write_harwell_boeing(&a, "sample matrix", "SAMPLE", false).unwrap();
+        assert!(hb.lines().all(|l| l.len() <= HB_LINE_WIDTH));
+        assert_eq!(SparseFormat::detect(&hb), SparseFormat::HarwellBoeing);
+        assert_eq!(dense(&read_sparse(&hb).unwrap()), dense(&a));
+
+        let mut coo = CooMatrix::new(2, 2);
+        for (i, j, v) in [(0, 0, 2.0), (0, 1, 0.1), (1, 0, 0.1), (1, 1, 3.0)] {
+            coo.push(i, j, v);
+        }
+        let spd = coo.to_csr();
+        let rsa = write_harwell_boeing(&spd, "spd", "SPD", true).unwrap();
+        assert!(rsa.lines().nth(2).unwrap().starts_with("RSA"));
+        assert_eq!(dense(&read_harwell_boeing(&rsa).unwrap()), dense(&spd));
+        assert!(write_matrix_market(&a, true).is_err());
+    }
+
+    #[test]
+    fn harwell_boeing_fixed_width_fields() {
+        // Fields run together without separators, as Fortran writes them.
+        let text = [
+            format!("{:<72}{:<8}", "Test matrix", "TEST"),
+            format!("{:>14}{:>14}{:>14}{:>14}{:>14}", 3, 1, 1, 1, 0),
+            format!("RUA{:11}{:>14}{:>14}{:>14}{:>14}", "", 2, 2, 3, 0),
+            "(3I2)           (3I2)           (3E10.3)".to_string(),
+            " 1 3 4".to_string(),
+            " 1 2 2".to_string(),
+            " 1.000E+00-2.500E-01 4.000D+00".to_string(),
+        ]
+        .join("\n");
+        let a = read_harwell_boeing(&text).unwrap();
+        assert_eq!(dense(&a), vec![1.0, 0.0, -0.25, 4.0]);
+    }
+
+    #[test]
+    fn oversized_headers_are_errors() {
+        // Sizes far beyond the data must fail before anything is allocated.
+        let mm = |body: &str| read_matrix_market(&format!("%%MatrixMarket matrix coordinate real general\n{body}"));
+        assert!(mm("1000000000000 2 1\n1 1 1\n").unwrap_err().contains("limit"));
+        assert!(mm("2 2 18446744073709551615\n1 1 1\n").unwrap_err().contains("entries"));
+        assert!(read_matrix_market("%%MatrixMarket matrix array real general\n99999999999 1\n1\n").is_err());
+
+        let hb = |counts: (usize, usize, usize), dims: (usize, usize, usize), formats: &str, mxtype: &str| {
+            let text = [
+                format!("{:<72}{:<8}", "Test matrix", "TEST"),
+                format!("{:>14}{:>14}{:>14}{:>14}{:>14}", 3, counts.0, counts.1, counts.2, 0),
+                format!("{mxtype}{:11}{:>14}{:>14}{:>14}{:>14}", "", dims.0, dims.1, dims.2, 0),
+                formats.to_string(),
+                " 1 3 4".to_string(),
+                " 1 2 2".to_string(),
+                " 1.000E+00-2.500E-01 4.000D+00".to_string(),
+            ]
+            .join("\n");
+            read_harwell_boeing(&text)
+        };
+        let formats = "(3I2)           (3I2)           (3E10.3)";
+        assert!(hb((1, 1, 1), (2, 2, 3), formats, "RUA").is_ok());
+        let huge = 9_999_999_999_999; // widest value a 14-column header field separates
+        assert!(hb((1, 1, 1), (2, huge, 3), formats, "RUA").unwrap_err().contains("limit"));
+        assert!(hb((1, 1, 1), (2, 2, huge), formats, "PUA").unwrap_err().contains("fields"));
+        assert!(hb((1, huge, huge), (2, 2, 3), formats, "RUA").unwrap_err().contains("shorter"));
+        assert!(hb((1, 1, 1), (2, 2, 3), "(0I2) (3I2) (3E10.3)", "RUA").is_err());
+        assert!(hb((1, 1, 1), (2, 2, 3), "(3I99999999999999999999) (3I2) (3E10.3)", "RUA").is_err());
+    }
+}
@@ -142,6 +142,13 @@ impl Ilu0 {
         }
     }
 
+    /// Whether a diagonal entry of U vanished, leaving the preconditioner unusable.
+    pub fn has_zero_pivot(&self) -> bool {
+        self.diag_indices.iter().enumerate().any(|(i, &k)| {
+            self.col_indices.get(k) != Some(&i) || self.values[k].abs() < 1e-300 || !self.values[k].is_finite()
+        })
+    }
+
     /// Solve M * z = r where M ≈ LU (forward then backward substitution).
     pub fn solve(&self, r: &[f64]) -> Vec<f64> {
         let n = self.rows;
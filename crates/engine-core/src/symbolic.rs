@@ -2,8 +2,12 @@
 //!
 //! Provides a symbolic expression AST (`SymExpr`) stored as a DAG with
 //! common subexpression sharing via `Rc`. Supports construction, simplification,
-//! symbolic differentiation, polynomial arithmetic, and LaTeX rendering.
+//! symbolic differentiation, polynomial arithmetic, equation solving, series
+//! expansion, limits, and LaTeX rendering. `Display` output parses back with
+//! [`parse_expr`].
 
+use crate::rational::{BigInt, Rational};
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt;
 use std::rc::Rc;
@@ -569,7 +573,8 @@ pub fn simplify(expr: &Expr) -> Expr {
         }
         SymExpr::Function { func: f, arg } => {
             let sa = simplify(arg);
-            // Constant fold
+            // Constant fold, keeping irrational values such as sqrt(2) or
+            // ln(3) exact.
             if let SymExpr::Constant(v) = sa.as_ref() {
                 let result = match f {
                     Func::Sin => v.sin(),
@@ -586,7 +591,10 @@ pub fn simplify(expr: &Expr) -> Expr {
                     Func::Cosh => v.cosh(),
                     Func::Tanh => v.tanh(),
                 };
-                con(result)
+                match exact_fold(result) {
+                    Some(r) => con(r),
+                    None => func(*f, sa),
+                }
             } else {
                 func(*f, sa)
             }
@@ -660,16 +668,30 @@ pub fn simplify(expr: &Expr) -> Expr {
     }
 }
 
+/// A folded function or fractional power value if it is exact (an
+/// integer, or non-finite), else `None` so the expression stays symbolic.
+fn exact_fold(v: f64) -> Option<f64> {
+    if !v.is_finite() || v.fract() == 0.0 {
+        return Some(v);
+    }
+    let n = v.round();
+    ((v - n).abs() <= 4.0 * f64::EPSILON * n.abs().max(1.0)).then_some(n)
+}
+
 fn simplify_binop(op: BinOp, lhs: Expr, rhs: Expr) -> Expr {
-    // Constant folding
+    // Constant folding; fractional powers only when the result is exact.
     if let (SymExpr::Constant(a), SymExpr::Constant(b)) = (lhs.as_ref(), rhs.as_ref()) {
-        return con(match op {
-            BinOp::Add => a + b,
-            BinOp::Sub => a - b,
-            BinOp::Mul => a * b,
-            BinOp::Div => a / b,
-            BinOp::Pow => a.powf(*b),
-        });
+        return match op {
+            BinOp::Add => con(a + b),
+            BinOp::Sub => con(a - b),
+            BinOp::Mul => con(a * b),
+            BinOp::Div => con(a / b),
+            BinOp::Pow if b.fract() == 0.0 => con(a.powf(*b)),
+            BinOp::Pow => match exact_fold(a.powf(*b)) {
+                Some(r) => con(r),
+                None => binop(BinOp::Pow, lhs, rhs),
+            },
+        };
     }
     match op {
         BinOp::Add => {
@@ -809,7 +831,7 @@ impl fmt::Display for SymExpr {
             SymExpr::Variable(name) => write!(f, "{name}"),
             SymExpr::Constant(v) => {
                 if *v == std::f64::consts::PI {
-                    write!(f, "π")
+                    write!(f, "pi")
                 } else if *v == std::f64::consts::E {
                     write!(f, "e")
                 } else if v.fract() == 0.0 && v.abs() < 1e15 {
@@ -831,7 +853,7 @@ impl fmt::Display for SymExpr {
             SymExpr::UnaryOp { op, operand } => {
                 match op {
                     UnaryOp::Neg => write!(f, "(-{operand})"),
-                    UnaryOp::Abs => write!(f, "|{operand}|"),
+                    UnaryOp::Abs => write!(f, "abs({operand})"),
                     UnaryOp::Floor => write!(f, "floor({operand})"),
                     UnaryOp::Ceil => write!(f, "ceil({operand})"),
                     UnaryOp::Sign => write!(f, "sign({operand})"),
@@ -884,6 +906,8 @@ pub fn to_latex(expr: &Expr) -> String {
                 "\\pi".to_string()
             } else if *v == std::f64::consts::E {
                 "e".to_string()
+            } else if v.is_infinite() {
+                if *v > 0.0 { "\\infty".to_string() } else { "-\\infty".to_string() }
             } else if v.fract() == 0.0 && v.abs() < 1e15 {
                 format!("{}", *v as i64)
             } else {
@@ -968,18 +992,84 @@ fn to_latex_paren_if_compound(expr: &Expr) -> String {
     }
 }
 
+// ── Coefficient Domains ─────────────────────────────────────────────────────
+
+/// Coefficient field of [`Polynomial`] and [`MultiPoly`]: `f64` (the
+/// default; terms below [`COEFF_EPS`] are dropped) or [`Rational`], where
+/// division, `gcd` and Gröbner bases are exact.
+pub trait Coefficient:
+    Clone
+    + fmt::Debug
+    + fmt::Display
+    + PartialEq
+    + std::ops::Add<Output = Self>
+    + std::ops::Sub<Output = Self>
+    + std::ops::Mul<Output = Self>
+    + std::ops::Div<Output = Self>
+    + std::ops::Neg<Output = Self>
+{
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn from_i64(n: i64) -> Self;
+    /// The coefficient for a numeric constant; rationals read the shortest
+    /// decimal form, so `0.1` is exactly `1/10`. `None` if not finite.
+    fn from_f64(v: f64) -> Option<Self>;
+    fn to_f64(&self) -> f64;
+    /// Whether a term with this coefficient is dropped.
+    fn is_negligible(&self) -> bool;
+    fn is_negative(&self) -> bool;
+    fn abs(&self) -> Self;
+    /// The coefficient as an exact expression (`p/q` for fractions), or
+    /// `None` if expression constants (`f64`) cannot hold it exactly.
+    fn to_expr(&self) -> Option<Expr>;
+}
+
+impl Coefficient for f64 {
+    fn zero() -> Self { 0.0 }
+    fn one() -> Self { 1.0 }
+    fn from_i64(n: i64) -> Self { n as f64 }
+    fn from_f64(v: f64) -> Option<Self> { v.is_finite().then_some(v) }
+    fn to_f64(&self) -> f64 { *self }
+    fn is_negligible(&self) -> bool { f64::abs(*self) < COEFF_EPS }
+    fn is_negative(&self) -> bool { *self < 0.0 }
+    fn abs(&self) -> Self { f64::abs(*self) }
+    fn to_expr(&self) -> Option<Expr> { Some(con(*self)) }
+}
+
+impl Coefficient for Rational {
+    fn zero() -> Self { Rational::zero() }
+    fn one() -> Self { Rational::one() }
+    fn from_i64(n: i64) -> Self { Rational::from(n) }
+    fn from_f64(v: f64) -> Option<Self> { Rational::from_f64(v) }
+    fn to_f64(&self) -> f64 { Rational::to_f64(self) }
+    fn is_negligible(&self) -> bool { self.is_zero() }
+    fn is_negative(&self) -> bool { Rational::is_negative(self) }
+    fn abs(&self) -> Self { Rational::abs(self) }
+    /// `None` when the numerator or denominator reaches 2^53: beyond that
+    /// `f64` constants would round them.
+    fn to_expr(&self) -> Option<Expr> {
+        if self.numer().bits() > 53 || self.denom().bits() > 53 {
+            None
+        } else if self.is_integer() {
+            Some(con(self.to_f64()))
+        } else {
+            Some(div(con(self.numer().to_f64()), con(self.denom().to_f64())))
+        }
+    }
+}
+
 // ── Polynomial Utilities ────────────────────────────────────────────────────
 
 /// Represents a univariate polynomial: `coeffs[i]` is the coefficient of x^i.
 /// E.g., 3 + 2x + x^2 → coeffs = `[3.0, 2.0, 1.0]`
 #[derive(Debug, Clone, PartialEq)]
-pub struct Polynomial {
-    pub coeffs: Vec<f64>,
+pub struct Polynomial<C = f64> {
+    pub coeffs: Vec<C>,
 }
 
-impl Polynomial {
+impl<C: Coefficient> Polynomial<C> {
     /// Create from coefficient vector `[c0, c1, ..., cn]`.
-    pub fn new(coeffs: Vec<f64>) -> Self {
+    pub fn new(coeffs: Vec<C>) -> Self {
         let mut p = Polynomial { coeffs };
         p.trim();
         p
@@ -991,14 +1081,14 @@ impl Polynomial {
     }
 
     /// Constant polynomial.
-    pub fn constant(c: f64) -> Self {
-        if c == 0.0 { Self::zero() } else { Polynomial { coeffs: vec![c] } }
+    pub fn constant(c: C) -> Self {
+        if c == C::zero() { Self::zero() } else { Polynomial { coeffs: vec![c] } }
     }
 
     /// Monomial x^n.
     pub fn monomial(n: usize) -> Self {
-        let mut coeffs = vec![0.0; n + 1];
-        coeffs[n] = 1.0;
+        let mut coeffs = vec![C::zero(); n + 1];
+        coeffs[n] = C::one();
         Polynomial { coeffs }
     }
 
@@ -1012,59 +1102,59 @@ impl Polynomial {
         // Horner's method
         let mut result = 0.0;
         for c in self.coeffs.iter().rev() {
-            result = result * x + c;
+            result = result * x + c.to_f64();
         }
         result
     }
 
     /// Add two polynomials.
-    pub fn add(&self, other: &Polynomial) -> Polynomial {
+    pub fn add(&self, other: &Polynomial<C>) -> Polynomial<C> {
         let n = self.coeffs.len().max(other.coeffs.len());
-        let mut coeffs = vec![0.0; n];
+        let mut coeffs = vec![C::zero(); n];
         for (i, c) in self.coeffs.iter().enumerate() {
-            coeffs[i] += c;
+            coeffs[i] = coeffs[i].clone() + c.clone();
         }
         for (i, c) in other.coeffs.iter().enumerate() {
-            coeffs[i] += c;
+            coeffs[i] = coeffs[i].clone() + c.clone();
         }
         Polynomial::new(coeffs)
     }
 
     /// Subtract two polynomials.
-    pub fn sub(&self, other: &Polynomial) -> Polynomial {
+    pub fn sub(&self, other: &Polynomial<C>) -> Polynomial<C> {
         let n = self.coeffs.len().max(other.coeffs.len());
-        let mut coeffs = vec![0.0; n];
+        let mut coeffs = vec![C::zero(); n];
         for (i, c) in self.coeffs.iter().enumerate() {
-            coeffs[i] += c;
+            coeffs[i] = coeffs[i].clone() + c.clone();
         }
         for (i, c) in other.coeffs.iter().enumerate() {
-            coeffs[i] -= c;
+            coeffs[i] = coeffs[i].clone() - c.clone();
         }
         Polynomial::new(coeffs)
     }
 
     /// Multiply two polynomials.
-    pub fn mul(&self, other: &Polynomial) -> Polynomial {
+    pub fn mul(&self, other: &Polynomial<C>) -> Polynomial<C> {
         if self.coeffs.is_empty() || other.coeffs.is_empty() {
             return Polynomial::zero();
         }
         let n = self.coeffs.len() + other.coeffs.len() - 1;
-        let mut coeffs = vec![0.0; n];
+        let mut coeffs = vec![C::zero(); n];
         for (i, a) in self.coeffs.iter().enumerate() {
             for (j, b) in other.coeffs.iter().enumerate() {
-                coeffs[i + j] += a * b;
+                coeffs[i + j] = coeffs[i + j].clone() + a.clone() * b.clone();
             }
         }
         Polynomial::new(coeffs)
     }
 
     /// Scalar multiply.
-    pub fn scale(&self, s: f64) -> Polynomial {
-        Polynomial::new(self.coeffs.iter().map(|c| c * s).collect())
+    pub fn scale(&self, s: C) -> Polynomial<C> {
+        Polynomial::new(self.coeffs.iter().map(|c| c.clone() * s.clone()).collect())
     }
 
     /// Polynomial long division: returns (quotient, remainder).
-    pub fn div_rem(&self, divisor: &Polynomial) -> (Polynomial, Polynomial) {
+    pub fn div_rem(&self, divisor: &Polynomial<C>) -> (Polynomial<C>, Polynomial<C>) {
         if divisor.coeffs.is_empty() {
             panic!("Division by zero polynomial");
         }
@@ -1072,23 +1162,23 @@ impl Polynomial {
             return (Polynomial::zero(), self.clone());
         }
         let mut remainder = self.coeffs.clone();
-        let d_lead = *divisor.coeffs.last().unwrap();
+        let d_lead = divisor.coeffs.last().unwrap().clone();
         let d_deg = divisor.degree() as usize;
         let q_len = self.coeffs.len() - d_deg;
-        let mut quotient = vec![0.0; q_len];
+        let mut quotient = vec![C::zero(); q_len];
 
         for i in (0..q_len).rev() {
-            let q = remainder[i + d_deg] / d_lead;
-            quotient[i] = q;
+            let q = remainder[i + d_deg].clone() / d_lead.clone();
             for (j, c) in divisor.coeffs.iter().enumerate() {
-                remainder[i + j] -= q * c;
+                remainder[i + j] = remainder[i + j].clone() - q.clone() * c.clone();
             }
+            quotient[i] = q;
         }
         (Polynomial::new(quotient), Polynomial::new(remainder))
     }
 
     /// GCD of two polynomials (Euclidean algorithm).
-    pub fn gcd(&self, other: &Polynomial) -> Polynomial {
+    pub fn gcd(&self, other: &Polynomial<C>) -> Polynomial<C> {
         let mut a = self.clone();
         let mut b = other.clone();
         while !b.coeffs.is_empty() {
@@ -1097,81 +1187,84 @@ impl Polynomial {
             b = r;
         }
         // Normalize: make leading coefficient 1
-        if let Some(&lead) = a.coeffs.last() {
-            if lead != 0.0 {
-                a = a.scale(1.0 / lead);
+        if let Some(lead) = a.coeffs.last().cloned() {
+            if lead != C::zero() {
+                a = a.scale(C::one() / lead);
             }
         }
         a
     }
 
     /// Formal derivative.
-    pub fn derivative(&self) -> Polynomial {
+    pub fn derivative(&self) -> Polynomial<C> {
         if self.coeffs.len() <= 1 {
             return Polynomial::zero();
         }
-        let coeffs: Vec<f64> = self.coeffs[1..]
+        let coeffs: Vec<C> = self.coeffs[1..]
             .iter()
             .enumerate()
-            .map(|(i, c)| c * (i + 1) as f64)
+            .map(|(i, c)| c.clone() * C::from_i64(i as i64 + 1))
             .collect();
         Polynomial::new(coeffs)
     }
 
-    /// Convert to symbolic expression in given variable.
-    pub fn to_expr(&self, var: &str) -> Expr {
+    /// Convert to symbolic expression in given variable; `None` if a
+    /// coefficient has no exact expression (see [`Coefficient::to_expr`]).
+    pub fn to_expr(&self, var: &str) -> Option<Expr> {
         if self.coeffs.is_empty() {
-            return zero();
+            return Some(zero());
         }
         let x = self::var(var);
         let mut terms: Vec<Expr> = Vec::new();
-        for (i, &c) in self.coeffs.iter().enumerate() {
-            if c == 0.0 { continue; }
+        for (i, c) in self.coeffs.iter().enumerate() {
+            if *c == C::zero() { continue; }
+            let unit = *c == C::one();
             let term = if i == 0 {
-                con(c)
+                c.to_expr()?
             } else if i == 1 {
-                if c == 1.0 { x.clone() } else { mul(con(c), x.clone()) }
+                if unit { x.clone() } else { mul(c.to_expr()?, x.clone()) }
             } else {
                 let x_pow = pow(x.clone(), con(i as f64));
-                if c == 1.0 { x_pow } else { mul(con(c), x_pow) }
+                if unit { x_pow } else { mul(c.to_expr()?, x_pow) }
             };
             terms.push(term);
         }
-        match terms.len() {
+        Some(match terms.len() {
             0 => zero(),
             1 => terms.pop().unwrap(),
             _ => Rc::new(SymExpr::Sum(terms)),
-        }
+        })
     }
 
     /// Remove trailing zeros.
     fn trim(&mut self) {
-        while self.coeffs.last() == Some(&0.0) {
+        while self.coeffs.last() == Some(&C::zero()) {
             self.coeffs.pop();
         }
     }
 }
 
-impl fmt::Display for Polynomial {
+impl<C: Coefficient> fmt::Display for Polynomial<C> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.coeffs.is_empty() {
             return write!(f, "0");
         }
         let mut first = true;
-        for (i, &c) in self.coeffs.iter().enumerate().rev() {
-            if c == 0.0 { continue; }
-            if !first && c > 0.0 { write!(f, " + ")?; }
-            if !first && c < 0.0 { write!(f, " - ")?; }
-            if first && c < 0.0 { write!(f, "-")?; }
+        for (i, c) in self.coeffs.iter().enumerate().rev() {
+            if *c == C::zero() { continue; }
+            if !first && !c.is_negative() { write!(f, " + ")?; }
+            if !first && c.is_negative() { write!(f, " - ")?; }
+            if first && c.is_negative() { write!(f, "-")?; }
             let ac = c.abs();
+            let unit = ac == C::one();
             match i {
                 0 => write!(f, "{ac}")?,
                 1 => {
-                    if ac != 1.0 { write!(f, "{ac}")?; }
+                    if !unit { write!(f, "{}", coefficient_text(&ac))?; }
                     write!(f, "x")?;
                 }
                 _ => {
-                    if ac != 1.0 { write!(f, "{ac}")?; }
+                    if !unit { write!(f, "{}", coefficient_text(&ac))?; }
                     write!(f, "x^{i}")?;
                 }
             }
@@ -1181,6 +1274,13 @@ impl fmt::Display for Polynomial {
     }
 }
 
+/// A coefficient written before a variable: fractions are parenthesised so
+/// `(1/3)x` does not read as `1/(3x)`.
+fn coefficient_text<C: Coefficient>(c: &C) -> String {
+    let text = c.to_string();
+    if text.contains('/') { format!("({text})") } else { text }
+}
+
 // ── Multivariate Polynomials & Gröbner Bases ────────────────────────────────
 
 /// Monomial ordering for Gröbner basis computation.
@@ -1342,12 +1442,12 @@ impl Monomial {
 
 /// A multivariate polynomial: sparse representation as a list of (coefficient, monomial) pairs.
 #[derive(Debug, Clone)]
-pub struct MultiPoly {
+pub struct MultiPoly<C = f64> {
     /// Variable names for display. E.g., `["x", "y", "z"]`.
     pub var_names: Vec<String>,
     /// Terms sorted in descending order by the chosen monomial ordering.
     /// Invariant: no two terms share the same monomial; no zero-coefficient terms.
-    pub terms: Vec<(f64, Monomial)>,
+    pub terms: Vec<(C, Monomial)>,
     /// Monomial ordering used for this polynomial.
     pub order: MonomialOrder,
 }
@@ -1355,9 +1455,9 @@ pub struct MultiPoly {
 /// Tolerance for treating coefficients as zero.
 const COEFF_EPS: f64 = 1e-12;
 
-impl MultiPoly {
+impl<C: Coefficient> MultiPoly<C> {
     /// Create from raw terms, normalizing (sorting + combining like terms).
-    pub fn new(var_names: Vec<String>, terms: Vec<(f64, Monomial)>, order: MonomialOrder) -> Self {
+    pub fn new(var_names: Vec<String>, terms: Vec<(C, Monomial)>, order: MonomialOrder) -> Self {
         let mut poly = MultiPoly { var_names, terms, order };
         poly.normalize();
         poly
@@ -1369,8 +1469,8 @@ impl MultiPoly {
     }
 
     /// Constant polynomial.
-    pub fn constant(var_names: Vec<String>, c: f64, order: MonomialOrder) -> Self {
-        if c.abs() < COEFF_EPS {
+    pub fn constant(var_names: Vec<String>, c: C, order: MonomialOrder) -> Self {
+        if c.is_negligible() {
             return Self::zero(var_names, order);
         }
         let n = var_names.len();
@@ -1388,7 +1488,7 @@ impl MultiPoly {
         exponents[var_idx] = exp;
         MultiPoly {
             var_names,
-            terms: vec![(1.0, Monomial { exponents })],
+            terms: vec![(C::one(), Monomial { exponents })],
             order,
         }
     }
@@ -1404,7 +1504,7 @@ impl MultiPoly {
     }
 
     /// Leading term (coefficient, monomial) under the current ordering.
-    pub fn leading_term(&self) -> Option<&(f64, Monomial)> {
+    pub fn leading_term(&self) -> Option<&(C, Monomial)> {
         self.terms.first()
     }
 
@@ -1414,53 +1514,53 @@ impl MultiPoly {
     }
 
     /// Leading coefficient.
-    pub fn leading_coeff(&self) -> Option<f64> {
-        self.terms.first().map(|(c, _)| *c)
+    pub fn leading_coeff(&self) -> Option<C> {
+        self.terms.first().map(|(c, _)| c.clone())
     }
 
     /// Add two multivariate polynomials.
-    pub fn add(&self, other: &MultiPoly) -> MultiPoly {
-        let mut terms: Vec<(f64, Monomial)> = self.terms.clone();
+    pub fn add(&self, other: &MultiPoly<C>) -> MultiPoly<C> {
+        let mut terms: Vec<(C, Monomial)> = self.terms.clone();
         terms.extend(other.terms.iter().cloned());
         MultiPoly::new(self.var_names.clone(), terms, self.order)
     }
 
     /// Subtract two multivariate polynomials.
-    pub fn sub(&self, other: &MultiPoly) -> MultiPoly {
-        let neg: Vec<(f64, Monomial)> = other.terms.iter().map(|(c, m)| (-c, m.clone())).collect();
+    pub fn sub(&self, other: &MultiPoly<C>) -> MultiPoly<C> {
+        let neg: Vec<(C, Monomial)> = other.terms.iter().map(|(c, m)| (-c.clone(), m.clone())).collect();
         let mut terms = self.terms.clone();
         terms.extend(neg);
         MultiPoly::new(self.var_names.clone(), terms, self.order)
     }
 
     /// Multiply two multivariate polynomials.
-    pub fn mul(&self, other: &MultiPoly) -> MultiPoly {
+    pub fn mul(&self, other: &MultiPoly<C>) -> MultiPoly<C> {
         let mut terms = Vec::with_capacity(self.terms.len() * other.terms.len());
         for (ca, ma) in &self.terms {
             for (cb, mb) in &other.terms {
-                terms.push((ca * cb, ma.mul(mb)));
+                terms.push((ca.clone() * cb.clone(), ma.mul(mb)));
             }
         }
         MultiPoly::new(self.var_names.clone(), terms, self.order)
     }
 
     /// Scalar multiplication.
-    pub fn scale(&self, s: f64) -> MultiPoly {
-        let terms: Vec<(f64, Monomial)> = self.terms.iter().map(|(c, m)| (c * s, m.clone())).collect();
+    pub fn scale(&self, s: C) -> MultiPoly<C> {
+        let terms: Vec<(C, Monomial)> = self.terms.iter().map(|(c, m)| (c.clone() * s.clone(), m.clone())).collect();
         MultiPoly::new(self.var_names.clone(), terms, self.order)
     }
 
     /// Multiply by a monomial.
-    pub fn mul_monomial(&self, coeff: f64, mono: &Monomial) -> MultiPoly {
-        let terms: Vec<(f64, Monomial)> = self.terms.iter().map(|(c, m)| (c * coeff, m.mul(mono))).collect();
+    pub fn mul_monomial(&self, coeff: C, mono: &Monomial) -> MultiPoly<C> {
+        let terms: Vec<(C, Monomial)> = self.terms.iter().map(|(c, m)| (c.clone() * coeff.clone(), m.mul(mono))).collect();
         MultiPoly::new(self.var_names.clone(), terms, self.order)
     }
 
     /// Make the leading coefficient 1 (monic normalization).
-    pub fn make_monic(&self) -> MultiPoly {
+    pub fn make_monic(&self) -> MultiPoly<C> {
         if let Some(lc) = self.leading_coeff() {
-            if lc.abs() > COEFF_EPS {
-                return self.scale(1.0 / lc);
+            if !lc.is_negligible() {
+                return self.scale(C::one() / lc);
             }
         }
         self.clone()
@@ -1471,12 +1571,12 @@ impl MultiPoly {
     ///
     /// Includes a safety iteration limit to prevent infinite loops from
     /// floating-point imprecision in coefficient cancellation.
-    pub fn divide(&self, divisors: &[MultiPoly]) -> (Vec<MultiPoly>, MultiPoly) {
+    pub fn divide(&self, divisors: &[MultiPoly<C>]) -> (Vec<MultiPoly<C>>, MultiPoly<C>) {
         const MAX_DIVIDE_ITERATIONS: usize = 50_000;
         let order = self.order;
         let vars = self.var_names.clone();
         let n = divisors.len();
-        let mut quotients: Vec<MultiPoly> = (0..n).map(|_| MultiPoly::zero(vars.clone(), order)).collect();
+        let mut quotients: Vec<MultiPoly<C>> = (0..n).map(|_| MultiPoly::zero(vars.clone(), order)).collect();
         let mut remainder = MultiPoly::zero(vars.clone(), order);
         let mut p = self.clone();
         let mut iterations = 0usize;
@@ -1498,12 +1598,12 @@ impl MultiPoly {
             for i in 0..n {
                 if let (Some(lt_p), Some(lt_d)) = (p.leading_term(), divisors[i].leading_term()) {
                     if lt_p.1.is_divisible_by(&lt_d.1) {
-                        let coeff = lt_p.0 / lt_d.0;
+                        let coeff = lt_p.0.clone() / lt_d.0.clone();
                         let mono = lt_p.1.div(&lt_d.1);
                         // quotients[i] += coeff * mono
                         quotients[i] = quotients[i].add(&MultiPoly {
                             var_names: vars.clone(),
-                            terms: vec![(coeff, mono.clone())],
+                            terms: vec![(coeff.clone(), mono.clone())],
                             order,
                         });
                         // p -= coeff * mono * divisors[i]
@@ -1532,16 +1632,71 @@ impl MultiPoly {
     }
 
     /// Reduce `self` modulo a set of polynomials (compute normal form).
-    pub fn reduce(&self, basis: &[MultiPoly]) -> MultiPoly {
+    pub fn reduce(&self, basis: &[MultiPoly<C>]) -> MultiPoly<C> {
         let (_, remainder) = self.divide(basis);
         remainder
     }
 
+    /// Read a polynomial in `var_names` from an expression: sums,
+    /// products, non-negative integer powers and division by constants.
+    /// Any other symbol or function is an error.
+    pub fn from_expr(expr: &Expr, var_names: &[String], order: MonomialOrder) -> Result<Self, String> {
+        let recurse = |e: &Expr| MultiPoly::from_expr(e, var_names, order);
+        let constant_of = |p: &MultiPoly<C>| match p.terms.as_slice() {
+            [] => Some(C::zero()),
+            [(c, m)] if m.is_one() => Some(c.clone()),
+            _ => None,
+        };
+        let vars = var_names.to_vec();
+        match expr.as_ref() {
+            SymExpr::Variable(name) => match var_names.iter().position(|v| v == name) {
+                Some(i) => Ok(MultiPoly::var_power(vars, i, 1, order)),
+                None => Err(format!("'{name}' is not one of the variables {}", var_names.join(", "))),
+            },
+            SymExpr::Constant(v) => C::from_f64(*v)
+                .map(|c| MultiPoly::constant(vars, c, order))
+                .ok_or_else(|| format!("constant {v} is not finite")),
+            SymExpr::BinaryOp { op, lhs, rhs } => {
+                let (a, b) = (recurse(lhs)?, recurse(rhs)?);
+                match op {
+                    BinOp::Add => Ok(a.add(&b)),
+                    BinOp::Sub => Ok(a.sub(&b)),
+                    BinOp::Mul => Ok(a.mul(&b)),
+                    BinOp::Div => match constant_of(&b) {
+                        Some(d) if !d.is_negligible() => Ok(a.scale(C::one() / d)),
+                        Some(_) => Err("division by zero".to_string()),
+                        None => Err(format!("cannot divide by the non-constant polynomial {b}")),
+                    },
+                    BinOp::Pow => {
+                        let n = constant_of(&b).map(|c| c.to_f64()).unwrap_or(f64::NAN);
+                        if n.fract() != 0.0 || !(0.0..=MAX_POLY_POWER as f64).contains(&n) {
+                            return Err(format!("exponent {rhs} is not a non-negative integer"));
+                        }
+                        let one = MultiPoly::constant(vars, C::one(), order);
+                        Ok((0..n as u32).fold(one, |acc, _| acc.mul(&a)))
+                    }
+                }
+            }
+            SymExpr::UnaryOp { op: UnaryOp::Neg, operand } => Ok(recurse(operand)?.scale(-C::one())),
+            SymExpr::Sum(terms) => terms.iter().try_fold(MultiPoly::zero(vars, order), |acc, t| Ok(acc.add(&recurse(t)?))),
+            SymExpr::Product(factors) => factors
+                .iter()
+                .try_fold(MultiPoly::constant(vars, C::one(), order), |acc, f| Ok(acc.mul(&recurse(f)?))),
+            _ => Err(format!("'{expr}' is not a polynomial")),
+        }
+    }
+
+    /// The same polynomial with `f64` coefficients.
+    pub fn to_f64(&self) -> MultiPoly {
+        let terms = self.terms.iter().map(|(c, m)| (c.to_f64(), m.clone())).collect();
+        MultiPoly::new(self.var_names.clone(), terms, self.order)
+    }
+
     /// Evaluate at a point (variable values).
     pub fn eval_at(&self, values: &[f64]) -> f64 {
         let mut result = 0.0;
         for (coeff, mono) in &self.terms {
-            let mut term_val = *coeff;
+            let mut term_val = coeff.to_f64();
             for (i, &exp) in mono.exponents.iter().enumerate() {
                 if exp > 0 && i < values.len() {
                     term_val *= values[i].powi(exp as i32);
@@ -1559,11 +1714,11 @@ impl MultiPoly {
         self.terms.sort_by(|a, b| b.1.cmp_order(&a.1, order));
 
         // Combine like terms
-        let mut combined: Vec<(f64, Monomial)> = Vec::with_capacity(self.terms.len());
+        let mut combined: Vec<(C, Monomial)> = Vec::with_capacity(self.terms.len());
         for (c, m) in self.terms.drain(..) {
             if let Some(last) = combined.last_mut() {
                 if last.1 == m {
-                    last.0 += c;
+                    last.0 = last.0.clone() + c;
                     continue;
                 }
             }
@@ -1571,34 +1726,31 @@ impl MultiPoly {
         }
 
         // Remove near-zero coefficients
-        combined.retain(|(c, _)| c.abs() > COEFF_EPS);
+        combined.retain(|(c, _)| !c.is_negligible());
         self.terms = combined;
     }
 }
 
-impl fmt::Display for MultiPoly {
+impl<C: Coefficient> fmt::Display for MultiPoly<C> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.terms.is_empty() {
             return write!(f, "0");
         }
         let mut first = true;
         for (coeff, mono) in &self.terms {
-            let c = *coeff;
-            if !first && c > 0.0 {
+            if !first && !coeff.is_negative() {
                 write!(f, " + ")?;
-            } else if !first && c < 0.0 {
+            } else if !first && coeff.is_negative() {
                 write!(f, " - ")?;
-            } else if first && c < 0.0 {
+            } else if first && coeff.is_negative() {
                 write!(f, "-")?;
             }
-            let ac = c.abs();
+            let ac = coeff.abs();
             let is_const = mono.is_one();
-            if is_const || (ac - 1.0).abs() > COEFF_EPS {
-                if is_const {
-                    write!(f, "{ac}")?;
-                } else {
-                    write!(f, "{ac}")?;
-                }
+            if is_const {
+                write!(f, "{ac}")?;
+            } else if !(ac.clone() - C::one()).is_negligible() {
+                write!(f, "{}", coefficient_text(&ac))?;
             }
             if !is_const {
                 for (i, &exp) in mono.exponents.iter().enumerate() {
@@ -1625,7 +1777,7 @@ impl fmt::Display for MultiPoly {
 /// S-polynomial of two polynomials f, g.
 ///
 /// S(f,g) = (lcm(LM(f),LM(g)) / LT(f)) * f  -  (lcm(LM(f),LM(g)) / LT(g)) * g
-pub fn s_polynomial(f: &MultiPoly, g: &MultiPoly) -> MultiPoly {
+pub fn s_polynomial<C: Coefficient>(f: &MultiPoly<C>, g: &MultiPoly<C>) -> MultiPoly<C> {
     let lt_f = match f.leading_term() {
         Some(t) => t,
         None => return MultiPoly::zero(f.var_names.clone(), f.order),
@@ -1641,17 +1793,17 @@ pub fn s_polynomial(f: &MultiPoly, g: &MultiPoly) -> MultiPoly {
     let div_f = lcm_mono.div(&lt_f.1);
     let div_g = lcm_mono.div(&lt_g.1);
 
-    let scaled_f = f.mul_monomial(1.0 / lt_f.0, &div_f);
-    let scaled_g = g.mul_monomial(1.0 / lt_g.0, &div_g);
+    let scaled_f = f.mul_monomial(C::one() / lt_f.0.clone(), &div_f);
+    let scaled_g = g.mul_monomial(C::one() / lt_g.0.clone(), &div_g);
 
     scaled_f.sub(&scaled_g)
 }
 
 /// Result of Gröbner basis computation.
 #[derive(Debug, Clone)]
-pub struct GroebnerResult {
+pub struct GroebnerResult<C = f64> {
     /// The computed Gröbner basis.
-    pub basis: Vec<MultiPoly>,
+    pub basis: Vec<MultiPoly<C>>,
     /// Number of S-polynomials computed during the algorithm.
     pub s_poly_count: usize,
     /// Number of reductions to zero (indicates efficiency of criteria).
@@ -1668,8 +1820,10 @@ pub struct GroebnerResult {
 /// * `max_iterations` - Safety limit on number of iterations (default: 10000)
 ///
 /// # Returns
-/// A `GroebnerResult` containing the reduced Gröbner basis.
-pub fn groebner_basis(generators: &[MultiPoly], max_iterations: usize) -> GroebnerResult {
+/// A `GroebnerResult` containing the reduced Gröbner basis. With
+/// [`Rational`] coefficients every step is exact; with `f64`, cancellation
+/// can leave spurious near-zero terms on ill-conditioned systems.
+pub fn groebner_basis<C: Coefficient>(generators: &[MultiPoly<C>], max_iterations: usize) -> GroebnerResult<C> {
     if generators.is_empty() {
         return GroebnerResult {
             basis: vec![],
@@ -1682,7 +1836,7 @@ pub fn groebner_basis(generators: &[MultiPoly], max_iterations: usize) -> Groebn
     let vars = generators[0].var_names.clone();
 
     // Start with normalized copies
-    let mut basis: Vec<MultiPoly> = generators.iter()
+    let mut basis: Vec<MultiPoly<C>> = generators.iter()
         .filter(|g| !g.is_zero())
         .map(|g| g.make_monic())
         .collect();
@@ -1787,9 +1941,9 @@ pub fn groebner_basis(generators: &[MultiPoly], max_iterations: usize) -> Groebn
 }
 
 /// Reduce a Gröbner basis: remove redundant generators and inter-reduce.
-fn reduce_basis(basis: &[MultiPoly], _vars: &[String], order: MonomialOrder) -> Vec<MultiPoly> {
+fn reduce_basis<C: Coefficient>(basis: &[MultiPoly<C>], _vars: &[String], order: MonomialOrder) -> Vec<MultiPoly<C>> {
     // Step 1: Remove generators whose LM is divisible by another generator's LM
-    let mut minimal: Vec<MultiPoly> = Vec::new();
+    let mut minimal: Vec<MultiPoly<C>> = Vec::new();
     for (i, gi) in basis.iter().enumerate() {
         if gi.is_zero() { continue; }
         let lm_i = match gi.leading_monomial() {
@@ -1823,7 +1977,7 @@ fn reduce_basis(basis: &[MultiPoly], _vars: &[String], order: MonomialOrder) ->
         }
         let mut i = 0;
         while i < reduced.len() {
-            let others: Vec<MultiPoly> = reduced.iter().enumerate()
+            let others: Vec<MultiPoly<C>> = reduced.iter().enumerate()
                 .filter(|&(j, _)| j != i)
                 .map(|(_, p)| p.clone())
                 .collect();
@@ -1886,23 +2040,46 @@ pub fn solve_polynomial_system(
         return Err("Empty polynomial system".to_string());
     }
 
-    let var_names = system[0].var_names.clone();
-    let n_vars = var_names.len();
-
     // Convert to lex order for elimination
     let lex_system: Vec<MultiPoly> = system.iter().map(|p| {
         MultiPoly::new(p.var_names.clone(), p.terms.clone(), MonomialOrder::Lex)
     }).collect();
 
     let result = groebner_basis(&lex_system, max_iterations);
+    solve_lex_basis(&result.basis, &system[0].var_names, max_iterations)
+}
+
+/// [`solve_polynomial_system`] with the Gröbner basis computed exactly over
+/// the rationals; only the back-substitution is numeric.
+pub fn solve_polynomial_system_exact(
+    system: &[MultiPoly<Rational>],
+    max_iterations: usize,
+) -> Result<Vec<Vec<f64>>, String> {
+    if system.is_empty() {
+        return Err("Empty polynomial system".to_string());
+    }
+    let lex_system: Vec<MultiPoly<Rational>> = system.iter().map(|p| {
+        MultiPoly::new(p.var_names.clone(), p.terms.clone(), MonomialOrder::Lex)
+    }).collect();
+    let basis: Vec<MultiPoly> = groebner_basis(&lex_system, max_iterations).basis.iter().map(MultiPoly::to_f64).collect();
+    solve_lex_basis(&basis, &system[0].var_names, max_iterations)
+}
+
+/// Real solutions from a lex Gröbner basis by back-substitution.
+fn solve_lex_basis(
+    basis: &[MultiPoly],
+    var_names: &[String],
+    max_iterations: usize,
+) -> Result<Vec<Vec<f64>>, String> {
+    let n_vars = var_names.len();
 
-    if result.basis.is_empty() {
+    if basis.is_empty() {
         return Err("System has no solutions (basis is empty or all generators are zero)".to_string());
     }
 
     // Check for inconsistency: if basis = {1}, no solutions
-    if result.basis.len() == 1 {
-        if let Some((c, m)) = result.basis[0].leading_term() {
+    if basis.len() == 1 {
+        if let Some((c, m)) = basis[0].leading_term() {
             if m.is_one() && c.abs() > COEFF_EPS {
                 return Err("System is inconsistent (no solutions)".to_string());
             }
@@ -1916,7 +2093,7 @@ pub fn solve_polynomial_system(
 
     // Find a univariate polynomial in the last variable
     let mut univariate: Option<&MultiPoly> = None;
-    for poly in &result.basis {
+    for poly in basis {
         let is_univariate = poly.terms.iter().all(|(_, m)| {
             m.exponents.iter().enumerate().all(|(i, &e)| i == last_var_idx || e == 0)
         });
@@ -1961,7 +2138,7 @@ pub fn solve_polynomial_system(
     for &root_val in &roots {
         // Substitute the last variable's value into the remaining polynomials
         let mut remaining: Vec<MultiPoly> = Vec::new();
-        for poly in &result.basis {
+        for poly in basis {
             let substituted = substitute_var_in_multipoly(poly, last_var_idx, root_val);
             if !substituted.is_zero() {
                 remaining.push(substituted);
@@ -2441,6 +2618,8 @@ fn parse_atom(tokens: &[Token], pos: &mut usize) -> Result<Expr, String> {
                     _ => return Err("Expected ')' after function argument".to_string()),
                 }
                 let f = match name.to_lowercase().as_str() {
+                    "abs" => return Ok(unop(UnaryOp::Abs, arg)),
+                    "sign" | "sgn" => return Ok(unop(UnaryOp::Sign, arg)),
                     "sin" => Func::Sin,
                     "cos" => Func::Cos,
                     "tan" => Func::Tan,
@@ -2584,11 +2763,1236 @@ fn expand_power(base: &Expr, n: u32) -> Expr {
     }
 }
 
+// ── Equation Solving ────────────────────────────────────────────────────────
+
+/// Parse `lhs = rhs` into the expression `lhs - rhs`; text without `=` is
+/// taken as `expr = 0`.
+pub fn parse_equation(s: &str) -> Result<Expr, String> {
+    match s.split_once('=') {
+        Some((lhs, rhs)) => Ok(sub(parse_expr(lhs)?, parse_expr(rhs)?)),
+        None => parse_expr(s),
+    }
+}
+
+/// Sample values for the probabilistic zero test below.
+const ZERO_TEST_POINTS: [f64; 3] = [0.6180339887, 1.3247179572, -0.9189385332];
+
+/// Whether `expr` is identically zero: it folds to the constant 0, or it
+/// vanishes at several sample assignments of its free variables (`simplify`
+/// does not collect like terms, so `a*b - b*a` only shows up numerically).
+pub(crate) fn is_zero_expr(expr: &Expr) -> bool {
+    let s = simplify(expr);
+    if let SymExpr::Constant(v) = s.as_ref() {
+        return v.abs() < COEFF_EPS;
+    }
+    let names = free_variables(&s);
+    (0..ZERO_TEST_POINTS.len()).all(|k| {
+        let vars: HashMap<String, f64> = names
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (n.clone(), ZERO_TEST_POINTS[(i + k) % ZERO_TEST_POINTS.len()] + 0.1 * i as f64))
+            .collect();
+        eval(&s, &vars).abs() < 1e-9
+    })
+}
+
+/// Simplify, replacing anything identically zero by the constant 0.
+pub(crate) fn clean(expr: &Expr) -> Expr {
+    if is_zero_expr(expr) { zero() } else { simplify(expr) }
+}
+
+/// The numeric value of `expr` if it has no free variables.
+pub(crate) fn constant_value(expr: &Expr) -> Option<f64> {
+    match simplify(expr).as_ref() {
+        SymExpr::Constant(v) => Some(*v),
+        // Closed forms such as sqrt(2) stay exact under `simplify`.
+        _ if free_variables(expr).is_empty() => Some(eval(expr, &HashMap::new())),
+        _ => None,
+    }
+}
+
+/// Largest power expanded when reading an expression as a polynomial.
+const MAX_POLY_POWER: i32 = 64;
+
+/// An integer-valued constant exponent within `±MAX_POLY_POWER`.
+fn integer_exponent(expr: &Expr) -> Option<i32> {
+    match expr.as_ref() {
+        SymExpr::Constant(v) if v.fract() == 0.0 && v.abs() <= MAX_POLY_POWER as f64 => Some(*v as i32),
+        SymExpr::UnaryOp { op: UnaryOp::Neg, operand } => integer_exponent(operand).map(|n| -n),
+        _ => None,
+    }
+}
+
+/// Coefficients `[c0, c1, …]` of `expr` read as a polynomial in `var`
+/// (coefficients may contain other symbols), or `None` when `var` appears
+/// other than in non-negative integer powers.
+pub fn polynomial_coefficients(expr: &Expr, var: &str) -> Option<Vec<Expr>> {
+    Some(poly_trim(poly_coeffs(expr, var)?.iter().map(clean).collect()))
+}
+
+fn poly_coeffs(expr: &Expr, var: &str) -> Option<Vec<Expr>> {
+    if is_constant(expr, var) {
+        return Some(vec![expr.clone()]);
+    }
+    match expr.as_ref() {
+        SymExpr::Variable(_) => Some(vec![zero(), one()]),
+        SymExpr::BinaryOp { op, lhs, rhs } => match op {
+            BinOp::Add => Some(poly_add(&poly_coeffs(lhs, var)?, &poly_coeffs(rhs, var)?)),
+            BinOp::Sub => Some(poly_add(&poly_coeffs(lhs, var)?, &poly_neg(&poly_coeffs(rhs, var)?))),
+            BinOp::Mul => Some(poly_mul(&poly_coeffs(lhs, var)?, &poly_coeffs(rhs, var)?)),
+            BinOp::Div if is_constant(rhs, var) => {
+                Some(poly_coeffs(lhs, var)?.into_iter().map(|c| simplify(&div(c, rhs.clone()))).collect())
+            }
+            BinOp::Pow => match integer_exponent(rhs) {
+                Some(n) if n >= 0 => Some(poly_pow(&poly_coeffs(lhs, var)?, n as u32)),
+                _ => None,
+            },
+            BinOp::Div => None,
+        },
+        SymExpr::UnaryOp { op: UnaryOp::Neg, operand } => Some(poly_neg(&poly_coeffs(operand, var)?)),
+        SymExpr::Sum(terms) => terms
+            .iter()
+            .try_fold(vec![zero()], |acc, t| Some(poly_add(&acc, &poly_coeffs(t, var)?))),
+        SymExpr::Product(factors) => factors
+            .iter()
+            .try_fold(vec![one()], |acc, f| Some(poly_mul(&acc, &poly_coeffs(f, var)?))),
+        SymExpr::UnaryOp { .. } | SymExpr::Function { .. } | SymExpr::Constant(_) => None,
+    }
+}
+
+fn poly_trim(mut p: Vec<Expr>) -> Vec<Expr> {
+    while p.len() > 1 && p.last().is_some_and(is_zero) {
+        p.pop();
+    }
+    p
+}
+
+fn poly_add(a: &[Expr], b: &[Expr]) -> Vec<Expr> {
+    (0..a.len().max(b.len()))
+        .map(|i| match (a.get(i), b.get(i)) {
+            (Some(x), Some(y)) => simplify(&add(x.clone(), y.clone())),
+            (Some(x), None) | (None, Some(x)) => x.clone(),
+            (None, None) => zero(),
+        })
+        .collect()
+}
+
+fn poly_neg(a: &[Expr]) -> Vec<Expr> {
+    a.iter().map(|c| simplify(&neg(c.clone()))).collect()
+}
+
+fn poly_mul(a: &[Expr], b: &[Expr]) -> Vec<Expr> {
+    let mut out = vec![zero(); a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            out[i + j] = simplify(&add(out[i + j].clone(), mul(x.clone(), y.clone())));
+        }
+    }
+    out
+}
+
+fn poly_pow(a: &[Expr], n: u32) -> Vec<Expr> {
+    (0..n).fold(vec![one()], |acc, _| poly_mul(&acc, a))
+}
+
+/// Evaluate coefficient list `p` at `x` (Horner).
+fn poly_at(p: &[Expr], x: &Expr) -> Expr {
+    simplify(&p.iter().rev().fold(zero(), |acc, c| add(mul(acc, x.clone()), c.clone())))
+}
+
+/// `expr` as a quotient of polynomials in `var` (numerator, denominator).
+fn rational_parts(expr: &Expr, var: &str) -> Option<(Vec<Expr>, Vec<Expr>)> {
+    if let Some(p) = poly_coeffs(expr, var) {
+        return Some((p, vec![one()]));
+    }
+    match expr.as_ref() {
+        SymExpr::BinaryOp { op, lhs, rhs } => {
+            let (n1, d1) = rational_parts(lhs, var)?;
+            match op {
+                BinOp::Pow => {
+                    let n = integer_exponent(rhs)?;
+                    let (n1, d1) = if n < 0 { (d1, n1) } else { (n1, d1) };
+                    Some((poly_pow(&n1, n.unsigned_abs()), poly_pow(&d1, n.unsigned_abs())))
+                }
+                _ => {
+                    let (n2, d2) = rational_parts(rhs, var)?;
+                    match op {
+                        BinOp::Add => Some((poly_add(&poly_mul(&n1, &d2), &poly_mul(&n2, &d1)), poly_mul(&d1, &d2))),
+                        BinOp::Sub => Some((
+                            poly_add(&poly_mul(&n1, &d2), &poly_neg(&poly_mul(&n2, &d1))),
+                            poly_mul(&d1, &d2),
+                        )),
+                        BinOp::Mul => Some((poly_mul(&n1, &n2), poly_mul(&d1, &d2))),
+                        BinOp::Div => Some((poly_mul(&n1, &d2), poly_mul(&d1, &n2))),
+                        BinOp::Pow => None,
+                    }
+                }
+            }
+        }
+        SymExpr::UnaryOp { op: UnaryOp::Neg, operand } => {
+            let (n, d) = rational_parts(operand, var)?;
+            Some((poly_neg(&n), d))
+        }
+        SymExpr::Sum(terms) => terms.iter().try_fold((vec![zero()], vec![one()]), |(n1, d1), t| {
+            let (n2, d2) = rational_parts(t, var)?;
+            Some((poly_add(&poly_mul(&n1, &d2), &poly_mul(&n2, &d1)), poly_mul(&d1, &d2)))
+        }),
+        SymExpr::Product(factors) => factors.iter().try_fold((vec![one()], vec![one()]), |(n1, d1), f| {
+            let (n2, d2) = rational_parts(f, var)?;
+            Some((poly_mul(&n1, &n2), poly_mul(&d1, &d2)))
+        }),
+        _ => None,
+    }
+}
+
+/// Solve `expr = 0` for `var`.
+///
+/// Polynomial and rational equations are solved in closed form through
+/// quartics (exact rational and radical roots, real only for numeric
+/// coefficients; higher numeric degrees fall back to companion-matrix
+/// eigenvalues), and
+/// roots of a rational equation's denominator are discarded. Otherwise the
+/// single occurrence of `var` is isolated by inverting `exp`, `ln`, powers,
+/// trigonometric and hyperbolic functions (principal branches; `sin`/`cos`
+/// give both solutions in one period). Numeric solutions are returned in
+/// ascending order.
+pub fn solve(expr: &Expr, var: &str) -> Result<Vec<Expr>, String> {
+    if is_constant(expr, var) {
+        return Err(format!("the equation does not contain '{var}'"));
+    }
+    let roots = match rational_parts(expr, var) {
+        Some((num, den)) => {
+            let num = poly_trim(num.iter().map(clean).collect());
+            if num.len() == 1 && is_zero(&num[0]) {
+                return Err(format!("the equation holds for every value of '{var}'"));
+            }
+            polynomial_roots(&num)?
+                .into_iter()
+                .filter(|r| !vanishes(&poly_at(&den, r)))
+                .collect()
+        }
+        None => {
+            let names = free_variables(expr);
+            isolate(expr, &zero(), var)?
+                .into_iter()
+                .map(|r| simplify(&r))
+                .filter(|r| match constant_value(r) {
+                    // Branch inversions can introduce extraneous roots
+                    // (e.g. sqrt(x) = -1); check numeric ones against the equation.
+                    Some(v) if names.len() == 1 => {
+                        let at = HashMap::from([(var.to_string(), v)]);
+                        v.is_finite() && eval(expr, &at).abs() <= 1e-9 * (1.0 + v.abs())
+                    }
+                    Some(v) => v.is_finite(),
+                    None => true,
+                })
+                .collect()
+        }
+    };
+    Ok(sort_roots(roots))
+}
+
+/// Numeric roots ascending and deduplicated by value, followed by symbolic ones.
+fn sort_roots(roots: Vec<Expr>) -> Vec<Expr> {
+    let mut numeric = Vec::new();
+    let mut symbolic = Vec::new();
+    for r in roots {
+        match constant_value(&r) {
+            Some(v) => numeric.push((v, r)),
+            None => symbolic.push(r),
+        }
+    }
+    numeric.sort_by(|a, b| a.0.total_cmp(&b.0));
+    numeric.dedup_by(|a, b| (a.0 - b.0).abs() <= 1e-9 * (1.0 + b.0.abs()));
+    numeric.into_iter().map(|(_, r)| r).chain(symbolic).collect()
+}
+
+/// Whether `expr` is zero, allowing round-off for numeric values.
+fn vanishes(expr: &Expr) -> bool {
+    match constant_value(expr) {
+        Some(v) => v.abs() < 1e-9,
+        None => is_zero_expr(expr),
+    }
+}
+
+/// Roots of the polynomial with coefficients `coeffs`, in closed form
+/// through quartics.
+///
+/// Rational coefficients give exact real roots: rational roots are split off
+/// first and the remaining factor is solved in radicals (higher numeric
+/// degrees fall back to floating-point roots). Symbolic coefficients give the
+/// quadratic formula, Cardano's real root of a cubic and Ferrari's four roots
+/// of a quartic.
+fn polynomial_roots(coeffs: &[Expr]) -> Result<Vec<Expr>, String> {
+    let shift = coeffs.iter().take_while(|c| is_zero(c)).count();
+    let mut roots = if shift > 0 { vec![zero()] } else { Vec::new() };
+    let p = &coeffs[shift..];
+    let degree = p.len().saturating_sub(1);
+    if degree == 0 {
+        return Ok(roots);
+    }
+    if let Some(exact) = p.iter().map(as_rational).collect::<Option<Vec<Rational>>>() {
+        roots.extend(rational_polynomial_roots(exact));
+    } else if degree <= 4 {
+        // Irrational constants decide branches by value; complex roots evaluate to NaN.
+        roots.extend(radical_roots(p).into_iter().filter(|r| constant_value(r).is_none_or(f64::is_finite)));
+    } else if let Some(numeric) = p.iter().map(constant_value).collect::<Option<Vec<f64>>>() {
+        roots.extend(real_polynomial_roots(&numeric).into_iter().map(con));
+    } else {
+        return Err(format!(
+            "closed-form roots with symbolic coefficients are limited to quartics (degree {degree})"
+        ));
+    }
+    Ok(roots)
+}
+
+/// Real roots of a polynomial with rational coefficients `p` (ascending).
+fn rational_polynomial_roots(mut p: Vec<Rational>) -> Vec<Expr> {
+    let mut roots = Vec::new();
+    // Rational roots: snap each floating-point root to a nearby fraction and
+    // keep it if it is an exact root, then deflate.
+    'deflate: while p.len() > 2 {
+        let numeric: Vec<f64> = p.iter().map(Rational::to_f64).collect();
+        for r in real_polynomial_roots(&numeric) {
+            if let Some(q) = nearby_rational(r, 1_000_000, 1e-9).filter(|q| horner(&p, q).is_zero()) {
+                p = deflate(&p, &q);
+                roots.push(rational_expr(&q));
+                continue 'deflate;
+            }
+        }
+        break;
+    }
+    if p.len() <= 5 {
+        let rest: Vec<Expr> = p.iter().map(rational_expr).collect();
+        roots.extend(radical_roots(&rest));
+    } else {
+        let numeric: Vec<f64> = p.iter().map(Rational::to_f64).collect();
+        roots.extend(real_polynomial_roots(&numeric).into_iter().map(con));
+    }
+    roots
+}
+
+/// `p(x)` by Horner's rule.
+fn horner(p: &[Rational], x: &Rational) -> Rational {
+    p.iter().rev().fold(Rational::zero(), |acc, c| &(&acc * x) + c)
+}
+
+/// `p(x) / (x − r)` for a root `r` of `p`.
+fn deflate(p: &[Rational], r: &Rational) -> Vec<Rational> {
+    let n = p.len() - 1;
+    let mut q = vec![Rational::zero(); n];
+    q[n - 1] = p[n].clone();
+    for i in (1..n).rev() {
+        q[i - 1] = &p[i] + &(r * &q[i]);
+    }
+    q
+}
+
+/// The continued-fraction convergent of `v` with the smallest denominator
+/// (at most `max_den`) within `tol` relative error.
+fn nearby_rational(v: f64, max_den: i64, tol: f64) -> Option<Rational> {
+    if !v.is_finite() || v.abs() >= 2f64.powi(53) {
+        return None;
+    }
+    let (mut h0, mut h1, mut k0, mut k1) = (0i64, 1i64, 1i64, 0i64);
+    let mut x = v;
+    loop {
+        let a = x.floor() as i64;
+        let h = a.checked_mul(h1)?.checked_add(h0)?;
+        let k = a.checked_mul(k1)?.checked_add(k0)?;
+        if k > max_den {
+            return None;
+        }
+        if (v - h as f64 / k as f64).abs() <= tol * v.abs().max(f64::MIN_POSITIVE) {
+            return Some(Rational::new(BigInt::from(h), BigInt::from(k)));
+        }
+        (h0, h1, k0, k1) = (h1, h, k1, k);
+        let frac = x - x.floor();
+        if frac == 0.0 {
+            return None;
+        }
+        x = 1.0 / frac;
+    }
+}
+
+/// `expr` as an exact rational: an integer constant, a constant within
+/// round-off of a simple fraction (so 0.1 reads as 1/10), or a quotient or
+/// negation of those.
+fn as_rational(expr: &Expr) -> Option<Rational> {
+    match expr.as_ref() {
+        SymExpr::Constant(v) if v.fract() == 0.0 => Rational::from_f64(*v),
+        SymExpr::Constant(v) => nearby_rational(*v, 1_000_000, 4.0 * f64::EPSILON),
+        SymExpr::BinaryOp { op: BinOp::Div, lhs, rhs } => as_rational(lhs)?.checked_div(&as_rational(rhs)?),
+        SymExpr::UnaryOp { op: UnaryOp::Neg, operand } => Some(-as_rational(operand)?),
+        _ => None,
+    }
+}
+
+/// `r` as an expression: a constant when it is an integer or a short binary
+/// fraction, else `n / d`.
+fn rational_expr(r: &Rational) -> Expr {
+    let (n, d) = (r.numer(), r.denom());
+    if n.bits() > 53 || d.bits() > 53 {
+        return con(r.to_f64());
+    }
+    let (n, d) = (n.to_f64(), d.to_f64());
+    if d <= 1024.0 && (d as u64).is_power_of_two() {
+        con(n / d)
+    } else {
+        div(con(n), con(d))
+    }
+}
+
+/// The exponent 1/3 of a cube root.
+fn third() -> Expr {
+    div(one(), con(3.0))
+}
+
+/// The real square root (`k = 2`, `r ≥ 0`) or cube root (`k = 3`) of `r`
+/// with perfect powers taken out, e.g. √8 = 2·√2 and ∛(−16) = −2·∛2.
+fn rational_root(r: &Rational, k: u32) -> Expr {
+    // ᵏ√(n/d) = ᵏ√(n·dᵏ⁻¹) / d
+    let radicand = &r.numer().abs() * &r.denom().pow(k - 1);
+    let root = |e: Expr| if k == 2 { sqrt(e) } else { pow(e, third()) };
+    if radicand.bits() > 53 {
+        let root = root(rational_expr(&r.abs()));
+        return if r.is_negative() { neg(root) } else { root };
+    }
+    let (s, t) = split_power(radicand.to_f64() as u64, k);
+    let coef = Rational::new(BigInt::from(s as i64), r.denom().clone());
+    let coef = if r.is_negative() { -coef } else { coef };
+    if t == 1 {
+        return rational_expr(&coef);
+    }
+    simplify_binop(BinOp::Mul, rational_expr(&coef), root(con(t as f64)))
+}
+
+/// `(s, t)` with `n = sᵏ·t`, taking out factors up to 10⁴ and a remaining
+/// perfect `k`-th power.
+fn split_power(mut n: u64, k: u32) -> (u64, u64) {
+    let mut s = 1;
+    let mut f = 2u64;
+    while f <= 10_000 && f.pow(k) <= n {
+        while n.is_multiple_of(f.pow(k)) {
+            n /= f.pow(k);
+            s *= f;
+        }
+        f += 1;
+    }
+    let r = (n as f64).powf(1.0 / k as f64).round() as u64;
+    match (r.saturating_sub(1)..=r + 1).find(|&c| c > 1 && c.checked_pow(k) == Some(n)) {
+        Some(c) => (s * c, 1),
+        None => (s, n),
+    }
+}
+
+/// `lhs op rhs`, exact when both sides are rational. Otherwise only the
+/// identities of `simplify_binop` apply, so operands such as `2^(1/3)` are
+/// not rounded to floats.
+fn exact_op(op: BinOp, lhs: Expr, rhs: Expr) -> Expr {
+    if let (Some(a), Some(b)) = (as_rational(&lhs), as_rational(&rhs)) {
+        let r = match op {
+            BinOp::Add => Some(&a + &b),
+            BinOp::Sub => Some(&a - &b),
+            BinOp::Mul => Some(&a * &b),
+            BinOp::Div => a.checked_div(&b),
+            BinOp::Pow => None,
+        };
+        if let Some(r) = r {
+            return rational_expr(&r);
+        }
+    }
+    // a + (−c) → a − c and a − (−c) → a + c
+    match (op, as_rational(&rhs)) {
+        (BinOp::Add, Some(c)) if c.is_negative() => simplify_binop(BinOp::Sub, lhs, rational_expr(&-c)),
+        (BinOp::Sub, Some(c)) if c.is_negative() => simplify_binop(BinOp::Add, lhs, rational_expr(&-c)),
+        _ => simplify_binop(op, lhs, rhs),
+    }
+}
+
+/// `−expr`, pushing the sign into sums and differences.
+fn negate(expr: Expr) -> Expr {
+    match expr.as_ref() {
+        SymExpr::UnaryOp { op: UnaryOp::Neg, operand } => operand.clone(),
+        SymExpr::BinaryOp { op: BinOp::Add, lhs, rhs } => e_sub(negate(lhs.clone()), rhs.clone()),
+        SymExpr::BinaryOp { op: BinOp::Sub, lhs, rhs } => e_add(negate(lhs.clone()), rhs.clone()),
+        _ => e_sub(zero(), expr),
+    }
+}
+
+fn e_add(a: Expr, b: Expr) -> Expr { exact_op(BinOp::Add, a, b) }
+fn e_sub(a: Expr, b: Expr) -> Expr { exact_op(BinOp::Sub, a, b) }
+fn e_mul(a: Expr, b: Expr) -> Expr { exact_op(BinOp::Mul, a, b) }
+fn e_div(a: Expr, b: Expr) -> Expr { exact_op(BinOp::Div, a, b) }
+
+/// Simplify an intermediate coefficient if it is symbolic (closed forms are
+/// already exact and `simplify` would round their fractions).
+fn settle(expr: Expr) -> Expr {
+    if free_variables(&expr).is_empty() { expr } else { simplify(&expr) }
+}
+
+/// The sign of a closed-form `expr`: exact for rationals, within round-off
+/// otherwise, `None` when symbolic.
+fn sign_of(expr: &Expr) -> Option<Ordering> {
+    if let Some(r) = as_rational(expr) {
+        return Some(r.cmp(&Rational::zero()));
+    }
+    let v = constant_value(expr).filter(|v| !v.is_nan())?;
+    Some(if v.abs() <= 1e-12 { Ordering::Equal } else { v.total_cmp(&0.0) })
+}
+
+/// `√expr`, exact for rationals.
+fn exact_sqrt(expr: Expr) -> Expr {
+    match as_rational(&expr) {
+        Some(r) if !r.is_negative() => rational_root(&r, 2),
+        _ => sqrt(expr),
+    }
+}
+
+/// The real cube root of `expr`: `-∛(-u)` for negative values and
+/// `sign(u)·∛|u|` when the sign is unknown.
+fn exact_cbrt(expr: Expr) -> Expr {
+    if let Some(r) = as_rational(&expr) {
+        return rational_root(&r, 3);
+    }
+    match sign_of(&expr) {
+        Some(Ordering::Less) => neg(pow(negate(expr), third())),
+        Some(_) => pow(expr, third()),
+        None => mul(unop(UnaryOp::Sign, expr.clone()), pow(unop(UnaryOp::Abs, expr), third())),
+    }
+}
+
+/// Roots of `p[0] + p[1]·x + … + p[n]·xⁿ` for `n ≤ 4` in radicals; with
+/// numeric coefficients only the real ones.
+fn radical_roots(p: &[Expr]) -> Vec<Expr> {
+    let monic = |i: usize| settle(e_div(p[i].clone(), p[p.len() - 1].clone()));
+    match p.len() {
+        2 => vec![negate(monic(0))],
+        3 => quadratic_roots(monic(1), monic(0)),
+        4 => cubic_roots(monic(2), monic(1), monic(0)),
+        5 => quartic_roots(monic(3), monic(2), monic(1), monic(0)),
+        _ => Vec::new(),
+    }
+}
+
+/// Roots of x² + b·x + c.
+fn quadratic_roots(b: Expr, c: Expr) -> Vec<Expr> {
+    let h = settle(e_div(b, con(-2.0)));
+    let k = settle(e_sub(e_mul(h.clone(), h.clone()), c));
+    match sign_of(&k) {
+        Some(Ordering::Less) => Vec::new(),
+        Some(Ordering::Equal) => vec![h],
+        _ => {
+            let s = exact_sqrt(k);
+            vec![e_sub(h.clone(), s.clone()), e_add(h, s)]
+        }
+    }
+}
+
+/// Real roots of x³ + b·x² + c·x + d: Cardano's formula for one real root,
+/// the trigonometric form for three.
+fn cubic_roots(b: Expr, c: Expr, d: Expr) -> Vec<Expr> {
+    // Depressed cubic t³ + p·t + q with x = t − b/3.
+    let b2 = e_mul(b.clone(), b.clone());
+    let p = settle(e_sub(c.clone(), e_div(b2.clone(), con(3.0))));
+    let q = settle(e_add(
+        e_sub(e_div(e_mul(con(2.0), e_mul(b2, b.clone())), con(27.0)), e_div(e_mul(b.clone(), c), con(3.0))),
+        d,
+    ));
+    let shift = settle(e_div(b, con(-3.0)));
+    match (sign_of(&p), sign_of(&q)) {
+        (Some(Ordering::Equal), Some(Ordering::Equal)) => return vec![shift],
+        (Some(Ordering::Equal), _) => return vec![e_add(shift, exact_cbrt(negate(q)))],
+        _ => {}
+    }
+    let disc = settle(e_add(
+        e_div(e_mul(q.clone(), q.clone()), con(4.0)),
+        e_div(e_mul(p.clone(), e_mul(p.clone(), p.clone())), con(27.0)),
+    ));
+    let three_q = e_mul(con(3.0), q.clone());
+    match sign_of(&disc) {
+        // Repeated root.
+        Some(Ordering::Equal) => vec![
+            e_add(shift.clone(), e_div(three_q.clone(), p.clone())),
+            e_sub(shift, e_div(three_q, e_mul(con(2.0), p))),
+        ],
+        // tₖ = 2√(−p/3)·cos((acos((3q/2p)·√(−3/p)) − 2πk)/3)
+        Some(Ordering::Less) => {
+            let m = e_mul(con(2.0), exact_sqrt(e_div(negate(p.clone()), con(3.0))));
+            let arg = e_mul(e_div(three_q, e_mul(con(2.0), p.clone())), exact_sqrt(e_div(con(-3.0), p)));
+            let theta = func(Func::Acos, arg);
+            (0..3)
+                .map(|k| {
+                    let angle = match k {
+                        0 => theta.clone(),
+                        k => sub(theta.clone(), binop(BinOp::Mul, con(2.0 * k as f64), con(std::f64::consts::PI))),
+                    };
+                    e_add(shift.clone(), e_mul(m.clone(), cos(div(angle, con(3.0)))))
+                })
+                .collect()
+        }
+        _ => {
+            let u = e_div(q, con(-2.0));
+            let s = exact_sqrt(disc);
+            let t = e_add(exact_cbrt(e_add(u.clone(), s.clone())), exact_cbrt(e_sub(u, s)));
+            vec![e_add(shift, t)]
+        }
+    }
+}
+
+/// Real roots of x⁴ + b·x³ + c·x² + d·x + e: biquadratic when the depressed
+/// quartic has no linear term, else Ferrari's method.
+fn quartic_roots(b: Expr, c: Expr, d: Expr, e: Expr) -> Vec<Expr> {
+    // Depressed quartic y⁴ + p·y² + q·y + r with x = y − b/4.
+    let b2 = e_mul(b.clone(), b.clone());
+    let p = settle(e_sub(c.clone(), e_div(e_mul(con(3.0), b2.clone()), con(8.0))));
+    let q = settle(e_add(
+        e_sub(d.clone(), e_div(e_mul(b.clone(), c.clone()), con(2.0))),
+        e_div(e_mul(b2.clone(), b.clone()), con(8.0)),
+    ));
+    let r = settle(e_add(
+        e_sub(e.clone(), e_div(e_mul(b.clone(), d.clone()), con(4.0))),
+        e_sub(
+            e_div(e_mul(b2.clone(), c.clone()), con(16.0)),
+            e_div(e_mul(con(3.0), e_mul(b2.clone(), b2)), con(256.0)),
+        ),
+    ));
+    let shift = settle(e_div(b.clone(), con(-4.0)));
+    let mut ys = Vec::new();
+    if sign_of(&q) == Some(Ordering::Equal) || is_zero(&q) {
+        // Biquadratic: z = y².
+        for z in quadratic_roots(p, r) {
+            match sign_of(&z) {
+                Some(Ordering::Less) => {}
+                Some(Ordering::Equal) => ys.push(zero()),
+                _ => {
+                    let y = exact_sqrt(z);
+                    ys.extend([negate(y.clone()), y]);
+                }
+            }
+        }
+    } else {
+        // Ferrari: pick m > 0 with m³ + p·m² + (p²/4 − r)·m − q²/8 = 0, so the
+        // quartic splits into (y² − s·y + p/2 + m + q/2s)(y² + s·y + p/2 + m − q/2s)
+        // with s = √(2m).
+        let resolvent = [
+            settle(e_div(e_mul(q.clone(), q.clone()), con(-8.0))),
+            settle(e_sub(e_div(e_mul(p.clone(), p.clone()), con(4.0)), r)),
+            p.clone(),
+            one(),
+        ];
+        let candidates = match resolvent.iter().map(as_rational).collect::<Option<Vec<Rational>>>() {
+            Some(exact) => rational_polynomial_roots(exact),
+            None => radical_roots(&resolvent),
+        };
+        let m = match candidates.iter().map(constant_value).collect::<Option<Vec<f64>>>() {
+            Some(values) => match values.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)) {
+                Some((i, v)) if *v > 0.0 => candidates[i].clone(),
+                _ => {
+                    let numeric = [e, d, c, b, one()].iter().map(constant_value).collect::<Option<Vec<f64>>>();
+                    return real_polynomial_roots(&numeric.unwrap_or_default()).into_iter().map(con).collect();
+                }
+            },
+            None => match candidates.into_iter().next() {
+                Some(m) => m,
+                None => return Vec::new(),
+            },
+        };
+        let s = exact_sqrt(e_mul(con(2.0), m.clone()));
+        let base = e_sub(negate(e_div(m, con(2.0))), e_div(p, con(2.0)));
+        let q_term = e_div(q, e_mul(con(2.0), s.clone()));
+        for sign in [1.0, -1.0] {
+            // y = ±s/2 ± √(−m/2 − p/2 ∓ q/2s)
+            let center = e_mul(con(sign), e_div(s.clone(), con(2.0)));
+            let w = e_sub(base.clone(), e_mul(con(sign), q_term.clone()));
+            match sign_of(&w) {
+                Some(Ordering::Less) => {}
+                Some(Ordering::Equal) => ys.push(center),
+                _ => {
+                    let root = exact_sqrt(w);
+                    ys.extend([e_sub(center.clone(), root.clone()), e_add(center, root)]);
+                }
+            }
+        }
+    }
+    ys.into_iter().map(|y| e_add(shift.clone(), y)).collect()
+}
+
+/// Real roots of `c[0] + c[1]·x + … + c[n]·xⁿ` in ascending order: closed
+/// forms through quartics (Cardano, Ferrari), polished with Newton steps;
+/// companion-matrix eigenvalues beyond.
+fn real_polynomial_roots(c: &[f64]) -> Vec<f64> {
+    let poly = Polynomial::new(c.to_vec());
+    let mut roots = match c.len() {
+        0 | 1 => Vec::new(),
+        2 => vec![-c[0] / c[1]],
+        3 => real_quadratic_roots(c[2], c[1], c[0]),
+        4 => real_cubic_roots(c[3], c[2], c[1], c[0]),
+        5 => real_quartic_roots(c[4], c[3], c[2], c[1], c[0]),
+        _ => return find_real_roots_of_poly(&poly),
+    };
+    for r in roots.iter_mut() {
+        *r = newton_refine_root(&poly, *r, 8);
+    }
+    roots.retain(|r| r.is_finite());
+    roots.sort_by(|a, b| a.total_cmp(b));
+    roots.dedup_by(|a, b| (*a - *b).abs() <= 1e-9 * (1.0 + b.abs()));
+    roots
+}
+
+fn real_quadratic_roots(a: f64, b: f64, c: f64) -> Vec<f64> {
+    let disc = b * b - 4.0 * a * c;
+    if disc < -COEFF_EPS * (b * b).max(1.0) {
+        return Vec::new();
+    }
+    // Numerically stable form: avoid cancelling -b against √disc.
+    let q = -0.5 * (b + b.signum() * disc.max(0.0).sqrt());
+    if q == 0.0 {
+        return vec![0.0];
+    }
+    vec![q / a, c / q]
+}
+
+fn real_cubic_roots(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    let (b, c, d) = (b / a, c / a, d / a);
+    // Depressed cubic t³ + p·t + q with x = t − b/3.
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+    let shift = -b / 3.0;
+    let disc = (q / 2.0).powi(2) + (p / 3.0).powi(3);
+    let scale = (q * q).max(p.abs().powi(3)).max(1.0);
+    if p.abs() < COEFF_EPS * (1.0 + b * b) && q.abs() < COEFF_EPS * (1.0 + b.abs().powi(3)) {
+        vec![shift]
+    } else if disc.abs() <= 1e-14 * scale {
+        // Repeated root.
+        vec![3.0 * q / p + shift, -1.5 * q / p + shift]
+    } else if disc > 0.0 {
+        let s = disc.sqrt();
+        vec![(-q / 2.0 + s).cbrt() + (-q / 2.0 - s).cbrt() + shift]
+    } else {
+        // Three real roots: trigonometric form.
+        let m = 2.0 * (-p / 3.0).sqrt();
+        let theta = (3.0 * q / (p * m)).clamp(-1.0, 1.0).acos() / 3.0;
+        (0..3)
+            .map(|k| m * (theta - 2.0 * std::f64::consts::PI * k as f64 / 3.0).cos() + shift)
+            .collect()
+    }
+}
+
+fn real_quartic_roots(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+    // Depressed quartic y⁴ + p·y² + q·y + r with x = y − b/4.
+    let p = c - 3.0 * b * b / 8.0;
+    let q = d - b * c / 2.0 + b * b * b / 8.0;
+    let r = e - b * d / 4.0 + b * b * c / 16.0 - 3.0 * b.powi(4) / 256.0;
+    let shift = -b / 4.0;
+    let ys: Vec<f64> = if q.abs() < COEFF_EPS * (1.0 + p.abs() + r.abs()) {
+        // Biquadratic: z = y².
+        real_quadratic_roots(1.0, p, r)
+            .into_iter()
+            .filter(|z| *z >= -COEFF_EPS)
+            .flat_map(|z| {
+                let y = z.max(0.0).sqrt();
+                [y, -y]
+            })
+            .collect()
+    } else {
+        // Ferrari: pick m > 0 with 8m³ + 8p·m² + (2p² − 8r)·m − q² = 0, so the
+        // quartic splits into (y² − s·y + p/2 + m + q/2s)(y² + s·y + p/2 + m − q/2s).
+        let m = real_cubic_roots(8.0, 8.0 * p, 2.0 * p * p - 8.0 * r, -q * q)
+            .into_iter()
+            .fold(f64::NEG_INFINITY, f64::max);
+        if m <= 0.0 || !m.is_finite() {
+            return find_real_roots_of_poly(&Polynomial::new(vec![e, d, c, b, 1.0]));
+        }
+        let s = (2.0 * m).sqrt();
+        let mut ys = real_quadratic_roots(1.0, -s, p / 2.0 + m + q / (2.0 * s));
+        ys.extend(real_quadratic_roots(1.0, s, p / 2.0 + m - q / (2.0 * s)));
+        ys
+    };
+    ys.into_iter().map(|y| y + shift).collect()
+}
+
+/// Solve `lhs = rhs` (with `var` only in `lhs`) by inverting the outermost
+/// operation of `lhs` until only `var` remains.
+fn isolate(lhs: &Expr, rhs: &Expr, var: &str) -> Result<Vec<Expr>, String> {
+    let cannot = || Err(format!("cannot isolate '{var}' in {lhs}: it must occur exactly once"));
+    match lhs.as_ref() {
+        SymExpr::Variable(name) if name == var => Ok(vec![rhs.clone()]),
+        SymExpr::BinaryOp { op, lhs: a, rhs: b } => {
+            let (a_const, b_const) = (is_constant(a, var), is_constant(b, var));
+            let r = rhs.clone();
+            match (op, a_const, b_const) {
+                (_, true, true) => cannot(),
+                (BinOp::Add, false, true) => isolate(a, &sub(r, b.clone()), var),
+                (BinOp::Add, true, false) => isolate(b, &sub(r, a.clone()), var),
+                (BinOp::Sub, false, true) => isolate(a, &add(r, b.clone()), var),
+                (BinOp::Sub, true, false) => isolate(b, &sub(a.clone(), r), var),
+                (BinOp::Mul, false, true) => isolate(a, &div(r, b.clone()), var),
+                (BinOp::Mul, true, false) => isolate(b, &div(r, a.clone()), var),
+                (BinOp::Div, false, true) => isolate(a, &mul(r, b.clone()), var),
+                (BinOp::Div, true, false) => isolate(b, &div(a.clone(), r), var),
+                (BinOp::Pow, false, true) => {
+                    let root = pow(r.clone(), div(one(), b.clone()));
+                    match integer_exponent(b) {
+                        // x^n = r has ±r^(1/n) for even n and a sign-preserving root for odd n.
+                        Some(n) if n % 2 == 0 => {
+                            let mut out = isolate(a, &root, var)?;
+                            out.extend(isolate(a, &neg(root), var)?);
+                            Ok(out)
+                        }
+                        Some(_) if constant_value(&r).is_some_and(|v| v < 0.0) => {
+                            isolate(a, &neg(pow(neg(r), div(one(), b.clone()))), var)
+                        }
+                        _ => isolate(a, &root, var),
+                    }
+                }
+                (BinOp::Pow, true, false) => isolate(b, &div(ln(r), ln(a.clone())), var),
+                _ => cannot(),
+            }
+        }
+        SymExpr::UnaryOp { op: UnaryOp::Neg, operand } => isolate(operand, &neg(rhs.clone()), var),
+        SymExpr::UnaryOp { op: UnaryOp::Abs, operand } => {
+            let mut out = isolate(operand, rhs, var)?;
+            out.extend(isolate(operand, &neg(rhs.clone()), var)?);
+            Ok(out)
+        }
+        SymExpr::Function { func: f, arg } => {
+            let r = rhs.clone();
+            let pi = con(std::f64::consts::PI);
+            let candidates = match f {
+                Func::Exp => vec![ln(r)],
+                Func::Ln => vec![exp(r)],
+                Func::Log10 => vec![pow(con(10.0), r)],
+                Func::Sqrt => vec![pow(r, two())],
+                Func::Sin => vec![func(Func::Asin, r.clone()), sub(pi, func(Func::Asin, r))],
+                Func::Cos => vec![func(Func::Acos, r.clone()), neg(func(Func::Acos, r))],
+                Func::Tan => vec![func(Func::Atan, r)],
+                Func::Asin => vec![sin(r)],
+                Func::Acos => vec![cos(r)],
+                Func::Atan => vec![tan(r)],
+                Func::Sinh => vec![ln(add(r.clone(), sqrt(add(pow(r, two()), one()))))],
+                Func::Cosh => {
+                    let v = ln(add(r.clone(), sqrt(sub(pow(r, two()), one()))));
+                    vec![v.clone(), neg(v)]
+                }
+                Func::Tanh => vec![div(ln(div(add(one(), r.clone()), sub(one(), r))), two())],
+            };
+            let mut out = Vec::new();
+            for c in candidates {
+                out.extend(isolate(arg, &c, var)?);
+            }
+            Ok(out)
+        }
+        SymExpr::Sum(terms) => {
+            let (with, without): (Vec<&Expr>, Vec<&Expr>) = terms.iter().partition(|t| !is_constant(t, var));
+            match with.as_slice() {
+                [t] => isolate(t, &without.into_iter().fold(rhs.clone(), |acc, c| sub(acc, c.clone())), var),
+                _ => cannot(),
+            }
+        }
+        SymExpr::Product(factors) => {
+            let (with, without): (Vec<&Expr>, Vec<&Expr>) = factors.iter().partition(|f| !is_constant(f, var));
+            match with.as_slice() {
+                [f] => isolate(f, &without.into_iter().fold(rhs.clone(), |acc, c| div(acc, c.clone())), var),
+                _ => cannot(),
+            }
+        }
+        _ => cannot(),
+    }
+}
+
+/// Solve the linear equations `equations[i] = 0` for `vars` by Gaussian
+/// elimination over expressions; other symbols stay symbolic. Numeric
+/// pivots are chosen by magnitude. Extra equations must be consistent.
+pub fn solve_linear_system(equations: &[Expr], vars: &[String]) -> Result<Vec<Expr>, String> {
+    let n = vars.len();
+    if equations.len() < n {
+        return Err(format!("{} equations cannot determine {n} unknowns", equations.len()));
+    }
+    // Augmented rows [a_1 … a_n | b] with Σ a_j·x_j = b.
+    let mut rows: Vec<Vec<Expr>> = Vec::with_capacity(equations.len());
+    for (i, eq) in equations.iter().enumerate() {
+        let mut row = Vec::with_capacity(n + 1);
+        for v in vars {
+            let coeffs = polynomial_coefficients(eq, v)
+                .filter(|c| c.len() <= 2)
+                .ok_or_else(|| format!("equation {} is not linear in '{v}'", i + 1))?;
+            let a = coeffs.get(1).cloned().unwrap_or_else(zero);
+            if vars.iter().any(|w| !is_constant(&a, w)) {
+                return Err(format!("equation {} is not linear in '{v}'", i + 1));
+            }
+            row.push(a);
+        }
+        let constant = vars.iter().fold(eq.clone(), |e, v| substitute(&e, v, &zero()));
+        row.push(clean(&neg(constant)));
+        rows.push(row);
+    }
+
+    for col in 0..n {
+        let candidates = (col..rows.len()).filter(|&r| !vanishes(&rows[r][col]));
+        let pivot = candidates
+            .max_by(|&r1, &r2| {
+                let mag = |r: usize| constant_value(&rows[r][col]).map_or(-1.0, f64::abs);
+                mag(r1).total_cmp(&mag(r2)).then(r2.cmp(&r1))
+            })
+            .ok_or_else(|| format!("the system is singular: '{}' is not determined", vars[col]))?;
+        rows.swap(col, pivot);
+        let (upper, lower) = rows.split_at_mut(col + 1);
+        let pivot_row = &upper[col];
+        for row in lower.iter_mut().filter(|row| !vanishes(&row[col])) {
+            let factor = simplify(&div(row[col].clone(), pivot_row[col].clone()));
+            for k in col..=n {
+                row[k] = clean(&sub(row[k].clone(), mul(factor.clone(), pivot_row[k].clone())));
+            }
+        }
+    }
+    if rows[n..].iter().any(|row| !vanishes(&row[n])) {
+        return Err("the system is inconsistent".to_string());
+    }
+
+    let mut solution = vec![zero(); n];
+    for i in (0..n).rev() {
+        let rest = (i + 1..n).fold(rows[i][n].clone(), |acc, j| sub(acc, mul(rows[i][j].clone(), solution[j].clone())));
+        solution[i] = clean(&div(rest, rows[i][i].clone()));
+    }
+    Ok(solution)
+}
+
+// ── Series Expansion ────────────────────────────────────────────────────────
+
+/// A truncated Laurent series `Σ coeffs[k]·hᵛᵃˡ⁺ᵏ` in the offset `h` from
+/// the expansion point, known exactly through `h^prec`. Coefficients past
+/// the end of `coeffs` are zero.
+#[derive(Debug, Clone)]
+struct Series {
+    val: i32,
+    prec: i32,
+    coeffs: Vec<Expr>,
+}
+
+/// Dummy variable for expanding `f(c0 + h)` about `c0`.
+const SERIES_ARG: &str = "__series_u";
+
+impl Series {
+    fn constant(c: Expr, prec: i32) -> Self {
+        Series { val: 0, prec, coeffs: vec![c] }
+    }
+
+    fn coeff(&self, e: i32) -> Expr {
+        if e < self.val || e > self.prec {
+            return zero();
+        }
+        self.coeffs.get((e - self.val) as usize).cloned().unwrap_or_else(zero)
+    }
+
+    /// Strip leading zero coefficients so `val` is the true valuation; a
+    /// series with no known non-zero term gets `val = prec + 1`.
+    fn normalized(mut self) -> Self {
+        self.coeffs.truncate((self.prec - self.val + 1).max(0) as usize);
+        let lead = self.coeffs.iter().take_while(|c| is_zero_expr(c)).count();
+        self.coeffs.drain(..lead);
+        self.val = if self.coeffs.is_empty() { self.prec + 1 } else { self.val + lead as i32 };
+        self
+    }
+
+    fn build(val: i32, prec: i32, mut coeff: impl FnMut(i32) -> Expr) -> Self {
+        let coeffs = (val..=prec).map(|e| clean(&coeff(e))).collect();
+        Series { val, prec, coeffs }
+    }
+
+    fn add(&self, other: &Series) -> Series {
+        Series::build(self.val.min(other.val), self.prec.min(other.prec), |e| {
+            add(self.coeff(e), other.coeff(e))
+        })
+    }
+
+    fn neg(&self) -> Series {
+        Series { coeffs: self.coeffs.iter().map(|c| simplify(&neg(c.clone()))).collect(), ..self.clone() }
+    }
+
+    fn scale(&self, factor: &Expr) -> Series {
+        Series::build(self.val, self.prec, |e| mul(factor.clone(), self.coeff(e)))
+    }
+
+    fn mul(&self, other: &Series) -> Series {
+        let (a, b) = (self.clone().normalized(), other.clone().normalized());
+        let prec = (a.prec + b.val).min(b.prec + a.val);
+        Series::build(a.val + b.val, prec, |e| {
+            (a.val..=e - b.val).fold(zero(), |acc, i| add(acc, mul(a.coeff(i), b.coeff(e - i))))
+        })
+    }
+
+    fn inv(&self) -> Result<Series, String> {
+        let b = self.clone().normalized();
+        if b.coeffs.is_empty() {
+            return Err(format!("division by a series that vanishes through order {}", b.prec));
+        }
+        // b = h^v·(b0 + b1·h + …); 1/b = h^−v·(d0 + d1·h + …), d_k = −Σ b_j·d_{k−j} / b0.
+        let rel = b.prec - b.val;
+        let b0 = b.coeffs[0].clone();
+        let mut d: Vec<Expr> = vec![simplify(&div(one(), b0.clone()))];
+        for k in 1..=rel {
+            let s = (1..=k).fold(zero(), |acc, j| add(acc, mul(b.coeff(b.val + j), d[(k - j) as usize].clone())));
+            d.push(clean(&neg(div(s, b0.clone()))));
+        }
+        Ok(Series { val: -b.val, prec: b.prec - 2 * b.val, coeffs: d })
+    }
+
+    fn powi(&self, n: i32) -> Result<Series, String> {
+        let base = if n < 0 { self.inv()? } else { self.clone() };
+        let prec = base.prec;
+        Ok((0..n.unsigned_abs()).fold(Series::constant(one(), prec), |acc, _| acc.mul(&base)))
+    }
+
+    /// `f(self)` for `f(u) = template` in [`SERIES_ARG`], by Taylor-expanding
+    /// `f` about the constant term: `Σ f⁽ᵏ⁾(c0)/k!·hᵏ`.
+    fn compose(&self, template: &Expr) -> Result<Series, String> {
+        let s = self.clone().normalized();
+        if s.val < 0 {
+            return Err(format!("{template} is not analytic at a pole of its argument"));
+        }
+        let c0 = s.coeff(0);
+        let h = Series { coeffs: s.coeffs.iter().skip(usize::from(s.val == 0)).cloned().collect(), val: s.val.max(1), prec: s.prec }
+            .normalized();
+        let terms = if h.coeffs.is_empty() { 0 } else { (s.prec / h.val).max(0) };
+        let mut deriv = template.clone();
+        let mut power = Series::constant(one(), s.prec);
+        let mut factorial = 1.0;
+        let mut out = Series::constant(zero(), s.prec);
+        for k in 0..=terms {
+            if k > 0 {
+                deriv = simplify(&differentiate(&deriv, SERIES_ARG));
+                power = power.mul(&h);
+                factorial *= k as f64;
+            }
+            let c = clean(&div(substitute(&deriv, SERIES_ARG, &c0), con(factorial)));
+            if constant_value(&c).is_some_and(|v| !v.is_finite()) {
+                return Err(format!("{} is not analytic at {}", substitute(template, SERIES_ARG, &var("u")), c0));
+            }
+            out = out.add(&power.scale(&c));
+        }
+        Ok(out)
+    }
+}
+
+/// Series of `expr` about `var = x0`, exact through `h^prec`.
+fn series_of(expr: &Expr, var: &str, x0: &Expr, prec: i32) -> Result<Series, String> {
+    if is_constant(expr, var) {
+        return Ok(Series::constant(expr.clone(), prec));
+    }
+    let u = || self::var(SERIES_ARG);
+    match expr.as_ref() {
+        SymExpr::Variable(_) => Ok(Series { val: 0, prec, coeffs: vec![x0.clone(), one()] }),
+        SymExpr::Constant(_) => Ok(Series::constant(expr.clone(), prec)),
+        SymExpr::BinaryOp { op, lhs, rhs } => {
+            if *op == BinOp::Pow {
+                let base = series_of(lhs, var, x0, prec)?;
+                return match (is_constant(rhs, var), integer_exponent(rhs)) {
+                    (true, Some(n)) => base.powi(n),
+                    (true, None) => base.compose(&pow(u(), rhs.clone())),
+                    // f^g = exp(g·ln f)
+                    (false, _) => series_of(&exp(mul(rhs.clone(), ln(lhs.clone()))), var, x0, prec),
+                };
+            }
+            let a = series_of(lhs, var, x0, prec)?;
+            let b = series_of(rhs, var, x0, prec)?;
+            Ok(match op {
+                BinOp::Add => a.add(&b),
+                BinOp::Sub => a.add(&b.neg()),
+                BinOp::Mul => a.mul(&b),
+                BinOp::Div => a.mul(&b.inv()?),
+                BinOp::Pow => unreachable!(),
+            })
+        }
+        SymExpr::UnaryOp { op: UnaryOp::Neg, operand } => Ok(series_of(operand, var, x0, prec)?.neg()),
+        SymExpr::UnaryOp { op, operand } => series_of(operand, var, x0, prec)?.compose(&unop(*op, u())),
+        SymExpr::Function { func: f, arg } => series_of(arg, var, x0, prec)?.compose(&func(*f, u())),
+        SymExpr::Sum(terms) => terms
+            .iter()
+            .try_fold(Series::constant(zero(), prec), |acc, t| Ok(acc.add(&series_of(t, var, x0, prec)?))),
+        SymExpr::Product(factors) => factors
+            .iter()
+            .try_fold(Series::constant(one(), prec), |acc, f| Ok(acc.mul(&series_of(f, var, x0, prec)?))),
+    }
+}
+
+/// Series of `expr` about `var = x0` known through at least `h^order`,
+/// raising the working precision when divisions lose terms.
+fn series_to_order(expr: &Expr, var: &str, x0: f64, order: i32) -> Result<Series, String> {
+    let mut prec = order;
+    for _ in 0..4 {
+        let s = series_of(expr, var, &con(x0), prec)?;
+        if s.prec >= order {
+            return Ok(Series { prec: order, ..s });
+        }
+        prec += order - s.prec;
+    }
+    Err(format!("could not reach order {order} (the expansion loses too many terms)"))
+}
+
+/// Taylor expansion of `expr` about `var = x0` through `(var − x0)^order`
+/// (a Laurent expansion with negative powers at a pole), as the
+/// `(power, coefficient)` pairs with non-zero coefficients.
+pub fn series_terms(expr: &Expr, var: &str, x0: f64, order: i32) -> Result<Vec<(i32, Expr)>, String> {
+    let s = series_to_order(expr, var, x0, order)?.normalized();
+    Ok((s.val..=s.prec).map(|e| (e, s.coeff(e))).filter(|(_, c)| !is_zero(c)).collect())
+}
+
+/// Truncated series of `expr` about `var = x0` through `(var − x0)^order`
+/// as an expression (see [`series_terms`]).
+pub fn series(expr: &Expr, var: &str, x0: f64, order: i32) -> Result<Expr, String> {
+    Ok(series_expr(&series_terms(expr, var, x0, order)?, var, x0))
+}
+
+/// The sum of `(power, coefficient)` terms from [`series_terms`] as an
+/// expression in `var − x0`.
+pub fn series_expr(terms: &[(i32, Expr)], var: &str, x0: f64) -> Expr {
+    let offset = if x0 == 0.0 {
+        self::var(var)
+    } else if x0 < 0.0 {
+        add(self::var(var), con(-x0))
+    } else {
+        sub(self::var(var), con(x0))
+    };
+    let terms: Vec<Expr> = terms
+        .iter()
+        .map(|(k, c)| {
+            let h = if *k == 1 { offset.clone() } else { pow(offset.clone(), con(*k as f64)) };
+            match k {
+                0 => c.clone(),
+                _ if is_one(c) => h,
+                _ if is_neg_one(c) => neg(h),
+                _ => mul(c.clone(), h),
+            }
+        })
+        .collect();
+    simplify(&terms.into_iter().reduce(add).unwrap_or_else(zero))
+}
+
+// ── Limits ──────────────────────────────────────────────────────────────────
+
+/// Limit of `expr` as `var → x0`; `x0` may be `±∞` (one-sided by nature),
+/// finite points are two-sided.
+///
+/// The leading term of the series about `x0` decides the limit when the
+/// expansion exists (symbolic parameters allowed). Otherwise the limit is
+/// taken numerically through the expression tree, applying L'Hôpital's rule
+/// to `0/0` and `∞/∞` quotients (and `0·∞`, `1^∞`, `0⁰`, `∞⁰` rewritten
+/// as quotients).
+pub fn limit(expr: &Expr, var: &str, x0: f64) -> Result<Expr, String> {
+    if let Some(l) = series_limit(expr, var, x0)? {
+        return Ok(l);
+    }
+    limit_numeric(expr, var, x0, 0).map(con)
+}
+
+/// Orders tried when looking for the leading series term.
+const LIMIT_SERIES_ORDERS: [i32; 3] = [3, 6, 12];
+
+/// The limit from the leading series term: `Ok(None)` when no expansion
+/// exists, `Err` when the leading term shows the limit does not exist.
+fn series_limit(expr: &Expr, var: &str, x0: f64) -> Result<Option<Expr>, String> {
+    // x → ±∞ becomes h → 0⁺ with x = ±1/h.
+    let (target, at, one_sided) = if x0.is_infinite() {
+        (substitute(expr, var, &div(con(x0.signum()), self::var(var))), 0.0, true)
+    } else {
+        (expr.clone(), x0, false)
+    };
+    for order in LIMIT_SERIES_ORDERS {
+        let s = match series_to_order(&target, var, at, order) {
+            Ok(s) => s.normalized(),
+            Err(_) => return Ok(None),
+        };
+        if s.coeffs.is_empty() {
+            continue;
+        }
+        let lead = s.coeffs[0].clone();
+        return match s.val.cmp(&0) {
+            std::cmp::Ordering::Greater => Ok(Some(zero())),
+            std::cmp::Ordering::Equal => Ok(Some(lead)),
+            std::cmp::Ordering::Less => {
+                let sign = constant_value(&lead)
+                    .map(f64::signum)
+                    .ok_or_else(|| format!("the limit is infinite with the sign of {lead}"))?;
+                if one_sided || s.val % 2 == 0 {
+                    Ok(Some(con(sign * f64::INFINITY)))
+                } else {
+                    Err(format!("the one-sided limits at {var} = {x0} are -∞ and +∞"))
+                }
+            }
+        };
+    }
+    Ok(None)
+}
+
+/// Levels of L'Hôpital's rule tried before giving up.
+const MAX_LHOPITAL_DEPTH: usize = 8;
+
+fn limit_numeric(expr: &Expr, var: &str, x0: f64, depth: usize) -> Result<f64, String> {
+    let lim = |e: &Expr| limit_numeric(e, var, x0, depth);
+    let value = match expr.as_ref() {
+        SymExpr::Constant(v) => *v,
+        SymExpr::Variable(name) if name == var => x0,
+        SymExpr::Variable(name) => return Err(format!("the limit depends on '{name}'")),
+        SymExpr::BinaryOp { op, lhs, rhs } => match op {
+            BinOp::Add => lim(lhs)? + lim(rhs)?,
+            BinOp::Sub => lim(lhs)? - lim(rhs)?,
+            BinOp::Mul => {
+                let (a, b) = (lim(lhs)?, lim(rhs)?);
+                // 0·∞ → ∞-factor / (1 / 0-factor)
+                if a == 0.0 && b.is_infinite() {
+                    quotient_limit(rhs, &div(one(), lhs.clone()), var, x0, depth)?
+                } else if b == 0.0 && a.is_infinite() {
+                    quotient_limit(lhs, &div(one(), rhs.clone()), var, x0, depth)?
+                } else {
+                    a * b
+                }
+            }
+            BinOp::Div => quotient_limit(lhs, rhs, var, x0, depth)?,
+            BinOp::Pow => {
+                let (a, b) = (lim(lhs)?, lim(rhs)?);
+                if (a == 1.0 && b.is_infinite()) || (a == 0.0 && b == 0.0) || (a.is_infinite() && b == 0.0) {
+                    lim(&mul(rhs.clone(), ln(lhs.clone())))?.exp()
+                } else {
+                    a.powf(b)
+                }
+            }
+        },
+        SymExpr::Sum(terms) => return lim(&terms.iter().cloned().reduce(add).unwrap_or_else(zero)),
+        SymExpr::Product(factors) => return lim(&factors.iter().cloned().reduce(mul).unwrap_or_else(one)),
+        SymExpr::UnaryOp { op, operand } => eval(&unop(*op, con(lim(operand)?)), &HashMap::new()),
+        SymExpr::Function { func: f, arg } => eval(&func(*f, con(lim(arg)?)), &HashMap::new()),
+    };
+    if value.is_nan() {
+        return Err(format!("the limit of {expr} as {var} → {x0} does not exist or is indeterminate"));
+    }
+    Ok(value)
+}
+
+fn quotient_limit(num: &Expr, den: &Expr, var: &str, x0: f64, depth: usize) -> Result<f64, String> {
+    if let Ok(Some(l)) = series_limit(&div(num.clone(), den.clone()), var, x0) {
+        if let Some(v) = constant_value(&l) {
+            return Ok(v);
+        }
+    }
+    let (a, b) = (limit_numeric(num, var, x0, depth)?, limit_numeric(den, var, x0, depth)?);
+    if (a == 0.0 && b == 0.0) || (a.is_infinite() && b.is_infinite()) {
+        if depth >= MAX_LHOPITAL_DEPTH {
+            return Err(format!("L'Hôpital's rule did not resolve the limit after {MAX_LHOPITAL_DEPTH} steps"));
+        }
+        let (dn, dd) = (simplify(&differentiate(num, var)), simplify(&differentiate(den, var)));
+        return quotient_limit(&dn, &dd, var, x0, depth + 1);
+    }
+    Ok(a / b)
+}
+
 // ── Tests ───────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::f64::consts::{E, PI};
 
     #[test]
     fn constant_eval() {
@@ -3227,4 +4631,230 @@ mod tests {
         vars.insert("x".to_string(), 3.0);
         assert!((eval(&expanded, &vars) - 16.0).abs() < 1e-10);
     }
+
+    fn numeric_roots(equation: &str, var: &str) -> Vec<f64> {
+        let roots = solve(&parse_equation(equation).unwrap(), var).unwrap();
+        roots.iter().map(|r| constant_value(r).expect("numeric root")).collect()
+    }
+
+    fn assert_close(got: &[f64], expected: &[f64]) {
+        assert_eq!(got.len(), expected.len(), "{got:?} vs {expected:?}");
+        for (g, e) in got.iter().zip(expected) {
+            assert!((g - e).abs() < 1e-9, "{got:?} vs {expected:?}");
+        }
+    }
+
+    #[test]
+    fn solve_polynomials_in_closed_form() {
+        assert_close(&numeric_roots("x^2 - 5*x + 6 = 0", "x"), &[2.0, 3.0]);
+        assert_close(&numeric_roots("x^3 - 6*x^2 + 11*x = 6", "x"), &[1.0, 2.0, 3.0]);
+        assert_close(&numeric_roots("x^3 + 1", "x"), &[-1.0]);
+        assert_close(&numeric_roots("x^4 - 5*x^2 + 4", "x"), &[-2.0, -1.0, 1.0, 2.0]);
+        assert_close(&numeric_roots("(x - 1)*(x + 2)*(x - 3)*(x + 0.5)", "x"), &[-2.0, -0.5, 1.0, 3.0]);
+        assert_close(&numeric_roots("x^5 - x^3", "x"), &[-1.0, 0.0, 1.0]);
+        assert!(numeric_roots("x^2 + 1", "x").is_empty());
+
+        // Quadratic formula with symbolic coefficients.
+        let roots = solve(&parse_expr("a*x^2 + b*x + c").unwrap(), "x").unwrap();
+        assert_eq!(roots.len(), 2);
+        let vars = HashMap::from([("a".to_string(), 1.0), ("b".to_string(), -3.0), ("c".to_string(), 2.0)]);
+        let mut values: Vec<f64> = roots.iter().map(|r| eval(r, &vars)).collect();
+        values.sort_by(|a, b| a.total_cmp(b));
+        assert_close(&values, &[1.0, 2.0]);
+        // Cardano's real root and Ferrari's roots with symbolic coefficients.
+        let roots = solve(&parse_expr("a*x^3 + x + 1").unwrap(), "x").unwrap();
+        let at = HashMap::from([("a".to_string(), 1.0)]);
+        assert_eq!(roots.len(), 1);
+        assert!((eval(&roots[0], &at) + 0.682_327_803_828_019_3).abs() < 1e-12);
+        let roots = solve(&parse_expr("x^4 + a*x + b").unwrap(), "x").unwrap();
+        let at = HashMap::from([("a".to_string(), -6.0), ("b".to_string(), 1.0)]);
+        let mut values: Vec<f64> = roots.iter().map(|r| eval(r, &at)).filter(|v| v.is_finite()).collect();
+        values.sort_by(|a, b| a.total_cmp(b));
+        assert_eq!(values.len(), 2);
+        for v in values {
+            assert!((v.powi(4) - 6.0 * v + 1.0).abs() < 1e-9);
+        }
+        assert!(solve(&parse_expr("a*x^5 + x + 1").unwrap(), "x").is_err());
+        assert!(solve(&parse_equation("x + 1 = 1 + x").unwrap(), "x").is_err());
+    }
+
+    fn displayed_roots(equation: &str) -> Vec<String> {
+        solve(&parse_equation(equation).unwrap(), "x").unwrap().iter().map(|r| r.to_string()).collect()
+    }
+
+    #[test]
+    fn solve_gives_exact_roots() {
+        assert_eq!(displayed_roots("x^2 - 2 = 0"), ["(-sqrt(2))", "sqrt(2)"]);
+        assert_eq!(displayed_roots("x^2 = 8"), ["(-(2 * sqrt(2)))", "(2 * sqrt(2))"]);
+        assert_eq!(displayed_roots("x^3 = 2"), ["(2^(1 / 3))"]);
+        assert_eq!(displayed_roots("3*x^2 - 1 = 0")[1], "((1 / 3) * sqrt(3))");
+        assert_eq!(displayed_roots("exp(x) = 3"), ["ln(3)"]);
+        // Rational roots are split off before the radicals.
+        assert_eq!(displayed_roots("(3*x - 1)*(x^2 - 5) = 0"), ["(-sqrt(5))", "(1 / 3)", "sqrt(5)"]);
+        // Three real roots of an irreducible cubic (trigonometric form).
+        let roots = solve(&parse_expr("x^3 - 3*x + 1").unwrap(), "x").unwrap();
+        assert_eq!(roots.len(), 3);
+        assert!(roots.iter().all(|r| r.to_string().contains("acos")));
+        let values: Vec<f64> = roots.iter().map(|r| constant_value(r).unwrap()).collect();
+        assert_close(&values, &[-1.879_385_241_571_816_6, 0.347_296_355_333_860_7, 1.532_088_886_237_956]);
+        // Ferrari with an irrational resolvent root.
+        let values = numeric_roots("x^4 - 6*x + 1 = 0", "x");
+        assert_eq!(values.len(), 2);
+        for v in values {
+            assert!((v.powi(4) - 6.0 * v + 1.0).abs() < 1e-9);
+        }
+        // Two real roots of x^4 + x - 1 (quartic with a rational-free resolvent).
+        assert_eq!(numeric_roots("x^4 + x - 1", "x").len(), 2);
+    }
+
+    #[test]
+    fn solve_rational_and_transcendental_equations() {
+        // The hole at x = 1 is not a solution.
+        assert_close(&numeric_roots("(x^2 - 1)/(x - 1) = 0", "x"), &[-1.0]);
+        assert_close(&numeric_roots("1/x + 1/(x - 1) = 0", "x"), &[0.5]);
+        assert_close(&numeric_roots("exp(2*x) = 5", "x"), &[5f64.ln() / 2.0]);
+        assert_close(&numeric_roots("sin(x) = 0.5", "x"), &[PI / 6.0, 5.0 * PI / 6.0]);
+        assert_close(&numeric_roots("2^x = 8", "x"), &[3.0]);
+        assert_close(&numeric_roots("3*ln(x - 1) + 2 = 8", "x"), &[2f64.exp() + 1.0]);
+        assert!(numeric_roots("sqrt(x) = -1", "x").is_empty());
+        assert!(solve(&parse_expr("x*exp(x) - 1").unwrap(), "x").is_err());
+
+        // Symbolic right-hand side.
+        let roots = solve(&parse_equation("exp(k*t) = y").unwrap(), "t").unwrap();
+        let vars = HashMap::from([("k".to_string(), 2.0), ("y".to_string(), 3.0)]);
+        assert!((eval(&roots[0], &vars) - 3f64.ln() / 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn solve_linear_systems_by_elimination() {
+        let eqs: Vec<Expr> = ["x + y = 3", "x - y = 1", "2*x + 2*y = 6"].iter().map(|e| parse_equation(e).unwrap()).collect();
+        let vars = vec!["x".to_string(), "y".to_string()];
+        let sol = super::solve_linear_system(&eqs, &vars).unwrap();
+        assert_close(&sol.iter().map(|s| constant_value(s).unwrap()).collect::<Vec<_>>(), &[2.0, 1.0]);
+
+        let eqs: Vec<Expr> = ["a*x + y = 1", "x - y = 0"].iter().map(|e| parse_equation(e).unwrap()).collect();
+        let sol = super::solve_linear_system(&eqs, &vars).unwrap();
+        let at = HashMap::from([("a".to_string(), 3.0)]);
+        assert!((eval(&sol[0], &at) - 0.25).abs() < 1e-12 && (eval(&sol[1], &at) - 0.25).abs() < 1e-12);
+
+        let singular: Vec<Expr> = ["x + y = 1", "2*x + 2*y = 2"].iter().map(|e| parse_equation(e).unwrap()).collect();
+        assert!(super::solve_linear_system(&singular, &vars).unwrap_err().contains("singular"));
+        let inconsistent: Vec<Expr> = ["x = 1", "y = 2", "x + y = 4"].iter().map(|e| parse_equation(e).unwrap()).collect();
+        assert!(super::solve_linear_system(&inconsistent, &vars).unwrap_err().contains("inconsistent"));
+        let nonlinear = vec![parse_equation("x*y = 1").unwrap(), parse_equation("x = y").unwrap()];
+        assert!(super::solve_linear_system(&nonlinear, &vars).unwrap_err().contains("not linear"));
+    }
+
+    fn numeric_terms(expr: &str, x0: f64, order: i32) -> Vec<(i32, f64)> {
+        series_terms(&parse_expr(expr).unwrap(), "x", x0, order)
+            .unwrap()
+            .into_iter()
+            .map(|(k, c)| (k, constant_value(&c).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn series_expansions() {
+        let terms = numeric_terms("exp(x)", 0.0, 4);
+        let expected = [(0, 1.0), (1, 1.0), (2, 0.5), (3, 1.0 / 6.0), (4, 1.0 / 24.0)];
+        assert_eq!(terms.len(), expected.len());
+        for ((k, c), (ek, ec)) in terms.iter().zip(expected) {
+            assert!(*k == ek && (c - ec).abs() < 1e-12, "{terms:?}");
+        }
+        // Removable singularity.
+        let terms = numeric_terms("sin(x)/x", 0.0, 4);
+        assert_eq!(terms.iter().map(|t| t.0).collect::<Vec<_>>(), [0, 2, 4]);
+        assert!((terms[1].1 + 1.0 / 6.0).abs() < 1e-12 && (terms[2].1 - 1.0 / 120.0).abs() < 1e-12);
+        // About x0 = 1, and a Laurent expansion at a pole.
+        let terms = numeric_terms("ln(x)", 1.0, 3);
+        assert_eq!(terms.len(), 3);
+        assert!((terms[2].1 - 1.0 / 3.0).abs() < 1e-12);
+        let terms = numeric_terms("1/sin(x)", 0.0, 3);
+        assert_eq!((terms[0].0, terms[1].0, terms[2].0), (-1, 1, 3));
+        assert!((terms[1].1 - 1.0 / 6.0).abs() < 1e-12);
+
+        let s = series(&parse_expr("exp(a*x)").unwrap(), "x", 0.0, 2).unwrap();
+        let at = HashMap::from([("a".to_string(), 3.0), ("x".to_string(), 0.1)]);
+        assert!((eval(&s, &at) - (1.0 + 0.3 + 0.045)).abs() < 1e-12);
+        let s = series(&parse_expr("x^2").unwrap(), "x", 2.0, 3).unwrap();
+        assert_eq!(format!("{s}"), "((4 + (4 * (x - 2))) + ((x - 2)^2))");
+        assert!(series(&parse_expr("sqrt(x)").unwrap(), "x", 0.0, 3).is_err());
+    }
+
+    fn numeric_limit(expr: &str, x0: f64) -> f64 {
+        constant_value(&limit(&parse_expr(expr).unwrap(), "x", x0).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn limits_by_series_and_lhopital() {
+        assert!((numeric_limit("sin(x)/x", 0.0) - 1.0).abs() < 1e-12);
+        assert!((numeric_limit("(1 - cos(x))/x^2", 0.0) - 0.5).abs() < 1e-12);
+        assert!((numeric_limit("(x^2 + 1)/(2*x^2 - 3)", f64::INFINITY) - 0.5).abs() < 1e-12);
+        assert!((numeric_limit("(1 + 1/x)^x", f64::INFINITY) - E).abs() < 1e-12);
+        assert!((numeric_limit("(x^2 - 4)/(x - 2)", 2.0) - 4.0).abs() < 1e-12);
+        assert_eq!(numeric_limit("1/x^2", 0.0), f64::INFINITY);
+        assert_eq!(numeric_limit("-x^3", f64::INFINITY), f64::NEG_INFINITY);
+        assert!(limit(&parse_expr("1/x").unwrap(), "x", 0.0).is_err());
+        // No expansion at the point: L'Hôpital and the 0^0 rewrite.
+        assert!(numeric_limit("x*ln(x)", 0.0).abs() < 1e-12);
+        assert!((numeric_limit("x^x", 0.0) - 1.0).abs() < 1e-12);
+        assert!(numeric_limit("x^2/exp(x)", f64::INFINITY).abs() < 1e-12);
+        assert!((numeric_limit("atan(x)", f64::INFINITY) - PI / 2.0).abs() < 1e-12);
+        assert!(limit(&parse_expr("sin(x)").unwrap(), "x", f64::INFINITY).is_err());
+
+        let l = limit(&parse_expr("sin(a*x)/x").unwrap(), "x", 0.0).unwrap();
+        assert_eq!(format!("{l}"), "a");
+    }
+
+    fn q(s: &str) -> Rational {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn exact_univariate_division_and_gcd() {
+        // (x^2 - 1) / (3x - 3) = x/3 + 1/3 exactly.
+        let p = Polynomial::new(vec![q("-1"), q("0"), q("1")]);
+        let d = Polynomial::new(vec![q("-3"), q("3")]);
+        let (quot, rem) = p.div_rem(&d);
+        assert_eq!(quot.coeffs, vec![q("1/3"), q("1/3")]);
+        assert_eq!(rem.degree(), -1);
+        assert_eq!(format!("{quot}"), "(1/3)x + 1/3");
+
+        // gcd((x - 1/10)(x + 2), (x - 1/10)(x - 7)) = x - 1/10.
+        let root = Polynomial::new(vec![q("-0.1"), q("1")]);
+        let a = root.mul(&Polynomial::new(vec![q("2"), q("1")]));
+        let b = root.mul(&Polynomial::new(vec![q("-7"), q("1")]));
+        assert_eq!(a.gcd(&b), root);
+        assert_eq!(format!("{}", root.to_expr("x").unwrap()), "((-1 / 10) + x)");
+        // 1/3^40 has a denominator beyond 2^53, which f64 constants would round.
+        let tiny = Polynomial::new(vec![q("1/12157665459056928801"), q("1")]);
+        assert!(tiny.to_expr("x").is_none());
+    }
+
+    #[test]
+    fn exact_groebner_basis() {
+        // Cox, Little & O'Shea, Ideals, Varieties, and Algorithms, §2.8.
+        let vars: Vec<String> = vec!["x".into(), "y".into(), "z".into()];
+        let system: Vec<MultiPoly<Rational>> = ["x^2 + y^2 + z^2 - 1", "x^2 + z^2 - y", "x - z"]
+            .iter()
+            .map(|e| MultiPoly::from_expr(&parse_expr(e).unwrap(), &vars, MonomialOrder::Lex).unwrap())
+            .collect();
+        let basis = groebner_basis(&system, 1000).basis;
+        let text: Vec<String> = basis.iter().map(|p| p.to_string()).collect();
+        assert_eq!(text, vec!["x - z", "y - 2z^2", "z^4 + (1/2)z^2 - 1/4"]);
+
+        let solutions = solve_polynomial_system_exact(&system, 1000).unwrap();
+        assert_eq!(solutions.len(), 2);
+        for sol in &solutions {
+            for f in &system {
+                assert!(f.eval_at(sol).abs() < 1e-10, "{sol:?}");
+            }
+        }
+
+        let vars = vec!["x".to_string()];
+        assert!(MultiPoly::<Rational>::from_expr(&parse_expr("x/y").unwrap(), &vars, MonomialOrder::Lex).is_err());
+        assert!(MultiPoly::<Rational>::from_expr(&parse_expr("x^0.5").unwrap(), &vars, MonomialOrder::Lex).is_err());
+        let p = MultiPoly::<Rational>::from_expr(&parse_expr("(x + 0.5)^2 / 3").unwrap(), &vars, MonomialOrder::Lex).unwrap();
+        assert_eq!(p.to_string(), "(1/3)x^2 + (1/3)x + 1/12");
+    }
 }
@@ -0,0 +1,472 @@
+//! `symbolic_codegen` — Rust, C and `expr` source from symbolic expressions.
+//!
+//! Outputs are hash-consed into one DAG before emission: `Rc`-shared nodes
+//! are visited once and structurally equal subtrees collapse to a single
+//! node, so any non-trivial node referenced more than once is computed once
+//! into a temporary (`cse0`, `cse1`, …). The emitted code evaluates exactly
+//! what [`crate::symbolic::eval`] would, including `sign(0) = 1`.
+
+use crate::symbolic::{free_variables, BinOp, Expr, Func, SymExpr, UnaryOp};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Source language for [`generate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodegenTarget {
+    /// A `pub fn` over `f64` arguments returning `f64` or `[f64; N]`.
+    Rust,
+    /// A C99 function writing its outputs to `double out[N]` (needs `-lm`).
+    C,
+    /// One [`crate::expr`] formula per output, temporaries as `let` bindings.
+    Expr,
+}
+
+impl CodegenTarget {
+    /// Parse `"rust"`, `"c"` or `"expr"`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "rust" | "rs" => Ok(CodegenTarget::Rust),
+            "c" => Ok(CodegenTarget::C),
+            "expr" => Ok(CodegenTarget::Expr),
+            other => Err(format!("unknown codegen target '{other}' (expected rust, c or expr)")),
+        }
+    }
+}
+
+// ── Expression DAG ──────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Node {
+    Var(String),
+    /// Constant by bit pattern so nodes can be hashed.
+    Const(u64),
+    Bin(BinOp, usize, usize),
+    Un(UnaryOp, usize),
+    Fun(Func, usize),
+    Sum(Vec<usize>),
+    Prod(Vec<usize>),
+}
+
+impl Node {
+    fn children(&self) -> Vec<usize> {
+        match self {
+            Node::Var(_) | Node::Const(_) => Vec::new(),
+            Node::Bin(_, a, b) => vec![*a, *b],
+            Node::Un(_, a) | Node::Fun(_, a) => vec![*a],
+            Node::Sum(xs) | Node::Prod(xs) => xs.clone(),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        matches!(self, Node::Var(_) | Node::Const(_))
+    }
+}
+
+/// Hash-consed DAG; children always have smaller ids than their parents.
+#[derive(Default)]
+struct Dag {
+    nodes: Vec<Node>,
+    ids: HashMap<Node, usize>,
+    seen: HashMap<*const SymExpr, usize>,
+}
+
+impl Dag {
+    fn intern(&mut self, expr: &Expr) -> usize {
+        if let Some(&id) = self.seen.get(&Rc::as_ptr(expr)) {
+            return id;
+        }
+        let node = match expr.as_ref() {
+            SymExpr::Variable(name) => Node::Var(name.clone()),
+            SymExpr::Constant(v) => Node::Const(v.to_bits()),
+            SymExpr::BinaryOp { op, lhs, rhs } => {
+                let (a, b) = (self.intern(lhs), self.intern(rhs));
+                Node::Bin(*op, a, b)
+            }
+            SymExpr::UnaryOp { op, operand } => Node::Un(*op, self.intern(operand)),
+            SymExpr::Function { func, arg } => Node::Fun(*func, self.intern(arg)),
+            SymExpr::Sum(terms) => Node::Sum(terms.iter().map(|t| self.intern(t)).collect()),
+            SymExpr::Product(factors) => Node::Prod(factors.iter().map(|f| self.intern(f)).collect()),
+        };
+        let id = match self.ids.get(&node) {
+            Some(&id) => id,
+            None => {
+                self.nodes.push(node.clone());
+                self.ids.insert(node, self.nodes.len() - 1);
+                self.nodes.len() - 1
+            }
+        };
+        self.seen.insert(Rc::as_ptr(expr), id);
+        id
+    }
+
+    /// Reference counts: one per parent edge and one per output root.
+    fn uses(&self, roots: &[usize]) -> Vec<usize> {
+        let mut uses = vec![0; self.nodes.len()];
+        for node in &self.nodes {
+            for c in node.children() {
+                uses[c] += 1;
+            }
+        }
+        for &r in roots {
+            uses[r] += 1;
+        }
+        uses
+    }
+}
+
+// ── Emission ────────────────────────────────────────────────────────────────
+
+struct Emitter<'a> {
+    dag: &'a Dag,
+    target: CodegenTarget,
+    temps: Vec<Option<String>>,
+}
+
+impl Emitter<'_> {
+    fn render(&self, id: usize) -> String {
+        match &self.temps[id] {
+            Some(name) => name.clone(),
+            None => self.render_node(id),
+        }
+    }
+
+    fn literal(&self, v: f64) -> String {
+        let text = if v.is_finite() {
+            format!("{:?}", v.abs())
+        } else if v.is_nan() {
+            match self.target {
+                CodegenTarget::Rust => "f64::NAN".to_string(),
+                CodegenTarget::C => "NAN".to_string(),
+                CodegenTarget::Expr => "(0/0)".to_string(),
+            }
+        } else {
+            match self.target {
+                CodegenTarget::Rust => "f64::INFINITY".to_string(),
+                CodegenTarget::C => "INFINITY".to_string(),
+                CodegenTarget::Expr => "(1/0)".to_string(),
+            }
+        };
+        if v.is_sign_negative() && !v.is_nan() { format!("(-{text})") } else { text }
+    }
+
+    /// Rust method receiver: literals need an explicit `f64` type.
+    fn receiver(&self, id: usize) -> String {
+        match (&self.temps[id], &self.dag.nodes[id]) {
+            (None, Node::Const(bits)) if f64::from_bits(*bits).is_finite() => {
+                let v = f64::from_bits(*bits);
+                let text = format!("{:?}_f64", v.abs());
+                if v.is_sign_negative() { format!("(-{text})") } else { text }
+            }
+            _ => self.render(id),
+        }
+    }
+
+    fn render_node(&self, id: usize) -> String {
+        let rust = self.target == CodegenTarget::Rust;
+        match &self.dag.nodes[id] {
+            Node::Var(name) => name.clone(),
+            Node::Const(bits) => self.literal(f64::from_bits(*bits)),
+            Node::Bin(op, a, b) => {
+                let sym = match op {
+                    BinOp::Add => "+",
+                    BinOp::Sub => "-",
+                    BinOp::Mul => "*",
+                    BinOp::Div => "/",
+                    BinOp::Pow => return self.render_pow(*a, *b),
+                };
+                format!("({} {sym} {})", self.render(*a), self.render(*b))
+            }
+            Node::Un(op, a) => {
+                let x = self.render(*a);
+                match (op, self.target) {
+                    (UnaryOp::Neg, _) => format!("(-{x})"),
+                    (UnaryOp::Abs, CodegenTarget::Rust) => format!("{}.abs()", self.receiver(*a)),
+                    (UnaryOp::Floor, CodegenTarget::Rust) => format!("{}.floor()", self.receiver(*a)),
+                    (UnaryOp::Ceil, CodegenTarget::Rust) => format!("{}.ceil()", self.receiver(*a)),
+                    (UnaryOp::Sign, CodegenTarget::Rust) => format!("{}.signum()", self.receiver(*a)),
+                    (UnaryOp::Abs, CodegenTarget::C) => format!("fabs({x})"),
+                    (UnaryOp::Sign, CodegenTarget::C) => format!("copysign(1.0, {x})"),
+                    (UnaryOp::Abs, CodegenTarget::Expr) => format!("abs({x})"),
+                    (UnaryOp::Sign, CodegenTarget::Expr) => format!("if({x} < 0, -1, 1)"),
+                    (UnaryOp::Floor, _) => format!("floor({x})"),
+                    (UnaryOp::Ceil, _) => format!("ceil({x})"),
+                }
+            }
+            Node::Fun(f, a) => {
+                let name = match f {
+                    Func::Sin => "sin",
+                    Func::Cos => "cos",
+                    Func::Tan => "tan",
+                    Func::Asin => "asin",
+                    Func::Acos => "acos",
+                    Func::Atan => "atan",
+                    Func::Exp => "exp",
+                    Func::Ln if self.target == CodegenTarget::C => "log",
+                    Func::Ln => "ln",
+                    Func::Log10 => "log10",
+                    Func::Sqrt => "sqrt",
+                    Func::Sinh => "sinh",
+                    Func::Cosh => "cosh",
+                    Func::Tanh => "tanh",
+                };
+                if rust {
+                    format!("{}.{name}()", self.receiver(*a))
+                } else {
+                    format!("{name}({})", self.render(*a))
+                }
+            }
+            Node::Sum(xs) | Node::Prod(xs) => {
+                let sep = if matches!(self.dag.nodes[id], Node::Sum(_)) { " + " } else { " * " };
+                if xs.is_empty() {
+                    return self.literal(if sep == " + " { 0.0 } else { 1.0 });
+                }
+                format!("({})", xs.iter().map(|&x| self.render(x)).collect::<Vec<_>>().join(sep))
+            }
+        }
+    }
+
+    fn render_pow(&self, base: usize, exp: usize) -> String {
+        let integer = match (&self.temps[exp], &self.dag.nodes[exp]) {
+            (None, Node::Const(bits)) => {
+                let v = f64::from_bits(*bits);
+                (v.fract() == 0.0 && v.abs() <= i32::MAX as f64).then_some(v as i32)
+            }
+            _ => None,
+        };
+        match (self.target, integer) {
+            (CodegenTarget::Rust, Some(n)) => format!("{}.powi({n})", self.receiver(base)),
+            (CodegenTarget::Rust, None) => format!("{}.powf({})", self.receiver(base), self.render(exp)),
+            (CodegenTarget::C, _) => format!("pow({}, {})", self.render(base), self.render(exp)),
+            // `expr` binds unary minus tighter than `^`, which the
+            // parenthesised operands already make explicit.
+            (CodegenTarget::Expr, _) => format!("({}^{})", self.render(base), self.render(exp)),
+        }
+    }
+}
+
+/// Assign temporaries to shared nodes, returning `(name, value)` in
+/// dependency order.
+fn eliminate(emitter: &mut Emitter, uses: &[usize], prefix_start: usize) -> Vec<(String, String)> {
+    let mut lets = Vec::new();
+    for (id, &count) in uses.iter().enumerate() {
+        if count >= 2 && !emitter.dag.nodes[id].is_leaf() {
+            let value = emitter.render_node(id);
+            let name = format!("cse{}", prefix_start + lets.len());
+            emitter.temps[id] = Some(name.clone());
+            lets.push((name, value));
+        }
+    }
+    lets
+}
+
+/// Rust keywords (strict, reserved and 2021-edition) plus `f64`, which the
+/// generated signature uses.
+const RUST_RESERVED: &[&str] = &[
+    "Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate", "do",
+    "dyn", "else", "enum", "extern", "f64", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "self",
+    "static", "struct", "super", "trait", "true", "try", "type", "typeof", "union", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// C99 keywords plus the names the generated function relies on: the `out`
+/// parameter and the `<math.h>` functions and macros it calls.
+const C_RESERVED: &[&str] = &[
+    "_Bool", "_Complex", "_Imaginary", "auto", "break", "case", "char", "const", "continue", "default", "do",
+    "double", "else", "enum", "extern", "float", "for", "goto", "if", "inline", "int", "long", "register",
+    "restrict", "return", "short", "signed", "sizeof", "static", "struct", "switch", "typedef", "union",
+    "unsigned", "void", "volatile", "while", "out", "INFINITY", "NAN", "acos", "asin", "atan", "ceil",
+    "copysign", "cos", "cosh", "exp", "fabs", "floor", "log", "log10", "pow", "sin", "sinh", "sqrt", "tan",
+    "tanh",
+];
+
+/// Names the `expr` language reserves for bindings and constants.
+const EXPR_RESERVED: &[&str] = &["let", "if", "pi", "PI", "e", "E"];
+
+/// Check that `name` can be emitted as an identifier; `target` is `None` for
+/// names that do not appear in the generated code (C outputs are comments,
+/// `expr` outputs are positional).
+fn check_identifier(name: &str, what: &str, target: Option<CodegenTarget>) -> Result<(), String> {
+    let mut chars = name.chars();
+    let ok = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !ok {
+        return Err(format!("{what} '{name}' is not a valid identifier"));
+    }
+    if name.starts_with("cse") && name[3..].chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("{what} '{name}' clashes with the generated temporaries"));
+    }
+    let (reserved, language) = match target {
+        Some(CodegenTarget::Rust) => (RUST_RESERVED, "Rust"),
+        Some(CodegenTarget::C) => (C_RESERVED, "C"),
+        Some(CodegenTarget::Expr) => (EXPR_RESERVED, "expr"),
+        None => return Ok(()),
+    };
+    if reserved.contains(&name) {
+        return Err(format!("{what} '{name}' is reserved in {language}"));
+    }
+    Ok(())
+}
+
+/// A single output as an `expr` formula with no `let` bindings (for inputs
+/// that split on `;`, such as ODE equation lists).
+pub fn to_expr_string(expr: &Expr) -> String {
+    let mut dag = Dag::default();
+    let root = dag.intern(expr);
+    let emitter = Emitter { temps: vec![None; dag.nodes.len()], dag: &dag, target: CodegenTarget::Expr };
+    emitter.render(root)
+}
+
+/// Generate source computing `outputs` from `inputs`.
+///
+/// `Rust` and `C` emit one function `name` sharing temporaries across all
+/// outputs; `Expr` emits one self-contained formula per output, one per
+/// line. Every free symbol of the outputs must be listed in `inputs`.
+pub fn generate(
+    name: &str,
+    inputs: &[String],
+    outputs: &[(String, Expr)],
+    target: CodegenTarget,
+) -> Result<String, String> {
+    if outputs.is_empty() {
+        return Err("no outputs to generate".to_string());
+    }
+    let emitted = |names: bool| names.then_some(target);
+    check_identifier(name, "function name", emitted(target != CodegenTarget::Expr))?;
+    for input in inputs {
+        check_identifier(input, "input", Some(target))?;
+    }
+    for (out, expr) in outputs {
+        check_identifier(out, "output", emitted(target == CodegenTarget::Rust))?;
+        if let Some(free) = free_variables(expr).into_iter().find(|v| !inputs.contains(v)) {
+            return Err(format!("output '{out}' uses '{free}', which is not an input"));
+        }
+    }
+
+    if target == CodegenTarget::Expr {
+        let lines: Vec<String> = outputs
+            .iter()
+            .map(|(_, expr)| {
+                let mut dag = Dag::default();
+                let root = dag.intern(expr);
+                let uses = dag.uses(&[root]);
+                let mut emitter = Emitter { temps: vec![None; dag.nodes.len()], dag: &dag, target };
+                let lets = eliminate(&mut emitter, &uses, 0);
+                let body = emitter.render(root);
+                lets.iter().map(|(n, v)| format!("let {n} = {v}; ")).collect::<String>() + &body
+            })
+            .collect();
+        return Ok(lines.join("\n"));
+    }
+
+    let mut dag = Dag::default();
+    let roots: Vec<usize> = outputs.iter().map(|(_, e)| dag.intern(e)).collect();
+    let uses = dag.uses(&roots);
+    let mut emitter = Emitter { temps: vec![None; dag.nodes.len()], dag: &dag, target };
+    let lets = eliminate(&mut emitter, &uses, 0);
+    let values: Vec<String> = roots.iter().map(|&r| emitter.render(r)).collect();
+    let n = outputs.len();
+
+    let mut src = String::new();
+    match target {
+        CodegenTarget::Rust => {
+            let args = inputs.iter().map(|i| format!("{i}: f64")).collect::<Vec<_>>().join(", ");
+            let ret = if n == 1 { "f64".to_string() } else { format!("[f64; {n}]") };
+            src.push_str("/// Generated by ChainSolve from symbolic expressions.\n");
+            src.push_str(&format!("pub fn {name}({args}) -> {ret} {{\n"));
+            for (t, v) in &lets {
+                src.push_str(&format!("    let {t} = {v};\n"));
+            }
+            for ((out, _), v) in outputs.iter().zip(&values) {
+                src.push_str(&format!("    let {out} = {v};\n"));
+            }
+            let names: Vec<&str> = outputs.iter().map(|(o, _)| o.as_str()).collect();
+            if n == 1 {
+                src.push_str(&format!("    {}\n}}\n", names[0]));
+            } else {
+                src.push_str(&format!("    [{}]\n}}\n", names.join(", ")));
+            }
+        }
+        CodegenTarget::C => {
+            let mut args: Vec<String> = inputs.iter().map(|i| format!("double {i}")).collect();
+            args.push(format!("double out[{n}]"));
+            src.push_str("#include <math.h>\n\n/* Generated by ChainSolve from symbolic expressions. */\n");
+            src.push_str(&format!("void {name}({})\n{{\n", args.join(", ")));
+            for (t, v) in &lets {
+                src.push_str(&format!("    const double {t} = {v};\n"));
+            }
+            for (k, ((out, _), v)) in outputs.iter().zip(&values).enumerate() {
+                src.push_str(&format!("    out[{k}] = {v}; /* {out} */\n"));
+            }
+            src.push_str("}\n");
+        }
+        CodegenTarget::Expr => unreachable!(),
+    }
+    Ok(src)
+}
+
+// ── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbolic::{eval, parse_expr};
+
+    fn outputs(pairs: &[(&str, &str)]) -> Vec<(String, Expr)> {
+        pairs.iter().map(|(n, e)| (n.to_string(), parse_expr(e).unwrap())).collect()
+    }
+
+    #[test]
+    fn shared_subexpressions_become_temporaries() {
+        let outs = outputs(&[("f", "sin(x*y) + sin(x*y)^2"), ("g", "cos(x*y) * sin(x*y)")]);
+        let inputs = vec!["x".to_string(), "y".to_string()];
+        let rust = generate("model", &inputs, &outs, CodegenTarget::Rust).unwrap();
+        assert!(rust.contains("pub fn model(x: f64, y: f64) -> [f64; 2] {"), "{rust}");
+        assert!(rust.contains("let cse0 = (x * y);"), "{rust}");
+        assert!(rust.contains("let cse1 = cse0.sin();"), "{rust}");
+        assert!(rust.contains("let f = (cse1 + cse1.powi(2));"), "{rust}");
+        assert!(rust.contains("let g = (cse0.cos() * cse1);"), "{rust}");
+
+        let c = generate("model", &inputs, &outs, CodegenTarget::C).unwrap();
+        assert!(c.contains("void model(double x, double y, double out[2])"), "{c}");
+        assert!(c.contains("const double cse1 = sin(cse0);"), "{c}");
+        assert!(c.contains("out[0] = (cse1 + pow(cse1, 2.0)); /* f */"), "{c}");
+
+        assert!(generate("model", &inputs[..1], &outs, CodegenTarget::C).unwrap_err().contains("'y'"));
+        assert!(generate("2model", &inputs, &outs, CodegenTarget::C).is_err());
+    }
+
+    #[test]
+    fn target_keywords_are_rejected() {
+        let outs = outputs(&[("f", "x + 1")]);
+        let x = vec!["x".to_string()];
+        for (name, target) in [("fn", CodegenTarget::Rust), ("type", CodegenTarget::Rust), ("double", CodegenTarget::C)] {
+            assert!(generate(name, &x, &outs, target).unwrap_err().contains("reserved"), "{name}");
+        }
+        let int = vec!["int".to_string()];
+        assert!(generate("model", &int, &outputs(&[("f", "int + 1")]), CodegenTarget::C).unwrap_err().contains("reserved in C"));
+        assert!(generate("model", &int, &outputs(&[("f", "int + 1")]), CodegenTarget::Rust).is_ok());
+        assert!(generate("model", &["out".to_string()], &outputs(&[("f", "out")]), CodegenTarget::C).is_err());
+        assert!(generate("model", &["e".to_string()], &outputs(&[("f", "e")]), CodegenTarget::Expr).is_err());
+        // Output names only appear in comments in C.
+        assert!(generate("model", &x, &outputs(&[("double", "x")]), CodegenTarget::C).is_ok());
+    }
+
+    #[test]
+    fn expr_target_round_trips_through_the_expr_language() {
+        let outs = outputs(&[("f", "-2^2 + exp(-x) * (1 + exp(-x)) + abs(x - 3) / sqrt(x)"), ("g", "sign(x) * ln(x)")]);
+        let code = generate("f", &["x".to_string()], &outs, CodegenTarget::Expr).unwrap();
+        let lines: Vec<&str> = code.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("let cse0 = exp((-x)); "), "{code}");
+        for (line, (_, expr)) in lines.iter().zip(&outs) {
+            for x in [0.5, 2.0, 7.25] {
+                let vars = HashMap::from([("x".to_string(), x)]);
+                let expected = eval(expr, &vars);
+                assert!((crate::expr::eval_expr(line, &vars).unwrap() - expected).abs() < 1e-12, "{line}");
+                let plain = to_expr_string(expr);
+                assert!((crate::expr::eval_expr(&plain, &vars).unwrap() - expected).abs() < 1e-12, "{plain}");
+            }
+        }
+    }
+}
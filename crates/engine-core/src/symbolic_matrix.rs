@@ -0,0 +1,476 @@
+//! `symbolic_matrix` — matrices of [`symbolic::Expr`](crate::symbolic::Expr).
+//!
+//! Dense row-major matrices with arithmetic, determinant and inverse for
+//! small sizes (cofactor expansion, so entries stay exact expressions),
+//! Jacobians and Hessians of vector expressions, and Euler–Lagrange
+//! equations of motion derived from a Lagrangian `L = T − V`.
+
+use crate::symbolic::{
+    self, add, clean, con, differentiate, div, free_variables, is_zero_expr, mul, neg, parse_expr, simplify, sub, to_latex,
+    var, zero, Expr,
+};
+use std::collections::HashMap;
+
+/// Largest size accepted by [`SymMatrix::determinant`] and
+/// [`SymMatrix::inverse`]; cofactor expansion grows as `n·2ⁿ`.
+pub const MAX_SYMBOLIC_SIZE: usize = 6;
+
+/// A dense matrix of symbolic expressions, stored row-major.
+#[derive(Debug, Clone)]
+pub struct SymMatrix {
+    pub rows: usize,
+    pub cols: usize,
+    pub data: Vec<Expr>,
+}
+
+impl SymMatrix {
+    /// Build from row-major entries.
+    pub fn new(rows: usize, cols: usize, data: Vec<Expr>) -> Result<Self, String> {
+        if data.len() != rows * cols {
+            return Err(format!("{rows}×{cols} matrix needs {} entries, got {}", rows * cols, data.len()));
+        }
+        Ok(SymMatrix { rows, cols, data })
+    }
+
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        SymMatrix { rows, cols, data: vec![zero(); rows * cols] }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut m = Self::zeros(n, n);
+        for i in 0..n {
+            m.data[i * n + i] = con(1.0);
+        }
+        m
+    }
+
+    /// Build from rows, which must all have the same length.
+    pub fn from_rows(rows: Vec<Vec<Expr>>) -> Result<Self, String> {
+        let cols = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|r| r.len() != cols) {
+            return Err("matrix rows have different lengths".to_string());
+        }
+        let n = rows.len();
+        Self::new(n, cols, rows.into_iter().flatten().collect())
+    }
+
+    /// Parse `[a, b; c, d]`: rows separated by `;`, entries by `,` (commas
+    /// inside parentheses belong to the entry). The brackets are optional.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let t = s.trim();
+        let t = t.strip_prefix('[').and_then(|t| t.strip_suffix(']')).unwrap_or(t);
+        let rows = split_top_level(t, ';')
+            .into_iter()
+            .filter(|r| !r.trim().is_empty())
+            .map(|r| split_top_level(r, ',').into_iter().map(parse_expr).collect::<Result<Vec<_>, _>>())
+            .collect::<Result<Vec<_>, _>>()?;
+        if rows.is_empty() {
+            return Err("empty matrix".to_string());
+        }
+        Self::from_rows(rows)
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> &Expr {
+        &self.data[i * self.cols + j]
+    }
+
+    fn zip_with(&self, other: &Self, f: fn(Expr, Expr) -> Expr, what: &str) -> Result<Self, String> {
+        if (self.rows, self.cols) != (other.rows, other.cols) {
+            return Err(format!(
+                "cannot {what} {}×{} and {}×{} matrices",
+                self.rows, self.cols, other.rows, other.cols
+            ));
+        }
+        let data = self.data.iter().zip(&other.data).map(|(a, b)| simplify(&f(a.clone(), b.clone()))).collect();
+        Ok(SymMatrix { rows: self.rows, cols: self.cols, data })
+    }
+
+    pub fn add(&self, other: &Self) -> Result<Self, String> {
+        self.zip_with(other, add, "add")
+    }
+
+    pub fn sub(&self, other: &Self) -> Result<Self, String> {
+        self.zip_with(other, sub, "subtract")
+    }
+
+    pub fn mul(&self, other: &Self) -> Result<Self, String> {
+        if self.cols != other.rows {
+            return Err(format!(
+                "cannot multiply {}×{} by {}×{}",
+                self.rows, self.cols, other.rows, other.cols
+            ));
+        }
+        let mut data = Vec::with_capacity(self.rows * other.cols);
+        for i in 0..self.rows {
+            for j in 0..other.cols {
+                let terms = (0..self.cols).map(|k| mul(self.get(i, k).clone(), other.get(k, j).clone()));
+                data.push(clean(&terms.reduce(add).unwrap_or_else(zero)));
+            }
+        }
+        Ok(SymMatrix { rows: self.rows, cols: other.cols, data })
+    }
+
+    pub fn scale(&self, factor: &Expr) -> Self {
+        let data = self.data.iter().map(|e| simplify(&mul(factor.clone(), e.clone()))).collect();
+        SymMatrix { rows: self.rows, cols: self.cols, data }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let data = (0..self.cols).flat_map(|j| (0..self.rows).map(move |i| (i, j))).map(|(i, j)| self.get(i, j).clone());
+        SymMatrix { rows: self.cols, cols: self.rows, data: data.collect() }
+    }
+
+    fn check_square(&self, what: &str) -> Result<(), String> {
+        if self.rows != self.cols {
+            return Err(format!("{what} needs a square matrix, got {}×{}", self.rows, self.cols));
+        }
+        if self.rows > MAX_SYMBOLIC_SIZE {
+            return Err(format!("symbolic {what} is limited to {MAX_SYMBOLIC_SIZE}×{MAX_SYMBOLIC_SIZE}"));
+        }
+        Ok(())
+    }
+
+    /// Determinant by cofactor expansion along the rows, memoised on the
+    /// set of columns still in play.
+    pub fn determinant(&self) -> Result<Expr, String> {
+        self.check_square("determinant")?;
+        Ok(clean(&self.minor_det(&(0..self.rows).collect::<Vec<_>>(), &(0..self.cols).collect::<Vec<_>>())))
+    }
+
+    /// Determinant of the submatrix with the given rows and columns.
+    fn minor_det(&self, rows: &[usize], cols: &[usize]) -> Expr {
+        fn expand(m: &SymMatrix, rows: &[usize], cols: &[usize], mask: u32, memo: &mut HashMap<u32, Expr>) -> Expr {
+            let depth = mask.count_ones() as usize;
+            if depth == cols.len() {
+                return con(1.0);
+            }
+            if let Some(e) = memo.get(&mask) {
+                return e.clone();
+            }
+            let row = rows[depth];
+            let mut acc: Option<Expr> = None;
+            let mut sign_positive = true;
+            for (k, &col) in cols.iter().enumerate() {
+                if mask & (1 << k) != 0 {
+                    continue;
+                }
+                let entry = m.get(row, col);
+                if !is_zero_expr(entry) {
+                    let term = simplify(&mul(entry.clone(), expand(m, rows, cols, mask | (1 << k), memo)));
+                    acc = Some(match acc {
+                        None if sign_positive => term,
+                        None => neg(term),
+                        Some(a) if sign_positive => add(a, term),
+                        Some(a) => sub(a, term),
+                    });
+                }
+                sign_positive = !sign_positive;
+            }
+            let det = simplify(&acc.unwrap_or_else(zero));
+            memo.insert(mask, det.clone());
+            det
+        }
+        expand(self, rows, cols, 0, &mut HashMap::new())
+    }
+
+    /// Inverse as adjugate / determinant. Fails if the determinant is
+    /// identically zero.
+    pub fn inverse(&self) -> Result<Self, String> {
+        self.check_square("inverse")?;
+        let n = self.rows;
+        let det = self.determinant()?;
+        if is_zero_expr(&det) {
+            return Err("matrix is singular (determinant is identically zero)".to_string());
+        }
+        let mut data = Vec::with_capacity(n * n);
+        for i in 0..n {
+            for j in 0..n {
+                // inv[i][j] = (−1)^{i+j} · M_ji / det.
+                let rows: Vec<usize> = (0..n).filter(|&r| r != j).collect();
+                let cols: Vec<usize> = (0..n).filter(|&c| c != i).collect();
+                let minor = self.minor_det(&rows, &cols);
+                let cofactor = if (i + j) % 2 == 0 { minor } else { neg(minor) };
+                data.push(clean(&div(cofactor, det.clone())));
+            }
+        }
+        Ok(SymMatrix { rows: n, cols: n, data })
+    }
+
+    pub fn simplify(&self) -> Self {
+        SymMatrix { rows: self.rows, cols: self.cols, data: self.data.iter().map(clean).collect() }
+    }
+
+    pub fn substitute(&self, name: &str, replacement: &Expr) -> Self {
+        let data = self.data.iter().map(|e| simplify(&symbolic::substitute(e, name, replacement))).collect();
+        SymMatrix { rows: self.rows, cols: self.cols, data }
+    }
+
+    /// Evaluate every entry, row by row.
+    pub fn eval(&self, vars: &HashMap<String, f64>) -> Vec<Vec<f64>> {
+        (0..self.rows).map(|i| (0..self.cols).map(|j| symbolic::eval(self.get(i, j), vars)).collect()).collect()
+    }
+
+    /// Entries as strings that parse back with [`parse_expr`].
+    pub fn to_strings(&self) -> Vec<Vec<String>> {
+        (0..self.rows).map(|i| (0..self.cols).map(|j| self.get(i, j).to_string()).collect()).collect()
+    }
+
+    pub fn to_latex(&self) -> String {
+        let body: Vec<String> = (0..self.rows)
+            .map(|i| (0..self.cols).map(|j| to_latex(self.get(i, j))).collect::<Vec<_>>().join(" & "))
+            .collect();
+        format!("\\begin{{pmatrix}} {} \\end{{pmatrix}}", body.join(" \\\\ "))
+    }
+}
+
+/// Split on `sep` outside parentheses and brackets.
+fn split_top_level(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0i32, 0);
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            c if c == sep && depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+// ── Jacobians and Hessians ──────────────────────────────────────────────────
+
+/// `J[i][j] = ∂f_i/∂x_j`.
+pub fn jacobian(exprs: &[Expr], vars: &[String]) -> SymMatrix {
+    let data = exprs.iter().flat_map(|f| vars.iter().map(move |v| clean(&differentiate(f, v)))).collect();
+    SymMatrix { rows: exprs.len(), cols: vars.len(), data }
+}
+
+/// Gradient of a scalar expression as a column vector.
+pub fn gradient(expr: &Expr, vars: &[String]) -> SymMatrix {
+    jacobian(std::slice::from_ref(expr), vars).transpose()
+}
+
+/// `H[i][j] = ∂²f/∂x_i∂x_j`; symmetric entries are differentiated once.
+pub fn hessian(expr: &Expr, vars: &[String]) -> SymMatrix {
+    let n = vars.len();
+    let first: Vec<Expr> = vars.iter().map(|v| differentiate(expr, v)).collect();
+    let mut h = SymMatrix::zeros(n, n);
+    for (i, fi) in first.iter().enumerate() {
+        for (j, vj) in vars.iter().enumerate().skip(i) {
+            let d = clean(&differentiate(fi, vj));
+            h.data[i * n + j] = d.clone();
+            h.data[j * n + i] = d;
+        }
+    }
+    h
+}
+
+// ── Lagrangian Mechanics ────────────────────────────────────────────────────
+
+/// Name of the generalised velocity for coordinate `q`.
+pub fn velocity_name(q: &str) -> String {
+    format!("{q}_dot")
+}
+
+/// Name of the generalised acceleration for coordinate `q`.
+pub fn acceleration_name(q: &str) -> String {
+    format!("{q}_ddot")
+}
+
+/// Euler–Lagrange equations `d/dt ∂L/∂q̇ − ∂L/∂q = 0` for each coordinate.
+#[derive(Debug, Clone)]
+pub struct EquationsOfMotion {
+    pub coords: Vec<String>,
+    /// `d/dt ∂L/∂q̇_i − ∂L/∂q_i`, linear in the accelerations `q_ddot`.
+    pub residuals: Vec<Expr>,
+    /// `M[i][j] = ∂²L/∂q̇_i∂q̇_j`.
+    pub mass_matrix: SymMatrix,
+    /// `q̈_i` in terms of coordinates, velocities and parameters.
+    pub accelerations: Vec<Expr>,
+}
+
+impl EquationsOfMotion {
+    /// First-order system for the ODE blocks: `y0..y(n-1)` are the
+    /// coordinates and `yn..y(2n-1)` the velocities; `time` becomes `t`.
+    pub fn first_order_system(&self, time: &str) -> Result<Vec<Expr>, String> {
+        let n = self.coords.len();
+        let mut rename: Vec<(String, Expr)> = Vec::with_capacity(2 * n + 1);
+        for (i, q) in self.coords.iter().enumerate() {
+            rename.push((q.clone(), var(&format!("y{i}"))));
+            rename.push((velocity_name(q), var(&format!("y{}", n + i))));
+        }
+        if time != "t" {
+            rename.push((time.to_string(), var("t")));
+        }
+        let renamed: Vec<&str> = rename.iter().map(|(name, _)| name.as_str()).collect();
+        for a in &self.accelerations {
+            if let Some(clash) = free_variables(a).into_iter().find(|v| {
+                !renamed.contains(&v.as_str())
+                    && (v == "t" || v.strip_prefix('y').is_some_and(|d| !d.is_empty() && d.chars().all(|c| c.is_ascii_digit())))
+            }) {
+                return Err(format!("parameter '{clash}' clashes with the ODE state names"));
+            }
+        }
+        let rename_all = |e: &Expr| {
+            // Rename via fresh placeholders so `y1` produced for one name is
+            // never captured by a later rename.
+            let mut e = e.clone();
+            for (k, (name, _)) in rename.iter().enumerate() {
+                e = symbolic::substitute(&e, name, &var(&format!("__lagrange_{k}")));
+            }
+            for (k, (_, target)) in rename.iter().enumerate() {
+                e = symbolic::substitute(&e, &format!("__lagrange_{k}"), target);
+            }
+            simplify(&e)
+        };
+        let velocities = (0..n).map(|i| var(&format!("y{}", n + i)));
+        Ok(velocities.chain(self.accelerations.iter().map(rename_all)).collect())
+    }
+}
+
+/// Total time derivative of `f(q, q̇, t)`.
+fn time_derivative(f: &Expr, coords: &[String], time: &str) -> Expr {
+    let mut terms = vec![differentiate(f, time)];
+    for q in coords {
+        let (qd, qdd) = (velocity_name(q), acceleration_name(q));
+        terms.push(mul(differentiate(f, q), var(&qd)));
+        terms.push(mul(differentiate(f, &qd), var(&qdd)));
+    }
+    terms.into_iter().reduce(add).unwrap_or_else(zero)
+}
+
+/// Derive the equations of motion of `lagrangian` in the generalised
+/// coordinates `coords`. Velocities appear as `q_dot`, accelerations as
+/// `q_ddot`; every other symbol except `time` is a parameter.
+pub fn lagrange_equations(lagrangian: &Expr, coords: &[String], time: &str) -> Result<EquationsOfMotion, String> {
+    if coords.is_empty() {
+        return Err("at least one generalised coordinate is required".to_string());
+    }
+    if coords.len() > MAX_SYMBOLIC_SIZE {
+        return Err(format!("at most {MAX_SYMBOLIC_SIZE} generalised coordinates are supported"));
+    }
+    let velocities: Vec<String> = coords.iter().map(|q| velocity_name(q)).collect();
+    let momenta: Vec<Expr> = velocities.iter().map(|v| differentiate(lagrangian, v)).collect();
+    let residuals: Vec<Expr> = coords
+        .iter()
+        .zip(&momenta)
+        .map(|(q, p)| clean(&sub(time_derivative(p, coords, time), differentiate(lagrangian, q))))
+        .collect();
+    let mass_matrix = jacobian(&momenta, &velocities);
+    let accel_names: Vec<String> = coords.iter().map(|q| acceleration_name(q)).collect();
+    let accelerations = symbolic::solve_linear_system(&residuals, &accel_names)
+        .map_err(|e| format!("cannot solve for the accelerations: {e}"))?
+        .iter()
+        .map(clean)
+        .collect();
+    Ok(EquationsOfMotion { coords: coords.to_vec(), residuals, mass_matrix, accelerations })
+}
+
+// ── Tests ───────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, f64)]) -> HashMap<String, f64> {
+        pairs.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    }
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn assert_close(a: &[Vec<f64>], b: &[Vec<f64>]) {
+        for (ra, rb) in a.iter().zip(b) {
+            for (x, y) in ra.iter().zip(rb) {
+                assert!((x - y).abs() < 1e-9, "{a:?} != {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn parse_multiply_transpose() {
+        let a = SymMatrix::parse("[a, b; c, d]").unwrap();
+        let b = SymMatrix::parse("[1; x]").unwrap();
+        let ab = a.mul(&b).unwrap();
+        assert_eq!((ab.rows, ab.cols), (2, 1));
+        let at = vars(&[("a", 1.0), ("b", 2.0), ("c", 3.0), ("d", 4.0), ("x", 5.0)]);
+        assert_close(&ab.eval(&at), &[vec![11.0], vec![23.0]]);
+        assert_close(&a.transpose().eval(&at), &[vec![1.0, 3.0], vec![2.0, 4.0]]);
+        assert!(b.mul(&b).is_err());
+        assert!(SymMatrix::parse("[1, 2; 3]").is_err());
+    }
+
+    #[test]
+    fn determinant_and_inverse() {
+        let m = SymMatrix::parse("[a, b; c, d]").unwrap();
+        let det = m.determinant().unwrap();
+        let at = vars(&[("a", 2.0), ("b", 7.0), ("c", 1.0), ("d", 5.0)]);
+        assert!((symbolic::eval(&det, &at) - 3.0).abs() < 1e-12);
+        let inv = m.inverse().unwrap();
+        assert_close(&m.mul(&inv).unwrap().eval(&at), &SymMatrix::identity(2).eval(&at));
+
+        let rot = SymMatrix::parse("[cos(t), -sin(t), 0; sin(t), cos(t), 0; 0, 0, k]").unwrap();
+        let at = vars(&[("t", 0.7), ("k", 3.0)]);
+        assert!((symbolic::eval(&rot.determinant().unwrap(), &at) - 3.0).abs() < 1e-12);
+        assert_close(&rot.inverse().unwrap().mul(&rot).unwrap().eval(&at), &SymMatrix::identity(3).eval(&at));
+
+        assert!(SymMatrix::parse("[x, 2*x; 1, 2]").unwrap().inverse().unwrap_err().contains("singular"));
+        assert!(SymMatrix::parse("[1, 2]").unwrap().determinant().is_err());
+    }
+
+    #[test]
+    fn jacobian_and_hessian() {
+        let f = vec![parse_expr("x^2*y").unwrap(), parse_expr("5*x + sin(y)").unwrap()];
+        let j = jacobian(&f, &names(&["x", "y"]));
+        let at = vars(&[("x", 1.5), ("y", 0.5)]);
+        assert_close(&j.eval(&at), &[vec![1.5, 2.25], vec![5.0, 0.5f64.cos()]]);
+
+        let h = hessian(&parse_expr("x^3 + x*y^2").unwrap(), &names(&["x", "y"]));
+        assert_close(&h.eval(&at), &[vec![9.0, 1.0], vec![1.0, 3.0]]);
+        assert_close(&gradient(&parse_expr("x*y").unwrap(), &names(&["x", "y"])).eval(&at), &[vec![0.5], vec![1.5]]);
+    }
+
+    #[test]
+    fn pendulum_equations_of_motion() {
+        // L = ½ m l² θ̇² + m g l cos θ  ⇒  θ̈ = −(g/l) sin θ.
+        let l = parse_expr("0.5*m*l^2*theta_dot^2 + m*g*l*cos(theta)").unwrap();
+        let eom = lagrange_equations(&l, &names(&["theta"]), "t").unwrap();
+        let at = vars(&[("m", 2.0), ("l", 0.5), ("g", 9.81), ("theta", 0.3), ("theta_dot", 1.1)]);
+        assert!((symbolic::eval(&eom.accelerations[0], &at) + 9.81 / 0.5 * 0.3f64.sin()).abs() < 1e-9);
+        assert_close(&eom.mass_matrix.eval(&at), &[vec![0.5]]);
+
+        let ode = eom.first_order_system("t").unwrap();
+        assert_eq!(ode[0].to_string(), "y1");
+        let at = vars(&[("l", 0.5), ("g", 9.81), ("y0", 0.3), ("m", 2.0)]);
+        assert!((symbolic::eval(&ode[1], &at) + 9.81 / 0.5 * 0.3f64.sin()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn coupled_oscillators_have_a_full_mass_matrix() {
+        // Cart (x) carrying a pendulum (p): mass matrix couples both.
+        let t = "0.5*(M + m)*x_dot^2 + m*l*x_dot*p_dot*cos(p) + 0.5*m*l^2*p_dot^2";
+        let v = "-m*g*l*cos(p) + 0.5*k*x^2";
+        let l = parse_expr(&format!("({t}) - ({v})")).unwrap();
+        let eom = lagrange_equations(&l, &names(&["x", "p"]), "t").unwrap();
+        let at = vars(&[
+            ("M", 3.0), ("m", 1.0), ("l", 0.8), ("g", 9.81), ("k", 2.0),
+            ("x", 0.1), ("p", 0.4), ("x_dot", -0.2), ("p_dot", 0.9),
+        ]);
+        let mm = eom.mass_matrix.eval(&at);
+        assert!((mm[0][1] - 0.8 * 0.4f64.cos()).abs() < 1e-12 && (mm[0][1] - mm[1][0]).abs() < 1e-12);
+        // The residuals vanish at the solved accelerations.
+        let mut full = at.clone();
+        full.insert("x_ddot".into(), symbolic::eval(&eom.accelerations[0], &at));
+        full.insert("p_ddot".into(), symbolic::eval(&eom.accelerations[1], &at));
+        for r in &eom.residuals {
+            assert!(symbolic::eval(r, &full).abs() < 1e-9);
+        }
+        assert!(lagrange_equations(&l, &[], "t").is_err());
+    }
+}
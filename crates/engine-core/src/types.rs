@@ -69,6 +69,83 @@ pub enum Value {
     /// the full decimal expansion losslessly across the WASM boundary. The
     /// `approx` f64 provides a fast preview for display/comparison.
     HighPrecision { display: String, approx: f64, precision: u32 },
+    /// Opaque binary payload produced by exporters (e.g. a Parquet file).
+    /// `data` is base64 in JSON. When the eval options set `binaryThreshold`,
+    /// the WASM wrapper leaves it out of the JSON result and JS copies it out
+    /// as a `Uint8Array` with `value_bytes` (see `engine-wasm`).
+    Bytes {
+        #[serde(with = "base64_bytes")]
+        data: Vec<u8>,
+        /// MIME type of the payload, e.g. `application/vnd.apache.parquet`.
+        mime: String,
+    },
+    /// Sparse matrix in compressed sparse row form (see [`crate::sparse::CsrMatrix`]),
+    /// so large sparse operators cross ports without being densified.
+    #[serde(rename_all = "camelCase")]
+    SparseMatrix {
+        rows: usize,
+        cols: usize,
+        row_ptrs: Vec<usize>,
+        col_indices: Vec<usize>,
+        values: Vec<f64>,
+    },
+}
+
+/// Serde adapter: `Vec<u8>` ⇄ standard base64 string (RFC 4648, padded).
+mod base64_bytes {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    pub fn encode(bytes: &[u8]) -> String {
+        let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+        for chunk in bytes.chunks(3) {
+            let n = (chunk[0] as u32) << 16
+                | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+                | *chunk.get(2).unwrap_or(&0) as u32;
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    out.push(ALPHABET[(n >> (18 - 6 * i)) as usize & 63] as char);
+                } else {
+                    out.push('=');
+                }
+            }
+        }
+        out
+    }
+
+    pub fn decode(text: &str) -> Result<Vec<u8>, String> {
+        let text = text.trim_end_matches('=');
+        let mut out = Vec::with_capacity(text.len() * 3 / 4);
+        let mut acc = 0u32;
+        let mut bits = 0;
+        for c in text.bytes() {
+            let v = match c {
+                b'A'..=b'Z' => c - b'A',
+                b'a'..=b'z' => c - b'a' + 26,
+                b'0'..=b'9' => c - b'0' + 52,
+                b'+' => 62,
+                b'/' => 63,
+                _ => return Err(format!("invalid base64 character '{}'", c as char)),
+            };
+            acc = (acc << 6) | v as u32;
+            bits += 6;
+            if bits >= 8 {
+                bits -= 8;
+                out.push((acc >> bits) as u8);
+            }
+        }
+        Ok(out)
+    }
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(d)?;
+        decode(&text).map_err(de::Error::custom)
+    }
 }
 
 impl Value {
@@ -108,6 +185,20 @@ impl Value {
         assert_eq!(data.len(), rows * cols);
         Value::Matrix { rows, cols, data }
     }
+
+    pub fn bytes(data: Vec<u8>, mime: impl Into<String>) -> Self {
+        Value::Bytes { data, mime: mime.into() }
+    }
+
+    pub fn sparse(m: crate::sparse::CsrMatrix) -> Self {
+        Value::SparseMatrix {
+            rows: m.rows,
+            cols: m.cols,
+            row_ptrs: m.row_ptrs,
+            col_indices: m.col_indices,
+            values: m.values,
+        }
+    }
 }
 
 /// Convenience: build a single-row `Value::Table` from parallel column-name and
@@ -160,6 +251,32 @@ impl Value {
         }
     }
 
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes { data, .. } => Some(data),
+            _ => None,
+        }
+    }
+
+    /// The CSR matrix of a `SparseMatrix` value; `None` for other variants
+    /// and for malformed CSR data (see [`crate::sparse::CsrMatrix::validate`]),
+    /// which can arrive from a hand-edited project file.
+    pub fn as_sparse(&self) -> Option<crate::sparse::CsrMatrix> {
+        match self {
+            Value::SparseMatrix { rows, cols, row_ptrs, col_indices, values } => {
+                let m = crate::sparse::CsrMatrix {
+                    rows: *rows,
+                    cols: *cols,
+                    row_ptrs: row_ptrs.clone(),
+                    col_indices: col_indices.clone(),
+                    values: values.clone(),
+                };
+                m.validate().is_ok().then_some(m)
+            }
+            _ => None,
+        }
+    }
+
     pub fn is_error(&self) -> bool {
         matches!(self, Value::Error { .. })
     }
@@ -176,6 +293,8 @@ impl Value {
             Value::Complex { .. } => "complex",
             Value::Matrix { .. } => "matrix",
             Value::HighPrecision { .. } => "highPrecision",
+            Value::Bytes { .. } => "bytes",
+            Value::SparseMatrix { .. } => "sparseMatrix",
         }
     }
 
@@ -201,6 +320,15 @@ impl Value {
             Value::Complex { re, im } => ValueSummary::Complex { re: *re, im: *im },
             Value::Matrix { rows, cols, data: _ } => ValueSummary::Matrix { rows: *rows, cols: *cols },
             Value::HighPrecision { approx, .. } => ValueSummary::Scalar { value: *approx },
+            Value::Bytes { data, mime } => ValueSummary::Bytes {
+                length: data.len(),
+                mime: mime.clone(),
+            },
+            Value::SparseMatrix { rows, cols, values, .. } => ValueSummary::SparseMatrix {
+                rows: *rows,
+                cols: *cols,
+                nnz: values.len(),
+            },
         }
     }
 }
@@ -312,6 +440,8 @@ pub enum ValueSummary {
     Interval { lo: f64, hi: f64 },
     Complex { re: f64, im: f64 },
     Matrix { rows: usize, cols: usize },
+    Bytes { length: usize, mime: String },
+    SparseMatrix { rows: usize, cols: usize, nnz: usize },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
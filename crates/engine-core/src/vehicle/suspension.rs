@@ -82,6 +82,7 @@ pub fn quarter_car_step_response(
         dt,
         tolerance: 1e-6,
         max_steps: 100_000,
+        ..Default::default()
     };
 
     solve_rk45(&system, &[0.0, 0.0, 0.0, 0.0], &config)
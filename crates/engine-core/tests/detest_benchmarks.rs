@@ -48,6 +48,7 @@ fn default_cfg(t_end: f64) -> OdeSolverConfig {
         dt: 0.01,
         tolerance: 1e-6,
         max_steps: 500_000,
+        ..Default::default()
     }
 }
 
@@ -58,6 +59,7 @@ fn stiff_cfg(t_end: f64) -> OdeSolverConfig {
         dt: 1e-4,
         tolerance: 1e-5,
         max_steps: 2_000_000,
+        ..Default::default()
     }
 }
 
@@ -135,6 +137,7 @@ fn detest_a5_harmonic_oscillator() {
         dt: 0.01,
         tolerance: 1e-7,
         max_steps: 2_000_000,
+        ..Default::default()
     });
     let y0_final = last_state(&result, 0);
     let y1_final = last_state(&result, 1);
@@ -160,6 +163,7 @@ fn detest_b1_mildly_stiff_tracking() {
         dt: 0.005,
         tolerance: 1e-6,
         max_steps: 500_000,
+        ..Default::default()
     });
     let y_final = last_state(&result, 0);
     let exact = (3.0_f64).sin();
@@ -189,6 +193,7 @@ fn detest_b2_predator_prey_conservation() {
         dt: 0.01,
         tolerance: 1e-7,
         max_steps: 500_000,
+        ..Default::default()
     });
     let y0f = last_state(&result, 0);
     let y1f = last_state(&result, 1);
@@ -245,6 +250,7 @@ fn detest_c2_stiff_linear_bdf() {
         dt: 0.001,
         tolerance: 1e-5,
         max_steps: 2_000_000,
+        ..Default::default()
     });
     let y_final = last_state(&result, 0);
     let exact = (-3.0_f64).exp(); // e^{-10×0.3} ≈ 0.04979
@@ -275,6 +281,7 @@ fn detest_c3_stiff_coupled_system() {
         dt: 1e-5,
         tolerance: 1e-5,
         max_steps: 2_000_000,
+        ..Default::default()
     });
     let y1_final = last_state(&result, 1);
     // At t=0.01: y1 ≈ e^{0.01}/1001 ≈ 1.01/1001 ≈ 1.009e-3
@@ -301,6 +308,7 @@ fn detest_e1_undamped_oscillator() {
         dt: 0.01,
         tolerance: 1e-7,
         max_steps: 2_000_000,
+        ..Default::default()
     });
     let y0_final = last_state(&result, 0);
     // At t=4π, cos(4π) = 1
@@ -324,6 +332,7 @@ fn detest_e2_damped_oscillator() {
         dt: 0.01,
         tolerance: 1e-7,
         max_steps: 500_000,
+        ..Default::default()
     });
     let y0_final = last_state(&result, 0);
     let y1_final = last_state(&result, 1);
@@ -352,6 +361,7 @@ fn detest_e3_duffing_oscillator_bounded() {
         dt: 0.01,
         tolerance: 1e-6,
         max_steps: 1_000_000,
+        ..Default::default()
     });
     // Damped Duffing: solution must remain bounded (|y| < 10)
     let max_y = result.states.iter()
@@ -398,6 +408,7 @@ fn detest_e4_kepler_energy_conservation() {
             dt: 0.01,
             tolerance: 1e-8,
             max_steps: 2_000_000,
+            ..Default::default()
         },
     );
 
@@ -431,6 +442,7 @@ fn detest_e5_van_der_pol_limit_cycle() {
         dt: 0.01,
         tolerance: 1e-6,
         max_steps: 2_000_000,
+        ..Default::default()
     });
     // On the limit cycle, the amplitude should be roughly 2.
     // Check that solution hasn't blown up or collapsed.
@@ -7,6 +7,8 @@
 //!   LE1  — Poisson MMS (manufactured solution) — verifies accuracy vs exact solution
 //!   LE10 — Mesh convergence — verifies h-convergence rate ≥ O(h) as mesh refines
 //!   LE11 — Boundary conditions — exact Dirichlet imposition, constant and non-constant BCs
+//!   Kirsch — Plate with a hole on an unstructured mesh (`fem` module) — stress concentration ≈ 3
+//!   Modal — Cantilever first bending frequency (`modal` module) vs Euler–Bernoulli
 //!
 //! Note: tests use moderate mesh sizes (n ≤ 8) compatible with the dense Gauss solver.
 //!
@@ -345,3 +347,86 @@ fn fea_diffusion_coefficient_scaling() {
         "FEM k-scaling: max ratio error = {max_ratio_err:.2e}, expected 2.0 at all interior nodes"
     );
 }
+
+// ── Plate with a circular hole (Kirsch) — unstructured mesh ─────────────────
+//
+// Quarter model of a plate with a unit hole under remote tension σ = 1, with
+// symmetry rollers on the cut edges. The hoop stress at the top of the hole
+// tends to 3σ for a plate much wider than the hole.
+
+/// Kirsch stress concentration: σxx at (0, a) ≈ 3σ.
+#[test]
+fn fea_plate_with_hole_stress_concentration() {
+    use engine_core::fem::elasticity::{solve_elasticity, ElasticityProblem, PlaneModel};
+    use engine_core::fem::mesh::{triangulate, Geometry};
+    use engine_core::fem::BoundaryCondition;
+    use std::collections::HashMap;
+
+    let w = 8.0;
+    // Edges: 0 bottom symmetry, 1 loaded right side, 2 top, 3 left symmetry, 4.. hole arc.
+    let mut outer = vec![(1.0, 0.0), (w, 0.0), (w, w), (0.0, w), (0.0, 1.0)];
+    let arc = 32;
+    for k in 1..arc {
+        let theta = std::f64::consts::FRAC_PI_2 * (1.0 - k as f64 / arc as f64);
+        outer.push((theta.cos(), theta.sin()));
+    }
+    let mesh = triangulate(&Geometry { outer, holes: vec![] }, 0.2).unwrap();
+    let problem = ElasticityProblem {
+        youngs_modulus: 1.0,
+        poisson_ratio: 0.3,
+        thickness: 1.0,
+        model: PlaneModel::PlaneStress,
+        body_force: ["0".into(), "0".into()],
+        bcs: HashMap::from([
+            (0, BoundaryCondition::Dirichlet(vec![None, Some("0".into())])),
+            (3, BoundaryCondition::Dirichlet(vec![Some("0".into()), None])),
+            (1, BoundaryCondition::Neumann(vec!["1".into(), "0".into()])),
+        ]),
+    };
+    let result = solve_elasticity(mesh, &problem).unwrap();
+    // Nodal von Mises; at the top of the hole only σxx is non-zero.
+    let (_, rows) = result.nodal_table();
+    let peak = rows
+        .iter()
+        .filter(|r| r[0].abs() < 1e-9 && (r[1] - 1.0).abs() < 1e-9)
+        .map(|r| r[4])
+        .next()
+        .unwrap();
+    assert!((peak - 3.0).abs() < 0.45, "Kirsch: σ at the hole = {peak:.3}, expected ≈ 3");
+}
+
+// ── Cantilever natural frequency — modal analysis ───────────────────────────
+//
+// Slender clamped beam L/H = 10 in plane stress. Euler–Bernoulli gives
+// f₁ = 1.875²/(2π)·√(EI/(ρAL⁴)); shear flexibility lowers the exact 2D value
+// by about 1% and linear triangles stiffen it slightly.
+
+/// First bending frequency of a cantilever from the FEM mass and stiffness matrices.
+#[test]
+fn fea_cantilever_first_frequency() {
+    use engine_core::fem::elasticity::{structural_matrices, ElasticityProblem, PlaneModel};
+    use engine_core::fem::mesh::{triangulate, Geometry};
+    use engine_core::fem::BoundaryCondition;
+    use engine_core::modal::{modes_lanczos, participation, StructuralMatrix};
+    use std::collections::HashMap;
+
+    let (l, h, e, rho) = (10.0, 1.0, 1000.0, 1.0);
+    let mesh = triangulate(&Geometry::rectangle(0.0, 0.0, l, h), 0.125).unwrap();
+    let problem = ElasticityProblem {
+        youngs_modulus: e,
+        poisson_ratio: 0.3,
+        thickness: 1.0,
+        model: PlaneModel::PlaneStress,
+        body_force: ["0".into(), "0".into()],
+        bcs: HashMap::from([(3, BoundaryCondition::Dirichlet(vec![Some("0".into()), Some("0".into())]))]),
+    };
+    let matrices = structural_matrices(&mesh, &problem, rho).unwrap();
+    let (k, m) = (StructuralMatrix::Sparse(matrices.stiffness.clone()), StructuralMatrix::Sparse(matrices.mass.clone()));
+    let modes = modes_lanczos(&k, &m, 3, None, 42).unwrap();
+    let f1 = modes.frequencies_hz()[0];
+    let exact = 1.875f64.powi(2) / (2.0 * std::f64::consts::PI) * (e * h.powi(3) / 12.0 / (rho * h * l.powi(4))).sqrt();
+    assert!((f1 / exact - 1.0).abs() < 0.03, "cantilever: f₁ = {f1:.4}, expected ≈ {exact:.4}");
+    // The first mode is transverse bending: it participates in y, not x.
+    let py = participation(&modes, &m, &matrices.influence(1));
+    assert!(py[0].mass_fraction > 0.5, "first-mode y mass fraction {}", py[0].mass_fraction);
+}
@@ -228,3 +228,32 @@ fn remove_edge_changes_result() {
         "result must differ from original after edge removal"
     );
 }
+
+/// fmu.export compiles the subgraph feeding its `data` port. Adding the
+/// export block by patch must give the same archive as a full eval.
+#[test]
+fn fmu_export_compiles_the_upstream_subgraph() {
+    let mut graph = EngineGraph::new();
+    let initial = add_snap(3.0, 4.0);
+    run_load_snapshot(&mut graph, &serde_json::to_string(&initial).unwrap()).unwrap();
+
+    let patch = vec![
+        PatchOp::AddNode { node: op_node("fmu", "fmu.export") },
+        PatchOp::AddEdge { edge: edge("e3", "op", "out", "fmu", "data") },
+    ];
+    let inc = run_patch(&mut graph, &serde_json::to_string(&patch).unwrap()).unwrap();
+
+    let mut full_snap = add_snap(3.0, 4.0);
+    full_snap.nodes.push(op_node("fmu", "fmu.export"));
+    full_snap.edges.push(edge("e3", "op", "out", "fmu", "data"));
+    let full = run(&serde_json::to_string(&full_snap).unwrap()).unwrap();
+
+    let archive = |vals: &HashMap<String, Value>| match vals.get("fmu") {
+        Some(Value::Bytes { data, mime }) if mime == "application/zip" => data.clone(),
+        other => panic!("expected an FMU archive, got {other:?}"),
+    };
+    let (inc_zip, full_zip) = (archive(&inc.changed_values), archive(&full.values));
+    assert_eq!(inc_zip, full_zip);
+    // Both number blocks become parameters summed by the output equation.
+    assert!(String::from_utf8_lossy(&full_zip).contains("(r[1] + r[2])"));
+}
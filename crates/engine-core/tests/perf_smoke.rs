@@ -332,6 +332,7 @@ fn perf_smoke_100_state_ode_rk45_under_2000ms() {
         dt: 0.1, // initial step size hint for adaptive solver
         tolerance: 1e-6,
         max_steps: 100_000,
+        ..Default::default()
     };
 
     let start = Instant::now();
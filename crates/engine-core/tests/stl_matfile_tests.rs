@@ -1,10 +1,12 @@
-//! Integration tests for STL mesh import (4.12) and MATLAB .mat import (4.9).
+//! Integration tests for STL mesh import (4.12), MATLAB .mat import (4.9),
+//! and binary dataset transport for file import/export blocks.
 
 use engine_core::stl::{
     parse_stl_ascii, parse_stl_binary, parse_stl_text, stl_to_matrix, write_stl_binary,
     StlTriangle,
 };
 use engine_core::matfile::{parse_mat_v5, write_mat_v5_double};
+use engine_core::graph::EngineGraph;
 use engine_core::types::Value;
 
 // ── STL tests ─────────────────────────────────────────────────────────────────
@@ -200,3 +202,60 @@ fn mat_identity_2x2() {
     assert_eq!(vars[0].rows, 2);
     assert_eq!(vars[0].cols, 2);
 }
+
+// ── Binary datasets (blobRef) ─────────────────────────────────────────────────
+
+fn import_snapshot(block_type: &str) -> String {
+    format!(
+        r#"{{"version":1,"nodes":[{{"id":"imp","blockType":"{block_type}","data":{{"blobRef":"file"}}}}],"edges":[]}}"#
+    )
+}
+
+#[test]
+fn mat_import_reads_registered_blob() {
+    let mut g = EngineGraph::new();
+    engine_core::run_load_snapshot(&mut g, &import_snapshot("mat_import")).unwrap();
+    assert!(g.values()["imp"].is_error(), "no blob registered yet");
+
+    // Registering the blob re-evaluates the reader without a patch.
+    g.register_blob("file".into(), write_mat_v5_double("E", 2, 2, &[1.0, 0.0, 0.0, 1.0]));
+    let inc = g.evaluate_dirty();
+    match &inc.changed_values["imp"] {
+        Value::Matrix { rows: 2, cols: 2, data } => assert_eq!(data, &[1.0, 0.0, 0.0, 1.0]),
+        other => panic!("expected 2x2 matrix, got {other:?}"),
+    }
+}
+
+#[test]
+fn parquet_export_bytes_round_trip_through_blob() {
+    let snapshot = r#"{"version":1,"nodes":[
+        {"id":"t","blockType":"tableInput","data":{"tableData":{"columns":["x","y"],"rows":[[1,2],[3,4]]}}},
+        {"id":"exp","blockType":"parquet_export","data":{}}
+    ],"edges":[
+        {"id":"e1","source":"t","sourceHandle":"out","target":"exp","targetHandle":"table"}
+    ]}"#;
+    let result = engine_core::run(snapshot).unwrap();
+    let (bytes, mime) = match &result.values["exp"] {
+        Value::Bytes { data, mime } => (data.clone(), mime.clone()),
+        other => panic!("expected bytes, got {other:?}"),
+    };
+    assert_eq!(mime, "application/vnd.apache.parquet");
+    assert_eq!(&bytes[..4], b"PAR1");
+
+    // Bytes values are base64 in JSON and survive a round trip.
+    let json = serde_json::to_string(&result.values["exp"]).unwrap();
+    assert!(json.contains(r#""kind":"bytes""#));
+    let back: Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.as_bytes(), Some(bytes.as_slice()));
+
+    let mut g = EngineGraph::new();
+    g.register_blob("file".into(), bytes);
+    let r = engine_core::run_load_snapshot(&mut g, &import_snapshot("parquet_import")).unwrap();
+    match &r.values["imp"] {
+        Value::Table { columns, rows } => {
+            assert_eq!(columns, &["x", "y"]);
+            assert_eq!(rows, &[vec![1.0, 2.0], vec![3.0, 4.0]]);
+        }
+        other => panic!("expected table, got {other:?}"),
+    }
+}
@@ -5,6 +5,18 @@
 //! side never touches Rust-allocated memory directly — it only passes and
 //! receives `String` values across the boundary.
 //!
+//! # Binary transport
+//!
+//! Large payloads bypass JSON:
+//!
+//! - File imports pass their bytes once as a `Uint8Array` to [`register_blob`];
+//!   import blocks reference them with `data.blobRef`.
+//! - When the eval options JSON sets `binaryThreshold: N`, the `*_with_options`
+//!   calls leave vector / matrix / table values with at least `N` elements (and
+//!   all `bytes` values) out of `values`, listing them under `binaryRefs`
+//!   instead. JS then copies each payload out with [`value_f64`]
+//!   (`Float64Array`) or [`value_bytes`] (`Uint8Array`).
+//!
 //! # Thread model
 //!
 //! Default: WASM is single-threaded. The persistent [`EngineGraph`] is stored
@@ -26,8 +38,10 @@
 //! call `await init_thread_pool(numThreads)` after WASM init.  See worker.ts.
 
 use engine_core::graph::{EngineGraph, EvalSignal};
-use engine_core::types::EvalOptions;
+use engine_core::simulation::{ConvergenceCheck, SimulationConfig};
+use engine_core::types::{EvalOptions, Value};
 use std::cell::RefCell;
+use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
 
 thread_local! {
@@ -49,6 +63,74 @@ fn err_json(code: &str, message: &str) -> String {
     format!(r#"{{"error":{{"code":"{code}","message":"{message}"}}}}"#)
 }
 
+/// Read `binaryThreshold` from an options JSON string (0 = disabled).
+fn binary_threshold(options_json: &str) -> usize {
+    serde_json::from_str::<serde_json::Value>(options_json)
+        .ok()
+        .and_then(|v| v.get("binaryThreshold")?.as_u64())
+        .unwrap_or(0) as usize
+}
+
+/// Move large values out of `values` and describe them as binary refs.
+///
+/// The removed values stay in the persistent graph's cache, where
+/// [`value_f64`] / [`value_bytes`] read them.
+fn split_binary(
+    values: &mut HashMap<String, Value>,
+    threshold: usize,
+) -> serde_json::Map<String, serde_json::Value> {
+    let mut refs = serde_json::Map::new();
+    if threshold == 0 {
+        return refs;
+    }
+    let large: Vec<String> = values
+        .iter()
+        .filter(|(_, v)| match v {
+            Value::Vector { value } => value.len() >= threshold,
+            Value::Matrix { data, .. } => data.len() >= threshold,
+            Value::Table { columns, rows } => columns.len() * rows.len() >= threshold,
+            Value::Bytes { .. } => true,
+            _ => false,
+        })
+        .map(|(id, _)| id.clone())
+        .collect();
+    for id in large {
+        let desc = match values.remove(&id) {
+            Some(Value::Vector { value }) => {
+                serde_json::json!({ "kind": "vector", "length": value.len() })
+            }
+            Some(Value::Matrix { rows, cols, .. }) => {
+                serde_json::json!({ "kind": "matrix", "rows": rows, "cols": cols })
+            }
+            Some(Value::Table { columns, rows }) => {
+                serde_json::json!({ "kind": "table", "columns": columns, "rows": rows.len() })
+            }
+            Some(Value::Bytes { data, mime }) => {
+                serde_json::json!({ "kind": "bytes", "length": data.len(), "mime": mime })
+            }
+            _ => continue,
+        };
+        refs.insert(id, desc);
+    }
+    refs
+}
+
+/// Serialize a result, attaching `binaryRefs` when any values were split off.
+fn json_with_refs(
+    result: Result<serde_json::Value, serde_json::Error>,
+    refs: serde_json::Map<String, serde_json::Value>,
+) -> String {
+    match result {
+        Ok(mut json) => {
+            if !refs.is_empty() {
+                json["binaryRefs"] = serde_json::Value::Object(refs);
+            }
+            json.to_string()
+        }
+        Err(e) => err_json("SERIALIZE_FAILED", &e.to_string()),
+    }
+}
+
 /// One-time setup: install better panic messages.
 #[wasm_bindgen(start)]
 pub fn init() {
@@ -120,7 +202,8 @@ pub fn apply_patch(patch_json: &str) -> String {
     })
 }
 
-/// Load a snapshot with eval options (trace, time budget) and a JS progress callback.
+/// Load a snapshot with eval options (trace, time budget, binary threshold) and
+/// a JS progress callback.
 /// The `progress_cb` is a JS function(evaluated, total) called after each node.
 #[wasm_bindgen]
 pub fn load_snapshot_with_options(
@@ -133,6 +216,7 @@ pub fn load_snapshot_with_options(
         Err(e) => return err_json("INVALID_OPTIONS", &e.to_string()),
     };
 
+    let threshold = binary_threshold(options_json);
     let start = js_sys::Date::now();
     let budget_ms = opts.time_budget_ms;
     let cb = progress_cb;
@@ -165,16 +249,16 @@ pub fn load_snapshot_with_options(
             Ok(mut r) => {
                 let elapsed_ms = js_sys::Date::now() - start;
                 r.elapsed_us = (elapsed_ms * 1000.0) as u64;
-                serde_json::to_string(&r).unwrap_or_else(|e| {
-                    err_json("SERIALIZE_FAILED", &e.to_string())
-                })
+                let refs = split_binary(&mut r.values, threshold);
+                json_with_refs(serde_json::to_value(&r), refs)
             }
             Err(err) => err_json(&err.code.to_string(), &err.message),
         }
     })
 }
 
-/// Apply a JSON patch with eval options (trace, time budget) and a JS progress callback.
+/// Apply a JSON patch with eval options (trace, time budget, binary threshold)
+/// and a JS progress callback.
 /// The `progress_cb` is a JS function(evaluated, total) called after each node.
 #[wasm_bindgen]
 pub fn apply_patch_with_options(
@@ -187,6 +271,7 @@ pub fn apply_patch_with_options(
         Err(e) => return err_json("INVALID_OPTIONS", &e.to_string()),
     };
 
+    let threshold = binary_threshold(options_json);
     let start = js_sys::Date::now();
     let budget_ms = opts.time_budget_ms;
     let cb = progress_cb;
@@ -219,9 +304,8 @@ pub fn apply_patch_with_options(
             Ok(mut r) => {
                 let elapsed_ms = js_sys::Date::now() - start;
                 r.elapsed_us = (elapsed_ms * 1000.0) as u64;
-                serde_json::to_string(&r).unwrap_or_else(|e| {
-                    err_json("SERIALIZE_FAILED", &e.to_string())
-                })
+                let refs = split_binary(&mut r.changed_values, threshold);
+                json_with_refs(serde_json::to_value(&r), refs)
             }
             Err(err) => err_json(&err.code.to_string(), &err.message),
         }
@@ -257,6 +341,44 @@ pub fn release_dataset(id: &str) {
     })
 }
 
+/// Register a binary dataset (raw file bytes from a `Uint8Array`) by id.
+/// Import blocks read it via `data.blobRef`; nodes referencing it are marked dirty.
+#[wasm_bindgen]
+pub fn register_blob(id: &str, bytes: &[u8]) {
+    with_engine(|graph| {
+        graph.register_blob(id.to_string(), bytes.to_vec());
+    })
+}
+
+/// Release (remove) a binary dataset from the engine graph.
+#[wasm_bindgen]
+pub fn release_blob(id: &str) {
+    with_engine(|graph| {
+        graph.release_blob(id);
+    })
+}
+
+/// Copy a node's cached numeric payload into a `Float64Array`.
+///
+/// Vectors return their elements, matrices their row-major data, tables
+/// their rows flattened row-major. Returns `undefined` for other kinds or
+/// unknown nodes.
+#[wasm_bindgen]
+pub fn value_f64(node_id: &str) -> Option<Vec<f64>> {
+    with_engine(|graph| match graph.values().get(node_id)? {
+        Value::Vector { value } => Some(value.clone()),
+        Value::Matrix { data, .. } => Some(data.clone()),
+        Value::Table { rows, .. } => Some(rows.iter().flatten().copied().collect()),
+        _ => None,
+    })
+}
+
+/// Copy a node's cached `bytes` payload into a `Uint8Array`.
+#[wasm_bindgen]
+pub fn value_bytes(node_id: &str) -> Option<Vec<u8>> {
+    with_engine(|graph| graph.values().get(node_id)?.as_bytes().map(<[u8]>::to_vec))
+}
+
 /// Return the ops catalog as a JSON array of CatalogEntry objects.
 #[wasm_bindgen]
 pub fn get_catalog() -> String {
@@ -313,27 +435,38 @@ pub fn validate_graph() -> String {
 
 // ── run_simulation (8.2) ──────────────────────────────────────────────────────
 
-/// Run a simulation task with iterative progress callbacks (8.2).
+/// Run a discrete-time simulation with progress callbacks (8.2).
 ///
-/// The WASM-side loop executes each "step" as a full engine evaluation,
-/// reporting progress after every `batchSize` iterations.  This function
-/// runs synchronously from Rust's point of view; the progress callback
-/// sends `postMessage` events back to the main thread from within the
-/// simulation Web Worker.
+/// Wraps [`engine_core::run_simulation`]: the snapshot is loaded into a
+/// dedicated persistent [`EngineGraph`] and stepped through the simulation
+/// clock. Clock and state nodes are updated between steps, so every step
+/// after the first is an incremental evaluation. Runs synchronously inside
+/// the simulation Web Worker; the progress callback `postMessage`s back to
+/// the main thread.
 ///
 /// # Config JSON shape
 /// ```json
 /// {
 ///   "nodeId": "sim1",
-///   "op": "solveOde",
 ///   "snapshot": "{ ... EngineSnapshotV1 ... }",
 ///   "maxIterations": 1000,
 ///   "batchSize": 50,
 ///   "loop": false,
 ///   "loopCount": 1,
-///   "convergenceThreshold": 1e-6
+///   "convergenceThreshold": 1e-6,
+///   "tStart": 0.0,
+///   "dt": 0.01,
+///   "times": [0.0, 0.1, 0.5],
+///   "timeNode": "t",
+///   "dtNode": "dt",
+///   "states": [{ "node": "x", "source": "x_next", "initial": 0.0 }],
+///   "record": ["x_next"],
+///   "convergenceNodes": ["x_next"]
 /// }
 /// ```
+/// `maxIterations` is the number of clock steps per cycle, `batchSize` the
+/// progress interval and `loopCount` the number of cycles when `loop` is set.
+/// All simulation fields after `convergenceThreshold` are optional.
 ///
 /// # Progress callback
 /// Called after every batch with a JSON argument:
@@ -343,18 +476,22 @@ pub fn validate_graph() -> String {
 ///   "totalIterations": 1000,
 ///   "cycle": 0,
 ///   "totalCycles": 1,
+///   "t": 0.49,
 ///   "elapsedUs": 12345
 /// }
 /// ```
 ///
 /// # Return value
 /// ```json
-/// { "cycles": 1, "iterations": 1000, "outputs": { "nodeId": <value> } }
+/// {
+///   "cycles": 1, "iterations": 1000, "elapsedUs": 0, "converged": false,
+///   "aborted": false, "outputs": { "nodeId": <value> },
+///   "table": <Table value>, "diagnostics": []
+/// }
 /// ```
 /// or `{ "error": { "code": "...", "message": "..." } }` on failure.
 #[wasm_bindgen]
 pub fn run_simulation(config_json: &str, progress_cb: &js_sys::Function) -> String {
-    // Parse the simulation config.
     let config: serde_json::Value = match serde_json::from_str(config_json) {
         Ok(v) => v,
         Err(e) => return err_json("INVALID_SIM_CONFIG", &e.to_string()),
@@ -364,94 +501,66 @@ pub fn run_simulation(config_json: &str, progress_cb: &js_sys::Function) -> Stri
         Some(s) => s.to_string(),
         None => return err_json("INVALID_SIM_CONFIG", "missing 'snapshot' field"),
     };
-    let max_iterations = config.get("maxIterations")
-        .and_then(|v| v.as_u64()).unwrap_or(100) as usize;
-    let batch_size = config.get("batchSize")
-        .and_then(|v| v.as_u64()).unwrap_or(10) as usize;
-    let loop_mode = config.get("loop")
-        .and_then(|v| v.as_bool()).unwrap_or(false);
-    let loop_count = if loop_mode {
+
+    // Simulation fields deserialize directly; the legacy 8.2 fields map onto them.
+    let mut sim: SimulationConfig = match serde_json::from_value(config.clone()) {
+        Ok(c) => c,
+        Err(e) => return err_json("INVALID_SIM_CONFIG", &e.to_string()),
+    };
+    if let Some(n) = config.get("maxIterations").and_then(|v| v.as_u64()) {
+        sim.steps = n as usize;
+    }
+    if let Some(n) = config.get("batchSize").and_then(|v| v.as_u64()) {
+        sim.progress_every = n as usize;
+    }
+    let loop_mode = config.get("loop").and_then(|v| v.as_bool()).unwrap_or(false);
+    sim.cycles = if loop_mode {
         config.get("loopCount").and_then(|v| v.as_u64()).unwrap_or(1) as usize
     } else {
         1
     };
-    let convergence_threshold = config.get("convergenceThreshold")
-        .and_then(|v| v.as_f64());
+    if let Some(tol) = config.get("convergenceThreshold").and_then(|v| v.as_f64()) {
+        let nodes = config
+            .get("convergenceNodes")
+            .and_then(|v| serde_json::from_value::<Vec<String>>(v.clone()).ok())
+            .unwrap_or_default();
+        sim.convergence = Some(ConvergenceCheck { nodes, tolerance: tol, min_steps: 0 });
+    }
 
     let start_ms = js_sys::Date::now();
-    let total_cycles = loop_count;
-    let total_iterations = max_iterations;
-
-    let mut last_outputs: std::collections::HashMap<String, engine_core::types::Value> =
-        std::collections::HashMap::new();
-    let mut converged = false;
-
-    for cycle in 0..total_cycles {
-        for iteration in (0..total_iterations).step_by(batch_size.max(1)) {
-            let batch_end = (iteration + batch_size).min(total_iterations);
-            let batch_steps = batch_end - iteration;
-
-            // Run the engine for this batch's iterations.
-            // Each step is one full graph evaluation on the snapshot.
-            // For real-world use, the snapshot would be mutated by prior
-            // outputs (e.g. ODE state) — here we evaluate batch_steps times.
-            let mut batch_result = None;
-            for _ in 0..batch_steps {
-                match engine_core::run(&snapshot_json) {
-                    Ok(r) => { batch_result = Some(r); }
-                    Err(e) => {
-                        return err_json(&e.code.to_string(), &e.message);
-                    }
-                }
-            }
-
-            if let Some(ref r) = batch_result {
-                // Check convergence if threshold is set.
-                if let Some(threshold) = convergence_threshold {
-                    let mut max_delta: f64 = 0.0;
-                    for (k, v) in &r.values {
-                        if let (
-                            Some(engine_core::types::Value::Scalar { value: prev }),
-                            engine_core::types::Value::Scalar { value: curr },
-                        ) = (last_outputs.get(k), v) {
-                            max_delta = max_delta.max((curr - prev).abs());
-                        }
-                    }
-                    if max_delta < threshold && iteration > 0 {
-                        converged = true;
-                    }
-                }
-                last_outputs = r.values.clone();
-            }
+    let mut graph = EngineGraph::new();
+    let result = engine_core::run_simulation(&mut graph, &snapshot_json, &sim, |p| {
+        let elapsed_us = ((js_sys::Date::now() - start_ms) * 1000.0) as u64;
+        let progress_json = format!(
+            r#"{{"iteration":{},"totalIterations":{},"cycle":{},"totalCycles":{},"t":{},"elapsedUs":{}}}"#,
+            p.step,
+            p.total_steps,
+            p.cycle,
+            p.total_cycles,
+            serde_json::to_string(&p.t).unwrap_or_else(|_| "null".into()),
+            elapsed_us
+        );
+        let _ = progress_cb.call1(&JsValue::NULL, &JsValue::from_str(&progress_json));
+        EvalSignal::Continue
+    });
 
-            // Report progress via JS callback.
+    match result {
+        Ok(r) => {
             let elapsed_us = ((js_sys::Date::now() - start_ms) * 1000.0) as u64;
-            let progress_json = format!(
-                r#"{{"iteration":{},"totalIterations":{},"cycle":{},"totalCycles":{},"elapsedUs":{}}}"#,
-                batch_end, total_iterations, cycle, total_cycles, elapsed_us
-            );
-            let this = wasm_bindgen::JsValue::NULL;
-            let _ = progress_cb.call1(
-                &this,
-                &wasm_bindgen::JsValue::from_str(&progress_json),
-            );
-
-            if converged { break; }
+            let out = serde_json::json!({
+                "cycles": r.cycles,
+                "iterations": r.steps,
+                "elapsedUs": elapsed_us,
+                "converged": r.converged,
+                "aborted": r.aborted,
+                "outputs": r.final_values,
+                "table": r.table,
+                "diagnostics": r.diagnostics,
+            });
+            serde_json::to_string(&out).unwrap_or_else(|e| {
+                err_json("SERIALIZE_FAILED", &e.to_string())
+            })
         }
-        if converged { break; }
+        Err(err) => err_json(&err.code.to_string(), &err.message),
     }
-
-    // Serialize final outputs.
-    let outputs_val = serde_json::to_value(&last_outputs).unwrap_or(serde_json::Value::Null);
-    let elapsed_us = ((js_sys::Date::now() - start_ms) * 1000.0) as u64;
-    let result = serde_json::json!({
-        "cycles": total_cycles,
-        "iterations": total_iterations,
-        "elapsedUs": elapsed_us,
-        "converged": converged,
-        "outputs": outputs_val,
-    });
-    serde_json::to_string(&result).unwrap_or_else(|e| {
-        err_json("SERIALIZE_FAILED", &e.to_string())
-    })
 }
@@ -2,6 +2,9 @@
 //!
 //! Provides:
 //! - Native Tauri commands for offline graph evaluation (`eval_snapshot`, `eval_patch`)
+//! - Session commands (`set_input`, `register_dataset`, `release_dataset`, `validate_graph`)
+//! - Background evaluation jobs with progress events and cancellation
+//!   (`start_eval_job`, `cancel_eval_job`)
 //! - File system helpers (`open_project_file`, `save_project_file`)
 //! - CUDA availability detection (`cuda_available`, `cuda_device_info`)
 //! - App info commands (`app_version`, `platform_info`)
@@ -9,8 +12,81 @@
 //! The WASM engine is not used in the desktop app. Instead, `engine_core::run()`
 //! is called directly on the native Rust binary, giving full CPU performance
 //! and optional CUDA acceleration without a WebAssembly sandbox.
+//!
+//! # Thread model
+//!
+//! Tauri may run each command invocation on a different thread, so session
+//! graphs live in managed [`EngineState`] rather than thread-local storage.
+//! Each session is an `Arc<Mutex<EngineGraph>>`: the session map lock is held
+//! only long enough to look a session up, and a background job holds its
+//! session's lock for the duration of the evaluation. Synchronous commands use
+//! `try_lock` and return a `SESSION_BUSY` error instead of blocking the command
+//! thread while a job is running.
+//!
+//! Like the single WASM graph, a session always exists from the caller's point
+//! of view: every session command creates an empty graph on first use, so the
+//! order of the first calls after `close_session` does not matter.
+
+use engine_core::graph::{EngineGraph, EvalSignal};
+use engine_core::types::EvalOptions;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State, Window};
+
+// ---------------------------------------------------------------------------
+// Shared engine state
+// ---------------------------------------------------------------------------
+
+type SharedGraph = Arc<Mutex<EngineGraph>>;
+
+/// A running background evaluation.
+struct JobHandle {
+    session_id: String,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Engine sessions and background jobs, managed by Tauri and shared across
+/// command threads.
+#[derive(Default)]
+struct EngineState {
+    /// session_id → persistent engine graph
+    sessions: Mutex<HashMap<String, SharedGraph>>,
+    /// job_id → running job
+    jobs: Mutex<HashMap<String, JobHandle>>,
+    next_job_id: AtomicU64,
+}
+
+impl EngineState {
+    /// Get a session graph, creating an empty one on first use.
+    fn session(&self, session_id: &str) -> SharedGraph {
+        let mut map = lock(&self.sessions);
+        map.entry(session_id.to_string())
+            .or_insert_with(|| Arc::new(Mutex::new(EngineGraph::new())))
+            .clone()
+    }
+}
+
+/// Lock a mutex, recovering the data if a previous holder panicked.
+fn lock<T>(m: &Mutex<T>) -> MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
 
-use tauri::{AppHandle, Manager};
+/// Lock a session graph without blocking the command thread.
+fn try_lock_graph(graph: &SharedGraph) -> Result<MutexGuard<'_, EngineGraph>, String> {
+    match graph.try_lock() {
+        Ok(g) => Ok(g),
+        Err(TryLockError::Poisoned(poisoned)) => Ok(poisoned.into_inner()),
+        Err(TryLockError::WouldBlock) => {
+            Err("[SESSION_BUSY] a background evaluation job is running on this session".into())
+        }
+    }
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string(value).map_err(|e| format!("[SERIALIZE_FAILED] {}", e))
+}
 
 // ---------------------------------------------------------------------------
 // Engine evaluation commands
@@ -26,57 +102,287 @@ use tauri::{AppHandle, Manager};
 #[tauri::command]
 fn eval_snapshot(snapshot_json: String) -> Result<String, String> {
     engine_core::run(&snapshot_json)
-        .map(|result| serde_json::to_string(&result).unwrap_or_default())
         .map_err(|e| e.to_string())
+        .and_then(|result| to_json(&result))
 }
 
 /// Apply a JSON patch to an existing engine graph and return incremental results.
-///
-/// The engine graph state is stored in thread-local storage keyed by `session_id`.
 #[tauri::command]
-fn eval_patch(session_id: String, patch_json: String) -> Result<String, String> {
-    SESSIONS.with(|sessions| {
-        let mut map = sessions.borrow_mut();
-        let graph = map
-            .entry(session_id)
-            .or_insert_with(engine_core::graph::EngineGraph::default);
-
-        engine_core::run_patch(graph, &patch_json)
-            .map(|result| serde_json::to_string(&result).unwrap_or_default())
-            .map_err(|e| e.to_string())
-    })
+fn eval_patch(
+    state: State<'_, EngineState>,
+    session_id: String,
+    patch_json: String,
+) -> Result<String, String> {
+    let graph = state.session(&session_id);
+    let mut graph = try_lock_graph(&graph)?;
+    engine_core::run_patch(&mut graph, &patch_json)
+        .map_err(|e| e.to_string())
+        .and_then(|result| to_json(&result))
 }
 
 /// Load a snapshot into a session graph and perform a full evaluation.
 #[tauri::command]
-fn eval_load_snapshot(session_id: String, snapshot_json: String) -> Result<String, String> {
-    SESSIONS.with(|sessions| {
-        let mut map = sessions.borrow_mut();
-        let graph = map
-            .entry(session_id)
-            .or_insert_with(engine_core::graph::EngineGraph::default);
+fn eval_load_snapshot(
+    state: State<'_, EngineState>,
+    session_id: String,
+    snapshot_json: String,
+) -> Result<String, String> {
+    let graph = state.session(&session_id);
+    let mut graph = try_lock_graph(&graph)?;
+    engine_core::run_load_snapshot(&mut graph, &snapshot_json)
+        .map_err(|e| e.to_string())
+        .and_then(|result| to_json(&result))
+}
+
+/// Override one node input and return incremental results.
+#[tauri::command]
+fn set_input(
+    state: State<'_, EngineState>,
+    session_id: String,
+    node_id: String,
+    port_id: String,
+    value: f64,
+) -> Result<String, String> {
+    let graph = state.session(&session_id);
+    let mut graph = try_lock_graph(&graph)?;
+    to_json(&engine_core::run_set_input(
+        &mut graph, &node_id, &port_id, value,
+    ))
+}
+
+/// Register a dataset (large numeric array) on a session graph.
+#[tauri::command]
+fn register_dataset(
+    state: State<'_, EngineState>,
+    session_id: String,
+    dataset_id: String,
+    data: Vec<f64>,
+) -> Result<(), String> {
+    let graph = state.session(&session_id);
+    let mut graph = try_lock_graph(&graph)?;
+    graph.register_dataset(dataset_id, data);
+    Ok(())
+}
+
+/// Release a dataset from a session graph.
+#[tauri::command]
+fn release_dataset(
+    state: State<'_, EngineState>,
+    session_id: String,
+    dataset_id: String,
+) -> Result<(), String> {
+    let graph = state.session(&session_id);
+    let mut graph = try_lock_graph(&graph)?;
+    graph.release_dataset(&dataset_id);
+    Ok(())
+}
+
+/// Pre-run validation of a session graph. Returns a JSON array of diagnostics.
+#[tauri::command]
+fn validate_graph(state: State<'_, EngineState>, session_id: String) -> Result<String, String> {
+    let graph = state.session(&session_id);
+    let graph = try_lock_graph(&graph)?;
+    to_json(&engine_core::run_validate(&graph))
+}
+
+/// Release a session graph from memory and cancel its running jobs.
+#[tauri::command]
+fn close_session(state: State<'_, EngineState>, session_id: String) {
+    for job in lock(&state.jobs).values() {
+        if job.session_id == session_id {
+            job.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+    lock(&state.sessions).remove(&session_id);
+}
+
+// ---------------------------------------------------------------------------
+// Background evaluation jobs
+// ---------------------------------------------------------------------------
+
+/// Window event carrying job progress (`engine://job-progress`).
+const JOB_PROGRESS_EVENT: &str = "engine://job-progress";
+/// Window event emitted once when a job finishes (`engine://job-complete`).
+const JOB_COMPLETE_EVENT: &str = "engine://job-complete";
+/// Minimum interval between progress events for one job.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(50);
 
-        engine_core::run_load_snapshot(graph, &snapshot_json)
-            .map(|result| serde_json::to_string(&result).unwrap_or_default())
-            .map_err(|e| e.to_string())
-    })
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JobProgress {
+    job_id: String,
+    session_id: String,
+    evaluated: usize,
+    total: usize,
 }
 
-/// Release a session graph from memory.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JobComplete {
+    job_id: String,
+    session_id: String,
+    /// True if the job was cancelled (the result is then partial).
+    cancelled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Start a background evaluation of `snapshot_json` or `patch_json` and return its job id.
 #[tauri::command]
-fn close_session(session_id: String) {
-    SESSIONS.with(|sessions| {
-        sessions.borrow_mut().remove(&session_id);
+fn start_eval_job(
+    window: Window,
+    state: State<'_, EngineState>,
+    session_id: String,
+    snapshot_json: Option<String>,
+    patch_json: Option<String>,
+    options_json: Option<String>,
+) -> Result<String, String> {
+    let opts: EvalOptions = match options_json {
+        Some(json) => {
+            serde_json::from_str(&json).map_err(|e| format!("[INVALID_OPTIONS] {}", e))?
+        }
+        None => EvalOptions::default(),
+    };
+    if snapshot_json.is_some() == patch_json.is_some() {
+        return Err("[INVALID_JOB] exactly one of snapshotJson or patchJson is required".into());
+    }
+
+    let job_id = format!(
+        "job_{}",
+        state.next_job_id.fetch_add(1, Ordering::Relaxed) + 1
+    );
+    let cancel = Arc::new(AtomicBool::new(false));
+    lock(&state.jobs).insert(
+        job_id.clone(),
+        JobHandle {
+            session_id: session_id.clone(),
+            cancel: cancel.clone(),
+        },
+    );
+    let graph = state.session(&session_id);
+    let app = window.app_handle().clone();
+    let label = window.label().to_string();
+    let id = job_id.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let start = Instant::now();
+        let mut last_emit: Option<Instant> = None;
+        let mut graph = lock(&graph);
+        let on_progress = |evaluated: usize, total: usize| {
+            let now = Instant::now();
+            if last_emit.is_none_or(|t| now.duration_since(t) >= PROGRESS_INTERVAL) {
+                last_emit = Some(now);
+                let _ = window.emit_to(
+                    label.as_str(),
+                    JOB_PROGRESS_EVENT,
+                    JobProgress {
+                        job_id: id.clone(),
+                        session_id: session_id.clone(),
+                        evaluated,
+                        total,
+                    },
+                );
+            }
+            if cancel.load(Ordering::Relaxed) {
+                return EvalSignal::Abort;
+            }
+            if opts.time_budget_ms > 0
+                && start.elapsed() >= Duration::from_millis(opts.time_budget_ms)
+            {
+                return EvalSignal::Abort;
+            }
+            EvalSignal::Continue
+        };
+
+        let outcome = match (&snapshot_json, &patch_json) {
+            (Some(snap), _) => {
+                engine_core::run_load_snapshot_with_options(&mut graph, snap, &opts, on_progress)
+                    .map(|r| serde_json::to_value(&r))
+            }
+            (None, Some(patch)) => {
+                engine_core::run_patch_with_options(&mut graph, patch, &opts, on_progress)
+                    .map(|r| serde_json::to_value(&r))
+            }
+            (None, None) => unreachable!("validated before spawning"),
+        };
+        drop(graph);
+
+        let (result, error) = match outcome {
+            Ok(Ok(v)) => (Some(v), None),
+            Ok(Err(e)) => (None, Some(format!("[SERIALIZE_FAILED] {}", e))),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        let _ = window.emit_to(
+            label.as_str(),
+            JOB_COMPLETE_EVENT,
+            JobComplete {
+                job_id: id.clone(),
+                session_id,
+                cancelled: cancel.load(Ordering::Relaxed),
+                result,
+                error,
+            },
+        );
+        lock(&app.state::<EngineState>().jobs).remove(&id);
     });
+
+    Ok(job_id)
 }
 
-// Thread-local session storage: session_id → EngineGraph
-use std::cell::RefCell;
-use std::collections::HashMap;
+/// Request cancellation of a running job. Returns `false` if no such job.
+#[tauri::command]
+fn cancel_eval_job(state: State<'_, EngineState>, job_id: String) -> bool {
+    match lock(&state.jobs).get(&job_id) {
+        Some(job) => {
+            job.cancel.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
 
-thread_local! {
-    static SESSIONS: RefCell<HashMap<String, engine_core::graph::EngineGraph>> =
-        RefCell::new(HashMap::new());
+// ---------------------------------------------------------------------------
+// Project file commands
+// ---------------------------------------------------------------------------
+
+/// Save a session graph (snapshot, datasets, last values) as a project file.
+#[tauri::command]
+fn save_project_file(
+    state: State<'_, EngineState>,
+    session_id: String,
+    path: String,
+    metadata: Option<std::collections::BTreeMap<String, String>>,
+) -> Result<(), String> {
+    let graph = state.session(&session_id);
+    let graph = try_lock_graph(&graph)?;
+    let bytes = engine_core::project::save_project(&graph, metadata.unwrap_or_default());
+    std::fs::write(&path, bytes).map_err(|e| format!("[IO_ERROR] cannot write '{}': {}", path, e))
+}
+
+/// Open a project file into a session graph without evaluating it.
+///
+/// Returns `{ summary, snapshot, values }` as JSON. `values` are the cached
+/// results; if `summary.stale` is true they came from another engine version
+/// and the next evaluation recomputes every node.
+#[tauri::command]
+fn open_project_file(
+    state: State<'_, EngineState>,
+    session_id: String,
+    path: String,
+) -> Result<String, String> {
+    let bytes =
+        std::fs::read(&path).map_err(|e| format!("[IO_ERROR] cannot read '{}': {}", path, e))?;
+    let graph = state.session(&session_id);
+    let mut graph = try_lock_graph(&graph)?;
+    let summary =
+        engine_core::project::open_project(&mut graph, &bytes).map_err(|e| e.to_string())?;
+    to_json(&serde_json::json!({
+        "summary": summary,
+        "snapshot": graph.to_snapshot(),
+        "values": graph.values(),
+    }))
 }
 
 // ---------------------------------------------------------------------------
@@ -143,6 +449,7 @@ fn platform_info(_app: AppHandle) -> PlatformInfo {
 
 pub fn run() {
     tauri::Builder::default()
+        .manage(EngineState::default())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
@@ -160,7 +467,15 @@ pub fn run() {
             eval_snapshot,
             eval_patch,
             eval_load_snapshot,
+            set_input,
+            register_dataset,
+            release_dataset,
+            validate_graph,
             close_session,
+            start_eval_job,
+            cancel_eval_job,
+            save_project_file,
+            open_project_file,
             cuda_available,
             cuda_device_info,
             platform_info,
@@ -0,0 +1,358 @@
+/**
+ * acausal-blocks.ts — Acausal (Modelica-style) physical modelling block pack.
+ *
+ * Networks of components joined at physical ports are flattened, index
+ * reduced and integrated by the Rust/WASM engine.
+ */
+
+import type { BlockDef } from './types'
+
+/** [name, unit, default] — mirrors `acausal::library` in the engine. */
+type Param = [name: string, unit: string, value: number]
+
+/** Standard component library: [kind, label, parameters, description]. */
+const LIBRARY: [kind: string, label: string, params: Param[], description: string][] = [
+  ['elec.ground', 'Ground', [], 'Electrical reference: fixes the connected node at 0 V.'],
+  ['elec.resistor', 'Resistor', [['R', 'Ω', 1]], 'Ohmic resistor: v = R·i between p and n.'],
+  [
+    'elec.capacitor',
+    'Capacitor',
+    [['C', 'F', 1e-6], ['v0', 'V', 0]],
+    'Capacitor: C·dv/dt = i, starting at v0.',
+  ],
+  [
+    'elec.inductor',
+    'Inductor',
+    [['L', 'H', 1e-3], ['i0', 'A', 0]],
+    'Inductor: L·di/dt = v, starting at i0.',
+  ],
+  [
+    'elec.voltage_source',
+    'Voltage Source',
+    [['V', 'V', 1]],
+    'Ideal DC voltage source: v(p) − v(n) = V.',
+  ],
+  [
+    'elec.current_source',
+    'Current Source',
+    [['I', 'A', 1]],
+    'Ideal DC current source driving I from p through the source to n.',
+  ],
+  [
+    'elec.sine_voltage',
+    'Sine Voltage Source',
+    [['V', 'V', 1], ['f', 'Hz', 50], ['phase', 'rad', 0], ['offset', 'V', 0]],
+    'Sinusoidal voltage source: offset + V·sin(2πft + phase).',
+  ],
+  [
+    'elec.diode',
+    'Diode',
+    [['Is', 'A', 1e-12], ['N', '', 1], ['Vt', 'V', 0.025852]],
+    'Shockley diode: i = Is·(exp(v / (N·Vt)) − 1).',
+  ],
+  [
+    'elec.switch',
+    'Ideal Switch',
+    [['Ron', 'Ω', 1e-5], ['Goff', 'S', 1e-5], ['t_on', 's', 0], ['t_off', 's', 1e30]],
+    'Ideal switch: Ron when closed between t_on and t_off, conductance Goff when open.',
+  ],
+  ['trans.fixed', 'Fixed (Translational)', [], 'Fixed translational flange (zero velocity).'],
+  [
+    'trans.mass',
+    'Mass',
+    [['m', 'kg', 1], ['v0', 'm/s', 0]],
+    'Sliding mass: m·dv/dt = sum of flange forces, starting at v0.',
+  ],
+  [
+    'trans.spring',
+    'Spring',
+    [['c', 'N/m', 1000], ['s_rel0', 'm', 0]],
+    'Linear spring: F = c·(s_rel − s_rel0).',
+  ],
+  ['trans.damper', 'Damper', [['d', 'N·s/m', 10]], 'Linear damper: F = d·v_rel.'],
+  [
+    'trans.friction',
+    'Friction (Translational)',
+    [['F_c', 'N', 10], ['d', 'N·s/m', 0], ['v_s', 'm/s', 1e-3]],
+    'Coulomb plus viscous friction, smoothed with tanh(v_rel / v_s).',
+  ],
+  [
+    'trans.force',
+    'Force Source',
+    [['F', 'N', 1]],
+    'Ideal force source pushing on the connected flange.',
+  ],
+  ['rot.fixed', 'Fixed (Rotational)', [], 'Fixed rotational flange (zero speed).'],
+  [
+    'rot.inertia',
+    'Inertia',
+    [['J', 'kg·m²', 1], ['w0', 'rad/s', 0]],
+    'Rotating inertia: J·dω/dt = sum of flange torques, starting at w0.',
+  ],
+  [
+    'rot.spring',
+    'Torsion Spring',
+    [['c', 'N·m/rad', 1000], ['phi_rel0', 'rad', 0]],
+    'Torsion spring: τ = c·(φ_rel − phi_rel0).',
+  ],
+  ['rot.damper', 'Rotational Damper', [['d', 'N·m·s/rad', 1]], 'Rotational damper: τ = d·ω_rel.'],
+  [
+    'rot.gear',
+    'Ideal Gear',
+    [['ratio', '', 2]],
+    'Ideal gear: ω_a = ratio·ω_b, lossless torque transfer.',
+  ],
+  [
+    'rot.clutch',
+    'Clutch',
+    [['T_max', 'N·m', 10], ['w_s', 'rad/s', 1e-2], ['t_engage', 's', 0]],
+    'Friction clutch engaging at t_engage with torque capacity T_max.',
+  ],
+  [
+    'rot.friction',
+    'Bearing Friction',
+    [['T_c', 'N·m', 1], ['d', 'N·m·s/rad', 0], ['w_s', 'rad/s', 1e-2]],
+    'Bearing friction: Coulomb plus viscous, smoothed with tanh(ω_rel / w_s).',
+  ],
+  [
+    'rot.torque',
+    'Torque Source',
+    [['tau', 'N·m', 1]],
+    'Ideal torque source driving the connected flange.',
+  ],
+  [
+    'thermal.heat_capacitor',
+    'Heat Capacitor',
+    [['C', 'J/K', 1000], ['T0', 'K', 293.15]],
+    'Lumped heat capacity: C·dT/dt = Q, starting at T0.',
+  ],
+  [
+    'thermal.conductor',
+    'Thermal Conductor',
+    [['G', 'W/K', 1]],
+    'Thermal conductor: Q = G·(T_a − T_b).',
+  ],
+  [
+    'thermal.convection',
+    'Convection',
+    [['h', 'W/(m²·K)', 10], ['A', 'm²', 1]],
+    'Convection between a solid and a fluid: Q = h·A·ΔT.',
+  ],
+  [
+    'thermal.radiation',
+    'Radiation',
+    [['epsilon', '', 0.9], ['A', 'm²', 1]],
+    'Grey-body radiation: Q = σ·ε·A·(T_a⁴ − T_b⁴).',
+  ],
+  [
+    'thermal.fixed_temperature',
+    'Fixed Temperature',
+    [['T', 'K', 293.15]],
+    'Fixed temperature boundary.',
+  ],
+  [
+    'thermal.heat_source',
+    'Heat Flow Source',
+    [['Q', 'W', 100]],
+    'Heat flow source into the connected port.',
+  ],
+  [
+    'hyd.pressure_source',
+    'Pressure Source',
+    [['p', 'Pa', 1e5]],
+    'Ideal pressure source (tank or supply).',
+  ],
+  [
+    'hyd.flow_source',
+    'Flow Source',
+    [['Q', 'm³/s', 1e-4]],
+    'Ideal volumetric flow source into the connected port.',
+  ],
+  [
+    'hyd.orifice',
+    'Orifice',
+    [['Cd', '', 0.7], ['A', 'm²', 1e-5], ['rho', 'kg/m³', 850], ['dp_t', 'Pa', 100]],
+    'Turbulent orifice Q = Cd·A·√(2Δp/ρ), laminar near Δp = 0.',
+  ],
+  [
+    'hyd.accumulator',
+    'Accumulator',
+    [['C', 'm³/Pa', 1e-9], ['p0', 'Pa', 1e5]],
+    'Linearised accumulator: C·dp/dt = Q, starting at p0.',
+  ],
+  [
+    'hyd.pump',
+    'Pump',
+    [['D', 'm³/rad', 1e-6]],
+    'Ideal displacement pump: Q = D·ω, shaft torque D·Δp.',
+  ],
+  [
+    'hyd.cylinder',
+    'Cylinder',
+    [['A', 'm²', 1e-3]],
+    'Single-acting cylinder: Q = A·v, rod force A·p.',
+  ],
+]
+
+export function registerAcausalBlocks(register: (def: BlockDef) => void): void {
+  register({
+    type: 'acausal.simulate',
+    label: 'Acausal Simulation',
+    category: 'simulation',
+    nodeKind: 'csOperation',
+    inputs: [
+      { id: 'network', label: 'Network (optional)' },
+      { id: 't_eval', label: 'Output times (optional)' },
+    ],
+    defaultData: {
+      blockType: 'acausal.simulate',
+      label: 'Acausal Simulation',
+      components: [
+        {
+          id: 'V1',
+          ports: [{ name: 'p', domain: 'electrical' }, { name: 'n', domain: 'electrical' }],
+          parameters: { V: 12 },
+          equations: ['p.voltage - n.voltage = V', 'p.current + n.current = 0'],
+        },
+        { id: 'G', ports: [{ name: 'p', domain: 'electrical' }], equations: ['p.voltage = 0'] },
+        { id: 'M1', type: 'dc_motor', R: 1, L: 0.5, k: 0.05, J: 0.01, b: 0.001 },
+        {
+          id: 'load',
+          ports: [{ name: 'flange', domain: 'rotational' }],
+          parameters: { J: 0.04 },
+          equations: ['J * der(flange.angular_velocity) = flange.torque'],
+        },
+      ],
+      connections: [
+        ['V1.p', 'M1.p_elec'],
+        ['M1.n_elec', 'V1.n'],
+        ['V1.n', 'G.p'],
+        ['M1.shaft', 'load.flange'],
+      ],
+      t_end: 10.0,
+      dt: 0.01,
+      tolerance: 1e-6,
+      output_interval: 0.05,
+    },
+    proOnly: true,
+    synonyms: ['acausal', 'modelica', 'physical modelling', 'multi-domain', 'dummy derivatives', 'index reduction', 'BLT'],
+    tags: ['simulation', 'dae', 'acausal', 'physical'],
+    description:
+      'Simulate a network of physical components joined at ports, from an Acausal Network block or data. components: library types (elec.resistor, rot.inertia, …), dc_motor, or custom blocks with ports (name, domain), parameters and equations (der(x) for derivatives, port variables like p.voltage). connections: [port_a, port_b] pairs. Pantelides index reduction with dummy derivatives, BLT sorting with tearing, consistent initialization and Radau IIA. Returns Table [t, every across, through and internal variable].',
+  })
+
+  register({
+    type: 'acausal.network',
+    label: 'Acausal Network',
+    category: 'simulation',
+    nodeKind: 'csOperation',
+    variadic: true,
+    minInputs: 1,
+    maxInputs: 64,
+    inputs: [
+      { id: 'in_0', label: 'Component 1' },
+      { id: 'in_1', label: 'Component 2' },
+    ],
+    defaultData: {
+      blockType: 'acausal.network',
+      label: 'Acausal Network',
+      dynamicInputCount: 2,
+      connections: [],
+    },
+    proOnly: true,
+    synonyms: ['acausal network', 'schematic', 'modelica', 'connect components'],
+    tags: ['simulation', 'acausal', 'physical'],
+    description:
+      'Collect acausal component blocks and join their ports. connections: [port_a, port_b] pairs such as ["R1.n", "C1.p"]. Feed the result to Acausal Simulation.',
+  })
+
+  register({
+    type: 'acausal.circuit.dc',
+    label: 'Circuit DC Operating Point',
+    category: 'simulation',
+    nodeKind: 'csOperation',
+    inputs: [{ id: 'network', label: 'Network' }],
+    defaultData: {
+      blockType: 'acausal.circuit.dc',
+      label: 'Circuit DC Operating Point',
+      output: 'voltages',
+    },
+    proOnly: true,
+    synonyms: ['dc operating point', 'bias point', 'spice .op', 'mna', 'nodal analysis'],
+    tags: ['simulation', 'circuit', 'electrical'],
+    description:
+      'DC operating point of an electrical network by modified nodal analysis: capacitors open, inductors shorted, Newton with junction limiting for diodes. output: voltages (v(node)) or currents (i(component)).',
+  })
+
+  register({
+    type: 'acausal.circuit.ac',
+    label: 'Circuit AC Sweep',
+    category: 'simulation',
+    nodeKind: 'csOperation',
+    inputs: [
+      { id: 'network', label: 'Network' },
+      { id: 'f', label: 'Frequency (optional)' },
+    ],
+    defaultData: {
+      blockType: 'acausal.circuit.ac',
+      label: 'Circuit AC Sweep',
+      probe: '',
+      source: '',
+      f_min: 1,
+      f_max: 1e6,
+      points: 200,
+    },
+    proOnly: true,
+    synonyms: ['ac analysis', 'bode', 'frequency response', 'spice .ac', 'small signal'],
+    tags: ['simulation', 'circuit', 'electrical', 'frequency'],
+    description:
+      'Small-signal AC response at probe (a port for its node voltage, or a component id for its current), driven by a unit source around the DC operating point. Scalar f returns a Complex; otherwise a log sweep Table [f_hz, re, im, magnitude, phase_deg].',
+  })
+
+  register({
+    type: 'acausal.circuit.transient',
+    label: 'Circuit Transient',
+    category: 'simulation',
+    nodeKind: 'csOperation',
+    inputs: [{ id: 'network', label: 'Network' }],
+    defaultData: {
+      blockType: 'acausal.circuit.transient',
+      label: 'Circuit Transient',
+      t_end: 1e-3,
+      dt: 1e-6,
+      method: 'trapezoidal',
+      use_initial_conditions: true,
+      output: 'voltages',
+    },
+    proOnly: true,
+    synonyms: ['transient analysis', 'spice .tran', 'time domain circuit', 'trapezoidal', 'gear'],
+    tags: ['simulation', 'circuit', 'electrical'],
+    description:
+      'Fixed-step circuit transient by modified nodal analysis. method: trapezoidal or gear (BDF2, damps switching ringing). Starts from component initial conditions or the DC operating point. Returns Table [t, v(node)… or i(component)…].',
+  })
+
+  for (const [kind, label, params, description] of LIBRARY) {
+    const type = `acausal.${kind}`
+    const domain = kind.split('.')[0]
+    register({
+      type,
+      label,
+      category: 'simulation',
+      nodeKind: 'csOperation',
+      inputs: params.map(([name, unit]) => ({
+        id: name,
+        label: unit ? `${name} (${unit})` : name,
+      })),
+      defaultData: {
+        blockType: type,
+        label,
+        id: kind.split('.')[1],
+        ...Object.fromEntries(params.map(([name, , value]) => [name, value])),
+      },
+      proOnly: true,
+      synonyms: ['acausal', 'modelica', label.toLowerCase()],
+      tags: ['acausal', 'physical', domain],
+      description: `${description} Connect its ports in an Acausal Network.`,
+    })
+  }
+}
@@ -0,0 +1,77 @@
+/**
+ * acausalSync.test.ts — Acausal component library ↔ TS block sync test.
+ *
+ * Reads the component table (`COMPONENTS`) from acausal/library.rs and checks
+ * that every component has an `acausal.<kind>` block with the same label and
+ * parameters (name, unit, default) in acausal-blocks.ts, and vice versa.
+ */
+
+import { describe, it, expect } from 'vitest'
+import { readFileSync } from 'fs'
+import { resolve } from 'path'
+import { BLOCK_REGISTRY } from './registry'
+import { registerAllBlocks } from './registerAllBlocks'
+
+registerAllBlocks()
+
+/** acausal.* blocks that are not library components. */
+const NON_COMPONENT_BLOCKS = new Set([
+  'acausal.simulate',
+  'acausal.network',
+  'acausal.circuit.dc',
+  'acausal.circuit.ac',
+  'acausal.circuit.transient',
+])
+
+interface RustComponent {
+  kind: string
+  label: string
+  params: [name: string, unit: string, value: number][]
+}
+
+function extractRustComponents(): RustComponent[] {
+  const src = readFileSync(
+    resolve(__dirname, '../../crates/engine-core/src/acausal/library.rs'),
+    'utf-8',
+  )
+  const start = src.indexOf('pub static COMPONENTS')
+  const table = src.slice(start, src.indexOf('\n];', start))
+  return table
+    .split('ComponentSpec {')
+    .slice(1)
+    .map((spec) => ({
+      kind: /kind:\s*"([^"]+)"/.exec(spec)?.[1] ?? '',
+      label: /label:\s*"([^"]+)"/.exec(spec)?.[1] ?? '',
+      params: [...spec.matchAll(/param\("([^"]*)",\s*"([^"]*)",\s*([-0-9.e_]+),/g)].map(
+        (m): [string, string, number] => [m[1], m[2], Number(m[3].replace(/_/g, ''))],
+      ),
+    }))
+}
+
+describe('Acausal library ↔ acausal-blocks.ts sync', () => {
+  const rust = extractRustComponents()
+  const tsTypes = [...BLOCK_REGISTRY.keys()].filter(
+    (type) => type.startsWith('acausal.') && !NON_COMPONENT_BLOCKS.has(type),
+  )
+
+  it('Rust library has components', () => {
+    expect(rust.length).toBeGreaterThan(30)
+  })
+
+  it('every library component has a TS block and vice versa', () => {
+    expect(tsTypes.sort()).toEqual(rust.map((c) => `acausal.${c.kind}`).sort())
+  })
+
+  it('labels and parameters match the library', () => {
+    for (const { kind, label, params } of rust) {
+      const def = BLOCK_REGISTRY.get(`acausal.${kind}`)
+      expect(def?.label, kind).toBe(label)
+      expect(def?.inputs.map((p) => p.label), kind).toEqual(
+        params.map(([name, unit]) => (unit ? `${name} (${unit})` : name)),
+      )
+      for (const [name, , value] of params) {
+        expect(def?.defaultData[name], `${kind}.${name}`).toBe(value)
+      }
+    }
+  })
+})
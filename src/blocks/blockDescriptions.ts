@@ -223,6 +223,20 @@ export const BLOCK_DESCRIPTIONS: Record<string, string> = {
   unit_convert: 'Generic unit conversion. Pick input and output units from any dimension.',
   'ode.fem2d':
     '2D FEM Poisson solver: solve on [x0,x1]x[y0,y1] with P1 triangular elements. Set rhs, dirichlet, nx/ny. Returns Table [x, y, u]. Post-process with a plot or contour block.',
+  'fem.mesh':
+    'Unstructured Delaunay triangle mesh of a polygon with holes. Edge markers number the polygon edges so boundary conditions can target them in the FEM blocks.',
+  'fem.heat':
+    'Steady 2D heat conduction on any polygonal part with fixed temperature, heat flux and convection edges. Use for heat sinks, insulation and thermal bridges.',
+  'fem.elasticity':
+    'Plane stress / plane strain FEA with displacement, traction and spring supports. Returns displacements and von Mises stress; for brackets, plates with holes and stress concentrations.',
+  'fem.stokes':
+    'Steady creeping (Stokes) flow in 2D channels and cavities with velocity inlets, walls and free outlets. Use for microfluidics, lubrication gaps and slow viscous flow.',
+  'fem.modal':
+    'Natural frequencies and mode shapes of a 2D plate or beam from FEM mass and stiffness. Use to check that a part stays clear of excitation frequencies.',
+  'modal.eigen':
+    'Natural frequencies, mass-normalised mode shapes and participation factors from mass and stiffness matrices. Works for spring–mass models and assembled FEM matrices.',
+  'modal.frf':
+    'Frequency response function of a damped structure between two degrees of freedom. Use for resonance peaks, isolator design and comparing with measured FRFs.',
   'ad.gradCheckpoint':
     'Gradient checkpointing (Revolve): compute ODE sensitivities with O(cs) memory using binomial Revolve schedule. Returns Table [param_idx, gradient, recomputations].',
   'ad.customVjp':
@@ -639,6 +653,20 @@ export const BLOCK_DESCRIPTIONS: Record<string, string> = {
   matrix_cond:
     'Condition number κ(A) = σ_max / σ_min via SVD. Large values indicate ill-conditioning.',
 
+  // ── Sparse matrices ────────────────────────────────────────────────────────
+  'sparse.fromMatrix': 'Convert a dense matrix to a sparse CSR matrix, dropping entries at or below a threshold.',
+  'sparse.fromTable': 'Assemble a sparse matrix from (row, col, value) triplets; duplicate entries are summed.',
+  'sparse.toMatrix': 'Expand a sparse matrix into a dense matrix.',
+  'sparse.toTable': 'List the nonzeros of a sparse matrix as (row, col, value) rows, e.g. for a spy plot.',
+  'sparse.multiply':
+    'Sparse product A·b with a vector, dense matrix or sparse matrix, without densifying A.',
+  'sparse.solve':
+    'Solve sparse Ax = b: Cholesky or pivoted LU with AMD/nested-dissection ordering, or ILU(0)-preconditioned CG/BiCGStab/GMRES.',
+  'sparse.stats':
+    'Sparsity pattern summary: nnz, density, bandwidths, row nnz range, missing diagonals, symmetry, diagonal dominance.',
+  'sparse.import': 'Read a sparse matrix from a Matrix Market (.mtx) or Harwell–Boeing file.',
+  'sparse.export': 'Write a sparse matrix as a Matrix Market or Harwell–Boeing file (Bytes output).',
+
   // ── Rootfinding ────────────────────────────────────────────────────────────
   root_newton:
     'Newton-Raphson rootfinding with backtracking line search. Uses numerical central difference for derivatives.',
@@ -810,6 +838,20 @@ export const BLOCK_DESCRIPTIONS: Record<string, string> = {
     'Expands a symbolic expression by distributing multiplication over addition. Supports binomial theorem for integer powers up to 6. E.g. (x+1)² → x² + 2x + 1. Returns LaTeX.',
   'sym.substitute':
     'Substitutes a numeric value for a named variable in a symbolic expression, then simplifies. Useful for evaluating symbolic expressions at a point. Returns a LaTeX string.',
+  'sym.solve':
+    'Solves equations for a variable: polynomials in closed form up to quartics, rational and simple transcendental equations, and linear systems by Gaussian elimination.',
+  'sym.series':
+    'Taylor or Laurent series of an expression about a point up to a chosen order. Returns LaTeX, the truncated series and its coefficients.',
+  'sym.limit':
+    "Limit of an expression as a variable approaches a point or ±infinity, from the leading series term or by L'Hôpital's rule.",
+  'sym.jacobian':
+    'Symbolic Jacobian of several expressions, or the gradient or Hessian of one, with respect to listed variables or the free symbols.',
+  'sym.matrix':
+    'Matrices of symbolic expressions: determinant, inverse up to 6×6, transpose, product, sum and difference. Entries stay exact expressions.',
+  'sym.codegen':
+    'Generates Rust, C or expr-language source from symbolic expressions, with common-subexpression elimination into temporaries.',
+  'sym.lagrange':
+    'Euler–Lagrange equations of motion from kinetic and potential energy in generalised coordinates, solved for the accelerations.',
   'sym.expressionInput':
     'Symbolic expression input: parse a math string via CAS, output as LaTeX. Connects to Differentiate, Integrate, Simplify, Substitute, and other CAS blocks. No input ports.',
   'ad.mixedJacobian':
@@ -817,20 +859,28 @@ export const BLOCK_DESCRIPTIONS: Record<string, string> = {
   'sym.compiledEval':
     'Compiled expression evaluator: pre-parses formula to AST for fast repeated evaluation. Faster than eval_expr() in tight loops (ODE steps, Monte Carlo).',
   'sym.groebner':
-    'Groebner Basis (Buchberger): auto-reduced basis for polynomial systems. Input: semicolon-separated polynomials, variables. Modes: basis/solve; orders: grevlex/lex/grlex.',
+    'Groebner Basis (Buchberger) in exact rational arithmetic: reduced basis for polynomial systems. Input: semicolon-separated polynomials, variables. Modes: basis/solve; orders: grevlex/lex/grlex.',
   // ODE Solvers
   'ode.rk4':
     'Solve a system of ODEs using the classic 4th-order Runge-Kutta method. Output = table of time vs state variables.',
   'ode.rk45':
     'Solve ODEs using the Dormand-Prince adaptive-step method. Automatically adjusts step size for accuracy.',
   'ode.event':
-    'ODE solver with zero-crossing event detection. Bisection refines the exact crossing time when event_expr changes sign. Use for impact, switch, or termination events.',
+    'Hybrid ODE solver with zero-crossing events (RK4, RK45, BDF, Radau). Events can terminate, reset states (e.g. y1 = -0.8*y1) or switch equation sets. Use for impacts, contact and clutches.',
   'ode.steady_state':
     'Find ODE steady-state y* where f(y*)=0 via Newton-Raphson with numerical Jacobian. Ideal for control operating points, chemical equilibria, or autonomous ODEs.',
+  'ode.dde':
+    'Delay differential equation solver: lagged states as y0(t - tau), constant or state-dependent delays, history for t < t_start. For transport delays, dead time and pipeline thermal models.',
+  'ode.sde':
+    'Stochastic differential equation solver (Euler-Maruyama, Milstein, SRA) with drift, diffusion and a seeded Brownian path. For noisy loads, Ornstein-Uhlenbeck processes and random walks.',
+  'ode.sdeEnsemble':
+    'Ensemble of seeded SDE paths reduced to mean, variance and quantile bands per time point. Use to size reliability margins from stochastic load or degradation models.',
   'ode.symplectic':
-    'Symplectic integrator (Stormer-Verlet) for Hamiltonian systems. Conserves energy over long runs. Ideal for orbital mechanics, molecular dynamics, and pendulums.',
+    'Symplectic integrators (Verlet, Yoshida 4/6, Forest-Ruth) with reversible adaptive steps and energy/momentum drift columns. For orbital mechanics, molecular dynamics and pendulums.',
   'ode.daeIndexReduction':
     'Pantelides index reduction: bipartite-graph structural analysis of a DAE. Detects high-index constraints needing differentiation. Reports structural_index and diff_count.',
+  'acausal.simulate':
+    'Acausal network simulation: wire physical components at their ports and run. Index reduction, BLT tearing and consistent initialization are automatic; outputs every across and through variable.',
   'ode.dae':
     'DAE solver: differential equations dy/dt=f(t,y,z) plus algebraic constraints g(t,y,z)=0. BDF-2 + Newton iteration with automatic consistent initialisation.',
   'ode.bdf':
@@ -838,7 +888,7 @@ export const BLOCK_DESCRIPTIONS: Record<string, string> = {
   'ode.radau':
     'Radau IIA stiff ODE solver (3-stage, order 5, L-stable). Damps spurious oscillations. Excellent for very stiff problems and problems with discontinuities.',
   'ode.pde1d':
-    '1D PDE via Method of Lines. Types: heat, advection, advection_diffusion, wave. BCs: dirichlet:VALUE or neumann. Output: table with columns t, x0..xN.',
+    '1D PDE via Method of Lines: heat, advection, advection_diffusion, wave. BCs: dirichlet:VALUE or neumann. method: rk4 or bdf (implicit, for fine grids). Output: table t, x0..xN.',
   // Vehicle Simulation
   'veh.tire.lateralForce': 'Pacejka Magic Formula lateral tire force Fy from slip angle.',
   'veh.tire.longForce': 'Pacejka Magic Formula longitudinal tire force Fx from slip ratio.',
@@ -953,7 +1003,10 @@ export const BLOCK_DESCRIPTIONS: Record<string, string> = {
 
   // ── FMU ──────────────────────────────────────────────────────────────────
   'fmu.import': 'Import and co-simulate a Functional Mock-up Unit (FMU v2.0) model.',
-  'fmu.export': 'Export a ChainSolve sub-graph as an FMU for use in external simulators.',
+  'fmu.export':
+    'Compile a ChainSolve sub-graph (arithmetic, expressions, ODE blocks) into an FMI 2.0 FMU with C sources and an embedded integrator.',
+  'fmu.cosimulate':
+    'Run declarative FMUs (modelDescription.xml plus equations) in a fixed-step co-simulation master and tabulate every output.',
 
   // ── Scripting ─────────────────────────────────────────────────────────────
   'scripting.python': 'Execute Python code via Pyodide (in-browser WASM). Full NumPy/SciPy access.',
@@ -35,7 +35,6 @@ registerAllBlocks()
 // 2.13: unitInput is remapped to 'number' in bridge.ts; SI conversion runs in the UI.
 // 2.65: transferFunction is remapped to 'display' in bridge.ts; LTI computation runs in the UI.
 // 2.66: stateSpace is remapped to 'display' in bridge.ts; state-space simulation runs in the UI.
-// 2.70: ctrl.zoh is remapped to 'number' in bridge.ts; hold logic runs in the UI.
 // 2.70: ctrl.rateTransition is remapped to 'number' in bridge.ts; rate conversion runs in the UI.
 // 2.71: stateMachine is remapped to 'number' in bridge.ts; FSM state tracking runs in the UI.
 // 9.15/2.134: codeBlock is remapped to 'number' in bridge.ts; JS evaluation runs in the UI.
@@ -70,7 +69,6 @@ const UI_ONLY_BLOCKS = new Set([
   'unitInput',
   'transferFunction',
   'stateSpace',
-  'ctrl.zoh',
   'ctrl.rateTransition',
   'stateMachine',
   'codeBlock',
@@ -3,17 +3,20 @@
  *
  * Mixed continuous/discrete simulation building blocks:
  *
- *  ZeroOrderHold: Samples the input at T_s intervals and holds the value
- *    until the next sample. In ChainSolve's reactive model, the block stores
- *    its last-sampled timestamp and value; outputs are updated only when the
- *    elapsed time since last sample exceeds T_s.
+ *  ZeroOrderHold (ctrl.zoh): Samples input u every samplePeriod seconds and
+ *    holds it in between. Engine-native like the blocks below: reactive eval
+ *    shows the first sample, the simulation worker steps the hold.
  *
  *  RateTransition: Converts data between different sample rates. In reactive
  *    mode, acts as a buffer that outputs the last received value at the target
- *    sample rate. Also enforces data type consistency at rate boundaries.
+ *    sample rate. Bridge: ctrl.rateTransition → 'number' (pass-through of
+ *    data.value); rate conversion runs in the UI.
  *
- * Bridge: both block types → 'number' (pass-through in reactive eval).
- * Full hold semantics require the simulation worker (Category 8).
+ *  UnitDelay / DiscreteIntegrator / RateLimiter / DiscreteTransferFn:
+ *    Engine-native stateful blocks (engine-core `discrete`). Reactive eval
+ *    shows the step-0 output; the simulation worker steps their state. Unit
+ *    delay, forward-Euler integrator and strictly proper transfer functions
+ *    may close feedback loops without a cycle error.
  */
 
 import type { BlockDef } from './registry'
@@ -86,4 +89,90 @@ export function registerDiscreteControlBlocks(register: (def: BlockDef) => void)
     description:
       'Transfers data between blocks running at different sample rates (e.g., 1kHz sensor → 100Hz controller). Prevents data corruption at rate boundaries.',
   })
+
+  // ── Unit Delay (z⁻¹) ────────────────────────────────────────────────────────
+  register({
+    type: 'ctrl.unit_delay',
+    label: 'Unit Delay (z⁻¹)',
+    category: 'controlSystems',
+    nodeKind: 'csOperation',
+    inputs: [{ id: 'u', label: 'u' }],
+    proOnly: true,
+    defaultData: {
+      blockType: 'ctrl.unit_delay',
+      label: 'Unit Delay (z⁻¹)',
+      /** Output before the first step (number or array). */
+      initial: 0,
+    },
+    synonyms: ['unit delay', 'z^-1', 'delay', 'memory', 'previous value'],
+    tags: ['control', 'discrete', 'state'],
+    description:
+      'Outputs the input from the previous simulation step: y[k] = u[k−1]. Breaks feedback loops so discrete-time models can close the loop.',
+  })
+
+  // ── Discrete Integrator ─────────────────────────────────────────────────────
+  register({
+    type: 'ctrl.discrete_integrator',
+    label: 'Discrete Integrator',
+    category: 'controlSystems',
+    nodeKind: 'csOperation',
+    inputs: [{ id: 'u', label: 'u' }],
+    proOnly: true,
+    defaultData: {
+      blockType: 'ctrl.discrete_integrator',
+      label: 'Discrete Integrator',
+      /** 'forward' | 'backward' | 'trapezoidal'. */
+      method: 'forward',
+      gain: 1,
+      initial: 0,
+    },
+    synonyms: ['integrator', 'accumulator', 'discrete integral', 'euler integrator'],
+    tags: ['control', 'discrete', 'state'],
+    description:
+      'Accumulates K·u·Δt each step (forward Euler, backward Euler or trapezoidal) with optional lower/upper saturation. Forward Euler can close feedback loops.',
+  })
+
+  // ── Rate Limiter ────────────────────────────────────────────────────────────
+  register({
+    type: 'ctrl.rate_limiter',
+    label: 'Rate Limiter',
+    category: 'controlSystems',
+    nodeKind: 'csOperation',
+    inputs: [{ id: 'u', label: 'u' }],
+    proOnly: true,
+    defaultData: {
+      blockType: 'ctrl.rate_limiter',
+      label: 'Rate Limiter',
+      /** Maximum rising slew rate (units/s). */
+      rising: 1,
+      /** Maximum falling slew rate (units/s, magnitude). */
+      falling: 1,
+    },
+    synonyms: ['rate limiter', 'slew rate', 'ramp limiter'],
+    tags: ['control', 'discrete', 'state'],
+    description:
+      'Limits how fast the output can change between steps: the slope is clamped to [−falling, rising] per second.',
+  })
+
+  // ── Discrete Transfer Function ──────────────────────────────────────────────
+  register({
+    type: 'ctrl.discrete_tf',
+    label: 'Discrete Transfer Fn',
+    category: 'controlSystems',
+    nodeKind: 'csOperation',
+    inputs: [{ id: 'u', label: 'u' }],
+    proOnly: true,
+    defaultData: {
+      blockType: 'ctrl.discrete_tf',
+      label: 'Discrete Transfer Fn',
+      /** Numerator coefficients in ascending powers of z⁻¹. */
+      num: [1],
+      /** Denominator coefficients in ascending powers of z⁻¹. */
+      den: [1, -0.5],
+    },
+    synonyms: ['discrete transfer function', 'digital filter', 'IIR', 'z-domain', 'H(z)'],
+    tags: ['control', 'discrete', 'state', 'filter'],
+    description:
+      'Digital filter H(z) = (b0 + b1·z⁻¹ + …)/(a0 + a1·z⁻¹ + …) in direct form II transposed. Strictly proper filters (b0 = 0) can close feedback loops.',
+  })
 }
@@ -74,9 +74,47 @@ export function registerFmuBlocks(register: (def: BlockDef) => void): void {
     ],
     tags: ['fmu', 'fmi', 'export', 'simulation', 'hil'],
     description:
-      'FMU Export: generates a modelDescription.xml and C stub for exporting a ' +
-      'ChainSolve computation as an FMI-compliant Functional Mock-up Unit. ' +
-      'Input: any computed value. Output: Text containing the modelDescription.xml. ' +
-      'Download the generated FMU from the block inspector.',
+      'FMU Export: compiles the sub-graph connected to its input into an FMI 2.0 ' +
+      'Co-Simulation Functional Mock-up Unit. Arithmetic and expression blocks become C ' +
+      'code, ODE blocks get an embedded RK4 integrator, and parameters and states map to ' +
+      'FMI variables. Output: the .fmu archive as a Bytes value (application/zip).',
+  })
+
+  // ── FMU Co-Simulation ──────────────────────────────────────────────────────
+
+  register({
+    type: 'fmu.cosimulate',
+    label: 'FMU Co-Simulation',
+    category: 'simulation',
+    nodeKind: 'csOperation',
+    inputs: [
+      { id: 'fmus', label: 'FMU spec (JSON)' },
+      { id: 'inputs', label: 'External inputs (optional)' },
+    ],
+    proOnly: true,
+    defaultData: {
+      blockType: 'fmu.cosimulate',
+      label: 'FMU Co-Simulation',
+      fmus: [],
+      connections: [],
+      startTime: 0,
+      stopTime: 10,
+      stepSize: 0.01,
+      algorithm: 'gaussSeidel',
+    },
+    synonyms: [
+      'co-simulation master',
+      'fmu master',
+      'dostep',
+      'fmi co-simulation',
+      'jacobi',
+      'gauss-seidel',
+    ],
+    tags: ['fmu', 'fmi', 'co-simulation', 'simulation'],
+    description:
+      'FMU Co-Simulation: fixed-step master for FMUs defined by modelDescription.xml plus ' +
+      'der(x) = ... / y = ... equations, or exported by FMU Export. Connections map ' +
+      'instance.output to instance.input; unconnected inputs are driven by the inputs port ' +
+      '(scalar, vector or time table). Output: table of time and every instance.output.',
   })
 }
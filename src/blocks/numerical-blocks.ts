@@ -347,4 +347,204 @@ export function registerNumericalBlocks(register: (def: BlockDef) => void): void
     description:
       'Solve −k∇²u = f on a rectangular domain [x0,x1]×[y0,y1] using P1 triangular finite elements. Set rhs (expression in x,y for source f) and dirichlet (expression for boundary condition g_D). Returns Table [x, y, u] — one row per node.',
   })
+
+  register({
+    type: 'fem.mesh',
+    label: 'FEM Mesh',
+    category: 'simulation',
+    nodeKind: 'csOperation',
+    inputs: [],
+    defaultData: {
+      blockType: 'fem.mesh',
+      label: 'FEM Mesh',
+      polygon: [
+        [0, 0],
+        [1, 0],
+        [1, 1],
+        [0, 1],
+      ],
+      holes: [],
+      h: 0.1,
+      output: 'nodes',
+    },
+    synonyms: ['mesh', 'triangulation', 'Delaunay', 'unstructured mesh', 'mesher'],
+    tags: ['fem', 'mesh', 'simulation'],
+    description:
+      'Delaunay triangle mesh of a polygon with holes at target element size h. Edge k of the polygon (vertex k to k+1) has marker k; hole edges are numbered on from there. output: nodes → [x, y], elements → [n0, n1, n2], boundary → [n0, n1, edge].',
+  })
+
+  register({
+    type: 'fem.heat',
+    label: 'FEM Heat Conduction',
+    category: 'simulation',
+    nodeKind: 'csOperation',
+    inputs: [],
+    defaultData: {
+      blockType: 'fem.heat',
+      label: 'FEM Heat Conduction',
+      polygon: [
+        [0, 0],
+        [1, 0],
+        [1, 1],
+        [0, 1],
+      ],
+      holes: [],
+      h: 0.1,
+      k: 1,
+      source: '0',
+      bcs: [
+        { edges: [3], type: 'dirichlet', value: '100' },
+        { edges: [1], type: 'robin', h: '10', ambient: '20' },
+      ],
+      output: 'nodes',
+    },
+    synonyms: ['heat transfer', 'thermal FEM', 'conduction', 'temperature field', 'convection boundary'],
+    tags: ['fem', 'pde', 'simulation', 'thermal'],
+    description:
+      'Steady heat conduction −∇·(k∇T) = Q on an unstructured triangle mesh. bcs list edge markers with type dirichlet (value), neumann (flux into the body) or robin (h, ambient); other edges are insulated. k and source are expressions in x, y. output: nodes → [x, y, T, q_mag], elements → [x, y, qx, qy].',
+  })
+
+  register({
+    type: 'fem.elasticity',
+    label: 'FEM Plane Elasticity',
+    category: 'simulation',
+    nodeKind: 'csOperation',
+    inputs: [],
+    defaultData: {
+      blockType: 'fem.elasticity',
+      label: 'FEM Plane Elasticity',
+      polygon: [
+        [0, 0],
+        [1, 0],
+        [1, 0.2],
+        [0, 0.2],
+      ],
+      holes: [],
+      h: 0.02,
+      E: 210e9,
+      nu: 0.3,
+      thickness: 0.01,
+      model: 'stress',
+      bx: '0',
+      by: '0',
+      bcs: [
+        { edges: [3], type: 'fixed', ux: '0', uy: '0' },
+        { edges: [1], type: 'traction', tx: '0', ty: '-1e6' },
+      ],
+      output: 'nodes',
+    },
+    synonyms: ['FEA', 'stress analysis', 'plane stress', 'plane strain', 'von Mises', 'linear elasticity'],
+    tags: ['fem', 'structural', 'simulation', 'stress'],
+    description:
+      'Linear elasticity in plane stress or plane strain on an unstructured mesh. bcs: fixed (ux and/or uy; omit one for a roller), traction (tx, ty per unit area) or spring (k, ux0, uy0); other edges are free. output: nodes → [x, y, ux, uy, von_mises], elements → [x, y, sxx, syy, sxy, szz, von_mises].',
+  })
+
+  register({
+    type: 'fem.stokes',
+    label: 'FEM Stokes Flow',
+    category: 'simulation',
+    nodeKind: 'csOperation',
+    inputs: [],
+    defaultData: {
+      blockType: 'fem.stokes',
+      label: 'FEM Stokes Flow',
+      polygon: [
+        [0, 0],
+        [3, 0],
+        [3, 1],
+        [0, 1],
+      ],
+      holes: [],
+      h: 0.1,
+      mu: 1,
+      fx: '0',
+      fy: '0',
+      bcs: [
+        { edges: [0, 2], type: 'dirichlet', ux: '0', uy: '0' },
+        { edges: [3], type: 'dirichlet', ux: '4*y*(1-y)', uy: '0' },
+      ],
+      output: 'nodes',
+    },
+    synonyms: ['creeping flow', 'Stokes', 'viscous flow', 'microfluidics', 'CFD', 'lubrication'],
+    tags: ['fem', 'cfd', 'simulation', 'flow'],
+    description:
+      'Steady Stokes flow −μΔu + ∇p = f, ∇·u = 0 with stabilised P1–P1 elements. bcs: dirichlet (ux, uy velocity) or traction (tx, ty); edges without a condition are free outflows. output: nodes → [x, y, ux, uy, p, speed], elements → [x, y, vorticity, divergence].',
+  })
+
+  register({
+    type: 'fem.modal',
+    label: 'FEM Modal Analysis',
+    category: 'simulation',
+    nodeKind: 'csOperation',
+    inputs: [],
+    defaultData: {
+      blockType: 'fem.modal',
+      label: 'FEM Modal Analysis',
+      polygon: [
+        [0, 0],
+        [1, 0],
+        [1, 0.1],
+        [0, 0.1],
+      ],
+      holes: [],
+      h: 0.0125,
+      E: 210e9,
+      nu: 0.3,
+      density: 7850,
+      thickness: 0.01,
+      model: 'stress',
+      bcs: [{ edges: [3], type: 'fixed', ux: '0', uy: '0' }],
+      n_modes: 6,
+      output: 'frequencies',
+    },
+    synonyms: ['natural frequencies', 'mode shapes', 'eigenfrequency', 'vibration modes', 'resonance'],
+    tags: ['fem', 'structural', 'simulation', 'vibration'],
+    description:
+      'Natural frequencies of a plane elastic body from its consistent mass and stiffness matrices. bcs as in FEM Plane Elasticity (fixed edges are removed; unsupported bodies give rigid-body modes at 0 Hz). output: frequencies → [mode, omega, freq_hz, mass_fraction_x, mass_fraction_y], shapes → [x, y, ux_1, uy_1, …].',
+  })
+
+  register({
+    type: 'modal.eigen',
+    label: 'Modal Analysis',
+    category: 'simulation',
+    nodeKind: 'csOperation',
+    inputs: [
+      { id: 'K', label: 'K (stiffness)' },
+      { id: 'M', label: 'M (mass)' },
+      { id: 'r', label: 'r (influence)' },
+    ],
+    defaultData: { blockType: 'modal.eigen', label: 'Modal Analysis', n_modes: 0, output: 'modes' },
+    synonyms: ['generalized eigenproblem', 'natural frequencies', 'mode shapes', 'participation factor', 'effective mass'],
+    tags: ['vibration', 'structural', 'simulation', 'eigen'],
+    description:
+      'Solves K·φ = ω²·M·φ for mass-normalised modes (dense below 300 dofs, shift-invert Lanczos above). M may be a matrix or a vector of lumped masses. n_modes = 0 returns all modes. output: modes → [mode, omega, freq_hz, participation, effective_mass, mass_fraction] for influence vector r (default all ones), shapes → [dof, mode_1, …].',
+  })
+
+  register({
+    type: 'modal.frf',
+    label: 'Frequency Response (FRF)',
+    category: 'simulation',
+    nodeKind: 'csOperation',
+    inputs: [
+      { id: 'K', label: 'K (stiffness)' },
+      { id: 'M', label: 'M (mass)' },
+      { id: 'C', label: 'C (damping)' },
+    ],
+    defaultData: {
+      blockType: 'modal.frf',
+      label: 'Frequency Response (FRF)',
+      input_dof: 0,
+      output_dof: 0,
+      f_min: 0.1,
+      f_max: 100,
+      points: 500,
+      method: 'auto',
+      zeta: 0.02,
+      kind: 'receptance',
+    },
+    synonyms: ['FRF', 'harmonic response', 'transfer function', 'receptance', 'mobility', 'accelerance', 'Bode'],
+    tags: ['vibration', 'structural', 'simulation', 'frequency'],
+    description:
+      'Frequency response from input_dof to output_dof. method direct solves (K − ω²M + iωC)·x = f at each frequency (up to 200 dofs); modal superposes modes with damping ratio zeta; auto picks by size. kind: receptance, mobility or accelerance. Returns [f_hz, re, im, magnitude, phase_deg].',
+  })
 }
@@ -6,11 +6,13 @@
  * Parquet binary format and ChainSolve's Table value type.
  *
  * Engine blocks:
- *   - parquet_import: reads parquetBytes (u8 array) → Table
- *   - parquet_export: Table → parquetBytes (Vector of u8 as f64)
+ *   - parquet_import: reads the blob named by data.blobRef → Table
+ *   - parquet_export: Table → Bytes value (application/vnd.apache.parquet)
  *
- * The UI serialises the file as a JSON array of byte values stored in
- * data.parquetBytes. This matches the pattern used by mat_import.
+ * The UI registers the file bytes once via engine.registerBlob(id, bytes) and
+ * stores the id in data.blobRef, so the file never travels through JSON.
+ * data.parquetBytes (a JSON array of u8) is still read when blobRef is empty,
+ * for projects saved before binary datasets existed. mat_import works the same way.
  */
 
 import type { BlockDef } from './registry'
@@ -26,7 +28,9 @@ export function registerParquetBlocks(register: (def: BlockDef) => void): void {
     defaultData: {
       blockType: 'parquet_import',
       label: 'Parquet Import',
-      /** Raw Parquet file bytes as a JSON array of u8 integers. */
+      /** Id of the binary dataset holding the Parquet file (see registerBlob). */
+      blobRef: '',
+      /** Legacy: raw Parquet file bytes as a JSON array of u8 integers. */
       parquetBytes: [] as number[],
       /** Original file name for display. */
       fileName: '',
@@ -72,7 +76,7 @@ export function registerParquetBlocks(register: (def: BlockDef) => void): void {
     ],
     tags: ['data', 'export', 'file', 'columnar', 'parquet'],
     description:
-      'Export a Table to Parquet binary format. Outputs a Vector of byte values (u8 as f64). ' +
+      'Export a Table to Parquet binary format. Outputs a Bytes value (raw file bytes). ' +
       'The UI downloads this as a .parquet file. Uses PLAIN encoding, UNCOMPRESSED.',
   })
 }
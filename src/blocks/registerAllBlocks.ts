@@ -29,7 +29,9 @@ import { registerIntervalBlocks } from './interval-blocks'
 import { registerSignalBlocks } from './signal-blocks'
 import { registerComplexBlocks } from './complex-blocks'
 import { registerMatrixBlocks } from './matrix-blocks'
+import { registerSparseBlocks } from './sparse-blocks'
 import { registerNumericalBlocks } from './numerical-blocks'
+import { registerAcausalBlocks } from './acausal-blocks'
 import { registerOptimBlocks } from './optim-blocks'
 import { registerMLBlocks } from './ml-blocks'
 import { registerNNBlocks } from './nn-blocks'
@@ -94,8 +96,10 @@ export function registerAllBlocks(): void {
   registerSignalBlocks(reg)
   registerComplexBlocks(reg)
   registerMatrixBlocks(reg)
+  registerSparseBlocks(reg)
   registerLookupBlocks(reg)
   registerNumericalBlocks(reg)
+  registerAcausalBlocks(reg)
   registerOptimBlocks(reg)
   registerMLBlocks(reg)
   registerNNBlocks(reg)
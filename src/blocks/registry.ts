@@ -1021,7 +1021,6 @@ export const UI_ONLY_BLOCKS: ReadonlySet<string> = new Set([
   'unitInput',
   'transferFunction',
   'stateSpace',
-  'ctrl.zoh',
   'ctrl.rateTransition',
   'stateMachine',
   'codeBlock',
@@ -1166,6 +1165,7 @@ reg({
   inputs: [
     { id: 'equations', label: 'Equations (text)' },
     { id: 'y0', label: 'Initial state' },
+    { id: 't_eval', label: 'Output times (optional)' },
   ],
   defaultData: { blockType: 'ode.rk4', label: 'ODE RK4', t_end: 1.0, dt: 0.01 },
   proOnly: true,
@@ -1183,11 +1183,12 @@ reg({
   inputs: [
     { id: 'equations', label: 'Equations (text)' },
     { id: 'y0', label: 'Initial state' },
+    { id: 't_eval', label: 'Output times (optional)' },
   ],
   defaultData: { blockType: 'ode.rk45', label: 'ODE RK45', t_end: 1.0, dt: 0.1, tolerance: 1e-6 },
   proOnly: true,
   description:
-    'Solve a system of ODEs using the Dormand-Prince RK4(5) adaptive-step method. Automatically adjusts step size for accuracy.',
+    'Solve a system of ODEs using the Dormand-Prince RK4(5) adaptive-step method. Automatically adjusts step size for accuracy. Connect t_eval (or set output_interval) to get rows at exactly those times from the dense-output interpolant.',
   synonyms: ['rk45', 'dormand prince', 'adaptive ode', 'variable step'],
   tags: ['ode', 'numerical'],
 })
@@ -1200,20 +1201,23 @@ reg({
   inputs: [
     { id: 'equations', label: 'Equations (text)' },
     { id: 'y0', label: 'Initial state' },
+    { id: 't_eval', label: 'Output times (optional)' },
   ],
   defaultData: {
     blockType: 'ode.event',
     label: 'ODE Event',
     t_end: 10.0,
     dt: 0.01,
+    method: 'rk4',
     event_expr: 'y0',
-    terminate: true,
+    direction: 'both',
+    reset: '',
     event_tol: 1e-8,
   },
   proOnly: true,
   description:
-    'Solve ODEs with zero-crossing event detection (RK4 + bisection refinement). Set event_expr to g(y,t); integration stops (or records) when g crosses 0. Event time located to within event_tol.',
-  synonyms: ['event detection', 'zero crossing', 'ode event', 'impact'],
+    'Solve hybrid ODEs with zero-crossing events (method: rk4, rk45, bdf or radau). Set event_expr to g(y,t) and direction to rising, falling or both; the crossing is located on the dense-output interpolant to within event_tol. An event stops the run unless it resets states (reset: "y1 = -0.8*y1") or switches to another equation set (events list with switch_to, extra sets in modes); terminate overrides.',
+  synonyms: ['event detection', 'zero crossing', 'ode event', 'impact', 'hybrid system', 'bouncing ball', 'state reset'],
   tags: ['ode', 'numerical', 'event'],
 })
 
@@ -1255,14 +1259,96 @@ reg({
     t_end: 10.0,
     dt: 0.01,
     method: 'verlet',
+    adaptive: false,
+    hamiltonian: '',
+    kinetic: '',
+    potential: '',
+    angular_dim: 0,
   },
   proOnly: true,
   description:
-    'Solve a Hamiltonian (conservative) system using a symplectic integrator. Provide N acceleration expressions a_i(q,v,t) and initial state [q0..qN-1, v0..vN-1]. Method: "verlet" (Störmer-Verlet, 2nd order) or "euler" (Symplectic Euler, 1st order). No secular energy drift.',
-  synonyms: ['verlet', 'symplectic', 'hamiltonian', 'energy conserving', 'leapfrog'],
+    'Solve a Hamiltonian (conservative) system using a symplectic integrator. Provide N acceleration expressions a_i(q,v,t), a Hamiltonian H(q,p), or separable kinetic T(p) + potential V(q), with initial state [q0..qN-1, p0..pN-1]. Method: "verlet" (2nd order), "euler" (1st), "yoshida4", "forest_ruth" (4th) or "yoshida6" (6th). adaptive: time-reversible step control. Adds an energy_drift column; set angular_dim to 2 or 3 to add angular_momentum_drift.',
+  synonyms: ['verlet', 'symplectic', 'hamiltonian', 'energy conserving', 'leapfrog', 'yoshida', 'forest ruth', 'orbit', 'kepler'],
   tags: ['ode', 'numerical', 'symplectic'],
 })
 
+reg({
+  type: 'ode.dde',
+  label: 'Delay ODE Solver (DDE)',
+  category: 'odeSolvers',
+  nodeKind: 'csOperation',
+  inputs: [
+    { id: 'equations', label: 'Equations (text)' },
+    { id: 'y0', label: 'Initial state' },
+    { id: 't_eval', label: 'Output times (optional)' },
+  ],
+  defaultData: {
+    blockType: 'ode.dde',
+    label: 'DDE',
+    t_end: 10.0,
+    dt: 0.01,
+    tolerance: 1e-6,
+    history: '',
+  },
+  proOnly: true,
+  description:
+    'Solve delay differential equations. Lagged states are written as calls, e.g. "-k*y0(t - tau)" (constant delay) or "y0(t - 1 - y0^2)" (state-dependent). history gives y_i(t) before t_start (semicolon-separated expressions in t; default constant y0). Adaptive Dormand-Prince with dense output for lagged values and breakpoint tracking. Returns Table [t, y0..yN].',
+  synonyms: ['dde', 'delay', 'time delay', 'transport delay', 'dead time', 'retarded'],
+  tags: ['ode', 'numerical', 'delay'],
+})
+
+reg({
+  type: 'ode.sde',
+  label: 'SDE Solver',
+  category: 'odeSolvers',
+  nodeKind: 'csOperation',
+  inputs: [
+    { id: 'drift', label: 'Drift (text)' },
+    { id: 'diffusion', label: 'Diffusion (text)' },
+    { id: 'y0', label: 'Initial state' },
+  ],
+  defaultData: {
+    blockType: 'ode.sde',
+    label: 'SDE',
+    t_end: 1.0,
+    dt: 0.01,
+    scheme: 'euler',
+    seed: 42,
+  },
+  proOnly: true,
+  description:
+    'Simulate one sample path of an Ito SDE dy_i = f_i dt + g_i dW_i with diagonal noise. drift and diffusion are semicolon-separated expressions in t, y0..yN and params. scheme: euler (Euler-Maruyama), milstein, or sra (Roessler SRA1, additive noise only). The same seed reproduces the same Brownian path. Returns Table [t, y0..yN].',
+  synonyms: ['sde', 'stochastic', 'euler maruyama', 'milstein', 'brownian motion', 'wiener process', 'ornstein uhlenbeck'],
+  tags: ['ode', 'numerical', 'stochastic'],
+})
+
+reg({
+  type: 'ode.sdeEnsemble',
+  label: 'SDE Ensemble',
+  category: 'odeSolvers',
+  nodeKind: 'csOperation',
+  inputs: [
+    { id: 'drift', label: 'Drift (text)' },
+    { id: 'diffusion', label: 'Diffusion (text)' },
+    { id: 'y0', label: 'Initial state' },
+  ],
+  defaultData: {
+    blockType: 'ode.sdeEnsemble',
+    label: 'SDE Ensemble',
+    t_end: 1.0,
+    dt: 0.01,
+    scheme: 'euler',
+    seed: 42,
+    paths: 500,
+    quantiles: [0.05, 0.5, 0.95],
+  },
+  proOnly: true,
+  description:
+    'Run many seeded sample paths of an SDE (see SDE Solver) and summarise them per time point. Returns Table [t, then for each state y_mean, y_var and one y_pXX column per quantile].',
+  synonyms: ['sde ensemble', 'stochastic simulation', 'sample paths', 'confidence band', 'degradation model', 'ornstein uhlenbeck'],
+  tags: ['ode', 'numerical', 'stochastic', 'statistics'],
+})
+
 reg({
   type: 'ode.dae',
   label: 'DAE Solver',
@@ -1318,6 +1404,7 @@ reg({
   inputs: [
     { id: 'equations', label: 'Equations (text)' },
     { id: 'y0', label: 'Initial state' },
+    { id: 't_eval', label: 'Output times (optional)' },
   ],
   defaultData: {
     blockType: 'ode.bdf',
@@ -1342,6 +1429,7 @@ reg({
   inputs: [
     { id: 'equations', label: 'Equations (text)' },
     { id: 'y0', label: 'Initial state' },
+    { id: 't_eval', label: 'Output times (optional)' },
   ],
   defaultData: {
     blockType: 'ode.radau',
@@ -1379,12 +1467,13 @@ reg({
     t_end: 1.0,
     dt: 0.001,
     n_snapshots: 10,
+    method: 'rk4',
   },
   proOnly: true,
   synonyms: ['pde', '1d', 'method of lines', 'heat equation', 'diffusion', 'advection', 'wave'],
   tags: ['pde', 'heat', 'diffusion', 'advection', 'wave', 'mol'],
   description:
-    'Solve a 1D PDE using the Method of Lines (MOL). Supports heat/diffusion (u_t = D·u_xx), advection (u_t = -c·u_x), advection-diffusion, and wave (u_tt = c²·u_xx). Outputs table of snapshots: columns t, x0..xN.',
+    'Solve a 1D PDE using the Method of Lines (MOL). Supports heat/diffusion (u_t = D·u_xx), advection (u_t = -c·u_x), advection-diffusion, and wave (u_tt = c²·u_xx). Time stepping: explicit RK4 (method: rk4) or implicit BDF with a sparse Jacobian for fine grids (method: bdf). Outputs table of snapshots: columns t, x0..xN.',
 })
 
 // ── Vehicle Simulation (Phase 5) ────────────────────────────────────────────
@@ -1914,6 +2003,28 @@ reg({
   tags: ['machine learning', 'classification'],
 })
 
+// ── Graph-level user functions ──────────────────────────────────────────────
+
+reg({
+  type: 'expr.define',
+  label: 'Define Function',
+  category: 'customFunctions',
+  nodeKind: 'csSource',
+  inputs: [],
+  defaultData: {
+    blockType: 'expr.define',
+    label: 'Define Function',
+    name: 'f',
+    params: ['x'],
+    body: 'x^2',
+  },
+  description:
+    'Define a function once per graph, e.g. f(x, y) = x^2 + y. Every expression in the graph ' +
+    '(custom functions, ODE equations, optimizer objectives) can call it. Outputs its signature.',
+  synonyms: ['user function', 'define', 'lambda', 'macro', 'helper function'],
+  tags: ['custom', 'expression'],
+})
+
 // ── H5-1: Custom function block dynamic registration ────────────────────────
 
 import type { CustomFunction } from '../lib/customFunctions'
@@ -0,0 +1,179 @@
+/**
+ * sparse-blocks.ts — Sparse matrix block pack.
+ *
+ * 9 blocks: sparse.fromMatrix, sparse.fromTable, sparse.toMatrix, sparse.toTable,
+ * sparse.multiply, sparse.solve, sparse.stats, sparse.import, sparse.export.
+ * They pass `sparseMatrix` values (CSR) between ports, so large operators are
+ * never densified. matrix_* blocks also accept sparse inputs: transpose, trace,
+ * multiply and solve stay sparse, the rest densify.
+ *
+ * sparse.import reads Matrix Market (.mtx) or Harwell–Boeing text from the blob
+ * named by data.blobRef (see engine.registerBlob), data.fileBytes, or pasted
+ * data.text. sparse.export outputs the file as a Bytes value.
+ * Evaluation handled by Rust/WASM engine ops (engine-core/src/sparse*.rs).
+ */
+
+import type { BlockDef } from './types'
+
+export function registerSparseBlocks(register: (def: BlockDef) => void): void {
+  register({
+    type: 'sparse.fromMatrix',
+    label: 'Matrix to Sparse',
+    category: 'matrix',
+    nodeKind: 'csOperation',
+    inputs: [{ id: 'matrix', label: 'Matrix' }],
+    defaultData: { blockType: 'sparse.fromMatrix', label: 'Matrix to Sparse', threshold: 0 },
+    description:
+      'Convert a dense matrix to a sparse (CSR) matrix, dropping entries with |aᵢⱼ| ≤ threshold.',
+    synonyms: ['sparse', 'csr', 'compress matrix', 'to sparse'],
+    tags: ['matrix', 'sparse', 'linear algebra'],
+  })
+
+  register({
+    type: 'sparse.fromTable',
+    label: 'Triplets to Sparse',
+    category: 'matrix',
+    nodeKind: 'csOperation',
+    inputs: [{ id: 'table', label: 'Table (row, col, value)' }],
+    defaultData: { blockType: 'sparse.fromTable', label: 'Triplets to Sparse', rows: 0, cols: 0 },
+    description:
+      'Build a sparse matrix from a table of 0-based (row, col, value) triplets. Duplicates are summed; ' +
+      'rows/cols of 0 size the matrix from the largest index.',
+    synonyms: ['triplets', 'coo', 'coordinate format', 'assemble sparse'],
+    tags: ['matrix', 'sparse', 'linear algebra'],
+  })
+
+  register({
+    type: 'sparse.toMatrix',
+    label: 'Sparse to Matrix',
+    category: 'matrix',
+    nodeKind: 'csOperation',
+    inputs: [{ id: 'matrix', label: 'Sparse matrix' }],
+    defaultData: { blockType: 'sparse.toMatrix', label: 'Sparse to Matrix' },
+    description: 'Expand a sparse matrix into a dense matrix.',
+    synonyms: ['densify', 'to dense', 'full matrix'],
+    tags: ['matrix', 'sparse', 'linear algebra'],
+  })
+
+  register({
+    type: 'sparse.toTable',
+    label: 'Sparse to Triplets',
+    category: 'matrix',
+    nodeKind: 'csOperation',
+    inputs: [{ id: 'matrix', label: 'Sparse matrix' }],
+    defaultData: { blockType: 'sparse.toTable', label: 'Sparse to Triplets' },
+    description:
+      'List the stored entries of a sparse matrix as a (row, col, value) table — plot row against col for a spy plot.',
+    synonyms: ['triplets', 'spy plot', 'nonzeros', 'coo'],
+    tags: ['matrix', 'sparse', 'linear algebra'],
+  })
+
+  register({
+    type: 'sparse.multiply',
+    label: 'Sparse Multiply',
+    category: 'matrix',
+    nodeKind: 'csOperation',
+    inputs: [
+      { id: 'a', label: 'A (sparse)' },
+      { id: 'b', label: 'b (vector / matrix)' },
+    ],
+    defaultData: { blockType: 'sparse.multiply', label: 'Sparse Multiply' },
+    description:
+      'Sparse product A·b: a vector gives a vector, a dense matrix a dense matrix, a sparse matrix a sparse matrix.',
+    synonyms: ['spmv', 'spgemm', 'sparse product', 'sparse matmul'],
+    tags: ['matrix', 'sparse', 'linear algebra'],
+  })
+
+  register({
+    type: 'sparse.solve',
+    label: 'Sparse Solve Ax = b',
+    category: 'matrix',
+    nodeKind: 'csOperation',
+    inputs: [
+      { id: 'a', label: 'A (sparse)' },
+      { id: 'b', label: 'b (vector)' },
+    ],
+    defaultData: {
+      blockType: 'sparse.solve',
+      label: 'Sparse Solve Ax = b',
+      /** auto | cholesky | lu | cg | bicgstab | gmres */
+      method: 'auto',
+      /** Fill-reducing ordering for the direct methods: amd | nd | natural */
+      ordering: 'amd',
+      tol: 1e-10,
+      maxIter: 1000,
+    },
+    description:
+      'Solve a sparse linear system. auto uses sparse Cholesky for SPD matrices and LU with partial pivoting otherwise; ' +
+      'cg, bicgstab and gmres are ILU(0)-preconditioned iterative solvers.',
+    synonyms: ['sparse solver', 'sparse cholesky', 'sparse lu', 'conjugate gradient', 'gmres'],
+    tags: ['matrix', 'sparse', 'linear algebra', 'solver'],
+  })
+
+  register({
+    type: 'sparse.stats',
+    label: 'Sparsity Pattern Stats',
+    category: 'matrix',
+    nodeKind: 'csOperation',
+    inputs: [{ id: 'matrix', label: 'Sparse matrix' }],
+    defaultData: { blockType: 'sparse.stats', label: 'Sparsity Pattern Stats' },
+    description:
+      'Structure of a sparse matrix as a one-row table: nnz, density, lower/upper bandwidth, row nnz range, ' +
+      'missing diagonals, symmetry and diagonal dominance.',
+    synonyms: ['spy', 'sparsity pattern', 'bandwidth', 'nnz', 'density'],
+    tags: ['matrix', 'sparse', 'linear algebra'],
+  })
+
+  register({
+    type: 'sparse.import',
+    label: 'Sparse Matrix Import',
+    category: 'matrix',
+    nodeKind: 'csSource',
+    inputs: [],
+    defaultData: {
+      blockType: 'sparse.import',
+      label: 'Sparse Matrix Import',
+      /** auto | mtx | hb */
+      format: 'auto',
+      /** Id of the binary dataset holding the file (see registerBlob). */
+      blobRef: '',
+      /** Pasted file contents, used when no file is attached. */
+      text: '',
+      /** Original file name for display. */
+      fileName: '',
+    },
+    description:
+      'Import a sparse matrix from a Matrix Market (.mtx) or Harwell–Boeing (.rua/.rsa) file. ' +
+      'Symmetric and skew-symmetric storage is expanded; complex matrices are not supported.',
+    synonyms: [
+      'matrix market',
+      '.mtx',
+      'harwell-boeing',
+      'rutherford-boeing',
+      'import sparse',
+      'suitesparse',
+    ],
+    tags: ['matrix', 'sparse', 'import', 'file'],
+  })
+
+  register({
+    type: 'sparse.export',
+    label: 'Sparse Matrix Export',
+    category: 'matrix',
+    nodeKind: 'csOperation',
+    inputs: [{ id: 'matrix', label: 'Sparse matrix' }],
+    defaultData: {
+      blockType: 'sparse.export',
+      label: 'Sparse Matrix Export',
+      /** mtx | hb */
+      format: 'mtx',
+      /** Store only the lower triangle (matrix must be symmetric). */
+      symmetric: false,
+    },
+    description:
+      'Export a sparse (or dense) matrix as a Matrix Market coordinate file or an assembled Harwell–Boeing file. ' +
+      'Outputs a Bytes value the UI downloads.',
+    synonyms: ['matrix market', '.mtx', 'harwell-boeing', 'export sparse', 'save sparse'],
+    tags: ['matrix', 'sparse', 'export', 'file'],
+  })
+}
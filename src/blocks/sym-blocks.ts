@@ -2,8 +2,10 @@
  * sym-blocks.ts — Symbolic mathematics (CAS) block pack.
  *
  * Blocks for symbolic differentiation, integration, simplification,
- * expansion, and substitution. Results are returned as LaTeX strings.
- * Backed by the Rust CAS in engine-core/src/symbolic.rs.
+ * expansion, and substitution (returned as LaTeX strings), plus equation
+ * solving, series, limits, matrices, Jacobians and Lagrangian mechanics
+ * (returned as JSON with LaTeX and expressions) and code generation.
+ * Backed by the Rust CAS in engine-core/src/symbolic*.rs.
  */
 
 import type { BlockDef } from './types'
@@ -84,6 +86,137 @@ export function registerSymBlocks(register: (def: BlockDef) => void): void {
       'Substitutes a numeric value for a variable in a symbolic expression. Returns simplified LaTeX string.',
   })
 
+  register({
+    type: 'sym.solve',
+    label: 'Solve',
+    category: 'math',
+    nodeKind: 'csOperation',
+    inputs: [
+      { id: 'expr', label: 'Equation(s) (text)' },
+      { id: 'var', label: 'Variable(s) (text)' },
+    ],
+    defaultData: { blockType: 'sym.solve', label: 'Solve', var: 'x' },
+    synonyms: ['solve', 'roots', 'solve equation', 'linear system', 'quadratic formula'],
+    tags: ['sym', 'algebra', 'equation'],
+    description:
+      'Solves equations symbolically: polynomials in closed form up to quartics, rational and simple transcendental equations, or ";"-separated linear systems for comma-separated variables. Returns JSON with LaTeX, solution expressions and values.',
+  })
+
+  register({
+    type: 'sym.series',
+    label: 'Series',
+    category: 'math',
+    nodeKind: 'csOperation',
+    inputs: [
+      { id: 'expr', label: 'Expression (text)' },
+      { id: 'var', label: 'Variable (text)' },
+      { id: 'x0', label: 'Expansion point' },
+    ],
+    defaultData: { blockType: 'sym.series', label: 'Series', var: 'x', x0: 0, order: 5 },
+    synonyms: ['series', 'taylor series', 'maclaurin', 'laurent series', 'expansion'],
+    tags: ['sym', 'calculus', 'series'],
+    description:
+      'Taylor (or Laurent) expansion of an expression about x0 up to the given order. Returns JSON with LaTeX, the truncated series expression and its coefficients.',
+  })
+
+  register({
+    type: 'sym.limit',
+    label: 'Limit',
+    category: 'math',
+    nodeKind: 'csOperation',
+    inputs: [
+      { id: 'expr', label: 'Expression (text)' },
+      { id: 'var', label: 'Variable (text)' },
+      { id: 'x0', label: 'Limit point' },
+    ],
+    defaultData: { blockType: 'sym.limit', label: 'Limit', var: 'x', x0: 0 },
+    synonyms: ['limit', 'lim', "l'hopital", 'limit at infinity'],
+    tags: ['sym', 'calculus', 'limit'],
+    description:
+      'Limit of an expression as var → x0 (x0 may be "inf" or "-inf") via series expansion or L\'Hôpital\'s rule. Returns JSON with LaTeX, the limit expression and its value.',
+  })
+
+  register({
+    type: 'sym.jacobian',
+    label: 'Jacobian',
+    category: 'math',
+    nodeKind: 'csOperation',
+    inputs: [
+      { id: 'expr', label: 'Expressions (text)' },
+      { id: 'vars', label: 'Variables (text)' },
+    ],
+    defaultData: { blockType: 'sym.jacobian', label: 'Jacobian', mode: 'jacobian' },
+    synonyms: ['jacobian', 'hessian', 'gradient', 'symbolic jacobian', 'partial derivatives'],
+    tags: ['sym', 'calculus', 'matrix'],
+    description:
+      'Symbolic Jacobian of semicolon-separated expressions, or gradient/Hessian of one expression (mode). Variables default to the free symbols. Returns JSON with LaTeX and entries.',
+  })
+
+  register({
+    type: 'sym.matrix',
+    label: 'Symbolic Matrix',
+    category: 'math',
+    nodeKind: 'csOperation',
+    inputs: [
+      { id: 'a', label: 'Matrix A (text)' },
+      { id: 'b', label: 'Matrix B (text)' },
+    ],
+    defaultData: { blockType: 'sym.matrix', label: 'Symbolic Matrix', op: 'det' },
+    synonyms: ['symbolic matrix', 'determinant', 'symbolic inverse', 'adjugate', 'matrix algebra'],
+    tags: ['sym', 'matrix', 'linear algebra'],
+    description:
+      'Matrices of expressions written as [a, b; c, d]. Ops: det, inverse (up to 6×6), transpose, multiply, add, subtract. Returns JSON with LaTeX and entries (or the determinant).',
+  })
+
+  register({
+    type: 'sym.codegen',
+    label: 'Code Generator',
+    category: 'math',
+    nodeKind: 'csOperation',
+    inputs: [
+      { id: 'expr', label: 'Outputs (text)' },
+      { id: 'vars', label: 'Inputs (text)' },
+    ],
+    defaultData: {
+      blockType: 'sym.codegen',
+      label: 'Code Generator',
+      target: 'rust',
+      functionName: 'generated',
+    },
+    synonyms: ['code generation', 'codegen', 'export c', 'export rust', 'cse'],
+    tags: ['sym', 'codegen', 'export'],
+    description:
+      'Emits Rust, C or expr source for "name = expression" outputs (semicolon-separated), computing shared subexpressions once as temporaries. Inputs default to the free symbols.',
+  })
+
+  register({
+    type: 'sym.lagrange',
+    label: 'Lagrangian Mechanics',
+    category: 'math',
+    nodeKind: 'csOperation',
+    inputs: [
+      { id: 'T', label: 'Kinetic energy (text)' },
+      { id: 'V', label: 'Potential energy (text)' },
+      { id: 'coords', label: 'Coordinates (text)' },
+    ],
+    defaultData: {
+      blockType: 'sym.lagrange',
+      label: 'Lagrangian Mechanics',
+      coords: 'q',
+      time: 't',
+    },
+    synonyms: [
+      'lagrangian',
+      'euler-lagrange',
+      'equations of motion',
+      'lagrange',
+      'analytical mechanics',
+    ],
+    tags: ['sym', 'mechanics', 'dynamics'],
+    description:
+      'Derives equations of motion from L = T − V. Velocities are written q_dot. Returns JSON with the accelerations, mass matrix and an odeEquations string ready for the ODE solver blocks.',
+  })
+
   register({
     type: 'sym.compiledEval',
     label: 'Compiled Eval',
@@ -251,6 +384,7 @@ export function registerSymBlocks(register: (def: BlockDef) => void): void {
       variables: 'x,y',
       order: 'grevlex',
       mode: 'basis',
+      arithmetic: 'exact',
       max_iter: 10000,
     },
     synonyms: [
@@ -263,6 +397,6 @@ export function registerSymBlocks(register: (def: BlockDef) => void): void {
     ],
     tags: ['sym', 'algebra', 'polynomial', 'groebner'],
     description:
-      'Gröbner basis (Buchberger\'s algorithm): computes a canonical basis for a polynomial ideal. Input: semicolon-separated polynomial expressions (e.g. "x^2+y-1;x+y^2-1"), variables field (comma-separated). order: "grevlex" (default), "lex", "grlex". mode: "basis" (return basis polynomials) or "solve" (find solutions for zero-dimensional ideals). Returns Text.',
+      'Gröbner basis (Buchberger\'s algorithm): computes a canonical basis for a polynomial ideal. Input: semicolon-separated polynomial expressions (e.g. "x^2+y-1;x+y^2-1"), variables field (comma-separated). order: "grevlex" (default), "lex", "grlex". mode: "basis" (return basis polynomials) or "solve" (find solutions for zero-dimensional ideals). arithmetic: "exact" (default, rational coefficients) or "float". Returns Text.',
   })
 }
@@ -5,11 +5,11 @@
  *  - ZOH: shows sample period, held value, timing diagram sketch
  *  - RateTransition: shows input/output rates, ratio, interpolation mode
  *
- * In reactive eval mode, the block reads the upstream value via useComputedValue
- * and passes it through as 'number'. Full hold semantics require the simulation
- * worker infrastructure (Category 8).
+ * ZOH is evaluated by the engine's ctrl.zoh op (sample-and-hold over the
+ * simulation clock); the node mirrors the engine output into heldValue.
+ * RateTransition still passes its input through as 'number' in reactive mode.
  *
- * Bridge: ctrl.zoh → 'number', ctrl.rateTransition → 'number'.
+ * Bridge: ctrl.rateTransition → 'number'.
  */
 
 import { memo, createElement, useMemo, useEffect } from 'react'
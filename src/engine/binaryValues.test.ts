@@ -0,0 +1,51 @@
+import { describe, it, expect } from 'vitest'
+import { binaryToEngineValue, fetchBinaryRefs } from './binaryValues.ts'
+
+describe('fetchBinaryRefs', () => {
+  it('reads each ref with the matching reader and collects buffers', () => {
+    const f64 = new Float64Array([1, 2, 3, 4])
+    const bytes = new Uint8Array([80, 65, 82, 49])
+    const { arrays, transfer } = fetchBinaryRefs(
+      {
+        m: { kind: 'matrix', rows: 2, cols: 2 },
+        b: { kind: 'bytes', length: 4, mime: 'application/vnd.apache.parquet' },
+        gone: { kind: 'vector', length: 10 },
+      },
+      (id) => (id === 'm' ? f64 : undefined),
+      (id) => (id === 'b' ? bytes : undefined),
+    )
+    expect(arrays.m).toEqual({ kind: 'matrix', rows: 2, cols: 2, data: f64 })
+    expect(arrays.b).toEqual({ kind: 'bytes', mime: 'application/vnd.apache.parquet', data: bytes })
+    expect(arrays.gone).toBeUndefined()
+    expect(transfer).toHaveLength(2)
+  })
+})
+
+describe('binaryToEngineValue', () => {
+  it('rebuilds vectors and matrices as plain arrays', () => {
+    expect(binaryToEngineValue({ kind: 'vector', data: new Float64Array([1, 2]) })).toEqual({
+      kind: 'vector',
+      value: [1, 2],
+    })
+    expect(
+      binaryToEngineValue({ kind: 'matrix', rows: 1, cols: 2, data: new Float64Array([3, 4]) }),
+    ).toEqual({ kind: 'matrix', rows: 1, cols: 2, data: [3, 4] })
+  })
+
+  it('splits flattened table data into rows', () => {
+    const v = binaryToEngineValue({
+      kind: 'table',
+      columns: ['x', 'y'],
+      data: new Float64Array([1, 2, 3, 4, 5, 6]),
+    })
+    expect(v).toEqual({
+      kind: 'table',
+      columns: ['x', 'y'],
+      rows: [
+        [1, 2],
+        [3, 4],
+        [5, 6],
+      ],
+    })
+  })
+})
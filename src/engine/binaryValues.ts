@@ -0,0 +1,61 @@
+/**
+ * Binary value transport helpers (engine-wasm `binaryThreshold`).
+ *
+ * With `EvalOptions.binaryThreshold` set, the WASM engine leaves large
+ * vector / matrix / table / bytes values out of its JSON result and lists
+ * them under `binaryRefs`. The worker copies each payload out of WASM memory
+ * as a typed array (`value_f64` / `value_bytes`), posts it as a Transferable,
+ * and the main thread turns it back into an `EngineValue`.
+ */
+
+import type { BinaryArrayValue, BinaryRef, EngineValue } from './wasm-types.ts'
+
+/**
+ * Resolve `binaryRefs` into typed arrays using the WASM readers.
+ * Returns the arrays plus the buffers to transfer to the main thread.
+ */
+export function fetchBinaryRefs(
+  refs: Record<string, BinaryRef>,
+  readF64: (nodeId: string) => Float64Array | undefined,
+  readBytes: (nodeId: string) => Uint8Array | undefined,
+): { arrays: Record<string, BinaryArrayValue>; transfer: ArrayBuffer[] } {
+  const arrays: Record<string, BinaryArrayValue> = {}
+  const transfer: ArrayBuffer[] = []
+  for (const [nodeId, ref] of Object.entries(refs)) {
+    if (ref.kind === 'bytes') {
+      const data = readBytes(nodeId)
+      if (!data) continue
+      arrays[nodeId] = { kind: 'bytes', mime: ref.mime, data }
+      transfer.push(data.buffer as ArrayBuffer)
+      continue
+    }
+    const data = readF64(nodeId)
+    if (!data) continue
+    if (ref.kind === 'vector') arrays[nodeId] = { kind: 'vector', data }
+    else if (ref.kind === 'matrix')
+      arrays[nodeId] = { kind: 'matrix', rows: ref.rows, cols: ref.cols, data }
+    else arrays[nodeId] = { kind: 'table', columns: ref.columns, data }
+    transfer.push(data.buffer as ArrayBuffer)
+  }
+  return { arrays, transfer }
+}
+
+/** Convert a typed-array payload back into the JSON-shaped `EngineValue`. */
+export function binaryToEngineValue(v: BinaryArrayValue): EngineValue {
+  switch (v.kind) {
+    case 'vector':
+      return { kind: 'vector', value: Array.from(v.data) }
+    case 'matrix':
+      return { kind: 'matrix', rows: v.rows, cols: v.cols, data: Array.from(v.data) }
+    case 'table': {
+      const width = v.columns.length
+      const rows: number[][] = []
+      for (let i = 0; width > 0 && i < v.data.length; i += width) {
+        rows.push(Array.from(v.data.subarray(i, i + width)))
+      }
+      return { kind: 'table', columns: v.columns, rows }
+    }
+    case 'bytes':
+      return { kind: 'bytes', mime: v.mime, data: v.data }
+  }
+}
@@ -144,15 +144,13 @@ export function toEngineSnapshot(
                                                   : // 2.71: stateMachine outputs current state index as 'number'.
                                                     data.blockType === 'stateMachine'
                                                     ? 'number'
-                                                    : // 2.70: ctrl.zoh and ctrl.rateTransition pass through input as 'number' in reactive mode.
-                                                      data.blockType === 'ctrl.zoh'
+                                                    : // 2.70: ctrl.rateTransition passes through input as 'number' in reactive mode.
+                                                      data.blockType === 'ctrl.rateTransition'
                                                       ? 'number'
-                                                      : data.blockType === 'ctrl.rateTransition'
-                                                        ? 'number'
-                                                        : // 6.16: viewport3d renders in the UI; engine sees as 'display'.
-                                                          data.blockType === 'viewport3d'
-                                                          ? 'display'
-                                                          : data.blockType
+                                                      : // 6.16: viewport3d renders in the UI; engine sees as 'display'.
+                                                        data.blockType === 'viewport3d'
+                                                        ? 'display'
+                                                        : data.blockType
       ) as string
       if (blockType === 'constant') {
         const constId = data.selectedConstantId
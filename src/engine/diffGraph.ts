@@ -27,7 +27,6 @@ const UI_ONLY_BLOCK_SET = new Set([
   'unitInput',
   'transferFunction',
   'stateSpace',
-  'ctrl.zoh',
   'ctrl.rateTransition',
   'stateMachine',
   'codeBlock',
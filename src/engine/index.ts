@@ -28,6 +28,7 @@ import type {
   WorkerRequest,
   WorkerResponse,
 } from './wasm-types.ts'
+import { binaryToEngineValue } from './binaryValues.ts'
 
 // ── ENG-03: Binary result decoding ───────────────────────────────────────────
 
@@ -36,10 +37,13 @@ function decodeBinaryFullResult(
   msg: Extract<WorkerResponse, { type: 'result-binary' }>,
 ): EngineEvalResult {
   const values: EngineEvalResult['values'] = { ...msg.scalars.nonScalars }
-  const { nodeIds, scalars } = msg.scalars
+  const { nodeIds, scalars, arrays } = msg.scalars
   for (let i = 0; i < nodeIds.length; i++) {
     values[nodeIds[i]] = { kind: 'scalar', value: scalars[i] }
   }
+  for (const [nodeId, v] of Object.entries(arrays ?? {})) {
+    values[nodeId] = binaryToEngineValue(v)
+  }
   return {
     values,
     diagnostics: msg.diagnostics,
@@ -53,10 +57,13 @@ function decodeBinaryIncrementalResult(
   msg: Extract<WorkerResponse, { type: 'incremental-binary' }>,
 ): IncrementalEvalResult {
   const changedValues: IncrementalEvalResult['changedValues'] = { ...msg.scalars.nonScalars }
-  const { nodeIds, scalars } = msg.scalars
+  const { nodeIds, scalars, arrays } = msg.scalars
   for (let i = 0; i < nodeIds.length; i++) {
     changedValues[nodeIds[i]] = { kind: 'scalar', value: scalars[i] }
   }
+  for (const [nodeId, v] of Object.entries(arrays ?? {})) {
+    changedValues[nodeId] = binaryToEngineValue(v)
+  }
   return {
     changedValues,
     diagnostics: msg.diagnostics,
@@ -101,6 +108,13 @@ export interface EngineAPI {
   registerDataset(id: string, data: Float64Array): void
   /** Release a previously registered dataset. */
   releaseDataset(id: string): void
+  /**
+   * Register raw file bytes (e.g. a .parquet or .mat file) as a binary dataset.
+   * Import blocks reference it via `data.blobRef`. Fire-and-forget.
+   */
+  registerBlob(id: string, bytes: Uint8Array): void
+  /** Release a previously registered binary dataset. */
+  releaseBlob(id: string): void
   /** Ops catalog received from the WASM engine on startup. */
   readonly catalog: CatalogEntry[]
   /** Pre-computed constant values for zero-input source blocks (W12.2). */
@@ -537,6 +551,21 @@ export async function createEngine(factory?: WorkerFactory): Promise<EngineAPI>
       worker.postMessage({ type: 'releaseDataset', datasetId: id } satisfies WorkerRequest)
     },
 
+    registerBlob(id, bytes) {
+      const buffer = bytes.buffer.slice(
+        bytes.byteOffset,
+        bytes.byteOffset + bytes.byteLength,
+      ) as ArrayBuffer
+      worker.postMessage(
+        { type: 'registerBlob', datasetId: id, buffer } satisfies WorkerRequest,
+        [buffer],
+      )
+    },
+
+    releaseBlob(id) {
+      worker.postMessage({ type: 'releaseBlob', datasetId: id } satisfies WorkerRequest)
+    },
+
     catalog,
     constantValues,
     engineVersion,
@@ -45,6 +45,7 @@ export function getValueTypeColor(v: Value | undefined): string {
       return 'var(--value-color-vector)'
     case 'table':
     case 'matrix':
+    case 'sparseMatrix':
       return 'var(--value-color-table)'
     case 'text':
       return 'var(--value-color-text, var(--value-color-any))'
@@ -74,6 +75,7 @@ export function getValueTypeShape(v: Value | undefined): string {
       return '4px' // rounded square
     case 'table':
     case 'matrix':
+    case 'sparseMatrix':
       return '2px' // square
     case 'text':
       return '8px' // wide pill
@@ -105,6 +107,8 @@ export function getValueTypeLabel(v: Value | undefined): string {
       return 'table'
     case 'matrix':
       return 'matrix'
+    case 'sparseMatrix':
+      return 'sparse matrix'
     case 'text':
       return 'text'
     case 'complex':
@@ -36,6 +36,8 @@ export interface EngineEvalResult {
   elapsedUs: number
   trace?: TraceEntry[]
   partial?: boolean
+  /** Values moved out of `values` by `binaryThreshold` (resolved by the worker). */
+  binaryRefs?: Record<string, BinaryRef>
 }
 
 export type EngineValue =
@@ -46,6 +48,24 @@ export type EngineValue =
   | { kind: 'text'; value: string }
   | { kind: 'complex'; re: number; im: number }
   | { kind: 'matrix'; rows: number; cols: number; data: number[] }
+  /** CSR storage: row i's entries are `colIndices`/`values[rowPtrs[i]..rowPtrs[i + 1]]`. */
+  | {
+      kind: 'sparseMatrix'
+      rows: number
+      cols: number
+      rowPtrs: number[]
+      colIndices: number[]
+      values: number[]
+    }
+  /** Exporter output. `data` is base64 from JSON, or a Uint8Array via binary transport. */
+  | { kind: 'bytes'; mime: string; data: string | Uint8Array }
+
+/** Shape of a value left out of the JSON result (see `EvalOptions.binaryThreshold`). */
+export type BinaryRef =
+  | { kind: 'vector'; length: number }
+  | { kind: 'matrix'; rows: number; cols: number }
+  | { kind: 'table'; columns: string[]; rows: number }
+  | { kind: 'bytes'; length: number; mime: string }
 
 export interface EngineDiagnostic {
   nodeId?: string
@@ -94,6 +114,8 @@ export interface IncrementalEvalResult {
   totalCount: number
   trace?: TraceEntry[]
   partial?: boolean
+  /** Values moved out of `changedValues` by `binaryThreshold`. */
+  binaryRefs?: Record<string, BinaryRef>
 }
 
 // ── Eval options (W9.3) ─────────────────────────────────────────
@@ -102,6 +124,12 @@ export interface EvalOptions {
   trace?: boolean
   maxTraceNodes?: number
   timeBudgetMs?: number
+  /**
+   * Vector / matrix / table values with at least this many elements (and all
+   * bytes values) skip JSON and are copied out of WASM as typed arrays.
+   * 0 or unset = everything goes through JSON.
+   */
+  binaryThreshold?: number
 }
 
 // ── Trace types (W9.3) ──────────────────────────────────────────
@@ -152,6 +180,13 @@ export type WorkerRequest =
       buffer: ArrayBuffer | SharedArrayBuffer
     }
   | { type: 'releaseDataset'; datasetId: string }
+  | {
+      type: 'registerBlob'
+      datasetId: string
+      /** Raw file bytes (transferred). */
+      buffer: ArrayBuffer
+    }
+  | { type: 'releaseBlob'; datasetId: string }
   | { type: 'cancel'; requestId: number }
   | { type: 'getStats'; requestId: number }
   | { type: 'validateGraph'; requestId: number }
@@ -181,8 +216,18 @@ export interface BinaryResultScalars {
   scalars: Float64Array
   /** Any non-scalar values (vectors, tables, errors). */
   nonScalars: Record<string, EngineValue>
+  /** Large values resolved from `binaryRefs`; buffers are transferred. */
+  arrays?: Record<string, BinaryArrayValue>
 }
 
+/** A large value carried as a typed array instead of JSON. */
+export type BinaryArrayValue =
+  | { kind: 'vector'; data: Float64Array }
+  | { kind: 'matrix'; rows: number; cols: number; data: Float64Array }
+  /** `data` holds the rows flattened row-major. */
+  | { kind: 'table'; columns: string[]; data: Float64Array }
+  | { kind: 'bytes'; mime: string; data: Uint8Array }
+
 /** Messages sent from worker → main thread. */
 export type WorkerResponse =
   | {
@@ -37,6 +37,24 @@ declare module '@engine-wasm/engine_wasm.js' {
   /** Release (remove) a dataset from the engine graph. */
   export function release_dataset(id: string): void
 
+  /**
+   * Register a binary dataset (raw file bytes) by id.
+   * Import blocks (mat_import, parquet_import) read it via `data.blobRef`.
+   */
+  export function register_blob(id: string, bytes: Uint8Array): void
+
+  /** Release (remove) a binary dataset from the engine graph. */
+  export function release_blob(id: string): void
+
+  /**
+   * Copy a node's cached vector / matrix (row-major) / table (rows flattened)
+   * payload out of WASM memory. Used to resolve `binaryRefs`.
+   */
+  export function value_f64(node_id: string): Float64Array | undefined
+
+  /** Copy a node's cached `bytes` payload out of WASM memory. */
+  export function value_bytes(node_id: string): Uint8Array | undefined
+
   /** Return the ops catalog as a JSON array. */
   export function get_catalog(): string
 
@@ -60,7 +78,8 @@ declare module '@engine-wasm/engine_wasm.js' {
    * @param snapshot_json - JSON-encoded EngineSnapshotV1
    * @param options_json - JSON-encoded EvalOptions
    * @param progress_cb - JS function(evaluated: number, total: number) called per-node
-   * @returns JSON-encoded EvalResult
+   * @returns JSON-encoded EvalResult. With `binaryThreshold` set, large values
+   *   are listed under `binaryRefs` instead of `values`.
    */
   export function load_snapshot_with_options(
     snapshot_json: string,
@@ -73,7 +92,8 @@ declare module '@engine-wasm/engine_wasm.js' {
    * @param patch_json - JSON-encoded PatchOp[]
    * @param options_json - JSON-encoded EvalOptions
    * @param progress_cb - JS function(evaluated: number, total: number) called per-node
-   * @returns JSON-encoded IncrementalEvalResult
+   * @returns JSON-encoded IncrementalEvalResult. With `binaryThreshold` set,
+   *   large values are listed under `binaryRefs` instead of `changedValues`.
    */
   export function apply_patch_with_options(
     patch_json: string,
@@ -82,14 +102,17 @@ declare module '@engine-wasm/engine_wasm.js' {
   ): string
 
   /**
-   * Run a simulation task with iterative progress callbacks (8.2).
+   * Run a discrete-time simulation with iterative progress callbacks (8.2).
    *
    * @param config_json - JSON-encoded SimulationConfig:
-   *   { nodeId, op, snapshot, maxIterations, batchSize?, loop?, loopCount?, convergenceThreshold? }
+   *   { nodeId, op, snapshot, maxIterations, batchSize?, loop?, loopCount?, convergenceThreshold?,
+   *     tStart?, dt?, times?, timeNode?, dtNode?, states?: { node, source, initial? }[],
+   *     record?: string[], convergenceNodes?: string[] }
    * @param progress_cb - Called after every batch with JSON:
-   *   { iteration, totalIterations, cycle, totalCycles, elapsedUs }
+   *   { iteration, totalIterations, cycle, totalCycles, t, elapsedUs }
    * @returns JSON-encoded result:
-   *   { cycles, iterations, elapsedUs, converged, outputs: Record<string, Value> }
+   *   { cycles, iterations, elapsedUs, converged, aborted, outputs: Record<string, Value>,
+   *     table: Value, diagnostics: Diagnostic[] }
    *   or { error: { code, message } } on failure.
    */
   export function run_simulation(
@@ -0,0 +1,94 @@
+/**
+ * worker.test.ts — binary transport through the engine worker.
+ *
+ * The WASM glue is mocked: load_snapshot_with_options returns the JSON the
+ * Rust side produces when `binaryThreshold` moves a value into `binaryRefs`,
+ * and value_bytes hands back the payload as a Uint8Array.
+ */
+
+import { describe, it, expect, vi, beforeAll } from 'vitest'
+import type { WorkerRequest, WorkerResponse } from './wasm-types.ts'
+
+const wasm = vi.hoisted(() => ({
+  load_snapshot_with_options: vi.fn(),
+  value_f64: vi.fn(),
+  value_bytes: vi.fn(),
+}))
+
+vi.mock('@engine-wasm/engine_wasm.js', () => ({
+  default: vi.fn(async () => undefined),
+  ...wasm,
+  evaluate: vi.fn(),
+  load_snapshot: vi.fn(),
+  apply_patch: vi.fn(),
+  apply_patch_with_options: vi.fn(),
+  set_input: vi.fn(),
+  register_dataset: vi.fn(),
+  release_dataset: vi.fn(),
+  register_blob: vi.fn(),
+  release_blob: vi.fn(),
+  get_catalog: () => '[]',
+  get_constant_values: () => '{}',
+  get_engine_version: () => 'test',
+  get_engine_contract_version: () => 1,
+  dataset_count: () => 0,
+  dataset_total_bytes: () => 0,
+  validate_graph: () => '[]',
+  init_thread_pool: vi.fn(),
+}))
+vi.mock('@engine-wasm/engine_wasm_bg.wasm?url', () => ({ default: 'engine_wasm_bg.wasm' }))
+vi.mock('./gpu/index.ts', () => ({ gpuAccelerator: { init: async () => undefined } }))
+vi.mock('./memory64.ts', () => ({ detectMemory64Support: async () => false }))
+
+const posted: { msg: WorkerResponse; transfer?: Transferable[] }[] = []
+
+beforeAll(async () => {
+  vi.spyOn(self, 'postMessage').mockImplementation(((
+    msg: WorkerResponse,
+    transfer?: Transferable[],
+  ) => {
+    posted.push({ msg, transfer })
+  }) as typeof self.postMessage)
+  await import('./worker.ts')
+})
+
+function send(msg: WorkerRequest) {
+  self.onmessage!({ data: msg } as MessageEvent<WorkerRequest>)
+}
+
+describe('engine worker binary transport', () => {
+  it('returns a large bytes value as a transferred Uint8Array, not JSON', () => {
+    const payload = new Uint8Array(1 << 20).fill(7)
+    wasm.load_snapshot_with_options.mockReturnValue(
+      JSON.stringify({
+        values: { n1: { kind: 'scalar', value: 2 } },
+        diagnostics: [],
+        elapsedUs: 10,
+        binaryRefs: {
+          file: { kind: 'bytes', length: payload.length, mime: 'application/vnd.apache.parquet' },
+        },
+      }),
+    )
+    wasm.value_bytes.mockImplementation((id: string) => (id === 'file' ? payload : undefined))
+
+    send({
+      type: 'loadSnapshot',
+      requestId: 1,
+      snapshot: { version: 1, nodes: [], edges: [] },
+    } as unknown as WorkerRequest)
+
+    // loadSnapshot turns the binary threshold on without the caller asking.
+    const options = JSON.parse(wasm.load_snapshot_with_options.mock.calls[0][1])
+    expect(options.binaryThreshold).toBeGreaterThan(0)
+
+    const last = posted[posted.length - 1]
+    expect(last.msg.type).toBe('result-binary')
+    if (last.msg.type !== 'result-binary') return
+    expect(last.msg.scalars.nonScalars.file).toBeUndefined()
+    const file = last.msg.scalars.arrays?.file
+    expect(file?.kind).toBe('bytes')
+    expect(file?.data).toBeInstanceOf(Uint8Array)
+    expect(file?.data.length).toBe(payload.length)
+    expect(last.transfer).toContain(payload.buffer)
+  })
+})
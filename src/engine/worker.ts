@@ -5,7 +5,8 @@
  *  1. Worker loads → imports WASM init + glue
  *  2. Calls init(wasmUrl) to instantiate the WASM module
  *  3. Posts { type: 'ready' } to main thread
- *  4. Listens for messages: evaluate, loadSnapshot, applyPatch, setInput, registerDataset, releaseDataset, cancel
+ *  4. Listens for messages: evaluate, loadSnapshot, applyPatch, setInput, registerDataset, releaseDataset,
+ *     registerBlob, releaseBlob, cancel
  *  5. Returns typed responses to main thread
  */
 
@@ -20,6 +21,10 @@ import initWasm, {
   set_input,
   register_dataset,
   release_dataset,
+  register_blob,
+  release_blob,
+  value_f64,
+  value_bytes,
   get_catalog,
   get_constant_values,
   get_engine_version,
@@ -34,6 +39,7 @@ import initWasm, {
   init_thread_pool,
 } from '@engine-wasm/engine_wasm.js'
 import wasmUrl from '@engine-wasm/engine_wasm_bg.wasm?url'
+import { binaryToEngineValue, fetchBinaryRefs } from './binaryValues.ts'
 import type {
   WorkerRequest,
   WorkerResponse,
@@ -43,6 +49,7 @@ import type {
   IncrementalEvalResult,
   EvalOptions,
   BinaryResultScalars,
+  BinaryRef,
 } from './wasm-types.ts'
 
 function post(msg: WorkerResponse, transfer?: Transferable[]) {
@@ -94,6 +101,26 @@ function encodeScalars(values: Record<string, EngineValue>): BinaryResultScalars
   }
 }
 
+/**
+ * Copy values listed in `binaryRefs` out of WASM memory.
+ * With `scalars`, the typed arrays ride along as Transferables; otherwise
+ * (trace results) they are folded back into `values` as plain EngineValues.
+ */
+function resolveBinaryRefs(
+  refs: Record<string, BinaryRef> | undefined,
+  values: Record<string, EngineValue>,
+  scalars?: BinaryResultScalars,
+): ArrayBuffer[] {
+  if (!refs) return []
+  const { arrays, transfer } = fetchBinaryRefs(refs, value_f64, value_bytes)
+  if (scalars) {
+    scalars.arrays = arrays
+    return transfer
+  }
+  for (const [nodeId, v] of Object.entries(arrays)) values[nodeId] = binaryToEngineValue(v)
+  return []
+}
+
 /** Parse a JSON result that may be an EvalResult or an error object. */
 function parseFullResult(raw: string, requestId: number): void {
   const parsed: EngineEvalResult | EngineErrorResult = JSON.parse(raw)
@@ -105,6 +132,7 @@ function parseFullResult(raw: string, requestId: number): void {
   // ENG-03: if no trace (trace adds complexity), use binary format for scalars.
   if (!parsed.trace) {
     const scalars = encodeScalars(parsed.values)
+    const transfer = resolveBinaryRefs(parsed.binaryRefs, parsed.values, scalars)
     post(
       {
         type: 'result-binary',
@@ -114,9 +142,11 @@ function parseFullResult(raw: string, requestId: number): void {
         elapsedUs: parsed.elapsedUs,
         partial: parsed.partial,
       },
-      [scalars.scalars.buffer],
+      [scalars.scalars.buffer, ...transfer],
     )
   } else {
+    resolveBinaryRefs(parsed.binaryRefs, parsed.values)
+    delete parsed.binaryRefs
     post({ type: 'result', requestId, result: parsed })
   }
 }
@@ -132,6 +162,7 @@ function parseIncrementalResult(raw: string, requestId: number): void {
   // ENG-03: if no trace, use binary format for changed scalar values.
   if (!parsed.trace) {
     const scalars = encodeScalars(parsed.changedValues)
+    const transfer = resolveBinaryRefs(parsed.binaryRefs, parsed.changedValues, scalars)
     post(
       {
         type: 'incremental-binary',
@@ -143,16 +174,33 @@ function parseIncrementalResult(raw: string, requestId: number): void {
         totalCount: parsed.totalCount,
         partial: parsed.partial,
       },
-      [scalars.scalars.buffer],
+      [scalars.scalars.buffer, ...transfer],
     )
   } else {
+    resolveBinaryRefs(parsed.binaryRefs, parsed.changedValues)
+    delete parsed.binaryRefs
     post({ type: 'incremental', requestId, result: parsed })
   }
 }
 
 /** Check if eval options require the *_with_options WASM path. */
 function needsOptions(opts?: EvalOptions): opts is EvalOptions {
-  return !!opts && (!!opts.trace || !!opts.timeBudgetMs || !!opts.maxTraceNodes)
+  return (
+    !!opts &&
+    (!!opts.trace || !!opts.timeBudgetMs || !!opts.maxTraceNodes || !!opts.binaryThreshold)
+  )
+}
+
+/**
+ * `binaryThreshold` used by loadSnapshot / applyPatch when the caller sets
+ * none: vectors, matrices and tables this large (and every bytes value) come
+ * back as typed arrays instead of JSON.
+ */
+export const DEFAULT_BINARY_THRESHOLD = 4096
+
+/** Fill in the default binary threshold for the persistent-graph paths. */
+function withBinaryThreshold(opts?: EvalOptions): EvalOptions {
+  return { ...opts, binaryThreshold: opts?.binaryThreshold ?? DEFAULT_BINARY_THRESHOLD }
 }
 
 // ── Cooperative cancellation (defence-in-depth) ───────────────────────────
@@ -245,11 +293,12 @@ self.onmessage = (e: MessageEvent<WorkerRequest>) => {
 
     case 'loadSnapshot': {
       try {
-        if (needsOptions(msg.options)) {
+        const opts = withBinaryThreshold(msg.options)
+        if (needsOptions(opts)) {
           const startMs = performance.now()
           const raw = load_snapshot_with_options(
             JSON.stringify(msg.snapshot),
-            JSON.stringify(msg.options),
+            JSON.stringify(opts),
             makeProgressCb(msg.requestId, startMs),
           )
           parseFullResult(raw, msg.requestId)
@@ -266,11 +315,12 @@ self.onmessage = (e: MessageEvent<WorkerRequest>) => {
     case 'applyPatch': {
       const patchSeq = ++latestEvalSeq
       try {
-        if (needsOptions(msg.options)) {
+        const opts = withBinaryThreshold(msg.options)
+        if (needsOptions(opts)) {
           const startMs = performance.now()
           const raw = apply_patch_with_options(
             JSON.stringify(msg.ops),
-            JSON.stringify(msg.options),
+            JSON.stringify(opts),
             makeProgressCb(msg.requestId, startMs),
           )
           // Discard if a cancel arrived before this eval began (seq mismatch).
@@ -316,6 +366,24 @@ self.onmessage = (e: MessageEvent<WorkerRequest>) => {
       break
     }
 
+    case 'registerBlob': {
+      try {
+        register_blob(msg.datasetId, new Uint8Array(msg.buffer))
+      } catch {
+        // Fire-and-forget — no requestId to report back on.
+      }
+      break
+    }
+
+    case 'releaseBlob': {
+      try {
+        release_blob(msg.datasetId)
+      } catch {
+        // Fire-and-forget.
+      }
+      break
+    }
+
     case 'getStats': {
       try {
         post({
@@ -64,6 +64,8 @@ export function isHeavyValue(value: EngineValue): boolean {
   switch (value.kind) {
     case 'matrix':
       return value.rows * value.cols > HYBRID_ELEMENT_THRESHOLD
+    case 'sparseMatrix':
+      return value.values.length > HYBRID_ELEMENT_THRESHOLD
     case 'vector':
       return value.value.length > HYBRID_ELEMENT_THRESHOLD
     case 'table': {
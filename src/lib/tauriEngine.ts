@@ -21,17 +21,28 @@ export function isTauri(): boolean {
 }
 
 // ---------------------------------------------------------------------------
-// Tauri invoke helper (avoids bundling @tauri-apps/api at build time)
+// Tauri IPC helpers (avoids bundling @tauri-apps/api at build time)
 // ---------------------------------------------------------------------------
 
 type TauriInvoke = (cmd: string, args?: Record<string, unknown>) => Promise<unknown>
+type Unlisten = () => void
+
+/** The subset of `window.__TAURI_INTERNALS__` used here. */
+interface TauriInternals {
+  invoke: TauriInvoke
+  transformCallback: (callback: (response: unknown) => void, once?: boolean) => number
+}
+
+function tauriInternals(): TauriInternals {
+  const internals = (window as { __TAURI_INTERNALS__?: TauriInternals }).__TAURI_INTERNALS__
+  if (!internals?.invoke) {
+    throw new Error('[TAURI_ENGINE] invoke not available — not running in Tauri')
+  }
+  return internals
+}
 
 function getInvoke(): TauriInvoke {
-  // eslint-disable-next-line @typescript-eslint/no-explicit-any
-  const w = window as any
-  if (w.__TAURI_INTERNALS__?.invoke) return w.__TAURI_INTERNALS__.invoke as TauriInvoke
-  if (w.__TAURI__?.invoke) return w.__TAURI__.invoke as TauriInvoke
-  throw new Error('[TAURI_ENGINE] invoke not available — not running in Tauri')
+  return tauriInternals().invoke
 }
 
 // ---------------------------------------------------------------------------
@@ -113,6 +124,201 @@ export async function nativeEvalPatch(patchJson: string): Promise<NativeEvalResu
   return JSON.parse(result as string) as NativeEvalResult
 }
 
+/** Override one node input on the session graph and return incremental results. */
+export async function nativeSetInput(
+  nodeId: string,
+  portId: string,
+  value: number,
+): Promise<NativeEvalResult> {
+  if (!isTauri()) {
+    throw new Error('[TAURI_ENGINE] Not running in Tauri desktop app')
+  }
+  const result = await getInvoke()('set_input', {
+    sessionId: getSessionId(),
+    nodeId,
+    portId,
+    value,
+  })
+  return JSON.parse(result as string) as NativeEvalResult
+}
+
+/** Register a large numeric dataset on the session graph. */
+export async function nativeRegisterDataset(datasetId: string, data: ArrayLike<number>): Promise<void> {
+  if (!isTauri()) {
+    throw new Error('[TAURI_ENGINE] Not running in Tauri desktop app')
+  }
+  await getInvoke()('register_dataset', {
+    sessionId: getSessionId(),
+    datasetId,
+    data: Array.from(data),
+  })
+}
+
+/** Release a dataset from the session graph. */
+export async function nativeReleaseDataset(datasetId: string): Promise<void> {
+  if (!isTauri()) return
+  await getInvoke()('release_dataset', { sessionId: getSessionId(), datasetId })
+}
+
+/** Run pre-evaluation validation on the session graph. */
+export async function nativeValidateGraph(): Promise<unknown[]> {
+  if (!isTauri()) {
+    throw new Error('[TAURI_ENGINE] Not running in Tauri desktop app')
+  }
+  const result = await getInvoke()('validate_graph', { sessionId: getSessionId() })
+  return JSON.parse(result as string) as unknown[]
+}
+
+// ---------------------------------------------------------------------------
+// Background evaluation jobs
+// ---------------------------------------------------------------------------
+
+/**
+ * Subscribe to an engine event through the event plugin, as
+ * `@tauri-apps/api/event` does. The backend emits job events to this window only.
+ */
+async function listen<T>(event: string, handler: (payload: T) => void): Promise<Unlisten> {
+  const internals = tauriInternals()
+  const callbackId = internals.transformCallback((e) => handler((e as { payload: T }).payload))
+  const eventId = await internals.invoke('plugin:event|listen', {
+    event,
+    target: { kind: 'Any' },
+    handler: callbackId,
+  })
+  return () => {
+    internals.invoke('plugin:event|unlisten', { event, eventId }).catch(() => {})
+  }
+}
+
+export interface NativeJobProgress {
+  jobId: string
+  sessionId: string
+  evaluated: number
+  total: number
+}
+
+export interface NativeJobComplete {
+  jobId: string
+  sessionId: string
+  /** True if the job was cancelled; the result is then partial. */
+  cancelled: boolean
+  result?: NativeEvalResult
+  error?: string
+}
+
+export interface NativeEvalJobRequest {
+  /** Load this snapshot and evaluate fully. */
+  snapshot?: EngineSnapshotV1
+  /** Or apply this patch (JSON) and evaluate incrementally. */
+  patchJson?: string
+  options?: { trace?: boolean; maxTraceNodes?: number; timeBudgetMs?: number }
+  onProgress?: (progress: NativeJobProgress) => void
+}
+
+export interface NativeEvalJob {
+  jobId: string
+  /** Resolves when the job finishes (including after cancellation). */
+  done: Promise<NativeJobComplete>
+  cancel: () => Promise<boolean>
+}
+
+/**
+ * Start a long evaluation on a native background thread.
+ * The UI stays responsive; progress arrives via `onProgress`.
+ */
+export async function startNativeEvalJob(req: NativeEvalJobRequest): Promise<NativeEvalJob> {
+  if (!isTauri()) {
+    throw new Error('[TAURI_ENGINE] Not running in Tauri desktop app')
+  }
+  let jobId: string | null = null
+  const early: NativeJobComplete[] = []
+  let resolveDone!: (c: NativeJobComplete) => void
+  const done = new Promise<NativeJobComplete>((resolve) => {
+    resolveDone = resolve
+  })
+
+  const unlistenProgress = await listen<NativeJobProgress>('engine://job-progress', (p) => {
+    if (p.jobId === jobId) req.onProgress?.(p)
+  })
+  const unlistenComplete = await listen<NativeJobComplete>('engine://job-complete', (c) => {
+    if (jobId === null) early.push(c)
+    else if (c.jobId === jobId) resolveDone(c)
+  })
+
+  try {
+    jobId = (await getInvoke()('start_eval_job', {
+      sessionId: getSessionId(),
+      snapshotJson: req.snapshot ? JSON.stringify(req.snapshot) : null,
+      patchJson: req.patchJson ?? null,
+      optionsJson: req.options ? JSON.stringify(req.options) : null,
+    })) as string
+  } catch (err) {
+    unlistenProgress()
+    unlistenComplete()
+    throw err
+  }
+  const finished = early.find((c) => c.jobId === jobId)
+  if (finished) resolveDone(finished)
+
+  const id = jobId
+  void done.finally(() => {
+    unlistenProgress()
+    unlistenComplete()
+  })
+  return { jobId: id, done, cancel: () => cancelNativeEvalJob(id) }
+}
+
+/** Request cancellation of a background job. Returns false if it already finished. */
+export async function cancelNativeEvalJob(jobId: string): Promise<boolean> {
+  if (!isTauri()) return false
+  return (await getInvoke()('cancel_eval_job', { jobId })) as boolean
+}
+
+// ---------------------------------------------------------------------------
+// Project files
+// ---------------------------------------------------------------------------
+
+export interface NativeProjectSummary {
+  /** True if cached values came from another engine version and will be recomputed. */
+  stale: boolean
+  cached: number
+  dirty: number
+  engineVersion: string
+  contractVersion: number
+  metadata: Record<string, string>
+}
+
+export interface NativeOpenedProject {
+  summary: NativeProjectSummary
+  snapshot: EngineSnapshotV1
+  /** Cached values from the last evaluation before the project was saved. */
+  values: Record<string, unknown>
+}
+
+/** Save the session graph, its datasets and last results to a project file. */
+export async function nativeSaveProject(
+  path: string,
+  metadata?: Record<string, string>,
+): Promise<void> {
+  if (!isTauri()) {
+    throw new Error('[TAURI_ENGINE] Not running in Tauri desktop app')
+  }
+  await getInvoke()('save_project_file', {
+    sessionId: getSessionId(),
+    path,
+    metadata: metadata ?? null,
+  })
+}
+
+/** Open a project file into the session graph without re-evaluating it. */
+export async function nativeOpenProject(path: string): Promise<NativeOpenedProject> {
+  if (!isTauri()) {
+    throw new Error('[TAURI_ENGINE] Not running in Tauri desktop app')
+  }
+  const result = await getInvoke()('open_project_file', { sessionId: getSessionId(), path })
+  return JSON.parse(result as string) as NativeOpenedProject
+}
+
 // ---------------------------------------------------------------------------
 // CUDA commands
 // ---------------------------------------------------------------------------